@@ -30,6 +30,7 @@ use crate::light_client_finality_update_verification::{
 use crate::light_client_optimistic_update_verification::{
     Error as LightClientOptimisticUpdateError, VerifiedLightClientOptimisticUpdate,
 };
+use crate::light_client_server_cache::LightClientServerCache;
 use crate::migrate::BackgroundMigrator;
 use crate::naive_aggregation_pool::{
     AggregatedAttestationMap, Error as NaiveAggregationError, NaiveAggregationPool,
@@ -102,8 +103,9 @@ use store::iter::{BlockRootsIterator, ParentRootBlockIterator, StateRootsIterato
 use store::{
     DatabaseBlock, Error as DBError, HotColdDB, KeyValueStore, KeyValueStoreOp, StoreItem, StoreOp,
 };
-use task_executor::{ShutdownReason, TaskExecutor};
+use task_executor::{FailureKind, ShutdownReason, TaskExecutor};
 use tokio_stream::Stream;
+use tracing::{info_span, instrument, Instrument, Span};
 use tree_hash::TreeHash;
 use types::beacon_state::CloneConfig;
 use types::*;
@@ -132,6 +134,7 @@ pub const BEACON_CHAIN_DB_KEY: Hash256 = Hash256::zero();
 pub const OP_POOL_DB_KEY: Hash256 = Hash256::zero();
 pub const ETH1_CACHE_DB_KEY: Hash256 = Hash256::zero();
 pub const FORK_CHOICE_DB_KEY: Hash256 = Hash256::zero();
+pub const SHUFFLING_CACHE_DB_KEY: Hash256 = Hash256::zero();
 
 /// Defines how old a block can be before it's no longer a candidate for the early attester cache.
 const EARLY_ATTESTER_CACHE_HISTORIC_SLOTS: u64 = 4;
@@ -367,6 +370,8 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub latest_seen_finality_update: Mutex<Option<LightClientFinalityUpdate<T::EthSpec>>>,
     /// The most recently validated light client optimistic update received on gossip.
     pub latest_seen_optimistic_update: Mutex<Option<LightClientOptimisticUpdate<T::EthSpec>>>,
+    /// Light client updates computed from imported blocks, served by the HTTP API.
+    pub light_client_server_cache: LightClientServerCache<T::EthSpec>,
     /// Provides information from the Ethereum 1 (PoW) chain.
     pub eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec>>,
     /// Interfaces with the execution client.
@@ -415,7 +420,7 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     /// Logging to CLI, etc.
     pub(crate) log: Logger,
     /// Arbitrary bytes included in the blocks.
-    pub(crate) graffiti: Graffiti,
+    pub(crate) graffiti: RwLock<Graffiti>,
     /// Optional slasher.
     pub slasher: Option<Arc<Slasher<T::EthSpec>>>,
     /// Provides monitoring of a set of explicitly defined validators.
@@ -562,6 +567,32 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(())
     }
 
+    /// Persists the committee caches in `self.shuffling_cache` which are likely to be needed
+    /// after a restart to disk, i.e. those for the epochs around the head and the justified and
+    /// finalized epochs.
+    pub fn persist_shuffling_cache(&self) -> Result<(), Error> {
+        let _timer = metrics::start_timer(&metrics::PERSIST_SHUFFLING_CACHE);
+
+        let head = self.canonical_head.cached_head();
+        let head_epoch = head.head_slot().epoch(T::EthSpec::slots_per_epoch());
+        let epochs = [
+            head_epoch.saturating_sub(1u64),
+            head_epoch,
+            head_epoch + 1,
+            head.justified_checkpoint().epoch,
+            head.finalized_checkpoint().epoch,
+        ];
+
+        let persisted = self
+            .shuffling_cache
+            .try_read_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+            .ok_or(Error::AttestationCacheLockTimeout)?
+            .to_persisted(&epochs);
+        self.store.put_item(&SHUFFLING_CACHE_DB_KEY, &persisted)?;
+
+        Ok(())
+    }
+
     /// Returns the slot _right now_ according to `self.slot_clock`. Returns `Err` if the slot is
     /// unavailable.
     ///
@@ -581,6 +612,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .map(|slot| slot.epoch(T::EthSpec::slots_per_epoch()))
     }
 
+    /// Sets the graffiti included in blocks for which the validator did not supply any graffiti.
+    pub fn set_graffiti(&self, graffiti: Graffiti) {
+        *self.graffiti.write() = graffiti;
+    }
+
     /// Iterates across all `(block_root, slot)` pairs from `start_slot`
     /// to the head of the chain (inclusive).
     ///
@@ -788,9 +824,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
 
         // Check limits w.r.t historic state bounds.
+        // The roots of states which aren't stored may still be indexed in the freezer database.
         let (historic_lower_limit, historic_upper_limit) = self.store.get_historic_state_limits();
         if request_slot > historic_lower_limit && request_slot < historic_upper_limit {
-            return Ok(None);
+            return Ok(self.store.load_frozen_state_root(request_slot)?);
         }
 
         // Try an optimized path of reading the root directly from the head state.
@@ -1248,6 +1285,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 Ok(state)
             }
             Ordering::Less => {
+                if let Some((_, state)) = self.store.load_historic_state_by_replay(slot)? {
+                    return Ok(state);
+                }
+
                 let state_root =
                     process_results(self.forwards_iter_state_roots_until(slot, slot)?, |iter| {
                         iter.take_while(|(_, current_slot)| *current_slot >= slot)
@@ -1763,6 +1804,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// Performs the same validation as `Self::verify_unaggregated_attestation_for_gossip`, but for
     /// multiple attestations using batch BLS verification. Batch verification can provide
     /// significant CPU-time savings compared to individual verification.
+    #[instrument(skip_all, fields(attestations = attestations.len()))]
     pub fn batch_verify_unaggregated_attestations_for_gossip<'a, I>(
         &self,
         attestations: I,
@@ -1781,6 +1823,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ///
     /// The attestation must be "unaggregated", that is it must have exactly one
     /// aggregation bit set.
+    #[instrument(skip_all)]
     pub fn verify_unaggregated_attestation_for_gossip<'a>(
         &self,
         unaggregated_attestation: &'a Attestation<T::EthSpec>,
@@ -1794,10 +1837,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             |v| {
                 // This method is called for API and gossip attestations, so this covers all unaggregated attestation events
                 if let Some(event_handler) = self.event_handler.as_ref() {
-                    if event_handler.has_attestation_subscribers() {
-                        event_handler
-                            .register(EventKind::Attestation(Box::new(v.attestation().clone())));
-                    }
+                    event_handler.register_attestation(v.attestation(), v.indexed_attestation());
                 }
                 metrics::inc_counter(&metrics::UNAGGREGATED_ATTESTATION_PROCESSING_SUCCESSES);
                 v
@@ -1808,6 +1848,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// Performs the same validation as `Self::verify_aggregated_attestation_for_gossip`, but for
     /// multiple attestations using batch BLS verification. Batch verification can provide
     /// significant CPU-time savings compared to individual verification.
+    #[instrument(skip_all, fields(aggregates = aggregates.len()))]
     pub fn batch_verify_aggregated_attestations_for_gossip<'a, I>(
         &self,
         aggregates: I,
@@ -1820,6 +1861,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
     /// Accepts some `SignedAggregateAndProof` from the network and attempts to verify it,
     /// returning `Ok(_)` if it is valid to be (re)broadcast on the gossip network.
+    #[instrument(skip_all)]
     pub fn verify_aggregated_attestation_for_gossip<'a>(
         &self,
         signed_aggregate: &'a SignedAggregateAndProof<T::EthSpec>,
@@ -1831,10 +1873,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         VerifiedAggregatedAttestation::verify(signed_aggregate, self).map(|v| {
             // This method is called for API and gossip attestations, so this covers all aggregated attestation events
             if let Some(event_handler) = self.event_handler.as_ref() {
-                if event_handler.has_attestation_subscribers() {
-                    event_handler
-                        .register(EventKind::Attestation(Box::new(v.attestation().clone())));
-                }
+                event_handler.register_attestation(v.attestation(), v.indexed_attestation());
             }
             metrics::inc_counter(&metrics::AGGREGATED_ATTESTATION_PROCESSING_SUCCESSES);
             v
@@ -2504,6 +2543,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ///
     /// This method is generally much more efficient than importing each block using
     /// `Self::process_block`.
+    #[instrument(skip_all, fields(blocks = chain_segment.len()))]
     pub async fn process_chain_segment(
         self: &Arc<Self>,
         chain_segment: Vec<Arc<SignedBeaconBlock<T::EthSpec>>>,
@@ -2533,14 +2573,15 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             // Determine the epoch of the first block in the remaining segment.
             let start_epoch = block.slot().epoch(T::EthSpec::slots_per_epoch());
 
-            // The `last_index` indicates the position of the first block in an epoch greater
-            // than the current epoch: partitioning the blocks into a run of blocks in the same
-            // epoch and everything else. These same-epoch blocks can all be signature-verified with
-            // the same `BeaconState`.
+            // The `last_index` indicates the position of the first block more than
+            // `MAX_SEED_LOOKAHEAD` epochs after the current epoch. The shuffling of the blocks
+            // before it is determined by the parent state and their RANDAO reveals, so their
+            // signatures can all be verified in one batch.
+            let end_epoch = start_epoch + self.spec.max_seed_lookahead;
             let last_index = filtered_chain_segment
                 .iter()
                 .position(|(_root, block)| {
-                    block.slot().epoch(T::EthSpec::slots_per_epoch()) > start_epoch
+                    block.slot().epoch(T::EthSpec::slots_per_epoch()) > end_epoch
                 })
                 .unwrap_or(filtered_chain_segment.len());
 
@@ -2661,6 +2702,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ///
     /// Returns an `Err` if the given block was invalid, or an error was encountered during
     /// verification.
+    #[instrument(
+        skip_all,
+        fields(block_root = ?block_root, slot = %unverified_block.block().slot())
+    )]
     pub async fn process_block<B: IntoExecutionPendingBlock<T>>(
         self: &Arc<Self>,
         block_root: Hash256,
@@ -2680,11 +2725,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // A small closure to group the verification and import errors.
         let chain = self.clone();
         let import_block = async move {
-            let execution_pending = unverified_block.into_execution_pending_block(
-                block_root,
-                &chain,
-                notify_execution_layer,
-            )?;
+            let execution_pending = info_span!("verify_block").in_scope(|| {
+                unverified_block.into_execution_pending_block(
+                    block_root,
+                    &chain,
+                    notify_execution_layer,
+                )
+            })?;
             chain
                 .import_execution_pending_block(execution_pending, count_unrealized)
                 .await
@@ -2761,6 +2808,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             payload_verification_status,
             is_valid_merge_transition_block,
         } = payload_verification_handle
+            .instrument(info_span!("await_payload_verification"))
             .await
             .map_err(BeaconChainError::TokioJoin)?
             .ok_or(BeaconChainError::RuntimeShutdown)??;
@@ -2796,9 +2844,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
 
         let chain = self.clone();
+        // Spans are not inherited by blocking tasks, so the parent is passed explicitly.
+        let parent_span = Span::current();
         let block_hash = self
             .spawn_blocking_handle(
                 move || {
+                    let _span = info_span!(parent: &parent_span, "import_block").entered();
                     chain.import_block(
                         block,
                         block_root,
@@ -3053,6 +3104,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         };
         let current_finalized_checkpoint = state.finalized_checkpoint();
 
+        if self.config.enable_light_client_server {
+            self.import_block_update_light_client_server(&signed_block, block_root, &mut state);
+        }
+
         self.snapshot_cache
             .try_write_for(BLOCK_PROCESSING_CACHE_LOCK_TIMEOUT)
             .ok_or(Error::SnapshotCacheLockTimeout)
@@ -3158,6 +3213,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 );
                 shutdown_sender
                     .try_send(ShutdownReason::Failure(
+                        FailureKind::Database,
                         "Weak subjectivity checkpoint verification failed. \
                              Provided block root is not a checkpoint.",
                     ))
@@ -3331,6 +3387,50 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Compute light client updates from the sync aggregate of the block, and cache the proofs
+    /// from its state which are needed to serve updates attesting to it.
+    fn import_block_update_light_client_server(
+        &self,
+        block: &SignedBeaconBlock<T::EthSpec>,
+        block_root: Hash256,
+        state: &mut BeaconState<T::EthSpec>,
+    ) {
+        let finalized_header = match self
+            .light_client_server_cache
+            .finalized_root_to_load(block, state)
+            .map(|finalized_root| self.get_blinded_block(&finalized_root))
+            .transpose()
+        {
+            Ok(finalized_block) => finalized_block
+                .flatten()
+                .map(|finalized_block| finalized_block.message().block_header()),
+            Err(e) => {
+                warn!(
+                    self.log,
+                    "Unable to load finalized block";
+                    "purpose" => "light client server",
+                    "error" => ?e,
+                );
+                None
+            }
+        };
+
+        if let Err(e) = self.light_client_server_cache.process_block(
+            block_root,
+            block,
+            state,
+            finalized_header,
+            &self.spec,
+        ) {
+            warn!(
+                self.log,
+                "Unable to compute light client updates";
+                "block_root" => ?block_root,
+                "error" => ?e,
+            );
+        }
+    }
+
     /// If a slasher is configured, provide the attestations from the block.
     fn import_block_update_slasher(
         &self,
@@ -4368,7 +4468,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // Override the beacon node's graffiti with graffiti from the validator, if present.
         let graffiti = match validator_graffiti {
             Some(graffiti) => graffiti,
-            None => self.graffiti,
+            None => *self.graffiti.read(),
         };
 
         let attestation_packing_timer =
@@ -4752,6 +4852,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
             let mut shutdown_sender = self.shutdown_sender();
             if let Err(e) = shutdown_sender.try_send(ShutdownReason::Failure(
+                FailureKind::Other,
                 INVALID_JUSTIFIED_PAYLOAD_SHUTDOWN_REASON,
             )) {
                 crit!(
@@ -5402,6 +5503,24 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             // Run fork choice and signal to any waiting task that it has completed.
             self.recompute_head_at_current_slot().await;
 
+            // Persist the op pool once per epoch, so that its operations survive an unclean
+            // shutdown. This is done mid-epoch to stay clear of the epoch transition.
+            if slot % T::EthSpec::slots_per_epoch() == T::EthSpec::slots_per_epoch() / 2 {
+                let chain = self.clone();
+                self.task_executor.clone().spawn_blocking(
+                    move || {
+                        if let Err(e) = chain.persist_op_pool() {
+                            error!(
+                                chain.log,
+                                "Failed to persist op pool";
+                                "error" => ?e,
+                            );
+                        }
+                    },
+                    "per_slot_task_persist_op_pool",
+                );
+            }
+
             // Send the notification regardless of fork choice success, this is a "best effort"
             // notification and we don't want block production to hit the timeout in case of error.
             // Use a blocking task to avoid blocking the core executor whilst waiting for locks
@@ -5912,7 +6031,8 @@ impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
         let drop = || -> Result<(), Error> {
             self.persist_head_and_fork_choice()?;
             self.persist_op_pool()?;
-            self.persist_eth1_cache()
+            self.persist_eth1_cache()?;
+            self.persist_shuffling_cache()
         };
 
         if let Err(e) = drop() {
@@ -74,7 +74,9 @@ use slot_clock::SlotClock;
 use ssz::Encode;
 use state_processing::per_block_processing::{errors::IntoWithIndex, is_merge_transition_block};
 use state_processing::{
-    block_signature_verifier::{BlockSignatureVerifier, Error as BlockSignatureVerifierError},
+    block_signature_verifier::{
+        BlockSignatureVerifier, Error as BlockSignatureVerifierError, ParallelSignatureSets,
+    },
     per_block_processing, per_slot_processing,
     state_advance::partial_state_advance,
     BlockProcessingError, BlockSignatureStrategy, ConsensusContext, SlotProcessingError,
@@ -524,10 +526,14 @@ fn process_block_slash_info<T: BeaconChainTypes>(
 /// signature in the block is invalid, an `Err` is returned (it is not possible to known _which_
 /// signature was invalid).
 ///
+/// The signatures of every block are verified in a single batch, which is split across the rayon
+/// thread pool.
+///
 /// ## Errors
 ///
-/// The given `chain_segment` must contain only blocks from the same epoch, otherwise an error
-/// will be returned.
+/// The given `chain_segment` must not span more than `MAX_SEED_LOOKAHEAD` epochs after the epoch
+/// of its first block, otherwise the later blocks may be verified with the wrong shuffling and
+/// rejected.
 pub fn signature_verify_chain_segment<T: BeaconChainTypes>(
     mut chain_segment: Vec<(Hash256, Arc<SignedBeaconBlock<T::EthSpec>>)>,
     chain: &BeaconChain<T>,
@@ -538,34 +544,40 @@ pub fn signature_verify_chain_segment<T: BeaconChainTypes>(
 
     let (first_root, first_block) = chain_segment.remove(0);
     let (mut parent, first_block) = load_parent(first_root, first_block, chain)?;
-    let slot = first_block.slot();
+    let first_epoch = first_block.slot().epoch(T::EthSpec::slots_per_epoch());
     chain_segment.insert(0, (first_root, first_block));
 
-    let highest_slot = chain_segment
-        .last()
-        .map(|(_, block)| block.slot())
-        .unwrap_or_else(|| slot);
-
-    let state = cheap_state_advance_to_obtain_committees(
-        &mut parent.pre_state,
-        parent.beacon_state_root,
-        highest_slot,
-        &chain.spec,
-    )?;
+    let states = advance_state_through_chain_segment(&mut parent, &chain_segment, &chain.spec)?;
 
     let pubkey_cache = get_validator_pubkey_cache(chain)?;
-    let mut signature_verifier = get_signature_verifier(&state, &pubkey_cache, &chain.spec);
+    let mut signature_sets = ParallelSignatureSets::default();
 
     let mut signature_verified_blocks = Vec::with_capacity(chain_segment.len());
 
     for (block_root, block) in &chain_segment {
+        let block_epoch = block.slot().epoch(T::EthSpec::slots_per_epoch());
+        let state = states
+            .iter()
+            .find(|state| state.current_epoch() == block_epoch)
+            .ok_or(BeaconChainError::MissingChainSegmentState(block_epoch))?;
+        let mut signature_verifier = get_signature_verifier(state, &pubkey_cache, &chain.spec);
+
         let mut consensus_context =
             ConsensusContext::new(block.slot()).set_current_block_root(*block_root);
 
-        signature_verifier.include_all_signatures(block, &mut consensus_context)?;
+        // Only the first epoch's proposers are known before the earlier blocks are applied, so
+        // the proposers of later blocks are checked when they're imported.
+        if block_epoch == first_epoch {
+            signature_verifier.include_all_signatures(block, &mut consensus_context)?;
+        } else {
+            signature_verifier
+                .include_all_signatures_with_block_proposer(block, &mut consensus_context)?;
+        }
+        signature_sets.append(signature_verifier.into_signature_sets());
 
-        // Save the block and its consensus context. The context will have had its proposer index
-        // and attesting indices filled in, which can be used to accelerate later block processing.
+        // Save the block and its consensus context. The context will have had its attesting
+        // indices (and proposer index, in the first epoch) filled in, which can be used to
+        // accelerate later block processing.
         signature_verified_blocks.push(SignatureVerifiedBlock {
             block: block.clone(),
             block_root: *block_root,
@@ -574,11 +586,12 @@ pub fn signature_verify_chain_segment<T: BeaconChainTypes>(
         });
     }
 
-    if signature_verifier.verify().is_err() {
+    if !signature_sets.verify() {
         return Err(BlockError::InvalidSignature);
     }
 
     drop(pubkey_cache);
+    drop(states);
 
     if let Some(signature_verified_block) = signature_verified_blocks.first_mut() {
         signature_verified_block.parent = Some(parent);
@@ -1735,6 +1748,7 @@ fn load_parent<T: BeaconChainTypes>(
                 spec,
             )
         }) {
+        metrics::inc_counter(&metrics::BLOCK_PROCESSING_SNAPSHOT_CACHE_HITS);
         if cloned {
             metrics::inc_counter(&metrics::BLOCK_PROCESSING_SNAPSHOT_CACHE_CLONES);
             debug!(
@@ -1849,6 +1863,79 @@ fn cheap_state_advance_to_obtain_committees<'a, E: EthSpec>(
     }
 }
 
+/// Returns a state with committee caches for each epoch of the `chain_segment`, which descends
+/// from `parent`, so that the signatures of all its blocks can be verified in one batch.
+///
+/// The states are advanced from the parent state without applying the blocks, except for their
+/// RANDAO reveals. The attester shuffling only depends on RANDAO mixes and on validator
+/// activations and exits, which take effect at least `MAX_SEED_LOOKAHEAD` epochs later, so it is
+/// correct for the first `MAX_SEED_LOOKAHEAD` epochs after the first block. The proposers are only
+/// correct for the first epoch, as they depend on the effective balances.
+fn advance_state_through_chain_segment<'a, E: EthSpec>(
+    parent: &'a mut PreProcessingSnapshot<E>,
+    chain_segment: &[(Hash256, Arc<SignedBeaconBlock<E>>)],
+    spec: &ChainSpec,
+) -> Result<Vec<Cow<'a, BeaconState<E>>>, BlockError<E>> {
+    let (first_slot, last_slot) = match (chain_segment.first(), chain_segment.last()) {
+        (Some((_, first)), Some((_, last))) => (first.slot(), last.slot()),
+        _ => return Ok(vec![]),
+    };
+    let parent_state_root = parent.beacon_state_root;
+    let first_state = cheap_state_advance_to_obtain_committees(
+        &mut parent.pre_state,
+        parent_state_root,
+        first_slot,
+        spec,
+    )?;
+
+    let last_epoch = last_slot.epoch(E::slots_per_epoch());
+    if first_state.current_epoch() == last_epoch {
+        return Ok(vec![first_state]);
+    }
+
+    let mut state = first_state.clone_with(CloneConfig::committee_caches_only());
+    let mut states = vec![first_state];
+    for (_, block) in chain_segment {
+        let block_epoch = block.slot().epoch(E::slots_per_epoch());
+        if block_epoch > state.current_epoch() {
+            advance_state_without_blocks(
+                &mut state,
+                parent_state_root,
+                block_epoch.start_slot(E::slots_per_epoch()),
+                spec,
+            )?;
+            state.build_committee_cache(RelativeEpoch::Previous, spec)?;
+            state.build_committee_cache(RelativeEpoch::Current, spec)?;
+            states.push(Cow::Owned(
+                state.clone_with(CloneConfig::committee_caches_only()),
+            ));
+        }
+
+        // Apply the RANDAO reveal of the block, which determines the shuffling two epochs later.
+        advance_state_without_blocks(&mut state, parent_state_root, block.slot(), spec)?;
+        state.update_randao_mix(
+            state.current_epoch(),
+            block.message().body().randao_reveal(),
+        )?;
+    }
+
+    Ok(states)
+}
+
+/// Advance `state` to `target_slot` without computing state roots, providing the root of the
+/// parent state if `state` is still the parent state.
+fn advance_state_without_blocks<E: EthSpec>(
+    state: &mut BeaconState<E>,
+    parent_state_root: Option<Hash256>,
+    target_slot: Slot,
+    spec: &ChainSpec,
+) -> Result<(), BlockError<E>> {
+    let state_root_opt =
+        parent_state_root.filter(|_| state.slot() == state.latest_block_header().slot);
+    partial_state_advance(state, state_root_opt, target_slot, spec)
+        .map_err(|e| BlockError::BeaconChainError(BeaconChainError::from(e)))
+}
+
 /// Obtains a read-locked `ValidatorPubkeyCache` from the `chain`.
 pub fn get_validator_pubkey_cache<T: BeaconChainTypes>(
     chain: &BeaconChain<T>,
@@ -1,4 +1,6 @@
-use crate::beacon_chain::{CanonicalHead, BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, OP_POOL_DB_KEY};
+use crate::beacon_chain::{
+    CanonicalHead, BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, OP_POOL_DB_KEY, SHUFFLING_CACHE_DB_KEY,
+};
 use crate::eth1_chain::{CachingEth1Backend, SszEth1};
 use crate::eth1_finalization_cache::Eth1FinalizationCache;
 use crate::fork_choice_signal::ForkChoiceSignalTx;
@@ -6,15 +8,15 @@ use crate::fork_revert::{reset_fork_choice_to_finalization, revert_to_fork_bound
 use crate::head_tracker::HeadTracker;
 use crate::migrate::{BackgroundMigrator, MigratorConfig};
 use crate::persisted_beacon_chain::PersistedBeaconChain;
-use crate::shuffling_cache::ShufflingCache;
-use crate::snapshot_cache::{SnapshotCache, DEFAULT_SNAPSHOT_CACHE_SIZE};
+use crate::shuffling_cache::{PersistedShufflingCache, ShufflingCache};
+use crate::snapshot_cache::SnapshotCache;
 use crate::timeout_rw_lock::TimeoutRwLock;
 use crate::validator_monitor::ValidatorMonitor;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::ChainConfig;
 use crate::{
-    BeaconChain, BeaconChainTypes, BeaconForkChoiceStore, BeaconSnapshot, Eth1Chain,
-    Eth1ChainBackend, ServerSentEventHandler,
+    BeaconChain, BeaconChainError, BeaconChainTypes, BeaconForkChoiceStore, BeaconSnapshot,
+    Eth1Chain, Eth1ChainBackend, ServerSentEventHandler,
 };
 use eth1::Config as Eth1Config;
 use execution_layer::ExecutionLayer;
@@ -24,8 +26,9 @@ use operation_pool::{OperationPool, PersistedOperationPool};
 use parking_lot::RwLock;
 use proto_array::{DisallowedReOrgOffsets, ReOrgThreshold};
 use slasher::Slasher;
-use slog::{crit, error, info, Logger};
+use slog::{crit, error, info, warn, Logger};
 use slot_clock::{SlotClock, TestingSlotClock};
+use state_processing::common::compute_weak_subjectivity_period;
 use std::marker::PhantomData;
 use std::sync::Arc;
 use std::time::Duration;
@@ -243,6 +246,72 @@ where
             .is_some())
     }
 
+    /// Returns `true` if the head of the chain in the builder's `Store` can be loaded, which
+    /// requires the persisted beacon chain and fork choice, and the head block and state.
+    ///
+    /// Only the hot database is read. An error is returned if the database can't be read, rather
+    /// than if an item is missing or can't be decoded.
+    pub fn head_in_store_is_loadable(&self) -> Result<bool, String> {
+        let log = self
+            .log
+            .as_ref()
+            .ok_or("head_in_store_is_loadable requires a log")?;
+        let store = self
+            .store
+            .clone()
+            .ok_or("head_in_store_is_loadable requires a store.")?;
+
+        let unloadable = |item: &str, error: Option<StoreError>| match error {
+            Some(StoreError::DBError { message }) => {
+                Err(format!("DB error when reading {}: {}", item, message))
+            }
+            error => {
+                warn!(log, "Unable to load head from store"; "item" => item, "error" => ?error);
+                Ok(false)
+            }
+        };
+
+        let chain = match store.get_item::<PersistedBeaconChain>(&BEACON_CHAIN_DB_KEY) {
+            Ok(Some(chain)) => chain,
+            Ok(None) => return unloadable("persisted beacon chain", None),
+            Err(e) => return unloadable("persisted beacon chain", Some(e)),
+        };
+        if let Err(e) = HeadTracker::from_ssz_container(&chain.ssz_head_tracker) {
+            warn!(log, "Unable to decode head tracker"; "error" => ?e);
+            return Ok(false);
+        }
+
+        let fork_choice =
+            match BeaconChain::<Witness<TSlotClock, TEth1Backend, _, _, _>>::load_fork_choice(
+                store.clone(),
+                ResetPayloadStatuses::OnlyWithInvalidPayload,
+                &self.spec,
+                log,
+            ) {
+                Ok(Some(fork_choice)) => fork_choice,
+                Ok(None) => return unloadable("fork choice", None),
+                Err(BeaconChainError::DBError(e)) => return unloadable("fork choice", Some(e)),
+                Err(e) => {
+                    warn!(log, "Unable to load fork choice"; "error" => ?e);
+                    return Ok(false);
+                }
+            };
+
+        let head_block_root = fork_choice.cached_fork_choice_view().head_block_root;
+        let head_block = match store.get_blinded_block(&head_block_root) {
+            Ok(Some(block)) => block,
+            Ok(None) => return unloadable("head block", None),
+            // A head block from a missed hard fork is reverted by `build`.
+            Err(StoreError::SszDecodeError(_)) => return Ok(true),
+            Err(e) => return unloadable("head block", Some(e)),
+        };
+        match store.get_state(&head_block.state_root(), Some(head_block.slot())) {
+            Ok(Some(_)) => Ok(true),
+            Ok(None) => unloadable("head state", None),
+            Err(e) => unloadable("head state", Some(e)),
+        }
+    }
+
     /// Attempt to load an existing chain from the builder's `Store`.
     ///
     /// May initialize several components; including the op_pool and finalized checkpoints.
@@ -618,6 +687,7 @@ where
         let mut fork_choice = self
             .fork_choice
             .ok_or("Cannot build without fork choice.")?;
+        fork_choice.set_prune_threshold(self.chain_config.proto_array_prune_threshold);
         let genesis_block_root = self
             .genesis_block_root
             .ok_or("Cannot build without a genesis block root")?;
@@ -771,6 +841,23 @@ where
         let head_for_snapshot_cache = head_snapshot.clone();
         let canonical_head = CanonicalHead::new(fork_choice, Arc::new(head_snapshot));
         let shuffling_cache_size = self.chain_config.shuffling_cache_size;
+        // The shuffling cache is only an optimisation, so start with an empty cache if the
+        // persisted one can't be read.
+        let shuffling_cache = match store
+            .get_item::<PersistedShufflingCache>(&SHUFFLING_CACHE_DB_KEY)
+        {
+            Ok(Some(persisted)) => ShufflingCache::from_persisted(persisted, shuffling_cache_size),
+            Ok(None) => ShufflingCache::new(shuffling_cache_size),
+            Err(e) => {
+                warn!(
+                    log,
+                    "Unable to load shuffling cache";
+                    "error" => ?e
+                );
+                ShufflingCache::new(shuffling_cache_size)
+            }
+        };
+        let snapshot_cache_size = self.chain_config.snapshot_cache_size;
 
         let beacon_chain = BeaconChain {
             spec: self.spec,
@@ -809,6 +896,7 @@ where
             observed_bls_to_execution_changes: <_>::default(),
             latest_seen_finality_update: <_>::default(),
             latest_seen_optimistic_update: <_>::default(),
+            light_client_server_cache: <_>::default(),
             eth1_chain: self.eth1_chain,
             execution_layer: self.execution_layer,
             genesis_validators_root,
@@ -821,10 +909,10 @@ where
             event_handler: self.event_handler,
             head_tracker,
             snapshot_cache: TimeoutRwLock::new(SnapshotCache::new(
-                DEFAULT_SNAPSHOT_CACHE_SIZE,
+                snapshot_cache_size,
                 head_for_snapshot_cache,
             )),
-            shuffling_cache: TimeoutRwLock::new(ShufflingCache::new(shuffling_cache_size)),
+            shuffling_cache: TimeoutRwLock::new(shuffling_cache),
             eth1_finalization_cache: TimeoutRwLock::new(Eth1FinalizationCache::new(log.clone())),
             beacon_proposer_cache: <_>::default(),
             block_times_cache: <_>::default(),
@@ -836,7 +924,7 @@ where
                 .shutdown_sender
                 .ok_or("Cannot build without a shutdown sender.")?,
             log: log.clone(),
-            graffiti: self.graffiti,
+            graffiti: RwLock::new(self.graffiti),
             slasher: self.slasher.clone(),
             validator_monitor: RwLock::new(validator_monitor),
         };
@@ -853,6 +941,15 @@ where
             )
             .map_err(|e| format!("Failed to prime attester cache: {:?}", e))?;
 
+        // Drop any operations in the persisted op pool which have become stale or invalid while
+        // the node was offline.
+        beacon_chain.op_pool.prune_all(
+            &head.beacon_block,
+            &head.beacon_state,
+            current_slot.epoch(TEthSpec::slots_per_epoch()),
+            &beacon_chain.spec,
+        );
+
         // Only perform the check if it was configured.
         if let Some(wss_checkpoint) = beacon_chain.config.weak_subjectivity_checkpoint {
             if let Err(e) = beacon_chain.verify_weak_subjectivity_checkpoint(
@@ -874,6 +971,21 @@ where
             }
         }
 
+        // Blocks within the weak subjectivity period are kept, so that peers which sync from any
+        // safe checkpoint can backfill from this node.
+        if let Some(epochs) = beacon_chain.store.get_config().prune_blocks_older_than {
+            let ws_period =
+                compute_weak_subjectivity_period(&head.beacon_state, &beacon_chain.spec)
+                    .map_err(|e| format!("Unable to compute weak subjectivity period: {:?}", e))?;
+            if epochs < ws_period {
+                return Err(format!(
+                    "Cannot prune blocks older than {} epochs, as the weak subjectivity period is \
+                     {} epochs",
+                    epochs, ws_period
+                ));
+            }
+        }
+
         info!(
             log,
             "Beacon chain initialized";
@@ -54,7 +54,7 @@ use slot_clock::SlotClock;
 use std::sync::Arc;
 use std::time::Duration;
 use store::{iter::StateRootsIterator, KeyValueStoreOp, StoreItem};
-use task_executor::{JoinHandle, ShutdownReason};
+use task_executor::{FailureKind, JoinHandle, ShutdownReason};
 use types::*;
 
 /// Simple wrapper around `RwLock` that uses private visibility to prevent any other modules from
@@ -862,10 +862,30 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         );
 
         if is_epoch_transition || reorg_distance.is_some() {
-            self.persist_head_and_fork_choice()?;
             self.op_pool.prune_attestations(self.epoch()?);
         }
 
+        let is_persist_epoch = is_epoch_transition
+            && new_snapshot.beacon_state.current_epoch().as_u64()
+                % self.config.fork_choice_persist_interval
+                == 0;
+        if is_persist_epoch || reorg_distance.is_some() {
+            // Write to disk in the background, so that block import doesn't wait for it.
+            let chain = self.clone();
+            self.task_executor.spawn_blocking(
+                move || {
+                    if let Err(e) = chain.persist_head_and_fork_choice() {
+                        error!(
+                            chain.log,
+                            "Failed to persist fork choice";
+                            "error" => ?e,
+                        );
+                    }
+                },
+                "persist_head_and_fork_choice",
+            );
+        }
+
         // Register server-sent-events for a new head.
         if let Some(event_handler) = self
             .event_handler
@@ -1060,6 +1080,7 @@ fn check_finalized_payload_validity<T: BeaconChainTypes>(
         let mut shutdown_sender = chain.shutdown_sender();
         shutdown_sender
             .try_send(ShutdownReason::Failure(
+                FailureKind::Database,
                 "Finalized block has an invalid execution payload.",
             ))
             .map_err(Error::InvalidFinalizedPayloadShutdownError)?;
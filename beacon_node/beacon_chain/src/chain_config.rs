@@ -73,12 +73,22 @@ pub struct ChainConfig {
     pub optimistic_finalized_sync: bool,
     /// The size of the shuffling cache,
     pub shuffling_cache_size: usize,
+    /// The number of states kept in the snapshot cache for block processing.
+    pub snapshot_cache_size: usize,
     /// Whether to send payload attributes every slot, regardless of connected proposers.
     ///
     /// This is useful for block builders and testing.
     pub always_prepare_payload: bool,
     /// Whether backfill sync processing should be rate-limited.
     pub enable_backfill_rate_limiting: bool,
+    /// The number of finalized nodes which must accumulate in fork choice before they are pruned.
+    pub proto_array_prune_threshold: usize,
+    /// The number of epochs between writes of fork choice to disk.
+    ///
+    /// Fork choice is always written on re-orgs and at shutdown.
+    pub fork_choice_persist_interval: u64,
+    /// Whether to compute and cache light client updates as blocks are imported.
+    pub enable_light_client_server: bool,
 }
 
 impl Default for ChainConfig {
@@ -106,8 +116,12 @@ impl Default for ChainConfig {
             // This value isn't actually read except in tests.
             optimistic_finalized_sync: true,
             shuffling_cache_size: crate::shuffling_cache::DEFAULT_CACHE_SIZE,
+            snapshot_cache_size: crate::snapshot_cache::DEFAULT_SNAPSHOT_CACHE_SIZE,
             always_prepare_payload: false,
             enable_backfill_rate_limiting: true,
+            proto_array_prune_threshold: proto_array::DEFAULT_PRUNE_THRESHOLD,
+            fork_choice_persist_interval: 1,
+            enable_light_client_server: false,
         }
     }
 }
@@ -59,6 +59,7 @@ pub enum BeaconChainError {
     ForkChoiceStoreError(ForkChoiceStoreError),
     MissingBeaconBlock(Hash256),
     MissingBeaconState(Hash256),
+    MissingChainSegmentState(Epoch),
     SlotProcessingError(SlotProcessingError),
     StateAdvanceError(StateAdvanceError),
     UnableToAdvanceState(String),
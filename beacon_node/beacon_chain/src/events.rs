@@ -1,13 +1,25 @@
 pub use eth2::types::{EventKind, SseBlock, SseFinalizedCheckpoint, SseHead};
 use slog::{trace, Logger};
+use std::sync::Arc;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::{error::SendError, Receiver, Sender};
-use types::EthSpec;
+use types::{Attestation, EthSpec, IndexedAttestation};
 
 const DEFAULT_CHANNEL_CAPACITY: usize = 16;
 
+/// An attestation event, with the indices of the validators which signed the attestation.
+///
+/// The indices are taken from the verified attestation, and shared by every subscriber which
+/// filters attestations by validator.
+#[derive(Clone)]
+pub struct AttestationWithAttesters<T: EthSpec> {
+    pub event: EventKind<T>,
+    pub attesting_indices: Arc<Vec<u64>>,
+}
+
 pub struct ServerSentEventHandler<T: EthSpec> {
     attestation_tx: Sender<EventKind<T>>,
+    attestation_with_attesters_tx: Sender<AttestationWithAttesters<T>>,
     block_tx: Sender<EventKind<T>>,
     finalized_tx: Sender<EventKind<T>>,
     head_tx: Sender<EventKind<T>>,
@@ -27,6 +39,7 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
 
     pub fn new_with_capacity(log: Logger, capacity: usize) -> Self {
         let (attestation_tx, _) = broadcast::channel(capacity);
+        let (attestation_with_attesters_tx, _) = broadcast::channel(capacity);
         let (block_tx, _) = broadcast::channel(capacity);
         let (finalized_tx, _) = broadcast::channel(capacity);
         let (head_tx, _) = broadcast::channel(capacity);
@@ -39,6 +52,7 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
 
         Self {
             attestation_tx,
+            attestation_with_attesters_tx,
             block_tx,
             finalized_tx,
             head_tx,
@@ -108,10 +122,40 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
         }
     }
 
+    /// Registers the event for a verified attestation, for both the subscribers to attestations and
+    /// those to attestations with their attesters.
+    pub fn register_attestation(
+        &self,
+        attestation: &Attestation<T>,
+        indexed_attestation: &IndexedAttestation<T>,
+    ) {
+        if self.has_attestation_subscribers() {
+            self.register(EventKind::Attestation(Box::new(attestation.clone())));
+        }
+        if self.attestation_with_attesters_tx.receiver_count() > 0 {
+            let event = AttestationWithAttesters {
+                event: EventKind::Attestation(Box::new(attestation.clone())),
+                attesting_indices: Arc::new(indexed_attestation.attesting_indices.to_vec()),
+            };
+            if let Ok(count) = self.attestation_with_attesters_tx.send(event) {
+                trace!(
+                    self.log,
+                    "Registering server-sent event";
+                    "kind" => "attestation with attesters",
+                    "receiver_count" => count
+                );
+            }
+        }
+    }
+
     pub fn subscribe_attestation(&self) -> Receiver<EventKind<T>> {
         self.attestation_tx.subscribe()
     }
 
+    pub fn subscribe_attestation_with_attesters(&self) -> Receiver<AttestationWithAttesters<T>> {
+        self.attestation_with_attesters_tx.subscribe()
+    }
+
     pub fn subscribe_block(&self) -> Receiver<EventKind<T>> {
         self.block_tx.subscribe()
     }
@@ -152,6 +196,10 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
         self.attestation_tx.receiver_count() > 0
     }
 
+    pub fn has_attestation_with_attesters_subscribers(&self) -> bool {
+        self.attestation_with_attesters_tx.receiver_count() > 0
+    }
+
     pub fn has_block_subscribers(&self) -> bool {
         self.block_tx.receiver_count() > 0
     }
@@ -26,6 +26,7 @@ mod head_tracker;
 pub mod historical_blocks;
 pub mod light_client_finality_update_verification;
 pub mod light_client_optimistic_update_verification;
+pub mod light_client_server_cache;
 pub mod merge_readiness;
 pub mod metrics;
 pub mod migrate;
@@ -0,0 +1,292 @@
+//! Caches the light client updates served by the HTTP API, which are computed from the blocks
+//! imported by the beacon chain.
+//!
+//! An update is built from a block's sync aggregate, which signs the block's parent (the
+//! "attested" block). The proofs required from the post-state of each imported block are cached
+//! until its children are imported, so that states never need to be loaded from the database.
+
+use lru::LruCache;
+use parking_lot::{Mutex, RwLock};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use types::{
+    light_client_update::{
+        Error, FinalizedRootProofLen, NextSyncCommitteeProofLen, FINALIZED_ROOT_INDEX,
+        NEXT_SYNC_COMMITTEE_INDEX,
+    },
+    BeaconBlockHeader, BeaconState, ChainSpec, EthSpec, FixedVector, Hash256,
+    LightClientFinalityUpdate, LightClientOptimisticUpdate, LightClientUpdate, SignedBeaconBlock,
+    Slot, SyncCommittee,
+};
+
+/// The number of recently imported blocks for which the data required to attest them is cached.
+const ATTESTED_DATA_CACHE_SIZE: usize = 32;
+
+/// The maximum number of updates which may be requested at once, as per the light client spec.
+pub const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u64 = 128;
+
+/// The data from the post-state of a block which is required to build an update attesting to it.
+struct AttestedData<E: EthSpec> {
+    header: BeaconBlockHeader,
+    finalized_root: Hash256,
+    /// The header of the block at `finalized_root`, if it is known.
+    finalized_header: Option<BeaconBlockHeader>,
+    finality_branch: FixedVector<Hash256, FinalizedRootProofLen>,
+    next_sync_committee: Arc<SyncCommittee<E>>,
+    next_sync_committee_branch: FixedVector<Hash256, NextSyncCommitteeProofLen>,
+}
+
+pub struct LightClientServerCache<E: EthSpec> {
+    /// Data from the post-states of recently imported blocks, by block root.
+    attested_data: Mutex<LruCache<Hash256, AttestedData<E>>>,
+    latest_finality_update: RwLock<Option<LightClientFinalityUpdate<E>>>,
+    latest_optimistic_update: RwLock<Option<LightClientOptimisticUpdate<E>>>,
+    /// The best update for each sync committee period, by period.
+    ///
+    /// Updates are only kept in memory, so updates for periods before the node was started are
+    /// unavailable.
+    best_updates: RwLock<BTreeMap<u64, LightClientUpdate<E>>>,
+}
+
+impl<E: EthSpec> Default for LightClientServerCache<E> {
+    fn default() -> Self {
+        Self {
+            attested_data: Mutex::new(LruCache::new(ATTESTED_DATA_CACHE_SIZE)),
+            latest_finality_update: <_>::default(),
+            latest_optimistic_update: <_>::default(),
+            best_updates: <_>::default(),
+        }
+    }
+}
+
+impl<E: EthSpec> LightClientServerCache<E> {
+    /// Returns the root of the finalized checkpoint of `state` if a header for it is needed, i.e.
+    /// if it is non-zero and isn't already known from the cached data of the block's parent.
+    pub fn finalized_root_to_load(
+        &self,
+        block: &SignedBeaconBlock<E>,
+        state: &BeaconState<E>,
+    ) -> Option<Hash256> {
+        let finalized_root = state.finalized_checkpoint().root;
+        if finalized_root.is_zero() {
+            return None;
+        }
+        let known = self
+            .attested_data
+            .lock()
+            .peek(&block.parent_root())
+            .map_or(false, |parent| {
+                parent.finalized_root == finalized_root && parent.finalized_header.is_some()
+            });
+        (!known).then_some(finalized_root)
+    }
+
+    /// Update the cached light client updates using the sync aggregate in the imported `block`,
+    /// then cache the data from its post-`state` which is needed to attest to it.
+    ///
+    /// Blocks from before the Altair fork are ignored. The `finalized_header` should be supplied if
+    /// `Self::finalized_root_to_load` returned a root.
+    pub fn process_block(
+        &self,
+        block_root: Hash256,
+        block: &SignedBeaconBlock<E>,
+        state: &mut BeaconState<E>,
+        finalized_header: Option<BeaconBlockHeader>,
+        spec: &ChainSpec,
+    ) -> Result<(), Error> {
+        if spec
+            .altair_fork_epoch
+            .map_or(true, |fork_epoch| state.current_epoch() < fork_epoch)
+        {
+            return Ok(());
+        }
+
+        let mut attested_data = self.attested_data.lock();
+
+        if let Some(parent) = attested_data.peek(&block.parent_root()) {
+            self.update_from_sync_aggregate(parent, block, spec)?;
+        }
+
+        let finalized_root = state.finalized_checkpoint().root;
+        let finalized_header = finalized_header.or_else(|| {
+            attested_data
+                .peek(&block.parent_root())
+                .filter(|parent| parent.finalized_root == finalized_root)
+                .and_then(|parent| parent.finalized_header.clone())
+        });
+        let data = AttestedData {
+            header: block.message().block_header(),
+            finalized_root,
+            finalized_header,
+            finality_branch: FixedVector::new(state.compute_merkle_proof(FINALIZED_ROOT_INDEX)?)?,
+            next_sync_committee: state.next_sync_committee()?.clone(),
+            next_sync_committee_branch: FixedVector::new(
+                state.compute_merkle_proof(NEXT_SYNC_COMMITTEE_INDEX)?,
+            )?,
+        };
+        attested_data.put(block_root, data);
+
+        Ok(())
+    }
+
+    fn update_from_sync_aggregate(
+        &self,
+        attested: &AttestedData<E>,
+        block: &SignedBeaconBlock<E>,
+        spec: &ChainSpec,
+    ) -> Result<(), Error> {
+        let sync_aggregate = block.message().body().sync_aggregate()?;
+        if sync_aggregate.num_set_bits() < spec.min_sync_committee_participants as usize {
+            return Ok(());
+        }
+        let signature_slot = block.slot();
+
+        let mut latest_optimistic_update = self.latest_optimistic_update.write();
+        let is_newer_optimistic = latest_optimistic_update.as_ref().map_or(true, |latest| {
+            attested.header.slot > latest.attested_header.slot
+        });
+        if is_newer_optimistic {
+            *latest_optimistic_update = Some(LightClientOptimisticUpdate {
+                attested_header: attested.header.clone(),
+                sync_aggregate: sync_aggregate.clone(),
+                signature_slot,
+            });
+        }
+        drop(latest_optimistic_update);
+
+        if let Some(finalized_header) = &attested.finalized_header {
+            let mut latest_finality_update = self.latest_finality_update.write();
+            let is_newer_finality = latest_finality_update.as_ref().map_or(true, |latest| {
+                finalized_header.slot > latest.finalized_header.slot
+                    || (finalized_header.slot == latest.finalized_header.slot
+                        && attested.header.slot > latest.attested_header.slot)
+            });
+            if is_newer_finality {
+                *latest_finality_update = Some(LightClientFinalityUpdate {
+                    attested_header: attested.header.clone(),
+                    finalized_header: finalized_header.clone(),
+                    finality_branch: attested.finality_branch.clone(),
+                    sync_aggregate: sync_aggregate.clone(),
+                    signature_slot,
+                });
+            }
+        }
+
+        // Only updates signed during the period of the attested block can prove its next sync
+        // committee.
+        let attested_period = attested
+            .header
+            .slot
+            .epoch(E::slots_per_epoch())
+            .sync_committee_period(spec)?;
+        let signature_period = signature_slot
+            .epoch(E::slots_per_epoch())
+            .sync_committee_period(spec)?;
+        if attested_period != signature_period {
+            return Ok(());
+        }
+
+        let update = LightClientUpdate {
+            attested_header: attested.header.clone(),
+            next_sync_committee: attested.next_sync_committee.clone(),
+            next_sync_committee_branch: attested.next_sync_committee_branch.clone(),
+            // Updates without finality have an empty finalized header and branch.
+            finalized_header: attested
+                .finalized_header
+                .clone()
+                .unwrap_or(BeaconBlockHeader {
+                    slot: Slot::new(0),
+                    proposer_index: 0,
+                    parent_root: Hash256::zero(),
+                    state_root: Hash256::zero(),
+                    body_root: Hash256::zero(),
+                }),
+            finality_branch: if attested.finalized_header.is_some() {
+                attested.finality_branch.clone()
+            } else {
+                <_>::default()
+            },
+            sync_aggregate: sync_aggregate.clone(),
+            signature_slot,
+        };
+        let mut best_updates = self.best_updates.write();
+        let is_better = best_updates
+            .get(&attested_period)
+            .map_or(Ok(true), |best| is_better_update(&update, best, spec))?;
+        if is_better {
+            best_updates.insert(attested_period, update);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_latest_finality_update(&self) -> Option<LightClientFinalityUpdate<E>> {
+        self.latest_finality_update.read().clone()
+    }
+
+    pub fn get_latest_optimistic_update(&self) -> Option<LightClientOptimisticUpdate<E>> {
+        self.latest_optimistic_update.read().clone()
+    }
+
+    /// Returns the best updates for up to `count` consecutive periods from `start_period`,
+    /// stopping at the first period without an update.
+    pub fn get_updates(&self, start_period: u64, count: u64) -> Vec<LightClientUpdate<E>> {
+        let best_updates = self.best_updates.read();
+        (start_period..start_period.saturating_add(count))
+            .map_while(|period| best_updates.get(&period).cloned())
+            .collect()
+    }
+}
+
+/// Returns `true` if `new` is a better update than `old` for the same sync committee period, as
+/// defined by `is_better_update` in the light client spec.
+fn is_better_update<E: EthSpec>(
+    new: &LightClientUpdate<E>,
+    old: &LightClientUpdate<E>,
+    spec: &ChainSpec,
+) -> Result<bool, Error> {
+    let period = |header: &BeaconBlockHeader| {
+        header
+            .slot
+            .epoch(E::slots_per_epoch())
+            .sync_committee_period(spec)
+    };
+
+    // Compare supermajority (> 2/3) sync committee participation.
+    let max_participants = E::sync_committee_size();
+    let new_participants = new.sync_aggregate.num_set_bits();
+    let old_participants = old.sync_aggregate.num_set_bits();
+    let new_supermajority = new_participants * 3 >= max_participants * 2;
+    let old_supermajority = old_participants * 3 >= max_participants * 2;
+    if new_supermajority != old_supermajority {
+        return Ok(new_supermajority);
+    }
+    if !new_supermajority && new_participants != old_participants {
+        return Ok(new_participants > old_participants);
+    }
+
+    // Compare presence of finality, and whether it is within the attested period.
+    let new_has_finality = new.finality_branch != <_>::default();
+    let old_has_finality = old.finality_branch != <_>::default();
+    if new_has_finality != old_has_finality {
+        return Ok(new_has_finality);
+    }
+    if new_has_finality {
+        let new_sync_committee_finality =
+            period(&new.finalized_header)? == period(&new.attested_header)?;
+        let old_sync_committee_finality =
+            period(&old.finalized_header)? == period(&old.attested_header)?;
+        if new_sync_committee_finality != old_sync_committee_finality {
+            return Ok(new_sync_committee_finality);
+        }
+    }
+
+    // Tiebreakers: more participation, then older data.
+    if new_participants != old_participants {
+        return Ok(new_participants > old_participants);
+    }
+    if new.attested_header.slot != old.attested_header.slot {
+        return Ok(new.attested_header.slot < old.attested_header.slot);
+    }
+    Ok(new.signature_slot < old.signature_slot)
+}
@@ -26,6 +26,10 @@ lazy_static! {
         "beacon_block_processing_snapshot_cache_size",
         "Count snapshots in the snapshot cache"
     );
+    pub static ref BLOCK_PROCESSING_SNAPSHOT_CACHE_HITS: Result<IntCounter> = try_create_int_counter(
+        "beacon_block_processing_snapshot_cache_hits",
+        "Count of snapshot cache hits"
+    );
     pub static ref BLOCK_PROCESSING_SNAPSHOT_CACHE_MISSES: Result<IntCounter> = try_create_int_counter(
         "beacon_block_processing_snapshot_cache_misses",
         "Count of snapshot cache misses"
@@ -281,7 +285,10 @@ lazy_static! {
         "beacon_early_attester_cache_hits",
         "Count of times the early attester cache returns an attestation"
     );
+}
 
+// Second lazy-static block is used to account for macro recursion limit.
+lazy_static! {
     /*
      * Attestation Production
      */
@@ -301,10 +308,7 @@ lazy_static! {
         "attestation_production_cache_prime_seconds",
         "Time spent loading a new state from the disk due to a cache miss"
     );
-}
 
-// Second lazy-static block is used to account for macro recursion limit.
-lazy_static! {
     /*
      * Fork Choice
      */
@@ -380,6 +384,8 @@ lazy_static! {
         try_create_histogram("beacon_persist_eth1_cache", "Time taken to persist the eth1 caches");
     pub static ref PERSIST_FORK_CHOICE: Result<Histogram> =
         try_create_histogram("beacon_persist_fork_choice", "Time taken to persist the fork choice struct");
+    pub static ref PERSIST_SHUFFLING_CACHE: Result<Histogram> =
+        try_create_histogram("beacon_persist_shuffling_cache", "Time taken to persist the shuffling cache");
 
     /*
      * Eth1
@@ -279,6 +279,11 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
             }
         };
 
+        // Prune blocks and states older than the history horizon, if configured.
+        if let Err(e) = db.try_prune_history() {
+            warn!(log, "History pruning failed"; "error" => ?e);
+        }
+
         // Finally, compact the database so that new free space is properly reclaimed.
         if let Err(e) = Self::run_compaction(
             db,
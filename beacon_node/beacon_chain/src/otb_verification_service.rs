@@ -12,7 +12,7 @@ use ssz_derive::{Decode, Encode};
 use state_processing::per_block_processing::is_merge_transition_complete;
 use std::sync::Arc;
 use store::{DBColumn, Error as StoreError, HotColdDB, KeyValueStore, StoreItem};
-use task_executor::{ShutdownReason, TaskExecutor};
+use task_executor::{FailureKind, ShutdownReason, TaskExecutor};
 use tokio::time::sleep;
 use tree_hash::TreeHash;
 use types::{BeaconBlockRef, EthSpec, Hash256, Slot};
@@ -213,6 +213,7 @@ pub async fn validate_optimistic_transition_blocks<T: BeaconChainTypes>(
                         );
                         let mut shutdown_sender = chain.shutdown_sender();
                         if let Err(e) = shutdown_sender.try_send(ShutdownReason::Failure(
+                            FailureKind::Database,
                             INVALID_FINALIZED_MERGE_TRANSITION_BLOCK_SHUTDOWN_REASON,
                         )) {
                             crit!(
@@ -0,0 +1,31 @@
+use crate::beacon_chain::BeaconChainTypes;
+use slog::{info, Logger};
+use std::sync::Arc;
+use store::{metadata::SchemaVersion, Error, HotColdDB, KeyValueStoreOp};
+
+/// Restore points may now be stored in the freezer database as diffs against a snapshot.
+///
+/// Existing restore points are all stored in full, so only the freezer's schema version changes on
+/// upgrade, so that older versions refuse to open it.
+pub fn upgrade_to_v17<T: BeaconChainTypes>(
+    db: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
+    _log: Logger,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    db.store_freezer_schema_version(SchemaVersion(2))?;
+    Ok(vec![])
+}
+
+/// Store every restore point which is stored as a diff in full, as older versions can't load
+/// diffs.
+///
+/// The freezer database is rewritten before the schema version is updated, so an interrupted
+/// downgrade can be resumed.
+pub fn downgrade_from_v17<T: BeaconChainTypes>(
+    db: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
+    log: Logger,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    let count = db.store_state_diffs_in_full()?;
+    info!(log, "Stored freezer state diffs in full"; "count" => count);
+    db.store_freezer_schema_version(SchemaVersion(1))?;
+    Ok(vec![])
+}
@@ -0,0 +1,31 @@
+use crate::beacon_chain::BeaconChainTypes;
+use slog::{info, Logger};
+use std::sync::Arc;
+use store::{metadata::SchemaVersion, Error, HotColdDB, KeyValueStoreOp};
+
+/// Restore points may now be stored compressed in the freezer database.
+///
+/// Existing restore points are all stored without compression, so only the freezer's schema
+/// version changes on upgrade, so that older versions refuse to open it.
+pub fn upgrade_to_v18<T: BeaconChainTypes>(
+    db: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
+    _log: Logger,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    db.store_freezer_schema_version(SchemaVersion(3))?;
+    Ok(vec![])
+}
+
+/// Decompress every compressed restore point, as older versions can't load them.
+///
+/// The freezer database is rewritten before the schema version is updated, so an interrupted
+/// downgrade can be resumed.
+pub fn downgrade_from_v18<T: BeaconChainTypes>(
+    db: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
+    log: Logger,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    let count = db.decompress_freezer_states()?;
+    info!(log, "Decompressed freezer states"; "count" => count);
+    db.store_freezer_compression_flag(false)?;
+    db.store_freezer_schema_version(SchemaVersion(2))?;
+    Ok(vec![])
+}
@@ -1,7 +1,10 @@
 use crate::{metrics, BeaconChainError};
 use lru::LruCache;
 use oneshot_broadcast::{oneshot, Receiver, Sender};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
 use std::sync::Arc;
+use store::{DBColumn, Error as StoreError, StoreItem};
 use types::{beacon_state::CommitteeCache, AttestationShufflingId, Epoch, Hash256};
 
 /// The size of the LRU cache that stores committee caches for quicker verification.
@@ -22,6 +25,11 @@ pub const DEFAULT_CACHE_SIZE: usize = 16;
 /// better than low-resource nodes going OOM.
 const MAX_CONCURRENT_PROMISES: usize = 2;
 
+/// The maximum number of committee caches written to disk on shutdown.
+///
+/// Each committee cache is around 15 MB with 1M validators, so this bounds the size of the write.
+pub const MAX_PERSISTED_COMMITTEE_CACHES: usize = 4;
+
 #[derive(Clone)]
 pub enum CacheItem {
     /// A committee.
@@ -151,6 +159,75 @@ impl ShufflingCache {
         self.cache.put(key, CacheItem::Promise(receiver));
         Ok(sender)
     }
+
+    /// Returns the resolved committee caches for the shuffling `epochs`, so they can be written
+    /// to disk.
+    ///
+    /// Unresolved promises are skipped, and only the `MAX_PERSISTED_COMMITTEE_CACHES` most
+    /// recently used committee caches are returned.
+    pub fn to_persisted(&self, epochs: &[Epoch]) -> PersistedShufflingCache {
+        let mut entries = self
+            .cache
+            .iter()
+            .filter(|(shuffling_id, _)| epochs.contains(&shuffling_id.shuffling_epoch))
+            .filter_map(|(shuffling_id, item)| match item {
+                CacheItem::Committee(committee_cache) => Some((shuffling_id, committee_cache)),
+                CacheItem::Promise(_) => None,
+            })
+            .take(MAX_PERSISTED_COMMITTEE_CACHES)
+            .collect::<Vec<_>>();
+        // Least recently used first, so that loading the entries in order restores the LRU order.
+        entries.reverse();
+
+        let mut persisted = PersistedShufflingCache::default();
+        for (shuffling_id, committee_cache) in entries {
+            persisted.shuffling_ids.push(shuffling_id.clone());
+            persisted
+                .committee_caches
+                .push(committee_cache.as_ref().clone());
+        }
+        persisted
+    }
+
+    /// Creates a cache containing the committee caches from `persisted`.
+    ///
+    /// If there are more than `cache_size` entries, the least recently used ones are dropped.
+    pub fn from_persisted(persisted: PersistedShufflingCache, cache_size: usize) -> Self {
+        let mut cache = Self::new(cache_size);
+        for (shuffling_id, committee_cache) in persisted
+            .shuffling_ids
+            .into_iter()
+            .zip(persisted.committee_caches)
+        {
+            cache.cache.put(
+                shuffling_id,
+                CacheItem::Committee(Arc::new(committee_cache)),
+            );
+        }
+        cache
+    }
+}
+
+/// The committee caches of a `ShufflingCache`, stored on shutdown so that they needn't be
+/// recomputed after a restart.
+#[derive(Default, Encode, Decode)]
+pub struct PersistedShufflingCache {
+    shuffling_ids: Vec<AttestationShufflingId>,
+    committee_caches: Vec<CommitteeCache>,
+}
+
+impl StoreItem for PersistedShufflingCache {
+    fn db_column() -> DBColumn {
+        DBColumn::ShufflingCache
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_ssz_bytes(bytes).map_err(Into::into)
+    }
 }
 
 /// A helper trait to allow lazy-cloning of the committee cache when inserting into the cache.
@@ -353,6 +430,71 @@ mod test {
         assert_eq!(cache.cache.len(), 2, "the cache should have two entries");
     }
 
+    #[test]
+    fn persist_and_load() {
+        let (committee_a, committee_b) = committee_caches();
+        let (id_a, id_b, id_c) = (shuffling_id(1), shuffling_id(2), shuffling_id(3));
+        let mut cache = ShufflingCache::default();
+
+        cache.insert_committee_cache(id_a.clone(), &committee_a);
+        cache.insert_committee_cache(id_b.clone(), &committee_b);
+        let _sender = cache.create_promise(id_c.clone()).unwrap();
+        // Make A the most recently used entry.
+        cache.get(&id_a).unwrap();
+
+        let epochs = [
+            id_a.shuffling_epoch,
+            id_b.shuffling_epoch,
+            id_c.shuffling_epoch,
+        ];
+        let persisted = PersistedShufflingCache::from_store_bytes(
+            &cache.to_persisted(&epochs).as_store_bytes(),
+        )
+        .unwrap();
+        let mut loaded = ShufflingCache::from_persisted(persisted, DEFAULT_CACHE_SIZE);
+
+        // The promise should not be persisted.
+        assert_eq!(loaded.cache.len(), 2, "the cache should have two entries");
+        assert!(!loaded.contains(&id_c), "the promise should not be loaded");
+        assert!(
+            matches!(loaded.get(&id_b).unwrap(), CacheItem::Committee(committee) if committee == committee_b),
+            "committee B should be loaded"
+        );
+
+        // A cache with a single entry should keep the most recently used committee.
+        let loaded = ShufflingCache::from_persisted(cache.to_persisted(&epochs), 1);
+        assert!(loaded.contains(&id_a), "committee A should be kept");
+        assert!(!loaded.contains(&id_b), "committee B should be dropped");
+    }
+
+    #[test]
+    fn persist_selected_epochs() {
+        let (committee_a, _) = committee_caches();
+        let mut cache = ShufflingCache::default();
+        let ids = (0..DEFAULT_CACHE_SIZE as u64)
+            .map(shuffling_id)
+            .collect::<Vec<_>>();
+        for id in &ids {
+            cache.insert_committee_cache(id.clone(), &committee_a);
+        }
+
+        // Only the committee caches of the given epochs are persisted.
+        let persisted = cache.to_persisted(&[ids[1].shuffling_epoch, ids[3].shuffling_epoch]);
+        assert_eq!(
+            persisted.shuffling_ids,
+            vec![ids[1].clone(), ids[3].clone()]
+        );
+
+        // At most `MAX_PERSISTED_COMMITTEE_CACHES` are persisted, preferring the most recently
+        // used.
+        let epochs = ids.iter().map(|id| id.shuffling_epoch).collect::<Vec<_>>();
+        let persisted = cache.to_persisted(&epochs);
+        assert_eq!(
+            persisted.shuffling_ids,
+            ids[DEFAULT_CACHE_SIZE - MAX_PERSISTED_COMMITTEE_CACHES..]
+        );
+    }
+
     #[test]
     fn too_many_promises() {
         let mut cache = ShufflingCache::default();
@@ -207,6 +207,72 @@ async fn chain_segment_varying_chunk_size() {
     }
 }
 
+#[tokio::test]
+async fn chain_segment_incorrect_proposer_in_later_epoch() {
+    let chain_segment = get_chain_segment().await;
+    let harness = get_invalid_sigs_harness(&chain_segment).await;
+    let spec = &harness.chain.spec;
+
+    // Have a block in the third epoch of the segment validly signed by the wrong proposer. Its
+    // signatures are verified in the same batch as those of the first epoch, before the correct
+    // proposer is known.
+    let block_index = 64;
+    let mut snapshots = chain_segment.clone();
+    let state = &snapshots[block_index].beacon_state;
+    let slot = snapshots[block_index].beacon_block.slot();
+    let epoch = slot.epoch(E::slots_per_epoch());
+    let proposer_index = state.get_beacon_proposer_index(slot, spec).unwrap();
+    let wrong_proposer_index = (proposer_index + 1) % VALIDATOR_COUNT;
+    let keypair = &harness.validator_keypairs[wrong_proposer_index];
+    let domain = spec.get_domain(
+        epoch,
+        Domain::Randao,
+        &state.fork(),
+        state.genesis_validators_root(),
+    );
+    let randao_reveal = keypair.sk.sign(epoch.signing_root(domain));
+
+    let (mut block, signature) = snapshots[block_index]
+        .beacon_block
+        .as_ref()
+        .clone()
+        .deconstruct();
+    *block.proposer_index_mut() = wrong_proposer_index as u64;
+    *block.body_mut().randao_reveal_mut() = randao_reveal;
+    snapshots[block_index].beacon_block = Arc::new(SignedBeaconBlock::from_block(block, signature));
+    update_parent_roots(&mut snapshots);
+    update_proposal_signatures(&mut snapshots, &harness);
+
+    let (block, _) = snapshots[block_index]
+        .beacon_block
+        .as_ref()
+        .clone()
+        .deconstruct();
+    let state = &snapshots[block_index].beacon_state;
+    snapshots[block_index].beacon_block = Arc::new(block.sign(
+        &keypair.sk,
+        &state.fork(),
+        state.genesis_validators_root(),
+        spec,
+    ));
+
+    // The blocks before it are imported, and it is rejected when it is processed.
+    assert!(matches!(
+        harness
+            .chain
+            .process_chain_segment(
+                chain_segment_blocks(&snapshots),
+                CountUnrealized::True,
+                NotifyExecutionLayer::Yes,
+            )
+            .await,
+        ChainSegmentResult::Failed {
+            imported_blocks,
+            error: BlockError::PerBlockProcessingError(BlockProcessingError::HeaderInvalid { .. }),
+        } if imported_blocks == block_index
+    ));
+}
+
 #[tokio::test]
 async fn chain_segment_non_linear_parent_roots() {
     let harness = get_harness(VALIDATOR_COUNT);
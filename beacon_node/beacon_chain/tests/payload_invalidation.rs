@@ -26,7 +26,7 @@ use slot_clock::SlotClock;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use task_executor::ShutdownReason;
+use task_executor::{FailureKind, ShutdownReason};
 use tree_hash::TreeHash;
 use types::*;
 
@@ -527,6 +527,7 @@ async fn justified_checkpoint_becomes_invalid() {
     assert_eq!(
         rig.harness.shutdown_reasons(),
         vec![ShutdownReason::Failure(
+            FailureKind::Other,
             INVALID_JUSTIFIED_PAYLOAD_SHUTDOWN_REASON
         )]
     );
@@ -1776,6 +1777,7 @@ async fn optimistic_transition_block_invalid_finalized() {
     assert_eq!(
         rig.harness.shutdown_reasons(),
         vec![ShutdownReason::Failure(
+            FailureKind::Database,
             INVALID_FINALIZED_MERGE_TRANSITION_BLOCK_SHUTDOWN_REASON
         )]
     );
@@ -23,10 +23,14 @@ use std::collections::HashSet;
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::Duration;
-use store::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION};
+use store::config::{FreezerCompression, OnDiskStoreConfig};
+use store::hot_cold_store::{check_freezer_schema_version, HotColdDBError};
+use store::metadata::{
+    SchemaVersion, CONFIG_KEY, CURRENT_FREEZER_SCHEMA_VERSION, CURRENT_SCHEMA_VERSION,
+};
 use store::{
     iter::{BlockRootsIterator, StateRootsIterator},
-    HotColdDB, LevelDB, StoreConfig,
+    BeaconNodeBackend, DBColumn, HotColdDB, HotStateSummary, ItemStore, KeyValueStore, StoreConfig,
 };
 use tempfile::{tempdir, TempDir};
 use tokio::time::sleep;
@@ -46,14 +50,14 @@ lazy_static! {
 type E = MinimalEthSpec;
 type TestHarness = BeaconChainHarness<DiskHarnessType<E>>;
 
-fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
     get_store_with_spec(db_path, test_spec::<E>())
 }
 
 fn get_store_with_spec(
     db_path: &TempDir,
     spec: ChainSpec,
-) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
     let hot_path = db_path.path().join("hot_db");
     let cold_path = db_path.path().join("cold_db");
     let config = StoreConfig::default();
@@ -64,7 +68,7 @@ fn get_store_with_spec(
 }
 
 fn get_harness(
-    store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
     validator_count: usize,
 ) -> TestHarness {
     let harness = BeaconChainHarness::builder(MinimalEthSpec)
@@ -2241,6 +2245,43 @@ async fn weak_subjectivity_sync() {
         assert_eq!(state.canonical_root(), state_root);
     }
 
+    // States which aren't stored can be replayed now that all of the blocks are available.
+    let expected_root = harness.chain.state_root_at_slot(Slot::new(1)).unwrap();
+    let (replayed_root, _) = store
+        .load_historic_state_by_replay(Slot::new(1))
+        .unwrap()
+        .unwrap();
+    assert_eq!(Some(replayed_root), expected_root);
+    let replayed_state = store
+        .get_state(&replayed_root, Some(Slot::new(1)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(replayed_state.canonical_root(), replayed_root);
+
+    // Later states are replayed from the cached state.
+    let (replayed_root, _) = store
+        .load_historic_state_by_replay(Slot::new(2))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Some(replayed_root),
+        harness.chain.state_root_at_slot(Slot::new(2)).unwrap()
+    );
+
+    // State roots in the gap are only read from the freezer database, never replayed, and the
+    // states can then be replayed by root.
+    let (lower_limit, upper_limit) = store.get_historic_state_limits();
+    for slot in (lower_limit.as_u64() + 1..upper_limit.as_u64()).map(Slot::new) {
+        if let Some(state_root) = beacon_chain.state_root_at_slot(slot).unwrap() {
+            assert_eq!(
+                Some(state_root),
+                harness.chain.state_root_at_slot(slot).unwrap()
+            );
+            let state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+            assert_eq!(state.canonical_root(), state_root);
+        }
+    }
+
     // Anchor slot is still set to the starting slot.
     assert_eq!(store.get_anchor_slot(), Some(wss_slot));
 
@@ -2532,6 +2573,889 @@ async fn revert_minority_fork_on_resume() {
     assert_eq!(heads.len(), 1);
 }
 
+#[tokio::test]
+async fn prune_historic_states() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let genesis_state_root = harness.chain.genesis_state_root;
+    let genesis_state = harness
+        .chain
+        .get_state(&genesis_state_root, None)
+        .unwrap()
+        .unwrap();
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    // Check the states of the first epoch are available from the freezer.
+    let first_epoch_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take(E::slots_per_epoch() as usize)
+        .collect::<Vec<_>>();
+    for &(state_root, slot) in &first_epoch_state_roots {
+        assert!(store.get_state(&state_root, Some(slot)).unwrap().is_some());
+    }
+
+    // A genesis state which doesn't match the database is refused before anything is deleted.
+    let mut other_genesis_state = genesis_state.clone();
+    other_genesis_state.balances_mut()[0] += 1;
+    let other_genesis_state_root = other_genesis_state.canonical_root();
+    assert!(matches!(
+        store.prune_historic_states(other_genesis_state_root, &other_genesis_state),
+        Err(store::Error::HotColdDBError(
+            HotColdDBError::GenesisStateRootMismatch { stored, supplied }
+        )) if stored == genesis_state_root && supplied == other_genesis_state_root
+    ));
+    assert!(!store
+        .get_anchor_info()
+        .map_or(false, |anchor| anchor.historic_states_pruned()));
+    for &(state_root, slot) in &first_epoch_state_roots {
+        assert!(store.get_state(&state_root, Some(slot)).unwrap().is_some());
+    }
+
+    store
+        .prune_historic_states(genesis_state_root, &genesis_state)
+        .unwrap();
+
+    assert!(store.get_anchor_info().unwrap().historic_states_pruned());
+
+    // Only the genesis state remains.
+    for &(state_root, slot) in &first_epoch_state_roots {
+        assert_eq!(
+            store.get_state(&state_root, Some(slot)).unwrap().is_some(),
+            slot == 0
+        );
+    }
+
+    // The chain continues to finalize without storing historic states.
+    harness
+        .extend_chain(
+            E::slots_per_epoch() as usize * 2,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    check_finalization(&harness, num_blocks_produced + E::slots_per_epoch() * 2);
+    check_split_slot(&harness, store.clone());
+    assert!(store.get_anchor_info().unwrap().historic_states_pruned());
+}
+
+fn get_store_with_slots_per_restore_point(
+    db_path: &TempDir,
+    slots_per_restore_point: u64,
+) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
+    get_store_with_config(
+        db_path,
+        StoreConfig {
+            slots_per_restore_point,
+            slots_per_restore_point_set_explicitly: true,
+            ..StoreConfig::default()
+        },
+    )
+}
+
+fn get_store_with_config(
+    db_path: &TempDir,
+    config: StoreConfig,
+) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
+    let hot_path = db_path.path().join("hot_db");
+    let cold_path = db_path.path().join("cold_db");
+    let log = test_logger();
+
+    HotColdDB::open(
+        &hot_path,
+        &cold_path,
+        |_, _, _| Ok(()),
+        config,
+        test_spec::<E>(),
+        log,
+    )
+    .expect("disk store should initialize")
+}
+
+/// Copy a LevelDB database into MDBX, as `lighthouse db convert-backend` does, and resume the chain
+/// from the copy.
+#[cfg(feature = "mdbx")]
+#[tokio::test]
+async fn convert_leveldb_to_mdbx() {
+    use store::config::DatabaseBackend;
+
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+    let head_block_root = harness.head_block_root();
+
+    let mdbx_path = tempdir().unwrap();
+    for (source, name) in [(&store.hot_db, "hot_db"), (&store.cold_db, "cold_db")] {
+        let target =
+            BeaconNodeBackend::open(DatabaseBackend::Mdbx, &mdbx_path.path().join(name)).unwrap();
+        assert!(source.copy_into(&target).unwrap() > 0);
+    }
+
+    let converted_store = get_store_with_config(
+        &mdbx_path,
+        StoreConfig {
+            backend: DatabaseBackend::Mdbx,
+            ..StoreConfig::default()
+        },
+    );
+    let resumed_harness = BeaconChainHarness::builder(MinimalEthSpec)
+        .default_spec()
+        .keypairs(KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec())
+        .resumed_disk_store(converted_store)
+        .mock_execution_layer()
+        .build();
+    assert_eq!(resumed_harness.head_block_root(), head_block_root);
+    assert_eq!(resumed_harness.head_slot(), num_blocks_produced);
+}
+
+#[tokio::test]
+async fn change_slots_per_restore_point() {
+    let num_blocks_produced = E::slots_per_epoch() * 10;
+    let db_path = tempdir().unwrap();
+    let store = get_store_with_slots_per_restore_point(&db_path, 2 * E::slots_per_epoch());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    assert!(split_slot >= 4 * E::slots_per_epoch());
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    drop(harness);
+    drop(store);
+
+    // Every restore point of the new config already exists, so all states remain available.
+    let store = get_store_with_slots_per_restore_point(&db_path, 4 * E::slots_per_epoch());
+    assert_eq!(store.get_anchor_info(), None);
+    for &(state_root, slot) in &frozen_state_roots {
+        let state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+        assert_eq!(state.slot(), slot);
+    }
+    drop(store);
+
+    // Simulate the process dying after the freezer was re-indexed but before the hot database's
+    // copy of the config was written. The freezer must not be re-indexed again.
+    store
+        .hot_db
+        .put(
+            &CONFIG_KEY,
+            &OnDiskStoreConfig {
+                slots_per_restore_point: 2 * E::slots_per_epoch(),
+            },
+        )
+        .unwrap();
+    drop(store);
+    let store = get_store_with_slots_per_restore_point(&db_path, 4 * E::slots_per_epoch());
+    assert_eq!(store.get_anchor_info(), None);
+    for &(state_root, slot) in &frozen_state_roots {
+        let state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+        assert_eq!(state.slot(), slot);
+    }
+    drop(store);
+
+    // Restore points are missing after genesis, so states are only available from genesis and
+    // the last restore point.
+    let store = get_store_with_slots_per_restore_point(&db_path, E::slots_per_epoch());
+    let anchor = store.get_anchor_info().unwrap();
+    assert_eq!(anchor.state_lower_limit, 0);
+    assert!(anchor.state_upper_limit > 0);
+    for &(state_root, slot) in &frozen_state_roots {
+        assert_eq!(
+            store.get_state(&state_root, Some(slot)).unwrap().is_some(),
+            slot == 0 || slot >= anchor.state_upper_limit
+        );
+    }
+}
+
+#[tokio::test]
+async fn state_diffs() {
+    let num_blocks_produced = E::slots_per_epoch() * 10;
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        slots_per_restore_point: E::slots_per_epoch(),
+        slots_per_restore_point_set_explicitly: true,
+        restore_points_per_snapshot: 4,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config.clone());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    assert!(split_slot >= 5 * E::slots_per_epoch());
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    drop(harness);
+    drop(store);
+
+    // Every restore point except those at a multiple of 4 epochs is stored as a diff, and the
+    // states can still be loaded after a restart, whatever the configured snapshot interval.
+    for restore_points_per_snapshot in [4, 1] {
+        let store = get_store_with_config(
+            &db_path,
+            StoreConfig {
+                restore_points_per_snapshot,
+                ..config.clone()
+            },
+        );
+        let restore_points = split_slot.as_u64() / E::slots_per_epoch();
+        let diffs = store
+            .cold_db
+            .iter_raw_keys(DBColumn::BeaconStateDiff)
+            .count() as u64;
+        assert_eq!(diffs, restore_points - (restore_points + 3) / 4);
+        for &(state_root, slot) in &frozen_state_roots {
+            let state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+            assert_eq!(state.slot(), slot);
+            assert_eq!(state.canonical_root(), state_root);
+        }
+    }
+}
+
+// Downgrading to a schema version from before state diffs and compression stores the diffs in full
+// and decompresses the states.
+#[tokio::test]
+async fn freezer_schema_downgrade() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        slots_per_restore_point: E::slots_per_epoch(),
+        slots_per_restore_point_set_explicitly: true,
+        restore_points_per_snapshot: 4,
+        freezer_compression: FreezerCompression::Zlib,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config.clone());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let spec = harness.chain.spec.clone();
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    drop(harness);
+    drop(store);
+
+    let store = get_store_with_config(&db_path, config);
+    let count = |column| store.cold_db.iter_raw_keys(column).count();
+    assert!(count(DBColumn::BeaconStateDiff) > 0);
+    assert!(count(DBColumn::BeaconStateCompressed) > 0);
+    assert!(store.load_freezer_compression_flag().unwrap());
+
+    migrate_schema::<DiskHarnessType<E>>(
+        store.clone(),
+        0,
+        CURRENT_SCHEMA_VERSION,
+        SchemaVersion(17),
+        store.logger().clone(),
+        &spec,
+    )
+    .expect("schema downgrade should work");
+    assert!(count(DBColumn::BeaconStateDiff) > 0);
+    assert_eq!(count(DBColumn::BeaconStateCompressed), 0);
+    assert!(!store.load_freezer_compression_flag().unwrap());
+    assert_eq!(
+        store.load_freezer_schema_version().unwrap(),
+        Some(SchemaVersion(2))
+    );
+
+    migrate_schema::<DiskHarnessType<E>>(
+        store.clone(),
+        0,
+        SchemaVersion(17),
+        SchemaVersion(16),
+        store.logger().clone(),
+        &spec,
+    )
+    .expect("schema downgrade should work");
+    assert_eq!(count(DBColumn::BeaconStateDiff), 0);
+    assert_eq!(count(DBColumn::BeaconStateCompressed), 0);
+    assert_eq!(
+        store.load_freezer_schema_version().unwrap(),
+        Some(SchemaVersion(1))
+    );
+    for &(state_root, slot) in &frozen_state_roots {
+        let state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+        assert_eq!(state.canonical_root(), state_root);
+    }
+
+    migrate_schema::<DiskHarnessType<E>>(
+        store.clone(),
+        0,
+        SchemaVersion(16),
+        CURRENT_SCHEMA_VERSION,
+        store.logger().clone(),
+        &spec,
+    )
+    .expect("schema upgrade should work");
+    assert_eq!(
+        store.load_freezer_schema_version().unwrap(),
+        Some(CURRENT_FREEZER_SCHEMA_VERSION)
+    );
+}
+
+// A freezer which may hold state diffs can't be opened by versions which only support full
+// restore points.
+#[test]
+fn state_diff_freezer_is_refused_by_older_versions() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let on_disk = store.load_freezer_schema_version().unwrap();
+    assert_eq!(on_disk, Some(CURRENT_FREEZER_SCHEMA_VERSION));
+
+    assert_eq!(
+        check_freezer_schema_version(on_disk, false, SchemaVersion(1)),
+        Err(HotColdDBError::UnsupportedFreezerSchemaVersion {
+            on_disk: CURRENT_FREEZER_SCHEMA_VERSION,
+            supported: SchemaVersion(1),
+        })
+    );
+    assert_eq!(
+        check_freezer_schema_version(on_disk, false, CURRENT_FREEZER_SCHEMA_VERSION),
+        Ok(())
+    );
+}
+
+// A freezer which holds compressed states records it, so that versions which can't read them
+// refuse to open it.
+#[test]
+fn compressed_freezer_is_refused_by_older_versions() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    assert!(!store.load_freezer_compression_flag().unwrap());
+    drop(store);
+
+    let config = StoreConfig {
+        freezer_compression: FreezerCompression::Snappy,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config);
+    let compressed = store.load_freezer_compression_flag().unwrap();
+    assert!(compressed);
+
+    // The flag is checked as well as the schema version, so a version which reads diffs but not
+    // compressed states is refused even by a freezer at its schema version.
+    assert_eq!(
+        check_freezer_schema_version(Some(SchemaVersion(2)), compressed, SchemaVersion(2)),
+        Err(HotColdDBError::UnsupportedFreezerCompression {
+            supported: SchemaVersion(2),
+        })
+    );
+    assert_eq!(
+        check_freezer_schema_version(
+            Some(CURRENT_FREEZER_SCHEMA_VERSION),
+            compressed,
+            CURRENT_FREEZER_SCHEMA_VERSION
+        ),
+        Ok(())
+    );
+}
+
+#[tokio::test]
+async fn freezer_compression() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        slots_per_restore_point: E::slots_per_epoch(),
+        slots_per_restore_point_set_explicitly: true,
+        freezer_compression: FreezerCompression::Snappy,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config.clone());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    drop(harness);
+    drop(store);
+
+    // Rewrite the states with each compression in turn, checking that they can still be loaded.
+    let restore_points = (split_slot.as_u64() / E::slots_per_epoch()) as usize;
+    for freezer_compression in [
+        FreezerCompression::Snappy,
+        FreezerCompression::Zlib,
+        FreezerCompression::None,
+    ] {
+        let store = get_store_with_config(
+            &db_path,
+            StoreConfig {
+                freezer_compression,
+                ..config.clone()
+            },
+        );
+        store.recompress_freezer_states().unwrap();
+
+        let count = |column| store.cold_db.iter_raw_keys(column).count();
+        if freezer_compression == FreezerCompression::None {
+            assert_eq!(count(DBColumn::BeaconState), restore_points);
+            assert_eq!(count(DBColumn::BeaconStateCompressed), 0);
+        } else {
+            assert_eq!(count(DBColumn::BeaconState), 0);
+            assert_eq!(count(DBColumn::BeaconStateCompressed), restore_points);
+        }
+        for &(state_root, slot) in &frozen_state_roots {
+            let state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+            assert_eq!(state.canonical_root(), state_root);
+        }
+    }
+}
+
+#[tokio::test]
+async fn prune_old_history() {
+    let num_blocks_produced = E::slots_per_epoch() * 10;
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        slots_per_restore_point: E::slots_per_epoch(),
+        slots_per_restore_point_set_explicitly: true,
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config.clone());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    assert!(split_slot >= 5 * E::slots_per_epoch());
+    let frozen_block_roots = harness
+        .chain
+        .forwards_iter_block_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    drop(harness);
+    drop(store);
+
+    // Re-open the database with a history horizon 3 epochs before the split, and prune.
+    let store = get_store_with_config(
+        &db_path,
+        StoreConfig {
+            prune_blocks_older_than: Some(3),
+            ..config
+        },
+    );
+    let horizon = store.get_history_horizon();
+    assert_eq!(horizon, split_slot - 3 * E::slots_per_epoch());
+    store.try_prune_history().unwrap();
+
+    // Every slot has a block, so the oldest block is the one at the horizon.
+    let anchor = store.get_anchor_info().unwrap();
+    assert_eq!(anchor.oldest_block_slot, horizon);
+    assert!(anchor.block_backfill_complete(store.get_history_horizon()));
+    assert_eq!(anchor.state_upper_limit, horizon);
+    for &(block_root, slot) in &frozen_block_roots {
+        assert_eq!(
+            store.get_blinded_block(&block_root).unwrap().is_some(),
+            slot >= horizon
+        );
+        if slot == horizon {
+            let block = store.get_blinded_block(&block_root).unwrap().unwrap();
+            assert_eq!(anchor.oldest_block_parent, block.parent_root());
+        }
+    }
+    for &(state_root, slot) in &frozen_state_roots {
+        assert_eq!(
+            store.get_state(&state_root, Some(slot)).unwrap().is_some(),
+            slot == 0 || slot >= horizon
+        );
+    }
+
+    // Pruning again without advancing the horizon is a no-op.
+    store.try_prune_history().unwrap();
+    assert_eq!(store.get_anchor_info(), Some(anchor));
+}
+
+#[tokio::test]
+async fn verify_integrity() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let db_path = tempdir().unwrap();
+    let store = get_store_with_slots_per_restore_point(&db_path, E::slots_per_epoch());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    let report = store.verify_integrity(false).unwrap();
+    assert!(report.is_ok(), "{:?}", report);
+    assert_eq!(report.blocks_checked as u64, split_slot.as_u64() + 1);
+    assert_eq!(
+        report.restore_points_checked as u64,
+        split_slot.as_u64() / E::slots_per_epoch()
+    );
+
+    // Leave a summary behind for a finalized state, as if a migration was interrupted, and delete
+    // a finalized block.
+    let (stale_root, stale_slot) = harness
+        .chain
+        .forwards_iter_state_roots(split_slot - 1)
+        .unwrap()
+        .map(Result::unwrap)
+        .next()
+        .unwrap();
+    let stale_state = store
+        .get_state(&stale_root, Some(stale_slot))
+        .unwrap()
+        .unwrap();
+    store
+        .put_state_summary(
+            &stale_root,
+            HotStateSummary::new(&stale_root, &stale_state).unwrap(),
+        )
+        .unwrap();
+    let (deleted_root, deleted_slot) = harness
+        .chain
+        .forwards_iter_block_roots(Slot::new(3))
+        .unwrap()
+        .map(Result::unwrap)
+        .next()
+        .unwrap();
+    store.delete_block(&deleted_root).unwrap();
+
+    let report = store.verify_integrity(true).unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.dangling_states, vec![(stale_root, Some(stale_slot))]);
+    assert!(report.repaired);
+    assert_eq!(report.broken_blocks, vec![(deleted_slot, deleted_root)]);
+    assert!(store.load_hot_state_summary(&stale_root).unwrap().is_none());
+
+    // The dangling state isn't reported again once it's deleted.
+    let report = store.verify_integrity(false).unwrap();
+    assert!(report.dangling_states.is_empty());
+
+    // The database can be re-anchored at the latest restore point, or the one before it if its
+    // block is missing.
+    let (anchor_state, anchor_block) = store.load_recovery_anchor().unwrap().unwrap();
+    assert_eq!(anchor_state.slot(), split_slot - E::slots_per_epoch());
+    assert_eq!(anchor_block.slot(), anchor_state.slot());
+    assert_eq!(anchor_block.state_root(), anchor_state.canonical_root());
+
+    store.delete_block(&anchor_block.canonical_root()).unwrap();
+    let (anchor_state, _) = store.load_recovery_anchor().unwrap().unwrap();
+    assert_eq!(anchor_state.slot(), split_slot - 2 * E::slots_per_epoch());
+}
+
+#[tokio::test]
+async fn import_era_blocks() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let temp1 = tempdir().unwrap();
+    let full_store = get_store(&temp1);
+    let harness = get_harness(full_store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let genesis_state = full_store
+        .get_state(&harness.chain.genesis_state_root, Some(Slot::new(0)))
+        .unwrap()
+        .unwrap();
+    let wss_checkpoint = harness.finalized_checkpoint();
+    let wss_block = full_store
+        .get_full_block(&wss_checkpoint.root)
+        .unwrap()
+        .unwrap();
+    let wss_state = full_store
+        .get_state(&wss_block.state_root(), None)
+        .unwrap()
+        .unwrap();
+    let wss_slot = wss_block.slot();
+
+    // Checkpoint sync a new store, which only has the blocks from the checkpoint.
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let log = test_logger();
+    let temp2 = tempdir().unwrap();
+    let store = get_store(&temp2);
+    let beacon_chain = BeaconChainBuilder::new(MinimalEthSpec)
+        .store(store.clone())
+        .custom_spec(test_spec::<E>())
+        .task_executor(harness.chain.task_executor.clone())
+        .weak_subjectivity_state(wss_state, wss_block, genesis_state)
+        .unwrap()
+        .logger(log.clone())
+        .store_migrator_config(MigratorConfig::default().blocking())
+        .dummy_eth1_backend()
+        .expect("should build dummy backend")
+        .testing_slot_clock(Duration::from_secs(test_spec::<E>().seconds_per_slot))
+        .expect("should configure testing slot clock")
+        .shutdown_sender(shutdown_tx)
+        .chain_config(ChainConfig::default())
+        .event_handler(Some(ServerSentEventHandler::new_with_capacity(
+            log.clone(),
+            1,
+        )))
+        .monitor_validators(true, vec![], DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD, log)
+        .build()
+        .expect("should build");
+    assert_eq!(store.get_oldest_block_slot(), wss_slot);
+
+    // Split the older blocks into two eras, which include the genesis block and the checkpoint
+    // block.
+    let blocks = harness
+        .chain
+        .chain_dump()
+        .unwrap()
+        .iter()
+        .take_while(|snapshot| snapshot.beacon_block.slot() <= wss_slot)
+        .map(|snapshot| {
+            full_store
+                .get_full_block(&snapshot.beacon_block_root)
+                .unwrap()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let (older, newer) = blocks.split_at(blocks.len() / 2);
+
+    // The older era can't be imported until the newer one is.
+    assert!(store.import_era_blocks(older.to_vec()).is_err());
+    assert_eq!(
+        store.import_era_blocks(newer.to_vec()).unwrap(),
+        newer.len() - 1
+    );
+    assert_eq!(store.get_oldest_block_slot(), newer[0].slot());
+    assert_eq!(
+        store.import_era_blocks(older.to_vec()).unwrap(),
+        older.len() - 1
+    );
+    assert_eq!(store.get_oldest_block_slot(), 0);
+    assert_eq!(store.import_era_blocks(blocks).unwrap(), 0);
+
+    let forwards = beacon_chain
+        .forwards_iter_block_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot <= wss_slot)
+        .collect::<Vec<_>>();
+    let expected = harness
+        .chain
+        .forwards_iter_block_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot <= wss_slot)
+        .collect::<Vec<_>>();
+    assert_eq!(forwards, expected);
+}
+
+#[tokio::test]
+async fn import_era_state() {
+    let temp1 = tempdir().unwrap();
+    let full_store = get_store(&temp1);
+    let harness = get_harness(full_store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            (E::slots_per_epoch() * 14) as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let genesis_state = full_store
+        .get_state(&harness.chain.genesis_state_root, Some(Slot::new(0)))
+        .unwrap()
+        .unwrap();
+    let wss_checkpoint = harness.finalized_checkpoint();
+    let wss_block = full_store
+        .get_full_block(&wss_checkpoint.root)
+        .unwrap()
+        .unwrap();
+    let wss_state = full_store
+        .get_state(&wss_block.state_root(), None)
+        .unwrap()
+        .unwrap();
+    let wss_slot = wss_block.slot();
+
+    // Advance finalization, so that the checkpoint synced node stores the restore point after the
+    // checkpoint.
+    harness.advance_slot();
+    harness
+        .extend_chain(
+            (E::slots_per_epoch() * 4) as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    // Checkpoint sync a new store with restore points more frequent than eras, so that restore
+    // points after the era state are regenerated.
+    let slots_per_restore_point = 2 * E::slots_per_epoch();
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let log = test_logger();
+    let temp2 = tempdir().unwrap();
+    let store = get_store_with_slots_per_restore_point(&temp2, slots_per_restore_point);
+    let beacon_chain = BeaconChainBuilder::new(MinimalEthSpec)
+        .store(store.clone())
+        .custom_spec(test_spec::<E>())
+        .task_executor(harness.chain.task_executor.clone())
+        .weak_subjectivity_state(wss_state, wss_block, genesis_state)
+        .unwrap()
+        .logger(log.clone())
+        .store_migrator_config(MigratorConfig::default().blocking())
+        .dummy_eth1_backend()
+        .expect("should build dummy backend")
+        .testing_slot_clock(Duration::from_secs(test_spec::<E>().seconds_per_slot))
+        .expect("should configure testing slot clock")
+        .shutdown_sender(shutdown_tx)
+        .chain_config(ChainConfig::default())
+        .event_handler(Some(ServerSentEventHandler::new_with_capacity(
+            log.clone(),
+            1,
+        )))
+        .monitor_validators(true, vec![], DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD, log)
+        .build()
+        .expect("should build");
+
+    let chain_dump = harness.chain.chain_dump().unwrap();
+    for snapshot in &chain_dump[wss_slot.as_usize() + 1..] {
+        let full_block = harness
+            .chain
+            .get_block(&snapshot.beacon_block_root)
+            .await
+            .unwrap()
+            .unwrap();
+        beacon_chain.slot_clock.set_slot(full_block.slot().as_u64());
+        beacon_chain
+            .process_block(
+                full_block.canonical_root(),
+                Arc::new(full_block),
+                CountUnrealized::True,
+                NotifyExecutionLayer::Yes,
+            )
+            .await
+            .unwrap();
+        beacon_chain.recompute_head_at_current_slot().await;
+    }
+    let state_upper_limit = store.get_anchor_info().unwrap().state_upper_limit;
+    assert!(store.get_split_slot() > state_upper_limit);
+
+    // The state at the end of the era before the checkpoint.
+    let era_slot =
+        wss_slot / E::slots_per_historical_root() as u64 * E::slots_per_historical_root() as u64;
+    assert!(era_slot + slots_per_restore_point < state_upper_limit);
+    let era_state_root = harness.chain.state_root_at_slot(era_slot).unwrap().unwrap();
+    let era_state = full_store
+        .get_state(&era_state_root, Some(era_slot))
+        .unwrap()
+        .unwrap();
+
+    // A state which doesn't match the state root in the freezer is rejected.
+    let mut wrong_state = era_state.clone();
+    wrong_state.balances_mut()[0] += 1;
+    assert!(store.import_era_state(wrong_state).is_err());
+    assert_eq!(
+        store.get_anchor_info().unwrap().state_upper_limit,
+        state_upper_limit
+    );
+
+    assert!(store.import_era_state(era_state.clone()).unwrap());
+    assert_eq!(store.get_anchor_info().unwrap().state_upper_limit, era_slot);
+    assert!(!store.import_era_state(era_state).unwrap());
+
+    // The imported state, the regenerated restore points and the states between them load.
+    for slot in (era_slot.as_u64()..state_upper_limit.as_u64()).map(Slot::new) {
+        let expected_root = harness.chain.state_root_at_slot(slot).unwrap().unwrap();
+        let state = store
+            .load_cold_state_by_slot(slot)
+            .unwrap()
+            .unwrap_or_else(|| panic!("state at slot {} should load", slot));
+        assert_eq!(state.canonical_root(), expected_root);
+    }
+}
+
 // This test checks whether the schema downgrade from the latest version to some minimum supported
 // version is correct. This is the easiest schema test to write without historic versions of
 // Lighthouse on-hand, but has the disadvantage that the min version needs to be adjusted manually
@@ -2617,6 +3541,35 @@ async fn schema_downgrade_to_min_version() {
     .expect_err("should not downgrade below minimum version");
 }
 
+#[test]
+fn newer_freezer_schema_version_is_refused() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    assert_eq!(
+        store.load_freezer_schema_version().unwrap(),
+        Some(CURRENT_FREEZER_SCHEMA_VERSION)
+    );
+
+    let newer_version = SchemaVersion(CURRENT_FREEZER_SCHEMA_VERSION.as_u64() + 1);
+    store.store_freezer_schema_version(newer_version).unwrap();
+    drop(store);
+
+    let hot_path = db_path.path().join("hot_db");
+    let cold_path = db_path.path().join("cold_db");
+    let result = HotColdDB::<E, _, _>::open(
+        &hot_path,
+        &cold_path,
+        |_, _, _| Ok(()),
+        StoreConfig::default(),
+        test_spec::<E>(),
+        test_logger(),
+    );
+    assert!(
+        result.is_err(),
+        "a freezer written by a newer version should not be opened"
+    );
+}
+
 /// Checks that two chains are the same, for the purpose of these tests.
 ///
 /// Several fields that are hard/impossible to check are ignored (e.g., the store).
@@ -2691,7 +3644,10 @@ fn check_finalization(harness: &TestHarness, expected_slot: u64) {
 }
 
 /// Check that the HotColdDB's split_slot is equal to the start slot of the last finalized epoch.
-fn check_split_slot(harness: &TestHarness, store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>) {
+fn check_split_slot(
+    harness: &TestHarness,
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
+) {
     let split_slot = store.get_split_slot();
     assert_eq!(
         harness
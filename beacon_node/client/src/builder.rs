@@ -10,16 +10,19 @@ use beacon_chain::{
     eth1_chain::{CachingEth1Backend, Eth1Chain},
     slot_clock::{SlotClock, SystemTimeSlotClock},
     state_advance_timer::spawn_state_advance_timer,
-    store::{HotColdDB, ItemStore, LevelDB, StoreConfig},
+    store::{
+        hot_cold_store::HotColdDBError, BeaconNodeBackend, Error as StoreError, HotColdDB,
+        ItemStore, StoreConfig,
+    },
     BeaconChain, BeaconChainTypes, Eth1ChainBackend, ServerSentEventHandler,
 };
-use environment::RuntimeContext;
+use environment::{FailureKind, RuntimeContext, StartupError};
 use eth1::{Config as Eth1Config, Service as Eth1Service};
 use eth2::{
     types::{BlockId, StateId},
     BeaconNodeHttpClient, Error as ApiError, Timeouts,
 };
-use execution_layer::ExecutionLayer;
+use execution_layer::{http::proxied_client, ExecutionLayer};
 use genesis::{interop_genesis_state, Eth1GenesisService, DEFAULT_ETH1_BLOCK_HASH};
 use lighthouse_network::{prometheus_client::registry::Registry, NetworkGlobals};
 use monitoring_api::{MonitoringHttpClient, ProcessType};
@@ -27,6 +30,8 @@ use network::{NetworkConfig, NetworkSenders, NetworkService};
 use slasher::Slasher;
 use slasher_service::SlasherService;
 use slog::{debug, info, warn, Logger};
+use ssz::Encode;
+use state_processing::common::compute_weak_subjectivity_period;
 use std::net::TcpListener;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -40,6 +45,8 @@ use types::{
 
 /// Interval between polling the eth1 node for genesis information.
 pub const ETH1_GENESIS_UPDATE_INTERVAL_MILLIS: u64 = 7_000;
+/// Interval between updates of the database size and disk space metrics.
+pub const DISK_METRICS_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Builds a `Client` instance.
 ///
@@ -258,6 +265,10 @@ where
                 let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes, &spec)
                     .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?;
 
+                // Unlike a state downloaded from a trusted node, a state from a file may be
+                // arbitrarily old.
+                check_weak_subjectivity_period(&anchor_state, genesis_state.genesis_time(), &spec)?;
+
                 builder
                     .weak_subjectivity_state(anchor_state, anchor_block, genesis_state)
                     .map(|v| (v, None))?
@@ -272,12 +283,17 @@ where
                     "remote_url" => %url,
                 );
 
-                let remote = BeaconNodeHttpClient::new(
-                    url,
-                    Timeouts::set_all(Duration::from_secs(
-                        config.chain.checkpoint_sync_url_timeout,
-                    )),
-                );
+                let timeouts = Timeouts::set_all(Duration::from_secs(
+                    config.chain.checkpoint_sync_url_timeout,
+                ));
+                let remote = match &config.http_proxy {
+                    Some(proxy) => {
+                        let client = proxied_client(proxy)
+                            .map_err(|e| format!("Failed to configure proxy: {:?}", e))?;
+                        BeaconNodeHttpClient::from_components(url, client, timeouts)
+                    }
+                    None => BeaconNodeHttpClient::new(url, timeouts),
+                };
                 let slots_per_epoch = TEthSpec::slots_per_epoch();
 
                 let deposit_snapshot = if config.sync_eth1_chain {
@@ -465,6 +481,8 @@ where
                         network_senders: None,
                         network_globals: None,
                         eth1_service: Some(genesis_service.eth1_service.clone()),
+                        db_path: None,
+                        freezer_db_path: None,
                         log: context.log().clone(),
                     });
 
@@ -487,7 +505,7 @@ where
                         .executor
                         .spawn_without_exit(http_api_task, "http-api");
 
-                    Some(listen_addr)
+                    listen_addr
                 } else {
                     None
                 };
@@ -530,7 +548,7 @@ where
     }
 
     /// Starts the networking stack.
-    pub async fn network(mut self, config: &NetworkConfig) -> Result<Self, String> {
+    pub async fn network(mut self, config: &NetworkConfig) -> Result<Self, StartupError> {
         let beacon_chain = self
             .beacon_chain
             .clone()
@@ -557,7 +575,15 @@ where
                 .map(|registry| registry.sub_registry_with_prefix("gossipsub")),
         )
         .await
-        .map_err(|e| format!("Failed to start network: {:?}", e))?;
+        .map_err(|e| {
+            let message = format!("Failed to start network: {:?}", e);
+            match e.kind() {
+                network::error::ErrorKind::Libp2p(
+                    lighthouse_network::error::ErrorKind::Listen(_),
+                ) => StartupError::port_bind(message),
+                _ => message.into(),
+            }
+        })?;
 
         self.network_globals = Some(network_globals);
         self.network_senders = Some(network_senders);
@@ -632,6 +658,40 @@ where
         Ok(self)
     }
 
+    /// Start the service which periodically checks the system clock against an NTP server.
+    pub fn clock_drift_monitor(self, config: clock_drift::Config) -> Result<Self, String> {
+        let context = self
+            .runtime_context
+            .as_ref()
+            .ok_or("clock_drift_monitor requires a runtime_context")?
+            .service_context("clock_drift".into());
+        let log = context.log().clone();
+        context
+            .executor
+            .spawn(clock_drift::monitor(config, log), "clock_drift");
+        Ok(self)
+    }
+
+    /// Start the watchdog, which shuts down the node if a critical service makes no progress for
+    /// `stall_slots` slots.
+    pub fn watchdog(self, stall_slots: u64) -> Result<Self, String> {
+        let context = self
+            .runtime_context
+            .as_ref()
+            .ok_or("watchdog requires a runtime_context")?
+            .service_context("watchdog".into());
+        let seconds_per_slot = self
+            .chain_spec
+            .as_ref()
+            .ok_or("watchdog requires a chain spec")?
+            .seconds_per_slot;
+
+        context
+            .executor
+            .spawn_watchdog(Duration::from_secs(seconds_per_slot * stall_slots));
+        Ok(self)
+    }
+
     /// Immediately starts the service that periodically logs information each slot.
     pub fn notifier(self) -> Result<Self, String> {
         let context = self
@@ -671,8 +731,10 @@ where
     #[allow(clippy::type_complexity)]
     pub fn build(
         mut self,
-    ) -> Result<Client<Witness<TSlotClock, TEth1Backend, TEthSpec, THotStore, TColdStore>>, String>
-    {
+    ) -> Result<
+        Client<Witness<TSlotClock, TEth1Backend, TEthSpec, THotStore, TColdStore>>,
+        StartupError,
+    > {
         let runtime_context = self
             .runtime_context
             .as_ref()
@@ -686,13 +748,22 @@ where
                 network_senders: self.network_senders.clone(),
                 network_globals: self.network_globals.clone(),
                 eth1_service: self.eth1_service.clone(),
+                db_path: self.db_path.clone(),
+                freezer_db_path: self.freezer_db_path.clone(),
                 log: log.clone(),
             });
 
             let exit = runtime_context.executor.exit();
 
-            let (listen_addr, server) = http_api::serve(ctx, exit)
-                .map_err(|e| format!("Unable to start HTTP API server: {:?}", e))?;
+            let (listen_addr, server) = http_api::serve(ctx, exit).map_err(|e| {
+                let message = format!("Unable to start HTTP API server: {:?}", e);
+                match e {
+                    http_api::Error::Warp(_) | http_api::Error::UnixSocket(_) => {
+                        StartupError::port_bind(message)
+                    }
+                    http_api::Error::Other(_) => message.into(),
+                }
+            })?;
 
             let http_log = runtime_context.log().clone();
             let http_api_task = async move {
@@ -705,26 +776,53 @@ where
                 .executor
                 .spawn_without_exit(http_api_task, "http-api");
 
-            Some(listen_addr)
+            listen_addr
         } else {
             info!(log, "HTTP server is disabled");
             None
         };
 
-        let http_metrics_listen_addr = if self.http_metrics_config.enabled {
-            let ctx = Arc::new(http_metrics::Context {
-                config: self.http_metrics_config.clone(),
-                chain: self.beacon_chain.clone(),
-                db_path: self.db_path.clone(),
-                freezer_db_path: self.freezer_db_path.clone(),
-                gossipsub_registry: self.gossipsub_registry.take().map(std::sync::Mutex::new),
-                log: log.clone(),
-            });
+        let http_metrics_ctx = Arc::new(http_metrics::Context {
+            config: self.http_metrics_config.clone(),
+            chain: self.beacon_chain.clone(),
+            gossipsub_registry: self.gossipsub_registry.take().map(std::sync::Mutex::new),
+            log: log.clone(),
+        });
+
+        // Sample disk usage periodically, rather than when metrics are scraped, so that it's
+        // available to the Pushgateway and kept up to date regardless of the scrape interval.
+        if let (Some(db_path), Some(freezer_db_path)) =
+            (self.db_path.clone(), self.freezer_db_path.clone())
+        {
+            let disk_metrics_task = async move {
+                let mut interval = tokio::time::interval(DISK_METRICS_UPDATE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    store::scrape_for_metrics(&db_path, &freezer_db_path);
+                }
+            };
+            runtime_context
+                .executor
+                .spawn(disk_metrics_task, "disk-metrics");
+        }
+
+        if let Some(push_metrics) = http_metrics::push(http_metrics_ctx.clone()) {
+            runtime_context
+                .executor
+                .spawn(push_metrics, "metrics-pushgateway");
+        }
 
+        let http_metrics_listen_addr = if self.http_metrics_config.enabled {
             let exit = runtime_context.executor.exit();
 
-            let (listen_addr, server) = http_metrics::serve(ctx, exit)
-                .map_err(|e| format!("Unable to start HTTP metrics server: {:?}", e))?;
+            let (listen_addr, server) =
+                http_metrics::serve(http_metrics_ctx, exit).map_err(|e| {
+                    let message = format!("Unable to start HTTP metrics server: {:?}", e);
+                    match e {
+                        http_metrics::Error::Warp(_) => StartupError::port_bind(message),
+                        http_metrics::Error::Other(_) => message.into(),
+                    }
+                })?;
 
             runtime_context
                 .executor
@@ -869,7 +967,15 @@ where
 }
 
 impl<TSlotClock, TEth1Backend, TEthSpec>
-    ClientBuilder<Witness<TSlotClock, TEth1Backend, TEthSpec, LevelDB<TEthSpec>, LevelDB<TEthSpec>>>
+    ClientBuilder<
+        Witness<
+            TSlotClock,
+            TEth1Backend,
+            TEthSpec,
+            BeaconNodeBackend<TEthSpec>,
+            BeaconNodeBackend<TEthSpec>,
+        >,
+    >
 where
     TSlotClock: SlotClock + 'static,
     TEth1Backend: Eth1ChainBackend<TEthSpec> + 'static,
@@ -882,7 +988,7 @@ where
         cold_path: &Path,
         config: StoreConfig,
         log: Logger,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, StartupError> {
         let context = self
             .runtime_context
             .as_ref()
@@ -922,10 +1028,174 @@ where
             spec,
             context.log().clone(),
         )
-        .map_err(|e| format!("Unable to open database: {:?}", e))?;
+        .map_err(|e| {
+            let message = format!("Unable to open database: {:?}", e);
+            match e {
+                StoreError::HotColdDBError(
+                    HotColdDBError::UnsupportedSchemaVersion { .. }
+                    | HotColdDBError::UnsupportedFreezerSchemaVersion { .. }
+                    | HotColdDBError::UnsupportedFreezerCompression { .. },
+                )
+                | StoreError::SchemaMigrationError(_) => {
+                    StartupError::new(FailureKind::IncompatibleSchema, message)
+                }
+                _ => StartupError::database(message),
+            }
+        })?;
         self.store = Some(store);
         Ok(self)
     }
+
+    /// Checks the integrity of the hot database opened by `disk_store`, and re-anchors it if it's
+    /// corrupt.
+    ///
+    /// Only the split state, the head and the hot states are checked, as the freezer database is
+    /// checked in full by `lighthouse db verify`. Dangling hot states are deleted in place. If any
+    /// other problem is found then the hot database is moved aside and `client_genesis` is
+    /// replaced so that the node checkpoint syncs from the latest usable restore point in the
+    /// freezer database, or from the checkpoint sync URL if there is no such restore point. If the
+    /// database can't be checked then startup is aborted, as it may not be corrupt.
+    pub fn recover_disk_store(
+        mut self,
+        client_genesis: &mut ClientGenesis,
+        log: Logger,
+    ) -> Result<Self, StartupError> {
+        let store = self
+            .store
+            .take()
+            .ok_or("recover_disk_store requires a store")?;
+        let spec = self
+            .chain_spec
+            .clone()
+            .ok_or("recover_disk_store requires a chain spec")?;
+        let hot_path = self
+            .db_path
+            .clone()
+            .ok_or("recover_disk_store requires a db path")?;
+        let cold_path = self
+            .freezer_db_path
+            .clone()
+            .ok_or("recover_disk_store requires a freezer db path")?;
+
+        // There is nothing to check in a database which doesn't contain a chain yet.
+        let chain_builder =
+            BeaconChainBuilder::<Witness<TSlotClock, TEth1Backend, TEthSpec, _, _>>::new(
+                self.eth_spec_instance.clone(),
+            )
+            .logger(log.clone())
+            .custom_spec(spec.clone())
+            .store(store.clone());
+        if !chain_builder.store_contains_beacon_chain().unwrap_or(true) {
+            self.store = Some(store);
+            return Ok(self);
+        }
+
+        let report = store
+            .verify_hot_integrity(true)
+            .map_err(|e| StartupError::database(format!("Unable to verify database: {:?}", e)))?;
+        let head_loadable = chain_builder
+            .head_in_store_is_loadable()
+            .map_err(|e| StartupError::database(format!("Unable to verify database: {}", e)))?;
+        drop(chain_builder);
+        if report.is_ok() && head_loadable {
+            self.store = Some(store);
+            return Ok(self);
+        }
+        warn!(
+            log,
+            "Database is corrupt, re-anchoring";
+            "split_state_missing" => report.split_state_missing,
+            "head_loadable" => head_loadable,
+        );
+
+        let genesis_state_bytes = match client_genesis {
+            ClientGenesis::SszBytes {
+                genesis_state_bytes,
+            }
+            | ClientGenesis::WeakSubjSszBytes {
+                genesis_state_bytes,
+                ..
+            }
+            | ClientGenesis::CheckpointSyncUrl {
+                genesis_state_bytes,
+                ..
+            } => Some(genesis_state_bytes.clone()),
+            _ => None,
+        }
+        .ok_or("Unable to recover the database as the genesis state of this network is unknown")?;
+
+        let anchor = match store.load_recovery_anchor() {
+            Ok(Some((state, block))) => {
+                match check_weak_subjectivity_period(&state, state.genesis_time(), &spec) {
+                    Ok(()) => Some((state, block)),
+                    Err(e) => {
+                        warn!(log, "Restore point unsuitable for recovery"; "error" => e);
+                        None
+                    }
+                }
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!(log, "Unable to load restore point"; "error" => ?e);
+                None
+            }
+        };
+        let recovered_genesis = match (anchor, &*client_genesis) {
+            (Some((anchor_state, anchor_block)), _) => {
+                info!(
+                    log,
+                    "Re-anchoring from freezer restore point";
+                    "slot" => anchor_state.slot(),
+                );
+                ClientGenesis::WeakSubjSszBytes {
+                    genesis_state_bytes,
+                    anchor_state_bytes: anchor_state.as_ssz_bytes(),
+                    anchor_block_bytes: anchor_block.as_ssz_bytes(),
+                }
+            }
+            (None, ClientGenesis::CheckpointSyncUrl { url, .. }) => {
+                info!(
+                    log,
+                    "Re-anchoring from checkpoint sync URL";
+                    "remote_url" => %url,
+                );
+                ClientGenesis::CheckpointSyncUrl {
+                    genesis_state_bytes,
+                    url: url.clone(),
+                }
+            }
+            (None, _) => {
+                return Err(
+                    "Unable to recover the database as no restore point is usable, provide \
+                     --checkpoint-sync-url or use --purge-db to resync"
+                        .into(),
+                )
+            }
+        };
+
+        // The database must be closed before it's moved. It's kept rather than deleted so that it
+        // can be inspected, or restored if the recovery was a mistake.
+        let store_config = store.get_config().clone();
+        drop(store);
+        let corrupt_path = hot_path.with_extension("corrupt");
+        if corrupt_path.exists() {
+            return Err(StartupError::database(format!(
+                "Unable to move the corrupt hot database aside as {:?} already exists, remove it \
+                 and restart",
+                corrupt_path
+            )));
+        }
+        std::fs::rename(&hot_path, &corrupt_path)
+            .map_err(|e| format!("Unable to move hot database: {:?}", e))?;
+        warn!(
+            log,
+            "Moved corrupt hot database aside";
+            "path" => ?corrupt_path,
+        );
+        *client_genesis = recovered_genesis;
+
+        self.disk_store(&hot_path, &cold_path, store_config, log)
+    }
 }
 
 impl<TSlotClock, TEthSpec, THotStore, TColdStore>
@@ -1063,3 +1333,35 @@ where
         Ok(self)
     }
 }
+
+/// Returns an error if the current epoch is beyond the weak subjectivity period of
+/// `checkpoint_state`, in which case it is unsafe to sync from it.
+fn check_weak_subjectivity_period<E: EthSpec>(
+    checkpoint_state: &BeaconState<E>,
+    genesis_time: u64,
+    spec: &ChainSpec,
+) -> Result<(), String> {
+    let slot_clock = SystemTimeSlotClock::new(
+        spec.genesis_slot,
+        Duration::from_secs(genesis_time),
+        Duration::from_secs(spec.seconds_per_slot),
+    );
+    let current_epoch = match slot_clock.now() {
+        Some(slot) => slot.epoch(E::slots_per_epoch()),
+        // The chain hasn't started yet.
+        None => return Ok(()),
+    };
+
+    let checkpoint_epoch = checkpoint_state.current_epoch();
+    let ws_period = compute_weak_subjectivity_period(checkpoint_state, spec)
+        .map_err(|e| format!("Unable to compute weak subjectivity period: {:?}", e))?;
+    if current_epoch > checkpoint_epoch + ws_period {
+        return Err(format!(
+            "Checkpoint state at epoch {} is outside its weak subjectivity period of {} epochs \
+             (current epoch {}) and is unsafe to sync from. Please provide a more recent \
+             checkpoint state and block.",
+            checkpoint_epoch, ws_period, current_epoch
+        ));
+    }
+    Ok(())
+}
@@ -5,7 +5,7 @@ use network::NetworkConfig;
 use sensitive_url::SensitiveUrl;
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use types::{Graffiti, PublicKeyBytes};
 /// Default directory name for the freezer database under the top-level data dir.
 const DEFAULT_FREEZER_DB_DIR: &str = "freezer_db";
@@ -69,6 +69,10 @@ pub struct Config {
     /// The `genesis` field is not serialized or deserialized by `serde` to ensure it is defined
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
     pub genesis: ClientGenesis,
+    /// If true, a corrupt hot database is moved aside to `<hot>.corrupt` at startup and the node
+    /// re-anchors from the latest restore point in the freezer database, or from the checkpoint
+    /// sync URL. The freezer database is kept.
+    pub auto_recover: bool,
     pub store: store::StoreConfig,
     pub network: network::NetworkConfig,
     pub chain: beacon_chain::ChainConfig,
@@ -77,9 +81,14 @@ pub struct Config {
     pub http_api: http_api::Config,
     pub http_metrics: http_metrics::Config,
     pub monitoring_api: Option<monitoring_api::Config>,
+    pub clock_drift: Option<clock_drift::Config>,
+    /// Shut down if a critical service makes no progress for this many slots.
+    pub watchdog_stall_slots: Option<u64>,
     pub slasher: Option<slasher::Config>,
     pub logger_config: LoggerConfig,
     pub always_prefer_builder_payload: bool,
+    /// A HTTP or SOCKS5 proxy to download the checkpoint sync state through.
+    pub http_proxy: Option<SensitiveUrl>,
 }
 
 impl Default for Config {
@@ -90,6 +99,7 @@ impl Default for Config {
             freezer_db_path: None,
             log_file: PathBuf::from(""),
             genesis: <_>::default(),
+            auto_recover: false,
             store: <_>::default(),
             network: NetworkConfig::default(),
             chain: <_>::default(),
@@ -101,12 +111,15 @@ impl Default for Config {
             http_api: <_>::default(),
             http_metrics: <_>::default(),
             monitoring_api: None,
+            clock_drift: Some(<_>::default()),
+            watchdog_stall_slots: None,
             slasher: None,
             validator_monitor_auto: false,
             validator_monitor_pubkeys: vec![],
             validator_monitor_individual_tracking_threshold: DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD,
             logger_config: LoggerConfig::default(),
             always_prefer_builder_payload: false,
+            http_proxy: None,
         }
     }
 }
@@ -153,6 +166,24 @@ impl Config {
         ensure_dir_exists(self.get_freezer_db_path())
     }
 
+    /// Moves an existing freezer database from the default location to the user-supplied
+    /// `freezer_db_path`, if no database exists there yet.
+    ///
+    /// Returns the previous location of the database if it was moved.
+    pub fn migrate_freezer_db(&self) -> Result<Option<PathBuf>, String> {
+        let new_path = match &self.freezer_db_path {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let old_path = self.default_freezer_db_path();
+        if !old_path.is_dir() || *new_path == old_path || !is_empty_dir(new_path)? {
+            return Ok(None);
+        }
+
+        move_dir(&old_path, new_path)?;
+        Ok(Some(old_path))
+    }
+
     /// Returns the "modern" path to the data_dir.
     ///
     /// See `Self::get_data_dir` documentation for more info.
@@ -208,6 +239,61 @@ fn ensure_dir_exists(path: PathBuf) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+/// Returns `true` if `path` doesn't exist or is an empty directory.
+fn is_empty_dir(path: &Path) -> Result<bool, String> {
+    if !path.exists() {
+        return Ok(true);
+    }
+    fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .map_err(|e| format!("Unable to read {}: {}", path.display(), e))
+}
+
+/// Moves the directory `from` to `to`, which must not exist or be empty.
+///
+/// If the directories are on different filesystems the contents are copied to a temporary
+/// directory next to `to` first, so that an interrupted copy never leaves a partial database at
+/// `to`.
+fn move_dir(from: &Path, to: &Path) -> Result<(), String> {
+    if to.exists() {
+        fs::remove_dir(to).map_err(|e| format!("Unable to remove {}: {}", to.display(), e))?;
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Unable to create {}: {}", parent.display(), e))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    let tmp = to.with_extension("partial");
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp)
+            .map_err(|e| format!("Unable to remove {}: {}", tmp.display(), e))?;
+    }
+    copy_dir(from, &tmp)?;
+    fs::rename(&tmp, to).map_err(|e| format!("Unable to rename {}: {}", tmp.display(), e))?;
+    fs::remove_dir_all(from).map_err(|e| format!("Unable to remove {}: {}", from.display(), e))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Unable to create {}: {}", to.display(), e))?;
+    let entries =
+        fs::read_dir(from).map_err(|e| format!("Unable to read {}: {}", from.display(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Unable to read {}: {}", from.display(), e))?;
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)
+                .map_err(|e| format!("Unable to copy {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,4 +305,34 @@ mod tests {
             serde_yaml::to_string(&config).expect("should serde encode default config");
         serde_yaml::from_str::<Config>(&serialized).expect("should serde decode default config");
     }
+
+    #[test]
+    fn migrate_freezer_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.set_data_dir(dir.path().join("beacon"));
+
+        // Nothing to move without a user-supplied path.
+        let old_path = config.create_freezer_db_path().unwrap();
+        fs::write(old_path.join("CURRENT"), "MANIFEST-000001").unwrap();
+        assert_eq!(config.migrate_freezer_db().unwrap(), None);
+
+        let new_path = dir.path().join("hdd").join("freezer_db");
+        config.freezer_db_path = Some(new_path.clone());
+        assert_eq!(config.migrate_freezer_db().unwrap(), Some(old_path.clone()));
+        assert!(!old_path.exists());
+        assert_eq!(
+            fs::read_to_string(new_path.join("CURRENT")).unwrap(),
+            "MANIFEST-000001"
+        );
+
+        // An existing database at the new path is never replaced.
+        fs::create_dir_all(&old_path).unwrap();
+        fs::write(old_path.join("CURRENT"), "MANIFEST-000002").unwrap();
+        assert_eq!(config.migrate_freezer_db().unwrap(), None);
+        assert_eq!(
+            fs::read_to_string(new_path.join("CURRENT")).unwrap(),
+            "MANIFEST-000001"
+        );
+    }
 }
@@ -36,7 +36,8 @@ impl<T: BeaconChainTypes> Client<T> {
         self.beacon_chain.clone()
     }
 
-    /// Returns the address of the client's standard eth2.0 API server, if it was started.
+    /// Returns the address of the client's standard eth2.0 API server, if it was started and is
+    /// listening on TCP rather than a Unix socket.
     pub fn http_api_listen_addr(&self) -> Option<SocketAddr> {
         self.http_api_listen_addr
     }
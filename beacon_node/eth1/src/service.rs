@@ -301,6 +301,9 @@ pub struct Config {
     /// If set to true, the eth1 caches are wiped clean when the eth1 service starts.
     pub purge_cache: bool,
     pub execution_timeout_multiplier: u32,
+    /// A HTTP or SOCKS5 proxy to send requests to an unauthenticated Eth1 node through, unless it is
+    /// on the local machine or a private network.
+    pub http_proxy: Option<SensitiveUrl>,
 }
 
 impl Config {
@@ -359,12 +362,15 @@ impl Default for Config {
             max_blocks_per_update: Some(8_192),
             purge_cache: false,
             execution_timeout_multiplier: 1,
+            http_proxy: None,
         }
     }
 }
 
 pub fn endpoint_from_config(config: &Config) -> Result<HttpJsonRpc, String> {
     match config.endpoint.clone() {
+        // An authenticated endpoint is an execution engine, which is never reached through the
+        // proxy.
         Eth1Endpoint::Auth {
             endpoint,
             jwt_path,
@@ -377,8 +383,14 @@ pub fn endpoint_from_config(config: &Config) -> Result<HttpJsonRpc, String> {
                 .map_err(|e| format!("Failed to create eth1 json rpc client: {:?}", e))
         }
         Eth1Endpoint::NoAuth(endpoint) => {
-            HttpJsonRpc::new(endpoint, Some(config.execution_timeout_multiplier))
-                .map_err(|e| format!("Failed to create eth1 json rpc client: {:?}", e))
+            let endpoint = HttpJsonRpc::new(endpoint, Some(config.execution_timeout_multiplier))
+                .map_err(|e| format!("Failed to create eth1 json rpc client: {:?}", e))?;
+            match &config.http_proxy {
+                Some(proxy) => endpoint
+                    .with_proxy(proxy)
+                    .map_err(|e| format!("Failed to configure eth1 proxy: {:?}", e)),
+                None => Ok(endpoint),
+            }
         }
     }
 }
@@ -4,7 +4,7 @@ use super::*;
 use crate::auth::Auth;
 use crate::json_structures::*;
 use reqwest::header::CONTENT_TYPE;
-use sensitive_url::SensitiveUrl;
+use sensitive_url::{SensitiveUrl, NO_PROXY_HOSTS};
 use serde::de::DeserializeOwned;
 use serde_json::json;
 use std::collections::HashSet;
@@ -625,6 +625,12 @@ impl HttpJsonRpc {
         })
     }
 
+    /// Send requests through the HTTP proxy at `proxy`, rather than directly to the endpoint.
+    pub fn with_proxy(mut self, proxy: &SensitiveUrl) -> Result<Self, Error> {
+        self.client = proxied_client(proxy)?;
+        Ok(self)
+    }
+
     pub async fn rpc_request<D: DeserializeOwned>(
         &self,
         method: &str,
@@ -668,6 +674,14 @@ impl HttpJsonRpc {
     }
 }
 
+/// Returns a client which sends requests through the HTTP or SOCKS5 proxy at `proxy`, except for
+/// requests to the hosts in `NO_PROXY_HOSTS`.
+pub fn proxied_client(proxy: &SensitiveUrl) -> Result<Client, reqwest::Error> {
+    let proxy = reqwest::Proxy::all(proxy.full.clone())?
+        .no_proxy(reqwest::NoProxy::from_string(NO_PROXY_HOSTS));
+    Client::builder().proxy(proxy).build()
+}
+
 impl std::fmt::Display for HttpJsonRpc {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}, auth={}", self.url, self.auth.is_some())
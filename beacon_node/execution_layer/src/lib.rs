@@ -213,7 +213,7 @@ struct Inner<E: EthSpec> {
     engine: Arc<Engine>,
     builder: Option<BuilderHttpClient>,
     execution_engine_forkchoice_lock: Mutex<()>,
-    suggested_fee_recipient: Option<Address>,
+    suggested_fee_recipient: RwLock<Option<Address>>,
     proposer_preparation_data: Mutex<HashMap<u64, ProposerPreparationDataEntry>>,
     execution_blocks: Mutex<LruCache<ExecutionBlockHash, ExecutionBlock>>,
     proposers: RwLock<HashMap<ProposerKey, Proposer>>,
@@ -341,7 +341,7 @@ impl<T: EthSpec> ExecutionLayer<T> {
             engine: Arc::new(engine),
             builder,
             execution_engine_forkchoice_lock: <_>::default(),
-            suggested_fee_recipient,
+            suggested_fee_recipient: RwLock::new(suggested_fee_recipient),
             proposer_preparation_data: Mutex::new(HashMap::new()),
             proposers: RwLock::new(HashMap::new()),
             execution_blocks: Mutex::new(LruCache::new(EXECUTION_BLOCKS_LRU_CACHE_SIZE)),
@@ -608,6 +608,11 @@ impl<T: EthSpec> ExecutionLayer<T> {
             .contains_key(&proposer_index)
     }
 
+    /// Sets the fee-recipient address used when none has been provided via the API.
+    pub async fn set_suggested_fee_recipient(&self, suggested_fee_recipient: Option<Address>) {
+        *self.inner.suggested_fee_recipient.write().await = suggested_fee_recipient;
+    }
+
     /// Returns the fee-recipient address that should be used to build a block
     pub async fn get_suggested_fee_recipient(&self, proposer_index: u64) -> Address {
         if let Some(preparation_data_entry) =
@@ -615,7 +620,7 @@ impl<T: EthSpec> ExecutionLayer<T> {
         {
             // The values provided via the API have first priority.
             preparation_data_entry.preparation_data.fee_recipient
-        } else if let Some(address) = self.inner.suggested_fee_recipient {
+        } else if let Some(address) = *self.inner.suggested_fee_recipient.read().await {
             // If there has been no fee recipient provided via the API, but the BN has been provided
             // with a global default address, use that.
             address
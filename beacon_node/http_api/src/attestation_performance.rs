@@ -1,16 +1,19 @@
 use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use eth2::lighthouse::{
-    AttestationPerformance, AttestationPerformanceQuery, AttestationPerformanceStatistics,
+    AttestationPerformance, AttestationPerformanceQuery, AttestationPerformanceRequest,
+    AttestationPerformanceStatistics, AttestationPerformanceSummary, AttestationPerformanceTotals,
 };
 use state_processing::{
     per_epoch_processing::altair::participation_cache::Error as ParticipationCacheError,
     per_epoch_processing::EpochProcessingSummary, BlockReplayError, BlockReplayer,
 };
 use std::sync::Arc;
-use types::{BeaconState, BeaconStateError, EthSpec, Hash256};
+use types::{BeaconState, BeaconStateError, Epoch, EthSpec, Hash256};
 use warp_utils::reject::{beacon_chain_error, custom_bad_request, custom_server_error};
 
 const MAX_REQUEST_RANGE_EPOCHS: usize = 100;
+/// The maximum number of validators in a `POST` request.
+pub const MAX_ATTESTATION_PERFORMANCE_INDICES: usize = 16_384;
 const BLOCK_ROOT_CHUNK_SIZE: usize = 100;
 
 #[derive(Debug)]
@@ -43,25 +46,94 @@ pub fn get_attestation_performance<T: BeaconChainTypes>(
     target: String,
     query: AttestationPerformanceQuery,
     chain: Arc<BeaconChain<T>>,
+) -> Result<Vec<AttestationPerformance>, warp::Rejection> {
+    // Either use the global validator set, or the specified index.
+    //
+    // Does no further validation of the indices, so in the event an index has not yet been
+    // activated or does not yet exist (according to the head state), it will return all fields as
+    // `false`.
+    let index_range = if target.to_lowercase() == "global" {
+        chain
+            .with_head(|head| Ok((0..head.beacon_state.validators().len() as u64).collect()))
+            .map_err(beacon_chain_error)?
+    } else {
+        vec![target.parse::<u64>().map_err(|_| {
+            custom_bad_request(format!(
+                "Invalid validator index: {:?}",
+                target.to_lowercase()
+            ))
+        })?]
+    };
+
+    compute_attestation_performance(index_range, query.start_epoch, query.end_epoch, chain)
+}
+
+/// Compute the attestation performance of many validators at once, with their totals.
+///
+/// Duplicate indices are ignored, and the validators are returned in order of index.
+pub fn post_attestation_performance<T: BeaconChainTypes>(
+    mut request: AttestationPerformanceRequest,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<AttestationPerformanceSummary, warp::Rejection> {
+    request.indices.sort_unstable();
+    request.indices.dedup();
+    if request.indices.len() > MAX_ATTESTATION_PERFORMANCE_INDICES {
+        return Err(custom_bad_request(format!(
+            "Too many validators, the maximum is {}",
+            MAX_ATTESTATION_PERFORMANCE_INDICES
+        )));
+    }
+
+    let validator_count = chain
+        .with_head(|head| Ok(head.beacon_state.validators().len() as u64))
+        .map_err(beacon_chain_error)?;
+    if let Some(index) = request
+        .indices
+        .iter()
+        .find(|index| **index >= validator_count)
+    {
+        return Err(custom_bad_request(format!(
+            "Unknown validator index: {}",
+            index
+        )));
+    }
+
+    let validators = compute_attestation_performance(
+        request.indices,
+        request.start_epoch,
+        request.end_epoch,
+        chain,
+    )?;
+    Ok(AttestationPerformanceSummary {
+        totals: AttestationPerformanceTotals::from_performances(&validators),
+        validators,
+    })
+}
+
+fn compute_attestation_performance<T: BeaconChainTypes>(
+    index_range: Vec<u64>,
+    query_start_epoch: Epoch,
+    query_end_epoch: Epoch,
+    chain: Arc<BeaconChain<T>>,
 ) -> Result<Vec<AttestationPerformance>, warp::Rejection> {
     let spec = &chain.spec;
     // We increment by 2 here so that when we build the state from the `prior_slot` it is
     // still 1 epoch ahead of the first epoch we want to analyse.
     // This ensures the `.is_previous_epoch_X` functions on `EpochProcessingSummary` return results
     // for the correct epoch.
-    let start_epoch = query.start_epoch + 2;
+    let start_epoch = query_start_epoch + 2;
     let start_slot = start_epoch.start_slot(T::EthSpec::slots_per_epoch());
     let prior_slot = start_slot - 1;
 
-    let end_epoch = query.end_epoch + 2;
+    let end_epoch = query_end_epoch + 2;
     let end_slot = end_epoch.end_slot(T::EthSpec::slots_per_epoch());
 
     // Ensure end_epoch is smaller than the current epoch - 1.
     let current_epoch = chain.epoch().map_err(beacon_chain_error)?;
-    if query.end_epoch >= current_epoch - 1 {
+    if query_end_epoch >= current_epoch - 1 {
         return Err(custom_bad_request(format!(
             "end_epoch must be less than the current epoch - 1. current: {}, end: {}",
-            current_epoch, query.end_epoch
+            current_epoch, query_end_epoch
         )));
     }
 
@@ -69,7 +141,7 @@ pub fn get_attestation_performance<T: BeaconChainTypes>(
     if start_epoch > end_epoch {
         return Err(custom_bad_request(format!(
             "start_epoch must not be larger than end_epoch. start: {}, end: {}",
-            query.start_epoch, query.end_epoch
+            query_start_epoch, query_end_epoch
         )));
     }
 
@@ -78,28 +150,10 @@ pub fn get_attestation_performance<T: BeaconChainTypes>(
     if (end_epoch - start_epoch).as_usize() > MAX_REQUEST_RANGE_EPOCHS {
         return Err(custom_bad_request(format!(
             "end_epoch must not exceed start_epoch by more than {} epochs. start: {}, end: {}",
-            MAX_REQUEST_RANGE_EPOCHS, query.start_epoch, query.end_epoch
+            MAX_REQUEST_RANGE_EPOCHS, query_start_epoch, query_end_epoch
         )));
     }
 
-    // Either use the global validator set, or the specified index.
-    //
-    // Does no further validation of the indices, so in the event an index has not yet been
-    // activated or does not yet exist (according to the head state), it will return all fields as
-    // `false`.
-    let index_range = if target.to_lowercase() == "global" {
-        chain
-            .with_head(|head| Ok((0..head.beacon_state.validators().len() as u64).collect()))
-            .map_err(beacon_chain_error)?
-    } else {
-        vec![target.parse::<u64>().map_err(|_| {
-            custom_bad_request(format!(
-                "Invalid validator index: {:?}",
-                target.to_lowercase()
-            ))
-        })?]
-    };
-
     // Load block roots.
     let mut block_roots: Vec<Hash256> = chain
         .forwards_iter_block_roots_until(start_slot, end_slot)
@@ -0,0 +1,40 @@
+//! The balances of recently requested states, so that balances can be served without loading the
+//! state again.
+//!
+//! The head state is always in memory, so only the balances of other states are cached.
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use types::Hash256;
+
+/// The number of states whose balances are cached, which is enough for the finalized and justified
+/// states and a few others, at about 8MB per state on mainnet.
+pub const BALANCES_CACHE_SIZE: usize = 4;
+
+pub struct BalancesCache {
+    balances: Mutex<LruCache<Hash256, Arc<Vec<u64>>>>,
+}
+
+impl BalancesCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            balances: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the balances of the state with `state_root`, if they are cached.
+    pub fn get(&self, state_root: &Hash256) -> Option<Arc<Vec<u64>>> {
+        self.balances.lock().get(state_root).cloned()
+    }
+
+    pub fn insert(&self, state_root: Hash256, balances: Arc<Vec<u64>>) {
+        self.balances.lock().put(state_root, balances);
+    }
+}
+
+impl Default for BalancesCache {
+    fn default() -> Self {
+        Self::new(BALANCES_CACHE_SIZE)
+    }
+}
@@ -1,11 +1,15 @@
 use beacon_chain::store::{metadata::CURRENT_SCHEMA_VERSION, AnchorInfo};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
+use directory::size_of_dir;
 use eth2::lighthouse::DatabaseInfo;
+use std::path::PathBuf;
 use std::sync::Arc;
 use types::SignedBlindedBeaconBlock;
 
 pub fn info<T: BeaconChainTypes>(
     chain: Arc<BeaconChain<T>>,
+    db_path: Option<PathBuf>,
+    freezer_db_path: Option<PathBuf>,
 ) -> Result<DatabaseInfo, warp::Rejection> {
     let store = &chain.store;
     let split = store.get_split_info();
@@ -17,6 +21,8 @@ pub fn info<T: BeaconChainTypes>(
         config,
         split,
         anchor,
+        hot_db_size: db_path.map(|path| size_of_dir(&path)),
+        freezer_db_size: freezer_db_path.map(|path| size_of_dir(&path)),
     })
 }
 
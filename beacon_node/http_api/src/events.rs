@@ -0,0 +1,176 @@
+//! Subscriptions to the events of the beacon chain, which are served as server-sent events by the
+//! standard `events` endpoint, and over a WebSocket by `lighthouse/events/ws`.
+
+use beacon_chain::events::{AttestationWithAttesters, ServerSentEventHandler};
+use eth2::types::{EventKind, EventTopic};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use slog::{debug, Logger};
+use std::collections::HashSet;
+use tokio::sync::broadcast::Receiver;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use types::EthSpec;
+use warp::ws::{Message, WebSocket};
+
+pub fn subscribe<E: EthSpec>(
+    event_handler: &ServerSentEventHandler<E>,
+    topic: EventTopic,
+) -> Receiver<EventKind<E>> {
+    match topic {
+        EventTopic::Head => event_handler.subscribe_head(),
+        EventTopic::Block => event_handler.subscribe_block(),
+        EventTopic::Attestation => event_handler.subscribe_attestation(),
+        EventTopic::VoluntaryExit => event_handler.subscribe_exit(),
+        EventTopic::FinalizedCheckpoint => event_handler.subscribe_finalized(),
+        EventTopic::ChainReorg => event_handler.subscribe_reorgs(),
+        EventTopic::ContributionAndProof => event_handler.subscribe_contributions(),
+        EventTopic::PayloadAttributes => event_handler.subscribe_payload_attributes(),
+        EventTopic::LateHead => event_handler.subscribe_late_head(),
+        EventTopic::BlockReward => event_handler.subscribe_block_reward(),
+    }
+}
+
+/// The events which a WebSocket client is subscribed to.
+pub struct WebSocketSubscription<E: EthSpec> {
+    receivers: Vec<Receiver<EventKind<E>>>,
+    /// Attestations with their attesters, if the client filters attestations by validator.
+    attestations: Option<Receiver<AttestationWithAttesters<E>>>,
+    validator_indices: Option<HashSet<u64>>,
+}
+
+impl<E: EthSpec> WebSocketSubscription<E> {
+    /// Subscribe to `topics`, dropping the events which concern validators other than those with
+    /// `validator_indices` if it is set.
+    pub fn new(
+        event_handler: &ServerSentEventHandler<E>,
+        topics: Vec<EventTopic>,
+        validator_indices: Option<HashSet<u64>>,
+    ) -> Self {
+        let mut receivers = vec![];
+        let mut attestations = None;
+        for topic in topics {
+            if topic == EventTopic::Attestation && validator_indices.is_some() {
+                attestations = Some(event_handler.subscribe_attestation_with_attesters());
+            } else {
+                receivers.push(subscribe(event_handler, topic));
+            }
+        }
+        Self {
+            receivers,
+            attestations,
+            validator_indices,
+        }
+    }
+}
+
+/// An event sent over a WebSocket, with the same topic name and data as a server-sent event.
+#[derive(Serialize)]
+#[serde(bound = "E: EthSpec")]
+struct WebSocketEvent<'a, E: EthSpec> {
+    event: &'a str,
+    data: &'a EventKind<E>,
+}
+
+/// Send the events of `subscription` to the client on `socket`, until either side closes it.
+pub async fn serve_websocket<E: EthSpec>(
+    socket: WebSocket,
+    subscription: WebSocketSubscription<E>,
+    log: Logger,
+) {
+    let (mut sender, mut incoming) = socket.split();
+    let WebSocketSubscription {
+        receivers,
+        attestations,
+        validator_indices,
+    } = subscription;
+    let mut events = futures::stream::select_all(
+        receivers
+            .into_iter()
+            .map(|receiver| {
+                BroadcastStream::new(receiver)
+                    .map(|result| result.map(|event| (event, None)))
+                    .boxed()
+            })
+            .chain(attestations.map(|receiver| {
+                BroadcastStream::new(receiver)
+                    .map(|result| {
+                        result.map(|attestation| {
+                            (attestation.event, Some(attestation.attesting_indices))
+                        })
+                    })
+                    .boxed()
+            })),
+    );
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let (event, attesting_indices) = match event {
+                    Some(Ok(event)) => event,
+                    Some(Err(BroadcastStreamRecvError::Lagged(missed))) => {
+                        debug!(log, "WebSocket client missed events"; "count" => missed);
+                        continue;
+                    }
+                    None => break,
+                };
+                if let Some(validator_indices) = &validator_indices {
+                    if !concerns_validators(&event, attesting_indices.as_deref(), validator_indices) {
+                        continue;
+                    }
+                }
+
+                let message = match serde_json::to_string(&WebSocketEvent {
+                    event: event.topic_name(),
+                    data: &event,
+                }) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        debug!(log, "Unable to serialize event"; "error" => ?e);
+                        continue;
+                    }
+                };
+                if sender.send(Message::text(message)).await.is_err() {
+                    break;
+                }
+            }
+            message = incoming.next() => match message {
+                // Pings are answered by the WebSocket implementation, and other messages are
+                // ignored.
+                Some(Ok(message)) if !message.is_close() => (),
+                _ => break,
+            },
+        }
+    }
+
+    let _ = sender.close().await;
+}
+
+/// Returns `true` if `event` concerns any of the validators with `validator_indices`.
+///
+/// Attestations concern the validators in `attesting_indices`, and events which concern the chain
+/// as a whole, such as new heads, concern every validator.
+fn concerns_validators<E: EthSpec>(
+    event: &EventKind<E>,
+    attesting_indices: Option<&Vec<u64>>,
+    validator_indices: &HashSet<u64>,
+) -> bool {
+    let validator_index = match event {
+        EventKind::Attestation(_) => {
+            return attesting_indices.map_or(false, |attesting_indices| {
+                attesting_indices
+                    .iter()
+                    .any(|index| validator_indices.contains(index))
+            })
+        }
+        EventKind::VoluntaryExit(exit) => exit.message.validator_index,
+        EventKind::ContributionAndProof(contribution) => contribution.message.aggregator_index,
+        EventKind::LateHead(late_head) => late_head.proposer_index,
+        EventKind::BlockReward(block_reward) => block_reward.meta.proposer_index,
+        EventKind::PayloadAttributes(attributes) => attributes.data.proposer_index,
+        EventKind::Head(_)
+        | EventKind::Block(_)
+        | EventKind::FinalizedCheckpoint(_)
+        | EventKind::ChainReorg(_) => return true,
+    };
+    validator_indices.contains(&validator_index)
+}
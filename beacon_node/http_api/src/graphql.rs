@@ -0,0 +1,306 @@
+//! A GraphQL schema over blocks, validators and attestations, served at `POST lighthouse/graphql`
+//! when the beacon node is started with `--http-graphql`.
+//!
+//! Clients select only the fields they need, rather than fetching whole blocks and states through
+//! the REST API. Blocks and states are identified as they are in the REST API, and integers are
+//! returned as strings, as they are in its JSON responses.
+
+use crate::{validators, BlockId, StateId};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error, Object, Request, Response, Result, Schema,
+    SimpleObject,
+};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::types::{PageQuery, ValidatorData, ValidatorId, ValidatorStatus};
+use parking_lot::Mutex;
+use ssz::Encode;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use types::{EthSpec, ExecPayload, SignedBlindedBeaconBlock};
+use warp_utils::reject::{CustomBadRequest, CustomNotFound};
+
+/// The maximum nesting of the fields of a query.
+const MAX_DEPTH: usize = 8;
+/// The maximum complexity of a query, which is the number of fields it selects plus the cost of
+/// the blocks and states it loads.
+const MAX_COMPLEXITY: usize = 256;
+/// The complexity of a field which loads a block.
+const BLOCK_COMPLEXITY: usize = 8;
+/// The complexity of a field which loads a state, which may have to be replayed from the
+/// database.
+const STATE_COMPLEXITY: usize = 32;
+/// The maximum number of distinct states which a query can load.
+const MAX_STATES: usize = 2;
+
+pub type GraphQLSchema<T> = Schema<QueryRoot<T>, EmptyMutation, EmptySubscription>;
+
+/// Builds the schema, which is shared by all requests.
+pub fn schema<T: BeaconChainTypes>(chain: Arc<BeaconChain<T>>) -> GraphQLSchema<T> {
+    Schema::build(QueryRoot { chain }, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// Executes a GraphQL `request`.
+///
+/// Blocks and states are loaded on blocking threads, so this can be awaited from an async task.
+pub async fn execute<T: BeaconChainTypes>(schema: &GraphQLSchema<T>, request: Request) -> Response {
+    schema.execute(request.data(LoadedStates::default())).await
+}
+
+/// The states loaded by a query, which are limited to `MAX_STATES`.
+#[derive(Default)]
+struct LoadedStates(Mutex<HashSet<String>>);
+
+impl LoadedStates {
+    /// Records that `state_id` is loaded, or returns an error if it would exceed `MAX_STATES`.
+    fn load(&self, state_id: &StateId) -> Result<()> {
+        let mut states = self.0.lock();
+        if states.insert(state_id.to_string()) && states.len() > MAX_STATES {
+            return Err(Error::new(format!(
+                "a query can load at most {} distinct states",
+                MAX_STATES
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub struct QueryRoot<T: BeaconChainTypes> {
+    chain: Arc<BeaconChain<T>>,
+}
+
+#[Object(name = "Query")]
+impl<T: BeaconChainTypes> QueryRoot<T> {
+    /// The block identified by `id`, which is `head`, `genesis`, `finalized`, `justified`, a slot
+    /// or a block root. Null if there is no such block.
+    #[graphql(complexity = "BLOCK_COMPLEXITY + child_complexity")]
+    async fn block(&self, #[graphql(default = "head")] id: String) -> Result<Option<Block>> {
+        let block_id = BlockId::from_str(&id).map_err(Error::new)?;
+        let chain = self.chain.clone();
+        spawn_blocking(move || match block_id.blinded_block(&chain) {
+            Ok((block, execution_optimistic, finalized)) => {
+                Ok(Some(Block::new(&block, execution_optimistic, finalized)))
+            }
+            Err(rejection) if rejection.find::<CustomNotFound>().is_some() => Ok(None),
+            Err(rejection) => Err(rejection_error(rejection)),
+        })
+        .await
+    }
+
+    /// The validators in the state identified by `state_id`, optionally only those with the given
+    /// indices or public keys and statuses.
+    #[graphql(complexity = "STATE_COMPLEXITY + child_complexity")]
+    async fn validators(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "head")] state_id: String,
+        ids: Option<Vec<String>>,
+        statuses: Option<Vec<String>>,
+    ) -> Result<Vec<Validator>> {
+        let state_id = load_state_id(ctx, &state_id)?;
+        let chain = self.chain.clone();
+        spawn_blocking(move || get_validators(&chain, state_id, ids, statuses)).await
+    }
+
+    /// The validator with the given index or public key in the state identified by `state_id`.
+    /// Null if there is no such validator.
+    #[graphql(complexity = "STATE_COMPLEXITY + child_complexity")]
+    async fn validator(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "head")] state_id: String,
+        id: String,
+    ) -> Result<Option<Validator>> {
+        let state_id = load_state_id(ctx, &state_id)?;
+        let chain = self.chain.clone();
+        spawn_blocking(move || get_validators(&chain, state_id, Some(vec![id]), None))
+            .await
+            .map(|validators| validators.into_iter().next())
+    }
+}
+
+/// Parses `state_id`, and counts it towards the states loaded by the query.
+fn load_state_id(ctx: &Context<'_>, state_id: &str) -> Result<StateId> {
+    let state_id = StateId::from_str(state_id).map_err(Error::new)?;
+    ctx.data_unchecked::<LoadedStates>().load(&state_id)?;
+    Ok(state_id)
+}
+
+/// Runs `func`, which loads blocks or states from the database, on a blocking thread.
+async fn spawn_blocking<F, R>(func: F) -> Result<R>
+where
+    F: FnOnce() -> Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::task::spawn_blocking(func)
+        .await
+        .map_err(|e| Error::new(format!("blocking task failed: {:?}", e)))?
+}
+
+fn get_validators<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    state_id: StateId,
+    ids: Option<Vec<String>>,
+    statuses: Option<Vec<String>>,
+) -> Result<Vec<Validator>> {
+    let ids = ids
+        .map(|ids| {
+            ids.iter()
+                .map(|id| ValidatorId::from_str(id))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(Error::new)?;
+    let statuses = statuses
+        .map(|statuses| {
+            statuses
+                .iter()
+                .map(|status| ValidatorStatus::from_str(status))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(Error::new)?;
+
+    let response = validators::get_beacon_state_validators(
+        state_id,
+        chain,
+        ids.as_deref(),
+        statuses.as_deref(),
+        PageQuery::default(),
+    )
+    .map_err(rejection_error)?;
+    Ok(response.data.into_iter().map(Validator::from).collect())
+}
+
+/// Converts a rejection from the REST API helpers into a GraphQL error.
+fn rejection_error(rejection: warp::Rejection) -> Error {
+    if let Some(CustomNotFound(message)) = rejection.find::<CustomNotFound>() {
+        Error::new(format!("not found: {}", message))
+    } else if let Some(CustomBadRequest(message)) = rejection.find::<CustomBadRequest>() {
+        Error::new(message.clone())
+    } else {
+        Error::new(format!("{:?}", rejection))
+    }
+}
+
+/// A block, without the transactions of its execution payload.
+#[derive(SimpleObject)]
+pub struct Block {
+    root: String,
+    slot: String,
+    proposer_index: String,
+    parent_root: String,
+    state_root: String,
+    /// The graffiti, decoded as UTF-8.
+    graffiti: String,
+    /// The hash of the execution block, from the Bellatrix fork onwards.
+    execution_block_hash: Option<String>,
+    /// Whether the block has been imported without its execution payload being verified.
+    execution_optimistic: bool,
+    finalized: bool,
+    attestations: Vec<Attestation>,
+}
+
+impl Block {
+    fn new<E: EthSpec>(
+        block: &SignedBlindedBeaconBlock<E>,
+        execution_optimistic: bool,
+        finalized: bool,
+    ) -> Self {
+        let message = block.message();
+        let body = message.body();
+        Self {
+            root: format!("{:?}", block.canonical_root()),
+            slot: message.slot().to_string(),
+            proposer_index: message.proposer_index().to_string(),
+            parent_root: format!("{:?}", message.parent_root()),
+            state_root: format!("{:?}", message.state_root()),
+            graffiti: body.graffiti().as_utf8_lossy(),
+            execution_block_hash: body
+                .execution_payload()
+                .ok()
+                .map(|payload| format!("{:?}", payload.block_hash().into_root())),
+            execution_optimistic,
+            finalized,
+            attestations: body
+                .attestations()
+                .iter()
+                .map(|attestation| Attestation {
+                    slot: attestation.data.slot.to_string(),
+                    index: attestation.data.index.to_string(),
+                    beacon_block_root: format!("{:?}", attestation.data.beacon_block_root),
+                    source: Checkpoint {
+                        epoch: attestation.data.source.epoch.to_string(),
+                        root: format!("{:?}", attestation.data.source.root),
+                    },
+                    target: Checkpoint {
+                        epoch: attestation.data.target.epoch.to_string(),
+                        root: format!("{:?}", attestation.data.target.root),
+                    },
+                    aggregation_bits: eth2_serde_utils::hex::encode(
+                        attestation.aggregation_bits.as_ssz_bytes(),
+                    ),
+                    signature: attestation.signature.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An aggregate attestation included in a block.
+#[derive(SimpleObject)]
+pub struct Attestation {
+    slot: String,
+    /// The index of the attesting committee.
+    index: String,
+    beacon_block_root: String,
+    source: Checkpoint,
+    target: Checkpoint,
+    /// The SSZ encoding of the bitfield of committee members which attested.
+    aggregation_bits: String,
+    signature: String,
+}
+
+#[derive(SimpleObject)]
+pub struct Checkpoint {
+    epoch: String,
+    root: String,
+}
+
+/// A validator, and its balance and status in the requested state.
+#[derive(SimpleObject)]
+pub struct Validator {
+    index: String,
+    balance: String,
+    status: String,
+    pubkey: String,
+    withdrawal_credentials: String,
+    effective_balance: String,
+    slashed: bool,
+    activation_eligibility_epoch: String,
+    activation_epoch: String,
+    exit_epoch: String,
+    withdrawable_epoch: String,
+}
+
+impl From<ValidatorData> for Validator {
+    fn from(data: ValidatorData) -> Self {
+        let validator = data.validator;
+        Self {
+            index: data.index.to_string(),
+            balance: data.balance.to_string(),
+            status: data.status.to_string(),
+            pubkey: validator.pubkey.to_string(),
+            withdrawal_credentials: format!("{:?}", validator.withdrawal_credentials),
+            effective_balance: validator.effective_balance.to_string(),
+            slashed: validator.slashed,
+            activation_eligibility_epoch: validator.activation_eligibility_epoch.to_string(),
+            activation_epoch: validator.activation_epoch.to_string(),
+            exit_epoch: validator.exit_epoch.to_string(),
+            withdrawable_epoch: validator.withdrawable_epoch.to_string(),
+        }
+    }
+}
@@ -7,22 +7,33 @@
 
 mod attestation_performance;
 mod attester_duties;
+mod balances_cache;
 mod block_id;
 mod block_packing_efficiency;
 mod block_rewards;
 mod database;
+mod events;
+mod graphql;
+mod light_client;
 mod metrics;
+mod node_health;
 mod proposer_duties;
 mod publish_blocks;
+mod rate_limiter;
+mod request_logger;
 mod standard_block_rewards;
 mod state_id;
 mod sync_committee_rewards;
 mod sync_committees;
+mod task_spawner;
 pub mod test_utils;
 mod ui;
 mod validator_inclusion;
+mod validators;
 mod version;
 
+pub use attestation_performance::MAX_ATTESTATION_PERFORMANCE_INDICES;
+use balances_cache::BalancesCache;
 use beacon_chain::{
     attestation_verification::VerifiedAttestation, observed_operations::ObservationOutcome,
     validator_monitor::timestamp_now, AttestationError as AttnError, BeaconChain, BeaconChainError,
@@ -34,12 +45,18 @@ use eth2::types::{
     self as api_types, EndpointVersion, ForkChoice, ForkChoiceNode, SkipRandaoVerification,
     ValidatorId, ValidatorStatus,
 };
-use lighthouse_network::{types::SyncState, EnrExt, NetworkGlobals, PeerId, PubsubMessage};
+use lighthouse_network::{
+    types::SyncState, AccessList, EnrExt, NetworkGlobals, PeerId, PubsubMessage,
+};
 use lighthouse_version::version_with_platform;
 use network::{NetworkMessage, NetworkSenders, ValidatorSubscriptionMessage};
 use operation_pool::ReceivedPreCapella;
 use parking_lot::RwLock;
+use proposer_duties::NextEpochProposerCache;
 use publish_blocks::ProvenancedBlock;
+pub use rate_limiter::RateLimitConfig;
+use rate_limiter::RateLimiter;
+use request_logger::RequestLogger;
 use serde::{Deserialize, Serialize};
 use slog::{crit, debug, error, info, warn, Logger};
 use slot_clock::SlotClock;
@@ -48,17 +65,19 @@ pub use state_id::StateId;
 use std::borrow::Cow;
 use std::future::Future;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use sysinfo::{System, SystemExt};
 use system_health::observe_system_health_bn;
+use task_spawner::{Priority, TaskSpawner};
 use tokio::sync::mpsc::{Sender, UnboundedSender};
 use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 use types::{
     Attestation, AttestationData, AttestationShufflingId, AttesterSlashing, BeaconStateError,
     BlindedPayload, CommitteeCache, ConfigAndPreset, Epoch, EthSpec, ForkName, FullPayload,
-    ProposerPreparationData, ProposerSlashing, RelativeEpoch, SignedAggregateAndProof,
+    Hash256, ProposerPreparationData, ProposerSlashing, RelativeEpoch, SignedAggregateAndProof,
     SignedBeaconBlock, SignedBlindedBeaconBlock, SignedBlsToExecutionChange,
     SignedContributionAndProof, SignedValidatorRegistrationData, SignedVoluntaryExit, Slot,
     SyncCommitteeMessage, SyncContributionData,
@@ -68,12 +87,13 @@ use version::{
     fork_versioned_response, inconsistent_fork_rejection, unsupported_version_rejection, V1, V2,
 };
 use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
 use warp::sse::Event;
 use warp::Reply;
 use warp::{http::Response, Filter};
+pub use warp_utils::api_secret::PK_FILENAME as API_TOKEN_FILENAME;
 use warp_utils::{
-    query::multi_key_query,
-    task::{blocking_json_task, blocking_response_task},
+    api_secret::ApiSecret, query::multi_key_query, task::blocking_response_task,
     uor::UnifyingOrFilter,
 };
 
@@ -86,8 +106,10 @@ const API_PREFIX: &str = "eth";
 /// finalized head.
 const SYNC_TOLERANCE_EPOCHS: u64 = 8;
 
-/// A custom type which allows for both unsecured and TLS-enabled HTTP servers.
-type HttpServer = (SocketAddr, Pin<Box<dyn Future<Output = ()> + Send>>);
+/// A custom type which allows for unsecured, TLS-enabled and Unix socket HTTP servers.
+///
+/// The address is `None` when serving over a Unix socket.
+type HttpServer = (Option<SocketAddr>, Pin<Box<dyn Future<Output = ()> + Send>>);
 
 /// Alias for readability.
 pub type ExecutionOptimistic = bool;
@@ -108,6 +130,9 @@ pub struct Context<T: BeaconChainTypes> {
     pub network_senders: Option<NetworkSenders<T::EthSpec>>,
     pub network_globals: Option<Arc<NetworkGlobals<T::EthSpec>>>,
     pub eth1_service: Option<eth1::Service>,
+    /// The paths of the hot and freezer databases, used to report their sizes.
+    pub db_path: Option<PathBuf>,
+    pub freezer_db_path: Option<PathBuf>,
     pub log: Logger,
 }
 
@@ -119,9 +144,24 @@ pub struct Config {
     pub listen_port: u16,
     pub allow_origin: Option<String>,
     pub tls_config: Option<TlsConfig>,
+    /// Serve the API on this Unix domain socket instead of the TCP listen address.
+    pub unix_socket: Option<PathBuf>,
+    /// Require the bearer token stored in this file, which is created if it doesn't exist, for
+    /// privileged endpoints such as those which modify the database.
+    pub api_token_path: Option<PathBuf>,
+    /// Limit the rate of requests from each IP address.
+    pub rate_limit: Option<RateLimitConfig>,
     pub allow_sync_stalled: bool,
     pub spec_fork_name: Option<ForkName>,
     pub data_dir: PathBuf,
+    /// Serve heap profiles at `/lighthouse/debug/heap`.
+    pub enable_heap_profiling: bool,
+    /// Serve a GraphQL schema over blocks, validators and attestations at `/lighthouse/graphql`.
+    pub enable_graphql: bool,
+    /// Log every request at `INFO` rather than `DEBUG`.
+    pub log_requests: bool,
+    /// Log a warning for requests which take at least this long to serve.
+    pub slow_request_threshold: Option<Duration>,
 }
 
 impl Default for Config {
@@ -132,9 +172,16 @@ impl Default for Config {
             listen_port: 5052,
             allow_origin: None,
             tls_config: None,
+            unix_socket: None,
+            api_token_path: None,
+            rate_limit: None,
             allow_sync_stalled: false,
             spec_fork_name: None,
             data_dir: PathBuf::from(DEFAULT_ROOT_DIR),
+            enable_heap_profiling: false,
+            enable_graphql: false,
+            log_requests: false,
+            slow_request_threshold: None,
         }
     }
 }
@@ -142,6 +189,8 @@ impl Default for Config {
 #[derive(Debug)]
 pub enum Error {
     Warp(warp::Error),
+    /// The Unix socket could not be bound.
+    UnixSocket(String),
     Other(String),
 }
 
@@ -157,37 +206,16 @@ impl From<String> for Error {
     }
 }
 
-/// Creates a `warp` logging wrapper which we use to create `slog` logs.
-pub fn slog_logging(
-    log: Logger,
-) -> warp::filters::log::Log<impl Fn(warp::filters::log::Info) + Clone> {
-    warp::log::custom(move |info| {
-        match info.status() {
-            status
-                if status == StatusCode::OK
-                    || status == StatusCode::NOT_FOUND
-                    || status == StatusCode::PARTIAL_CONTENT =>
-            {
-                debug!(
-                    log,
-                    "Processed HTTP API request";
-                    "elapsed" => format!("{:?}", info.elapsed()),
-                    "status" => status.to_string(),
-                    "path" => info.path(),
-                    "method" => info.method().to_string(),
-                );
-            }
-            status => {
-                warn!(
-                    log,
-                    "Error processing HTTP API request";
-                    "elapsed" => format!("{:?}", info.elapsed()),
-                    "status" => status.to_string(),
-                    "path" => info.path(),
-                    "method" => info.method().to_string(),
-                );
-            }
-        };
+/// Creates a `warp` wrapper which records each request within a `tracing` span.
+///
+/// The address of the client is deliberately omitted from the span.
+pub fn tracing_span() -> warp::trace::Trace<impl Fn(warp::trace::Info) -> tracing::Span + Clone> {
+    warp::trace(|info| {
+        tracing::info_span!(
+            "http_request",
+            method = %info.method(),
+            path = %info.path(),
+        )
     })
 }
 
@@ -276,13 +304,14 @@ pub fn serve<T: BeaconChainTypes>(
 ) -> Result<HttpServer, Error> {
     let config = ctx.config.clone();
     let allow_sync_stalled = config.allow_sync_stalled;
+    let enable_heap_profiling = config.enable_heap_profiling;
     let log = ctx.log.clone();
 
     // Configure CORS.
     let cors_builder = {
         let builder = warp::cors()
             .allow_methods(vec!["GET", "POST"])
-            .allow_headers(vec!["Content-Type"]);
+            .allow_headers(vec!["Content-Type", "Authorization"]);
 
         warp_utils::cors::set_builder_origins(
             builder,
@@ -291,6 +320,32 @@ pub fn serve<T: BeaconChainTypes>(
         )?
     };
 
+    // Privileged endpoints require the API token, if it is configured.
+    //
+    // These are the Lighthouse endpoints which ban peers via the access list, dump the heap, run
+    // database operations or expose validator monitor data. The standard endpoints used by
+    // validator clients remain open as validator clients of other implementations can't supply
+    // the token.
+    let privileged_filter = if let Some(path) = &config.api_token_path {
+        let api_secret = ApiSecret::create_or_open_token_file(path)?;
+        info!(log, "Privileged HTTP API endpoints require the API token"; "path" => ?path);
+        api_secret.authorization_header_filter()
+    } else {
+        warp::any().boxed()
+    };
+
+    let rate_limit_filter = if let Some(rate_limit) = config.rate_limit {
+        info!(
+            log,
+            "HTTP API requests are rate limited";
+            "requests_per_second" => rate_limit.requests_per_second,
+            "expensive_requests_per_second" => rate_limit.expensive_requests_per_second,
+        );
+        Arc::new(RateLimiter::new(rate_limit)).filter()
+    } else {
+        warp::any().boxed()
+    };
+
     // Sanity check.
     if !config.enabled {
         crit!(log, "Cannot start disabled HTTP server");
@@ -371,6 +426,14 @@ pub fn serve<T: BeaconChainTypes>(
                 }
             });
 
+    // Create a `warp` filter that provides access to the scheduling of requests by the beacon
+    // processor, so that the consensus work of the node takes priority over expensive queries.
+    let api_request_send = ctx
+        .network_senders
+        .as_ref()
+        .and_then(|senders| senders.api_request_send());
+    let task_spawner_filter = warp::any().map(move || TaskSpawner::new(api_request_send.clone()));
+
     // Create a `warp` filter that provides access to the network attestation subscription channel.
     let validator_subscriptions_tx = ctx
         .network_senders
@@ -444,6 +507,11 @@ pub fn serve<T: BeaconChainTypes>(
             )
             .untuple_one();
 
+    // Create a `warp` filter that provides access to the database paths.
+    let inner_ctx = ctx.clone();
+    let db_paths_filter =
+        warp::any().map(move || (inner_ctx.db_path.clone(), inner_ctx.freezer_db_path.clone()));
+
     // Create a `warp` filter that provides access to the logger.
     let inner_ctx = ctx.clone();
     let log_filter = warp::any().map(move || inner_ctx.log.clone());
@@ -491,8 +559,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("genesis"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P0, move || {
                 let genesis_data = api_types::GenesisData {
                     genesis_time: chain.genesis_time,
                     genesis_validators_root: chain.genesis_validators_root,
@@ -521,108 +590,60 @@ pub fn serve<T: BeaconChainTypes>(
         .clone()
         .and(warp::path("root"))
         .and(warp::path::end())
-        .and_then(|state_id: StateId, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let (root, execution_optimistic, finalized) = state_id.root(&chain)?;
-                Ok(root)
-                    .map(api_types::RootData::from)
-                    .map(api_types::GenericResponse::from)
-                    .map(|resp| {
-                        resp.add_execution_optimistic_finalized(execution_optimistic, finalized)
-                    })
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |state_id: StateId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (root, execution_optimistic, finalized) = state_id.root(&chain)?;
+                    Ok(root)
+                        .map(api_types::RootData::from)
+                        .map(api_types::GenericResponse::from)
+                        .map(|resp| {
+                            resp.add_execution_optimistic_finalized(execution_optimistic, finalized)
+                        })
+                })
+            },
+        );
 
     // GET beacon/states/{state_id}/fork
     let get_beacon_state_fork = beacon_states_path
         .clone()
         .and(warp::path("fork"))
         .and(warp::path::end())
-        .and_then(|state_id: StateId, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let (fork, execution_optimistic, finalized) =
-                    state_id.fork_and_execution_optimistic_and_finalized(&chain)?;
-                Ok(api_types::ExecutionOptimisticFinalizedResponse {
-                    data: fork,
-                    execution_optimistic: Some(execution_optimistic),
-                    finalized: Some(finalized),
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |state_id: StateId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (fork, execution_optimistic, finalized) =
+                        state_id.fork_and_execution_optimistic_and_finalized(&chain)?;
+                    Ok(api_types::ExecutionOptimisticFinalizedResponse {
+                        data: fork,
+                        execution_optimistic: Some(execution_optimistic),
+                        finalized: Some(finalized),
+                    })
                 })
-            })
-        });
+            },
+        );
 
     // GET beacon/states/{state_id}/finality_checkpoints
     let get_beacon_state_finality_checkpoints = beacon_states_path
         .clone()
         .and(warp::path("finality_checkpoints"))
         .and(warp::path::end())
-        .and_then(|state_id: StateId, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let (data, execution_optimistic, finalized) = state_id
-                    .map_state_and_execution_optimistic_and_finalized(
-                        &chain,
-                        |state, execution_optimistic, finalized| {
-                            Ok((
-                                api_types::FinalityCheckpointsData {
-                                    previous_justified: state.previous_justified_checkpoint(),
-                                    current_justified: state.current_justified_checkpoint(),
-                                    finalized: state.finalized_checkpoint(),
-                                },
-                                execution_optimistic,
-                                finalized,
-                            ))
-                        },
-                    )?;
-
-                Ok(api_types::ExecutionOptimisticFinalizedResponse {
-                    data,
-                    execution_optimistic: Some(execution_optimistic),
-                    finalized: Some(finalized),
-                })
-            })
-        });
-
-    // GET beacon/states/{state_id}/validator_balances?id
-    let get_beacon_state_validator_balances = beacon_states_path
-        .clone()
-        .and(warp::path("validator_balances"))
-        .and(warp::path::end())
-        .and(multi_key_query::<api_types::ValidatorBalancesQuery>())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |state_id: StateId,
-             chain: Arc<BeaconChain<T>>,
-             query_res: Result<api_types::ValidatorBalancesQuery, warp::Rejection>| {
-                blocking_json_task(move || {
-                    let query = query_res?;
+            |state_id: StateId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let (data, execution_optimistic, finalized) = state_id
                         .map_state_and_execution_optimistic_and_finalized(
                             &chain,
                             |state, execution_optimistic, finalized| {
                                 Ok((
-                                    state
-                                        .validators()
-                                        .iter()
-                                        .zip(state.balances().iter())
-                                        .enumerate()
-                                        // filter by validator id(s) if provided
-                                        .filter(|(index, (validator, _))| {
-                                            query.id.as_ref().map_or(true, |ids| {
-                                                ids.iter().any(|id| match id {
-                                                    ValidatorId::PublicKey(pubkey) => {
-                                                        &validator.pubkey == pubkey
-                                                    }
-                                                    ValidatorId::Index(param_index) => {
-                                                        *param_index == *index as u64
-                                                    }
-                                                })
-                                            })
-                                        })
-                                        .map(|(index, (_, balance))| {
-                                            Some(api_types::ValidatorBalanceData {
-                                                index: index as u64,
-                                                balance: *balance,
-                                            })
-                                        })
-                                        .collect::<Vec<_>>(),
+                                    api_types::FinalityCheckpointsData {
+                                        previous_justified: state.previous_justified_checkpoint(),
+                                        current_justified: state.current_justified_checkpoint(),
+                                        finalized: state.finalized_checkpoint(),
+                                    },
                                     execution_optimistic,
                                     finalized,
                                 ))
@@ -638,81 +659,117 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
-    // GET beacon/states/{state_id}/validators?id,status
+    let balances_cache = Arc::new(BalancesCache::default());
+    let balances_cache_filter = warp::any().map(move || balances_cache.clone());
+
+    // GET beacon/states/{state_id}/validator_balances?id,page,limit
+    let get_beacon_state_validator_balances = beacon_states_path
+        .clone()
+        .and(warp::path("validator_balances"))
+        .and(warp::path::end())
+        .and(multi_key_query::<api_types::ValidatorBalancesQuery>())
+        .and(balances_cache_filter.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |state_id: StateId,
+             chain: Arc<BeaconChain<T>>,
+             query_res: Result<api_types::ValidatorBalancesQuery, warp::Rejection>,
+             balances_cache: Arc<BalancesCache>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(state_id.priority(), move || {
+                    let query = query_res?;
+                    validators::get_beacon_state_validator_balances(
+                        state_id,
+                        &chain,
+                        &balances_cache,
+                        query.id.as_deref(),
+                        api_types::PageQuery {
+                            page: query.page,
+                            limit: query.limit,
+                        },
+                    )
+                })
+            },
+        );
+
+    // POST beacon/states/{state_id}/validator_balances?page,limit
+    let post_beacon_state_validator_balances = beacon_states_path
+        .clone()
+        .and(warp::path("validator_balances"))
+        .and(warp::path::end())
+        .and(warp::query::<api_types::PageQuery>())
+        .and(warp::body::json())
+        .and(balances_cache_filter)
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |state_id: StateId,
+             chain: Arc<BeaconChain<T>>,
+             page: api_types::PageQuery,
+             ids: Vec<ValidatorId>,
+             balances_cache: Arc<BalancesCache>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(state_id.priority(), move || {
+                    validators::get_beacon_state_validator_balances(
+                        state_id,
+                        &chain,
+                        &balances_cache,
+                        Some(&ids),
+                        page,
+                    )
+                })
+            },
+        );
+
+    // GET beacon/states/{state_id}/validators?id,status,page,limit
     let get_beacon_state_validators = beacon_states_path
         .clone()
         .and(warp::path("validators"))
         .and(warp::path::end())
         .and(multi_key_query::<api_types::ValidatorsQuery>())
+        .and(task_spawner_filter.clone())
         .and_then(
             |state_id: StateId,
              chain: Arc<BeaconChain<T>>,
-             query_res: Result<api_types::ValidatorsQuery, warp::Rejection>| {
-                blocking_json_task(move || {
+             query_res: Result<api_types::ValidatorsQuery, warp::Rejection>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(state_id.priority(), move || {
                     let query = query_res?;
-                    let (data, execution_optimistic, finalized) = state_id
-                        .map_state_and_execution_optimistic_and_finalized(
-                            &chain,
-                            |state, execution_optimistic, finalized| {
-                                let epoch = state.current_epoch();
-                                let far_future_epoch = chain.spec.far_future_epoch;
-
-                                Ok((
-                                    state
-                                        .validators()
-                                        .iter()
-                                        .zip(state.balances().iter())
-                                        .enumerate()
-                                        // filter by validator id(s) if provided
-                                        .filter(|(index, (validator, _))| {
-                                            query.id.as_ref().map_or(true, |ids| {
-                                                ids.iter().any(|id| match id {
-                                                    ValidatorId::PublicKey(pubkey) => {
-                                                        &validator.pubkey == pubkey
-                                                    }
-                                                    ValidatorId::Index(param_index) => {
-                                                        *param_index == *index as u64
-                                                    }
-                                                })
-                                            })
-                                        })
-                                        // filter by status(es) if provided and map the result
-                                        .filter_map(|(index, (validator, balance))| {
-                                            let status = api_types::ValidatorStatus::from_validator(
-                                                validator,
-                                                epoch,
-                                                far_future_epoch,
-                                            );
-
-                                            let status_matches =
-                                                query.status.as_ref().map_or(true, |statuses| {
-                                                    statuses.contains(&status)
-                                                        || statuses.contains(&status.superstatus())
-                                                });
-
-                                            if status_matches {
-                                                Some(api_types::ValidatorData {
-                                                    index: index as u64,
-                                                    balance: *balance,
-                                                    status,
-                                                    validator: validator.clone(),
-                                                })
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                        .collect::<Vec<_>>(),
-                                    execution_optimistic,
-                                    finalized,
-                                ))
-                            },
-                        )?;
+                    validators::get_beacon_state_validators(
+                        state_id,
+                        &chain,
+                        query.id.as_deref(),
+                        query.status.as_deref(),
+                        api_types::PageQuery {
+                            page: query.page,
+                            limit: query.limit,
+                        },
+                    )
+                })
+            },
+        );
 
-                    Ok(api_types::ExecutionOptimisticFinalizedResponse {
-                        data,
-                        execution_optimistic: Some(execution_optimistic),
-                        finalized: Some(finalized),
-                    })
+    // POST beacon/states/{state_id}/validators?page,limit
+    let post_beacon_state_validators = beacon_states_path
+        .clone()
+        .and(warp::path("validators"))
+        .and(warp::path::end())
+        .and(warp::query::<api_types::PageQuery>())
+        .and(warp::body::json())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |state_id: StateId,
+             chain: Arc<BeaconChain<T>>,
+             page: api_types::PageQuery,
+             query: api_types::ValidatorsRequestBody,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(state_id.priority(), move || {
+                    validators::get_beacon_state_validators(
+                        state_id,
+                        &chain,
+                        query.ids.as_deref(),
+                        query.statuses.as_deref(),
+                        page,
+                    )
                 })
             },
         );
@@ -727,9 +784,13 @@ pub fn serve<T: BeaconChainTypes>(
             ))
         }))
         .and(warp::path::end())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |state_id: StateId, chain: Arc<BeaconChain<T>>, validator_id: ValidatorId| {
-                blocking_json_task(move || {
+            |state_id: StateId,
+             chain: Arc<BeaconChain<T>>,
+             validator_id: ValidatorId,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(state_id.priority(), move || {
                     let (data, execution_optimistic, finalized) = state_id
                         .map_state_and_execution_optimistic_and_finalized(
                             &chain,
@@ -787,9 +848,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("committees"))
         .and(warp::query::<api_types::CommitteesQuery>())
         .and(warp::path::end())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |state_id: StateId, chain: Arc<BeaconChain<T>>, query: api_types::CommitteesQuery| {
-                blocking_json_task(move || {
+            |state_id: StateId,
+             chain: Arc<BeaconChain<T>>,
+             query: api_types::CommitteesQuery,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let (data, execution_optimistic, finalized) = state_id
                         .map_state_and_execution_optimistic_and_finalized(
                             &chain,
@@ -967,11 +1032,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("sync_committees"))
         .and(warp::query::<api_types::SyncCommitteesQuery>())
         .and(warp::path::end())
+        .and(task_spawner_filter.clone())
         .and_then(
             |state_id: StateId,
              chain: Arc<BeaconChain<T>>,
-             query: api_types::SyncCommitteesQuery| {
-                blocking_json_task(move || {
+             query: api_types::SyncCommitteesQuery,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let (sync_committee, execution_optimistic, finalized) = state_id
                         .map_state_and_execution_optimistic_and_finalized(
                             &chain,
@@ -1032,9 +1099,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("randao"))
         .and(warp::query::<api_types::RandaoQuery>())
         .and(warp::path::end())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |state_id: StateId, chain: Arc<BeaconChain<T>>, query: api_types::RandaoQuery| {
-                blocking_json_task(move || {
+            |state_id: StateId,
+             chain: Arc<BeaconChain<T>>,
+             query: api_types::RandaoQuery,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let (randao, execution_optimistic, finalized) = state_id
                         .map_state_and_execution_optimistic_and_finalized(
                             &chain,
@@ -1070,9 +1141,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::query::<api_types::HeadersQuery>())
         .and(warp::path::end())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |query: api_types::HeadersQuery, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |query: api_types::HeadersQuery,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let (root, block, execution_optimistic, finalized) =
                         match (query.slot, query.parent_root) {
                             // No query parameters, return the canonical head block.
@@ -1167,35 +1241,38 @@ pub fn serve<T: BeaconChainTypes>(
         }))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|block_id: BlockId, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let (root, execution_optimistic, finalized) = block_id.root(&chain)?;
-                // Ignore the second `execution_optimistic` since the first one has more
-                // information about the original request.
-                let (block, _execution_optimistic, _finalized) =
-                    BlockId::from_root(root).blinded_block(&chain)?;
-
-                let canonical = chain
-                    .block_root_at_slot(block.slot(), WhenSlotSkipped::None)
-                    .map_err(warp_utils::reject::beacon_chain_error)?
-                    .map_or(false, |canonical| root == canonical);
-
-                let data = api_types::BlockHeaderData {
-                    root,
-                    canonical,
-                    header: api_types::BlockHeaderAndSignature {
-                        message: block.message().block_header(),
-                        signature: block.signature().clone().into(),
-                    },
-                };
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |block_id: BlockId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (root, execution_optimistic, finalized) = block_id.root(&chain)?;
+                    // Ignore the second `execution_optimistic` since the first one has more
+                    // information about the original request.
+                    let (block, _execution_optimistic, _finalized) =
+                        BlockId::from_root(root).blinded_block(&chain)?;
+
+                    let canonical = chain
+                        .block_root_at_slot(block.slot(), WhenSlotSkipped::None)
+                        .map_err(warp_utils::reject::beacon_chain_error)?
+                        .map_or(false, |canonical| root == canonical);
 
-                Ok(api_types::ExecutionOptimisticFinalizedResponse {
-                    execution_optimistic: Some(execution_optimistic),
-                    finalized: Some(finalized),
-                    data,
+                    let data = api_types::BlockHeaderData {
+                        root,
+                        canonical,
+                        header: api_types::BlockHeaderAndSignature {
+                            message: block.message().block_header(),
+                            signature: block.signature().clone().into(),
+                        },
+                    };
+
+                    Ok(api_types::ExecutionOptimisticFinalizedResponse {
+                        execution_optimistic: Some(execution_optimistic),
+                        finalized: Some(finalized),
+                        data,
+                    })
                 })
-            })
-        });
+            },
+        );
 
     /*
      * beacon/blocks
@@ -1227,6 +1304,41 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    // POST beacon/blocks (SSZ)
+    let post_beacon_blocks_ssz = eth_v1
+        .and(warp::path("beacon"))
+        .and(warp::path("blocks"))
+        .and(warp::path::end())
+        .and(warp::header::exact(
+            "Content-Type",
+            "application/octet-stream",
+        ))
+        .and(warp::body::bytes())
+        .and(chain_filter.clone())
+        .and(network_tx_filter.clone())
+        .and(log_filter.clone())
+        .and_then(
+            |block_bytes: Bytes,
+             chain: Arc<BeaconChain<T>>,
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
+             log: Logger| async move {
+                let block =
+                    SignedBeaconBlock::<T::EthSpec>::from_ssz_bytes(&block_bytes, &chain.spec)
+                        .map_err(|e| {
+                            warp_utils::reject::custom_bad_request(format!("invalid SSZ: {:?}", e))
+                        })?;
+                publish_blocks::publish_block(
+                    None,
+                    ProvenancedBlock::Local(Arc::new(block)),
+                    chain,
+                    &network_tx,
+                    log,
+                )
+                .await
+                .map(|()| warp::reply().into_response())
+            },
+        );
+
     /*
      * beacon/blocks
      */
@@ -1251,6 +1363,37 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    // POST beacon/blinded_blocks (SSZ)
+    let post_beacon_blinded_blocks_ssz = eth_v1
+        .and(warp::path("beacon"))
+        .and(warp::path("blinded_blocks"))
+        .and(warp::path::end())
+        .and(warp::header::exact(
+            "Content-Type",
+            "application/octet-stream",
+        ))
+        .and(warp::body::bytes())
+        .and(chain_filter.clone())
+        .and(network_tx_filter.clone())
+        .and(log_filter.clone())
+        .and_then(
+            |block_bytes: Bytes,
+             chain: Arc<BeaconChain<T>>,
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
+             log: Logger| async move {
+                let block = SignedBeaconBlock::<T::EthSpec, BlindedPayload<_>>::from_ssz_bytes(
+                    &block_bytes,
+                    &chain.spec,
+                )
+                .map_err(|e| {
+                    warp_utils::reject::custom_bad_request(format!("invalid SSZ: {:?}", e))
+                })?;
+                publish_blocks::publish_blinded_block(block, chain, &network_tx, log)
+                    .await
+                    .map(|()| warp::reply().into_response())
+            },
+        );
+
     let block_id_or_err = warp::path::param::<BlockId>().or_else(|_| async {
         Err(warp_utils::reject::custom_bad_request(
             "Invalid block ID".to_string(),
@@ -1278,36 +1421,33 @@ pub fn serve<T: BeaconChainTypes>(
             |endpoint_version: EndpointVersion,
              block_id: BlockId,
              chain: Arc<BeaconChain<T>>,
-             accept_header: Option<api_types::Accept>| {
-                async move {
-                    let (block, execution_optimistic, finalized) =
-                        block_id.full_block(&chain).await?;
-                    let fork_name = block
-                        .fork_name(&chain.spec)
-                        .map_err(inconsistent_fork_rejection)?;
+             accept_header: Option<api_types::Accept>| async move {
+                let (block, execution_optimistic, finalized) = block_id.full_block(&chain).await?;
+                let fork_name = block
+                    .fork_name(&chain.spec)
+                    .map_err(inconsistent_fork_rejection)?;
 
-                    match accept_header {
-                        Some(api_types::Accept::Ssz) => Response::builder()
-                            .status(200)
-                            .header("Content-Type", "application/octet-stream")
-                            .body(block.as_ssz_bytes().into())
-                            .map_err(|e| {
-                                warp_utils::reject::custom_server_error(format!(
-                                    "failed to create response: {}",
-                                    e
-                                ))
-                            }),
-                        _ => execution_optimistic_finalized_fork_versioned_response(
-                            endpoint_version,
-                            fork_name,
-                            execution_optimistic,
-                            finalized,
-                            block,
-                        )
-                        .map(|res| warp::reply::json(&res).into_response()),
-                    }
-                    .map(|resp| add_consensus_version_header(resp, fork_name))
+                match accept_header {
+                    Some(api_types::Accept::Ssz) => Response::builder()
+                        .status(200)
+                        .header("Content-Type", "application/octet-stream")
+                        .body(block.as_ssz_bytes().into())
+                        .map_err(|e| {
+                            warp_utils::reject::custom_server_error(format!(
+                                "failed to create response: {}",
+                                e
+                            ))
+                        }),
+                    _ => execution_optimistic_finalized_fork_versioned_response(
+                        endpoint_version,
+                        fork_name,
+                        execution_optimistic,
+                        finalized,
+                        block,
+                    )
+                    .map(|res| warp::reply::json(&res).into_response()),
                 }
+                .map(|resp| add_consensus_version_header(resp, fork_name))
             },
         );
 
@@ -1316,30 +1456,38 @@ pub fn serve<T: BeaconChainTypes>(
         .clone()
         .and(warp::path("root"))
         .and(warp::path::end())
-        .and_then(|block_id: BlockId, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let (block, execution_optimistic, finalized) = block_id.blinded_block(&chain)?;
-                Ok(api_types::GenericResponse::from(api_types::RootData::from(
-                    block.canonical_root(),
-                ))
-                .add_execution_optimistic_finalized(execution_optimistic, finalized))
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |block_id: BlockId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
+                    let (block, execution_optimistic, finalized) =
+                        block_id.blinded_block(&chain)?;
+                    Ok(api_types::GenericResponse::from(api_types::RootData::from(
+                        block.canonical_root(),
+                    ))
+                    .add_execution_optimistic_finalized(execution_optimistic, finalized))
+                })
+            },
+        );
 
     // GET beacon/blocks/{block_id}/attestations
     let get_beacon_block_attestations = beacon_blocks_path_v1
         .clone()
         .and(warp::path("attestations"))
         .and(warp::path::end())
-        .and_then(|block_id: BlockId, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let (block, execution_optimistic, finalized) = block_id.blinded_block(&chain)?;
-                Ok(
-                    api_types::GenericResponse::from(block.message().body().attestations().clone())
-                        .add_execution_optimistic_finalized(execution_optimistic, finalized),
-                )
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |block_id: BlockId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (block, execution_optimistic, finalized) =
+                        block_id.blinded_block(&chain)?;
+                    Ok(api_types::GenericResponse::from(
+                        block.message().body().attestations().clone(),
+                    )
+                    .add_execution_optimistic_finalized(execution_optimistic, finalized))
+                })
+            },
+        );
 
     // GET beacon/blinded_blocks/{block_id}
     let get_beacon_blinded_block = eth_v1
@@ -1349,11 +1497,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(chain_filter.clone())
         .and(warp::path::end())
         .and(warp::header::optional::<api_types::Accept>("accept"))
+        .and(task_spawner_filter.clone())
         .and_then(
             |block_id: BlockId,
              chain: Arc<BeaconChain<T>>,
-             accept_header: Option<api_types::Accept>| {
-                blocking_response_task(move || {
+             accept_header: Option<api_types::Accept>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_response_task(Priority::P1, move || {
                     let (block, execution_optimistic, finalized) =
                         block_id.blinded_block(&chain)?;
                     let fork_name = block
@@ -1405,12 +1555,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::body::json())
         .and(network_tx_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              attestations: Vec<Attestation<T::EthSpec>>,
              network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let seen_timestamp = timestamp_now();
                     let mut failures = Vec::new();
                     let mut num_already_known = 0;
@@ -1546,9 +1698,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("attestations"))
         .and(warp::path::end())
         .and(warp::query::<api_types::AttestationPoolQuery>())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |chain: Arc<BeaconChain<T>>, query: api_types::AttestationPoolQuery| {
-                blocking_json_task(move || {
+            |chain: Arc<BeaconChain<T>>,
+             query: api_types::AttestationPoolQuery,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let query_filter = |data: &AttestationData| {
                         query.slot.map_or(true, |slot| slot == data.slot)
                             && query
@@ -1577,11 +1732,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::body::json())
         .and(network_tx_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              slashing: AttesterSlashing<T::EthSpec>,
-             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
-                blocking_json_task(move || {
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let outcome = chain
                         .verify_attester_slashing_for_gossip(slashing.clone())
                         .map_err(|e| {
@@ -1618,8 +1775,9 @@ pub fn serve<T: BeaconChainTypes>(
         .clone()
         .and(warp::path("attester_slashings"))
         .and(warp::path::end())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 let attestations = chain.op_pool.get_all_attester_slashings();
                 Ok(api_types::GenericResponse::from(attestations))
             })
@@ -1632,11 +1790,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::body::json())
         .and(network_tx_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              slashing: ProposerSlashing,
-             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
-                blocking_json_task(move || {
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let outcome = chain
                         .verify_proposer_slashing_for_gossip(slashing.clone())
                         .map_err(|e| {
@@ -1673,8 +1833,9 @@ pub fn serve<T: BeaconChainTypes>(
         .clone()
         .and(warp::path("proposer_slashings"))
         .and(warp::path::end())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 let attestations = chain.op_pool.get_all_proposer_slashings();
                 Ok(api_types::GenericResponse::from(attestations))
             })
@@ -1687,11 +1848,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::body::json())
         .and(network_tx_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              exit: SignedVoluntaryExit,
-             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
-                blocking_json_task(move || {
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let outcome = chain
                         .verify_voluntary_exit_for_gossip(exit.clone())
                         .map_err(|e| {
@@ -1726,8 +1889,9 @@ pub fn serve<T: BeaconChainTypes>(
         .clone()
         .and(warp::path("voluntary_exits"))
         .and(warp::path::end())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 let attestations = chain.op_pool.get_all_voluntary_exits();
                 Ok(api_types::GenericResponse::from(attestations))
             })
@@ -1741,12 +1905,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::body::json())
         .and(network_tx_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              signatures: Vec<SyncCommitteeMessage>,
              network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     sync_committees::process_sync_committee_signatures(
                         signatures, network_tx, &chain, log,
                     )?;
@@ -1760,8 +1926,9 @@ pub fn serve<T: BeaconChainTypes>(
         .clone()
         .and(warp::path("bls_to_execution_changes"))
         .and(warp::path::end())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 let address_changes = chain.op_pool.get_all_bls_to_execution_changes();
                 Ok(api_types::GenericResponse::from(address_changes))
             })
@@ -1775,12 +1942,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::body::json())
         .and(network_tx_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              address_changes: Vec<SignedBlsToExecutionChange>,
              network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let mut failures = vec![];
 
                     for (index, address_change) in address_changes.into_iter().enumerate() {
@@ -1865,9 +2033,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::header::optional::<api_types::Accept>("accept"))
         .and(eth1_service_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |accept_header: Option<api_types::Accept>, eth1_service: eth1::Service| {
-                blocking_response_task(move || match accept_header {
+            |accept_header: Option<api_types::Accept>,
+             eth1_service: eth1::Service,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_response_task(Priority::P1, move || match accept_header {
                     Some(api_types::Accept::Json) | None => {
                         let snapshot = eth1_service.get_deposit_snapshot();
                         Ok(
@@ -1905,6 +2076,75 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    /*
+     * beacon/light_client
+     */
+
+    let beacon_light_client_path = eth_v1
+        .and(warp::path("beacon"))
+        .and(warp::path("light_client"))
+        .and(chain_filter.clone());
+
+    // GET beacon/light_client/bootstrap/{block_root}
+    let get_beacon_light_client_bootstrap = beacon_light_client_path
+        .clone()
+        .and(warp::path("bootstrap"))
+        .and(warp::path::param::<Hash256>().or_else(|_| async {
+            Err(warp_utils::reject::custom_bad_request(
+                "Invalid block root".to_string(),
+            ))
+        }))
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |chain: Arc<BeaconChain<T>>, block_root: Hash256, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    light_client::get_light_client_bootstrap(&chain, block_root)
+                })
+            },
+        );
+
+    // GET beacon/light_client/updates?start_period,count
+    let get_beacon_light_client_updates = beacon_light_client_path
+        .clone()
+        .and(warp::path("updates"))
+        .and(warp::path::end())
+        .and(warp::query::<api_types::LightClientUpdatesQuery>())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |chain: Arc<BeaconChain<T>>,
+             query: api_types::LightClientUpdatesQuery,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    light_client::get_light_client_updates(&chain, query.start_period, query.count)
+                })
+            },
+        );
+
+    // GET beacon/light_client/finality_update
+    let get_beacon_light_client_finality_update = beacon_light_client_path
+        .clone()
+        .and(warp::path("finality_update"))
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
+                light_client::get_light_client_finality_update(&chain)
+            })
+        });
+
+    // GET beacon/light_client/optimistic_update
+    let get_beacon_light_client_optimistic_update = beacon_light_client_path
+        .clone()
+        .and(warp::path("optimistic_update"))
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
+                light_client::get_light_client_optimistic_update(&chain)
+            })
+        });
+
     let beacon_rewards_path = eth_v1
         .and(warp::path("beacon"))
         .and(warp::path("rewards"))
@@ -1916,17 +2156,20 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("blocks"))
         .and(block_id_or_err)
         .and(warp::path::end())
-        .and_then(|chain: Arc<BeaconChain<T>>, block_id: BlockId| {
-            blocking_json_task(move || {
-                let (rewards, execution_optimistic, finalized) =
-                    standard_block_rewards::compute_beacon_block_rewards(chain, block_id)?;
-                Ok(rewards)
-                    .map(api_types::GenericResponse::from)
-                    .map(|resp| {
-                        resp.add_execution_optimistic_finalized(execution_optimistic, finalized)
-                    })
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |chain: Arc<BeaconChain<T>>, block_id: BlockId, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (rewards, execution_optimistic, finalized) =
+                        standard_block_rewards::compute_beacon_block_rewards(chain, block_id)?;
+                    Ok(rewards)
+                        .map(api_types::GenericResponse::from)
+                        .map(|resp| {
+                            resp.add_execution_optimistic_finalized(execution_optimistic, finalized)
+                        })
+                })
+            },
+        );
 
     /*
      * beacon/rewards
@@ -1945,12 +2188,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::body::json())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              epoch: Epoch,
              validators: Vec<ValidatorId>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let attestation_rewards = chain
                         .compute_attestation_rewards(epoch, validators, log)
                         .map_err(|e| match e {
@@ -1997,12 +2242,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::body::json())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              block_id: BlockId,
              validators: Vec<ValidatorId>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let (rewards, execution_optimistic, finalized) =
                         sync_committee_rewards::compute_sync_committee_rewards(
                             chain, block_id, validators, log,
@@ -2028,8 +2275,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("fork_schedule"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P0, move || {
                 let forks = ForkName::list_all()
                     .into_iter()
                     .filter_map(|fork_name| chain.spec.fork_for_name(fork_name))
@@ -2044,21 +2292,25 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("spec"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(move |chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                let config_and_preset =
-                    ConfigAndPreset::from_chain_spec::<T::EthSpec>(&chain.spec, spec_fork_name);
-                Ok(api_types::GenericResponse::from(config_and_preset))
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            move |chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
+                    let config_and_preset =
+                        ConfigAndPreset::from_chain_spec::<T::EthSpec>(&chain.spec, spec_fork_name);
+                    Ok(api_types::GenericResponse::from(config_and_preset))
+                })
+            },
+        );
 
     // GET config/deposit_contract
     let get_config_deposit_contract = config_path
         .and(warp::path("deposit_contract"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P0, move || {
                 Ok(api_types::GenericResponse::from(
                     api_types::DepositContractData {
                         address: chain.spec.deposit_contract_address,
@@ -2085,12 +2337,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::header::optional::<api_types::Accept>("accept"))
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |endpoint_version: EndpointVersion,
              state_id: StateId,
              accept_header: Option<api_types::Accept>,
-             chain: Arc<BeaconChain<T>>| {
-                blocking_response_task(move || match accept_header {
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_response_task(Priority::P1, move || match accept_header {
                     Some(api_types::Accept::Ssz) => {
                         // We can ignore the optimistic status for the "fork" since it's a
                         // specification constant that doesn't change across competing heads of the
@@ -2143,9 +2397,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("heads"))
         .and(warp::path::end())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |endpoint_version: EndpointVersion, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |endpoint_version: EndpointVersion,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let heads = chain
                         .heads()
                         .into_iter()
@@ -2179,8 +2436,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("fork_choice"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 let beacon_fork_choice = chain.canonical_head.fork_choice_read_lock();
 
                 let proto_array = beacon_fork_choice.proto_array().core_proto_array();
@@ -2235,45 +2493,49 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("identity"))
         .and(warp::path::end())
         .and(network_globals.clone())
-        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-            blocking_json_task(move || {
-                let enr = network_globals.local_enr();
-                let p2p_addresses = enr.multiaddr_p2p_tcp();
-                let discovery_addresses = enr.multiaddr_p2p_udp();
-                let meta_data = network_globals.local_metadata.read();
-                Ok(api_types::GenericResponse::from(api_types::IdentityData {
-                    peer_id: network_globals.local_peer_id().to_base58(),
-                    enr,
-                    p2p_addresses,
-                    discovery_addresses,
-                    metadata: api_types::MetaData {
-                        seq_number: *meta_data.seq_number(),
-                        attnets: format!(
-                            "0x{}",
-                            hex::encode(meta_data.attnets().clone().into_bytes()),
-                        ),
-                        syncnets: format!(
-                            "0x{}",
-                            hex::encode(
-                                meta_data
-                                    .syncnets()
-                                    .map(|x| x.clone())
-                                    .unwrap_or_default()
-                                    .into_bytes()
-                            )
-                        ),
-                    },
-                }))
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
+                    let enr = network_globals.local_enr();
+                    let p2p_addresses = enr.multiaddr_p2p_tcp();
+                    let discovery_addresses = enr.multiaddr_p2p_udp();
+                    let meta_data = network_globals.local_metadata.read();
+                    Ok(api_types::GenericResponse::from(api_types::IdentityData {
+                        peer_id: network_globals.local_peer_id().to_base58(),
+                        enr,
+                        p2p_addresses,
+                        discovery_addresses,
+                        metadata: api_types::MetaData {
+                            seq_number: *meta_data.seq_number(),
+                            attnets: format!(
+                                "0x{}",
+                                hex::encode(meta_data.attnets().clone().into_bytes()),
+                            ),
+                            syncnets: format!(
+                                "0x{}",
+                                hex::encode(
+                                    meta_data
+                                        .syncnets()
+                                        .map(|x| x.clone())
+                                        .unwrap_or_default()
+                                        .into_bytes()
+                                )
+                            ),
+                        },
+                    }))
+                })
+            },
+        );
 
     // GET node/version
     let get_node_version = eth_v1
         .and(warp::path("node"))
         .and(warp::path("version"))
         .and(warp::path::end())
-        .and_then(|| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P0, move || {
                 Ok(api_types::GenericResponse::from(api_types::VersionData {
                     version: version_with_platform(),
                 }))
@@ -2287,9 +2549,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(network_globals.clone())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let head_slot = chain.canonical_head.cached_head().head_slot();
                     let current_slot = chain.slot_clock.now_or_genesis().ok_or_else(|| {
                         warp_utils::reject::custom_server_error("Unable to read slot clock".into())
@@ -2320,24 +2585,29 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("health"))
         .and(warp::path::end())
         .and(network_globals.clone())
-        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-            blocking_response_task(move || match *network_globals.sync_state.read() {
-                SyncState::SyncingFinalized { .. }
-                | SyncState::SyncingHead { .. }
-                | SyncState::SyncTransition
-                | SyncState::BackFillSyncing { .. } => Ok(warp::reply::with_status(
-                    warp::reply(),
-                    warp::http::StatusCode::PARTIAL_CONTENT,
-                )),
-                SyncState::Synced => Ok(warp::reply::with_status(
-                    warp::reply(),
-                    warp::http::StatusCode::OK,
-                )),
-                SyncState::Stalled => Err(warp_utils::reject::not_synced(
-                    "sync stalled, beacon chain may not yet be initialized.".to_string(),
-                )),
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_response_task(Priority::P0, move || {
+                    match *network_globals.sync_state.read() {
+                        SyncState::SyncingFinalized { .. }
+                        | SyncState::SyncingHead { .. }
+                        | SyncState::SyncTransition
+                        | SyncState::BackFillSyncing { .. } => Ok(warp::reply::with_status(
+                            warp::reply(),
+                            warp::http::StatusCode::PARTIAL_CONTENT,
+                        )),
+                        SyncState::Synced => Ok(warp::reply::with_status(
+                            warp::reply(),
+                            warp::http::StatusCode::OK,
+                        )),
+                        SyncState::Stalled => Err(warp_utils::reject::not_synced(
+                            "sync stalled, beacon chain may not yet be initialized.".to_string(),
+                        )),
+                    }
+                })
+            },
+        );
 
     // GET node/peers/{peer_id}
     let get_node_peers_by_id = eth_v1
@@ -2346,9 +2616,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::param::<String>())
         .and(warp::path::end())
         .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |requested_peer_id: String, network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-                blocking_json_task(move || {
+            |requested_peer_id: String,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let peer_id = PeerId::from_bytes(
                         &bs58::decode(requested_peer_id.as_str())
                             .into_vec()
@@ -2403,10 +2676,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(multi_key_query::<api_types::PeersQuery>())
         .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |query_res: Result<api_types::PeersQuery, warp::Rejection>,
-             network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-                blocking_json_task(move || {
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let query = query_res?;
                     let mut peers: Vec<api_types::PeerData> = Vec::new();
                     network_globals
@@ -2471,41 +2746,48 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("peer_count"))
         .and(warp::path::end())
         .and(network_globals.clone())
-        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-            blocking_json_task(move || {
-                let mut connected: u64 = 0;
-                let mut connecting: u64 = 0;
-                let mut disconnected: u64 = 0;
-                let mut disconnecting: u64 = 0;
-
-                network_globals
-                    .peers
-                    .read()
-                    .peers()
-                    .for_each(|(_, peer_info)| {
-                        let state = api_types::PeerState::from_peer_connection_status(
-                            peer_info.connection_status(),
-                        );
-                        match state {
-                            api_types::PeerState::Connected => connected += 1,
-                            api_types::PeerState::Connecting => connecting += 1,
-                            api_types::PeerState::Disconnected => disconnected += 1,
-                            api_types::PeerState::Disconnecting => disconnecting += 1,
-                        }
-                    });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
+                    let mut connected: u64 = 0;
+                    let mut connecting: u64 = 0;
+                    let mut disconnected: u64 = 0;
+                    let mut disconnecting: u64 = 0;
 
-                Ok(api_types::GenericResponse::from(api_types::PeerCount {
-                    connected,
-                    connecting,
-                    disconnected,
-                    disconnecting,
-                }))
-            })
-        });
+                    network_globals
+                        .peers
+                        .read()
+                        .peers()
+                        .for_each(|(_, peer_info)| {
+                            let state = api_types::PeerState::from_peer_connection_status(
+                                peer_info.connection_status(),
+                            );
+                            match state {
+                                api_types::PeerState::Connected => connected += 1,
+                                api_types::PeerState::Connecting => connecting += 1,
+                                api_types::PeerState::Disconnected => disconnected += 1,
+                                api_types::PeerState::Disconnecting => disconnecting += 1,
+                            }
+                        });
+
+                    Ok(api_types::GenericResponse::from(api_types::PeerCount {
+                        connected,
+                        connecting,
+                        disconnected,
+                        disconnecting,
+                    }))
+                })
+            },
+        );
     /*
      * validator
      */
 
+    let next_epoch_proposer_cache = Arc::new(NextEpochProposerCache::default());
+    let next_epoch_proposer_cache_filter =
+        warp::any().map(move || next_epoch_proposer_cache.clone());
+
     // GET validator/duties/proposer/{epoch}
     let get_validator_duties_proposer = eth_v1
         .and(warp::path("validator"))
@@ -2519,10 +2801,20 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(not_while_syncing_filter.clone())
         .and(chain_filter.clone())
+        .and(next_epoch_proposer_cache_filter)
         .and(log_filter.clone())
-        .and_then(|epoch: Epoch, chain: Arc<BeaconChain<T>>, log: Logger| {
-            blocking_json_task(move || proposer_duties::proposer_duties(epoch, &chain, &log))
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |epoch: Epoch,
+             chain: Arc<BeaconChain<T>>,
+             next_epoch_cache: Arc<NextEpochProposerCache>,
+             log: Logger,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
+                    proposer_duties::proposer_duties(epoch, &chain, &next_epoch_cache, &log)
+                })
+            },
+        );
 
     // GET validator/blocks/{slot}
     let get_validator_blocks = any_version
@@ -2606,43 +2898,43 @@ pub fn serve<T: BeaconChainTypes>(
             |slot: Slot,
              query: api_types::ValidatorBlocksQuery,
              chain: Arc<BeaconChain<T>>| async move {
-                let randao_reveal = query.randao_reveal.decompress().map_err(|e| {
-                    warp_utils::reject::custom_bad_request(format!(
-                        "randao reveal is not a valid BLS signature: {:?}",
-                        e
-                    ))
-                })?;
+                    let randao_reveal = query.randao_reveal.decompress().map_err(|e| {
+                        warp_utils::reject::custom_bad_request(format!(
+                            "randao reveal is not a valid BLS signature: {:?}",
+                            e
+                        ))
+                    })?;
 
-                let randao_verification =
-                    if query.skip_randao_verification == SkipRandaoVerification::Yes {
-                        if !randao_reveal.is_infinity() {
-                            return Err(warp_utils::reject::custom_bad_request(
-                                "randao_reveal must be point-at-infinity if verification is skipped"
-                                    .into()
-                            ));
-                        }
-                        ProduceBlockVerification::NoVerification
-                    } else {
-                        ProduceBlockVerification::VerifyRandao
-                    };
+                    let randao_verification =
+                        if query.skip_randao_verification == SkipRandaoVerification::Yes {
+                            if !randao_reveal.is_infinity() {
+                                return Err(warp_utils::reject::custom_bad_request(
+                                "randao_reveal must be point-at-infinity if verification is skipped"
+                                    .into()
+                            ));
+                            }
+                            ProduceBlockVerification::NoVerification
+                        } else {
+                            ProduceBlockVerification::VerifyRandao
+                        };
 
-                let (block, _) = chain
-                    .produce_block_with_verification::<BlindedPayload<T::EthSpec>>(
-                        randao_reveal,
-                        slot,
-                        query.graffiti.map(Into::into),
-                        randao_verification,
-                    )
-                    .await
-                    .map_err(warp_utils::reject::block_production_error)?;
-                let fork_name = block
-                    .to_ref()
-                    .fork_name(&chain.spec)
-                    .map_err(inconsistent_fork_rejection)?;
+                    let (block, _) = chain
+                        .produce_block_with_verification::<BlindedPayload<T::EthSpec>>(
+                            randao_reveal,
+                            slot,
+                            query.graffiti.map(Into::into),
+                            randao_verification,
+                        )
+                        .await
+                        .map_err(warp_utils::reject::block_production_error)?;
+                    let fork_name = block
+                        .to_ref()
+                        .fork_name(&chain.spec)
+                        .map_err(inconsistent_fork_rejection)?;
 
-                // Pose as a V2 endpoint so we return the fork `version`.
-                fork_versioned_response(V2, fork_name, block)
-                    .map(|response| warp::reply::json(&response).into_response())
+                    // Pose as a V2 endpoint so we return the fork `version`.
+                    fork_versioned_response(V2, fork_name, block)
+                        .map(|response| warp::reply::json(&response).into_response())
             },
         );
 
@@ -2654,9 +2946,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::query::<api_types::ValidatorAttestationDataQuery>())
         .and(not_while_syncing_filter.clone())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |query: api_types::ValidatorAttestationDataQuery, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |query: api_types::ValidatorAttestationDataQuery,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let current_slot = chain
                         .slot()
                         .map_err(warp_utils::reject::beacon_chain_error)?;
@@ -2686,9 +2981,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::query::<api_types::ValidatorAggregateAttestationQuery>())
         .and(not_while_syncing_filter.clone())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |query: api_types::ValidatorAggregateAttestationQuery, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |query: api_types::ValidatorAggregateAttestationQuery,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     chain
                         .get_aggregated_attestation_by_slot_and_root(
                             query.slot,
@@ -2724,9 +3022,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(not_while_syncing_filter.clone())
         .and(warp::body::json())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |epoch: Epoch, indices: api_types::ValidatorIndexData, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |epoch: Epoch,
+             indices: api_types::ValidatorIndexData,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     attester_duties::attester_duties(epoch, &indices.0, &chain)
                 })
             },
@@ -2746,9 +3048,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(not_while_syncing_filter.clone())
         .and(warp::body::json())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |epoch: Epoch, indices: api_types::ValidatorIndexData, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |epoch: Epoch,
+             indices: api_types::ValidatorIndexData,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     sync_committees::sync_committee_duties(epoch, &indices.0, &chain)
                 })
             },
@@ -2762,9 +3068,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::query::<SyncContributionData>())
         .and(not_while_syncing_filter.clone())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |sync_committee_data: SyncContributionData, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |sync_committee_data: SyncContributionData,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     chain
                         .get_aggregated_sync_committee_contribution(&sync_committee_data)
                         .map_err(|e| {
@@ -2793,11 +3102,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::body::json())
         .and(network_tx_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              aggregates: Vec<SignedAggregateAndProof<T::EthSpec>>,
-             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>, log: Logger| {
-                blocking_json_task(move || {
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>, log: Logger, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     let seen_timestamp = timestamp_now();
                     let mut verified_aggregates = Vec::with_capacity(aggregates.len());
                     let mut messages = Vec::with_capacity(aggregates.len());
@@ -2904,12 +3214,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::body::json())
         .and(network_tx_filter)
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |chain: Arc<BeaconChain<T>>,
              contributions: Vec<SignedContributionAndProof<T::EthSpec>>,
              network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     sync_committees::process_signed_contribution_and_proofs(
                         contributions,
                         network_tx,
@@ -2930,12 +3242,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(validator_subscription_tx_filter.clone())
         .and(chain_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |subscriptions: Vec<api_types::BeaconCommitteeSubscription>,
              validator_subscription_tx: Sender<ValidatorSubscriptionMessage>,
              chain: Arc<BeaconChain<T>>,
-             log: Logger| {
-                blocking_json_task(move || {
+             log: Logger, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     for subscription in &subscriptions {
                         chain
                             .validator_monitor
@@ -3156,13 +3469,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(validator_subscription_tx_filter)
         .and(chain_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |subscriptions: Vec<types::SyncCommitteeSubscription>,
              validator_subscription_tx: Sender<ValidatorSubscriptionMessage>,
              chain: Arc<BeaconChain<T>>,
-             log: Logger
-             | {
-                blocking_json_task(move || {
+             log: Logger, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     for subscription in subscriptions {
                         chain
                             .validator_monitor
@@ -3196,9 +3509,12 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(warp::body::json())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |request_data: api_types::LivenessRequestData, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |request_data: api_types::LivenessRequestData,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P0, move || {
                     // Ensure the request is for either the current, previous or next epoch.
                     let current_epoch = chain
                         .epoch()
@@ -3237,14 +3553,54 @@ pub fn serve<T: BeaconChainTypes>(
     let get_lighthouse_health = warp::path("lighthouse")
         .and(warp::path("health"))
         .and(warp::path::end())
-        .and_then(|| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 eth2::lighthouse::Health::observe()
                     .map(api_types::GenericResponse::from)
                     .map_err(warp_utils::reject::custom_bad_request)
             })
         });
 
+    // GET lighthouse/health/node
+    let get_lighthouse_health_node = warp::path("lighthouse")
+        .and(warp::path("health"))
+        .and(warp::path("node"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and(network_globals.clone())
+        .and(data_dir_filter.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |chain: Arc<BeaconChain<T>>,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             data_dir,
+             task_spawner: TaskSpawner| {
+                task_spawner.spawn_async(Priority::P0, async move {
+                    let sync_tolerance = SYNC_TOLERANCE_EPOCHS * T::EthSpec::slots_per_epoch();
+                    let health = node_health::observe_node_health(
+                        chain,
+                        network_globals,
+                        data_dir,
+                        sync_tolerance,
+                    )
+                    .await;
+                    let status = if health.healthy {
+                        warp::http::StatusCode::OK
+                    } else {
+                        warp::http::StatusCode::SERVICE_UNAVAILABLE
+                    };
+                    Ok::<_, warp::Rejection>(
+                        warp::reply::with_status(
+                            warp::reply::json(&api_types::GenericResponse::from(health)),
+                            status,
+                        )
+                        .into_response(),
+                    )
+                })
+            },
+        );
+
     // GET lighthouse/ui/health
     let get_lighthouse_ui_health = warp::path("lighthouse")
         .and(warp::path("ui"))
@@ -3254,9 +3610,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(app_start_filter)
         .and(data_dir_filter)
         .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |sysinfo, app_start: std::time::Instant, data_dir, network_globals| {
-                blocking_json_task(move || {
+            |sysinfo,
+             app_start: std::time::Instant,
+             data_dir,
+             network_globals,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     let app_uptime = app_start.elapsed().as_secs();
                     Ok(api_types::GenericResponse::from(observe_system_health_bn(
                         sysinfo,
@@ -3274,8 +3635,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("validator_count"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 ui::get_validator_count(chain).map(api_types::GenericResponse::from)
             })
         });
@@ -3285,11 +3647,15 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("ui"))
         .and(warp::path("validator_metrics"))
         .and(warp::path::end())
+        .and(privileged_filter.clone())
         .and(warp::body::json())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |request_data: ui::ValidatorMetricsRequestData, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |request_data: ui::ValidatorMetricsRequestData,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     ui::post_validator_monitor_metrics(request_data, chain)
                         .map(api_types::GenericResponse::from)
                 })
@@ -3301,11 +3667,15 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("ui"))
         .and(warp::path("validator_info"))
         .and(warp::path::end())
+        .and(privileged_filter.clone())
         .and(warp::body::json())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |request_data: ui::ValidatorInfoRequestData, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |request_data: ui::ValidatorInfoRequestData,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     ui::get_validator_info(request_data, chain)
                         .map(api_types::GenericResponse::from)
                 })
@@ -3317,20 +3687,24 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("syncing"))
         .and(warp::path::end())
         .and(network_globals.clone())
-        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-            blocking_json_task(move || {
-                Ok(api_types::GenericResponse::from(
-                    network_globals.sync_state(),
-                ))
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    Ok(api_types::GenericResponse::from(
+                        network_globals.sync_state(),
+                    ))
+                })
+            },
+        );
 
     // GET lighthouse/nat
     let get_lighthouse_nat = warp::path("lighthouse")
         .and(warp::path("nat"))
         .and(warp::path::end())
-        .and_then(|| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 Ok(api_types::GenericResponse::from(
                     lighthouse_network::metrics::NAT_OPEN
                         .as_ref()
@@ -3341,52 +3715,163 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/nat/ports
+    let get_lighthouse_nat_ports = warp::path("lighthouse")
+        .and(warp::path("nat"))
+        .and(warp::path("ports"))
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    use lighthouse_network::metrics::{
+                        nat_port_open, NAT_DISCOVERY_PORT, NAT_LIBP2P_PORT,
+                    };
+                    Ok(api_types::GenericResponse::from(
+                        eth2::lighthouse::NatPorts {
+                            discovery_port: network_globals.local_enr().udp4(),
+                            discovery_reachable: nat_port_open(NAT_DISCOVERY_PORT),
+                            libp2p_port: network_globals.listen_port_tcp4(),
+                            libp2p_reachable: nat_port_open(NAT_LIBP2P_PORT),
+                        },
+                    ))
+                })
+            },
+        );
+
     // GET lighthouse/peers
     let get_lighthouse_peers = warp::path("lighthouse")
         .and(warp::path("peers"))
         .and(warp::path::end())
         .and(network_globals.clone())
-        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-            blocking_json_task(move || {
-                Ok(network_globals
-                    .peers
-                    .read()
-                    .peers()
-                    .map(|(peer_id, peer_info)| eth2::lighthouse::Peer {
-                        peer_id: peer_id.to_string(),
-                        peer_info: peer_info.clone(),
-                    })
-                    .collect::<Vec<_>>())
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    Ok(network_globals
+                        .peers
+                        .read()
+                        .peers()
+                        .map(|(peer_id, peer_info)| eth2::lighthouse::Peer {
+                            peer_id: peer_id.to_string(),
+                            peer_info: peer_info.clone(),
+                        })
+                        .collect::<Vec<_>>())
+                })
+            },
+        );
 
     // GET lighthouse/peers/connected
     let get_lighthouse_peers_connected = warp::path("lighthouse")
         .and(warp::path("peers"))
         .and(warp::path("connected"))
         .and(warp::path::end())
+        .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    Ok(network_globals
+                        .peers
+                        .read()
+                        .connected_peers()
+                        .map(|(peer_id, peer_info)| eth2::lighthouse::Peer {
+                            peer_id: peer_id.to_string(),
+                            peer_info: peer_info.clone(),
+                        })
+                        .collect::<Vec<_>>())
+                })
+            },
+        );
+
+    // GET lighthouse/peers/access_list
+    let get_lighthouse_peers_access_list = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path("access_list"))
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |network_globals: Arc<NetworkGlobals<T::EthSpec>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    Ok(api_types::GenericResponse::from(peer_access_list(
+                        &network_globals.access_list.read(),
+                    )))
+                })
+            },
+        );
+
+    // POST lighthouse/peers/access_list/add
+    let post_lighthouse_peers_access_list_add = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path("access_list"))
+        .and(warp::path("add"))
+        .and(warp::path::end())
+        .and(privileged_filter.clone())
+        .and(warp::body::json())
+        .and(network_globals.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |entries: eth2::lighthouse::PeerAccessList,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let mut access_list = network_globals.access_list.write();
+                    entries
+                        .allowlist
+                        .into_iter()
+                        .for_each(|entry| access_list.allow(entry));
+                    entries
+                        .denylist
+                        .into_iter()
+                        .for_each(|entry| access_list.deny(entry));
+                    Ok(api_types::GenericResponse::from(peer_access_list(
+                        &access_list,
+                    )))
+                })
+            },
+        );
+
+    // POST lighthouse/peers/access_list/remove
+    let post_lighthouse_peers_access_list_remove = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path("access_list"))
+        .and(warp::path("remove"))
+        .and(warp::path::end())
+        .and(privileged_filter.clone())
+        .and(warp::body::json())
         .and(network_globals)
-        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
-            blocking_json_task(move || {
-                Ok(network_globals
-                    .peers
-                    .read()
-                    .connected_peers()
-                    .map(|(peer_id, peer_info)| eth2::lighthouse::Peer {
-                        peer_id: peer_id.to_string(),
-                        peer_info: peer_info.clone(),
-                    })
-                    .collect::<Vec<_>>())
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |entries: eth2::lighthouse::PeerAccessList,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let mut access_list = network_globals.access_list.write();
+                    entries
+                        .allowlist
+                        .iter()
+                        .for_each(|entry| access_list.remove_allowed(entry));
+                    entries
+                        .denylist
+                        .iter()
+                        .for_each(|entry| access_list.remove_denied(entry));
+                    Ok(api_types::GenericResponse::from(peer_access_list(
+                        &access_list,
+                    )))
+                })
+            },
+        );
 
     // GET lighthouse/proto_array
     let get_lighthouse_proto_array = warp::path("lighthouse")
         .and(warp::path("proto_array"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_response_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_response_task(Priority::P1, move || {
                 Ok::<_, warp::Rejection>(warp::reply::json(&api_types::GenericResponseRef::from(
                     chain
                         .canonical_head
@@ -3404,9 +3889,13 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::param::<ValidatorId>())
         .and(warp::path::end())
         .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
-            |epoch: Epoch, validator_id: ValidatorId, chain: Arc<BeaconChain<T>>| {
-                blocking_json_task(move || {
+            |epoch: Epoch,
+             validator_id: ValidatorId,
+             chain: Arc<BeaconChain<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
                     validator_inclusion::validator_inclusion_data(epoch, &validator_id, &chain)
                         .map(api_types::GenericResponse::from)
                 })
@@ -3420,12 +3909,15 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("global"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|epoch: Epoch, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                validator_inclusion::global_validator_inclusion_data(epoch, &chain)
-                    .map(api_types::GenericResponse::from)
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |epoch: Epoch, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    validator_inclusion::global_validator_inclusion_data(epoch, &chain)
+                        .map(api_types::GenericResponse::from)
+                })
+            },
+        );
 
     // GET lighthouse/eth1/syncing
     let get_lighthouse_eth1_syncing = warp::path("lighthouse")
@@ -3433,8 +3925,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("syncing"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 let current_slot_opt = chain.slot().ok();
 
                 chain
@@ -3463,8 +3956,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("block_cache"))
         .and(warp::path::end())
         .and(eth1_service_filter.clone())
-        .and_then(|eth1_service: eth1::Service| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|eth1_service: eth1::Service, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 Ok(api_types::GenericResponse::from(
                     eth1_service
                         .blocks()
@@ -3482,8 +3976,9 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("deposit_cache"))
         .and(warp::path::end())
         .and(eth1_service_filter)
-        .and_then(|eth1_service: eth1::Service| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|eth1_service: eth1::Service, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 Ok(api_types::GenericResponse::from(
                     eth1_service
                         .deposits()
@@ -3504,30 +3999,34 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("ssz"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|state_id: StateId, chain: Arc<BeaconChain<T>>| {
-            blocking_response_task(move || {
-                // This debug endpoint provides no indication of optimistic status.
-                let (state, _execution_optimistic, _finalized) = state_id.state(&chain)?;
-                Response::builder()
-                    .status(200)
-                    .header("Content-Type", "application/ssz")
-                    .body(state.as_ssz_bytes())
-                    .map_err(|e| {
-                        warp_utils::reject::custom_server_error(format!(
-                            "failed to create response: {}",
-                            e
-                        ))
-                    })
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |state_id: StateId, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_response_task(Priority::P1, move || {
+                    // This debug endpoint provides no indication of optimistic status.
+                    let (state, _execution_optimistic, _finalized) = state_id.state(&chain)?;
+                    Response::builder()
+                        .status(200)
+                        .header("Content-Type", "application/ssz")
+                        .body(state.as_ssz_bytes())
+                        .map_err(|e| {
+                            warp_utils::reject::custom_server_error(format!(
+                                "failed to create response: {}",
+                                e
+                            ))
+                        })
+                })
+            },
+        );
 
     // GET lighthouse/staking
     let get_lighthouse_staking = warp::path("lighthouse")
         .and(warp::path("staking"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P0, move || {
                 if chain.eth1_chain.is_some() {
                     Ok(())
                 } else {
@@ -3540,6 +4039,67 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/debug/heap
+    let get_lighthouse_debug_heap = warp::path("lighthouse")
+        .and(warp::path("debug"))
+        .and(warp::path("heap"))
+        .and(warp::path::end())
+        .and(privileged_filter.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(move |task_spawner: TaskSpawner| {
+            task_spawner.blocking_response_task(Priority::P1, move || {
+                if !enable_heap_profiling {
+                    return Err(warp_utils::reject::custom_not_found(
+                        "heap profiling is not enabled, \
+                        see the --http-enable-heap-profiling CLI flag"
+                            .to_string(),
+                    ));
+                }
+                let profile = malloc_utils::dump_heap_profile()
+                    .map_err(warp_utils::reject::custom_server_error)?;
+                Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/octet-stream")
+                    .body(profile)
+                    .map_err(|e| {
+                        warp_utils::reject::custom_server_error(format!(
+                            "failed to create response: {}",
+                            e
+                        ))
+                    })
+            })
+        });
+
+    // The GraphQL schema is built once and shared by all requests.
+    let graphql_schema = ctx
+        .chain
+        .clone()
+        .filter(|_| config.enable_graphql)
+        .map(graphql::schema);
+
+    // POST lighthouse/graphql
+    let post_lighthouse_graphql = warp::path("lighthouse")
+        .and(warp::path("graphql"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(warp::any().map(move || graphql_schema.clone()))
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |request: async_graphql::Request,
+             schema: Option<graphql::GraphQLSchema<T>>,
+             task_spawner: TaskSpawner| {
+                task_spawner.spawn_async(Priority::P1, async move {
+                    let schema = schema.ok_or_else(|| {
+                        warp_utils::reject::custom_not_found(
+                            "GraphQL is not enabled, see the --http-graphql CLI flag".to_string(),
+                        )
+                    })?;
+                    let response = graphql::execute(&schema, request).await;
+                    Ok::<_, warp::Rejection>(warp::reply::json(&response).into_response())
+                })
+            },
+        );
+
     let database_path = warp::path("lighthouse").and(warp::path("database"));
 
     // GET lighthouse/database/info
@@ -3547,16 +4107,28 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("info"))
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| blocking_json_task(move || database::info(chain)));
+        .and(db_paths_filter)
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |chain: Arc<BeaconChain<T>>,
+             (db_path, freezer_db_path): (Option<PathBuf>, Option<PathBuf>),
+             task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    database::info(chain, db_path, freezer_db_path)
+                })
+            },
+        );
 
     // POST lighthouse/database/reconstruct
     let post_lighthouse_database_reconstruct = database_path
         .and(warp::path("reconstruct"))
         .and(warp::path::end())
+        .and(privileged_filter.clone())
         .and(not_while_syncing_filter)
         .and(chain_filter.clone())
-        .and_then(|chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
+        .and(task_spawner_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
                 chain.store_migrator.process_reconstruction();
                 Ok("success")
             })
@@ -3566,20 +4138,25 @@ pub fn serve<T: BeaconChainTypes>(
     let post_lighthouse_database_historical_blocks = database_path
         .and(warp::path("historical_blocks"))
         .and(warp::path::end())
+        .and(privileged_filter.clone())
         .and(warp::body::json())
         .and(chain_filter.clone())
         .and(log_filter.clone())
+        .and(task_spawner_filter.clone())
         .and_then(
             |blocks: Vec<Arc<SignedBlindedBeaconBlock<T::EthSpec>>>,
              chain: Arc<BeaconChain<T>>,
-             log: Logger| {
+             log: Logger,
+             task_spawner: TaskSpawner| {
                 info!(
                     log,
                     "Importing historical blocks";
                     "count" => blocks.len(),
                     "source" => "http_api"
                 );
-                blocking_json_task(move || database::historical_blocks(chain, blocks))
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    database::historical_blocks(chain, blocks)
+                })
             },
         );
 
@@ -3591,8 +4168,11 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(chain_filter.clone())
         .and(log_filter.clone())
-        .and_then(|query, chain, log| {
-            blocking_json_task(move || block_rewards::get_block_rewards(query, chain, log))
+        .and(task_spawner_filter.clone())
+        .and_then(|query, chain, log, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
+                block_rewards::get_block_rewards(query, chain, log)
+            })
         });
 
     // POST lighthouse/analysis/block_rewards
@@ -3603,8 +4183,11 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path::end())
         .and(chain_filter.clone())
         .and(log_filter.clone())
-        .and_then(|blocks, chain, log| {
-            blocking_json_task(move || block_rewards::compute_block_rewards(blocks, chain, log))
+        .and(task_spawner_filter.clone())
+        .and_then(|blocks, chain, log, task_spawner: TaskSpawner| {
+            task_spawner.blocking_json_task(Priority::P1, move || {
+                block_rewards::compute_block_rewards(blocks, chain, log)
+            })
         });
 
     // GET lighthouse/analysis/attestation_performance/{index}
@@ -3615,11 +4198,30 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::query::<eth2::lighthouse::AttestationPerformanceQuery>())
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|target, query, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                attestation_performance::get_attestation_performance(target, query, chain)
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |target, query, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    attestation_performance::get_attestation_performance(target, query, chain)
+                })
+            },
+        );
+
+    // POST lighthouse/analysis/attestation_performance
+    let post_lighthouse_attestation_performance = warp::path("lighthouse")
+        .and(warp::path("analysis"))
+        .and(warp::path("attestation_performance"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(chain_filter.clone())
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |request, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    attestation_performance::post_attestation_performance(request, chain)
+                })
+            },
+        );
 
     // GET lighthouse/analysis/block_packing_efficiency
     let get_lighthouse_block_packing_efficiency = warp::path("lighthouse")
@@ -3628,11 +4230,14 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::query::<eth2::lighthouse::BlockPackingEfficiencyQuery>())
         .and(warp::path::end())
         .and(chain_filter.clone())
-        .and_then(|query, chain: Arc<BeaconChain<T>>| {
-            blocking_json_task(move || {
-                block_packing_efficiency::get_block_packing_efficiency(query, chain)
-            })
-        });
+        .and(task_spawner_filter.clone())
+        .and_then(
+            |query, chain: Arc<BeaconChain<T>>, task_spawner: TaskSpawner| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    block_packing_efficiency::get_block_packing_efficiency(query, chain)
+                })
+            },
+        );
 
     // GET lighthouse/merge_readiness
     let get_lighthouse_merge_readiness = warp::path("lighthouse")
@@ -3647,6 +4252,41 @@ pub fn serve<T: BeaconChainTypes>(
             )
         });
 
+    // GET lighthouse/events/ws
+    let get_lighthouse_events_ws = warp::path("lighthouse")
+        .and(warp::path("events"))
+        .and(warp::path("ws"))
+        .and(warp::path::end())
+        .and(multi_key_query::<api_types::WebSocketEventQuery>())
+        .and(warp::ws())
+        .and(chain_filter.clone())
+        .and(log_filter.clone())
+        .and_then(
+            |query_res: Result<api_types::WebSocketEventQuery, warp::Rejection>,
+             ws: warp::ws::Ws,
+             chain: Arc<BeaconChain<T>>,
+             log: Logger| async move {
+                let query = query_res?;
+                let event_handler = chain.event_handler.as_ref().ok_or_else(|| {
+                    warp_utils::reject::custom_server_error(
+                        "event handler was not initialized".to_string(),
+                    )
+                })?;
+                let subscription = events::WebSocketSubscription::new(
+                    event_handler,
+                    query.topics,
+                    query
+                        .validator_indices
+                        .map(|indices| indices.into_iter().collect()),
+                );
+
+                Ok::<_, warp::Rejection>(
+                    ws.on_upgrade(move |socket| events::serve_websocket(socket, subscription, log))
+                        .into_response(),
+                )
+            },
+        );
+
     let get_events = eth_v1
         .and(warp::path("events"))
         .and(warp::path::end())
@@ -3662,34 +4302,7 @@ pub fn serve<T: BeaconChainTypes>(
 
                     if let Some(event_handler) = chain.event_handler.as_ref() {
                         for topic in topics.topics {
-                            let receiver = match topic {
-                                api_types::EventTopic::Head => event_handler.subscribe_head(),
-                                api_types::EventTopic::Block => event_handler.subscribe_block(),
-                                api_types::EventTopic::Attestation => {
-                                    event_handler.subscribe_attestation()
-                                }
-                                api_types::EventTopic::VoluntaryExit => {
-                                    event_handler.subscribe_exit()
-                                }
-                                api_types::EventTopic::FinalizedCheckpoint => {
-                                    event_handler.subscribe_finalized()
-                                }
-                                api_types::EventTopic::ChainReorg => {
-                                    event_handler.subscribe_reorgs()
-                                }
-                                api_types::EventTopic::ContributionAndProof => {
-                                    event_handler.subscribe_contributions()
-                                }
-                                api_types::EventTopic::PayloadAttributes => {
-                                    event_handler.subscribe_payload_attributes()
-                                }
-                                api_types::EventTopic::LateHead => {
-                                    event_handler.subscribe_late_head()
-                                }
-                                api_types::EventTopic::BlockReward => {
-                                    event_handler.subscribe_block_reward()
-                                }
-                            };
+                            let receiver = events::subscribe(event_handler, topic);
 
                             receivers.push(BroadcastStream::new(receiver).map(|msg| {
                                 match msg {
@@ -3724,6 +4337,7 @@ pub fn serve<T: BeaconChainTypes>(
     // Define the ultimate set of routes that will be provided to the server.
     // Use `uor` rather than `or` in order to simplify types (see `UnifyingOrFilter`).
     let routes = warp::get()
+        .and(rate_limit_filter.clone())
         .and(
             get_beacon_genesis
                 .uor(get_beacon_state_root)
@@ -3747,6 +4361,10 @@ pub fn serve<T: BeaconChainTypes>(
                 .uor(get_beacon_pool_voluntary_exits)
                 .uor(get_beacon_pool_bls_to_execution_changes)
                 .uor(get_beacon_deposit_snapshot)
+                .uor(get_beacon_light_client_bootstrap)
+                .uor(get_beacon_light_client_updates)
+                .uor(get_beacon_light_client_finality_update)
+                .uor(get_beacon_light_client_optimistic_update)
                 .uor(get_beacon_rewards_blocks)
                 .uor(get_config_fork_schedule)
                 .uor(get_config_spec)
@@ -3768,12 +4386,15 @@ pub fn serve<T: BeaconChainTypes>(
                 .uor(get_validator_aggregate_attestation)
                 .uor(get_validator_sync_committee_contribution)
                 .uor(get_lighthouse_health)
+                .uor(get_lighthouse_health_node)
                 .uor(get_lighthouse_ui_health)
                 .uor(get_lighthouse_ui_validator_count)
                 .uor(get_lighthouse_syncing)
                 .uor(get_lighthouse_nat)
+                .uor(get_lighthouse_nat_ports)
                 .uor(get_lighthouse_peers)
                 .uor(get_lighthouse_peers_connected)
+                .uor(get_lighthouse_peers_access_list)
                 .uor(get_lighthouse_proto_array)
                 .uor(get_lighthouse_validator_inclusion_global)
                 .uor(get_lighthouse_validator_inclusion)
@@ -3782,18 +4403,24 @@ pub fn serve<T: BeaconChainTypes>(
                 .uor(get_lighthouse_eth1_deposit_cache)
                 .uor(get_lighthouse_beacon_states_ssz)
                 .uor(get_lighthouse_staking)
+                .uor(get_lighthouse_debug_heap)
                 .uor(get_lighthouse_database_info)
                 .uor(get_lighthouse_block_rewards)
                 .uor(get_lighthouse_attestation_performance)
                 .uor(get_lighthouse_block_packing_efficiency)
                 .uor(get_lighthouse_merge_readiness)
                 .uor(get_events)
+                .uor(get_lighthouse_events_ws)
                 .recover(warp_utils::reject::handle_rejection),
         )
         .boxed()
         .uor(
-            warp::post().and(
-                post_beacon_blocks
+            warp::post().and(rate_limit_filter).and(
+                post_beacon_blocks_ssz
+                    .uor(post_beacon_blinded_blocks_ssz)
+                    .uor(post_beacon_state_validators)
+                    .uor(post_beacon_state_validator_balances)
+                    .uor(post_beacon_blocks)
                     .uor(post_beacon_blinded_blocks)
                     .uor(post_beacon_pool_attestations)
                     .uor(post_beacon_pool_attester_slashings)
@@ -3815,22 +4442,57 @@ pub fn serve<T: BeaconChainTypes>(
                     .uor(post_lighthouse_database_reconstruct)
                     .uor(post_lighthouse_database_historical_blocks)
                     .uor(post_lighthouse_block_rewards)
+                    .uor(post_lighthouse_attestation_performance)
                     .uor(post_lighthouse_ui_validator_metrics)
                     .uor(post_lighthouse_ui_validator_info)
+                    .uor(post_lighthouse_graphql)
+                    .uor(post_lighthouse_peers_access_list_add)
+                    .uor(post_lighthouse_peers_access_list_remove)
                     .recover(warp_utils::reject::handle_rejection),
             ),
         )
-        .recover(warp_utils::reject::handle_rejection)
-        .with(slog_logging(log.clone()))
+        .recover(warp_utils::reject::handle_rejection);
+
+    // Log each request once its response is ready, rather than with a `warp::log` wrapper, so that
+    // the size of the response is known.
+    let request_logger = Arc::new(RequestLogger::new(
+        log.clone(),
+        config.log_requests,
+        config.slow_request_threshold,
+    ));
+    let routes = warp::any()
+        .map(Instant::now)
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(routes)
+        .map(
+            move |start: Instant, method: warp::http::Method, path: warp::path::FullPath, reply| {
+                let response: warp::reply::Response = Reply::into_response(reply);
+                request_logger.log(&method, path.as_str(), start.elapsed(), &response);
+                response
+            },
+        )
         .with(prometheus_metrics())
+        .with(tracing_span())
         // Add a `Server` header.
         .map(|reply| warp::reply::with_header(reply, "Server", &version_with_platform()))
         .with(cors_builder.build())
         .boxed();
 
     let http_socket: SocketAddr = SocketAddr::new(config.listen_addr, config.listen_port);
-    let http_server: HttpServer = match config.tls_config {
-        Some(tls_config) => {
+    let http_server: HttpServer = match (config.unix_socket, config.tls_config) {
+        (Some(socket_path), _) => {
+            let incoming = bind_unix_socket(&socket_path)?;
+            let server =
+                warp::serve(routes).serve_incoming_with_graceful_shutdown(incoming, async {
+                    shutdown.await;
+                });
+
+            info!(log, "HTTP API is being served over a Unix socket"; "path" => ?socket_path);
+
+            (None, Box::pin(server))
+        }
+        (None, Some(tls_config)) => {
             let (socket, server) = warp::serve(routes)
                 .tls()
                 .cert_path(tls_config.cert)
@@ -3841,26 +4503,81 @@ pub fn serve<T: BeaconChainTypes>(
 
             info!(log, "HTTP API is being served over TLS";);
 
-            (socket, Box::pin(server))
+            (Some(socket), Box::pin(server))
         }
-        None => {
+        (None, None) => {
             let (socket, server) =
                 warp::serve(routes).try_bind_with_graceful_shutdown(http_socket, async {
                     shutdown.await;
                 })?;
-            (socket, Box::pin(server))
+            (Some(socket), Box::pin(server))
         }
     };
 
-    info!(
-        log,
-        "HTTP API started";
-        "listen_address" => %http_server.0,
-    );
+    if let Some(listen_address) = http_server.0 {
+        info!(
+            log,
+            "HTTP API started";
+            "listen_address" => %listen_address,
+        );
+    }
 
     Ok(http_server)
 }
 
+/// Bind a Unix domain socket at `path`, replacing a socket left behind by a previous run.
+///
+/// The socket is only accessible to the user and group of this process. It is bound inside a
+/// private directory and only moved to `path` once its permissions have been restricted, so it is
+/// never reachable with the permissions given by the process umask.
+#[cfg(unix)]
+fn bind_unix_socket(path: &Path) -> Result<tokio_stream::wrappers::UnixListenerStream, Error> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(Error::UnixSocket(format!(
+                "{:?} already exists and is not a socket",
+                path
+            )));
+        }
+        std::fs::remove_file(path)
+            .map_err(|e| Error::UnixSocket(format!("Unable to remove {:?}: {}", path, e)))?;
+    }
+
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let private_dir = parent.join(format!(".lighthouse-http-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&private_dir);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .map_err(|e| Error::UnixSocket(format!("Unable to create {:?}: {}", private_dir, e)))?;
+
+    let private_path = private_dir.join("http.sock");
+    let result = tokio::net::UnixListener::bind(&private_path).and_then(|listener| {
+        std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o660))?;
+        std::fs::rename(&private_path, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private_dir);
+
+    let listener =
+        result.map_err(|e| Error::UnixSocket(format!("Unable to bind {:?}: {}", path, e)))?;
+    Ok(tokio_stream::wrappers::UnixListenerStream::new(listener))
+}
+
+#[cfg(not(unix))]
+fn bind_unix_socket(
+    _path: &Path,
+) -> Result<futures::stream::Empty<Result<tokio::net::TcpStream, std::io::Error>>, Error> {
+    Err(Error::Other(
+        "Unix sockets are not supported on this platform".to_string(),
+    ))
+}
+
 /// Publish a message to the libp2p pubsub network.
 fn publish_pubsub_message<T: EthSpec>(
     network_tx: &UnboundedSender<NetworkMessage<T>>,
@@ -3886,3 +4603,11 @@ fn publish_network_message<T: EthSpec>(
         ))
     })
 }
+
+/// Returns the entries of `access_list` as served by the HTTP API.
+fn peer_access_list(access_list: &AccessList) -> eth2::lighthouse::PeerAccessList {
+    eth2::lighthouse::PeerAccessList {
+        allowlist: access_list.allowlist(),
+        denylist: access_list.denylist(),
+    }
+}
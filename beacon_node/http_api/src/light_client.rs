@@ -0,0 +1,103 @@
+//! Handlers for the light client endpoints, which serve the data light clients use to follow the
+//! chain. They require the light client server to be enabled with `--light-client-server`.
+
+use beacon_chain::{
+    light_client_server_cache::MAX_REQUEST_LIGHT_CLIENT_UPDATES, BeaconChain, BeaconChainTypes,
+};
+use types::{
+    ForkVersionedResponse, Hash256, LightClientBootstrap, LightClientFinalityUpdate,
+    LightClientOptimisticUpdate, LightClientUpdate, Slot,
+};
+
+fn check_light_client_server<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+) -> Result<(), warp::Rejection> {
+    if chain.config.enable_light_client_server {
+        Ok(())
+    } else {
+        Err(warp_utils::reject::custom_not_found(
+            "the light client server is disabled, enable it with --light-client-server".to_string(),
+        ))
+    }
+}
+
+/// Wrap `data` with the name of the fork at `slot`, the slot of its attested header.
+fn fork_versioned<T: BeaconChainTypes, D>(
+    chain: &BeaconChain<T>,
+    slot: Slot,
+    data: D,
+) -> ForkVersionedResponse<D> {
+    ForkVersionedResponse {
+        version: Some(chain.spec.fork_name_at_slot::<T::EthSpec>(slot)),
+        data,
+    }
+}
+
+pub fn get_light_client_bootstrap<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    block_root: Hash256,
+) -> Result<ForkVersionedResponse<LightClientBootstrap<T::EthSpec>>, warp::Rejection> {
+    check_light_client_server(chain)?;
+
+    let block = chain
+        .get_blinded_block(&block_root)
+        .map_err(warp_utils::reject::beacon_chain_error)?
+        .ok_or_else(|| {
+            warp_utils::reject::custom_not_found(format!("beacon block {:?}", block_root))
+        })?;
+    let mut state = chain
+        .get_state(&block.state_root(), Some(block.slot()))
+        .map_err(warp_utils::reject::beacon_chain_error)?
+        .ok_or_else(|| {
+            warp_utils::reject::custom_not_found(format!("state of beacon block {:?}", block_root))
+        })?;
+    state.initialize_tree_hash_cache();
+
+    let bootstrap = LightClientBootstrap::from_beacon_state(&mut state).map_err(|e| {
+        warp_utils::reject::custom_bad_request(format!("unable to create bootstrap: {:?}", e))
+    })?;
+    Ok(fork_versioned(chain, block.slot(), bootstrap))
+}
+
+pub fn get_light_client_updates<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    start_period: u64,
+    count: u64,
+) -> Result<Vec<ForkVersionedResponse<LightClientUpdate<T::EthSpec>>>, warp::Rejection> {
+    check_light_client_server(chain)?;
+
+    Ok(chain
+        .light_client_server_cache
+        .get_updates(start_period, count.min(MAX_REQUEST_LIGHT_CLIENT_UPDATES))
+        .into_iter()
+        .map(|update| fork_versioned(chain, update.attested_header.slot, update))
+        .collect())
+}
+
+pub fn get_light_client_finality_update<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+) -> Result<ForkVersionedResponse<LightClientFinalityUpdate<T::EthSpec>>, warp::Rejection> {
+    check_light_client_server(chain)?;
+
+    let update = chain
+        .light_client_server_cache
+        .get_latest_finality_update()
+        .ok_or_else(|| {
+            warp_utils::reject::custom_not_found("no finality update is available".to_string())
+        })?;
+    Ok(fork_versioned(chain, update.attested_header.slot, update))
+}
+
+pub fn get_light_client_optimistic_update<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+) -> Result<ForkVersionedResponse<LightClientOptimisticUpdate<T::EthSpec>>, warp::Rejection> {
+    check_light_client_server(chain)?;
+
+    let update = chain
+        .light_client_server_cache
+        .get_latest_optimistic_update()
+        .ok_or_else(|| {
+            warp_utils::reject::custom_not_found("no optimistic update is available".to_string())
+        })?;
+    Ok(fork_versioned(chain, update.attested_header.slot, update))
+}
@@ -17,6 +17,17 @@ lazy_static::lazy_static! {
         &["path"]
     );
 
+    pub static ref HTTP_API_RATE_LIMITED_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "http_api_rate_limited_total",
+        "Count of HTTP requests rejected by the rate limiter",
+        &["class"]
+    );
+    pub static ref HTTP_API_REQUEST_QUEUE_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "http_api_request_queue_times",
+        "Duration that HTTP requests wait for a beacon processor worker",
+        &["priority"]
+    );
+
     pub static ref HTTP_API_BEACON_PROPOSER_CACHE_TIMES: Result<Histogram> = try_create_histogram(
         "http_api_beacon_proposer_cache_build_times",
         "Duration to process HTTP requests per path",
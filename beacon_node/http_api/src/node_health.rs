@@ -0,0 +1,94 @@
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::lighthouse::NodeHealth;
+use lighthouse_network::NetworkGlobals;
+use slot_clock::SlotClock;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The node is unhealthy if there is less than this much free space for its database.
+pub const MIN_FREE_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Observes the health of the node.
+///
+/// The node is considered unhealthy if it is not synced, its head is more than `sync_tolerance`
+/// slots behind the wall clock, it has no peers, its eth1 or execution endpoints are not ready or
+/// the filesystem of its data directory is almost full. If the free space can't be read it is
+/// reported as unknown, rather than failing the check. The filesystem and database are read on a
+/// blocking thread.
+pub async fn observe_node_health<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+    data_dir: PathBuf,
+    sync_tolerance: u64,
+) -> NodeHealth {
+    let mut issues = vec![];
+
+    let sync_state = network_globals.sync_state();
+    if !sync_state.is_synced() {
+        issues.push(format!("node is not synced: {}", sync_state));
+    }
+
+    let head_slot = chain.canonical_head.cached_head().head_slot();
+    let current_slot = chain.slot_clock.now_or_genesis().unwrap_or(head_slot);
+    let head_distance = current_slot.saturating_sub(head_slot).as_u64();
+    if head_distance > sync_tolerance {
+        issues.push(format!("head is {} slots behind", head_distance));
+    }
+
+    let peer_count = network_globals.connected_peers();
+    if peer_count == 0 {
+        issues.push("no connected peers".to_string());
+    }
+
+    let eth1_connected = chain.eth1_chain.as_ref().map(|eth1| {
+        eth1.sync_status(chain.genesis_time, Some(current_slot), &chain.spec)
+            .map_or(false, |status| status.lighthouse_is_cached_and_ready)
+    });
+    if eth1_connected == Some(false) {
+        issues.push("eth1 deposit cache is not ready".to_string());
+    }
+
+    let execution_engine_synced = match &chain.execution_layer {
+        Some(execution_layer) => Some(execution_layer.is_synced().await),
+        None => None,
+    };
+    if execution_engine_synced == Some(false) {
+        issues.push("execution engine is offline or syncing".to_string());
+    }
+
+    // Reading the free space and the schema version blocks on the filesystem and the database.
+    let store = chain.store.clone();
+    let read_disk = move || {
+        let disk_bytes_free = fs2::available_space(&data_dir).ok();
+        let db_schema_version = store
+            .load_schema_version()
+            .ok()
+            .flatten()
+            .map(|version| version.as_u64());
+        (disk_bytes_free, db_schema_version)
+    };
+    let (disk_bytes_free, db_schema_version) = match chain
+        .task_executor
+        .spawn_blocking_handle(read_disk, "node_health_read_disk")
+    {
+        Some(handle) => handle.await.unwrap_or_default(),
+        None => (None, None),
+    };
+    if let Some(disk_bytes_free) = disk_bytes_free.filter(|free| *free < MIN_FREE_DISK_BYTES) {
+        issues.push(format!("only {} bytes of disk space free", disk_bytes_free));
+    }
+
+    NodeHealth {
+        healthy: issues.is_empty(),
+        issues,
+        sync_state,
+        head_slot,
+        current_slot,
+        head_distance,
+        peer_count,
+        eth1_connected,
+        execution_engine_synced,
+        disk_bytes_free,
+        db_schema_version,
+    }
+}
@@ -6,6 +6,8 @@ use beacon_chain::{
     BeaconChain, BeaconChainError, BeaconChainTypes, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 use eth2::types::{self as api_types};
+use lru::LruCache;
+use parking_lot::Mutex;
 use safe_arith::SafeArith;
 use slog::{debug, Logger};
 use slot_clock::SlotClock;
@@ -15,10 +17,30 @@ use types::{CloneConfig, Epoch, EthSpec, Hash256, Slot};
 /// The struct that is returned to the requesting HTTP client.
 type ApiDuties = api_types::DutiesResponse<Vec<api_types::ProposerData>>;
 
+/// The number of next-epoch proposer duties to cache, enough for the heads of a few forks.
+const NEXT_EPOCH_CACHE_SIZE: usize = 4;
+
+/// Caches the proposer duties of the next epoch, keyed by the epoch and the dependent root.
+///
+/// These are kept apart from the `beacon_proposer_cache`, since the duties of the next epoch
+/// change with every new head and would otherwise wash out the duties used for block processing.
+pub struct NextEpochProposerCache {
+    cache: Mutex<LruCache<(Epoch, Hash256), Vec<usize>>>,
+}
+
+impl Default for NextEpochProposerCache {
+    fn default() -> Self {
+        Self {
+            cache: Mutex::new(LruCache::new(NEXT_EPOCH_CACHE_SIZE)),
+        }
+    }
+}
+
 /// Handles a request from the HTTP API for proposer duties.
 pub fn proposer_duties<T: BeaconChainTypes>(
     request_epoch: Epoch,
     chain: &BeaconChain<T>,
+    next_epoch_cache: &NextEpochProposerCache,
     log: &Logger,
 ) -> Result<ApiDuties, warp::reject::Rejection> {
     let current_epoch = chain
@@ -55,9 +77,37 @@ pub fn proposer_duties<T: BeaconChainTypes>(
             .safe_add(1)
             .map_err(warp_utils::reject::arith_error)?
     {
+        // The duties of the next epoch only change when the head does, so serve them from the
+        // cache until then.
+        let (dependent_root, execution_optimistic) = head_dependent_root(request_epoch, chain)?;
+        let cached = next_epoch_cache
+            .cache
+            .lock()
+            .get(&(request_epoch, dependent_root))
+            .cloned();
+        if let Some(proposers) = cached {
+            return convert_to_api_response(
+                chain,
+                request_epoch,
+                dependent_root,
+                execution_optimistic,
+                proposers,
+            );
+        }
+
+        debug!(
+            log,
+            "Next epoch proposer cache miss";
+            "request_epoch" => request_epoch,
+            "dependent_root" => ?dependent_root,
+        );
         let (proposers, dependent_root, execution_status, _fork) =
             compute_proposer_duties_from_head(request_epoch, chain)
                 .map_err(warp_utils::reject::beacon_chain_error)?;
+        next_epoch_cache
+            .cache
+            .lock()
+            .put((request_epoch, dependent_root), proposers.clone());
         convert_to_api_response(
             chain,
             request_epoch,
@@ -94,6 +144,33 @@ fn try_proposer_duties_from_cache<T: BeaconChainTypes>(
     request_epoch: Epoch,
     chain: &BeaconChain<T>,
 ) -> Result<Option<ApiDuties>, warp::reject::Rejection> {
+    let (dependent_root, execution_optimistic) = head_dependent_root(request_epoch, chain)?;
+
+    chain
+        .beacon_proposer_cache
+        .lock()
+        .get_epoch::<T::EthSpec>(dependent_root, request_epoch)
+        .cloned()
+        .map(|indices| {
+            convert_to_api_response(
+                chain,
+                request_epoch,
+                dependent_root,
+                execution_optimistic,
+                indices.to_vec(),
+            )
+        })
+        .transpose()
+}
+
+/// Returns the root of the block which decides the proposers of `request_epoch` on the chain of
+/// the head, and whether the head is optimistic.
+///
+/// The `request_epoch` must not be earlier than the epoch of the head block.
+fn head_dependent_root<T: BeaconChainTypes>(
+    request_epoch: Epoch,
+    chain: &BeaconChain<T>,
+) -> Result<(Hash256, bool), warp::reject::Rejection> {
     let head = chain.canonical_head.cached_head();
     let head_block = &head.snapshot.beacon_block;
     let head_block_root = head.head_block_root();
@@ -121,21 +198,7 @@ fn try_proposer_duties_from_cache<T: BeaconChainTypes>(
         }
     };
 
-    chain
-        .beacon_proposer_cache
-        .lock()
-        .get_epoch::<T::EthSpec>(dependent_root, request_epoch)
-        .cloned()
-        .map(|indices| {
-            convert_to_api_response(
-                chain,
-                request_epoch,
-                dependent_root,
-                execution_optimistic,
-                indices.to_vec(),
-            )
-        })
-        .transpose()
+    Ok((dependent_root, execution_optimistic))
 }
 
 /// Compute the proposer duties using the head state, add the duties to the proposer cache and
@@ -0,0 +1,250 @@
+//! Per-IP rate limiting of HTTP API requests, so that a misbehaving client can't starve the node of
+//! the resources it needs to perform its duties.
+//!
+//! Each client has a token bucket for each class of endpoint, where a client is an IPv4 address or
+//! an IPv6 /64 prefix. The endpoints used by validator clients to perform their duties are never
+//! rate limited.
+
+use crate::metrics;
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use warp::filters::path::FullPath;
+use warp::Filter;
+
+/// The maximum number of buckets, above which the least recently used bucket is evicted.
+const MAX_BUCKETS: usize = 16_384;
+
+/// The interval at which the buckets of idle clients are pruned.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// The number of requests per second allowed from each IP, for standard endpoints.
+    pub requests_per_second: u64,
+    /// The number of requests per second allowed from each IP, for expensive endpoints.
+    pub expensive_requests_per_second: u64,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum EndpointClass {
+    Standard,
+    /// Endpoints which may load states or analyse the database, i.e. the `debug` and `lighthouse`
+    /// endpoints.
+    Expensive,
+}
+
+impl EndpointClass {
+    /// Returns the class of the endpoint at `path`, or `None` if it isn't rate limited.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let mut segments = path.trim_start_matches('/').split('/');
+        match (segments.next(), segments.next(), segments.next()) {
+            (Some("lighthouse"), _, _) => Some(EndpointClass::Expensive),
+            (Some("eth"), _, Some("debug")) => Some(EndpointClass::Expensive),
+            // Validator duties and the publishing of blocks and other messages.
+            (Some("eth"), _, Some("validator")) => None,
+            (Some("eth"), _, Some("beacon")) => match (segments.next(), segments.next()) {
+                (Some("pool"), _) => None,
+                (Some("blocks" | "blinded_blocks"), None) => None,
+                _ => Some(EndpointClass::Standard),
+            },
+            _ => Some(EndpointClass::Standard),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EndpointClass::Standard => "standard",
+            EndpointClass::Expensive => "expensive",
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    last_update: Instant,
+}
+
+type BucketKey = (IpAddr, EndpointClass);
+
+struct Buckets {
+    buckets: LruCache<BucketKey, Bucket>,
+    last_prune: Instant,
+}
+
+/// Token buckets for each client and endpoint class, which hold up to one second of requests.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+}
+
+/// Returns the address identifying the client at `ip`.
+///
+/// A single IPv6 host is usually assigned a whole /64, so IPv6 clients are identified by their /64
+/// prefix rather than their full address.
+fn client_address(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ipv4) => IpAddr::V4(ipv4),
+            None => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & !(u64::MAX as u128))),
+        },
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(Buckets {
+                buckets: LruCache::new(MAX_BUCKETS),
+                last_prune: Instant::now(),
+            }),
+        }
+    }
+
+    fn rate(&self, class: EndpointClass) -> f64 {
+        match class {
+            EndpointClass::Standard => self.config.requests_per_second as f64,
+            EndpointClass::Expensive => self.config.expensive_requests_per_second as f64,
+        }
+    }
+
+    /// Take a token from the bucket of `ip` for `class`, returning `false` if it's empty.
+    pub fn allows(&self, ip: IpAddr, class: EndpointClass, now: Instant) -> bool {
+        let rate = self.rate(class);
+        let key = (client_address(ip), class);
+        let mut buckets = self.buckets.lock();
+
+        if now.duration_since(buckets.last_prune) >= PRUNE_INTERVAL {
+            self.prune(&mut buckets.buckets, now);
+            buckets.last_prune = now;
+        }
+
+        let mut bucket = buckets.buckets.pop(&key).unwrap_or(Bucket {
+            tokens: rate,
+            last_update: now,
+        });
+        let elapsed = now.duration_since(bucket.last_update).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.last_update = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        buckets.buckets.put(key, bucket);
+        allowed
+    }
+
+    /// Remove the buckets which have refilled, i.e. those of clients which are idle.
+    fn prune(&self, buckets: &mut LruCache<BucketKey, Bucket>, now: Instant) {
+        let full = buckets
+            .iter()
+            .filter(|((_, class), bucket)| {
+                let rate = self.rate(*class);
+                bucket.tokens + now.duration_since(bucket.last_update).as_secs_f64() * rate >= rate
+            })
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in full {
+            buckets.pop(&key);
+        }
+    }
+
+    /// Returns a `warp` filter which rejects requests from clients which have exceeded their rate
+    /// limit.
+    ///
+    /// Requests without a remote address, i.e. those received over a Unix socket, are not limited.
+    pub fn filter(self: Arc<Self>) -> warp::filters::BoxedFilter<()> {
+        warp::addr::remote()
+            .and(warp::path::full())
+            .and_then(move |remote: Option<SocketAddr>, path: FullPath| {
+                let rate_limiter = self.clone();
+                async move {
+                    let (remote, class) = match (remote, EndpointClass::from_path(path.as_str())) {
+                        (Some(remote), Some(class)) => (remote, class),
+                        _ => return Ok(()),
+                    };
+                    if rate_limiter.allows(remote.ip(), class, Instant::now()) {
+                        Ok(())
+                    } else {
+                        metrics::inc_counter_vec(
+                            &metrics::HTTP_API_RATE_LIMITED_TOTAL,
+                            &[class.as_str()],
+                        );
+                        Err(warp_utils::reject::too_many_requests(format!(
+                            "rate limit for {} endpoints exceeded",
+                            class.as_str()
+                        )))
+                    }
+                }
+            })
+            .untuple_one()
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: RateLimitConfig = RateLimitConfig {
+        requests_per_second: 2,
+        expensive_requests_per_second: 1,
+    };
+
+    #[test]
+    fn ipv6_clients_are_limited_by_prefix() {
+        let rate_limiter = RateLimiter::new(CONFIG);
+        let now = Instant::now();
+        let class = EndpointClass::Standard;
+
+        assert!(rate_limiter.allows("2001:db8::1".parse().unwrap(), class, now));
+        assert!(rate_limiter.allows("2001:db8::2".parse().unwrap(), class, now));
+        assert!(!rate_limiter.allows("2001:db8::ffff:1".parse().unwrap(), class, now));
+        // Another /64 has its own bucket.
+        assert!(rate_limiter.allows("2001:db8:0:1::1".parse().unwrap(), class, now));
+    }
+
+    #[test]
+    fn number_of_buckets_is_bounded() {
+        let rate_limiter = RateLimiter::new(CONFIG);
+        let now = Instant::now();
+
+        for i in 0..MAX_BUCKETS as u32 + 1 {
+            let ip = IpAddr::V4(i.into());
+            assert!(rate_limiter.allows(ip, EndpointClass::Standard, now));
+        }
+        let buckets = rate_limiter.buckets.lock();
+        assert_eq!(buckets.buckets.len(), MAX_BUCKETS);
+        // The least recently used bucket was evicted.
+        assert!(!buckets
+            .buckets
+            .contains(&(IpAddr::V4(0.into()), EndpointClass::Standard)));
+    }
+
+    #[test]
+    fn idle_buckets_are_pruned() {
+        let rate_limiter = RateLimiter::new(CONFIG);
+        let now = Instant::now();
+        rate_limiter.buckets.lock().last_prune = now;
+        let idle = "192.0.2.1".parse().unwrap();
+        let busy = "192.0.2.2".parse().unwrap();
+        let class = EndpointClass::Standard;
+
+        assert!(rate_limiter.allows(idle, class, now));
+        // The buckets are only pruned once the interval has passed.
+        let later = now + PRUNE_INTERVAL;
+        assert!(rate_limiter.allows(busy, class, later - Duration::from_millis(100)));
+        assert_eq!(rate_limiter.buckets.lock().buckets.len(), 2);
+
+        assert!(rate_limiter.allows("192.0.2.3".parse().unwrap(), class, later));
+        let buckets = rate_limiter.buckets.lock();
+        assert!(!buckets.buckets.contains(&(idle, class)));
+        assert!(buckets.buckets.contains(&(busy, class)));
+    }
+}
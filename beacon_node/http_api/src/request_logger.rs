@@ -0,0 +1,85 @@
+//! Logs of the requests processed by the HTTP API, which help to find the clients and queries
+//! which are loading the node.
+
+use slog::{debug, info, warn, Logger};
+use std::time::Duration;
+use warp::http::{Method, StatusCode};
+use warp::hyper::body::HttpBody;
+use warp::reply::Response;
+
+pub struct RequestLogger {
+    log: Logger,
+    /// Log every request at `INFO`, rather than only at `DEBUG`.
+    log_requests: bool,
+    /// Log a warning for requests which take at least this long.
+    slow_request_threshold: Option<Duration>,
+}
+
+impl RequestLogger {
+    pub fn new(log: Logger, log_requests: bool, slow_request_threshold: Option<Duration>) -> Self {
+        Self {
+            log,
+            log_requests,
+            slow_request_threshold,
+        }
+    }
+
+    /// Log a request for `path` which took `elapsed` to produce `response`.
+    ///
+    /// The size of the response is unknown if its body is streamed, e.g. for server-sent events,
+    /// and `elapsed` doesn't include the time taken to stream it.
+    pub fn log(&self, method: &Method, path: &str, elapsed: Duration, response: &Response) {
+        let log = &self.log;
+        let status = response.status();
+        let size = response.body().size_hint().exact();
+
+        if let Some(threshold) = self
+            .slow_request_threshold
+            .filter(|threshold| elapsed >= *threshold)
+        {
+            warn!(
+                log,
+                "Slow HTTP API request";
+                "threshold" => ?threshold,
+                "size" => size,
+                "elapsed" => ?elapsed,
+                "status" => status.to_string(),
+                "path" => path,
+                "method" => method.to_string(),
+            );
+        } else if status != StatusCode::OK
+            && status != StatusCode::NOT_FOUND
+            && status != StatusCode::PARTIAL_CONTENT
+        {
+            warn!(
+                log,
+                "Error processing HTTP API request";
+                "size" => size,
+                "elapsed" => ?elapsed,
+                "status" => status.to_string(),
+                "path" => path,
+                "method" => method.to_string(),
+            );
+        } else if self.log_requests {
+            info!(
+                log,
+                "Processed HTTP API request";
+                "size" => size,
+                "elapsed" => ?elapsed,
+                "status" => status.to_string(),
+                "path" => path,
+                "method" => method.to_string(),
+            );
+        } else {
+            debug!(
+                log,
+                "Processed HTTP API request";
+                "size" => size,
+                "elapsed" => ?elapsed,
+                "status" => status.to_string(),
+                "path" => path,
+                "method" => method.to_string(),
+            );
+        }
+    }
+}
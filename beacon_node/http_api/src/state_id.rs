@@ -1,3 +1,4 @@
+use crate::task_spawner::Priority;
 use crate::ExecutionOptimistic;
 use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use eth2::types::StateId as CoreStateId;
@@ -18,6 +19,17 @@ impl StateId {
         Self(CoreStateId::Slot(slot))
     }
 
+    /// Return the priority of a request which reads from the state identified by `self`.
+    ///
+    /// Validator clients read the validators of the head state to perform their duties, and it is
+    /// always in memory, whereas other states may need to be loaded from disk.
+    pub fn priority(&self) -> Priority {
+        match self.0 {
+            CoreStateId::Head => Priority::P0,
+            _ => Priority::P1,
+        }
+    }
+
     /// Return the state root identified by `self`.
     pub fn root<T: BeaconChainTypes>(
         &self,
@@ -105,12 +117,22 @@ impl StateId {
             }
         };
 
-        let root = chain
+        let root = match chain
             .state_root_at_slot(slot)
             .map_err(warp_utils::reject::beacon_chain_error)?
-            .ok_or_else(|| {
-                warp_utils::reject::custom_not_found(format!("beacon state at slot {}", slot))
-            })?;
+        {
+            Some(root) => root,
+            // A state which isn't stored may be replayed, after which it is cached by its root.
+            None => chain
+                .store
+                .load_historic_state_by_replay(slot)
+                .map_err(BeaconChainError::DBError)
+                .map_err(warp_utils::reject::beacon_chain_error)?
+                .map(|(root, _)| root)
+                .ok_or_else(|| {
+                    warp_utils::reject::custom_not_found(format!("beacon state at slot {}", slot))
+                })?,
+        };
 
         Ok((root, execution_optimistic, finalized))
     }
@@ -0,0 +1,136 @@
+//! Scheduling of HTTP API handlers by the beacon processor, so that expensive queries can't delay
+//! the processing of blocks and attestations.
+//!
+//! Handlers wait for the beacon processor only once the rate limiter, the authorization check and
+//! the rest of their route's filters have accepted the request, so that requests which would be
+//! rejected never occupy the queues.
+//!
+//! Handlers which wait on the execution engine or a builder aren't scheduled at all, since they
+//! would hold a worker for as long as the round-trip takes.
+
+use crate::metrics;
+use network::{ApiRequest, ApiRequestPermit};
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::oneshot;
+use warp::reply::{Reply, Response};
+
+pub use network::ApiRequestPriority as Priority;
+
+/// Requests are rejected if the beacon processor hasn't scheduled them within this time, rather
+/// than waiting indefinitely whilst the node is busy with consensus messages.
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(12);
+
+fn priority_as_str(priority: Priority) -> &'static str {
+    match priority {
+        Priority::P0 => "p0",
+        Priority::P1 => "p1",
+    }
+}
+
+/// Runs the handlers of HTTP API requests once the beacon processor has scheduled them.
+///
+/// Handlers run immediately if the beacon processor isn't running.
+#[derive(Clone)]
+pub struct TaskSpawner {
+    api_request_send: Option<mpsc::Sender<ApiRequest>>,
+}
+
+impl TaskSpawner {
+    pub fn new(api_request_send: Option<mpsc::Sender<ApiRequest>>) -> Self {
+        Self { api_request_send }
+    }
+
+    /// Waits for the beacon processor to schedule a request with `priority`, returning a permit
+    /// which holds a worker until it is dropped.
+    async fn permit(
+        &self,
+        priority: Priority,
+    ) -> Result<Option<ApiRequestPermit>, warp::Rejection> {
+        let api_request_send = match &self.api_request_send {
+            Some(api_request_send) => api_request_send,
+            None => return Ok(None),
+        };
+
+        let _timer = metrics::start_timer_vec(
+            &metrics::HTTP_API_REQUEST_QUEUE_TIMES,
+            &[priority_as_str(priority)],
+        );
+        let (permit_tx, permit_rx) = oneshot::channel();
+        match api_request_send.try_send(ApiRequest {
+            priority,
+            permit_tx,
+        }) {
+            Ok(()) => (),
+            // The beacon processor has stopped, which only happens during shutdown.
+            Err(TrySendError::Closed(_)) => return Ok(None),
+            Err(TrySendError::Full(_)) => {
+                return Err(warp_utils::reject::service_unavailable(
+                    "too many requests are waiting to be processed".to_string(),
+                ))
+            }
+        }
+
+        match tokio::time::timeout(QUEUE_TIMEOUT, permit_rx).await {
+            Ok(Ok(permit)) => Ok(Some(permit)),
+            // The permit is dropped if the request didn't fit in its queue, or if no worker may
+            // serve requests of its priority.
+            Ok(Err(_)) => Err(warp_utils::reject::service_unavailable(
+                "the beacon processor is unable to serve the request".to_string(),
+            )),
+            // The request is abandoned, and the beacon processor frees its worker as soon as it
+            // finds that nothing is waiting for the permit.
+            Err(_) => Err(warp_utils::reject::service_unavailable(
+                "timed out waiting for the beacon processor".to_string(),
+            )),
+        }
+    }
+
+    /// Runs `func` on a blocking thread once the request has been scheduled, returning a
+    /// `warp::reply::Response`.
+    pub async fn blocking_response_task<F, T>(
+        self,
+        priority: Priority,
+        func: F,
+    ) -> Result<Response, warp::Rejection>
+    where
+        F: FnOnce() -> Result<T, warp::Rejection> + Send + 'static,
+        T: Reply + Send + 'static,
+    {
+        let _permit = self.permit(priority).await?;
+        warp_utils::task::blocking_response_task(func).await
+    }
+
+    /// Runs `func` on a blocking thread once the request has been scheduled, for use with `warp`
+    /// JSON responses.
+    pub async fn blocking_json_task<F, T>(
+        self,
+        priority: Priority,
+        func: F,
+    ) -> Result<Response, warp::Rejection>
+    where
+        F: FnOnce() -> Result<T, warp::Rejection> + Send + 'static,
+        T: Serialize + Send + 'static,
+    {
+        let _permit = self.permit(priority).await?;
+        warp_utils::task::blocking_json_task(func).await
+    }
+
+    /// Runs `future` once the request has been scheduled.
+    ///
+    /// The worker is held until `future` completes, so it must not wait on network I/O such as
+    /// requests to the execution engine or a builder.
+    pub async fn spawn_async<F, T>(
+        self,
+        priority: Priority,
+        future: F,
+    ) -> Result<T, warp::Rejection>
+    where
+        F: Future<Output = Result<T, warp::Rejection>>,
+    {
+        let _permit = self.permit(priority).await?;
+        future.await
+    }
+}
@@ -21,7 +21,7 @@ use lighthouse_network::{
     ConnectedPoint, Enr, NetworkGlobals, PeerId, PeerManager,
 };
 use logging::test_logger;
-use network::{NetworkReceivers, NetworkSenders};
+use network::{spawn_api_request_processor, ApiRequest, NetworkReceivers, NetworkSenders};
 use sensitive_url::SensitiveUrl;
 use slog::Logger;
 use std::future::Future;
@@ -29,7 +29,8 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 use store::MemoryStore;
-use tokio::sync::oneshot;
+use task_executor::TaskExecutor;
+use tokio::sync::{mpsc, oneshot};
 use types::{ChainSpec, EthSpec};
 
 pub const TCP_PORT: u16 = 42;
@@ -139,7 +140,65 @@ pub async fn create_api_server_on_port<T: BeaconChainTypes>(
     log: Logger,
     port: u16,
 ) -> ApiServer<T::EthSpec, impl Future<Output = ()>> {
-    let (network_senders, network_receivers) = NetworkSenders::new();
+    let config = Config {
+        enabled: true,
+        listen_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+        listen_port: port,
+        allow_origin: None,
+        tls_config: None,
+        allow_sync_stalled: false,
+        data_dir: std::path::PathBuf::from(DEFAULT_ROOT_DIR),
+        spec_fork_name: None,
+        enable_heap_profiling: false,
+        enable_graphql: false,
+        unix_socket: None,
+        api_token_path: None,
+        rate_limit: None,
+        log_requests: false,
+        slow_request_threshold: None,
+    };
+    create_api_server_with_config(chain, log, config).await
+}
+
+pub async fn create_api_server_with_config<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    log: Logger,
+    config: Config,
+) -> ApiServer<T::EthSpec, impl Future<Output = ()>> {
+    let (server, _) = create_api_server_inner(chain, log, config, None);
+    server
+}
+
+/// Create an API server whose requests are scheduled by a beacon processor with `max_workers`
+/// workers, returning the channel on which requests are scheduled alongside the server.
+pub async fn create_api_server_with_beacon_processor<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    log: Logger,
+    config: Config,
+    max_workers: usize,
+    executor: TaskExecutor,
+) -> (
+    ApiServer<T::EthSpec, impl Future<Output = ()>>,
+    mpsc::Sender<ApiRequest>,
+) {
+    let (server, api_request_send) =
+        create_api_server_inner(chain, log, config, Some((max_workers, executor)));
+    (
+        server,
+        api_request_send.expect("beacon processor should be running"),
+    )
+}
+
+fn create_api_server_inner<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    log: Logger,
+    config: Config,
+    beacon_processor: Option<(usize, TaskExecutor)>,
+) -> (
+    ApiServer<T::EthSpec, impl Future<Output = ()>>,
+    Option<mpsc::Sender<ApiRequest>>,
+) {
+    let (mut network_senders, network_receivers) = NetworkSenders::new();
 
     // Default metadata
     let meta_data = MetaData::V2(MetaDataV2 {
@@ -156,12 +215,13 @@ pub async fn create_api_server_on_port<T: BeaconChainTypes>(
         meta_data,
         vec![],
         false,
+        Default::default(),
         &log,
     ));
 
     // Only a peer manager can add peers, so we create a dummy manager.
-    let config = lighthouse_network::peer_manager::config::Config::default();
-    let mut pm = PeerManager::new(config, network_globals.clone(), &log).unwrap();
+    let pm_config = lighthouse_network::peer_manager::config::Config::default();
+    let mut pm = PeerManager::new(pm_config, network_globals.clone(), &log).unwrap();
 
     // add a peer
     let peer_id = PeerId::random();
@@ -180,24 +240,29 @@ pub async fn create_api_server_on_port<T: BeaconChainTypes>(
     }));
     *network_globals.sync_state.write() = SyncState::Synced;
 
+    let api_request_send = beacon_processor.map(|(max_workers, executor)| {
+        let api_request_send = spawn_api_request_processor(
+            &chain,
+            network_globals.clone(),
+            max_workers,
+            executor,
+            log.clone(),
+        );
+        network_senders.set_api_request_send(api_request_send.clone());
+        api_request_send
+    });
+
     let eth1_service =
         eth1::Service::new(eth1::Config::default(), log.clone(), chain.spec.clone()).unwrap();
 
     let ctx = Arc::new(Context {
-        config: Config {
-            enabled: true,
-            listen_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-            listen_port: port,
-            allow_origin: None,
-            tls_config: None,
-            allow_sync_stalled: false,
-            data_dir: std::path::PathBuf::from(DEFAULT_ROOT_DIR),
-            spec_fork_name: None,
-        },
+        config,
         chain: Some(chain),
         network_senders: Some(network_senders),
         network_globals: Some(network_globals),
         eth1_service: Some(eth1_service),
+        db_path: None,
+        freezer_db_path: None,
         log,
     });
 
@@ -207,13 +272,15 @@ pub async fn create_api_server_on_port<T: BeaconChainTypes>(
         let _ = shutdown_rx.await;
     };
     let (listening_socket, server) = crate::serve(ctx, server_shutdown).unwrap();
+    let listening_socket = listening_socket.expect("should listen on TCP");
 
-    ApiServer {
+    let server = ApiServer {
         server,
         listening_socket,
         shutdown_tx,
         network_rx: network_receivers,
         local_enr: enr,
         external_peer_id: peer_id,
-    }
+    };
+    (server, api_request_send)
 }
@@ -0,0 +1,252 @@
+use crate::balances_cache::BalancesCache;
+use crate::state_id::StateId;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::types::{
+    ExecutionOptimisticFinalizedPageResponse, PageQuery, StateId as CoreStateId,
+    ValidatorBalanceData, ValidatorData, ValidatorId, ValidatorStatus,
+};
+use eth2_serde_utils::quoted_u64::Quoted;
+use std::collections::HashMap;
+use std::sync::Arc;
+use types::{BeaconState, PublicKeyBytes};
+
+/// Returns the indices of the validators with the given `ids` in a registry of `registry_len`
+/// validators, in ascending order and without duplicates.
+///
+/// Validators which are unknown, or which were added to the registry later, are ignored.
+fn resolve_validator_ids<F>(
+    ids: &[ValidatorId],
+    registry_len: usize,
+    mut pubkey_index: F,
+) -> Result<Vec<usize>, warp::Rejection>
+where
+    F: FnMut(&PublicKeyBytes) -> Result<Option<usize>, warp::Rejection>,
+{
+    let mut indices = Vec::with_capacity(ids.len());
+    for id in ids {
+        let index = match id {
+            ValidatorId::PublicKey(pubkey) => pubkey_index(pubkey)?,
+            ValidatorId::Index(index) => Some(*index as usize),
+        };
+        if let Some(index) = index.filter(|index| *index < registry_len) {
+            indices.push(index);
+        }
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(indices)
+}
+
+/// Returns the indices of the validators in `state` with the given `ids`, in ascending order and
+/// without duplicates, or `None` if no `ids` are given.
+///
+/// Public keys are resolved using the validator pubkey cache, rather than by scanning the validator
+/// registry of `state`. The cache follows the head, so if `state` is on another fork the validator
+/// at the cached index may differ, in which case the registry of `state` is indexed instead.
+fn validator_indices<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    state: &BeaconState<T::EthSpec>,
+    ids: Option<&[ValidatorId]>,
+) -> Result<Option<Vec<usize>>, warp::Rejection> {
+    let ids = match ids {
+        Some(ids) => ids,
+        None => return Ok(None),
+    };
+
+    // Built at most once, the first time the pubkey cache can't be used.
+    let mut state_indices: Option<HashMap<PublicKeyBytes, usize>> = None;
+    resolve_validator_ids(ids, state.validators().len(), |pubkey| {
+        let cached_index = chain
+            .validator_index(pubkey)
+            .map_err(warp_utils::reject::beacon_chain_error)?;
+        Ok(
+            match cached_index.and_then(|index| state.validators().get(index)) {
+                Some(validator) if validator.pubkey == *pubkey => cached_index,
+                _ => state_indices
+                    .get_or_insert_with(|| {
+                        state
+                            .validators()
+                            .iter()
+                            .enumerate()
+                            .map(|(index, validator)| (validator.pubkey, index))
+                            .collect()
+                    })
+                    .get(pubkey)
+                    .copied(),
+            },
+        )
+    })
+    .map(Some)
+}
+
+/// Returns the items of the validators with `indices`, in ascending order, which are in `page`.
+///
+/// `item` returns the item of a validator, or `None` if it should be left out. Also returns the
+/// `page` of the next page, if the items don't all fit in this one.
+fn paginate<U>(
+    indices: impl Iterator<Item = usize>,
+    page: PageQuery,
+    item: impl Fn(usize) -> Option<U>,
+) -> Result<(Vec<U>, Option<u64>), warp::Rejection> {
+    let first_index = page.page.unwrap_or(0);
+    let mut items = indices
+        .skip_while(|index| (*index as u64) < first_index)
+        .filter_map(|index| item(index).map(|item| (index, item)));
+    match page.limit {
+        None => Ok((items.map(|(_, item)| item).collect(), None)),
+        Some(0) => Err(warp_utils::reject::custom_bad_request(
+            "limit must be at least 1".to_string(),
+        )),
+        Some(limit) => {
+            let data = items
+                .by_ref()
+                .take(limit as usize)
+                .map(|(_, item)| item)
+                .collect();
+            let next_page = items.next().map(|(index, _)| index as u64);
+            Ok((data, next_page))
+        }
+    }
+}
+
+pub fn get_beacon_state_validators<T: BeaconChainTypes>(
+    state_id: StateId,
+    chain: &BeaconChain<T>,
+    ids: Option<&[ValidatorId]>,
+    statuses: Option<&[ValidatorStatus]>,
+    page: PageQuery,
+) -> Result<ExecutionOptimisticFinalizedPageResponse<Vec<ValidatorData>>, warp::Rejection> {
+    let ((data, next_page), execution_optimistic, finalized) = state_id
+        .map_state_and_execution_optimistic_and_finalized(
+            chain,
+            |state, execution_optimistic, finalized| {
+                let epoch = state.current_epoch();
+                let far_future_epoch = chain.spec.far_future_epoch;
+
+                let validator_data = |index: usize| {
+                    let validator = state.validators().get(index)?;
+                    let balance = *state.balances().get(index)?;
+                    let status =
+                        ValidatorStatus::from_validator(validator, epoch, far_future_epoch);
+
+                    let status_matches = statuses.map_or(true, |statuses| {
+                        statuses.contains(&status) || statuses.contains(&status.superstatus())
+                    });
+
+                    status_matches.then(|| ValidatorData {
+                        index: index as u64,
+                        balance,
+                        status,
+                        validator: validator.clone(),
+                    })
+                };
+
+                let data = match validator_indices(chain, state, ids)? {
+                    Some(indices) => paginate(indices.into_iter(), page, validator_data)?,
+                    None => paginate(0..state.validators().len(), page, validator_data)?,
+                };
+                Ok((data, execution_optimistic, finalized))
+            },
+        )?;
+
+    Ok(ExecutionOptimisticFinalizedPageResponse {
+        data,
+        execution_optimistic: Some(execution_optimistic),
+        finalized: Some(finalized),
+        next_page: next_page.map(|value| Quoted { value }),
+    })
+}
+
+/// Returns the balances of the state identified by `state_id`, and the indices of the validators
+/// with `ids` (or `None` for all validators).
+///
+/// The balances of states other than the head are cached in `balances_cache`. Public keys can only
+/// be resolved without the state's registry if it is finalized, as the validator pubkey cache then
+/// agrees with it, so requests for other states by public key load the state.
+fn balances_and_indices<T: BeaconChainTypes>(
+    state_id: &StateId,
+    chain: &BeaconChain<T>,
+    balances_cache: &BalancesCache,
+    ids: Option<&[ValidatorId]>,
+) -> Result<(Arc<Vec<u64>>, Option<Vec<usize>>, bool, bool), warp::Rejection> {
+    let (state_root, execution_optimistic, finalized) = state_id.root(chain)?;
+    let only_indices = ids.map_or(true, |ids| {
+        ids.iter().all(|id| matches!(id, ValidatorId::Index(_)))
+    });
+
+    if let Some(balances) = balances_cache.get(&state_root) {
+        if finalized || only_indices {
+            let indices = ids
+                .map(|ids| {
+                    resolve_validator_ids(ids, balances.len(), |pubkey| {
+                        chain
+                            .validator_index(pubkey)
+                            .map_err(warp_utils::reject::beacon_chain_error)
+                    })
+                })
+                .transpose()?;
+            return Ok((balances, indices, execution_optimistic, finalized));
+        }
+    }
+
+    let slot = match state_id.0 {
+        CoreStateId::Slot(slot) => Some(slot),
+        _ => None,
+    };
+    let state = chain
+        .get_state(&state_root, slot)
+        .map_err(warp_utils::reject::beacon_chain_error)?
+        .ok_or_else(|| {
+            warp_utils::reject::custom_not_found(format!("beacon state at root {}", state_root))
+        })?;
+    let balances = Arc::new(state.balances().to_vec());
+    balances_cache.insert(state_root, balances.clone());
+    let indices = validator_indices(chain, &state, ids)?;
+    Ok((balances, indices, execution_optimistic, finalized))
+}
+
+pub fn get_beacon_state_validator_balances<T: BeaconChainTypes>(
+    state_id: StateId,
+    chain: &BeaconChain<T>,
+    balances_cache: &BalancesCache,
+    ids: Option<&[ValidatorId]>,
+    page: PageQuery,
+) -> Result<ExecutionOptimisticFinalizedPageResponse<Vec<ValidatorBalanceData>>, warp::Rejection> {
+    let paginate_balances = |balances: &[u64], indices: Option<Vec<usize>>| {
+        let balance_data = |index: usize| {
+            Some(ValidatorBalanceData {
+                index: index as u64,
+                balance: *balances.get(index)?,
+            })
+        };
+        match indices {
+            Some(indices) => paginate(indices.into_iter(), page, balance_data),
+            None => paginate(0..balances.len(), page, balance_data),
+        }
+    };
+
+    let ((data, next_page), execution_optimistic, finalized) = match state_id.0 {
+        // The head state is always in memory, so its balances aren't cached.
+        CoreStateId::Head => state_id.map_state_and_execution_optimistic_and_finalized(
+            chain,
+            |state, execution_optimistic, finalized| {
+                let indices = validator_indices(chain, state, ids)?;
+                let data = paginate_balances(state.balances(), indices)?;
+                Ok((data, execution_optimistic, finalized))
+            },
+        )?,
+        _ => {
+            let (balances, indices, execution_optimistic, finalized) =
+                balances_and_indices(&state_id, chain, balances_cache, ids)?;
+            let data = paginate_balances(&balances, indices)?;
+            (data, execution_optimistic, finalized)
+        }
+    };
+
+    Ok(ExecutionOptimisticFinalizedPageResponse {
+        data,
+        execution_optimistic: Some(execution_optimistic),
+        finalized: Some(finalized),
+        next_page: next_page.map(|value| Quoted { value }),
+    })
+}
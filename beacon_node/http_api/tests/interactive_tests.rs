@@ -1,12 +1,22 @@
 //! Generic tests that make use of the (newer) `InteractiveApiTester`
 use beacon_chain::{
     chain_config::{DisallowedReOrgOffsets, ReOrgThreshold},
-    test_utils::{AttestationStrategy, BlockStrategy, SyncCommitteeStrategy},
+    test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy, SyncCommitteeStrategy},
+    ChainConfig,
 };
-use eth2::types::DepositContractData;
+use eth2::lighthouse::{GraphQLRequest, PeerAccessList};
+use eth2::types::{DepositContractData, StateId};
+use eth2::{BeaconNodeHttpClient, StatusCode, Timeouts};
 use execution_layer::{ForkchoiceState, PayloadAttributes};
-use http_api::test_utils::InteractiveTester;
+use futures::StreamExt;
+use http_api::test_utils::{
+    create_api_server, create_api_server_with_beacon_processor, create_api_server_with_config,
+    ApiServer, InteractiveTester,
+};
+use http_api::{Config, Context, RateLimitConfig, API_TOKEN_FILENAME};
+use network::{ApiRequest, ApiRequestPriority};
 use parking_lot::Mutex;
+use sensitive_url::SensitiveUrl;
 use slot_clock::SlotClock;
 use state_processing::{
     per_block_processing::get_expected_withdrawals, state_advance::complete_state_advance,
@@ -14,6 +24,7 @@ use state_processing::{
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::oneshot;
 use tree_hash::TreeHash;
 use types::{
     Address, Epoch, EthSpec, ExecPayload, ExecutionBlockHash, ForkName, FullPayload,
@@ -743,3 +754,745 @@ pub async fn fork_choice_before_proposal() {
     // D's parent is B.
     assert_eq!(block_d.parent_root(), block_root_b.into());
 }
+
+// Test that privileged endpoints require the API token, while standard endpoints don't.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn privileged_endpoints_require_api_token() {
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .build();
+
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join(API_TOKEN_FILENAME);
+    let config = Config {
+        enabled: true,
+        listen_port: unused_port::unused_tcp4_port().unwrap(),
+        api_token_path: Some(token_path.clone()),
+        ..Config::default()
+    };
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server_with_config(harness.chain.clone(), harness.logger().clone(), config)
+        .await;
+    tokio::spawn(server);
+
+    let url = SensitiveUrl::parse(&format!("http://{}", listening_socket)).unwrap();
+    let timeouts = Timeouts::set_all(Duration::from_secs(1));
+    let client = BeaconNodeHttpClient::new(url.clone(), timeouts.clone());
+
+    client.get_node_version().await.unwrap();
+    let error = client
+        .post_lighthouse_database_reconstruct()
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+    for path in ["validator_metrics", "validator_info"] {
+        let response = reqwest::Client::new()
+            .post(format!(
+                "http://{}/lighthouse/ui/{}",
+                listening_socket, path
+            ))
+            .json(&serde_json::json!({ "indices": [0] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // The token is generated when the server starts.
+    let token = std::fs::read_to_string(&token_path).unwrap();
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        reqwest::header::AUTHORIZATION,
+        format!("Bearer {}", token.trim()).parse().unwrap(),
+    );
+    let client = BeaconNodeHttpClient::from_components(
+        url,
+        reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap(),
+        timeouts,
+    );
+    assert_eq!(
+        client.post_lighthouse_database_reconstruct().await.unwrap(),
+        "success"
+    );
+}
+
+// Test that the peer access list can't be changed without the API token.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn peer_access_list_requires_api_token() {
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .build();
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = Config {
+        enabled: true,
+        listen_port: unused_port::unused_tcp4_port().unwrap(),
+        api_token_path: Some(dir.path().join(API_TOKEN_FILENAME)),
+        ..Config::default()
+    };
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server_with_config(harness.chain.clone(), harness.logger().clone(), config)
+        .await;
+    tokio::spawn(server);
+
+    let url = SensitiveUrl::parse(&format!("http://{}", listening_socket)).unwrap();
+    let client = BeaconNodeHttpClient::new(url, Timeouts::set_all(Duration::from_secs(1)));
+
+    let entries = PeerAccessList {
+        allowlist: vec![],
+        denylist: vec!["192.0.2.0/24".parse().unwrap()],
+    };
+    let error = client
+        .post_lighthouse_peers_access_list_add(&entries)
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+    let error = client
+        .post_lighthouse_peers_access_list_remove(&entries)
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+
+    // The access list can still be read, and is unchanged.
+    assert_eq!(
+        client
+            .get_lighthouse_peers_access_list()
+            .await
+            .unwrap()
+            .data,
+        PeerAccessList::default()
+    );
+}
+
+// Test that the API is served over a Unix socket which is only accessible to the user and group
+// of the beacon node.
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unix_socket_serves_requests() {
+    use std::io::{Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .build();
+
+    let dir = tempfile::tempdir().unwrap();
+    let socket_path = dir.path().join("beacon.sock");
+    // Leave a socket behind, as a previous run would. It should be replaced.
+    drop(UnixListener::bind(&socket_path).unwrap());
+
+    let ctx = Arc::new(Context {
+        config: Config {
+            enabled: true,
+            unix_socket: Some(socket_path.clone()),
+            ..Config::default()
+        },
+        chain: Some(harness.chain.clone()),
+        network_senders: None,
+        network_globals: None,
+        eth1_service: None,
+        db_path: None,
+        freezer_db_path: None,
+        log: harness.logger().clone(),
+    });
+    let (_server_shutdown, shutdown_rx) = oneshot::channel::<()>();
+    let (listening_socket, server) = http_api::serve(ctx, async {
+        let _ = shutdown_rx.await;
+    })
+    .unwrap();
+    assert_eq!(listening_socket, None);
+    tokio::spawn(server);
+
+    let permissions = std::fs::metadata(&socket_path).unwrap().permissions();
+    assert_eq!(permissions.mode() & 0o777, 0o660);
+    // The private directory the socket was bound in has been removed.
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    let response = tokio::task::spawn_blocking(move || {
+        let mut stream = UnixStream::connect(&socket_path).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(
+                b"GET /eth/v1/node/version HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    })
+    .await
+    .unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.contains("Lighthouse/"), "{}", response);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn graphql_queries() {
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .mock_execution_layer()
+        .build();
+    harness.advance_slot();
+    harness
+        .extend_chain(
+            E::slots_per_epoch() as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let config = Config {
+        enabled: true,
+        listen_port: unused_port::unused_tcp4_port().unwrap(),
+        enable_graphql: true,
+        ..Config::default()
+    };
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server_with_config(harness.chain.clone(), harness.logger().clone(), config)
+        .await;
+    tokio::spawn(server);
+
+    let url = SensitiveUrl::parse(&format!("http://{}", listening_socket)).unwrap();
+    let client = BeaconNodeHttpClient::new(url, Timeouts::set_all(Duration::from_secs(5)));
+
+    let head = harness.chain.head_snapshot();
+    let request = GraphQLRequest {
+        query: r#"{
+            block { slot root attestations { slot } }
+            validator(id: "1") { index balance status }
+            missing: block(id: "1000") { slot }
+        }"#
+        .to_string(),
+        ..GraphQLRequest::default()
+    };
+    let response = client.post_lighthouse_graphql(&request).await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let block = &response.data["block"];
+    assert_eq!(block["slot"], head.beacon_block.slot().to_string());
+    assert_eq!(block["root"], format!("{:?}", head.beacon_block_root));
+    assert_eq!(
+        block["attestations"].as_array().unwrap().len(),
+        head.beacon_block.message().body().attestations().len()
+    );
+    // Fields which weren't selected aren't returned.
+    assert!(block.get("stateRoot").is_none());
+
+    let validator = &response.data["validator"];
+    assert_eq!(validator["index"], "1");
+    assert_eq!(
+        validator["balance"],
+        head.beacon_state.balances()[1].to_string()
+    );
+    assert_eq!(validator["status"], "active_ongoing");
+
+    assert!(response.data["missing"].is_null());
+
+    // Invalid arguments are reported as errors.
+    let request = GraphQLRequest {
+        query: r#"{ block(id: "invalid") { slot } }"#.to_string(),
+        ..GraphQLRequest::default()
+    };
+    let response = client.post_lighthouse_graphql(&request).await.unwrap();
+    assert_eq!(response.errors.len(), 1);
+
+    // A query can only load a limited number of distinct states.
+    let request = GraphQLRequest {
+        query: r#"{
+            head: validator(id: "1") { index }
+            sameHead: validator(stateId: "head", id: "2") { index }
+            genesis: validator(stateId: "genesis", id: "1") { index }
+            finalized: validator(stateId: "finalized", id: "1") { index }
+        }"#
+        .to_string(),
+        ..GraphQLRequest::default()
+    };
+    let response = client.post_lighthouse_graphql(&request).await.unwrap();
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+
+    // Fields which load states count heavily towards the complexity limit.
+    let query = (0..8)
+        .map(|i| format!("v{}: validators {{ index }}", i))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let request = GraphQLRequest {
+        query: format!("{{ {} }}", query),
+        ..GraphQLRequest::default()
+    };
+    let response = client.post_lighthouse_graphql(&request).await.unwrap();
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    assert!(response.data.is_null());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn graphql_disabled_by_default() {
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .build();
+
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server(harness.chain.clone(), harness.logger().clone()).await;
+    tokio::spawn(server);
+
+    let url = SensitiveUrl::parse(&format!("http://{}", listening_socket)).unwrap();
+    let client = BeaconNodeHttpClient::new(url, Timeouts::set_all(Duration::from_secs(1)));
+
+    let request = GraphQLRequest {
+        query: "{ block { slot } }".to_string(),
+        ..GraphQLRequest::default()
+    };
+    let error = client.post_lighthouse_graphql(&request).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn requests_are_rate_limited() {
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .build();
+
+    let config = Config {
+        enabled: true,
+        listen_port: unused_port::unused_tcp4_port().unwrap(),
+        rate_limit: Some(RateLimitConfig {
+            requests_per_second: 1,
+            expensive_requests_per_second: 1,
+        }),
+        ..Config::default()
+    };
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server_with_config(harness.chain.clone(), harness.logger().clone(), config)
+        .await;
+    tokio::spawn(server);
+
+    let url = SensitiveUrl::parse(&format!("http://{}", listening_socket)).unwrap();
+    let client = BeaconNodeHttpClient::new(url, Timeouts::set_all(Duration::from_secs(1)));
+
+    client.get_node_version().await.unwrap();
+    let error = client.get_node_version().await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+
+    // Expensive endpoints have a separate quota.
+    client.get_lighthouse_database_info().await.unwrap();
+    let error = client.get_lighthouse_database_info().await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+
+    // Validator duties are never limited.
+    for _ in 0..3 {
+        client
+            .get_validator_duties_proposer(Epoch::new(0))
+            .await
+            .unwrap();
+    }
+}
+
+/// Low-priority requests are limited to all but one of the beacon processor's workers, so neither
+/// validator duties nor block publishing wait behind them.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn saturated_p1_requests_do_not_block_validators() {
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(24)
+        .fresh_ephemeral_store()
+        .build();
+
+    let config = Config {
+        enabled: true,
+        listen_port: unused_port::unused_tcp4_port().unwrap(),
+        ..Config::default()
+    };
+    // With two workers, a single `P1` request saturates the workers available to them.
+    let (
+        ApiServer {
+            server,
+            listening_socket,
+            shutdown_tx: _server_shutdown,
+            network_rx: _network_rx,
+            ..
+        },
+        api_request_send,
+    ) = create_api_server_with_beacon_processor(
+        harness.chain.clone(),
+        harness.logger().clone(),
+        config,
+        2,
+        harness.runtime.task_executor.clone(),
+    )
+    .await;
+    tokio::spawn(server);
+
+    let url = SensitiveUrl::parse(&format!("http://{}", listening_socket)).unwrap();
+    let client = BeaconNodeHttpClient::new(url, Timeouts::set_all(Duration::from_secs(5)));
+
+    let (permit_tx, permit_rx) = oneshot::channel();
+    api_request_send
+        .send(ApiRequest {
+            priority: ApiRequestPriority::P1,
+            permit_tx,
+        })
+        .await
+        .unwrap();
+    let p1_permit = permit_rx.await.unwrap();
+
+    // Further `P1` requests wait for the permit to be released.
+    let p1_client = client.clone();
+    let mut p1_request =
+        tokio::spawn(async move { p1_client.get_beacon_states_root(StateId::Head).await });
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), &mut p1_request)
+            .await
+            .is_err(),
+        "P1 request should wait for a worker"
+    );
+
+    // Validator duties are served by the remaining worker.
+    client
+        .get_validator_duties_proposer(Epoch::new(0))
+        .await
+        .unwrap();
+
+    // Block publishing doesn't wait for a worker at all.
+    harness.advance_slot();
+    let slot = harness.get_current_slot();
+    let (block, _) = harness.make_block(harness.get_current_state(), slot).await;
+    client.post_beacon_blocks(&block).await.unwrap();
+    assert_eq!(
+        harness.chain.head_snapshot().beacon_block_root,
+        block.canonical_root()
+    );
+
+    drop(p1_permit);
+    let root = p1_request.await.unwrap().unwrap().unwrap().data.root;
+    assert_eq!(root, harness.chain.head_snapshot().beacon_state_root());
+}
+
+/// Returns the next event received on `socket`, with its `event` topic and `data`.
+async fn next_websocket_message<S>(socket: &mut S) -> serde_json::Value
+where
+    S: futures::Stream<
+            Item = Result<
+                tokio_tungstenite::tungstenite::Message,
+                tokio_tungstenite::tungstenite::Error,
+            >,
+        > + Unpin,
+{
+    let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+        .await
+        .expect("should receive an event")
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(&message.into_text().unwrap()).unwrap()
+}
+
+/// Returns the topic of the next event received on `socket`.
+async fn next_websocket_event<S>(socket: &mut S) -> String
+where
+    S: futures::Stream<
+            Item = Result<
+                tokio_tungstenite::tungstenite::Message,
+                tokio_tungstenite::tungstenite::Error,
+            >,
+        > + Unpin,
+{
+    next_websocket_message(socket).await["event"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+/// Events are sent over a WebSocket, optionally only those which concern some validators.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn websocket_events() {
+    let validator_count = 24;
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(validator_count)
+        .fresh_ephemeral_store()
+        .build();
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server(harness.chain.clone(), harness.logger().clone()).await;
+    tokio::spawn(server);
+
+    let connect = |query: String| async move {
+        let url = format!("ws://{}/lighthouse/events/ws?{}", listening_socket, query);
+        tokio_tungstenite::connect_async(url).await.unwrap().0
+    };
+    let all_validators = (0..validator_count)
+        .map(|index| index.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut all_validators_socket = connect(format!(
+        "topics=block_reward,head&validator_indices={}",
+        all_validators
+    ))
+    .await;
+    let mut unknown_validator_socket = connect(format!(
+        "topics=block_reward,head&validator_indices={}",
+        validator_count
+    ))
+    .await;
+    let mut unfiltered_socket = connect("topics=head".to_string()).await;
+
+    harness.advance_slot();
+    harness
+        .extend_chain(
+            1,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    // The block reward is computed before the block becomes the head, and is only sent to clients
+    // interested in its proposer.
+    assert_eq!(
+        next_websocket_event(&mut all_validators_socket).await,
+        "block_reward"
+    );
+    assert_eq!(
+        next_websocket_event(&mut all_validators_socket).await,
+        "head"
+    );
+    assert_eq!(
+        next_websocket_event(&mut unknown_validator_socket).await,
+        "head"
+    );
+    assert_eq!(next_websocket_event(&mut unfiltered_socket).await, "head");
+}
+
+/// Attestations are only sent to WebSocket clients filtering by validator if one of those
+/// validators attested.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn websocket_attestation_events_filtered_by_validator() {
+    let validator_count = 24;
+    let harness = BeaconChainHarness::builder(E::default())
+        .default_spec()
+        .logger(logging::test_logger())
+        .deterministic_keypairs(validator_count)
+        .fresh_ephemeral_store()
+        .build();
+    let ApiServer {
+        server,
+        listening_socket,
+        shutdown_tx: _server_shutdown,
+        ..
+    } = create_api_server(harness.chain.clone(), harness.logger().clone()).await;
+    tokio::spawn(server);
+
+    harness.advance_slot();
+    harness
+        .extend_chain(
+            1,
+            BlockStrategy::OnCanonicalHead,
+            // Leave the validators free to attest to the new head below.
+            AttestationStrategy::SomeValidators(vec![]),
+        )
+        .await;
+
+    let connect = |query: &str| {
+        let url = format!("ws://{}/lighthouse/events/ws?{}", listening_socket, query);
+        async move { tokio_tungstenite::connect_async(url).await.unwrap().0 }
+    };
+    let watched_validator = 0;
+    let mut filtered_socket = connect(&format!(
+        "topics=attestation&validator_indices={}",
+        watched_validator
+    ))
+    .await;
+    let mut unfiltered_socket = connect("topics=attestation").await;
+
+    let head = harness.chain.head_snapshot();
+    let make_attestations = |validators: Vec<usize>| {
+        harness
+            .get_unaggregated_attestations(
+                &AttestationStrategy::SomeValidators(validators),
+                &head.beacon_state,
+                head.beacon_state_root(),
+                head.beacon_block_root,
+                harness.chain.slot().unwrap(),
+            )
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+    };
+    let other_attestations = make_attestations(
+        (0..validator_count)
+            .filter(|index| *index != watched_validator)
+            .collect(),
+    );
+    let watched_attestations = make_attestations(vec![watched_validator]);
+    assert!(!other_attestations.is_empty());
+    assert_eq!(watched_attestations.len(), 1);
+
+    // The attestations of the other validators are verified first, so the first attestation sent to
+    // the filtered client must be that of the watched validator.
+    for (attestation, subnet_id) in other_attestations.iter().chain(&watched_attestations) {
+        harness
+            .chain
+            .verify_unaggregated_attestation_for_gossip(attestation, Some(*subnet_id))
+            .unwrap();
+    }
+
+    let event = next_websocket_message(&mut filtered_socket).await;
+    assert_eq!(event["event"], "attestation");
+    assert_eq!(
+        event["data"],
+        serde_json::to_value(&watched_attestations[0].0).unwrap()
+    );
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), filtered_socket.next())
+            .await
+            .is_err(),
+        "should not receive the attestations of other validators"
+    );
+
+    for _ in 0..other_attestations.len() + watched_attestations.len() {
+        assert_eq!(
+            next_websocket_event(&mut unfiltered_socket).await,
+            "attestation"
+        );
+    }
+}
+
+/// Light client updates are computed as blocks are imported, and served by the HTTP API.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn light_client_updates() {
+    let validator_count = 32;
+    let spec = ForkName::Altair.make_genesis_spec(E::default_spec());
+
+    // The endpoints are disabled unless the light client server is enabled.
+    let tester = InteractiveTester::<E>::new(Some(spec.clone()), validator_count).await;
+    let error = tester
+        .client
+        .get_beacon_light_client_updates::<E>(0, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+
+    let tester = InteractiveTester::<E>::new_with_initializer_and_mutator(
+        Some(spec),
+        validator_count,
+        Some(Box::new(move |builder| {
+            builder
+                .deterministic_keypairs(validator_count)
+                .fresh_ephemeral_store()
+                .chain_config(ChainConfig {
+                    enable_light_client_server: true,
+                    ..ChainConfig::default()
+                })
+        })),
+        None,
+    )
+    .await;
+    let harness = &tester.harness;
+    let client = &tester.client;
+
+    // Extend the chain with sync aggregates until it is finalized.
+    harness.advance_slot();
+    harness
+        .extend_chain_with_sync(
+            E::slots_per_epoch() as usize * 5,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+            SyncCommitteeStrategy::AllValidators,
+        )
+        .await;
+    let head = harness.chain.head_snapshot();
+    let head_slot = head.beacon_block.slot();
+    let head_state = &head.beacon_state;
+    assert!(head_state.finalized_checkpoint().epoch > 0);
+
+    // The head block signs its parent.
+    let optimistic_update = client
+        .get_beacon_light_client_optimistic_update::<E>()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(optimistic_update.version, Some(ForkName::Altair));
+    assert_eq!(optimistic_update.data.signature_slot, head_slot);
+    assert_eq!(
+        optimistic_update.data.attested_header.tree_hash_root(),
+        head.beacon_block.parent_root()
+    );
+
+    let finality_update = client
+        .get_beacon_light_client_finality_update::<E>()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(finality_update.data.signature_slot, head_slot);
+    assert!(finality_update.data.finalized_header.slot > 0);
+
+    let updates = client
+        .get_beacon_light_client_updates::<E>(0, 2)
+        .await
+        .unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(
+        updates[0].data.next_sync_committee,
+        *head_state.next_sync_committee().unwrap()
+    );
+
+    let bootstrap = client
+        .get_beacon_light_client_bootstrap::<E>(head.beacon_block_root)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        bootstrap.data.header.tree_hash_root(),
+        head.beacon_block_root
+    );
+    assert_eq!(
+        bootstrap.data.current_sync_committee,
+        *head_state.current_sync_committee().unwrap()
+    );
+}
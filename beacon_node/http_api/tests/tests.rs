@@ -5,6 +5,7 @@ use beacon_chain::{
 };
 use environment::null_logger;
 use eth2::{
+    lighthouse::{AttestationPerformanceRequest, AttestationPerformanceTotals, PeerAccessList},
     mixin::{RequestAccept, ResponseForkName, ResponseOptional},
     reqwest::RequestBuilder,
     types::{BlockId as CoreBlockId, ForkChoiceNode, StateId as CoreStateId, *},
@@ -19,9 +20,9 @@ use futures::stream::{Stream, StreamExt};
 use futures::FutureExt;
 use http_api::{
     test_utils::{create_api_server, create_api_server_on_port, ApiServer},
-    BlockId, StateId,
+    BlockId, StateId, MAX_ATTESTATION_PERFORMANCE_INDICES,
 };
-use lighthouse_network::{Enr, EnrExt, PeerId};
+use lighthouse_network::{AccessListEntry, Enr, EnrExt, PeerId};
 use network::NetworkReceivers;
 use proto_array::ExecutionStatus;
 use sensitive_url::SensitiveUrl;
@@ -827,6 +828,12 @@ impl ApiTester {
                     .await
                     .unwrap()
                     .map(|res| res.data);
+                let result_post_ids = self
+                    .client
+                    .post_beacon_states_validator_balances(state_id.0, &validator_pubkey_ids)
+                    .await
+                    .unwrap()
+                    .map(|res| res.data);
 
                 let expected = state_opt.map(|(state, _execution_optimistic, _finalized)| {
                     let mut validators = Vec::with_capacity(validator_indices.len());
@@ -845,6 +852,7 @@ impl ApiTester {
 
                 assert_eq!(result_index_ids, expected, "{:?}", state_id);
                 assert_eq!(result_pubkey_ids, expected, "{:?}", state_id);
+                assert_eq!(result_post_ids, expected, "{:?}", state_id);
             }
         }
 
@@ -902,6 +910,17 @@ impl ApiTester {
                         .unwrap()
                         .map(|res| res.data);
 
+                    let result_post_ids = self
+                        .client
+                        .post_beacon_states_validators(
+                            state_id.0,
+                            Some(validator_index_ids.clone()),
+                            None,
+                        )
+                        .await
+                        .unwrap()
+                        .map(|res| res.data);
+
                     let expected = state_opt.map(|state| {
                         let epoch = state.current_epoch();
                         let far_future_epoch = self.chain.spec.far_future_epoch;
@@ -936,6 +955,7 @@ impl ApiTester {
 
                     assert_eq!(result_index_ids, expected, "{:?}", state_id);
                     assert_eq!(result_pubkey_ids, expected, "{:?}", state_id);
+                    assert_eq!(result_post_ids, expected, "{:?}", state_id);
                 }
             }
         }
@@ -943,6 +963,94 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_beacon_states_validator_pages(self) -> Self {
+        for state_id in [
+            StateId(CoreStateId::Head),
+            StateId(CoreStateId::Finalized),
+            StateId(CoreStateId::Slot(Slot::new(32))),
+        ] {
+            let all_validators = self
+                .client
+                .get_beacon_states_validators(state_id.0, None, None)
+                .await
+                .unwrap()
+                .unwrap()
+                .data;
+            let all_balances = self
+                .client
+                .get_beacon_states_validator_balances(state_id.0, None)
+                .await
+                .unwrap()
+                .unwrap()
+                .data;
+
+            // Follow `next_page` until the last page, twice so the second pass of balances is
+            // served from the cache.
+            for limit in [1, 3, 3, VALIDATOR_COUNT as u64] {
+                let mut validators = vec![];
+                let mut page = None;
+                loop {
+                    let response = self
+                        .client
+                        .get_beacon_states_validators_page(state_id.0, None, None, page, limit)
+                        .await
+                        .unwrap()
+                        .unwrap();
+                    assert!(response.data.len() as u64 <= limit, "{:?}", state_id);
+                    validators.extend(response.data);
+                    match response.next_page {
+                        Some(next_page) => page = Some(next_page.value),
+                        None => break,
+                    }
+                }
+                assert_eq!(validators, all_validators, "{:?}", state_id);
+
+                let mut balances = vec![];
+                let mut page = None;
+                loop {
+                    let response = self
+                        .client
+                        .get_beacon_states_validator_balances_page(state_id.0, None, page, limit)
+                        .await
+                        .unwrap()
+                        .unwrap();
+                    assert!(response.data.len() as u64 <= limit, "{:?}", state_id);
+                    balances.extend(response.data);
+                    match response.next_page {
+                        Some(next_page) => page = Some(next_page.value),
+                        None => break,
+                    }
+                }
+                assert_eq!(balances, all_balances, "{:?}", state_id);
+            }
+
+            // Pages of selected validators skip the validators which weren't selected.
+            let ids = [
+                ValidatorId::Index(4),
+                ValidatorId::Index(1),
+                ValidatorId::Index(6),
+            ];
+            let response = self
+                .client
+                .get_beacon_states_validator_balances_page(state_id.0, Some(&ids), Some(2), 1)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(response.data, vec![all_balances[4].clone()]);
+            assert_eq!(response.next_page.map(|page| page.value), Some(6));
+
+            // A page can't be empty.
+            let error = self
+                .client
+                .get_beacon_states_validators_page(state_id.0, None, None, None, 0)
+                .await
+                .unwrap_err();
+            assert_eq!(error.status(), Some(StatusCode::BAD_REQUEST));
+        }
+
+        self
+    }
+
     pub async fn test_beacon_states_validator_id(self) -> Self {
         for state_id in self.interesting_state_ids() {
             let state_opt = state_id
@@ -1247,6 +1355,22 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_post_beacon_blocks_ssz_valid(mut self) -> Self {
+        let next_block = &self.next_block;
+
+        self.client
+            .post_beacon_blocks_ssz(next_block)
+            .await
+            .unwrap();
+
+        assert!(
+            self.network_rx.network_recv.recv().await.is_some(),
+            "valid blocks should be sent to network"
+        );
+
+        self
+    }
+
     pub async fn test_post_beacon_blocks_invalid(mut self) -> Self {
         let mut next_block = self.next_block.clone();
         *next_block.message_mut().proposer_index_mut() += 1;
@@ -2216,6 +2340,17 @@ impl ApiTester {
 
             assert_eq!(result, expected);
 
+            // Next-epoch requests are served from their own cache, and should be unchanged.
+            if epoch == current_epoch + 1 {
+                let result = self
+                    .client
+                    .get_validator_duties_proposer(epoch)
+                    .await
+                    .unwrap();
+
+                assert_eq!(result, expected);
+            }
+
             // If it's the current epoch, check the function with a primed proposer cache.
             if epoch == current_epoch {
                 // This is technically a double-check, but it's defensive.
@@ -3833,12 +3968,83 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_health_node(self) -> Self {
+        let health = self.client.get_lighthouse_health_node().await.unwrap().data;
+
+        assert_eq!(health.healthy, health.issues.is_empty());
+        assert_eq!(
+            health.head_slot,
+            self.chain.head_snapshot().beacon_block.slot()
+        );
+        assert_eq!(
+            health.db_schema_version,
+            Some(store::metadata::CURRENT_SCHEMA_VERSION.as_u64())
+        );
+
+        self
+    }
+
     pub async fn test_get_lighthouse_syncing(self) -> Self {
         self.client.get_lighthouse_syncing().await.unwrap();
 
         self
     }
 
+    pub async fn test_get_lighthouse_nat_ports(self) -> Self {
+        let nat_ports = self.client.get_lighthouse_nat_ports().await.unwrap().data;
+
+        assert_eq!(nat_ports.discovery_port, self.local_enr.udp4());
+
+        self
+    }
+
+    pub async fn test_lighthouse_peers_access_list(self) -> Self {
+        let access_list = self
+            .client
+            .get_lighthouse_peers_access_list()
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(access_list, PeerAccessList::default());
+
+        let peer_entry = AccessListEntry::Peer(PeerId::random());
+        let subnet_entry: AccessListEntry = "192.0.2.0/24".parse().unwrap();
+        let access_list = self
+            .client
+            .post_lighthouse_peers_access_list_add(&PeerAccessList {
+                allowlist: vec![],
+                denylist: vec![peer_entry, subnet_entry],
+            })
+            .await
+            .unwrap()
+            .data;
+        assert!(access_list.allowlist.is_empty());
+        assert_eq!(access_list.denylist.len(), 2);
+        assert!(access_list.denylist.contains(&peer_entry));
+        assert!(access_list.denylist.contains(&subnet_entry));
+
+        let access_list = self
+            .client
+            .post_lighthouse_peers_access_list_remove(&PeerAccessList {
+                allowlist: vec![],
+                denylist: vec![peer_entry],
+            })
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(access_list.denylist, vec![subnet_entry]);
+        assert_eq!(
+            self.client
+                .get_lighthouse_peers_access_list()
+                .await
+                .unwrap()
+                .data,
+            access_list
+        );
+
+        self
+    }
+
     pub async fn test_get_lighthouse_proto_array(self) -> Self {
         self.client.get_lighthouse_proto_array().await.unwrap();
 
@@ -3919,6 +4125,87 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_debug_heap_disabled(self) -> Self {
+        let result = self.client.get_lighthouse_debug_heap().await.unwrap();
+
+        assert!(result.is_none());
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_analysis_attestation_performance(self) -> Self {
+        let start_epoch = Epoch::new(0);
+        let end_epoch = Epoch::new(1);
+        let indices = vec![0, 1, 3];
+
+        let summary = self
+            .client
+            .post_lighthouse_analysis_attestation_performance(&AttestationPerformanceRequest {
+                start_epoch,
+                end_epoch,
+                indices: indices.clone(),
+            })
+            .await
+            .unwrap();
+
+        let mut expected = vec![];
+        for index in indices {
+            expected.extend(
+                self.client
+                    .get_lighthouse_analysis_attestation_performance(
+                        start_epoch,
+                        end_epoch,
+                        index.to_string(),
+                    )
+                    .await
+                    .unwrap(),
+            );
+        }
+        assert_eq!(summary.validators, expected);
+        assert_eq!(
+            summary.totals,
+            AttestationPerformanceTotals::from_performances(&expected)
+        );
+
+        // Unknown validators are rejected.
+        let error = self
+            .client
+            .post_lighthouse_analysis_attestation_performance(&AttestationPerformanceRequest {
+                start_epoch,
+                end_epoch,
+                indices: vec![VALIDATOR_COUNT as u64],
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::BAD_REQUEST));
+
+        // Duplicate indices are ignored.
+        let deduplicated = self
+            .client
+            .post_lighthouse_analysis_attestation_performance(&AttestationPerformanceRequest {
+                start_epoch,
+                end_epoch,
+                indices: vec![3, 0, 1, 0, 3],
+            })
+            .await
+            .unwrap();
+        assert_eq!(deduplicated, summary);
+
+        // Requests for too many validators are rejected.
+        let error = self
+            .client
+            .post_lighthouse_analysis_attestation_performance(&AttestationPerformanceRequest {
+                start_epoch,
+                end_epoch,
+                indices: (0..=MAX_ATTESTATION_PERFORMANCE_INDICES as u64).collect(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), Some(StatusCode::BAD_REQUEST));
+
+        self
+    }
+
     pub async fn test_get_lighthouse_database_info(self) -> Self {
         let info = self.client.get_lighthouse_database_info().await.unwrap();
 
@@ -3928,6 +4215,9 @@ impl ApiTester {
             info.schema_version,
             store::metadata::CURRENT_SCHEMA_VERSION.as_u64()
         );
+        // The test harness uses an in-memory database, which has no size on disk.
+        assert_eq!(info.hot_db_size, None);
+        assert_eq!(info.freezer_db_size, None);
 
         self
     }
@@ -4334,6 +4624,8 @@ async fn beacon_get() {
         .await
         .test_beacon_states_validator_balances()
         .await
+        .test_beacon_states_validator_pages()
+        .await
         .test_beacon_states_committees()
         .await
         .test_beacon_states_validator_id()
@@ -4369,6 +4661,14 @@ async fn post_beacon_blocks_valid() {
     ApiTester::new().await.test_post_beacon_blocks_valid().await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn post_beacon_blocks_ssz_valid() {
+    ApiTester::new()
+        .await
+        .test_post_beacon_blocks_ssz_valid()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn post_beacon_blocks_invalid() {
     ApiTester::new()
@@ -4857,8 +5157,14 @@ async fn lighthouse_endpoints() {
         .await
         .test_get_lighthouse_health()
         .await
+        .test_get_lighthouse_health_node()
+        .await
         .test_get_lighthouse_syncing()
         .await
+        .test_get_lighthouse_nat_ports()
+        .await
+        .test_lighthouse_peers_access_list()
+        .await
         .test_get_lighthouse_proto_array()
         .await
         .test_get_lighthouse_validator_inclusion()
@@ -4875,8 +5181,12 @@ async fn lighthouse_endpoints() {
         .await
         .test_get_lighthouse_staking()
         .await
+        .test_get_lighthouse_debug_heap_disabled()
+        .await
         .test_get_lighthouse_database_info()
         .await
+        .test_post_lighthouse_analysis_attestation_performance()
+        .await
         .test_post_lighthouse_database_reconstruct()
         .await
         .test_post_lighthouse_liveness()
@@ -33,12 +33,6 @@ pub fn gather_prometheus_metrics<T: BeaconChainTypes>(
         beacon_chain::scrape_for_metrics(beacon_chain);
     }
 
-    if let (Some(db_path), Some(freezer_db_path)) =
-        (ctx.db_path.as_ref(), ctx.freezer_db_path.as_ref())
-    {
-        store::scrape_for_metrics(db_path, freezer_db_path);
-    }
-
     lighthouse_network::scrape_discovery_metrics();
 
     warp_utils::metrics::scrape_health_metrics();
@@ -49,13 +43,22 @@ pub fn gather_prometheus_metrics<T: BeaconChainTypes>(
         scrape_allocator_metrics();
     }
 
+    let filter = MetricsFilter::new(ctx.config.allowlist.clone(), ctx.config.denylist.clone());
     encoder
-        .encode(&lighthouse_metrics::gather(), &mut buffer)
+        .encode(
+            &filter.filter_families(lighthouse_metrics::gather()),
+            &mut buffer,
+        )
         .unwrap();
     // encode gossipsub metrics also if they exist
     if let Some(registry) = ctx.gossipsub_registry.as_ref() {
         if let Ok(registry_locked) = registry.lock() {
-            let _ = encode(&mut buffer, &registry_locked);
+            let mut gossipsub_buffer = vec![];
+            if encode(&mut gossipsub_buffer, &registry_locked).is_ok() {
+                if let Ok(text) = std::str::from_utf8(&gossipsub_buffer) {
+                    buffer.extend_from_slice(filter.filter_text(text).as_bytes());
+                }
+            }
         }
     }
 
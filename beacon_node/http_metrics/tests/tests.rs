@@ -21,11 +21,15 @@ async fn returns_200_ok() {
                 listen_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 listen_port: 0,
                 allow_origin: None,
+                tls_config: None,
+                bearer_token_path: None,
                 allocator_metrics_enabled: true,
+                pushgateway: None,
+                histogram_buckets: <_>::default(),
+                allowlist: vec![],
+                denylist: vec![],
             },
             chain: None,
-            db_path: None,
-            freezer_db_path: None,
             gossipsub_registry: None,
             log,
         });
@@ -56,3 +60,50 @@ async fn returns_200_ok() {
     }
     .await
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn requires_bearer_token() {
+    async {
+        let log = null_logger().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let token_path = dir.path().join("token.txt");
+        std::fs::write(&token_path, "secret\n").unwrap();
+
+        let context = Arc::new(Context {
+            config: Config {
+                enabled: true,
+                listen_port: 0,
+                bearer_token_path: Some(token_path),
+                ..Config::default()
+            },
+            chain: None,
+            gossipsub_registry: None,
+            log,
+        });
+
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server_shutdown = async {
+            let _ = shutdown_rx.await;
+        };
+        let (listening_socket, server) = http_metrics::serve(context, server_shutdown).unwrap();
+
+        tokio::spawn(async { server.await });
+
+        let url = format!(
+            "http://{}:{}/metrics",
+            listening_socket.ip(),
+            listening_socket.port()
+        );
+        let client = reqwest::Client::new();
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = client.get(&url).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = client.get(&url).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    .await
+}
@@ -1,7 +1,10 @@
 use crate::listen_addr::{ListenAddr, ListenAddress};
+use crate::peer_manager::MIN_OUTBOUND_ONLY_FACTOR;
+use crate::proxy::ProxyConfig;
 use crate::rpc::config::OutboundRateLimiterConfig;
+use crate::service::gossipsub_scoring_parameters::PeerScoreOverrides;
 use crate::types::GossipKind;
-use crate::{Enr, PeerIdSerialized};
+use crate::{AccessListEntry, Enr, PeerIdSerialized};
 use directory::{
     DEFAULT_BEACON_NODE_DIR, DEFAULT_HARDCODED_NETWORK, DEFAULT_NETWORK_DIR, DEFAULT_ROOT_DIR,
 };
@@ -15,6 +18,7 @@ use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use types::{ForkContext, ForkName};
@@ -43,6 +47,30 @@ pub const DUPLICATE_CACHE_TIME: Duration = Duration::from_secs(33 * 12 + 1);
 // const MESSAGE_DOMAIN_INVALID_SNAPPY: [u8; 4] = [0, 0, 0, 0];
 const MESSAGE_DOMAIN_VALID_SNAPPY: [u8; 4] = [1, 0, 0, 0];
 
+/// The default average number of unsolicited discovery packets accepted per second.
+pub const DEFAULT_DISCOVERY_RATE_LIMIT: u64 = 10;
+/// The default average number of unsolicited discovery packets accepted per second from an IP.
+pub const DEFAULT_DISCOVERY_IP_RATE_LIMIT: u64 = 9;
+/// The average number of unsolicited discovery packets accepted per second from a node.
+const DISCOVERY_NODE_RATE_LIMIT: u64 = 8;
+/// The default number of nodes which may share an IP in the discovery packet filter.
+pub const DEFAULT_DISCOVERY_MAX_NODES_PER_IP: usize = 10;
+
+/// The rate limiter applied to unsolicited discovery packets (i.e. requests) by the discv5 packet
+/// filter. Bursts are allowed, with an average of `total` packets per second, of which at most
+/// `per_ip` may be from the same IP address.
+pub fn discovery_rate_limiter(total: u64, per_ip: u64) -> discv5::RateLimiter {
+    discv5::RateLimiterBuilder::new()
+        .total_n_every(total, Duration::from_secs(1))
+        .ip_n_every(per_ip, Duration::from_secs(1))
+        .node_n_every(
+            DISCOVERY_NODE_RATE_LIMIT.min(per_ip),
+            Duration::from_secs(1),
+        )
+        .build()
+        .expect("The total rate limit has been specified")
+}
+
 /// The maximum size of gossip messages.
 pub fn gossip_max_size(is_merge_enabled: bool) -> usize {
     if is_merge_enabled {
@@ -78,9 +106,27 @@ pub struct Config {
     /// The tcp6 port to broadcast to peers in order to reach back for libp2p services.
     pub enr_tcp6_port: Option<u16>,
 
+    /// The quic4 port to broadcast to peers in order to reach back for libp2p services.
+    pub enr_quic4_port: Option<u16>,
+
+    /// The quic6 port to broadcast to peers in order to reach back for libp2p services.
+    pub enr_quic6_port: Option<u16>,
+
+    /// Omit the IP addresses and ports from the local ENR, for nodes which only dial out. Such an
+    /// ENR can't be used to reach the node, and is never updated with an external address.
+    pub enr_omit_address: bool,
+
     /// Target number of connected peers.
     pub target_peers: usize,
 
+    /// Maximum number of connected peers, above which inbound connections are refused. If not set,
+    /// this is 10% more than `target_peers`.
+    pub max_peers: Option<usize>,
+
+    /// The fraction of `target_peers` which should be peers we dialed. Discovery searches for more
+    /// peers to dial when there are fewer.
+    pub min_outbound_peers_ratio: f32,
+
     /// Gossipsub configuration parameters.
     #[serde(skip)]
     pub gs_config: GossipsubConfig,
@@ -101,18 +147,37 @@ pub struct Config {
     /// List of trusted libp2p nodes which are not scored.
     pub trusted_peers: Vec<PeerIdSerialized>,
 
+    /// List of libp2p nodes, with their peer ids, which are kept connected independently of
+    /// discovery and redialed when they disconnect.
+    pub static_peers: Vec<Multiaddr>,
+
+    /// Peers and IP subnets which are allowed to connect. If non-empty, other peers are refused.
+    pub peer_allowlist: Vec<AccessListEntry>,
+
+    /// Peers and IP subnets which are refused connections.
+    pub peer_denylist: Vec<AccessListEntry>,
+
     /// Disables peer scoring altogether.
     pub disable_peer_scoring: bool,
 
+    /// Overrides of the gossipsub peer scoring parameters.
+    pub gossipsub_score_overrides: PeerScoreOverrides,
+
     /// Client version
     pub client_version: String,
 
     /// Disables the discovery protocol from starting.
     pub disable_discovery: bool,
 
-    /// Attempt to construct external port mappings with UPnP.
+    /// Attempt to construct external port mappings with UPnP or NAT-PMP/PCP.
     pub upnp_enabled: bool,
 
+    /// The protocol used to construct external port mappings, if `upnp_enabled` is set.
+    pub port_mapping_protocol: PortMappingProtocol,
+
+    /// A proxy through which peers are dialed over TCP.
+    pub proxy: Option<ProxyConfig>,
+
     /// Subscribe to all subnets for the duration of the runtime.
     pub subscribe_all_subnets: bool,
 
@@ -142,6 +207,37 @@ pub struct Config {
 
     /// Configuration for the outbound rate limiter (requests made by this node).
     pub outbound_rate_limiter_config: Option<OutboundRateLimiterConfig>,
+
+    /// The maximum number of workers which process network messages concurrently, which is never
+    /// more than the number of CPUs.
+    pub beacon_processor_max_workers: Option<usize>,
+
+    /// The maximum length of each queue of network messages awaiting processing is divided by
+    /// this value.
+    pub beacon_processor_queue_length_divisor: usize,
+}
+
+/// The protocols which can construct external port mappings on the local gateway.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PortMappingProtocol {
+    /// Use UPnP, or NAT-PMP/PCP if no UPnP gateway is found.
+    Auto,
+    UPnP,
+    /// NAT-PMP, or its successor PCP if the gateway supports it.
+    NatPmp,
+}
+
+impl FromStr for PortMappingProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(PortMappingProtocol::Auto),
+            "upnp" => Ok(PortMappingProtocol::UPnP),
+            "nat-pmp" => Ok(PortMappingProtocol::NatPmp),
+            other => Err(format!("Unknown port mapping protocol: {}", other)),
+        }
+    }
 }
 
 impl Config {
@@ -153,6 +249,7 @@ impl Config {
             addr,
             udp_port,
             tcp_port,
+            quic_port: None,
         });
         self.discv5_config.ip_mode = discv5::IpMode::Ip4;
         self.discv5_config.table_filter = |enr| enr.ip4().as_ref().map_or(false, is_global_ipv4)
@@ -166,6 +263,7 @@ impl Config {
             addr,
             udp_port,
             tcp_port,
+            quic_port: None,
         });
         self.discv5_config.ip_mode = discv5::IpMode::Ip6 {
             enable_mapped_addresses: false,
@@ -190,11 +288,13 @@ impl Config {
                 addr: v4_addr,
                 udp_port: udp4_port,
                 tcp_port: tcp4_port,
+                quic_port: None,
             },
             ListenAddr {
                 addr: v6_addr,
                 udp_port: udp6_port,
                 tcp_port: tcp6_port,
+                quic_port: None,
             },
         );
 
@@ -209,33 +309,26 @@ impl Config {
         };
     }
 
+    /// Sets the listening addresses, including any QUIC ports, and adjusts the discv5 ip_mode and
+    /// table filter accordingly.
     pub fn set_listening_addr(&mut self, listen_addr: ListenAddress) {
-        match listen_addr {
-            ListenAddress::V4(ListenAddr {
-                addr,
-                udp_port,
-                tcp_port,
-            }) => self.set_ipv4_listening_address(addr, tcp_port, udp_port),
-            ListenAddress::V6(ListenAddr {
-                addr,
-                udp_port,
-                tcp_port,
-            }) => self.set_ipv6_listening_address(addr, tcp_port, udp_port),
-            ListenAddress::DualStack(
-                ListenAddr {
-                    addr: ip4addr,
-                    udp_port: udp4_port,
-                    tcp_port: tcp4_port,
-                },
-                ListenAddr {
-                    addr: ip6addr,
-                    udp_port: udp6_port,
-                    tcp_port: tcp6_port,
-                },
-            ) => self.set_ipv4_ipv6_listening_addresses(
-                ip4addr, tcp4_port, udp4_port, ip6addr, tcp6_port, udp6_port,
+        match &listen_addr {
+            ListenAddress::V4(v4_addr) => {
+                self.set_ipv4_listening_address(v4_addr.addr, v4_addr.tcp_port, v4_addr.udp_port)
+            }
+            ListenAddress::V6(v6_addr) => {
+                self.set_ipv6_listening_address(v6_addr.addr, v6_addr.tcp_port, v6_addr.udp_port)
+            }
+            ListenAddress::DualStack(v4_addr, v6_addr) => self.set_ipv4_ipv6_listening_addresses(
+                v4_addr.addr,
+                v4_addr.tcp_port,
+                v4_addr.udp_port,
+                v6_addr.addr,
+                v6_addr.tcp_port,
+                v6_addr.udp_port,
             ),
         }
+        self.listen_addresses = listen_addr;
     }
 
     pub fn listen_addrs(&self) -> &ListenAddress {
@@ -262,14 +355,10 @@ impl Default for Config {
             .expect("valid gossipsub configuration");
 
         // Discv5 Unsolicited Packet Rate Limiter
-        let filter_rate_limiter = Some(
-            discv5::RateLimiterBuilder::new()
-                .total_n_every(10, Duration::from_secs(1)) // Allow bursts, average 10 per second
-                .ip_n_every(9, Duration::from_secs(1)) // Allow bursts, average 9 per second
-                .node_n_every(8, Duration::from_secs(1)) // Allow bursts, average 8 per second
-                .build()
-                .expect("The total rate limit has been specified"),
-        );
+        let filter_rate_limiter = Some(discovery_rate_limiter(
+            DEFAULT_DISCOVERY_RATE_LIMIT,
+            DEFAULT_DISCOVERY_IP_RATE_LIMIT,
+        ));
 
         // discv5 configuration
         let discv5_config = Discv5ConfigBuilder::new()
@@ -286,7 +375,7 @@ impl Default for Config {
             .incoming_bucket_limit(8) // half the bucket size
             .filter_rate_limiter(filter_rate_limiter)
             .filter_max_bans_per_ip(Some(5))
-            .filter_max_nodes_per_ip(Some(10))
+            .filter_max_nodes_per_ip(Some(DEFAULT_DISCOVERY_MAX_NODES_PER_IP))
             .table_filter(|enr| enr.ip4().map_or(false, |ip| is_global_ipv4(&ip))) // Filter non-global IPs
             .ban_duration(Some(Duration::from_secs(3600)))
             .ping_interval(Duration::from_secs(300))
@@ -299,23 +388,35 @@ impl Default for Config {
                 addr: Ipv4Addr::UNSPECIFIED,
                 udp_port: 9000,
                 tcp_port: 9000,
+                quic_port: None,
             }),
             enr_address: (None, None),
             enr_udp4_port: None,
             enr_tcp4_port: None,
             enr_udp6_port: None,
             enr_tcp6_port: None,
+            enr_quic4_port: None,
+            enr_quic6_port: None,
+            enr_omit_address: false,
             target_peers: 50,
+            max_peers: None,
+            min_outbound_peers_ratio: MIN_OUTBOUND_ONLY_FACTOR,
             gs_config,
             discv5_config,
             boot_nodes_enr: vec![],
             boot_nodes_multiaddr: vec![],
             libp2p_nodes: vec![],
             trusted_peers: vec![],
+            static_peers: vec![],
+            peer_allowlist: vec![],
+            peer_denylist: vec![],
             disable_peer_scoring: false,
+            gossipsub_score_overrides: PeerScoreOverrides::default(),
             client_version: lighthouse_version::version_with_platform(),
             disable_discovery: false,
             upnp_enabled: true,
+            port_mapping_protocol: PortMappingProtocol::Auto,
+            proxy: None,
             network_load: 3,
             private: false,
             subscribe_all_subnets: false,
@@ -325,6 +426,8 @@ impl Default for Config {
             metrics_enabled: false,
             enable_light_client_server: false,
             outbound_rate_limiter_config: None,
+            beacon_processor_max_workers: None,
+            beacon_processor_queue_length_divisor: 1,
         }
     }
 }
@@ -23,6 +23,10 @@ pub const ETH2_ENR_KEY: &str = "eth2";
 pub const ATTESTATION_BITFIELD_ENR_KEY: &str = "attnets";
 /// The ENR field specifying the sync committee subnet bitfield.
 pub const SYNC_COMMITTEE_BITFIELD_ENR_KEY: &str = "syncnets";
+/// The ENR field specifying the IPv4 QUIC port.
+pub const QUIC_ENR_KEY: &str = "quic";
+/// The ENR field specifying the IPv6 QUIC port.
+pub const QUIC6_ENR_KEY: &str = "quic6";
 
 /// Extension trait for ENR's within Eth2.
 pub trait Eth2Enr {
@@ -37,6 +41,12 @@ pub trait Eth2Enr {
     ) -> Result<EnrSyncCommitteeBitfield<TSpec>, &'static str>;
 
     fn eth2(&self) -> Result<EnrForkId, &'static str>;
+
+    /// The IPv4 QUIC port associated with the ENR, if any.
+    fn quic4(&self) -> Option<u16>;
+
+    /// The IPv6 QUIC port associated with the ENR, if any.
+    fn quic6(&self) -> Option<u16>;
 }
 
 impl Eth2Enr for Enr {
@@ -67,6 +77,24 @@ impl Eth2Enr for Enr {
 
         EnrForkId::from_ssz_bytes(eth2_bytes).map_err(|_| "Could not decode EnrForkId")
     }
+
+    fn quic4(&self) -> Option<u16> {
+        self.get(QUIC_ENR_KEY).and_then(decode_port)
+    }
+
+    fn quic6(&self) -> Option<u16> {
+        self.get(QUIC6_ENR_KEY).and_then(decode_port)
+    }
+}
+
+/// Decodes a big-endian port number from an ENR field.
+fn decode_port(bytes: &[u8]) -> Option<u16> {
+    if bytes.len() > 2 {
+        return None;
+    }
+    let mut port = [0u8; 2];
+    port[2 - bytes.len()..].copy_from_slice(bytes);
+    Some(u16::from_be_bytes(port))
 }
 
 /// Either use the given ENR or load an ENR from file if it exists and matches the current NodeId
@@ -89,7 +117,7 @@ pub fn use_or_load_enr(
                     Ok(disk_enr) => {
                         // if the same node id, then we may need to update our sequence number
                         if local_enr.node_id() == disk_enr.node_id() {
-                            if compare_enr(local_enr, &disk_enr) {
+                            if compare_enr(local_enr, &disk_enr, config.enr_omit_address) {
                                 debug!(log, "ENR loaded from disk"; "file" => ?enr_f);
                                 // the stored ENR has the same configuration, use it
                                 *local_enr = disk_enr;
@@ -145,6 +173,12 @@ pub fn create_enr_builder_from_config<T: EnrKey>(
     enable_tcp: bool,
 ) -> EnrBuilder<T> {
     let mut builder = EnrBuilder::new("v4");
+
+    // Nodes which only dial out don't advertise any way to reach them.
+    if config.enr_omit_address {
+        return builder;
+    }
+
     let (maybe_ipv4_address, maybe_ipv6_address) = &config.enr_address;
 
     if let Some(ip) = maybe_ipv4_address {
@@ -178,6 +212,21 @@ pub fn create_enr_builder_from_config<T: EnrKey>(
         if let Some(tcp6_port) = tcp6_port {
             builder.tcp6(tcp6_port);
         }
+
+        // QUIC ports are only advertised if we are listening on them.
+        if let Some(quic4_port) = config.listen_addrs().v4().and_then(|v4| {
+            v4.quic_port
+                .map(|quic_port| config.enr_quic4_port.unwrap_or(quic_port))
+        }) {
+            builder.add_value(QUIC_ENR_KEY, quic4_port.to_be_bytes().as_slice());
+        }
+
+        if let Some(quic6_port) = config.listen_addrs().v6().and_then(|v6| {
+            v6.quic_port
+                .map(|quic_port| config.enr_quic6_port.unwrap_or(quic_port))
+        }) {
+            builder.add_value(QUIC6_ENR_KEY, quic6_port.to_be_bytes().as_slice());
+        }
     }
     builder
 }
@@ -210,11 +259,16 @@ pub fn build_enr<T: EthSpec>(
 
 /// Defines the conditions under which we use the locally built ENR or the one stored on disk.
 /// If this function returns true, we use the `disk_enr`.
-fn compare_enr(local_enr: &Enr, disk_enr: &Enr) -> bool {
-    // take preference over disk_enr address if one is not specified
-    (local_enr.ip4().is_none() || local_enr.ip4() == disk_enr.ip4())
+fn compare_enr(local_enr: &Enr, disk_enr: &Enr, omit_address: bool) -> bool {
+    // an address learned by a previous run can't be reused if it must be omitted
+    (!omit_address || (disk_enr.ip4().is_none() && disk_enr.ip6().is_none()))
+        // take preference over disk_enr address if one is not specified
+        && (local_enr.ip4().is_none() || local_enr.ip4() == disk_enr.ip4())
         // tcp ports must match
         && local_enr.tcp4() == disk_enr.tcp4()
+        // quic ports must match
+        && local_enr.quic4() == disk_enr.quic4()
+        && local_enr.quic6() == disk_enr.quic6()
         // must match on the same fork
         && local_enr.get(ETH2_ENR_KEY) == disk_enr.get(ETH2_ENR_KEY)
         // take preference over disk udp port if one is not specified
@@ -1,4 +1,5 @@
 //! ENR extension trait to support libp2p integration.
+use super::enr::Eth2Enr;
 use crate::{Enr, Multiaddr, PeerId};
 use discv5::enr::{CombinedKey, CombinedPublicKey};
 use libp2p::core::{identity::Keypair, identity::PublicKey, multiaddr::Protocol};
@@ -24,6 +25,9 @@ pub trait EnrExt {
 
     /// Returns any multiaddrs that contain the TCP protocol.
     fn multiaddr_tcp(&self) -> Vec<Multiaddr>;
+
+    /// Returns any multiaddrs that contain the QUIC protocol.
+    fn multiaddr_quic(&self) -> Vec<Multiaddr>;
 }
 
 /// Extend ENR CombinedPublicKey for libp2p types.
@@ -189,6 +193,29 @@ impl EnrExt for Enr {
         }
         multiaddrs
     }
+
+    /// Returns a list of multiaddrs if the ENR has an `ip` and a `quic` key **or** an `ip6` and a
+    /// `quic6`. The vector remains empty if these fields are not defined.
+    fn multiaddr_quic(&self) -> Vec<Multiaddr> {
+        let mut multiaddrs: Vec<Multiaddr> = Vec::new();
+        if let Some(ip) = self.ip4() {
+            if let Some(quic) = self.quic4() {
+                let mut multiaddr: Multiaddr = ip.into();
+                multiaddr.push(Protocol::Udp(quic));
+                multiaddr.push(Protocol::Quic);
+                multiaddrs.push(multiaddr);
+            }
+        }
+        if let Some(ip6) = self.ip6() {
+            if let Some(quic6) = self.quic6() {
+                let mut multiaddr: Multiaddr = ip6.into();
+                multiaddr.push(Protocol::Udp(quic6));
+                multiaddr.push(Protocol::Quic);
+                multiaddrs.push(multiaddr);
+            }
+        }
+        multiaddrs
+    }
 }
 
 impl CombinedKeyPublicExt for CombinedPublicKey {
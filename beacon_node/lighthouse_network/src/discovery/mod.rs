@@ -9,7 +9,7 @@ pub mod enr_ext;
 // Allow external use of the lighthouse ENR builder
 use crate::metrics;
 use crate::service::TARGET_SUBNET_PEERS;
-use crate::{error, Enr, NetworkConfig, NetworkGlobals, Subnet, SubnetDiscovery};
+use crate::{error, Enr, ListenAddress, NetworkConfig, NetworkGlobals, Subnet, SubnetDiscovery};
 use discv5::{enr::NodeId, Discv5, Discv5Event};
 pub use enr::{
     build_enr, create_enr_builder_from_config, load_enr_from_disk, use_or_load_enr, CombinedKey,
@@ -184,6 +184,9 @@ pub struct Discovery<TSpec: EthSpec> {
     /// second indicates tcp6.
     update_tcp_port: (bool, bool),
 
+    /// The addresses we listen on, which determine the IP versions of the addresses we dial.
+    listen_addresses: ListenAddress,
+
     /// Logger for the discovery behaviour.
     log: slog::Logger,
 }
@@ -253,7 +256,14 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
         let event_stream = if !config.disable_discovery {
             discv5
                 .start(listen_socket)
-                .map_err(|e| e.to_string())
+                .map_err(|e| match e {
+                    discv5::Discv5Error::Io(e) => error::ErrorKind::Listen(format!(
+                        "Discovery was unable to listen on {}: {}",
+                        listen_socket, e
+                    ))
+                    .into(),
+                    e => error::Error::from(e.to_string()),
+                })
                 .await?;
             debug!(log, "Discovery service started");
             EventStream::Awaiting(Box::pin(discv5.event_stream()))
@@ -322,6 +332,7 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
             event_stream,
             started: !config.disable_discovery,
             update_tcp_port,
+            listen_addresses: config.listen_addrs().clone(),
             log,
             enr_dir,
         })
@@ -773,6 +784,12 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
             return;
         }
 
+        let query_label = match &query {
+            QueryType::Subnet(_) => "subnet",
+            QueryType::FindPeers => "find_peers",
+        };
+        metrics::inc_counter_vec(&metrics::DISCOVERY_FIND_NODE_QUERIES, &[query_label]);
+
         // Generate a random target node id.
         let random_node = NodeId::random();
 
@@ -783,13 +800,16 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
                 return;
             }
         };
-        // predicate for finding nodes with a matching fork and valid tcp port
+        let listen_addresses = self.listen_addresses.clone();
+        // predicate for finding nodes with a matching fork, a valid tcp port and an address of an
+        // IP version we listen on
         let eth2_fork_predicate = move |enr: &Enr| {
             // `next_fork_epoch` and `next_fork_version` can be different so that
             // we can connect to peers who aren't compatible with an upcoming fork.
             // `fork_digest` **must** be same.
             enr.eth2().map(|e| e.fork_digest) == Ok(enr_fork_id.fork_digest)
                 && (enr.tcp4().is_some() || enr.tcp6().is_some())
+                && !dial_addresses(enr, &listen_addresses).is_empty()
         };
 
         // General predicate
@@ -964,7 +984,7 @@ impl<TSpec: EthSpec> NetworkBehaviour for Discovery<TSpec> {
             // ENR's may have multiple Multiaddrs. The multi-addr associated with the UDP
             // port is removed, which is assumed to be associated with the discv5 protocol (and
             // therefore irrelevant for other libp2p components).
-            enr.multiaddr_tcp()
+            dial_addresses(&enr, &self.listen_addresses)
         } else {
             // PeerId is not known
             Vec::new()
@@ -1067,8 +1087,12 @@ impl<TSpec: EthSpec> NetworkBehaviour for Discovery<TSpec> {
                                 });
                             }
                         }
+                        Discv5Event::TalkRequest(_) => {
+                            // Lighthouse doesn't support any TALKREQ protocols, so requests are
+                            // only counted, which helps to identify floods of them.
+                            metrics::inc_counter(&metrics::DISCOVERY_TALK_REQUESTS);
+                        }
                         Discv5Event::EnrAdded { .. }
-                        | Discv5Event::TalkRequest(_)
                         | Discv5Event::NodeInserted { .. }
                         | Discv5Event::SessionEstablished { .. } => {} // Ignore all other discv5 server events
                     }
@@ -1123,11 +1147,29 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
     }
 }
 
+/// Returns the TCP addresses of `enr` to dial, preceded by its QUIC addresses if we support QUIC.
+///
+/// Only the addresses of the IP versions we listen on are dialed, so that nodes without IPv4 or
+/// IPv6 connectivity don't waste dial attempts. If the peer has no such addresses, it can't be
+/// dialed and the list is empty.
+fn dial_addresses(enr: &Enr, listen_addresses: &ListenAddress) -> Vec<Multiaddr> {
+    let quic_addresses = if listen_addresses.quic_enabled() {
+        enr.multiaddr_quic()
+    } else {
+        vec![]
+    };
+    quic_addresses
+        .into_iter()
+        .chain(enr.multiaddr_tcp())
+        .filter(|addr| listen_addresses.supports_ip_version(addr))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::rpc::methods::{MetaData, MetaDataV2};
-    use enr::EnrBuilder;
+    use enr::{EnrBuilder, QUIC_ENR_KEY};
     use slog::{o, Drain};
     use types::{BitVector, MinimalEthSpec, SubnetId};
 
@@ -1163,6 +1205,7 @@ mod tests {
             }),
             vec![],
             false,
+            Default::default(),
             &log,
         );
         Discovery::new(&keypair, &config, Arc::new(globals), &log)
@@ -1264,4 +1307,71 @@ mod tests {
         // when a peer belongs to multiple subnet ids, we use the highest ttl.
         assert_eq!(results.get(&enr1.peer_id()).unwrap(), &instant1);
     }
+
+    #[test]
+    fn test_dial_addresses() {
+        let keypair = libp2p::identity::Keypair::generate_secp256k1();
+        let enr_key: CombinedKey = CombinedKey::from_libp2p(&keypair).unwrap();
+        let ip4 = "1.2.3.4".parse::<std::net::Ipv4Addr>().unwrap();
+        let ip6 = "2001:db8::1".parse::<std::net::Ipv6Addr>().unwrap();
+        let v4_addr = Multiaddr::from(ip4).with(Protocol::Tcp(9000));
+        let v6_addr = Multiaddr::from(ip6).with(Protocol::Tcp(9090));
+
+        let dual_stack_enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .tcp4(9000)
+            .ip6(ip6)
+            .tcp6(9090)
+            .build(&enr_key)
+            .unwrap();
+        let v4_enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .tcp4(9000)
+            .build(&enr_key)
+            .unwrap();
+
+        let listen_v4 = ListenAddress::unused_v4_ports();
+        let listen_v6 = ListenAddress::unused_v6_ports();
+        let listen_dual_stack = match (listen_v4.clone(), listen_v6.clone()) {
+            (ListenAddress::V4(v4), ListenAddress::V6(v6)) => ListenAddress::DualStack(v4, v6),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            dial_addresses(&dual_stack_enr, &listen_v4),
+            vec![v4_addr.clone()]
+        );
+        assert_eq!(
+            dial_addresses(&dual_stack_enr, &listen_v6),
+            vec![v6_addr.clone()]
+        );
+        assert_eq!(
+            dial_addresses(&dual_stack_enr, &listen_dual_stack),
+            vec![v4_addr.clone(), v6_addr]
+        );
+        // Peers without any addresses of the IP versions we listen on aren't dialed.
+        assert!(dial_addresses(&v4_enr, &listen_v6).is_empty());
+        assert_eq!(dial_addresses(&v4_enr, &listen_v4), vec![v4_addr.clone()]);
+
+        // QUIC addresses are dialed first, but only if we support QUIC.
+        let quic_enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .tcp4(9000)
+            .add_value(QUIC_ENR_KEY, 9001u16.to_be_bytes().as_slice())
+            .build(&enr_key)
+            .unwrap();
+        assert_eq!(quic_enr.quic4(), Some(9001));
+        let quic_addr = Multiaddr::from(ip4)
+            .with(Protocol::Udp(9001))
+            .with(Protocol::Quic);
+        let mut listen_quic = listen_v4.clone();
+        if let ListenAddress::V4(v4) = &mut listen_quic {
+            v4.quic_port = Some(unused_port::unused_udp4_port().unwrap());
+        }
+        assert_eq!(
+            dial_addresses(&quic_enr, &listen_quic),
+            vec![quic_addr, v4_addr.clone()]
+        );
+        assert_eq!(dial_addresses(&quic_enr, &listen_v4), vec![v4_addr]);
+    }
 }
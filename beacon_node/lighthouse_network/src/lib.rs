@@ -13,11 +13,16 @@ pub mod discovery;
 pub mod listen_addr;
 pub mod metrics;
 pub mod peer_manager;
+pub mod proxy;
 pub mod rpc;
 pub mod types;
 
-pub use config::gossip_max_size;
+pub use config::{
+    discovery_rate_limiter, gossip_max_size, DEFAULT_DISCOVERY_IP_RATE_LIMIT,
+    DEFAULT_DISCOVERY_MAX_NODES_PER_IP, DEFAULT_DISCOVERY_RATE_LIMIT,
+};
 pub use listen_addr::*;
+pub use proxy::{ProxyConfig, ProxyKind};
 
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::str::FromStr;
@@ -32,6 +37,12 @@ impl From<PeerIdSerialized> for PeerId {
     }
 }
 
+impl From<PeerId> for PeerIdSerialized {
+    fn from(peer_id: PeerId) -> Self {
+        Self(peer_id)
+    }
+}
+
 impl FromStr for PeerIdSerialized {
     type Err = String;
 
@@ -64,13 +75,13 @@ impl<'de> Deserialize<'de> for PeerIdSerialized {
 }
 
 pub use crate::types::{
-    error, Enr, EnrSyncCommitteeBitfield, GossipTopic, NetworkGlobals, PubsubMessage, Subnet,
-    SubnetDiscovery,
+    error, AccessList, AccessListEntry, Enr, EnrSyncCommitteeBitfield, GossipTopic, NetworkGlobals,
+    PubsubMessage, Subnet, SubnetDiscovery,
 };
 
 pub use prometheus_client;
 
-pub use config::Config as NetworkConfig;
+pub use config::{Config as NetworkConfig, PortMappingProtocol};
 pub use discovery::{CombinedKeyExt, EnrExt, Eth2Enr};
 pub use discv5;
 pub use libp2p;
@@ -87,5 +98,6 @@ pub use peer_manager::{
 };
 // pub use service::{load_private_key, Context, Libp2pEvent, Service, NETWORK_KEY_FILENAME};
 pub use service::api_types::{PeerRequestId, Request, Response};
+pub use service::gossipsub_scoring_parameters::PeerScoreOverrides;
 pub use service::utils::*;
 pub use service::{Gossipsub, NetworkEvent};
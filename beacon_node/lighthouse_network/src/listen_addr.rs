@@ -3,12 +3,15 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use libp2p::{multiaddr::Protocol, Multiaddr};
 use serde::{Deserialize, Serialize};
 
-/// A listening address composed by an Ip, an UDP port and a TCP port.
+/// A listening address composed by an Ip, an UDP port, a TCP port and an optional QUIC port.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ListenAddr<Ip> {
     pub addr: Ip,
     pub udp_port: u16,
     pub tcp_port: u16,
+    /// The UDP port to listen on for QUIC connections, if QUIC is enabled.
+    #[serde(default)]
+    pub quic_port: Option<u16>,
 }
 
 impl<Ip: Into<IpAddr> + Clone> ListenAddr<Ip> {
@@ -57,12 +60,47 @@ impl ListenAddress {
         v4_multiaddr.into_iter().chain(v6_multiaddr)
     }
 
+    /// Returns the QUIC addresses, which are empty if QUIC is disabled.
+    pub fn quic_addresses(&self) -> impl Iterator<Item = Multiaddr> + '_ {
+        let v4_multiaddr = self.v4().and_then(|v4_addr| {
+            v4_addr.quic_port.map(|quic_port| {
+                Multiaddr::from(v4_addr.addr)
+                    .with(Protocol::Udp(quic_port))
+                    .with(Protocol::Quic)
+            })
+        });
+        let v6_multiaddr = self.v6().and_then(|v6_addr| {
+            v6_addr.quic_port.map(|quic_port| {
+                Multiaddr::from(v6_addr.addr)
+                    .with(Protocol::Udp(quic_port))
+                    .with(Protocol::Quic)
+            })
+        });
+        v4_multiaddr.into_iter().chain(v6_multiaddr)
+    }
+
+    /// Returns `true` if QUIC is enabled on any of the listening addresses.
+    pub fn quic_enabled(&self) -> bool {
+        self.quic_addresses().next().is_some()
+    }
+
+    /// Returns `true` if `addr` uses an IP version which is listened on, and so is assumed to be
+    /// reachable from this node.
+    pub fn supports_ip_version(&self, addr: &Multiaddr) -> bool {
+        match addr.iter().next() {
+            Some(Protocol::Ip4(_)) => self.v4().is_some(),
+            Some(Protocol::Ip6(_)) => self.v6().is_some(),
+            _ => false,
+        }
+    }
+
     #[cfg(test)]
     pub fn unused_v4_ports() -> Self {
         ListenAddress::V4(ListenAddr {
             addr: Ipv4Addr::UNSPECIFIED,
             udp_port: unused_port::unused_udp4_port().unwrap(),
             tcp_port: unused_port::unused_tcp4_port().unwrap(),
+            quic_port: None,
         })
     }
 
@@ -72,6 +110,7 @@ impl ListenAddress {
             addr: Ipv6Addr::UNSPECIFIED,
             udp_port: unused_port::unused_udp6_port().unwrap(),
             tcp_port: unused_port::unused_tcp6_port().unwrap(),
+            quic_port: None,
         })
     }
 }
@@ -86,11 +125,17 @@ impl slog::KV for ListenAddress {
             serializer.emit_arguments("ip4_address", &format_args!("{}", v4_addr.addr))?;
             serializer.emit_u16("udp4_port", v4_addr.udp_port)?;
             serializer.emit_u16("tcp4_port", v4_addr.tcp_port)?;
+            if let Some(quic_port) = v4_addr.quic_port {
+                serializer.emit_u16("quic4_port", quic_port)?;
+            }
         }
         if let Some(v6_addr) = self.v6() {
             serializer.emit_arguments("ip6_address", &format_args!("{}", v6_addr.addr))?;
             serializer.emit_u16("udp6_port", v6_addr.udp_port)?;
             serializer.emit_u16("tcp6_port", v6_addr.tcp_port)?;
+            if let Some(quic_port) = v6_addr.quic_port {
+                serializer.emit_u16("quic6_port", quic_port)?;
+            }
         }
         slog::Result::Ok(())
     }
@@ -5,6 +5,11 @@ lazy_static! {
         "nat_open",
         "An estimate indicating if the local node is exposed to the internet."
     );
+    pub static ref NAT_OPEN_PORTS: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "nat_open_ports",
+        "An estimate indicating if each of the local node's ports is exposed to the internet.",
+        &["port"]
+    );
     pub static ref ADDRESS_UPDATE_COUNT: Result<IntCounter> = try_create_int_counter(
         "libp2p_address_update_total",
         "Count of libp2p socked updated events (when our view of our IP address has changed)"
@@ -22,6 +27,22 @@ lazy_static! {
         "libp2p_peer_disconnect_event_total",
         "Count of libp2p peer disconnect events"
     );
+    pub static ref STATIC_PEERS_CONNECTED: Result<IntGauge> = try_create_int_gauge(
+        "libp2p_static_peers",
+        "Count of static peers currently connected"
+    );
+    pub static ref STATIC_PEER_DIAL_ATTEMPTS: Result<IntCounter> = try_create_int_counter(
+        "libp2p_static_peer_dial_attempts_total",
+        "Count of attempts to (re)connect to static peers"
+    );
+    pub static ref STATIC_PEER_DIAL_FAILURES: Result<IntCounter> = try_create_int_counter(
+        "libp2p_static_peer_dial_failures_total",
+        "Count of failed attempts to (re)connect to static peers"
+    );
+    pub static ref STATIC_PEER_RECONNECTS: Result<IntCounter> = try_create_int_counter(
+        "libp2p_static_peer_reconnects_total",
+        "Count of connections re-established to static peers after they disconnected"
+    );
     pub static ref DISCOVERY_SENT_BYTES: Result<IntGauge> = try_create_int_gauge(
         "discovery_sent_bytes",
         "The number of bytes sent in discovery"
@@ -42,7 +63,45 @@ lazy_static! {
         "discovery_sessions",
         "The number of active discovery sessions with peers"
     );
+    pub static ref DISCOVERY_FIND_NODE_QUERIES: Result<IntCounterVec> = try_create_int_counter_vec(
+        "discovery_find_node_queries_total",
+        "Count of outbound FINDNODE queries started by the local node, by query type. Inbound \
+         FINDNODE requests are not reported individually by discv5, and are included in \
+         discovery_requests",
+        &["type"]
+    );
+    pub static ref DISCOVERY_TALK_REQUESTS: Result<IntCounter> = try_create_int_counter(
+        "discovery_talk_requests_total",
+        "Count of TALKREQ requests received from other nodes"
+    );
 
+    pub static ref RPC_BYTES: Result<IntCounterVec> = try_create_int_counter_vec(
+        "libp2p_rpc_bytes_total",
+        "Bytes of compressed RPC messages sent and received, by protocol",
+        &["protocol", "direction"]
+    );
+    pub static ref RPC_BYTES_PER_CLIENT: Result<IntCounterVec> = try_create_int_counter_vec(
+        "libp2p_rpc_bytes_per_client_total",
+        "Bytes of RPC messages sent and received before compression, by the client of the peer",
+        &["client", "direction"]
+    );
+    pub static ref GOSSIP_BYTES: Result<IntCounterVec> = try_create_int_counter_vec(
+        "libp2p_gossip_bytes_total",
+        "Bytes of gossip messages published, forwarded and received before compression, by topic",
+        &["topic", "direction"]
+    );
+    pub static ref GOSSIP_BYTES_RECEIVED_PER_CLIENT: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "libp2p_gossip_bytes_received_per_client_total",
+            "Bytes of gossip messages received before compression, by the client of the forwarding peer",
+            &["client"]
+        );
+    pub static ref GOSSIP_BYTES_SENT_PER_CLIENT: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "libp2p_gossip_bytes_sent_per_client_total",
+            "Bytes of gossip messages published and forwarded before compression, by the client of the receiving peer",
+            &["client"]
+        );
     pub static ref PEERS_PER_CLIENT: Result<IntGaugeVec> = try_create_int_gauge_vec(
         "libp2p_peers_per_client",
         "The connected peers via client implementation",
@@ -154,18 +213,42 @@ lazy_static! {
 /// 2. We have 0 SOCKET_UPDATED messages (can be true if the port was correct on boot), then we
 ///    rely on whether we have any inbound messages. If we have no socket update messages, but
 ///    manage to get at least one inbound peer, we are exposed correctly.
+///
+/// The discovery port is deemed open by condition 1, and the libp2p port by condition 2.
 pub fn check_nat() {
+    let discovery_open = ADDRESS_UPDATE_COUNT.as_ref().map(|v| v.get()).unwrap_or(0) != 0;
+    let libp2p_open = NETWORK_INBOUND_PEERS.as_ref().map(|v| v.get()).unwrap_or(0) != 0_i64;
+    if discovery_open {
+        set_gauge_vec(&NAT_OPEN_PORTS, &[NAT_DISCOVERY_PORT], 1);
+    }
+    if libp2p_open {
+        set_gauge_vec(&NAT_OPEN_PORTS, &[NAT_LIBP2P_PORT], 1);
+    }
+
     // NAT is already deemed open.
     if NAT_OPEN.as_ref().map(|v| v.get()).unwrap_or(0) != 0 {
         return;
     }
-    if ADDRESS_UPDATE_COUNT.as_ref().map(|v| v.get()).unwrap_or(0) != 0
-        || NETWORK_INBOUND_PEERS.as_ref().map(|v| v.get()).unwrap_or(0) != 0_i64
-    {
+    if discovery_open || libp2p_open {
         inc_counter(&NAT_OPEN);
     }
 }
 
+/// Label of bytes received in `RPC_BYTES`, `RPC_BYTES_PER_CLIENT` and `GOSSIP_BYTES`.
+pub const INBOUND: &str = "inbound";
+/// Label of bytes sent in `RPC_BYTES`, `RPC_BYTES_PER_CLIENT` and `GOSSIP_BYTES`.
+pub const OUTBOUND: &str = "outbound";
+
+/// Label of the discovery (UDP) port in `NAT_OPEN_PORTS`.
+pub const NAT_DISCOVERY_PORT: &str = "discovery";
+/// Label of the libp2p (TCP) port in `NAT_OPEN_PORTS`.
+pub const NAT_LIBP2P_PORT: &str = "libp2p";
+
+/// Returns whether the port labelled `port` in `NAT_OPEN_PORTS` has been deemed open.
+pub fn nat_port_open(port: &str) -> bool {
+    get_int_gauge(&NAT_OPEN_PORTS, &[port]).map_or(false, |gauge| gauge.get() != 0)
+}
+
 pub fn scrape_discovery_metrics() {
     let metrics =
         discv5::metrics::Metrics::from(discv5::Discv5::<discv5::DefaultProtocolId>::raw_metrics());
@@ -10,6 +10,9 @@ pub const DEFAULT_PING_INTERVAL_INBOUND: u64 = 20;
 /// Default number of peers to connect to.
 pub const DEFAULT_TARGET_PEERS: usize = 50;
 
+use super::MIN_OUTBOUND_ONLY_FACTOR;
+use libp2p::Multiaddr;
+
 /// Configurations for the PeerManager.
 #[derive(Debug)]
 pub struct Config {
@@ -20,6 +23,14 @@ pub struct Config {
     pub metrics_enabled: bool,
     /// Target number of peers to connect to.
     pub target_peer_count: usize,
+    /// Maximum number of peers to connect to, if it differs from the default excess over the
+    /// target.
+    pub max_peer_count: Option<usize>,
+    /// The fraction of the target peers which should be outbound-only, below which we search for
+    /// more peers to dial.
+    pub min_outbound_only_factor: f32,
+    /// Peers to keep connected to, on Multiaddr format ending with their peer id.
+    pub static_peers: Vec<Multiaddr>,
 
     /* RPC related configurations */
     /// Time in seconds between status requests sent to peers.
@@ -38,6 +49,9 @@ impl Default for Config {
             discovery_enabled: true,
             metrics_enabled: false,
             target_peer_count: DEFAULT_TARGET_PEERS,
+            max_peer_count: None,
+            min_outbound_only_factor: MIN_OUTBOUND_ONLY_FACTOR,
+            static_peers: vec![],
             status_interval: DEFAULT_STATUS_INTERVAL,
             ping_interval_inbound: DEFAULT_PING_INTERVAL_INBOUND,
             ping_interval_outbound: DEFAULT_PING_INTERVAL_OUTBOUND,
@@ -8,6 +8,7 @@ use crate::{Subnet, SubnetDiscovery};
 use delay_map::HashSetDelay;
 use discv5::Enr;
 use libp2p::identify::Info as IdentifyInfo;
+use libp2p::multiaddr::Protocol as MProtocol;
 use lru_cache::LRUTimeCache;
 use peerdb::{client::ClientKind, BanOperation, BanResult, ScoreUpdateResult};
 use rand::seq::SliceRandom;
@@ -51,16 +52,22 @@ pub const MIN_SYNC_COMMITTEE_PEERS: u64 = 2;
 /// `PeerManager::target_peers`. For clarity, if `PeerManager::target_peers` is 50 and
 /// PEER_EXCESS_FACTOR = 0.1 we allow 10% more nodes, i.e 55.
 pub const PEER_EXCESS_FACTOR: f32 = 0.1;
-/// A fraction of `PeerManager::target_peers` that we want to be outbound-only connections.
+/// A fraction of `PeerManager::target_peers` that we want to be outbound-only connections. If the
+/// configured minimum fraction is higher, that is used instead.
 pub const TARGET_OUTBOUND_ONLY_FACTOR: f32 = 0.3;
-/// A fraction of `PeerManager::target_peers` that if we get below, we start a discovery query to
-/// reach our target. MIN_OUTBOUND_ONLY_FACTOR must be < TARGET_OUTBOUND_ONLY_FACTOR.
+/// The default fraction of `PeerManager::target_peers` that if we get below, we start a discovery
+/// query to reach our target.
 pub const MIN_OUTBOUND_ONLY_FACTOR: f32 = 0.2;
 /// The fraction of extra peers beyond the PEER_EXCESS_FACTOR that we allow us to dial for when
 /// requiring subnet peers. More specifically, if our target peer limit is 50, and our excess peer
 /// limit is 55, and we are at 55 peers, the following parameter provisions a few more slots of
 /// dialing priority peers we need for validator duties.
 pub const PRIORITY_PEER_EXCESS: f32 = 0.2;
+/// The delay before redialing a static peer which has disconnected. The delay doubles for each
+/// consecutive redial that fails to connect, up to `STATIC_PEER_MAX_REDIAL_DELAY`.
+pub const STATIC_PEER_REDIAL_DELAY: Duration = Duration::from_secs(5);
+/// The maximum delay between attempts to connect to a static peer.
+pub const STATIC_PEER_MAX_REDIAL_DELAY: Duration = Duration::from_secs(300);
 
 /// The main struct that handles peer's reputation and connection status.
 pub struct PeerManager<TSpec: EthSpec> {
@@ -76,6 +83,13 @@ pub struct PeerManager<TSpec: EthSpec> {
     status_peers: HashSetDelay<PeerId>,
     /// The target number of peers we would like to connect to.
     target_peers: usize,
+    /// The maximum number of peers, if it is configured rather than derived from `target_peers`.
+    max_peer_count: Option<usize>,
+    /// The fraction of `target_peers` that if we get below in outbound-only peers, we start a
+    /// discovery query.
+    min_outbound_only_factor: f32,
+    /// The fraction of `target_peers` that we want to be outbound-only peers.
+    target_outbound_only_factor: f32,
     /// Peers queued to be dialed.
     peers_to_dial: BTreeMap<PeerId, Option<Enr>>,
     /// The number of temporarily banned peers. This is used to prevent instantaneous
@@ -92,6 +106,10 @@ pub struct PeerManager<TSpec: EthSpec> {
     // to update and clear the cache. Therefore the PEER_RECONNECTION_TIMEOUT only has a resolution
     // of the HEARTBEAT_INTERVAL.
     temporary_banned_peers: LRUTimeCache<PeerId>,
+    /// Peers which are kept connected independently of discovery, by peer id.
+    static_peers: HashMap<PeerId, StaticPeer>,
+    /// A collection of disconnected static peers awaiting to be redialed.
+    static_peer_redials: HashSetDelay<PeerId>,
     /// A collection of sync committee subnets that we need to stay subscribed to.
     /// Sync committee subnets are longer term (256 epochs). Hence, we need to re-run
     /// discovery queries for subnet peers if we disconnect from existing sync
@@ -145,6 +163,9 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             discovery_enabled,
             metrics_enabled,
             target_peer_count,
+            max_peer_count,
+            min_outbound_only_factor,
+            static_peers: static_peer_addresses,
             status_interval,
             ping_interval_inbound,
             ping_interval_outbound,
@@ -153,7 +174,35 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         // Set up the peer manager heartbeat interval
         let heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(HEARTBEAT_INTERVAL));
 
-        Ok(PeerManager {
+        // Group the addresses of the static peers by their peer id.
+        let mut static_peers: HashMap<PeerId, StaticPeer> = HashMap::new();
+        for mut multiaddr in static_peer_addresses {
+            let peer_id = match multiaddr.iter().last() {
+                Some(MProtocol::P2p(multihash)) => PeerId::from_multihash(multihash).ok(),
+                _ => None,
+            };
+            match peer_id {
+                Some(peer_id) => {
+                    // Libp2p dials addresses without the peer id.
+                    multiaddr.pop();
+                    static_peers
+                        .entry(peer_id)
+                        .or_insert_with(|| StaticPeer {
+                            addresses: vec![],
+                            redials: 0,
+                            has_connected: false,
+                        })
+                        .addresses
+                        .push(multiaddr);
+                }
+                None => {
+                    warn!(log, "Ignoring static peer without a peer id"; "address" => %multiaddr)
+                }
+            }
+        }
+        let static_peer_ids = static_peers.keys().copied().collect::<Vec<_>>();
+
+        let mut peer_manager = PeerManager {
             network_globals,
             events: SmallVec::new(),
             peers_to_dial: Default::default(),
@@ -161,13 +210,25 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             outbound_ping_peers: HashSetDelay::new(Duration::from_secs(ping_interval_outbound)),
             status_peers: HashSetDelay::new(Duration::from_secs(status_interval)),
             target_peers: target_peer_count,
+            max_peer_count,
+            min_outbound_only_factor,
+            target_outbound_only_factor: TARGET_OUTBOUND_ONLY_FACTOR.max(min_outbound_only_factor),
             temporary_banned_peers: LRUTimeCache::new(PEER_RECONNECTION_TIMEOUT),
+            static_peers,
+            static_peer_redials: HashSetDelay::new(STATIC_PEER_REDIAL_DELAY),
             sync_committee_subnets: Default::default(),
             heartbeat,
             discovery_enabled,
             metrics_enabled,
             log: log.clone(),
-        })
+        };
+
+        // Connect to the static peers as soon as the swarm is polled.
+        for peer_id in static_peer_ids {
+            peer_manager.dial_static_peer(peer_id);
+        }
+
+        Ok(peer_manager)
     }
 
     /* Public accessible functions */
@@ -372,41 +433,74 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         }
     }
 
-    /// The maximum number of peers we allow to connect to us. This is `target_peers` * (1 +
-    /// PEER_EXCESS_FACTOR)
+    /// The maximum number of peers we allow to connect to us. This is the configured maximum, or
+    /// `target_peers` * (1 + PEER_EXCESS_FACTOR)
     fn max_peers(&self) -> usize {
-        (self.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as usize
+        self.max_peer_count.unwrap_or_else(|| {
+            (self.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as usize
+        })
     }
 
     /// The maximum number of peers we allow when dialing a priority peer (i.e a peer that is
-    /// subscribed to subnets that our validator requires. This is `target_peers` * (1 +
-    /// PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS)
+    /// subscribed to subnets that our validator requires. This is `max_peers` plus
+    /// `target_peers` * PRIORITY_PEER_EXCESS
     fn max_priority_peers(&self) -> usize {
-        (self.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS)).ceil()
-            as usize
+        match self.max_peer_count {
+            Some(max_peers) => {
+                max_peers + (self.target_peers as f32 * PRIORITY_PEER_EXCESS).ceil() as usize
+            }
+            None => (self.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS))
+                .ceil() as usize,
+        }
     }
 
     /// The minimum number of outbound peers that we reach before we start another discovery query.
     fn min_outbound_only_peers(&self) -> usize {
-        (self.target_peers as f32 * MIN_OUTBOUND_ONLY_FACTOR).ceil() as usize
+        (self.target_peers as f32 * self.min_outbound_only_factor).ceil() as usize
     }
 
-    /// The minimum number of outbound peers that we reach before we start another discovery query.
+    /// The number of outbound peers that we keep when pruning peers.
     fn target_outbound_peers(&self) -> usize {
-        (self.target_peers as f32 * TARGET_OUTBOUND_ONLY_FACTOR).ceil() as usize
+        (self.target_peers as f32 * self.target_outbound_only_factor).ceil() as usize
     }
 
     /// The maximum number of peers that are connected or dialing before we refuse to do another
     /// discovery search for more outbound peers. We can use up to half the priority peer excess allocation.
     fn max_outbound_dialing_peers(&self) -> usize {
-        (self.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS / 2.0)).ceil()
-            as usize
+        match self.max_peer_count {
+            Some(max_peers) => {
+                max_peers + (self.target_peers as f32 * PRIORITY_PEER_EXCESS / 2.0).ceil() as usize
+            }
+            None => (self.target_peers as f32
+                * (1.0 + PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS / 2.0))
+                .ceil() as usize,
+        }
     }
 
     /* Notifications from the Swarm */
 
     // A peer is being dialed.
     pub fn dial_peer(&mut self, peer_id: &PeerId, enr: Option<Enr>) {
+        // Don't dial peers which are known to be denied by the access list. Peers are checked
+        // against the full access list once connected, when their IP address is known.
+        let enr_ips = enr
+            .iter()
+            .flat_map(|enr| {
+                enr.ip4()
+                    .map(IpAddr::V4)
+                    .into_iter()
+                    .chain(enr.ip6().map(IpAddr::V6))
+            })
+            .collect::<Vec<_>>();
+        if self
+            .network_globals
+            .access_list
+            .read()
+            .denies(peer_id, &enr_ips)
+        {
+            debug!(self.log, "Not dialing peer denied by the access list"; "peer_id" => %peer_id);
+            return;
+        }
         self.peers_to_dial.insert(*peer_id, enr);
     }
 
@@ -421,6 +515,11 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.network_globals.peers.read().is_connected(peer_id)
     }
 
+    /// Returns `true` if the peer is one of the static peers which are kept connected.
+    pub fn is_static_peer(&self, peer_id: &PeerId) -> bool {
+        self.static_peers.contains_key(peer_id)
+    }
+
     /// Reports whether the peer limit is reached in which case we stop allowing new incoming
     /// connections.
     pub fn peer_limit_reached(&self, count_dialing: bool) -> bool {
@@ -440,6 +539,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             let previous_listening_addresses =
                 peer_info.set_listening_addresses(info.listen_addrs.clone());
             peer_info.set_client(peerdb::client::Client::from_identify_info(info));
+            peer_info.set_protocols(info.protocols.clone());
 
             if previous_kind != peer_info.client().kind
                 || *peer_info.listening_addresses() != previous_listening_addresses
@@ -470,6 +570,18 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         }
     }
 
+    /// Records the size of a gossipsub message received from a peer.
+    pub fn gossip_message_received(&mut self, peer_id: &PeerId, bytes: usize) {
+        if let Some(peer_info) = self.network_globals.peers.write().peer_info_mut(peer_id) {
+            peer_info.add_gossip_bytes_received(bytes);
+            metrics::inc_counter_vec_by(
+                &metrics::GOSSIP_BYTES_RECEIVED_PER_CLIENT,
+                &[peer_info.client().kind.as_ref()],
+                bytes as u64,
+            );
+        }
+    }
+
     /// An error has occurred in the RPC.
     ///
     /// This adjusts a peer's score based on the error.
@@ -703,10 +815,14 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         let mut connected_peer_count = 0;
         let mut inbound_connected_peers = 0;
         let mut outbound_connected_peers = 0;
+        let mut static_connected_peers = 0;
         let mut clients_per_peer = HashMap::new();
 
-        for (_peer, peer_info) in self.network_globals.peers.read().connected_peers() {
+        for (peer_id, peer_info) in self.network_globals.peers.read().connected_peers() {
             connected_peer_count += 1;
+            if self.is_static_peer(peer_id) {
+                static_connected_peers += 1;
+            }
             if let PeerConnectionStatus::Connected { n_in, .. } = peer_info.connection_status() {
                 if *n_in > 0 {
                     inbound_connected_peers += 1;
@@ -722,6 +838,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         metrics::set_gauge(&metrics::PEERS_CONNECTED, connected_peer_count);
         metrics::set_gauge(&metrics::NETWORK_INBOUND_PEERS, inbound_connected_peers);
         metrics::set_gauge(&metrics::NETWORK_OUTBOUND_PEERS, outbound_connected_peers);
+        metrics::set_gauge(&metrics::STATIC_PEERS_CONNECTED, static_connected_peers);
 
         for client_kind in ClientKind::iter() {
             let value = clients_per_peer.get(&client_kind.to_string()).unwrap_or(&0);
@@ -787,6 +904,89 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         );
     }
 
+    /// Dials the static peer `peer_id`, unless it is already connected or being dialed.
+    fn dial_static_peer(&mut self, peer_id: PeerId) {
+        if self
+            .network_globals
+            .peers
+            .read()
+            .is_connected_or_dialing(&peer_id)
+        {
+            return;
+        }
+        if !matches!(self.ban_status(&peer_id), BanResult::NotBanned) {
+            // Try again later, in case the ban has expired.
+            debug!(self.log, "Not dialing banned static peer"; "peer_id" => %peer_id);
+            self.schedule_static_peer_redial(peer_id);
+            return;
+        }
+
+        debug!(self.log, "Dialing static peer"; "peer_id" => %peer_id);
+        metrics::inc_counter(&metrics::STATIC_PEER_DIAL_ATTEMPTS);
+        self.dial_peer(&peer_id, None);
+    }
+
+    /// Schedules the static peer `peer_id` to be redialed, unless a redial is already pending.
+    ///
+    /// The delay doubles with each redial since the peer was last connected, so that unreachable
+    /// peers are dialed less often. Peers which aren't static peers are ignored.
+    fn schedule_static_peer_redial(&mut self, peer_id: PeerId) {
+        let static_peer = match self.static_peers.get_mut(&peer_id) {
+            Some(static_peer) => static_peer,
+            None => return,
+        };
+        if self.static_peer_redials.contains_key(&peer_id) {
+            return;
+        }
+
+        let delay = STATIC_PEER_REDIAL_DELAY
+            .saturating_mul(2_u32.saturating_pow(static_peer.redials))
+            .min(STATIC_PEER_MAX_REDIAL_DELAY);
+        static_peer.redials = static_peer.redials.saturating_add(1);
+        debug!(self.log, "Scheduling static peer redial"; "peer_id" => %peer_id, "delay" => ?delay);
+        self.static_peer_redials.insert_at(peer_id, delay);
+    }
+
+    /// Schedules a redial of any disconnected static peers without one pending, e.g. static peers
+    /// whose dial timed out without being reported as failed.
+    fn maintain_static_peers(&mut self) {
+        let disconnected_peers = {
+            let peers = self.network_globals.peers.read();
+            self.static_peers
+                .keys()
+                .filter(|peer_id| {
+                    !peers.is_connected_or_dialing(peer_id)
+                        && !self.static_peer_redials.contains_key(peer_id)
+                })
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        for peer_id in disconnected_peers {
+            self.schedule_static_peer_redial(peer_id);
+        }
+    }
+
+    /// Disconnects the connected peers which aren't permitted by the access list, which may have
+    /// been updated since they connected.
+    fn enforce_access_list(&mut self) {
+        let peers_to_disconnect = {
+            let access_list = self.network_globals.access_list.read();
+            self.network_globals
+                .peers
+                .read()
+                .connected_peers()
+                .filter(|(peer_id, info)| {
+                    !access_list.permits(peer_id, &info.seen_ip_addresses().collect::<Vec<_>>())
+                })
+                .map(|(peer_id, _)| *peer_id)
+                .collect::<Vec<_>>()
+        };
+        for peer_id in peers_to_disconnect {
+            debug!(self.log, "Disconnecting peer denied by the access list"; "peer_id" => %peer_id);
+            self.disconnect_peer(peer_id, GoodbyeReason::Banned);
+        }
+    }
+
     /// Registers a peer as connected. The `ingoing` parameter determines if the peer is being
     /// dialed or connecting to us.
     ///
@@ -970,7 +1170,12 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                     .read()
                     .worst_connected_peers()
                     .iter()
-                    .filter(|(_, info)| !info.has_future_duty() && $filter(*info))
+                    .filter(|(peer_id, info)| {
+                        !info.has_future_duty()
+                            && !info.is_trusted()
+                            && !self.is_static_peer(peer_id)
+                            && $filter(*info)
+                    })
                 {
                     if peers_to_prune.len()
                         >= connected_peer_count.saturating_sub(self.target_peers)
@@ -1020,8 +1225,12 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             > = HashMap::new();
 
             for (peer_id, info) in self.network_globals.peers.read().connected_peers() {
-                // Ignore peers we are already pruning
-                if peers_to_prune.contains(peer_id) {
+                // Ignore peers we are already pruning, and trusted and static peers which are never
+                // pruned.
+                if peers_to_prune.contains(peer_id)
+                    || info.is_trusted()
+                    || self.is_static_peer(peer_id)
+                {
                     continue;
                 }
 
@@ -1188,6 +1397,12 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         // Maintain minimum count for sync committee peers.
         self.maintain_sync_committee_peers();
 
+        // Redial any static peers which have dropped out of the redial schedule.
+        self.maintain_static_peers();
+
+        // Disconnect any peers which have since been denied by the access list.
+        self.enforce_access_list();
+
         // Prune any excess peers back to our target in such a way that incentivises good scores and
         // a uniform distribution of subnets.
         self.prune_excess_peers();
@@ -1278,6 +1493,16 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     }
 }
 
+/// A peer which is kept connected independently of discovery.
+struct StaticPeer {
+    /// The addresses to dial the peer on, without its peer id.
+    addresses: Vec<Multiaddr>,
+    /// The number of redials since the peer was last connected.
+    redials: u32,
+    /// Whether the peer has been connected since startup.
+    has_connected: bool,
+}
+
 enum ConnectingType {
     /// We are in the process of dialing this peer.
     Dialing,
@@ -1296,6 +1521,7 @@ enum ConnectingType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::AccessListEntry;
     use slog::{o, Drain};
     use types::MainnetEthSpec as E;
 
@@ -1322,6 +1548,20 @@ mod tests {
         PeerManager::new(config, Arc::new(globals), &log).unwrap()
     }
 
+    async fn build_peer_manager_with_trusted_peers(
+        trusted_peers: Vec<PeerId>,
+        target_peer_count: usize,
+    ) -> PeerManager<E> {
+        let config = config::Config {
+            target_peer_count,
+            discovery_enabled: false,
+            ..Default::default()
+        };
+        let log = build_log(slog::Level::Debug, false);
+        let globals = NetworkGlobals::new_test_globals_with_trusted_peers(trusted_peers, &log);
+        PeerManager::new(config, Arc::new(globals), &log).unwrap()
+    }
+
     #[tokio::test]
     async fn test_peer_manager_disconnects_correctly_during_heartbeat() {
         let mut peer_manager = build_peer_manager(3).await;
@@ -1391,6 +1631,141 @@ mod tests {
         assert_eq!(peer_manager.network_globals.connected_or_dialing_peers(), 3);
     }
 
+    #[tokio::test]
+    async fn test_peer_manager_trusted_peers_not_pruned() {
+        let trusted_peer = PeerId::random();
+        let mut peer_manager = build_peer_manager_with_trusted_peers(vec![trusted_peer], 3).await;
+
+        peer_manager.inject_connect_ingoing(&trusted_peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        for _ in 0..4 {
+            let peer = PeerId::random();
+            peer_manager.inject_connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        }
+
+        // Peers are pruned back to the target, but the trusted peer is kept.
+        peer_manager.heartbeat();
+        assert_eq!(peer_manager.network_globals.connected_or_dialing_peers(), 3);
+        assert!(peer_manager
+            .network_globals
+            .peers
+            .read()
+            .is_connected(&trusted_peer));
+    }
+
+    #[tokio::test]
+    async fn test_peer_manager_static_peers() {
+        let static_peer = PeerId::random();
+        let address: Multiaddr = "/ip4/192.0.2.1/tcp/9000".parse().unwrap();
+        let config = config::Config {
+            target_peer_count: 3,
+            discovery_enabled: false,
+            static_peers: vec![address.clone().with(MProtocol::P2p(static_peer.into()))],
+            ..Default::default()
+        };
+        let log = build_log(slog::Level::Debug, false);
+        let globals = NetworkGlobals::new_test_globals(&log);
+        let mut peer_manager: PeerManager<E> =
+            PeerManager::new(config, Arc::new(globals), &log).unwrap();
+
+        // The static peer is dialed on startup, on its address without the peer id.
+        assert!(peer_manager.peers_to_dial.contains_key(&static_peer));
+        assert_eq!(
+            peer_manager.static_peers[&static_peer].addresses,
+            vec![address]
+        );
+
+        peer_manager.inject_connect_outgoing(&static_peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        for _ in 0..4 {
+            let peer = PeerId::random();
+            peer_manager.inject_connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        }
+
+        // Peers are pruned back to the target, but the static peer is kept.
+        peer_manager.heartbeat();
+        assert_eq!(peer_manager.network_globals.connected_or_dialing_peers(), 3);
+        assert!(peer_manager.is_connected(&static_peer));
+
+        // The static peer is redialed after it disconnects, with an increasing delay.
+        peer_manager.inject_disconnect(&static_peer);
+        peer_manager.heartbeat();
+        assert!(peer_manager.static_peer_redials.contains_key(&static_peer));
+        assert_eq!(peer_manager.static_peers[&static_peer].redials, 1);
+    }
+
+    #[tokio::test]
+    async fn test_peer_manager_configured_peer_limits() {
+        let config = config::Config {
+            target_peer_count: 10,
+            max_peer_count: Some(15),
+            min_outbound_only_factor: 0.5,
+            discovery_enabled: false,
+            ..Default::default()
+        };
+        let log = build_log(slog::Level::Debug, false);
+        let globals = NetworkGlobals::new_test_globals(&log);
+        let mut peer_manager: PeerManager<E> =
+            PeerManager::new(config, Arc::new(globals), &log).unwrap();
+
+        assert_eq!(peer_manager.max_peers(), 15);
+        assert_eq!(peer_manager.max_priority_peers(), 17);
+        assert_eq!(peer_manager.max_outbound_dialing_peers(), 16);
+        assert_eq!(peer_manager.min_outbound_only_peers(), 5);
+        // The target is raised to at least the minimum.
+        assert_eq!(peer_manager.target_outbound_peers(), 5);
+
+        for _ in 0..14 {
+            let peer = PeerId::random();
+            peer_manager.inject_connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        }
+        assert!(!peer_manager.peer_limit_reached(true));
+        peer_manager.inject_connect_ingoing(
+            &PeerId::random(),
+            "/ip4/0.0.0.0".parse().unwrap(),
+            None,
+        );
+        assert!(peer_manager.peer_limit_reached(true));
+    }
+
+    #[tokio::test]
+    async fn test_peer_manager_enforces_access_list() {
+        let mut peer_manager = build_peer_manager(3).await;
+        let denied_peer = PeerId::random();
+        let peer_in_denied_subnet = PeerId::random();
+        let other_peer = PeerId::random();
+
+        peer_manager.inject_connect_ingoing(
+            &denied_peer,
+            "/ip4/198.51.100.1/tcp/9000".parse().unwrap(),
+            None,
+        );
+        peer_manager.inject_connect_ingoing(
+            &peer_in_denied_subnet,
+            "/ip4/192.0.2.1/tcp/9000".parse().unwrap(),
+            None,
+        );
+        peer_manager.inject_connect_ingoing(
+            &other_peer,
+            "/ip4/198.51.100.2/tcp/9000".parse().unwrap(),
+            None,
+        );
+
+        // Deny peers at runtime.
+        {
+            let mut access_list = peer_manager.network_globals.access_list.write();
+            access_list.deny(AccessListEntry::Peer(denied_peer));
+            access_list.deny("192.0.2.0/24".parse().unwrap());
+        }
+
+        // Denied peers are disconnected on the next heartbeat, and not dialed again.
+        peer_manager.heartbeat();
+        assert!(!peer_manager.is_connected(&denied_peer));
+        assert!(!peer_manager.is_connected(&peer_in_denied_subnet));
+        assert!(peer_manager.is_connected(&other_peer));
+
+        peer_manager.dial_peer(&denied_peer, None);
+        assert!(!peer_manager.peers_to_dial.contains_key(&denied_peer));
+    }
+
     #[tokio::test]
     async fn test_peer_manager_not_enough_outbound_peers_no_panic_during_heartbeat() {
         let mut peer_manager = build_peer_manager(20).await;
@@ -1,12 +1,14 @@
+use std::net::IpAddr;
 use std::task::{Context, Poll};
 
 use futures::StreamExt;
 use libp2p::core::ConnectedPoint;
+use libp2p::multiaddr::Protocol;
 use libp2p::swarm::behaviour::{ConnectionClosed, ConnectionEstablished, DialFailure, FromSwarm};
 use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
 use libp2p::swarm::dummy::ConnectionHandler;
 use libp2p::swarm::{NetworkBehaviour, NetworkBehaviourAction, PollParameters};
-use libp2p::PeerId;
+use libp2p::{Multiaddr, PeerId};
 use slog::{debug, error};
 use types::EthSpec;
 
@@ -28,6 +30,14 @@ impl<TSpec: EthSpec> NetworkBehaviour for PeerManager<TSpec> {
         ConnectionHandler
     }
 
+    /// Static peers are dialed on their configured addresses, as they may be unknown to discovery.
+    fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.static_peers
+            .get(peer_id)
+            .map(|static_peer| static_peer.addresses.clone())
+            .unwrap_or_default()
+    }
+
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
@@ -65,6 +75,16 @@ impl<TSpec: EthSpec> NetworkBehaviour for PeerManager<TSpec> {
             }
         }
 
+        loop {
+            match self.static_peer_redials.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(peer_id))) => self.dial_static_peer(peer_id),
+                Poll::Ready(Some(Err(e))) => {
+                    error!(self.log, "Failed to check for static peers to redial"; "error" => e.to_string())
+                }
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+
         if !matches!(
             self.network_globals.sync_state(),
             SyncState::SyncingFinalized { .. } | SyncState::SyncingHead { .. }
@@ -172,16 +192,38 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             BanResult::NotBanned => {}
         }
 
+        // Refuse peers which aren't permitted by the access list.
+        let remote_ips = endpoint
+            .get_remote_address()
+            .iter()
+            .filter_map(|proto| match proto {
+                Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+                Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !self
+            .network_globals
+            .access_list
+            .read()
+            .permits(&peer_id, &remote_ips)
+        {
+            debug!(self.log, "Refusing peer denied by the access list"; "peer_id" => %peer_id, "ips" => ?remote_ips);
+            self.disconnect_peer(peer_id, GoodbyeReason::Banned);
+            return;
+        }
+
         // Count dialing peers in the limit if the peer dialed us.
         let count_dialing = endpoint.is_listener();
-        // Check the connection limits
+        // Check the connection limits, which trusted and static peers are exempt from.
         if self.peer_limit_reached(count_dialing)
+            && !self.is_static_peer(&peer_id)
             && self
                 .network_globals
                 .peers
                 .read()
                 .peer_info(&peer_id)
-                .map_or(true, |peer| !peer.has_future_duty())
+                .map_or(true, |peer| !peer.has_future_duty() && !peer.is_trusted())
         {
             // Gracefully disconnect the peer.
             self.disconnect_peer(peer_id, GoodbyeReason::TooManyPeers);
@@ -203,6 +245,18 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             }
         }
 
+        // Reset the redial backoff of static peers.
+        if other_established == 0 {
+            if let Some(static_peer) = self.static_peers.get_mut(&peer_id) {
+                if static_peer.has_connected {
+                    metrics::inc_counter(&metrics::STATIC_PEER_RECONNECTS);
+                }
+                static_peer.has_connected = true;
+                static_peer.redials = 0;
+                self.static_peer_redials.remove(&peer_id);
+            }
+        }
+
         // increment prometheus metrics
         self.update_connected_peer_metrics();
         metrics::inc_counter(&metrics::PEER_CONNECT_EVENT_COUNT);
@@ -234,6 +288,9 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         // reference so that peer manager can track this peer.
         self.inject_disconnect(&peer_id);
 
+        // Static peers are kept connected.
+        self.schedule_static_peer_redial(peer_id);
+
         // Update the prometheus metrics
         self.update_connected_peer_metrics();
         metrics::inc_counter(&metrics::PEER_DISCONNECT_EVENT_COUNT);
@@ -248,6 +305,12 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         if let Some(peer_id) = peer_id {
             if !self.network_globals.peers.read().is_connected(&peer_id) {
                 self.inject_disconnect(&peer_id);
+
+                if self.is_static_peer(&peer_id) {
+                    debug!(self.log, "Failed to dial static peer"; "peer_id" => %peer_id);
+                    metrics::inc_counter(&metrics::STATIC_PEER_DIAL_FAILURES);
+                    self.schedule_static_peer_redial(peer_id);
+                }
             }
         }
     }
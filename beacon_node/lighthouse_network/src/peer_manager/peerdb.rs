@@ -770,13 +770,14 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
                     |(found_ip, found_port), protocol| match protocol {
                         Protocol::Ip4(ip) => (Some(ip.into()), found_port),
                         Protocol::Ip6(ip) => (Some(ip.into()), found_port),
-                        Protocol::Tcp(port) => (found_ip, Some(port)),
+                        // Peers connected over QUIC have a UDP port.
+                        Protocol::Tcp(port) | Protocol::Udp(port) => (found_ip, Some(port)),
                         _ => (found_ip, found_port),
                     },
                 ) {
                     (Some(ip), Some(port)) => Some(SocketAddr::new(ip, port)),
                     (Some(_ip), None) => {
-                        crit!(self.log, "Connected peer has an IP but no TCP port"; "peer_id" => %peer_id);
+                        crit!(self.log, "Connected peer has an IP but no port"; "peer_id" => %peer_id);
                         None
                     }
                     _ => None,
@@ -2049,4 +2050,26 @@ mod tests {
             Score::max_score().score()
         );
     }
+
+    #[test]
+    fn test_gossip_bytes_received() {
+        let mut pdb = get_db();
+        let peer = PeerId::random();
+        pdb.connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        assert_eq!(pdb.peer_info(&peer).unwrap().gossip_bytes_received(), 0);
+
+        let peer_info = pdb.peer_info_mut(&peer).unwrap();
+        peer_info.add_gossip_bytes_received(100);
+        peer_info.add_gossip_bytes_received(28);
+        assert_eq!(pdb.peer_info(&peer).unwrap().gossip_bytes_received(), 128);
+
+        // The count saturates rather than overflowing.
+        let peer_info = pdb.peer_info_mut(&peer).unwrap();
+        peer_info.add_gossip_bytes_received(usize::MAX);
+        peer_info.add_gossip_bytes_received(usize::MAX);
+        assert_eq!(
+            pdb.peer_info(&peer).unwrap().gossip_bytes_received(),
+            u64::MAX
+        );
+    }
 }
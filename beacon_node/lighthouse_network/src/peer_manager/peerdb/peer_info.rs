@@ -51,6 +51,13 @@ pub struct PeerInfo<T: EthSpec> {
     connection_direction: Option<ConnectionDirection>,
     /// The enr of the peer, if known.
     enr: Option<Enr>,
+    /// The protocols the peer supports, as given by identify.
+    protocols: Vec<String>,
+    /// The number of bytes of gossipsub messages received from this peer, before compression.
+    ///
+    /// Only the first copy of each message is counted, and bytes sent to the peer or exchanged
+    /// over RPC aren't tracked.
+    gossip_bytes_received: u64,
 }
 
 impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
@@ -68,6 +75,8 @@ impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
             is_trusted: false,
             connection_direction: None,
             enr: None,
+            protocols: Vec::new(),
+            gossip_bytes_received: 0,
         }
     }
 }
@@ -140,6 +149,16 @@ impl<T: EthSpec> PeerInfo<T> {
         self.enr.as_ref()
     }
 
+    /// The protocols the peer supports, as given by identify.
+    pub fn protocols(&self) -> &[String] {
+        &self.protocols
+    }
+
+    /// The number of bytes of gossipsub messages received from this peer.
+    pub fn gossip_bytes_received(&self) -> u64 {
+        self.gossip_bytes_received
+    }
+
     /// An iterator over all the subnets this peer is subscribed to.
     pub fn subnets(&self) -> impl Iterator<Item = &Subnet> {
         self.subnets.iter()
@@ -336,6 +355,16 @@ impl<T: EthSpec> PeerInfo<T> {
         std::mem::replace(&mut self.listening_addresses, listening_addresses)
     }
 
+    /// Sets the protocols the peer supports.
+    pub(in crate::peer_manager) fn set_protocols(&mut self, protocols: Vec<String>) {
+        self.protocols = protocols;
+    }
+
+    /// Counts the bytes of a gossipsub message received from the peer.
+    pub(in crate::peer_manager) fn add_gossip_bytes_received(&mut self, bytes: usize) {
+        self.gossip_bytes_received = self.gossip_bytes_received.saturating_add(bytes as u64);
+    }
+
     /// Sets an explicit value for the meta data.
     // VISIBILITY: The peer manager is able to adjust the meta_data
     pub(in crate::peer_manager) fn set_meta_data(&mut self, meta_data: MetaData<T>) {
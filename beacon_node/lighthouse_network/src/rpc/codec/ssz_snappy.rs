@@ -3,7 +3,7 @@ use crate::rpc::{
     protocol::{Encoding, Protocol, ProtocolId, RPCError, Version, ERROR_TYPE_MAX, ERROR_TYPE_MIN},
 };
 use crate::rpc::{InboundRequest, OutboundRequest, RPCCodedResponse, RPCResponse};
-use crate::{rpc::methods::*, EnrSyncCommitteeBitfield};
+use crate::{metrics, rpc::methods::*, EnrSyncCommitteeBitfield};
 use libp2p::bytes::BytesMut;
 use snap::read::FrameDecoder;
 use snap::write::FrameEncoder;
@@ -24,6 +24,15 @@ use unsigned_varint::codec::Uvi;
 
 const CONTEXT_BYTES_LEN: usize = 4;
 
+/// Counts `bytes` of a compressed message of `protocol`, sent or received in `direction`.
+fn record_bytes(protocol: &ProtocolId, direction: &str, bytes: usize) {
+    metrics::inc_counter_vec_by(
+        &metrics::RPC_BYTES,
+        &[protocol.message_name.as_ref(), direction],
+        bytes as u64,
+    );
+}
+
 /* Inbound Codec */
 
 pub struct SSZSnappyInboundCodec<TSpec: EthSpec> {
@@ -129,6 +138,7 @@ impl<TSpec: EthSpec> Encoder<RPCCodedResponse<TSpec>> for SSZSnappyInboundCodec<
 
         // Write compressed bytes to `dst`
         dst.extend_from_slice(writer.get_ref());
+        record_bytes(&self.protocol, metrics::OUTBOUND, writer.get_ref().len());
         Ok(())
     }
 }
@@ -170,6 +180,7 @@ impl<TSpec: EthSpec> Decoder for SSZSnappyInboundCodec<TSpec> {
                 let n = reader.get_ref().get_ref().position();
                 self.len = None;
                 let _read_bytes = src.split_to(n as usize);
+                record_bytes(&self.protocol, metrics::INBOUND, n as usize);
 
                 match self.protocol.version {
                     Version::V1 => handle_v1_request(self.protocol.message_name, &decoded_buffer),
@@ -253,6 +264,7 @@ impl<TSpec: EthSpec> Encoder<OutboundRequest<TSpec>> for SSZSnappyOutboundCodec<
 
         // Write compressed bytes to `dst`
         dst.extend_from_slice(writer.get_ref());
+        record_bytes(&self.protocol, metrics::OUTBOUND, writer.get_ref().len());
         Ok(())
     }
 }
@@ -311,6 +323,7 @@ impl<TSpec: EthSpec> Decoder for SSZSnappyOutboundCodec<TSpec> {
                 let n = reader.get_ref().get_ref().position();
                 self.len = None;
                 let _read_bytes = src.split_to(n as usize);
+                record_bytes(&self.protocol, metrics::INBOUND, n as usize);
 
                 match self.protocol.version {
                     Version::V1 => handle_v1_response(self.protocol.message_name, &decoded_buffer),
@@ -359,6 +372,7 @@ impl<TSpec: EthSpec> OutboundCodec<OutboundRequest<TSpec>> for SSZSnappyOutbound
                 let n = reader.get_ref().get_ref().position();
                 self.len = None;
                 let _read_bytes = src.split_to(n as usize);
+                record_bytes(&self.protocol, metrics::INBOUND, n as usize);
                 Ok(Some(ErrorType(VariableList::from_ssz_bytes(
                     &decoded_buffer,
                 )?)))
@@ -1500,6 +1514,46 @@ mod tests {
         ));
     }
 
+    /// Test that the compressed bytes of messages sent and received are counted by protocol.
+    #[test]
+    fn test_rpc_bytes_metrics() {
+        let bytes = |direction| {
+            metrics::get_int_counter(&metrics::RPC_BYTES, &[Protocol::Status.as_ref(), direction])
+                .map_or(0, |counter| counter.get())
+        };
+        let sent = bytes(metrics::OUTBOUND);
+        let received = bytes(metrics::INBOUND);
+
+        encode_then_decode_response(
+            Protocol::Status,
+            Version::V1,
+            RPCCodedResponse::Success(RPCResponse::Status(status_message())),
+            ForkName::Base,
+        )
+        .unwrap();
+
+        assert!(bytes(metrics::OUTBOUND) > sent);
+        assert!(bytes(metrics::INBOUND) > received);
+    }
+
+    /// Test that the length of a message before compression, which is counted by client, matches
+    /// the length prefix written by the codec.
+    #[test]
+    fn test_ssz_bytes_len() {
+        let response = RPCCodedResponse::Success(RPCResponse::Status(status_message()));
+        let expected_len = response.ssz_bytes_len();
+        let buf = encode_response(Protocol::Status, Version::V1, response, ForkName::Base).unwrap();
+        // Skip the response code.
+        let (len, _) = unsigned_varint::decode::usize(&buf[1..]).unwrap();
+        assert_eq!(len, expected_len);
+
+        let request = OutboundRequest::<Spec>::BlocksByRoot(bbroot_request());
+        assert_eq!(
+            request.ssz_bytes_len(),
+            bbroot_request().block_roots.as_ssz_bytes().len()
+        );
+    }
+
     /// Test sending a message with encoded length prefix > max_rpc_size.
     #[test]
     fn test_decode_invalid_length() {
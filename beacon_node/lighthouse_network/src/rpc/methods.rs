@@ -3,6 +3,7 @@
 use crate::types::{EnrAttestationBitfield, EnrSyncCommitteeBitfield};
 use regex::bytes::Regex;
 use serde::Serialize;
+use ssz::Encode;
 use ssz_derive::{Decode, Encode};
 use ssz_types::{
     typenum::{U1024, U256},
@@ -344,6 +345,15 @@ impl<T: EthSpec> RPCCodedResponse<T> {
     pub fn close_after(&self) -> bool {
         !matches!(self, RPCCodedResponse::Success(_))
     }
+
+    /// The length of the SSZ encoding of the response, before compression.
+    pub fn ssz_bytes_len(&self) -> usize {
+        match self {
+            RPCCodedResponse::Success(resp) => resp.ssz_bytes_len(),
+            RPCCodedResponse::Error(_, err) => err.0.len(),
+            RPCCodedResponse::StreamTermination(_) => 0,
+        }
+    }
 }
 
 impl RPCResponseErrorCode {
@@ -370,6 +380,18 @@ impl<T: EthSpec> RPCResponse<T> {
             RPCResponse::LightClientBootstrap(_) => Protocol::LightClientBootstrap,
         }
     }
+
+    /// The length of the SSZ encoding of the response, before compression.
+    pub fn ssz_bytes_len(&self) -> usize {
+        match self {
+            RPCResponse::Status(res) => res.ssz_bytes_len(),
+            RPCResponse::BlocksByRange(res) => res.ssz_bytes_len(),
+            RPCResponse::BlocksByRoot(res) => res.ssz_bytes_len(),
+            RPCResponse::LightClientBootstrap(res) => res.ssz_bytes_len(),
+            RPCResponse::Pong(res) => res.data.ssz_bytes_len(),
+            RPCResponse::MetaData(res) => res.ssz_bytes_len(),
+        }
+    }
 }
 
 impl std::fmt::Display for RPCResponseErrorCode {
@@ -14,6 +14,7 @@ use libp2p::swarm::{
 use libp2p::PeerId;
 use rate_limiter::{RPCRateLimiter as RateLimiter, RateLimitedErr};
 use slog::{crit, debug, o};
+use std::collections::HashSet;
 use std::marker::PhantomData;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -115,6 +116,8 @@ pub struct RPC<Id: ReqId, TSpec: EthSpec> {
     limiter: RateLimiter,
     /// Rate limiter for our own requests.
     self_limiter: Option<SelfRateLimiter<Id, TSpec>>,
+    /// Trusted peers, whose requests are not rate limited.
+    trusted_peers: HashSet<PeerId>,
     /// Queue of events to be processed.
     events: Vec<BehaviourAction<Id, TSpec>>,
     fork_context: Arc<ForkContext>,
@@ -128,6 +131,7 @@ impl<Id: ReqId, TSpec: EthSpec> RPC<Id, TSpec> {
         fork_context: Arc<ForkContext>,
         enable_light_client_server: bool,
         outbound_rate_limiter_config: Option<OutboundRateLimiterConfig>,
+        trusted_peers: HashSet<PeerId>,
         log: slog::Logger,
     ) -> Self {
         let log = log.new(o!("service" => "libp2p_rpc"));
@@ -154,6 +158,7 @@ impl<Id: ReqId, TSpec: EthSpec> RPC<Id, TSpec> {
         RPC {
             limiter,
             self_limiter,
+            trusted_peers,
             events: Vec::new(),
             fork_context,
             enable_light_client_server,
@@ -242,8 +247,13 @@ where
         event: <Self::ConnectionHandler as ConnectionHandler>::OutEvent,
     ) {
         if let Ok(RPCReceived::Request(ref id, ref req)) = event {
-            // check if the request is conformant to the quota
-            match self.limiter.allows(&peer_id, req) {
+            // check if the request is conformant to the quota, unless the peer is trusted
+            let allowed = if self.trusted_peers.contains(&peer_id) {
+                Ok(())
+            } else {
+                self.limiter.allows(&peer_id, req)
+            };
+            match allowed {
                 Ok(()) => {
                     // send the event to the user
                     self.events
@@ -14,6 +14,7 @@ use futures::future::BoxFuture;
 use futures::prelude::{AsyncRead, AsyncWrite};
 use futures::{FutureExt, SinkExt};
 use libp2p::core::{OutboundUpgrade, UpgradeInfo};
+use ssz::Encode;
 use std::sync::Arc;
 use tokio_util::{
     codec::Framed,
@@ -119,6 +120,19 @@ impl<TSpec: EthSpec> OutboundRequest<TSpec> {
         }
     }
 
+    /// The length of the SSZ encoding of the request, before compression.
+    pub fn ssz_bytes_len(&self) -> usize {
+        match self {
+            OutboundRequest::Status(req) => req.ssz_bytes_len(),
+            OutboundRequest::Goodbye(req) => req.ssz_bytes_len(),
+            OutboundRequest::BlocksByRange(req) => req.ssz_bytes_len(),
+            OutboundRequest::BlocksByRoot(req) => req.block_roots.ssz_bytes_len(),
+            OutboundRequest::Ping(req) => req.ssz_bytes_len(),
+            OutboundRequest::MetaData(_) => 0,
+            OutboundRequest::LightClientBootstrap(req) => req.ssz_bytes_len(),
+        }
+    }
+
     /// Returns the `ResponseTermination` type associated with the request if a stream gets
     /// terminated.
     pub fn stream_termination(&self) -> ResponseTermination {
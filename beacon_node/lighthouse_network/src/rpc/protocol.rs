@@ -481,6 +481,19 @@ impl<TSpec: EthSpec> InboundRequest<TSpec> {
         }
     }
 
+    /// The length of the SSZ encoding of the request, before compression.
+    pub fn ssz_bytes_len(&self) -> usize {
+        match self {
+            InboundRequest::Status(req) => req.ssz_bytes_len(),
+            InboundRequest::Goodbye(req) => req.ssz_bytes_len(),
+            InboundRequest::BlocksByRange(req) => req.ssz_bytes_len(),
+            InboundRequest::BlocksByRoot(req) => req.block_roots.ssz_bytes_len(),
+            InboundRequest::Ping(req) => req.ssz_bytes_len(),
+            InboundRequest::MetaData(_) => 0,
+            InboundRequest::LightClientBootstrap(req) => req.ssz_bytes_len(),
+        }
+    }
+
     /// Returns the `ResponseTermination` type associated with the request if a stream gets
     /// terminated.
     pub fn stream_termination(&self) -> ResponseTermination {
@@ -3,8 +3,9 @@ use crate::{error, TopicHash};
 use libp2p::gossipsub::{
     GossipsubConfig, IdentTopic as Topic, PeerScoreParams, PeerScoreThresholds, TopicScoreParams,
 };
+use serde_derive::{Deserialize, Serialize};
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 use std::time::Duration;
 use types::{ChainSpec, EnrForkId, EthSpec, Slot, SubnetId};
@@ -34,6 +35,153 @@ pub fn lighthouse_gossip_thresholds() -> PeerScoreThresholds {
     }
 }
 
+/// Sets each of the `fields` of `$target` which is set in `$overrides`.
+macro_rules! apply_overrides {
+    ($overrides:expr, $target:expr, $($field:ident),+ $(,)?) => {
+        $(
+            if let Some(value) = $overrides.$field {
+                $target.$field = value;
+            }
+        )+
+    };
+}
+
+/// Overrides of the peer scoring parameters, which are otherwise derived from the chain spec.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerScoreOverrides {
+    pub thresholds: PeerScoreThresholdOverrides,
+    pub params: PeerScoreParamOverrides,
+    /// Overrides of the parameters of each scored topic, by the name of its kind. The parameters of
+    /// `beacon_attestation` apply to every attestation subnet.
+    pub topics: BTreeMap<String, TopicScoreOverrides>,
+}
+
+/// Overrides of `PeerScoreThresholds`.
+///
+/// The graylist threshold can't be overridden, since the peer manager scales gossipsub scores by it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerScoreThresholdOverrides {
+    pub gossip_threshold: Option<f64>,
+    pub publish_threshold: Option<f64>,
+    pub accept_px_threshold: Option<f64>,
+    pub opportunistic_graft_threshold: Option<f64>,
+}
+
+/// Overrides of the topic-independent fields of `PeerScoreParams`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerScoreParamOverrides {
+    pub topic_score_cap: Option<f64>,
+    pub app_specific_weight: Option<f64>,
+    pub ip_colocation_factor_weight: Option<f64>,
+    pub ip_colocation_factor_threshold: Option<f64>,
+    pub behaviour_penalty_weight: Option<f64>,
+    pub behaviour_penalty_threshold: Option<f64>,
+    pub behaviour_penalty_decay: Option<f64>,
+    pub decay_to_zero: Option<f64>,
+}
+
+/// Overrides of `TopicScoreParams`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TopicScoreOverrides {
+    pub topic_weight: Option<f64>,
+    pub time_in_mesh_weight: Option<f64>,
+    pub time_in_mesh_cap: Option<f64>,
+    pub first_message_deliveries_weight: Option<f64>,
+    pub first_message_deliveries_decay: Option<f64>,
+    pub first_message_deliveries_cap: Option<f64>,
+    pub mesh_message_deliveries_weight: Option<f64>,
+    pub mesh_message_deliveries_decay: Option<f64>,
+    pub mesh_message_deliveries_threshold: Option<f64>,
+    pub mesh_message_deliveries_cap: Option<f64>,
+    pub mesh_failure_penalty_weight: Option<f64>,
+    pub mesh_failure_penalty_decay: Option<f64>,
+    pub invalid_message_deliveries_weight: Option<f64>,
+    pub invalid_message_deliveries_decay: Option<f64>,
+}
+
+/// The names of the kinds of topics which are scored, and may be overridden.
+const SCORED_TOPIC_KINDS: [&str; 6] = [
+    "beacon_block",
+    "beacon_aggregate_and_proof",
+    "beacon_attestation",
+    "voluntary_exit",
+    "proposer_slashing",
+    "attester_slashing",
+];
+
+impl PeerScoreOverrides {
+    /// Returns an error if any of the overridden topics aren't scored.
+    pub fn validate(&self) -> Result<(), String> {
+        match self
+            .topics
+            .keys()
+            .find(|kind| !SCORED_TOPIC_KINDS.contains(&kind.as_str()))
+        {
+            Some(kind) => Err(format!(
+                "Unable to override scoring of topic {}, expected one of: {}",
+                kind,
+                SCORED_TOPIC_KINDS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_to_thresholds(&self, thresholds: &mut PeerScoreThresholds) {
+        apply_overrides!(
+            self.thresholds,
+            thresholds,
+            gossip_threshold,
+            publish_threshold,
+            accept_px_threshold,
+            opportunistic_graft_threshold,
+        );
+    }
+
+    fn apply_to_params(&self, params: &mut PeerScoreParams) {
+        apply_overrides!(
+            self.params,
+            params,
+            topic_score_cap,
+            app_specific_weight,
+            ip_colocation_factor_weight,
+            ip_colocation_factor_threshold,
+            behaviour_penalty_weight,
+            behaviour_penalty_threshold,
+            behaviour_penalty_decay,
+            decay_to_zero,
+        );
+    }
+
+    fn apply_to_topic_params(&self, kind: &GossipKind, params: &mut TopicScoreParams) {
+        let overrides = match self.topics.get(kind.as_ref()) {
+            Some(overrides) => overrides,
+            None => return,
+        };
+        apply_overrides!(
+            overrides,
+            params,
+            topic_weight,
+            time_in_mesh_weight,
+            time_in_mesh_cap,
+            first_message_deliveries_weight,
+            first_message_deliveries_decay,
+            first_message_deliveries_cap,
+            mesh_message_deliveries_weight,
+            mesh_message_deliveries_decay,
+            mesh_message_deliveries_threshold,
+            mesh_message_deliveries_cap,
+            mesh_failure_penalty_weight,
+            mesh_failure_penalty_decay,
+            invalid_message_deliveries_weight,
+            invalid_message_deliveries_decay,
+        );
+    }
+}
+
 pub struct PeerScoreSettings<TSpec: EthSpec> {
     slot: Duration,
     epoch: Duration,
@@ -49,11 +197,16 @@ pub struct PeerScoreSettings<TSpec: EthSpec> {
     target_committee_size: usize,
     target_aggregators_per_committee: usize,
     attestation_subnet_count: u64,
+    overrides: PeerScoreOverrides,
     phantom: PhantomData<TSpec>,
 }
 
 impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
-    pub fn new(chain_spec: &ChainSpec, gs_config: &GossipsubConfig) -> PeerScoreSettings<TSpec> {
+    pub fn new(
+        chain_spec: &ChainSpec,
+        gs_config: &GossipsubConfig,
+        overrides: PeerScoreOverrides,
+    ) -> PeerScoreSettings<TSpec> {
         let slot = Duration::from_secs(chain_spec.seconds_per_slot);
         let beacon_attestation_subnet_weight = 1.0 / chain_spec.attestation_subnet_count as f64;
         let max_positive_score = (MAX_IN_MESH_SCORE + MAX_FIRST_MESSAGE_DELIVERIES_SCORE)
@@ -76,10 +229,18 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
             target_committee_size: chain_spec.target_committee_size,
             target_aggregators_per_committee: chain_spec.target_aggregators_per_committee as usize,
             attestation_subnet_count: chain_spec.attestation_subnet_count,
+            overrides,
             phantom: PhantomData,
         }
     }
 
+    /// Returns the peer score thresholds, with any overrides applied.
+    pub fn thresholds(&self) -> PeerScoreThresholds {
+        let mut thresholds = lighthouse_gossip_thresholds();
+        self.overrides.apply_to_thresholds(&mut thresholds);
+        thresholds
+    }
+
     pub fn get_peer_score_params(
         &self,
         active_validators: usize,
@@ -106,6 +267,7 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
 
         params.topic_score_cap = self.max_positive_score * 0.5;
         params.ip_colocation_factor_weight = -params.topic_score_cap;
+        self.overrides.apply_to_params(&mut params);
 
         params.topics = HashMap::new();
 
@@ -118,8 +280,8 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         //first all fixed topics
         params.topics.insert(
             get_hash(GossipKind::VoluntaryExit),
-            Self::get_topic_params(
-                self,
+            self.get_topic_params_with_overrides(
+                GossipKind::VoluntaryExit,
                 VOLUNTARY_EXIT_WEIGHT,
                 4.0 / TSpec::slots_per_epoch() as f64,
                 self.epoch * 100,
@@ -128,8 +290,8 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         );
         params.topics.insert(
             get_hash(GossipKind::AttesterSlashing),
-            Self::get_topic_params(
-                self,
+            self.get_topic_params_with_overrides(
+                GossipKind::AttesterSlashing,
                 ATTESTER_SLASHING_WEIGHT,
                 1.0 / 5.0 / TSpec::slots_per_epoch() as f64,
                 self.epoch * 100,
@@ -138,8 +300,8 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         );
         params.topics.insert(
             get_hash(GossipKind::ProposerSlashing),
-            Self::get_topic_params(
-                self,
+            self.get_topic_params_with_overrides(
+                GossipKind::ProposerSlashing,
                 PROPOSER_SLASHING_WEIGHT,
                 1.0 / 5.0 / TSpec::slots_per_epoch() as f64,
                 self.epoch * 100,
@@ -180,23 +342,23 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         let multiple_bursts_per_subnet_per_epoch = committees_per_slot as u64
             >= 2 * self.attestation_subnet_count / TSpec::slots_per_epoch();
 
-        let beacon_block_params = Self::get_topic_params(
-            self,
+        let beacon_block_params = self.get_topic_params_with_overrides(
+            GossipKind::BeaconBlock,
             BEACON_BLOCK_WEIGHT,
             1.0,
             self.epoch * 20,
             Some((TSpec::slots_per_epoch() * 5, 3.0, self.epoch, current_slot)),
         );
 
-        let beacon_aggregate_proof_params = Self::get_topic_params(
-            self,
+        let beacon_aggregate_proof_params = self.get_topic_params_with_overrides(
+            GossipKind::BeaconAggregateAndProof,
             BEACON_AGGREGATE_PROOF_WEIGHT,
             aggregators_per_slot,
             self.epoch,
             Some((TSpec::slots_per_epoch() * 2, 4.0, self.epoch, current_slot)),
         );
-        let beacon_attestation_subnet_params = Self::get_topic_params(
-            self,
+        let beacon_attestation_subnet_params = self.get_topic_params_with_overrides(
+            GossipKind::Attestation(SubnetId::new(0)),
             self.beacon_attestation_subnet_weight,
             active_validators as f64
                 / self.attestation_subnet_count as f64
@@ -291,6 +453,32 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         Self::score_parameter_decay_with_base(decay_time, self.decay_interval, self.decay_to_zero)
     }
 
+    /// Returns the parameters of topics of `kind` from `Self::get_topic_params`, with any
+    /// overrides applied. An overridden topic weight is also used to derive the other parameters.
+    fn get_topic_params_with_overrides(
+        &self,
+        kind: GossipKind,
+        topic_weight: f64,
+        expected_message_rate: f64,
+        first_message_decay_time: Duration,
+        mesh_message_info: Option<(u64, f64, Duration, Slot)>,
+    ) -> TopicScoreParams {
+        let topic_weight = self
+            .overrides
+            .topics
+            .get(kind.as_ref())
+            .and_then(|overrides| overrides.topic_weight)
+            .unwrap_or(topic_weight);
+        let mut params = self.get_topic_params(
+            topic_weight,
+            expected_message_rate,
+            first_message_decay_time,
+            mesh_message_info,
+        );
+        self.overrides.apply_to_topic_params(&kind, &mut params);
+        params
+    }
+
     fn get_topic_params(
         &self,
         topic_weight: f64,
@@ -357,3 +545,69 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         t_params
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MainnetEthSpec;
+
+    #[test]
+    fn test_score_overrides() {
+        let spec = ChainSpec::mainnet();
+        let gs_config = GossipsubConfig::default();
+        let enr_fork_id = EnrForkId::default();
+        let active_validators = 100_000;
+        let defaults = PeerScoreSettings::<MainnetEthSpec>::new(
+            &spec,
+            &gs_config,
+            PeerScoreOverrides::default(),
+        );
+
+        let mut overrides = PeerScoreOverrides::default();
+        overrides.thresholds.gossip_threshold = Some(-1000.0);
+        overrides.params.app_specific_weight = Some(2.0);
+        overrides.topics.insert(
+            "beacon_attestation".to_string(),
+            TopicScoreOverrides {
+                first_message_deliveries_cap: Some(1.0),
+                ..Default::default()
+            },
+        );
+        overrides.validate().unwrap();
+        let settings = PeerScoreSettings::<MainnetEthSpec>::new(&spec, &gs_config, overrides);
+
+        let thresholds = settings.thresholds();
+        assert_eq!(thresholds.gossip_threshold, -1000.0);
+        assert_eq!(
+            thresholds.publish_threshold,
+            defaults.thresholds().publish_threshold
+        );
+
+        let params = settings
+            .get_peer_score_params(active_validators, &thresholds, &enr_fork_id, Slot::new(0))
+            .unwrap();
+        assert_eq!(params.app_specific_weight, 2.0);
+
+        let (block, _, attestation) = settings
+            .get_dynamic_topic_params(active_validators, Slot::new(0))
+            .unwrap();
+        let (default_block, _, default_attestation) = defaults
+            .get_dynamic_topic_params(active_validators, Slot::new(0))
+            .unwrap();
+        assert_eq!(attestation.first_message_deliveries_cap, 1.0);
+        assert_eq!(attestation.topic_weight, default_attestation.topic_weight);
+        assert_eq!(
+            block.first_message_deliveries_cap,
+            default_block.first_message_deliveries_cap
+        );
+    }
+
+    #[test]
+    fn test_score_overrides_unscored_topic() {
+        let mut overrides = PeerScoreOverrides::default();
+        overrides
+            .topics
+            .insert("sync_committee".to_string(), Default::default());
+        assert!(overrides.validate().is_err());
+    }
+}
@@ -8,7 +8,7 @@ use crate::peer_manager::{
     config::Config as PeerManagerCfg, peerdb::score::PeerAction, peerdb::score::ReportSource,
     ConnectionDirection, PeerManager, PeerManagerEvent,
 };
-use crate::peer_manager::{MIN_OUTBOUND_ONLY_FACTOR, PEER_EXCESS_FACTOR, PRIORITY_PEER_EXCESS};
+use crate::peer_manager::{PEER_EXCESS_FACTOR, PRIORITY_PEER_EXCESS};
 use crate::rpc::*;
 use crate::service::behaviour::BehaviourEvent;
 pub use crate::service::behaviour::Gossipsub;
@@ -18,10 +18,10 @@ use crate::types::{
 };
 use crate::EnrExt;
 use crate::Eth2Enr;
-use crate::{error, metrics, Enr, NetworkGlobals, PubsubMessage, TopicHash};
+use crate::{error, metrics, AccessList, Enr, NetworkGlobals, PubsubMessage, TopicHash};
 use api_types::{PeerRequestId, Request, RequestId, Response};
 use futures::stream::StreamExt;
-use gossipsub_scoring_parameters::{lighthouse_gossip_thresholds, PeerScoreSettings};
+use gossipsub_scoring_parameters::PeerScoreSettings;
 use libp2p::bandwidth::BandwidthSinks;
 use libp2p::gossipsub::error::PublishError;
 use libp2p::gossipsub::metrics::Config as GossipsubMetricsConfig;
@@ -33,6 +33,7 @@ use libp2p::identify::{Behaviour as Identify, Config as IdentifyConfig, Event as
 use libp2p::multiaddr::{Multiaddr, Protocol as MProtocol};
 use libp2p::swarm::{ConnectionLimits, Swarm, SwarmBuilder, SwarmEvent};
 use libp2p::PeerId;
+use lru::LruCache;
 use slog::{crit, debug, info, o, trace, warn};
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -57,6 +58,10 @@ pub const TARGET_SUBNET_PEERS: usize = 6;
 
 const MAX_IDENTIFY_ADDRESSES: usize = 10;
 
+/// The number of received gossip messages awaiting validation whose topic and size are kept, so
+/// that their bytes can be counted when gossipsub forwards them.
+const GOSSIP_PENDING_VALIDATION_CACHE_SIZE: usize = 16_384;
+
 /// The types of events than can be obtained from polling the behaviour.
 #[derive(Debug)]
 pub enum NetworkEvent<AppReqId: ReqId, TSpec: EthSpec> {
@@ -129,6 +134,8 @@ pub struct Network<AppReqId: ReqId, TSpec: EthSpec> {
     /// The interval for updating gossipsub scores
     update_gossipsub_scores: tokio::time::Interval,
     gossip_cache: GossipCache,
+    /// The topic and size of received gossip messages awaiting validation.
+    gossip_pending_validation: LruCache<MessageId, (TopicHash, usize)>,
     /// The bandwidth logger for the underlying libp2p transport.
     pub bandwidth: Arc<BandwidthSinks>,
     /// This node's PeerId.
@@ -172,6 +179,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                     .map(|x| PeerId::from(x.clone()))
                     .collect(),
                 config.disable_peer_scoring,
+                AccessList::new(config.peer_allowlist.clone(), config.peer_denylist.clone()),
                 &log,
             );
             Arc::new(globals)
@@ -183,7 +191,11 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
             .eth2()
             .expect("Local ENR must have a fork id");
 
-        let score_settings = PeerScoreSettings::new(ctx.chain_spec, &config.gs_config);
+        let score_settings = PeerScoreSettings::new(
+            ctx.chain_spec,
+            &config.gs_config,
+            config.gossipsub_score_overrides.clone(),
+        );
 
         let gossip_cache = {
             let slot_duration = std::time::Duration::from_secs(ctx.chain_spec.seconds_per_slot);
@@ -207,7 +219,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         let local_peer_id = network_globals.local_peer_id();
 
         let (gossipsub, update_gossipsub_scores) = {
-            let thresholds = lighthouse_gossip_thresholds();
+            let thresholds = score_settings.thresholds();
 
             // Prepare scoring parameters
             let params = {
@@ -258,7 +270,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
 
             gossipsub
                 .with_peer_score(params, thresholds)
-                .expect("Valid score params and thresholds");
+                .map_err(|e| format!("Invalid gossipsub scoring parameters: {}", e))?;
 
             (gossipsub, update_gossipsub_scores)
         };
@@ -267,6 +279,11 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
             ctx.fork_context.clone(),
             config.enable_light_client_server,
             config.outbound_rate_limiter_config.clone(),
+            config
+                .trusted_peers
+                .iter()
+                .map(|x| PeerId::from(x.clone()))
+                .collect(),
             log.clone(),
         );
 
@@ -299,6 +316,9 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                 discovery_enabled: !config.disable_discovery,
                 metrics_enabled: config.metrics_enabled,
                 target_peer_count: config.target_peers,
+                max_peer_count: config.max_peers,
+                min_outbound_only_factor: config.min_outbound_peers_ratio,
+                static_peers: config.static_peers.clone(),
                 ..Default::default()
             };
             PeerManager::new(peer_manager_cfg, network_globals.clone(), &log)?
@@ -315,9 +335,18 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         };
 
         let (swarm, bandwidth) = {
-            // Set up the transport - tcp/ws with noise and mplex
-            let (transport, bandwidth) = build_transport(local_keypair.clone())
-                .map_err(|e| format!("Failed to build transport: {:?}", e))?;
+            // QUIC connections would bypass the proxy.
+            if config.proxy.is_some() && config.listen_addrs().quic_enabled() {
+                return Err("QUIC can't be enabled when dialing through a proxy".into());
+            }
+
+            // Set up the transport - tcp/ws with noise and mplex, and optionally quic
+            let (transport, bandwidth) = build_transport(
+                local_keypair.clone(),
+                config.listen_addrs().quic_enabled(),
+                config.proxy.as_ref(),
+            )
+            .map_err(|e| format!("Failed to build transport: {:?}", e))?;
 
             // use the executor for libp2p
             struct Executor(task_executor::TaskExecutor);
@@ -327,22 +356,23 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                 }
             }
 
-            // sets up the libp2p connection limits
+            // sets up the libp2p connection limits, leaving room for the minimum number of
+            // outbound peers and for priority peers
+            let max_peers = config.max_peers.unwrap_or_else(|| {
+                (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as usize
+            });
+            let min_outbound_peers =
+                (config.target_peers as f32 * config.min_outbound_peers_ratio).ceil() as usize;
+            let priority_peer_excess =
+                (config.target_peers as f32 * PRIORITY_PEER_EXCESS).ceil() as usize;
             let limits = ConnectionLimits::default()
                 .with_max_pending_incoming(Some(5))
                 .with_max_pending_outgoing(Some(16))
                 .with_max_established_incoming(Some(
-                    (config.target_peers as f32
-                        * (1.0 + PEER_EXCESS_FACTOR - MIN_OUTBOUND_ONLY_FACTOR))
-                        .ceil() as u32,
-                ))
-                .with_max_established_outgoing(Some(
-                    (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as u32,
-                ))
-                .with_max_established(Some(
-                    (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS))
-                        .ceil() as u32,
+                    max_peers.saturating_sub(min_outbound_peers) as u32
                 ))
+                .with_max_established_outgoing(Some(max_peers as u32))
+                .with_max_established(Some((max_peers + priority_peer_excess) as u32))
                 .with_max_established_per_peer(Some(MAX_CONNECTIONS_PER_PEER));
 
             (
@@ -369,6 +399,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
             score_settings,
             update_gossipsub_scores,
             gossip_cache,
+            gossip_pending_validation: LruCache::new(GOSSIP_PENDING_VALIDATION_CACHE_SIZE),
             bandwidth,
             local_peer_id,
             log,
@@ -391,7 +422,11 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         info!(self.log, "Libp2p Starting"; "peer_id" => %enr.peer_id(), "bandwidth_config" => format!("{}-{}", config.network_load, NetworkLoad::from(config.network_load).name));
         debug!(self.log, "Attempting to open listening ports"; config.listen_addrs(), "discovery_enabled" => !config.disable_discovery);
 
-        for listen_multiaddr in config.listen_addrs().tcp_addresses() {
+        let listen_addrs = config.listen_addrs();
+        for listen_multiaddr in listen_addrs
+            .tcp_addresses()
+            .chain(listen_addrs.quic_addresses())
+        {
             match self.swarm.listen_on(listen_multiaddr.clone()) {
                 Ok(_) => {
                     let mut log_address = listen_multiaddr;
@@ -405,7 +440,10 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                         "error" => ?err,
                         "listen_multiaddr" => %listen_multiaddr,
                     );
-                    return Err("Libp2p was unable to listen on the given listen address.".into());
+                    return Err(error::ErrorKind::Listen(
+                        "Libp2p was unable to listen on the given listen address.".into(),
+                    )
+                    .into());
                 }
             };
         }
@@ -632,10 +670,9 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         for message in messages {
             for topic in message.topics(GossipEncoding::default(), self.enr_fork_id.fork_digest) {
                 let message_data = message.encode(GossipEncoding::default());
-                if let Err(e) = self
-                    .gossipsub_mut()
-                    .publish(Topic::from(topic.clone()), message_data.clone())
-                {
+                let gs_topic = Topic::from(topic.clone());
+                let topic_hash = gs_topic.hash();
+                if let Err(e) = self.gossipsub_mut().publish(gs_topic, message_data.clone()) {
                     slog::warn!(self.log, "Could not publish message"; "error" => ?e);
 
                     // add to metrics
@@ -661,11 +698,65 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                     if let PublishError::InsufficientPeers = e {
                         self.gossip_cache.insert(topic, message_data);
                     }
+                } else {
+                    self.record_gossip_bytes_published(&topic_hash, message_data.len());
                 }
             }
         }
     }
 
+    /// Counts `bytes` of a gossip message on `topic` sent to each of `peers`, by topic and by the
+    /// client of each peer.
+    fn record_gossip_bytes_sent(&self, topic: &TopicHash, peers: &[PeerId], bytes: usize) {
+        if let Ok(topic) = GossipTopic::decode(topic.as_str()) {
+            metrics::inc_counter_vec_by(
+                &metrics::GOSSIP_BYTES,
+                &[topic.kind().as_ref(), metrics::OUTBOUND],
+                (bytes * peers.len()) as u64,
+            );
+        }
+        let peer_db = self.network_globals.peers.read();
+        for peer_id in peers {
+            if let Some(peer_info) = peer_db.peer_info(peer_id) {
+                metrics::inc_counter_vec_by(
+                    &metrics::GOSSIP_BYTES_SENT_PER_CLIENT,
+                    &[peer_info.client().kind.as_ref()],
+                    bytes as u64,
+                );
+            }
+        }
+    }
+
+    /// Counts the bytes of a message published on `topic`.
+    ///
+    /// Messages are flood published, so they are sent to every peer subscribed to the topic.
+    fn record_gossip_bytes_published(&self, topic: &TopicHash, bytes: usize) {
+        let peers = self
+            .gossipsub()
+            .all_peers()
+            .filter(|(_, topics)| topics.contains(&topic))
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+        self.record_gossip_bytes_sent(topic, &peers, bytes);
+    }
+
+    /// Counts the bytes of a validated message on `topic`, which gossipsub forwards to the mesh
+    /// peers of the topic other than the peer it was received from.
+    fn record_gossip_bytes_forwarded(
+        &self,
+        topic: &TopicHash,
+        propagation_source: &PeerId,
+        bytes: usize,
+    ) {
+        let peers = self
+            .gossipsub()
+            .mesh_peers(topic)
+            .filter(|peer_id| *peer_id != propagation_source)
+            .copied()
+            .collect::<Vec<_>>();
+        self.record_gossip_bytes_sent(topic, &peers, bytes);
+    }
+
     /// Informs the gossipsub about the result of a message validation.
     /// If the message is valid it will get propagated by gossipsub.
     pub fn report_message_validation_result(
@@ -693,12 +784,23 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
             }
         }
 
-        if let Err(e) = self.gossipsub_mut().report_message_validation_result(
+        let accepted = matches!(validation_result, MessageAcceptance::Accept);
+        let pending = self.gossip_pending_validation.pop(&message_id);
+        match self.gossipsub_mut().report_message_validation_result(
             &message_id,
             propagation_source,
             validation_result,
         ) {
-            warn!(self.log, "Failed to report message validation"; "message_id" => %message_id, "peer_id" => %propagation_source, "error" => ?e);
+            // The message was still cached, so gossipsub forwards it if it was accepted.
+            Ok(true) => {
+                if let Some((topic, bytes)) = pending.filter(|_| accepted) {
+                    self.record_gossip_bytes_forwarded(&topic, propagation_source, bytes);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => {
+                warn!(self.log, "Failed to report message validation"; "message_id" => %message_id, "peer_id" => %propagation_source, "error" => ?e);
+            }
         }
     }
 
@@ -748,17 +850,12 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
 
     /// Send a request to a peer over RPC.
     pub fn send_request(&mut self, peer_id: PeerId, request_id: AppReqId, request: Request) {
-        self.eth2_rpc_mut().send_request(
-            peer_id,
-            RequestId::Application(request_id),
-            request.into(),
-        )
+        self.send_rpc_request(peer_id, RequestId::Application(request_id), request.into())
     }
 
     /// Send a successful response to a peer over RPC.
     pub fn send_response(&mut self, peer_id: PeerId, id: PeerRequestId, response: Response<TSpec>) {
-        self.eth2_rpc_mut()
-            .send_response(peer_id, id, response.into())
+        self.send_rpc_response(peer_id, id, response.into())
     }
 
     /// Inform the peer that their request produced an error.
@@ -769,11 +866,41 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         error: RPCResponseErrorCode,
         reason: String,
     ) {
-        self.eth2_rpc_mut().send_response(
-            peer_id,
-            id,
-            RPCCodedResponse::Error(error, reason.into()),
-        )
+        self.send_rpc_response(peer_id, id, RPCCodedResponse::Error(error, reason.into()))
+    }
+
+    /// Sends a request to a peer over RPC, counting its bytes.
+    fn send_rpc_request(
+        &mut self,
+        peer_id: PeerId,
+        id: RequestId<AppReqId>,
+        request: OutboundRequest<TSpec>,
+    ) {
+        self.record_rpc_bytes(&peer_id, metrics::OUTBOUND, request.ssz_bytes_len());
+        self.eth2_rpc_mut().send_request(peer_id, id, request)
+    }
+
+    /// Sends a response to a peer over RPC, counting its bytes.
+    fn send_rpc_response(
+        &mut self,
+        peer_id: PeerId,
+        id: PeerRequestId,
+        response: RPCCodedResponse<TSpec>,
+    ) {
+        self.record_rpc_bytes(&peer_id, metrics::OUTBOUND, response.ssz_bytes_len());
+        self.eth2_rpc_mut().send_response(peer_id, id, response)
+    }
+
+    /// Counts `bytes` of an RPC message sent to or received from `peer_id`, by the client of the
+    /// peer.
+    fn record_rpc_bytes(&self, peer_id: &PeerId, direction: &str, bytes: usize) {
+        if let Some(peer_info) = self.network_globals.peers.read().peer_info(peer_id) {
+            metrics::inc_counter_vec_by(
+                &metrics::RPC_BYTES_PER_CLIENT,
+                &[peer_info.client().kind.as_ref(), direction],
+                bytes as u64,
+            );
+        }
     }
 
     /* Peer management functions */
@@ -927,8 +1054,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         };
         trace!(self.log, "Sending Ping"; "peer_id" => %peer_id);
         let id = RequestId::Internal;
-        self.eth2_rpc_mut()
-            .send_request(peer_id, id, OutboundRequest::Ping(ping));
+        self.send_rpc_request(peer_id, id, OutboundRequest::Ping(ping));
     }
 
     /// Sends a Pong response to the peer.
@@ -938,14 +1064,13 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         };
         trace!(self.log, "Sending Pong"; "request_id" => id.1, "peer_id" => %peer_id);
         let event = RPCCodedResponse::Success(RPCResponse::Pong(ping));
-        self.eth2_rpc_mut().send_response(peer_id, id, event);
+        self.send_rpc_response(peer_id, id, event);
     }
 
     /// Sends a METADATA request to a peer.
     fn send_meta_data_request(&mut self, peer_id: PeerId) {
         let event = OutboundRequest::MetaData(PhantomData);
-        self.eth2_rpc_mut()
-            .send_request(peer_id, RequestId::Internal, event);
+        self.send_rpc_request(peer_id, RequestId::Internal, event);
     }
 
     /// Sends a METADATA response to a peer.
@@ -953,7 +1078,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
         let event = RPCCodedResponse::Success(RPCResponse::MetaData(
             self.network_globals.local_metadata.read().clone(),
         ));
-        self.eth2_rpc_mut().send_response(peer_id, id, event);
+        self.send_rpc_response(peer_id, id, event);
     }
 
     // RPC Propagation methods
@@ -1044,6 +1169,15 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
             } => {
                 // Note: We are keeping track here of the peer that sent us the message, not the
                 // peer that originally published the message.
+                self.peer_manager_mut()
+                    .gossip_message_received(&propagation_source, gs_msg.data.len());
+                if let Ok(topic) = GossipTopic::decode(gs_msg.topic.as_str()) {
+                    metrics::inc_counter_vec_by(
+                        &metrics::GOSSIP_BYTES,
+                        &[topic.kind().as_ref(), metrics::INBOUND],
+                        gs_msg.data.len() as u64,
+                    );
+                }
                 match PubsubMessage::decode(&gs_msg.topic, &gs_msg.data, &self.fork_context) {
                     Err(e) => {
                         debug!(self.log, "Could not decode gossipsub message"; "topic" => ?gs_msg.topic,"error" => e);
@@ -1057,6 +1191,8 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                         }
                     }
                     Ok(msg) => {
+                        self.gossip_pending_validation
+                            .put(id.clone(), (gs_msg.topic.clone(), gs_msg.data.len()));
                         // Notify the network
                         return Some(NetworkEvent::PubsubMessage {
                             id,
@@ -1076,16 +1212,19 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                             .add_subscription(&peer_id, subnet_id);
                     }
                     // Try to send the cached messages for this topic
-                    if let Some(msgs) = self.gossip_cache.retrieve(&topic) {
+                    if let Some(msgs) = self
+                        .gossip_cache
+                        .retrieve(&topic)
+                        .map(|msgs| msgs.collect::<Vec<_>>())
+                    {
                         for data in msgs {
                             let topic_str: &str = topic.kind().as_ref();
-                            match self
-                                .swarm
-                                .behaviour_mut()
-                                .gossipsub
-                                .publish(Topic::from(topic.clone()), data)
-                            {
+                            let gs_topic = Topic::from(topic.clone());
+                            let topic_hash = gs_topic.hash();
+                            let bytes = data.len();
+                            match self.swarm.behaviour_mut().gossipsub.publish(gs_topic, data) {
                                 Ok(_) => {
+                                    self.record_gossip_bytes_published(&topic_hash, bytes);
                                     warn!(self.log, "Gossip message published on retry"; "topic" => topic_str);
                                     if let Some(v) = metrics::get_int_counter(
                                         &metrics::GOSSIP_LATE_PUBLISH_PER_TOPIC_KIND,
@@ -1185,6 +1324,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                 }
             }
             Ok(RPCReceived::Request(id, request)) => {
+                self.record_rpc_bytes(&peer_id, metrics::INBOUND, request.ssz_bytes_len());
                 let peer_request_id = (handler_id, id);
                 match request {
                     /* Behaviour managed protocols: Ping and Metadata */
@@ -1271,6 +1411,7 @@ impl<AppReqId: ReqId, TSpec: EthSpec> Network<AppReqId, TSpec> {
                 }
             }
             Ok(RPCReceived::Response(id, resp)) => {
+                self.record_rpc_bytes(&peer_id, metrics::INBOUND, resp.ssz_bytes_len());
                 match resp {
                     /* Behaviour managed protocols */
                     RPCResponse::Pong(ping) => {
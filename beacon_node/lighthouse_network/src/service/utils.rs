@@ -1,4 +1,5 @@
 use crate::multiaddr::Protocol;
+use crate::proxy::{DirectTransport, ProxyConfig, ProxyTransport};
 use crate::rpc::{MetaData, MetaDataV1, MetaDataV2};
 use crate::types::{
     error, EnrAttestationBitfield, EnrSyncCommitteeBitfield, GossipEncoding, GossipKind,
@@ -6,7 +7,11 @@ use crate::types::{
 use crate::{GossipTopic, NetworkConfig};
 use libp2p::bandwidth::{BandwidthLogging, BandwidthSinks};
 use libp2p::core::{
-    identity::Keypair, multiaddr::Multiaddr, muxing::StreamMuxerBox, transport::Boxed,
+    either::EitherOutput,
+    identity::Keypair,
+    multiaddr::Multiaddr,
+    muxing::StreamMuxerBox,
+    transport::{Boxed, OptionalTransport},
 };
 use libp2p::gossipsub::subscription_filter::WhitelistSubscriptionFilter;
 use libp2p::gossipsub::IdentTopic as Topic;
@@ -41,8 +46,16 @@ type BoxedTransport = Boxed<(PeerId, StreamMuxerBox)>;
 
 /// The implementation supports TCP/IP, WebSockets over TCP/IP, noise as the encryption layer, and
 /// mplex as the multiplexing layer.
+///
+/// If `quic_support` is set, QUIC is supported alongside TCP. QUIC provides its own encryption and
+/// multiplexing, and its traffic is not included in the bandwidth metrics.
+///
+/// If a `proxy` is given, TCP addresses are dialed through it and no other addresses are dialed.
+/// Listening is unaffected.
 pub fn build_transport(
     local_private_key: Keypair,
+    quic_support: bool,
+    proxy: Option<&ProxyConfig>,
 ) -> std::io::Result<(BoxedTransport, Arc<BandwidthSinks>)> {
     let tcp = libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::default().nodelay(true));
     let transport = libp2p::dns::TokioDnsConfig::system(tcp)?;
@@ -52,6 +65,15 @@ pub fn build_transport(
         transport.or_transport(libp2p::websocket::WsConfig::new(trans_clone))
     };
 
+    // The proxy transport is tried first, and refuses any address it can't dial. Those addresses
+    // must not be dialed directly either, so the direct transport is only used to listen.
+    let transport = DirectTransport::new(transport, proxy.is_none());
+    let proxy_transport = match proxy {
+        Some(proxy) => OptionalTransport::some(ProxyTransport::new(proxy.clone())),
+        None => OptionalTransport::none(),
+    };
+    let transport = proxy_transport.or_transport(transport);
+
     let (transport, bandwidth) = BandwidthLogging::new(transport);
 
     // mplex config
@@ -64,18 +86,30 @@ pub fn build_transport(
     yamux_config.set_window_update_mode(libp2p::yamux::WindowUpdateMode::on_read());
 
     // Authentication
-    Ok((
+    let transport = transport
+        .upgrade(core::upgrade::Version::V1)
+        .authenticate(generate_noise_config(&local_private_key))
+        .multiplex(core::upgrade::SelectUpgrade::new(
+            yamux_config,
+            mplex_config,
+        ))
+        .timeout(Duration::from_secs(10));
+
+    let transport = if quic_support {
+        // The default QUIC config suits us for now.
+        let quic_config = libp2p::quic::Config::new(&local_private_key);
         transport
-            .upgrade(core::upgrade::Version::V1)
-            .authenticate(generate_noise_config(&local_private_key))
-            .multiplex(core::upgrade::SelectUpgrade::new(
-                yamux_config,
-                mplex_config,
-            ))
-            .timeout(Duration::from_secs(10))
-            .boxed(),
-        bandwidth,
-    ))
+            .or_transport(libp2p::quic::tokio::Transport::new(quic_config))
+            .map(|either_output, _| match either_output {
+                EitherOutput::First((peer_id, muxer)) => (peer_id, StreamMuxerBox::new(muxer)),
+                EitherOutput::Second((peer_id, muxer)) => (peer_id, StreamMuxerBox::new(muxer)),
+            })
+            .boxed()
+    } else {
+        transport.boxed()
+    };
+
+    Ok((transport, bandwidth))
 }
 
 // Useful helper functions for debugging. Currently not used in the client.
@@ -0,0 +1,233 @@
+//! Allow and deny lists of peers, which are enforced whenever a peer connects.
+
+use crate::PeerId;
+use ipnet::IpNet;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// An entry of an allow or deny list, matching either a single peer or the peers connecting from
+/// an IP subnet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccessListEntry {
+    Peer(PeerId),
+    Subnet(IpNet),
+}
+
+impl FromStr for AccessListEntry {
+    type Err = String;
+
+    /// Parses a peer id, a subnet in CIDR notation, or a single IP address.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(subnet) = s.parse::<IpNet>() {
+            Ok(Self::Subnet(subnet.trunc()))
+        } else if let Ok(ip) = s.parse::<IpAddr>() {
+            Ok(Self::Subnet(IpNet::from(ip)))
+        } else {
+            s.parse::<PeerId>()
+                .map(Self::Peer)
+                .map_err(|_| format!("Invalid peer id, subnet or IP address: {}", s))
+        }
+    }
+}
+
+impl fmt::Display for AccessListEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Peer(peer_id) => write!(f, "{}", peer_id),
+            Self::Subnet(subnet) => write!(f, "{}", subnet),
+        }
+    }
+}
+
+impl Serialize for AccessListEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for AccessListEntry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// A set of peer ids and IP subnets.
+#[derive(Default, Debug)]
+struct PeerList {
+    peers: HashSet<PeerId>,
+    subnets: Vec<IpNet>,
+}
+
+impl PeerList {
+    fn insert(&mut self, entry: AccessListEntry) {
+        match entry {
+            AccessListEntry::Peer(peer_id) => {
+                self.peers.insert(peer_id);
+            }
+            AccessListEntry::Subnet(subnet) => {
+                if !self.subnets.contains(&subnet) {
+                    self.subnets.push(subnet);
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, entry: &AccessListEntry) {
+        match entry {
+            AccessListEntry::Peer(peer_id) => {
+                self.peers.remove(peer_id);
+            }
+            AccessListEntry::Subnet(subnet) => self.subnets.retain(|s| s != subnet),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.peers.is_empty() && self.subnets.is_empty()
+    }
+
+    /// Returns `true` if `peer_id`, or any of the IP addresses it is known by, is in the list.
+    fn matches(&self, peer_id: &PeerId, ips: &[IpAddr]) -> bool {
+        self.peers.contains(peer_id)
+            || ips
+                .iter()
+                .any(|ip| self.subnets.iter().any(|subnet| subnet.contains(ip)))
+    }
+
+    fn entries(&self) -> Vec<AccessListEntry> {
+        self.peers
+            .iter()
+            .copied()
+            .map(AccessListEntry::Peer)
+            .chain(self.subnets.iter().copied().map(AccessListEntry::Subnet))
+            .collect()
+    }
+}
+
+/// The peers which are allowed or denied connections.
+///
+/// Peers matching the denylist are always refused. If the allowlist is non-empty, peers which
+/// don't match it are refused as well.
+#[derive(Default, Debug)]
+pub struct AccessList {
+    allowlist: PeerList,
+    denylist: PeerList,
+}
+
+impl AccessList {
+    pub fn new(allowlist: Vec<AccessListEntry>, denylist: Vec<AccessListEntry>) -> Self {
+        let mut access_list = Self::default();
+        allowlist
+            .into_iter()
+            .for_each(|entry| access_list.allow(entry));
+        denylist
+            .into_iter()
+            .for_each(|entry| access_list.deny(entry));
+        access_list
+    }
+
+    /// Adds `entry` to the allowlist.
+    pub fn allow(&mut self, entry: AccessListEntry) {
+        self.allowlist.insert(entry);
+    }
+
+    /// Adds `entry` to the denylist.
+    pub fn deny(&mut self, entry: AccessListEntry) {
+        self.denylist.insert(entry);
+    }
+
+    /// Removes `entry` from the allowlist, if it is present.
+    pub fn remove_allowed(&mut self, entry: &AccessListEntry) {
+        self.allowlist.remove(entry);
+    }
+
+    /// Removes `entry` from the denylist, if it is present.
+    pub fn remove_denied(&mut self, entry: &AccessListEntry) {
+        self.denylist.remove(entry);
+    }
+
+    pub fn allowlist(&self) -> Vec<AccessListEntry> {
+        self.allowlist.entries()
+    }
+
+    pub fn denylist(&self) -> Vec<AccessListEntry> {
+        self.denylist.entries()
+    }
+
+    /// Returns `true` if `peer_id`, connecting from any of `ips`, matches the denylist.
+    pub fn denies(&self, peer_id: &PeerId, ips: &[IpAddr]) -> bool {
+        self.denylist.matches(peer_id, ips)
+    }
+
+    /// Returns `true` if `peer_id`, connecting from any of `ips`, should be accepted.
+    pub fn permits(&self, peer_id: &PeerId, ips: &[IpAddr]) -> bool {
+        !self.denies(peer_id, ips)
+            && (self.allowlist.is_empty() || self.allowlist.matches(peer_id, ips))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(s: &str) -> AccessListEntry {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_entries() {
+        let peer_id = PeerId::random();
+        assert_eq!(entry(&peer_id.to_string()), AccessListEntry::Peer(peer_id));
+        assert_eq!(entry("10.0.0.1/8").to_string(), "10.0.0.0/8");
+        assert_eq!(entry("192.0.2.1").to_string(), "192.0.2.1/32");
+        assert_eq!(entry("2001:db8::/32").to_string(), "2001:db8::/32");
+        assert!("not-a-peer".parse::<AccessListEntry>().is_err());
+    }
+
+    #[test]
+    fn empty_access_list_permits_all() {
+        let access_list = AccessList::default();
+        assert!(access_list.permits(&PeerId::random(), &[]));
+        assert!(access_list.permits(&PeerId::random(), &["192.0.2.1".parse().unwrap()]));
+    }
+
+    #[test]
+    fn denylist() {
+        let denied_peer = PeerId::random();
+        let mut access_list = AccessList::new(
+            vec![],
+            vec![AccessListEntry::Peer(denied_peer), entry("192.0.2.0/24")],
+        );
+
+        assert!(!access_list.permits(&denied_peer, &[]));
+        assert!(!access_list.permits(&PeerId::random(), &["192.0.2.7".parse().unwrap()]));
+        assert!(access_list.permits(&PeerId::random(), &["198.51.100.7".parse().unwrap()]));
+
+        access_list.remove_denied(&AccessListEntry::Peer(denied_peer));
+        assert!(access_list.permits(&denied_peer, &[]));
+    }
+
+    #[test]
+    fn allowlist() {
+        let allowed_peer = PeerId::random();
+        let access_list = AccessList::new(
+            vec![AccessListEntry::Peer(allowed_peer), entry("10.0.0.0/8")],
+            vec![entry("10.1.0.0/16")],
+        );
+
+        assert!(access_list.permits(&allowed_peer, &["198.51.100.7".parse().unwrap()]));
+        assert!(access_list.permits(&PeerId::random(), &["10.2.3.4".parse().unwrap()]));
+        assert!(!access_list.permits(&PeerId::random(), &["198.51.100.7".parse().unwrap()]));
+        // The denylist takes precedence.
+        assert!(!access_list.permits(&allowed_peer, &["10.1.2.3".parse().unwrap()]));
+    }
+}
@@ -2,4 +2,12 @@
 
 use error_chain::error_chain;
 
-error_chain! {}
+error_chain! {
+    errors {
+        /// A listening socket could not be bound, usually because the port is already in use.
+        Listen(msg: String) {
+            description("unable to listen")
+            display("{}", msg)
+        }
+    }
+}
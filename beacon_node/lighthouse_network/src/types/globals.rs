@@ -1,7 +1,7 @@
 //! A collection of variables that are accessible outside of the network thread itself.
 use crate::peer_manager::peerdb::PeerDB;
 use crate::rpc::{MetaData, MetaDataV2};
-use crate::types::{BackFillState, SyncState};
+use crate::types::{AccessList, BackFillState, SyncState};
 use crate::Client;
 use crate::EnrExt;
 use crate::{Enr, GossipTopic, Multiaddr, PeerId};
@@ -30,6 +30,8 @@ pub struct NetworkGlobals<TSpec: EthSpec> {
     pub sync_state: RwLock<SyncState>,
     /// The current state of the backfill sync.
     pub backfill_state: RwLock<BackFillState>,
+    /// The peers which are allowed or denied connections.
+    pub access_list: RwLock<AccessList>,
 }
 
 impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
@@ -40,6 +42,7 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
         local_metadata: MetaData<TSpec>,
         trusted_peers: Vec<PeerId>,
         disable_peer_scoring: bool,
+        access_list: AccessList,
         log: &slog::Logger,
     ) -> Self {
         NetworkGlobals {
@@ -53,6 +56,7 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
             backfill_state: RwLock::new(BackFillState::NotRequired),
+            access_list: RwLock::new(access_list),
         }
     }
 
@@ -130,6 +134,14 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
 
     /// TESTING ONLY. Build a dummy NetworkGlobals instance.
     pub fn new_test_globals(log: &slog::Logger) -> NetworkGlobals<TSpec> {
+        Self::new_test_globals_with_trusted_peers(vec![], log)
+    }
+
+    /// TESTING ONLY. Build a dummy NetworkGlobals instance with the given trusted peers.
+    pub fn new_test_globals_with_trusted_peers(
+        trusted_peers: Vec<PeerId>,
+        log: &slog::Logger,
+    ) -> NetworkGlobals<TSpec> {
         use crate::CombinedKeyExt;
         let keypair = libp2p::identity::Keypair::generate_secp256k1();
         let enr_key: discv5::enr::CombinedKey =
@@ -144,8 +156,9 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
                 attnets: Default::default(),
                 syncnets: Default::default(),
             }),
-            vec![],
+            trusted_peers,
             false,
+            Default::default(),
             log,
         )
     }
@@ -37,6 +37,17 @@
 //! Whenever the manager receives a notification that a worker has finished a parcel of work, it
 //! checks the queues to see if there are more parcels of work that can be spawned in a new worker
 //! task.
+//!
+//! ## HTTP API requests
+//!
+//! Requests to the HTTP API are also scheduled by the manager, so that they can't starve the
+//! consensus work of CPU. Each request waits in one of two queues, depending on its priority, until
+//! it is given an `ApiRequestPermit` which holds a worker until the request has been served.
+//! Requests made by validator clients to perform their duties are `P0`, and are handled right after
+//! blocks. All other requests, such as historical state queries, are `P1`, and are only handled
+//! once there are no consensus messages waiting. At most all but one of the workers may be held by
+//! `P1` requests at once, so that slow queries can't leave no worker free for the next block. With
+//! a single worker there is none to spare, so `P1` requests share it with everything else.
 
 use crate::sync::manager::BlockProcessType;
 use crate::{metrics, service::NetworkMessage, sync::SyncMessage};
@@ -55,13 +66,14 @@ use slog::{crit, debug, error, trace, warn, Logger};
 use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::task::Context;
 use std::time::Duration;
 use std::{cmp, collections::HashSet};
 use task_executor::TaskExecutor;
-use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot};
 use types::{
     Attestation, AttesterSlashing, Hash256, LightClientFinalityUpdate, LightClientOptimisticUpdate,
     ProposerSlashing, SignedAggregateAndProof, SignedBeaconBlock, SignedBlsToExecutionChange,
@@ -183,6 +195,15 @@ const MAX_BLS_TO_EXECUTION_CHANGE_QUEUE_LEN: usize = 16_384;
 /// will be stored before we start dropping them.
 const MAX_LIGHT_CLIENT_BOOTSTRAP_QUEUE_LEN: usize = 1_024;
 
+/// The maximum number of queued HTTP API requests of each priority that will be stored before we
+/// start dropping them.
+const MAX_API_REQUEST_P0_QUEUE_LEN: usize = 1_024;
+const MAX_API_REQUEST_P1_QUEUE_LEN: usize = 1_024;
+
+/// The number of workers which `P1` HTTP API requests leave free, if there are more workers than
+/// this.
+const API_REQUEST_P1_RESERVED_WORKERS: usize = 1;
+
 /// The name of the manager tokio task.
 const MANAGER_TASK_NAME: &str = "beacon_processor_manager";
 
@@ -230,6 +251,102 @@ pub const UNKNOWN_BLOCK_ATTESTATION: &str = "unknown_block_attestation";
 pub const UNKNOWN_BLOCK_AGGREGATE: &str = "unknown_block_aggregate";
 pub const UNKNOWN_LIGHT_CLIENT_UPDATE: &str = "unknown_light_client_update";
 pub const GOSSIP_BLS_TO_EXECUTION_CHANGE: &str = "gossip_bls_to_execution_change";
+pub const API_REQUEST_P0: &str = "api_request_p0";
+pub const API_REQUEST_P1: &str = "api_request_p1";
+
+/// The priority of a request to the HTTP API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiRequestPriority {
+    /// Requests which validator clients make to perform their duties.
+    P0,
+    /// All other requests, which are handled after consensus messages.
+    P1,
+}
+
+/// A request to the HTTP API which is waiting for a worker.
+pub struct ApiRequest {
+    pub priority: ApiRequestPriority,
+    /// Receives the permit once a worker is available. The sender is dropped if the request is
+    /// dropped from its queue.
+    pub permit_tx: oneshot::Sender<ApiRequestPermit>,
+}
+
+/// Holds a worker until it is dropped, i.e. once the HTTP API request has been served.
+pub struct ApiRequestPermit {
+    // Declared first so that it is dropped before the manager is told that the worker is idle.
+    _p1_permit: Option<ApiRequestP1Permit>,
+    _send_idle_on_drop: SendOnDrop,
+}
+
+/// Counts a `P1` HTTP API request against the limit on the workers they may hold, until dropped.
+struct ApiRequestP1Permit {
+    p1_workers: Arc<AtomicUsize>,
+}
+
+impl ApiRequestP1Permit {
+    fn new(p1_workers: Arc<AtomicUsize>) -> Self {
+        p1_workers.fetch_add(1, Ordering::Relaxed);
+        Self { p1_workers }
+    }
+}
+
+impl Drop for ApiRequestP1Permit {
+    fn drop(&mut self) {
+        self.p1_workers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Passes requests from the HTTP API to the beacon processor, to be scheduled alongside the
+/// messages from the network. A request which doesn't fit in the queue is dropped, which fails it.
+pub fn spawn_api_request_forwarder<T: BeaconChainTypes>(
+    mut api_request_recv: mpsc::Receiver<ApiRequest>,
+    beacon_processor_send: mpsc::Sender<WorkEvent<T>>,
+    executor: &TaskExecutor,
+) {
+    executor.spawn(
+        async move {
+            while let Some(request) = api_request_recv.recv().await {
+                let _ = beacon_processor_send.try_send(WorkEvent::api_request(request));
+            }
+        },
+        "api_request_router",
+    );
+}
+
+/// Spawns a `BeaconProcessor` which only serves the HTTP API, returning the channel on which
+/// requests are scheduled.
+///
+/// Used by the HTTP API tests, which run without a network service.
+pub fn spawn_api_request_processor<T: BeaconChainTypes>(
+    beacon_chain: &Arc<BeaconChain<T>>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+    max_workers: usize,
+    executor: TaskExecutor,
+    log: Logger,
+) -> mpsc::Sender<ApiRequest> {
+    let (network_tx, _) = mpsc::unbounded_channel();
+    let (sync_tx, _) = mpsc::unbounded_channel();
+    let (beacon_processor_send, beacon_processor_receive) = mpsc::channel(MAX_WORK_EVENT_QUEUE_LEN);
+    let (api_request_send, api_request_recv) = mpsc::channel(MAX_WORK_EVENT_QUEUE_LEN);
+
+    spawn_api_request_forwarder(api_request_recv, beacon_processor_send, &executor);
+
+    BeaconProcessor {
+        beacon_chain: Arc::downgrade(beacon_chain),
+        network_tx,
+        sync_tx,
+        network_globals,
+        executor,
+        max_workers,
+        queue_length_divisor: 1,
+        current_workers: 0,
+        importing_blocks: Default::default(),
+        log,
+    }
+    .spawn_manager(beacon_processor_receive, None);
+
+    api_request_send
+}
 
 /// A simple first-in-first-out queue with a maximum length.
 struct FifoQueue<T> {
@@ -658,6 +775,21 @@ impl<T: BeaconChainTypes> WorkEvent<T> {
         }
     }
 
+    /// Create a new `Work` event for a request to the HTTP API.
+    pub fn api_request(request: ApiRequest) -> Self {
+        let ApiRequest {
+            priority,
+            permit_tx,
+        } = request;
+        Self {
+            drop_during_sync: false,
+            work: match priority {
+                ApiRequestPriority::P0 => Work::ApiRequestP0 { permit_tx },
+                ApiRequestPriority::P1 => Work::ApiRequestP1 { permit_tx },
+            },
+        }
+    }
+
     /// Get a `str` representation of the type of work this `WorkEvent` contains.
     pub fn work_type(&self) -> &'static str {
         self.work.str_id()
@@ -880,6 +1012,14 @@ pub enum Work<T: BeaconChainTypes> {
         request_id: PeerRequestId,
         request: LightClientBootstrapRequest,
     },
+    ApiRequestP0 {
+        #[derivative(Debug = "ignore")]
+        permit_tx: oneshot::Sender<ApiRequestPermit>,
+    },
+    ApiRequestP1 {
+        #[derivative(Debug = "ignore")]
+        permit_tx: oneshot::Sender<ApiRequestPermit>,
+    },
 }
 
 impl<T: BeaconChainTypes> Work<T> {
@@ -913,6 +1053,8 @@ impl<T: BeaconChainTypes> Work<T> {
             Work::UnknownBlockAggregate { .. } => UNKNOWN_BLOCK_AGGREGATE,
             Work::GossipBlsToExecutionChange { .. } => GOSSIP_BLS_TO_EXECUTION_CHANGE,
             Work::UnknownLightClientOptimisticUpdate { .. } => UNKNOWN_LIGHT_CLIENT_UPDATE,
+            Work::ApiRequestP0 { .. } => API_REQUEST_P0,
+            Work::ApiRequestP1 { .. } => API_REQUEST_P1,
         }
     }
 }
@@ -993,6 +1135,9 @@ pub struct BeaconProcessor<T: BeaconChainTypes> {
     pub network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     pub executor: TaskExecutor,
     pub max_workers: usize,
+    /// The maximum length of each work queue is divided by this value, to reduce the memory used
+    /// by work awaiting processing.
+    pub queue_length_divisor: usize,
     pub current_workers: usize,
     pub importing_blocks: DuplicateCache,
     pub log: Logger,
@@ -1018,53 +1163,71 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
         // Used by workers to communicate that they are finished a task.
         let (idle_tx, idle_rx) = mpsc::channel::<()>(MAX_IDLE_QUEUE_LEN);
 
+        let queue_length_divisor = cmp::max(1, self.queue_length_divisor);
+        let queue_len = |max_len: usize| cmp::max(1, max_len / queue_length_divisor);
+
+        // The number of workers held by `P1` HTTP API requests.
+        let api_request_p1_workers = Arc::new(AtomicUsize::new(0));
+        let max_api_request_p1_workers = if self.max_workers > API_REQUEST_P1_RESERVED_WORKERS {
+            self.max_workers - API_REQUEST_P1_RESERVED_WORKERS
+        } else {
+            self.max_workers
+        };
+
         // Using LIFO queues for attestations since validator profits rely upon getting fresh
         // attestations into blocks. Additionally, later attestations contain more information than
         // earlier ones, so we consider them more valuable.
-        let mut aggregate_queue = LifoQueue::new(MAX_AGGREGATED_ATTESTATION_QUEUE_LEN);
+        let mut aggregate_queue = LifoQueue::new(queue_len(MAX_AGGREGATED_ATTESTATION_QUEUE_LEN));
         let mut aggregate_debounce = TimeLatch::default();
-        let mut attestation_queue = LifoQueue::new(MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN);
+        let mut attestation_queue =
+            LifoQueue::new(queue_len(MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN));
         let mut attestation_debounce = TimeLatch::default();
         let mut unknown_block_aggregate_queue =
-            LifoQueue::new(MAX_AGGREGATED_ATTESTATION_REPROCESS_QUEUE_LEN);
+            LifoQueue::new(queue_len(MAX_AGGREGATED_ATTESTATION_REPROCESS_QUEUE_LEN));
         let mut unknown_block_attestation_queue =
-            LifoQueue::new(MAX_UNAGGREGATED_ATTESTATION_REPROCESS_QUEUE_LEN);
+            LifoQueue::new(queue_len(MAX_UNAGGREGATED_ATTESTATION_REPROCESS_QUEUE_LEN));
 
-        let mut sync_message_queue = LifoQueue::new(MAX_SYNC_MESSAGE_QUEUE_LEN);
-        let mut sync_contribution_queue = LifoQueue::new(MAX_SYNC_CONTRIBUTION_QUEUE_LEN);
+        let mut sync_message_queue = LifoQueue::new(queue_len(MAX_SYNC_MESSAGE_QUEUE_LEN));
+        let mut sync_contribution_queue =
+            LifoQueue::new(queue_len(MAX_SYNC_CONTRIBUTION_QUEUE_LEN));
 
         // Using a FIFO queue for voluntary exits since it prevents exit censoring. I don't have
         // a strong feeling about queue type for exits.
-        let mut gossip_voluntary_exit_queue = FifoQueue::new(MAX_GOSSIP_EXIT_QUEUE_LEN);
+        let mut gossip_voluntary_exit_queue = FifoQueue::new(queue_len(MAX_GOSSIP_EXIT_QUEUE_LEN));
 
         // Using a FIFO queue for slashing to prevent people from flushing their slashings from the
         // queues with lots of junk messages.
         let mut gossip_proposer_slashing_queue =
-            FifoQueue::new(MAX_GOSSIP_PROPOSER_SLASHING_QUEUE_LEN);
+            FifoQueue::new(queue_len(MAX_GOSSIP_PROPOSER_SLASHING_QUEUE_LEN));
         let mut gossip_attester_slashing_queue =
-            FifoQueue::new(MAX_GOSSIP_ATTESTER_SLASHING_QUEUE_LEN);
+            FifoQueue::new(queue_len(MAX_GOSSIP_ATTESTER_SLASHING_QUEUE_LEN));
 
         // Using a FIFO queue for light client updates to maintain sequence order.
-        let mut finality_update_queue = FifoQueue::new(MAX_GOSSIP_FINALITY_UPDATE_QUEUE_LEN);
-        let mut optimistic_update_queue = FifoQueue::new(MAX_GOSSIP_OPTIMISTIC_UPDATE_QUEUE_LEN);
+        let mut finality_update_queue =
+            FifoQueue::new(queue_len(MAX_GOSSIP_FINALITY_UPDATE_QUEUE_LEN));
+        let mut optimistic_update_queue =
+            FifoQueue::new(queue_len(MAX_GOSSIP_OPTIMISTIC_UPDATE_QUEUE_LEN));
         let mut unknown_light_client_update_queue =
-            FifoQueue::new(MAX_GOSSIP_OPTIMISTIC_UPDATE_REPROCESS_QUEUE_LEN);
+            FifoQueue::new(queue_len(MAX_GOSSIP_OPTIMISTIC_UPDATE_REPROCESS_QUEUE_LEN));
 
         // Using a FIFO queue since blocks need to be imported sequentially.
-        let mut rpc_block_queue = FifoQueue::new(MAX_RPC_BLOCK_QUEUE_LEN);
-        let mut chain_segment_queue = FifoQueue::new(MAX_CHAIN_SEGMENT_QUEUE_LEN);
-        let mut backfill_chain_segment = FifoQueue::new(MAX_CHAIN_SEGMENT_QUEUE_LEN);
-        let mut gossip_block_queue = FifoQueue::new(MAX_GOSSIP_BLOCK_QUEUE_LEN);
-        let mut delayed_block_queue = FifoQueue::new(MAX_DELAYED_BLOCK_QUEUE_LEN);
+        let mut rpc_block_queue = FifoQueue::new(queue_len(MAX_RPC_BLOCK_QUEUE_LEN));
+        let mut chain_segment_queue = FifoQueue::new(queue_len(MAX_CHAIN_SEGMENT_QUEUE_LEN));
+        let mut backfill_chain_segment = FifoQueue::new(queue_len(MAX_CHAIN_SEGMENT_QUEUE_LEN));
+        let mut gossip_block_queue = FifoQueue::new(queue_len(MAX_GOSSIP_BLOCK_QUEUE_LEN));
+        let mut delayed_block_queue = FifoQueue::new(queue_len(MAX_DELAYED_BLOCK_QUEUE_LEN));
 
-        let mut status_queue = FifoQueue::new(MAX_STATUS_QUEUE_LEN);
-        let mut bbrange_queue = FifoQueue::new(MAX_BLOCKS_BY_RANGE_QUEUE_LEN);
-        let mut bbroots_queue = FifoQueue::new(MAX_BLOCKS_BY_ROOTS_QUEUE_LEN);
+        let mut status_queue = FifoQueue::new(queue_len(MAX_STATUS_QUEUE_LEN));
+        let mut bbrange_queue = FifoQueue::new(queue_len(MAX_BLOCKS_BY_RANGE_QUEUE_LEN));
+        let mut bbroots_queue = FifoQueue::new(queue_len(MAX_BLOCKS_BY_ROOTS_QUEUE_LEN));
 
         let mut gossip_bls_to_execution_change_queue =
-            FifoQueue::new(MAX_BLS_TO_EXECUTION_CHANGE_QUEUE_LEN);
+            FifoQueue::new(queue_len(MAX_BLS_TO_EXECUTION_CHANGE_QUEUE_LEN));
+
+        let mut lcbootstrap_queue = FifoQueue::new(queue_len(MAX_LIGHT_CLIENT_BOOTSTRAP_QUEUE_LEN));
 
-        let mut lcbootstrap_queue = FifoQueue::new(MAX_LIGHT_CLIENT_BOOTSTRAP_QUEUE_LEN);
+        let mut api_request_p0_queue = FifoQueue::new(queue_len(MAX_API_REQUEST_P0_QUEUE_LEN));
+        let mut api_request_p1_queue = FifoQueue::new(queue_len(MAX_API_REQUEST_P1_QUEUE_LEN));
 
         let chain = match self.beacon_chain.upgrade() {
             Some(chain) => chain,
@@ -1178,6 +1341,8 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                 }
 
                 let can_spawn = self.current_workers < self.max_workers;
+                let can_spawn_api_request_p1 = can_spawn
+                    && api_request_p1_workers.load(Ordering::Relaxed) < max_api_request_p1_workers;
                 let drop_during_sync = work_event
                     .as_ref()
                     .map_or(false, |event| event.drop_during_sync);
@@ -1191,6 +1356,7 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         let toolbox = Toolbox {
                             idle_tx: idle_tx.clone(),
                             work_reprocessing_tx: work_reprocessing_tx.clone(),
+                            api_request_p1_workers: api_request_p1_workers.clone(),
                         };
 
                         // Check for chain segments first, they're the most efficient way to get
@@ -1209,6 +1375,10 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         // required to verify some attestations.
                         } else if let Some(item) = gossip_block_queue.pop() {
                             self.spawn_worker(item, toolbox);
+                        // Check the duties of validator clients before attestations, since they
+                        // include the publishing of blocks and attestations.
+                        } else if let Some(item) = api_request_p0_queue.pop() {
+                            self.spawn_worker(item, toolbox);
                         // Check the aggregates, *then* the unaggregates since we assume that
                         // aggregates are more valuable to local validators and effectively give us
                         // more information with less signature verification time.
@@ -1345,6 +1515,12 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                             self.spawn_worker(item, toolbox);
                         } else if let Some(item) = gossip_bls_to_execution_change_queue.pop() {
                             self.spawn_worker(item, toolbox);
+                        // Handle other HTTP API requests once all consensus messages are handled.
+                        } else if let Some(item) = can_spawn_api_request_p1
+                            .then(|| api_request_p1_queue.pop())
+                            .flatten()
+                        {
+                            self.spawn_worker(item, toolbox);
                         // Handle backfill sync chain segments.
                         } else if let Some(item) = backfill_chain_segment.pop() {
                             self.spawn_worker(item, toolbox);
@@ -1395,9 +1571,13 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         let toolbox = Toolbox {
                             idle_tx: idle_tx.clone(),
                             work_reprocessing_tx: work_reprocessing_tx.clone(),
+                            api_request_p1_workers: api_request_p1_workers.clone(),
                         };
 
                         match work {
+                            Work::ApiRequestP1 { .. } if !can_spawn_api_request_p1 => {
+                                api_request_p1_queue.push(work, work_id, &self.log)
+                            }
                             _ if can_spawn => self.spawn_worker(work, toolbox),
                             Work::GossipAttestation { .. } => attestation_queue.push(work),
                             // Attestation batches are formed internally within the
@@ -1472,6 +1652,12 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                             Work::UnknownLightClientOptimisticUpdate { .. } => {
                                 unknown_light_client_update_queue.push(work, work_id, &self.log)
                             }
+                            Work::ApiRequestP0 { .. } => {
+                                api_request_p0_queue.push(work, work_id, &self.log)
+                            }
+                            Work::ApiRequestP1 { .. } => {
+                                api_request_p1_queue.push(work, work_id, &self.log)
+                            }
                         }
                     }
                 }
@@ -1528,6 +1714,14 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                     &metrics::BEACON_PROCESSOR_BLS_TO_EXECUTION_CHANGE_QUEUE_TOTAL,
                     gossip_bls_to_execution_change_queue.len() as i64,
                 );
+                metrics::set_gauge(
+                    &metrics::BEACON_PROCESSOR_API_REQUEST_P0_QUEUE_TOTAL,
+                    api_request_p0_queue.len() as i64,
+                );
+                metrics::set_gauge(
+                    &metrics::BEACON_PROCESSOR_API_REQUEST_P1_QUEUE_TOTAL,
+                    api_request_p1_queue.len() as i64,
+                );
 
                 if aggregate_queue.is_full() && aggregate_debounce.elapsed() {
                     error!(
@@ -1559,6 +1753,7 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
     fn spawn_worker(&mut self, work: Work<T>, toolbox: Toolbox<T>) {
         let idle_tx = toolbox.idle_tx;
         let work_reprocessing_tx = toolbox.work_reprocessing_tx;
+        let api_request_p1_workers = toolbox.api_request_p1_workers;
 
         let work_id = work.str_id();
         let worker_timer =
@@ -1946,6 +2141,22 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                     seen_timestamp,
                 )
             }),
+            /*
+             * Requests to the HTTP API, which hold the worker until they have been served.
+             */
+            Work::ApiRequestP0 { permit_tx } => {
+                // If the request has been abandoned, the permit is dropped and frees the worker.
+                let _ = permit_tx.send(ApiRequestPermit {
+                    _p1_permit: None,
+                    _send_idle_on_drop: task_spawner.send_idle_on_drop,
+                });
+            }
+            Work::ApiRequestP1 { permit_tx } => {
+                let _ = permit_tx.send(ApiRequestPermit {
+                    _p1_permit: Some(ApiRequestP1Permit::new(api_request_p1_workers)),
+                    _send_idle_on_drop: task_spawner.send_idle_on_drop,
+                });
+            }
         };
     }
 }
@@ -21,7 +21,7 @@ use std::cmp;
 use std::iter::Iterator;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use types::{
     Attestation, AttesterSlashing, Epoch, EthSpec, MainnetEthSpec, ProposerSlashing,
     SignedBeaconBlock, SignedVoluntaryExit, SubnetId,
@@ -74,6 +74,14 @@ impl TestRig {
     }
 
     pub async fn new_with_chain_config(chain_length: u64, chain_config: ChainConfig) -> Self {
+        Self::new_with_max_workers(chain_length, chain_config, cmp::max(1, num_cpus::get())).await
+    }
+
+    pub async fn new_with_max_workers(
+        chain_length: u64,
+        chain_config: ChainConfig,
+        max_workers: usize,
+    ) -> Self {
         // This allows for testing voluntary exits without building out a massive chain.
         let mut spec = E::default_spec();
         spec.shard_committee_period = 2;
@@ -186,6 +194,7 @@ impl TestRig {
             meta_data,
             vec![],
             false,
+            Default::default(),
             &log,
         ));
 
@@ -200,7 +209,8 @@ impl TestRig {
             sync_tx,
             network_globals,
             executor,
-            max_workers: cmp::max(1, num_cpus::get()),
+            max_workers,
+            queue_length_divisor: 1,
             current_workers: 0,
             importing_blocks: duplicate_cache.clone(),
             log: log.clone(),
@@ -349,6 +359,20 @@ impl TestRig {
             .unwrap();
     }
 
+    pub fn enqueue_api_request(
+        &self,
+        priority: ApiRequestPriority,
+    ) -> oneshot::Receiver<ApiRequestPermit> {
+        let (permit_tx, permit_rx) = oneshot::channel();
+        self.beacon_processor_tx
+            .try_send(WorkEvent::api_request(ApiRequest {
+                priority,
+                permit_tx,
+            }))
+            .unwrap();
+        permit_rx
+    }
+
     /// Assert that the `BeaconProcessor` doesn't produce any events in the given `duration`.
     pub async fn assert_no_events_for(&mut self, duration: Duration) {
         tokio::select! {
@@ -933,3 +957,95 @@ async fn test_backfill_sync_processing_rate_limiting_disabled() {
     )
     .await;
 }
+
+/// Ensure that an HTTP API request holds its worker until its permit is dropped.
+#[tokio::test]
+async fn api_request_holds_worker() {
+    let mut rig = TestRig::new(SMALL_CHAIN).await;
+
+    for (priority, work_id) in [
+        (ApiRequestPriority::P0, API_REQUEST_P0),
+        (ApiRequestPriority::P1, API_REQUEST_P1),
+    ] {
+        let permit = rig
+            .enqueue_api_request(priority)
+            .await
+            .expect("should receive a permit");
+        rig.assert_event_journal(&[work_id]).await;
+        rig.assert_no_events_for(Duration::from_millis(100)).await;
+
+        drop(permit);
+        rig.assert_event_journal(&[WORKER_FREED, NOTHING_TO_DO])
+            .await;
+    }
+}
+
+/// Ensure that `P1` HTTP API requests can't hold every worker, so that blocks are still processed.
+#[tokio::test]
+async fn api_request_p1_leaves_workers_for_blocks() {
+    let max_workers = 4;
+    let max_p1_workers = max_workers - API_REQUEST_P1_RESERVED_WORKERS;
+    let mut rig =
+        TestRig::new_with_max_workers(SMALL_CHAIN, ChainConfig::default(), max_workers).await;
+
+    let permit_rxs = (0..max_p1_workers)
+        .map(|_| rig.enqueue_api_request(ApiRequestPriority::P1))
+        .collect::<Vec<_>>();
+    let mut queued_permit_rx = rig.enqueue_api_request(ApiRequestPriority::P1);
+    rig.assert_event_journal(&vec![API_REQUEST_P1; max_p1_workers + 1])
+        .await;
+
+    let mut permits = vec![];
+    for permit_rx in permit_rxs {
+        permits.push(permit_rx.await.expect("should receive a permit"));
+    }
+    rig.assert_no_events_for(Duration::from_millis(100)).await;
+    assert!(
+        queued_permit_rx.try_recv().is_err(),
+        "request should wait for a P1 permit to be released"
+    );
+
+    // Workers are free for a block, even though the P1 request is still waiting.
+    rig.enqueue_gossip_block();
+    rig.assert_event_journal(&[GOSSIP_BLOCK, WORKER_FREED, NOTHING_TO_DO])
+        .await;
+    assert_eq!(
+        rig.head_root(),
+        rig.next_block.canonical_root(),
+        "block should be imported and become head"
+    );
+
+    // Releasing a P1 permit allows the waiting request to be served.
+    permits.pop();
+    rig.assert_event_journal(&[WORKER_FREED]).await;
+    queued_permit_rx
+        .await
+        .expect("should receive a permit once another is released");
+}
+
+/// Ensure that `P1` HTTP API requests are still served when there is only one worker to hold.
+#[tokio::test]
+async fn api_request_p1_served_with_one_worker() {
+    let mut rig = TestRig::new_with_max_workers(SMALL_CHAIN, ChainConfig::default(), 1).await;
+
+    let p1_permit = rig
+        .enqueue_api_request(ApiRequestPriority::P1)
+        .await
+        .expect("P1 request should be served");
+    rig.assert_event_journal(&[API_REQUEST_P1]).await;
+
+    // The `P0` request waits for the only worker.
+    let mut p0_permit_rx = rig.enqueue_api_request(ApiRequestPriority::P0);
+    rig.assert_event_journal(&[API_REQUEST_P0]).await;
+    assert!(
+        p0_permit_rx.try_recv().is_err(),
+        "P0 request should wait for the worker"
+    );
+
+    drop(p1_permit);
+    rig.assert_event_journal(&[WORKER_FREED]).await;
+    let p0_permit = p0_permit_rx.await.expect("should receive a permit");
+    drop(p0_permit);
+    rig.assert_event_journal(&[WORKER_FREED, NOTHING_TO_DO])
+        .await;
+}
@@ -2,6 +2,7 @@ use super::work_reprocessing_queue::ReprocessQueueMessage;
 use crate::{service::NetworkMessage, sync::SyncMessage};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use slog::{debug, Logger};
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
@@ -48,4 +49,6 @@ impl<T: BeaconChainTypes> Worker<T> {
 pub struct Toolbox<T: BeaconChainTypes> {
     pub idle_tx: mpsc::Sender<()>,
     pub work_reprocessing_tx: mpsc::Sender<ReprocessQueueMessage<T>>,
+    /// The number of workers held by `P1` HTTP API requests.
+    pub api_request_p1_workers: Arc<AtomicUsize>,
 }
@@ -312,11 +312,25 @@ impl<T: BeaconChainTypes> Worker<T> {
                     oldest_block_slot,
                 },
             )) => {
-                debug!(self.log, "Range request failed during backfill"; "requested_slot" => slot, "oldest_known_slot" => oldest_block_slot);
+                // Tell the peer whether the blocks are yet to be backfilled, or have been pruned and
+                // will never be served by this node.
+                let history_horizon = self.chain.store.get_history_horizon();
+                let reason = if oldest_block_slot <= history_horizon {
+                    "Blocks pruned"
+                } else {
+                    "Backfilling"
+                };
+                debug!(
+                    self.log,
+                    "Range request for unavailable blocks";
+                    "requested_slot" => slot,
+                    "oldest_known_slot" => oldest_block_slot,
+                    "reason" => reason,
+                );
                 return self.send_error_response(
                     peer_id,
                     RPCResponseErrorCode::ResourceUnavailable,
-                    "Backfilling".into(),
+                    reason.into(),
                     request_id,
                 );
             }
@@ -17,6 +17,9 @@ mod subnet_service;
 #[allow(clippy::mutable_key_type)] // PeerId in hashmaps are no longer permitted by clippy
 mod sync;
 
+pub use beacon_processor::{
+    spawn_api_request_processor, ApiRequest, ApiRequestPermit, ApiRequestPriority,
+};
 pub use lighthouse_network::NetworkConfig;
 pub use service::{
     NetworkMessage, NetworkReceivers, NetworkSenders, NetworkService, ValidatorSubscriptionMessage,
@@ -8,8 +8,9 @@ use fnv::FnvHashMap;
 pub use lighthouse_metrics::*;
 use lighthouse_network::{
     peer_manager::peerdb::client::ClientKind, types::GossipKind, BandwidthSinks, GossipTopic,
-    Gossipsub, NetworkGlobals,
+    Gossipsub, NetworkGlobals, PeerId, TopicHash,
 };
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 use types::EthSpec;
@@ -30,6 +31,30 @@ lazy_static! {
             &["Client"]
         );
 
+    /*
+     * Gossipsub mesh health, by topic kind (e.g. `beacon_block` or `beacon_attestation_5`)
+     */
+    pub static ref GOSSIPSUB_MESH_PEERS_PER_TOPIC: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "gossipsub_mesh_peers_per_topic",
+        "Number of peers in the mesh of each topic",
+        &["topic"]
+    );
+    pub static ref GOSSIPSUB_MESH_GRAFTS_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_mesh_grafts_total",
+        "Count of peers observed joining the mesh of each topic",
+        &["topic"]
+    );
+    pub static ref GOSSIPSUB_MESH_PRUNES_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "gossipsub_mesh_prunes_total",
+        "Count of peers observed leaving the mesh of each topic",
+        &["topic"]
+    );
+    pub static ref GOSSIPSUB_MESH_PEER_SCORE: Result<GaugeVec> = try_create_float_gauge_vec(
+        "gossipsub_mesh_peer_avg_score",
+        "Average gossipsub score, including message delivery scoring, of the mesh peers of each topic",
+        &["topic"]
+    );
+
     /*
      * Attestation subnet subscriptions
      */
@@ -158,6 +183,15 @@ lazy_static! {
         "beacon_processor_bls_to_execution_change_imported_total",
         "Total number of address changes imported to the op pool."
     );
+    // HTTP API requests.
+    pub static ref BEACON_PROCESSOR_API_REQUEST_P0_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_api_request_p0_queue_total",
+        "Count of validator duty HTTP API requests waiting for a worker."
+    );
+    pub static ref BEACON_PROCESSOR_API_REQUEST_P1_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_api_request_p1_queue_total",
+        "Count of other HTTP API requests waiting for a worker."
+    );
     // Rpc blocks.
     pub static ref BEACON_PROCESSOR_RPC_BLOCK_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
         "beacon_processor_rpc_block_queue_total",
@@ -279,7 +313,9 @@ lazy_static! {
             "Gossipsub light_client_optimistic_update errors per error type",
             &["type"]
         );
+}
 
+lazy_static! {
 
     /*
      * Network queue metrics
@@ -294,9 +330,6 @@ lazy_static! {
         "Time taken for network to handle an event sent to the network service.",
         &["type"]
     );
-}
-
-lazy_static! {
 
     /*
      * Bandwidth metrics
@@ -425,10 +458,16 @@ pub fn register_sync_committee_error(error: &SyncCommitteeError) {
     inc_counter_vec(&GOSSIP_SYNC_COMMITTEE_ERRORS_PER_TYPE, &[error.as_ref()]);
 }
 
+/// The peers in the mesh of each topic, as of the last update of the gossip metrics.
+pub type MeshPeers = HashMap<TopicHash, HashSet<PeerId>>;
+
 pub fn update_gossip_metrics<T: EthSpec>(
     gossipsub: &Gossipsub,
     network_globals: &Arc<NetworkGlobals<T>>,
+    previous_mesh_peers: &mut MeshPeers,
 ) {
+    update_mesh_health_metrics(gossipsub, previous_mesh_peers);
+
     // Mesh peers per client
     // Reset the gauges
     for client_kind in ClientKind::iter() {
@@ -486,6 +525,66 @@ pub fn update_gossip_metrics<T: EthSpec>(
     }
 }
 
+/// Updates the mesh size, graft, prune and score metrics of each topic.
+///
+/// Grafts and prunes are found by comparing each mesh with `previous_mesh_peers`, so a peer which
+/// joins and leaves a mesh between updates is not counted.
+fn update_mesh_health_metrics(gossipsub: &Gossipsub, previous_mesh_peers: &mut MeshPeers) {
+    // Topics of different forks are combined, so that the labels don't change at each fork.
+    let mut mesh_peers = MeshPeers::new();
+    let mut peers_and_scores_per_kind: HashMap<String, (usize, f64)> = HashMap::new();
+
+    for topic_hash in gossipsub.topics() {
+        let kind = match GossipTopic::decode(topic_hash.as_str()) {
+            Ok(topic) => topic.kind().to_string(),
+            Err(_) => continue,
+        };
+        let peers = gossipsub
+            .mesh_peers(topic_hash)
+            .cloned()
+            .collect::<HashSet<_>>();
+        let previous_peers = previous_mesh_peers.remove(topic_hash).unwrap_or_default();
+        inc_counter_vec_by(
+            &GOSSIPSUB_MESH_GRAFTS_TOTAL,
+            &[&kind],
+            peers.difference(&previous_peers).count() as u64,
+        );
+        inc_counter_vec_by(
+            &GOSSIPSUB_MESH_PRUNES_TOTAL,
+            &[&kind],
+            previous_peers.difference(&peers).count() as u64,
+        );
+
+        let (peer_count, total_score) = peers_and_scores_per_kind.entry(kind).or_default();
+        *peer_count += peers.len();
+        *total_score += peers
+            .iter()
+            .filter_map(|peer_id| gossipsub.peer_score(peer_id))
+            .sum::<f64>();
+
+        mesh_peers.insert(topic_hash.clone(), peers);
+    }
+    *previous_mesh_peers = mesh_peers;
+
+    // Remove the topics which are no longer subscribed to.
+    if let Ok(gauge_vec) = GOSSIPSUB_MESH_PEERS_PER_TOPIC.as_ref() {
+        gauge_vec.reset();
+    }
+    if let Ok(gauge_vec) = GOSSIPSUB_MESH_PEER_SCORE.as_ref() {
+        gauge_vec.reset();
+    }
+    for (kind, (peer_count, total_score)) in peers_and_scores_per_kind {
+        set_gauge_vec(&GOSSIPSUB_MESH_PEERS_PER_TOPIC, &[&kind], peer_count as i64);
+        if peer_count > 0 {
+            set_float_gauge_vec(
+                &GOSSIPSUB_MESH_PEER_SCORE,
+                &[&kind],
+                total_score / peer_count as f64,
+            );
+        }
+    }
+}
+
 pub fn update_sync_metrics<T: EthSpec>(network_globals: &Arc<NetworkGlobals<T>>) {
     // reset the counts
     if PEERS_PER_SYNC_TYPE
@@ -2,16 +2,30 @@
 //!
 //! Currently supported strategies:
 //! - UPnP
+//! - NAT-PMP and PCP
+
+mod pcp;
 
 use crate::{NetworkConfig, NetworkMessage};
 use if_addrs::get_if_addrs;
+use lighthouse_network::PortMappingProtocol;
 use slog::{debug, info};
 use std::net::{IpAddr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+use task_executor::TaskExecutor;
 use tokio::sync::mpsc;
 use types::EthSpec;
 
-/// Configuration required to construct the UPnP port mappings.
-pub struct UPnPConfig {
+/// The duration of the leases requested for port mappings.
+const LEASE_DURATION: Duration = Duration::from_secs(3600);
+/// How often the port mappings are renewed, well before their leases expire.
+const RENEWAL_INTERVAL: Duration = Duration::from_secs(1800);
+
+/// Configuration required to construct the port mappings.
+#[derive(Clone)]
+pub struct PortMappingConfig {
+    /// The protocol used to construct the mappings.
+    protocol: PortMappingProtocol,
     /// The local tcp port.
     tcp_port: u16,
     /// The local udp port.
@@ -20,9 +34,10 @@ pub struct UPnPConfig {
     disable_discovery: bool,
 }
 
-impl UPnPConfig {
+impl PortMappingConfig {
     pub fn from_config(config: &NetworkConfig) -> Option<Self> {
-        config.listen_addrs().v4().map(|v4_addr| UPnPConfig {
+        config.listen_addrs().v4().map(|v4_addr| PortMappingConfig {
+            protocol: config.port_mapping_protocol,
             tcp_port: v4_addr.tcp_port,
             udp_port: v4_addr.udp_port,
             disable_discovery: config.disable_discovery,
@@ -30,22 +45,113 @@ impl UPnPConfig {
     }
 }
 
-/// Attempts to construct external port mappings with UPnP.
-pub fn construct_upnp_mappings<T: EthSpec>(
-    config: UPnPConfig,
+/// The port mappings constructed on the local gateway.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PortMappings {
+    /// The protocol which constructed the mappings, which is never `Auto`.
+    pub protocol: PortMappingProtocol,
+    /// The external TCP socket, if the TCP port was mapped.
+    pub tcp_socket: Option<SocketAddr>,
+    /// The external UDP socket, if the UDP port was mapped.
+    pub udp_socket: Option<SocketAddr>,
+    /// The local tcp port.
+    tcp_port: u16,
+    /// The local udp port.
+    udp_port: u16,
+}
+
+impl PortMappings {
+    fn is_empty(&self) -> bool {
+        self.tcp_socket.is_none() && self.udp_socket.is_none()
+    }
+}
+
+/// Constructs external port mappings, then renews them periodically so that their leases don't
+/// expire.
+///
+/// The network service is informed of the mappings when they are first established, and again if
+/// they change on renewal, e.g. because the external IP address of the router has changed.
+pub async fn port_mapping_task<T: EthSpec>(
+    config: PortMappingConfig,
+    executor: TaskExecutor,
     network_send: mpsc::UnboundedSender<NetworkMessage<T>>,
     log: slog::Logger,
 ) {
-    info!(log, "UPnP Attempting to initialise routes");
+    info!(log, "Attempting to construct port mappings"; "protocol" => ?config.protocol);
+    let mut established: Option<PortMappings> = None;
+    loop {
+        // Once established, the mappings are renewed with the same protocol.
+        let protocol = established.map_or(config.protocol, |mappings| mappings.protocol);
+        let mapping_config = config.clone();
+        let mapping_log = log.clone();
+        let mappings = match executor.spawn_blocking_handle(
+            move || construct_mappings(&mapping_config, protocol, &mapping_log),
+            "port_mappings",
+        ) {
+            Some(handle) => handle.await.ok().flatten(),
+            // The executor is shutting down.
+            None => return,
+        };
+
+        match mappings {
+            Some(mappings) if established != Some(mappings) => {
+                if let Some(tcp_socket) = mappings.tcp_socket {
+                    info!(log, "TCP route established"; "protocol" => ?mappings.protocol, "external_socket" => %tcp_socket);
+                }
+                if let Some(udp_socket) = mappings.udp_socket {
+                    info!(log, "UDP route established"; "protocol" => ?mappings.protocol, "external_socket" => %udp_socket);
+                }
+                established = Some(mappings);
+
+                // report any updates to the network service.
+                if let Err(e) =
+                    network_send.send(NetworkMessage::PortMappingEstablished { mappings })
+                {
+                    debug!(log, "Could not send message to the network service"; "error" => %e);
+                    return;
+                }
+            }
+            Some(_) => debug!(log, "Port mappings renewed"),
+            None => (),
+        }
+
+        tokio::time::sleep(RENEWAL_INTERVAL).await;
+    }
+}
+
+/// Attempts to construct external port mappings with `protocol`, returning `None` if no mappings
+/// could be attempted.
+///
+/// With `PortMappingProtocol::Auto`, NAT-PMP/PCP is used if UPnP doesn't map any ports.
+fn construct_mappings(
+    config: &PortMappingConfig,
+    protocol: PortMappingProtocol,
+    log: &slog::Logger,
+) -> Option<PortMappings> {
+    match protocol {
+        PortMappingProtocol::UPnP => construct_upnp_mappings(config, log),
+        PortMappingProtocol::NatPmp => pcp::construct_mappings(config, log),
+        PortMappingProtocol::Auto => construct_upnp_mappings(config, log)
+            .filter(|mappings| !mappings.is_empty())
+            .or_else(|| pcp::construct_mappings(config, log)),
+    }
+}
+
+/// Attempts to construct external port mappings with UPnP, returning `None` if no mappings could
+/// be attempted.
+fn construct_upnp_mappings(config: &PortMappingConfig, log: &slog::Logger) -> Option<PortMappings> {
     match igd::search_gateway(Default::default()) {
-        Err(e) => info!(log, "UPnP not available"; "error" => %e),
+        Err(e) => {
+            info!(log, "UPnP not available"; "error" => %e);
+            None
+        }
         Ok(gateway) => {
             // Need to find the local listening address matched with the router subnet
             let interfaces = match get_if_addrs() {
                 Ok(v) => v,
                 Err(e) => {
                     info!(log, "UPnP failed to get local interfaces"; "error" => %e);
-                    return;
+                    return None;
                 }
             };
             let local_ip = interfaces.iter().find_map(|interface| {
@@ -61,7 +167,7 @@ pub fn construct_upnp_mappings<T: EthSpec>(
             let local_ip = match local_ip {
                 None => {
                     info!(log, "UPnP failed to find local IP address");
-                    return;
+                    return None;
                 }
                 Some(v) => v,
             };
@@ -81,12 +187,15 @@ pub fn construct_upnp_mappings<T: EthSpec>(
                         igd::PortMappingProtocol::TCP,
                         libp2p_socket,
                         "tcp",
-                        &log,
-                    ).and_then(|_| {
-                        let external_socket = external_ip.as_ref().map(|ip| SocketAddr::new((*ip).into(), config.tcp_port)).map_err(|_| ());
-                        info!(log, "UPnP TCP route established"; "external_socket" => format!("{}:{}", external_socket.as_ref().map(|ip| ip.to_string()).unwrap_or_else(|_| "".into()), config.tcp_port));
-                        external_socket
-                    }).ok();
+                        log,
+                    )
+                    .and_then(|_| {
+                        external_ip
+                            .as_ref()
+                            .map(|ip| SocketAddr::new((*ip).into(), config.tcp_port))
+                            .map_err(|_| ())
+                    })
+                    .ok();
 
                     let udp_socket = if !config.disable_discovery {
                         let discovery_socket = SocketAddrV4::new(address, config.udp_port);
@@ -95,25 +204,34 @@ pub fn construct_upnp_mappings<T: EthSpec>(
                             igd::PortMappingProtocol::UDP,
                             discovery_socket,
                             "udp",
-                            &log,
-                        ).and_then(|_| {
-                            let external_socket = external_ip
-                                    .map(|ip| SocketAddr::new(ip.into(), config.udp_port)).map_err(|_| ());
-                        info!(log, "UPnP UDP route established"; "external_socket" => format!("{}:{}", external_socket.as_ref().map(|ip| ip.to_string()).unwrap_or_else(|_| "".into()), config.udp_port));
-                        external_socket
-                    }).ok()
+                            log,
+                        )
+                        .and_then(|_| {
+                            external_ip
+                                .as_ref()
+                                .map(|ip| SocketAddr::new((*ip).into(), config.udp_port))
+                                .map_err(|_| ())
+                        })
+                        .ok()
                     } else {
                         None
                     };
 
-                    // report any updates to the network service.
-                    network_send.send(NetworkMessage::UPnPMappingEstablished{ tcp_socket, udp_socket })
-            .unwrap_or_else(|e| debug!(log, "Could not send message to the network service"; "error" => %e));
+                    Some(PortMappings {
+                        protocol: PortMappingProtocol::UPnP,
+                        tcp_socket,
+                        udp_socket,
+                        tcp_port: config.tcp_port,
+                        udp_port: config.udp_port,
+                    })
+                }
+                _ => {
+                    debug!(log, "UPnP no routes constructed. IPv6 not supported");
+                    None
                 }
-                _ => debug!(log, "UPnP no routes constructed. IPv6 not supported"),
             }
         }
-    };
+    }
 }
 
 /// Sets up a port mapping for a protocol returning the mapped port if successful.
@@ -129,8 +247,15 @@ fn add_port_mapping(
     // I've found this to be more reliable. If multiple users are behind a single
     // router, they should ideally try to set different port numbers.
     let mapping_string = &format!("lighthouse-{}", protocol_string);
-    for _ in 0..2 {
-        match gateway.add_port(protocol, socket.port(), socket, 0, mapping_string) {
+    let mut lease_duration = LEASE_DURATION.as_secs() as u32;
+    for _ in 0..3 {
+        match gateway.add_port(
+            protocol,
+            socket.port(),
+            socket,
+            lease_duration,
+            mapping_string,
+        ) {
             Err(e) => {
                 match e {
                     igd::AddPortError::PortInUse => {
@@ -146,6 +271,11 @@ fn add_port_mapping(
                             }
                         }
                     }
+                    igd::AddPortError::OnlyPermanentLeasesSupported => {
+                        // The mapping is renewed anyway, which is harmless.
+                        debug!(log, "UPnP gateway only supports permanent leases"; "protocol" => protocol_string);
+                        lease_duration = 0;
+                    }
                     e => {
                         info!(log, "UPnP TCP route not set"; "error" => %e);
                         return Err(());
@@ -160,8 +290,20 @@ fn add_port_mapping(
     Err(())
 }
 
-/// Removes the specified TCP and UDP port mappings.
-pub fn remove_mappings(tcp_port: Option<u16>, udp_port: Option<u16>, log: &slog::Logger) {
+/// Removes the established TCP and UDP port mappings.
+pub fn remove_mappings(mappings: &PortMappings, log: &slog::Logger) {
+    match mappings.protocol {
+        PortMappingProtocol::NatPmp => pcp::remove_mappings(mappings, log),
+        PortMappingProtocol::UPnP | PortMappingProtocol::Auto => remove_upnp_mappings(
+            mappings.tcp_socket.map(|_| mappings.tcp_port),
+            mappings.udp_socket.map(|_| mappings.udp_port),
+            log,
+        ),
+    }
+}
+
+/// Removes the specified TCP and UDP port mappings with UPnP.
+fn remove_upnp_mappings(tcp_port: Option<u16>, udp_port: Option<u16>, log: &slog::Logger) {
     if tcp_port.is_some() || udp_port.is_some() {
         debug!(log, "Removing UPnP port mappings");
         match igd::search_gateway(Default::default()) {
@@ -0,0 +1,363 @@
+//! Port mappings with NAT-PMP (RFC 6886) and its successor PCP (RFC 6887).
+//!
+//! Requests are sent to the default IPv4 gateway, which is read from the routing table on Linux.
+//! PCP is attempted first, and NAT-PMP is used if the gateway responds that it only supports
+//! NAT-PMP.
+
+use super::{PortMappingConfig, PortMappings, LEASE_DURATION};
+use lazy_static::lazy_static;
+use lighthouse_network::PortMappingProtocol;
+use slog::{debug, info};
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::Duration;
+
+/// The port on which the gateway listens for NAT-PMP and PCP requests.
+const SERVER_PORT: u16 = 5351;
+const NAT_PMP_VERSION: u8 = 0;
+const PCP_VERSION: u8 = 2;
+/// The NAT-PMP opcode requesting the external address of the gateway.
+const NAT_PMP_OPCODE_EXTERNAL_ADDRESS: u8 = 0;
+/// The PCP opcode requesting a port mapping.
+const PCP_OPCODE_MAP: u8 = 1;
+/// The bit set in the opcodes of responses.
+const RESPONSE_BIT: u8 = 0x80;
+/// The length of PCP `MAP` requests and responses.
+const PCP_MAP_LEN: usize = 60;
+/// Requests are retransmitted after this timeout, which doubles on each attempt.
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+/// The maximum number of times a request is sent, which is fewer than the RFCs suggest so that a
+/// missing gateway is detected within a few seconds.
+const MAX_ATTEMPTS: u32 = 4;
+
+lazy_static! {
+    /// The nonce identifying our PCP mappings, which is required to renew or remove them.
+    static ref PCP_NONCE: [u8; 12] = rand::random();
+}
+
+#[derive(Clone, Copy)]
+enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    fn nat_pmp_opcode(self) -> u8 {
+        match self {
+            Protocol::Udp => 1,
+            Protocol::Tcp => 2,
+        }
+    }
+
+    fn iana_number(self) -> u8 {
+        match self {
+            Protocol::Tcp => 6,
+            Protocol::Udp => 17,
+        }
+    }
+}
+
+/// Attempts to construct external port mappings with PCP or NAT-PMP, returning `None` if no
+/// gateway could be found.
+pub fn construct_mappings(config: &PortMappingConfig, log: &slog::Logger) -> Option<PortMappings> {
+    let client = match Client::for_default_gateway() {
+        Ok(client) => client,
+        Err(e) => {
+            info!(log, "NAT-PMP not available"; "error" => e);
+            return None;
+        }
+    };
+    let lifetime = LEASE_DURATION.as_secs() as u32;
+
+    let tcp_socket = client
+        .map(Protocol::Tcp, config.tcp_port, lifetime)
+        .map_err(|e| info!(log, "NAT-PMP TCP route not set"; "error" => e))
+        .ok();
+
+    let udp_socket = if !config.disable_discovery {
+        client
+            .map(Protocol::Udp, config.udp_port, lifetime)
+            .map_err(|e| info!(log, "NAT-PMP UDP route not set"; "error" => e))
+            .ok()
+    } else {
+        None
+    };
+
+    Some(PortMappings {
+        protocol: PortMappingProtocol::NatPmp,
+        tcp_socket,
+        udp_socket,
+        tcp_port: config.tcp_port,
+        udp_port: config.udp_port,
+    })
+}
+
+/// Removes the established port mappings, by requesting them again with a lifetime of zero.
+pub fn remove_mappings(mappings: &PortMappings, log: &slog::Logger) {
+    if mappings.is_empty() {
+        return;
+    }
+    debug!(log, "Removing NAT-PMP port mappings");
+    let client = match Client::for_default_gateway() {
+        Ok(client) => client,
+        Err(e) => {
+            debug!(log, "NAT-PMP failed to remove mappings"; "error" => e);
+            return;
+        }
+    };
+
+    if mappings.tcp_socket.is_some() {
+        match client.map(Protocol::Tcp, mappings.tcp_port, 0) {
+            Ok(_) => debug!(log, "NAT-PMP Removed TCP port mapping"; "port" => mappings.tcp_port),
+            Err(e) => {
+                debug!(log, "NAT-PMP Failed to remove TCP port mapping"; "port" => mappings.tcp_port, "error" => e)
+            }
+        }
+    }
+    if mappings.udp_socket.is_some() {
+        match client.map(Protocol::Udp, mappings.udp_port, 0) {
+            Ok(_) => debug!(log, "NAT-PMP Removed UDP port mapping"; "port" => mappings.udp_port),
+            Err(e) => {
+                debug!(log, "NAT-PMP Failed to remove UDP port mapping"; "port" => mappings.udp_port, "error" => e)
+            }
+        }
+    }
+}
+
+/// A client which sends requests to the gateway from a socket connected to it.
+struct Client {
+    socket: UdpSocket,
+    /// The address of this node on the gateway's network, which PCP requests must contain.
+    local_ip: Ipv4Addr,
+}
+
+impl Client {
+    fn for_default_gateway() -> Result<Self, String> {
+        let routes = std::fs::read_to_string("/proc/net/route")
+            .map_err(|e| format!("Unable to read the routing table: {}", e))?;
+        let gateway = parse_default_gateway(&routes).ok_or("No default gateway")?;
+
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .map_err(|e| format!("Unable to bind socket: {}", e))?;
+        socket
+            .connect((gateway, SERVER_PORT))
+            .map_err(|e| format!("Unable to connect to gateway {}: {}", gateway, e))?;
+        let local_ip = match socket.local_addr() {
+            Ok(SocketAddr::V4(local_addr)) => *local_addr.ip(),
+            Ok(SocketAddr::V6(_)) => return Err("Socket has an IPv6 address".into()),
+            Err(e) => return Err(format!("Unable to get local address: {}", e)),
+        };
+
+        Ok(Self { socket, local_ip })
+    }
+
+    /// Sends `request` to the gateway, retransmitting it until a response is received into
+    /// `response`, and returns the length of the response.
+    fn request(&self, request: &[u8], response: &mut [u8]) -> Result<usize, String> {
+        let mut timeout = INITIAL_TIMEOUT;
+        for _ in 0..MAX_ATTEMPTS {
+            self.socket
+                .send(request)
+                .map_err(|e| format!("Unable to send request: {}", e))?;
+            self.socket
+                .set_read_timeout(Some(timeout))
+                .map_err(|e| format!("Unable to set timeout: {}", e))?;
+            match self.socket.recv(response) {
+                Ok(len) => return Ok(len),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    timeout *= 2
+                }
+                Err(e) => return Err(format!("Unable to receive response: {}", e)),
+            }
+        }
+        Err("No response from gateway".into())
+    }
+
+    /// Maps `internal_port` to an external socket for `lifetime` seconds, returning the external
+    /// socket. The mapping is removed if `lifetime` is zero.
+    fn map(
+        &self,
+        protocol: Protocol,
+        internal_port: u16,
+        lifetime: u32,
+    ) -> Result<SocketAddr, String> {
+        let request = pcp_map_request(self.local_ip, protocol, internal_port, lifetime);
+        let mut response = [0u8; 1100];
+        let len = self.request(&request, &mut response)?;
+        if len >= 2 && response[0] == NAT_PMP_VERSION {
+            // The gateway doesn't support PCP.
+            return self.nat_pmp_map(protocol, internal_port, lifetime);
+        }
+        parse_pcp_map_response(&response[..len], protocol, internal_port)
+    }
+
+    fn nat_pmp_map(
+        &self,
+        protocol: Protocol,
+        internal_port: u16,
+        lifetime: u32,
+    ) -> Result<SocketAddr, String> {
+        let mut request = [0u8; 12];
+        request[0] = NAT_PMP_VERSION;
+        request[1] = protocol.nat_pmp_opcode();
+        request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+        // The suggested external port must be zero when removing a mapping.
+        let external_port = if lifetime == 0 { 0 } else { internal_port };
+        request[6..8].copy_from_slice(&external_port.to_be_bytes());
+        request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+        let mut response = [0u8; 16];
+        let len = self.request(&request, &mut response)?;
+        check_nat_pmp_response(&response[..len], protocol.nat_pmp_opcode(), 16)?;
+        let external_port = u16::from_be_bytes([response[10], response[11]]);
+
+        let mut response = [0u8; 12];
+        let len = self.request(
+            &[NAT_PMP_VERSION, NAT_PMP_OPCODE_EXTERNAL_ADDRESS],
+            &mut response,
+        )?;
+        check_nat_pmp_response(&response[..len], NAT_PMP_OPCODE_EXTERNAL_ADDRESS, 12)?;
+        let external_ip = Ipv4Addr::new(response[8], response[9], response[10], response[11]);
+
+        Ok(SocketAddr::new(external_ip.into(), external_port))
+    }
+}
+
+/// Returns the default gateway from the contents of `/proc/net/route`.
+///
+/// Each route after the header is a line of whitespace-separated fields, starting with the
+/// interface, destination and gateway. Addresses are hex encoded in host byte order.
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|route| {
+        let mut fields = route.split_whitespace().skip(1);
+        let destination = fields.next()?;
+        let gateway = u32::from_str_radix(fields.next()?, 16).ok()?;
+        (destination == "00000000" && gateway != 0).then(|| Ipv4Addr::from(gateway.to_ne_bytes()))
+    })
+}
+
+fn pcp_map_request(
+    local_ip: Ipv4Addr,
+    protocol: Protocol,
+    internal_port: u16,
+    lifetime: u32,
+) -> [u8; PCP_MAP_LEN] {
+    let mut request = [0u8; PCP_MAP_LEN];
+    request[0] = PCP_VERSION;
+    request[1] = PCP_OPCODE_MAP;
+    request[4..8].copy_from_slice(&lifetime.to_be_bytes());
+    request[8..24].copy_from_slice(&local_ip.to_ipv6_mapped().octets());
+    request[24..36].copy_from_slice(&*PCP_NONCE);
+    request[36] = protocol.iana_number();
+    request[40..42].copy_from_slice(&internal_port.to_be_bytes());
+    request[42..44].copy_from_slice(&internal_port.to_be_bytes());
+    // Any external address is suggested.
+    request[44..60].copy_from_slice(&Ipv4Addr::UNSPECIFIED.to_ipv6_mapped().octets());
+    request
+}
+
+fn parse_pcp_map_response(
+    response: &[u8],
+    protocol: Protocol,
+    internal_port: u16,
+) -> Result<SocketAddr, String> {
+    if response.len() < PCP_MAP_LEN
+        || response[0] != PCP_VERSION
+        || response[1] != RESPONSE_BIT | PCP_OPCODE_MAP
+    {
+        return Err("Invalid PCP response".into());
+    }
+    let result_code = response[3];
+    if result_code != 0 {
+        return Err(format!(
+            "PCP request failed with result code {}",
+            result_code
+        ));
+    }
+    if response[24..36] != *PCP_NONCE
+        || response[36] != protocol.iana_number()
+        || response[40..42] != internal_port.to_be_bytes()
+    {
+        return Err("PCP response is for another mapping".into());
+    }
+
+    let external_port = u16::from_be_bytes([response[42], response[43]]);
+    let mut external_ip = [0u8; 16];
+    external_ip.copy_from_slice(&response[44..60]);
+    let external_ip = Ipv6Addr::from(external_ip);
+    let external_ip = external_ip
+        .to_ipv4_mapped()
+        .map_or(IpAddr::V6(external_ip), IpAddr::V4);
+
+    Ok(SocketAddr::new(external_ip, external_port))
+}
+
+fn check_nat_pmp_response(response: &[u8], opcode: u8, len: usize) -> Result<(), String> {
+    if response.len() < len
+        || response[0] != NAT_PMP_VERSION
+        || response[1] != RESPONSE_BIT | opcode
+    {
+        return Err("Invalid NAT-PMP response".into());
+    }
+    let result_code = u16::from_be_bytes([response[2], response[3]]);
+    if result_code != 0 {
+        return Err(format!(
+            "NAT-PMP request failed with result code {}",
+            result_code
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_gateway() {
+        let routes =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                      eth0\t0000A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n\
+                      eth0\t00000000\t0100A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n";
+        assert_eq!(
+            parse_default_gateway(routes),
+            Some(Ipv4Addr::new(192, 168, 0, 1))
+        );
+        assert_eq!(
+            parse_default_gateway(
+                routes
+                    .lines()
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .as_str()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn pcp_map_response() {
+        let internal_port = 9000;
+        let external_ip = Ipv4Addr::new(203, 0, 113, 1);
+        let mut response = pcp_map_request(
+            Ipv4Addr::new(192, 168, 0, 2),
+            Protocol::Tcp,
+            internal_port,
+            3600,
+        );
+        response[1] |= RESPONSE_BIT;
+        response[42..44].copy_from_slice(&9001u16.to_be_bytes());
+        response[44..60].copy_from_slice(&external_ip.to_ipv6_mapped().octets());
+
+        assert_eq!(
+            parse_pcp_map_response(&response, Protocol::Tcp, internal_port),
+            Ok(SocketAddr::new(external_ip.into(), 9001))
+        );
+        assert!(parse_pcp_map_response(&response, Protocol::Udp, internal_port).is_err());
+
+        // Result code 2 is `NOT_AUTHORIZED`.
+        response[3] = 2;
+        assert!(parse_pcp_map_response(&response, Protocol::Tcp, internal_port).is_err());
+    }
+}
@@ -6,7 +6,8 @@
 #![allow(clippy::unit_arg)]
 
 use crate::beacon_processor::{
-    BeaconProcessor, WorkEvent as BeaconWorkEvent, MAX_WORK_EVENT_QUEUE_LEN,
+    spawn_api_request_forwarder, ApiRequest, BeaconProcessor, WorkEvent as BeaconWorkEvent,
+    MAX_WORK_EVENT_QUEUE_LEN,
 };
 use crate::error;
 use crate::service::{NetworkMessage, RequestId};
@@ -17,7 +18,8 @@ use beacon_chain::{BeaconChain, BeaconChainTypes};
 use futures::prelude::*;
 use lighthouse_network::rpc::*;
 use lighthouse_network::{
-    MessageId, NetworkGlobals, PeerId, PeerRequestId, PubsubMessage, Request, Response,
+    MessageId, NetworkConfig, NetworkGlobals, PeerId, PeerRequestId, PubsubMessage, Request,
+    Response,
 };
 use slog::{debug, o, trace};
 use slog::{error, warn};
@@ -78,8 +80,10 @@ impl<T: BeaconChainTypes> Router<T> {
     /// Initializes and runs the Router.
     pub fn spawn(
         beacon_chain: Arc<BeaconChain<T>>,
+        config: &NetworkConfig,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
         network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+        api_request_recv: mpsc::Receiver<ApiRequest>,
         executor: task_executor::TaskExecutor,
         log: slog::Logger,
     ) -> error::Result<mpsc::UnboundedSender<RouterMessage<T::EthSpec>>> {
@@ -109,13 +113,21 @@ impl<T: BeaconChainTypes> Router<T> {
             sync_tx: sync_send.clone(),
             network_globals: network_globals.clone(),
             executor: executor.clone(),
-            max_workers: cmp::max(1, num_cpus::get()),
+            max_workers: cmp::max(
+                1,
+                config
+                    .beacon_processor_max_workers
+                    .map_or(num_cpus::get(), |max| cmp::min(max, num_cpus::get())),
+            ),
+            queue_length_divisor: config.beacon_processor_queue_length_divisor,
             current_workers: 0,
             importing_blocks: Default::default(),
             log: log.clone(),
         }
         .spawn_manager(beacon_processor_receive, None);
 
+        spawn_api_request_forwarder(api_request_recv, beacon_processor_send.clone(), &executor);
+
         // generate the Message handler
         let mut handler = Router {
             network_globals,
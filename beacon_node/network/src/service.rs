@@ -1,4 +1,6 @@
 use super::sync::manager::RequestId as SyncId;
+use crate::beacon_processor::ApiRequest;
+use crate::nat::PortMappings;
 use crate::persisted_dht::{clear_dht, load_dht, persist_dht};
 use crate::router::{Router, RouterMessage};
 use crate::subnet_service::SyncCommitteeService;
@@ -23,10 +25,10 @@ use lighthouse_network::{
     MessageId, NetworkEvent, NetworkGlobals, PeerId,
 };
 use slog::{crit, debug, error, info, o, trace, warn};
-use std::{net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+use std::{pin::Pin, sync::Arc, time::Duration};
 use store::HotColdDB;
 use strum::IntoStaticStr;
-use task_executor::ShutdownReason;
+use task_executor::{FailureKind, ShutdownReason, HEARTBEAT_INTERVAL};
 use tokio::sync::mpsc;
 use tokio::time::Sleep;
 use types::{
@@ -45,6 +47,8 @@ const UNSUBSCRIBE_DELAY_EPOCHS: u64 = 2;
 /// Size of the queue for validator subnet subscriptions. The number is chosen so that we may be
 /// able to run tens of thousands of validators on one BN.
 const VALIDATOR_SUBSCRIPTION_MESSAGE_QUEUE_SIZE: usize = 65_536;
+/// Size of the queue for HTTP API requests waiting to be passed to the beacon processor.
+const API_REQUEST_QUEUE_SIZE: usize = 1_024;
 
 /// Application level requests sent to the network.
 #[derive(Debug, Clone, Copy)]
@@ -90,13 +94,8 @@ pub enum NetworkMessage<T: EthSpec> {
         /// The result of the validation
         validation_result: MessageAcceptance,
     },
-    /// Called if a known external TCP socket address has been updated.
-    UPnPMappingEstablished {
-        /// The external TCP address has been updated.
-        tcp_socket: Option<SocketAddr>,
-        /// The external UDP address has been updated.
-        udp_socket: Option<SocketAddr>,
-    },
+    /// Called if the external sockets mapped on the local gateway have been updated.
+    PortMappingEstablished { mappings: PortMappings },
     /// Reports a peer to the peer manager for performing an action.
     ReportPeer {
         peer_id: PeerId,
@@ -133,6 +132,8 @@ pub enum ValidatorSubscriptionMessage {
 pub struct NetworkSenders<E: EthSpec> {
     network_send: mpsc::UnboundedSender<NetworkMessage<E>>,
     validator_subscription_send: mpsc::Sender<ValidatorSubscriptionMessage>,
+    /// Schedules HTTP API requests with the beacon processor, if it is running.
+    api_request_send: Option<mpsc::Sender<ApiRequest>>,
 }
 
 pub struct NetworkReceivers<E: EthSpec> {
@@ -148,6 +149,7 @@ impl<E: EthSpec> NetworkSenders<E> {
         let senders = Self {
             network_send,
             validator_subscription_send,
+            api_request_send: None,
         };
         let receivers = NetworkReceivers {
             network_recv,
@@ -163,6 +165,14 @@ impl<E: EthSpec> NetworkSenders<E> {
     pub fn validator_subscription_send(&self) -> mpsc::Sender<ValidatorSubscriptionMessage> {
         self.validator_subscription_send.clone()
     }
+
+    pub fn api_request_send(&self) -> Option<mpsc::Sender<ApiRequest>> {
+        self.api_request_send.clone()
+    }
+
+    pub fn set_api_request_send(&mut self, api_request_send: mpsc::Sender<ApiRequest>) {
+        self.api_request_send = Some(api_request_send);
+    }
 }
 
 /// Service that handles communication between internal services and the `lighthouse_network` network service.
@@ -186,11 +196,10 @@ pub struct NetworkService<T: BeaconChainTypes> {
     store: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
     /// A collection of global variables, accessible outside of the network service.
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
-    /// Stores potentially created UPnP mappings to be removed on shutdown. (TCP port and UDP
-    /// port).
-    upnp_mappings: (Option<u16>, Option<u16>),
+    /// Stores potentially created UPnP or NAT-PMP mappings to be removed on shutdown.
+    port_mappings: Option<PortMappings>,
     /// Keeps track of if discovery is auto-updating or not. This is used to inform us if we should
-    /// update the UDP socket of discovery if the UPnP mappings get established.
+    /// update the UDP socket of discovery if the port mappings get established.
     discovery_auto_update: bool,
     /// A delay that expires when a new fork takes place.
     next_fork_update: Pin<Box<OptionFuture<Sleep>>>,
@@ -206,6 +215,8 @@ pub struct NetworkService<T: BeaconChainTypes> {
     metrics_enabled: bool,
     /// A timer for updating various network metrics.
     metrics_update: tokio::time::Interval,
+    /// The mesh peers of each topic at the last metrics update.
+    mesh_peers: metrics::MeshPeers,
     /// gossipsub_parameter_update timer
     gossipsub_parameter_update: tokio::time::Interval,
     /// enable_light_client_server indicator
@@ -225,22 +236,24 @@ impl<T: BeaconChainTypes> NetworkService<T> {
     ) -> error::Result<(Arc<NetworkGlobals<T::EthSpec>>, NetworkSenders<T::EthSpec>)> {
         let network_log = executor.log().clone();
         // build the channels for external comms
-        let (network_senders, network_recievers) = NetworkSenders::new();
-
-        // try and construct UPnP port mappings if required.
-        if let Some(upnp_config) = crate::nat::UPnPConfig::from_config(config) {
-            let upnp_log = network_log.new(o!("service" => "UPnP"));
-            let upnp_network_send = network_senders.network_send();
-            if config.upnp_enabled {
-                executor.spawn_blocking(
-                    move || {
-                        crate::nat::construct_upnp_mappings(
-                            upnp_config,
-                            upnp_network_send,
-                            upnp_log,
-                        )
-                    },
-                    "UPnP",
+        let (mut network_senders, network_recievers) = NetworkSenders::new();
+        let (api_request_send, api_request_recv) = mpsc::channel(API_REQUEST_QUEUE_SIZE);
+        network_senders.set_api_request_send(api_request_send);
+
+        // try and construct UPnP or NAT-PMP port mappings if required. The mappings are only
+        // used to advertise an external address, so are unnecessary if the ENR omits it.
+        if let Some(port_mapping_config) = crate::nat::PortMappingConfig::from_config(config) {
+            let port_mapping_log = network_log.new(o!("service" => "port_mapping"));
+            let port_mapping_network_send = network_senders.network_send();
+            if config.upnp_enabled && !config.enr_omit_address {
+                executor.spawn(
+                    crate::nat::port_mapping_task(
+                        port_mapping_config,
+                        executor.clone(),
+                        port_mapping_network_send,
+                        port_mapping_log,
+                    ),
+                    "port_mapping",
                 );
             }
         }
@@ -299,8 +312,10 @@ impl<T: BeaconChainTypes> NetworkService<T> {
         // router task
         let router_send = Router::spawn(
             beacon_chain.clone(),
+            config,
             network_globals.clone(),
             network_senders.network_send(),
+            api_request_recv,
             executor.clone(),
             network_log.clone(),
         )?;
@@ -340,7 +355,7 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             router_send,
             store,
             network_globals: network_globals.clone(),
-            upnp_mappings: (None, None),
+            port_mappings: None,
             discovery_auto_update: config.discv5_config.enr_update,
             next_fork_update,
             next_fork_subscriptions,
@@ -349,6 +364,7 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             shutdown_after_sync: config.shutdown_after_sync,
             metrics_enabled: config.metrics_enabled,
             metrics_update,
+            mesh_peers: <_>::default(),
             gossipsub_parameter_update,
             fork_context,
             log: network_log,
@@ -405,16 +421,21 @@ impl<T: BeaconChainTypes> NetworkService<T> {
 
     fn spawn_service(mut self, executor: task_executor::TaskExecutor) {
         let mut shutdown_sender = executor.shutdown_sender();
+        let heartbeat = executor.heartbeat("network");
+        let mut heartbeat_interval = tokio::time::interval(HEARTBEAT_INTERVAL);
 
         // spawn on the current executor
         let service_fut = async move {
             loop {
                 tokio::select! {
+                    _ = heartbeat_interval.tick() => heartbeat.beat(),
+
                     _ = self.metrics_update.tick(), if self.metrics_enabled => {
                         // update various network metrics
                         metrics::update_gossip_metrics::<T::EthSpec>(
                             self.libp2p.gossipsub(),
                             &self.network_globals,
+                            &mut self.mesh_peers,
                             );
                         // update sync metrics
                         metrics::update_sync_metrics(&self.network_globals);
@@ -553,6 +574,7 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             NetworkEvent::ZeroListeners => {
                 let _ = shutdown_sender
                     .send(ShutdownReason::Failure(
+                        FailureKind::PortBind,
                         "All listeners are closed. Unable to listen",
                     ))
                     .await
@@ -599,13 +621,10 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             } => {
                 self.libp2p.send_error_reponse(peer_id, id, error, reason);
             }
-            NetworkMessage::UPnPMappingEstablished {
-                tcp_socket,
-                udp_socket,
-            } => {
-                self.upnp_mappings = (tcp_socket.map(|s| s.port()), udp_socket.map(|s| s.port()));
+            NetworkMessage::PortMappingEstablished { mappings } => {
+                self.port_mappings = Some(mappings);
                 // If there is an external TCP port update, modify our local ENR.
-                if let Some(tcp_socket) = tcp_socket {
+                if let Some(tcp_socket) = mappings.tcp_socket {
                     if let Err(e) = self
                         .libp2p
                         .discovery_mut()
@@ -615,9 +634,9 @@ impl<T: BeaconChainTypes> NetworkService<T> {
                     }
                 }
                 // if the discovery service is not auto-updating, update it with the
-                // UPnP mappings
+                // port mappings
                 if !self.discovery_auto_update {
-                    if let Some(udp_socket) = udp_socket {
+                    if let Some(udp_socket) = mappings.udp_socket {
                         if let Err(e) = self
                             .libp2p
                             .discovery_mut()
@@ -963,7 +982,9 @@ impl<T: BeaconChainTypes> Drop for NetworkService<T> {
         }
 
         // attempt to remove port mappings
-        crate::nat::remove_mappings(self.upnp_mappings.0, self.upnp_mappings.1, &self.log);
+        if let Some(mappings) = &self.port_mappings {
+            crate::nat::remove_mappings(mappings, &self.log);
+        }
 
         info!(self.log, "Network service shutdown");
     }
@@ -161,7 +161,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
 
         let (state, current_start) = if let Some(anchor_info) = beacon_chain.store.get_anchor_info()
         {
-            if anchor_info.block_backfill_complete() {
+            if anchor_info.block_backfill_complete(beacon_chain.store.get_history_horizon()) {
                 (BackFillState::Completed, Epoch::new(0))
             } else {
                 (
@@ -1068,7 +1068,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
     /// Creates the next required batch from the chain. If there are no more batches required,
     /// `false` is returned.
     fn include_next_batch(&mut self) -> Option<BatchId> {
-        // don't request batches beyond genesis;
+        // don't request batches beyond genesis, or the history horizon if old blocks are pruned;
         if self.last_batch_downloaded {
             return None;
         }
@@ -1097,7 +1097,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
         match self.batches.entry(batch_id) {
             Entry::Occupied(_) => {
                 // this batch doesn't need downloading, let this same function decide the next batch
-                if batch_id == 0 {
+                if batch_id <= self.target_epoch() {
                     self.last_batch_downloaded = true;
                 }
 
@@ -1108,7 +1108,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
             }
             Entry::Vacant(entry) => {
                 entry.insert(BatchInfo::new(&batch_id, BACKFILL_EPOCHS_PER_BATCH));
-                if batch_id == 0 {
+                if batch_id <= self.target_epoch() {
                     self.last_batch_downloaded = true;
                 }
                 self.to_be_downloaded = self
@@ -1125,7 +1125,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
     /// not required.
     fn reset_start_epoch(&mut self) -> Result<(), ResetEpochError> {
         if let Some(anchor_info) = self.beacon_chain.store.get_anchor_info() {
-            if anchor_info.block_backfill_complete() {
+            if anchor_info.block_backfill_complete(self.beacon_chain.store.get_history_horizon()) {
                 Err(ResetEpochError::SyncCompleted)
             } else {
                 self.current_start = anchor_info
@@ -1138,14 +1138,28 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
         }
     }
 
+    /// The epoch of the last batch to download, which is the batch containing the history horizon.
+    ///
+    /// Blocks older than the horizon are pruned, so backfilling them would be wasted effort. The
+    /// horizon is genesis unless `--prune-blocks-older-than` is set.
+    fn target_epoch(&self) -> Epoch {
+        self.beacon_chain
+            .store
+            .get_history_horizon()
+            .epoch(T::EthSpec::slots_per_epoch())
+            .saturating_sub(1u64)
+    }
+
     /// Checks with the beacon chain if backfill sync has completed.
     fn check_completed(&mut self) -> bool {
-        if self.current_start == 0 {
+        if self.current_start <= self.target_epoch() {
             // Check that the beacon chain agrees
 
             if let Some(anchor_info) = self.beacon_chain.store.get_anchor_info() {
                 // Conditions that we have completed a backfill sync
-                if anchor_info.block_backfill_complete() {
+                if anchor_info
+                    .block_backfill_complete(self.beacon_chain.store.get_history_horizon())
+                {
                     return true;
                 } else {
                     error!(self.log, "Backfill out of sync with beacon chain");
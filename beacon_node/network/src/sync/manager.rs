@@ -52,6 +52,7 @@ use std::boxed::Box;
 use std::ops::Sub;
 use std::sync::Arc;
 use std::time::Duration;
+use task_executor::{Heartbeat, HEARTBEAT_INTERVAL};
 use tokio::sync::mpsc;
 use types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
 
@@ -217,7 +218,11 @@ pub fn spawn<T: BeaconChainTypes>(
 
     // spawn the sync manager thread
     debug!(log, "Sync Manager started");
-    executor.spawn(async move { Box::pin(sync_manager.main()).await }, "sync");
+    let heartbeat = executor.heartbeat("sync");
+    executor.spawn(
+        async move { Box::pin(sync_manager.main(heartbeat)).await },
+        "sync",
+    );
     sync_send
 }
 
@@ -464,7 +469,7 @@ impl<T: BeaconChainTypes> SyncManager<T> {
     }
 
     /// The main driving future for the sync manager.
-    async fn main(&mut self) {
+    async fn main(&mut self, heartbeat: Heartbeat) {
         let check_ee = self.chain.execution_layer.is_some();
         let mut check_ee_stream = {
             // some magic to have an instance implementing stream even if there is no execution layer
@@ -477,9 +482,12 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             futures::stream::iter(ee_responsiveness_watch.await).flatten()
         };
 
+        let mut heartbeat_interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+
         // process any inbound messages
         loop {
             tokio::select! {
+                _ = heartbeat_interval.tick() => heartbeat.beat(),
                 Some(sync_message) = self.input_channel.recv() => {
                     self.handle_message(sync_message);
                 },
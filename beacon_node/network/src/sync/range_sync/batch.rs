@@ -5,6 +5,7 @@ use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::ops::Sub;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use types::{Epoch, EthSpec, SignedBeaconBlock, Slot};
 
 /// The number of times to retry a batch before it is considered failed.
@@ -96,6 +97,8 @@ pub struct BatchInfo<T: EthSpec, B: BatchConfig = RangeSyncBatchConfig> {
     failed_download_attempts: Vec<PeerId>,
     /// State of the batch.
     state: BatchState<T>,
+    /// When the latest download of the batch was requested.
+    download_started: Option<Instant>,
     /// Pin the generic
     marker: std::marker::PhantomData<B>,
 }
@@ -149,10 +152,16 @@ impl<T: EthSpec, B: BatchConfig> BatchInfo<T, B> {
             failed_download_attempts: Vec::new(),
             non_faulty_processing_attempts: 0,
             state: BatchState::AwaitingDownload,
+            download_started: None,
             marker: std::marker::PhantomData,
         }
     }
 
+    /// Returns the time since the latest download of the batch was requested.
+    pub fn download_elapsed(&self) -> Option<Duration> {
+        self.download_started.map(|started| started.elapsed())
+    }
+
     /// Gives a list of peers from which this batch has had a failed download or processing
     /// attempt.
     pub fn failed_peers(&self) -> HashSet<PeerId> {
@@ -350,6 +359,7 @@ impl<T: EthSpec, B: BatchConfig> BatchInfo<T, B> {
         match self.state.poison() {
             BatchState::AwaitingDownload => {
                 self.state = BatchState::Downloading(peer, Vec::new(), request_id);
+                self.download_started = Some(Instant::now());
                 Ok(())
             }
             BatchState::Poisoned => unreachable!("Poisoned batch"),
@@ -1,4 +1,5 @@
 use super::batch::{BatchInfo, BatchProcessingResult, BatchState};
+use super::peer_stats::PeerStats;
 use crate::beacon_processor::{ChainSegmentProcessId, WorkEvent as BeaconWorkEvent};
 use crate::sync::{
     manager::Id, network_context::SyncNetworkContext, BatchOperationOutcome, BatchProcessResult,
@@ -77,6 +78,10 @@ pub struct SyncingChain<T: BeaconChainTypes> {
     /// requesting.
     peers: FnvHashMap<PeerId, HashSet<BatchId>>,
 
+    /// The download statistics of the peers in `peers`, used to request batches from the fastest
+    /// and most reliable peers first.
+    peer_stats: FnvHashMap<PeerId, PeerStats>,
+
     /// Starting epoch of the next batch that needs to be downloaded.
     to_be_downloaded: BatchId,
 
@@ -143,6 +148,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             target_head_root,
             batches: BTreeMap::new(),
             peers,
+            peer_stats: FnvHashMap::default(),
             to_be_downloaded: start_epoch,
             processing_target: start_epoch,
             optimistic_start: None,
@@ -170,6 +176,11 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         self.peers.keys().cloned()
     }
 
+    /// The download statistics of a peer, which are empty if it hasn't completed any requests.
+    fn peer_stats(&self, peer_id: &PeerId) -> PeerStats {
+        self.peer_stats.get(peer_id).copied().unwrap_or_default()
+    }
+
     /// Progress in epochs made by the chain
     pub fn validated_epochs(&self) -> u64 {
         self.validated_batches * EPOCHS_PER_BATCH
@@ -182,6 +193,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         peer_id: &PeerId,
         network: &mut SyncNetworkContext<T>,
     ) -> ProcessingResult {
+        self.peer_stats.remove(peer_id);
         if let Some(batch_ids) = self.peers.remove(peer_id) {
             // fail the batches
             for id in batch_ids {
@@ -257,8 +269,14 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                 .get_mut(peer_id)
                 .map(|active_requests| active_requests.remove(&batch_id));
 
+            let slots = batch.to_blocks_by_range_request().count;
+            let elapsed = batch.download_elapsed().unwrap_or_default();
             match batch.download_completed() {
                 Ok(received) => {
+                    self.peer_stats
+                        .entry(*peer_id)
+                        .or_default()
+                        .on_completed(slots, elapsed);
                     let awaiting_batches = batch_id
                         .saturating_sub(self.optimistic_start.unwrap_or(self.processing_target))
                         / EPOCHS_PER_BATCH;
@@ -272,6 +290,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     let (expected_boundary, received_boundary, outcome) = result?;
                     warn!(self.log, "Batch received out of range blocks"; "expected_boundary" => expected_boundary, "received_boundary" => received_boundary,
                         "peer_id" => %peer_id, batch);
+                    self.peer_stats.entry(*peer_id).or_default().on_failed();
 
                     if let BatchOperationOutcome::Failed { blacklist } = outcome {
                         return Err(RemoveChain::ChainFailed {
@@ -843,6 +862,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             if let Some(active_requests) = self.peers.get_mut(peer_id) {
                 active_requests.remove(&batch_id);
             }
+            self.peer_stats.entry(*peer_id).or_default().on_failed();
             if let BatchOperationOutcome::Failed { blacklist } = batch.download_failed(true)? {
                 return Err(RemoveChain::ChainFailed {
                     blacklist,
@@ -874,11 +894,23 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             let mut priorized_peers = self
                 .peers
                 .iter()
-                .map(|(peer, requests)| (failed_peers.contains(peer), requests.len(), *peer))
+                .map(|(peer, requests)| {
+                    (
+                        failed_peers.contains(peer),
+                        requests.len(),
+                        self.peer_stats(peer),
+                        *peer,
+                    )
+                })
                 .collect::<Vec<_>>();
-            // Sort peers prioritizing unrelated peers with less active requests.
-            priorized_peers.sort_unstable();
-            priorized_peers.get(0).map(|&(_, _, peer)| peer)
+            // Sort peers prioritizing unrelated peers with less active requests, then the fastest
+            // and most reliable peers.
+            priorized_peers.sort_unstable_by(|a, b| {
+                (a.0, a.1)
+                    .cmp(&(b.0, b.1))
+                    .then_with(|| a.2.cmp_best_first(&b.2))
+            });
+            priorized_peers.get(0).map(|&(_, _, _, peer)| peer)
         };
 
         if let Some(peer) = new_peer {
@@ -982,7 +1014,8 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
 
         // find the next pending batch and request it from the peer
 
-        // randomize the peers for load balancing
+        // randomize the peers for load balancing, then order them so that the fastest and most
+        // reliable peers are popped first
         let mut rng = rand::thread_rng();
         let mut idle_peers = self
             .peers
@@ -996,6 +1029,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             })
             .collect::<Vec<_>>();
         idle_peers.shuffle(&mut rng);
+        idle_peers.sort_by(|a, b| self.peer_stats(b).cmp_best_first(&self.peer_stats(a)));
 
         // check if we have the batch for our optimistic start. If not, request it first.
         // We wait for this batch before requesting any other batches.
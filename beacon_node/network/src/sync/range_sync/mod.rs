@@ -5,6 +5,7 @@ mod batch;
 mod block_storage;
 mod chain;
 mod chain_collection;
+mod peer_stats;
 mod range;
 mod sync_type;
 
@@ -0,0 +1,101 @@
+//! Tracks how quickly and reliably each peer of a chain serves `BlocksByRange` requests, so that
+//! batches are requested from the best peers first.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+/// The weight given to the latest request in the moving average of a peer's throughput.
+const THROUGHPUT_SMOOTHING: f64 = 0.3;
+
+/// The download statistics of a single peer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PeerStats {
+    /// Moving average of the number of slots per second served by the peer, if any request has
+    /// completed.
+    ///
+    /// Slots are counted rather than blocks, so that ranges of skipped slots aren't penalised.
+    slots_per_second: Option<f64>,
+    /// The number of requests which completed successfully.
+    completed: u32,
+    /// The number of requests which errored, timed out or returned invalid blocks.
+    failed: u32,
+}
+
+impl PeerStats {
+    /// Records a request for `slots` slots which completed after `elapsed`.
+    pub fn on_completed(&mut self, slots: u64, elapsed: Duration) {
+        // Avoid dividing by zero for requests which are answered instantly.
+        let throughput = slots as f64 / elapsed.as_secs_f64().max(0.001);
+        self.slots_per_second = Some(match self.slots_per_second {
+            Some(average) => {
+                average * (1.0 - THROUGHPUT_SMOOTHING) + throughput * THROUGHPUT_SMOOTHING
+            }
+            None => throughput,
+        });
+        self.completed = self.completed.saturating_add(1);
+    }
+
+    /// Records a request which failed.
+    pub fn on_failed(&mut self) {
+        self.failed = self.failed.saturating_add(1);
+    }
+
+    /// The expected number of slots per second from this peer, discounted by its failure rate.
+    ///
+    /// New peers are preferred to all others so that their throughput is measured, whilst peers
+    /// which have only failed come last.
+    fn score(&self) -> f64 {
+        match self.slots_per_second {
+            Some(throughput) => {
+                let success_rate = (self.completed as f64 + 1.0)
+                    / (self.completed as f64 + self.failed as f64 + 1.0);
+                throughput * success_rate
+            }
+            None if self.failed == 0 => f64::INFINITY,
+            None => 0.0,
+        }
+    }
+
+    /// Orders peers from best to worst.
+    pub fn cmp_best_first(&self, other: &Self) -> Ordering {
+        other.score().total_cmp(&self.score())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn best_peers_first() {
+        let mut fast = PeerStats::default();
+        fast.on_completed(64, Duration::from_secs(1));
+
+        let mut slow = PeerStats::default();
+        slow.on_completed(64, Duration::from_secs(8));
+
+        let mut unreliable = PeerStats::default();
+        unreliable.on_completed(64, Duration::from_secs(1));
+        for _ in 0..3 {
+            unreliable.on_failed();
+        }
+
+        let mut failed = PeerStats::default();
+        failed.on_failed();
+
+        let unmeasured = PeerStats::default();
+
+        let mut peers = [
+            ("slow", slow),
+            ("failed", failed),
+            ("unreliable", unreliable),
+            ("fast", fast),
+            ("unmeasured", unmeasured),
+        ];
+        peers.sort_by(|(_, a), (_, b)| a.cmp_best_first(b));
+        assert_eq!(
+            peers.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            vec!["unmeasured", "fast", "unreliable", "slow", "failed"]
+        );
+    }
+}
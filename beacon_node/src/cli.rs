@@ -2,14 +2,25 @@ use clap::{App, Arg};
 use strum::VariantNames;
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
-    App::new("beacon_node")
-        .visible_aliases(&["b", "bn", "beacon"])
-        .version(crate_version!())
-        .author("Sigma Prime <contact@sigmaprime.io>")
-        .setting(clap::AppSettings::ColoredHelp)
-        .about("The primary component which connects to the Ethereum 2.0 P2P network and \
-                downloads, verifies and stores blocks. Provides a HTTP API for querying \
-                the beacon chain and publishing messages to the network.")
+    cli_args(
+        App::new("beacon_node")
+            .visible_aliases(&["b", "bn", "beacon"])
+            .version(crate_version!())
+            .author("Sigma Prime <contact@sigmaprime.io>")
+            .setting(clap::AppSettings::ColoredHelp)
+            .about(
+                "The primary component which connects to the Ethereum 2.0 P2P network and \
+                    downloads, verifies and stores blocks. Provides a HTTP API for querying \
+                    the beacon chain and publishing messages to the network.",
+            ),
+    )
+}
+
+/// Adds all beacon node arguments to `app`.
+///
+/// Used by `cli_app` and by other subcommands which embed a beacon node (e.g. `lighthouse node`).
+pub fn cli_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+    app
         /*
          * Configuration directory locations.
          */
@@ -20,13 +31,26 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Data directory for network keys. Defaults to network/ inside the beacon node \
                        dir.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_NETWORK_DIR")
         )
         .arg(
             Arg::with_name("freezer-dir")
                 .long("freezer-dir")
                 .value_name("DIR")
-                .help("Data directory for the freezer database.")
+                .help("Data directory for the freezer database. If a freezer database exists \
+                       in the default location and not in this directory, it is moved here on \
+                       start-up.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_FREEZER_DIR")
+        )
+        .arg(
+            Arg::with_name("force-unlock")
+                .long("force-unlock")
+                .help("Start even if another Lighthouse process holds the lock on the datadir. \
+                       Running two beacon nodes on the same datadir will corrupt the database, \
+                       so only use this if the lock is known to be held in error (e.g. on a \
+                       network filesystem).")
+                .takes_value(false)
         )
         /*
          * Network parameters.
@@ -52,6 +76,37 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Disables the discovery packet filter. Useful for testing in smaller networks")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("discovery-rate-limit")
+                .long("discovery-rate-limit")
+                .value_name("RATE")
+                .help("The average number of discovery requests per second accepted from other \
+                      nodes, above which requests are dropped. Short bursts are allowed. Only \
+                      applies while the discovery packet filter is enabled. [default: 10]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_DISCOVERY_RATE_LIMIT"),
+        )
+        .arg(
+            Arg::with_name("discovery-ip-rate-limit")
+                .long("discovery-ip-rate-limit")
+                .value_name("RATE")
+                .help("The average number of discovery requests per second accepted from a \
+                      single IP address, above which its requests are dropped. Short bursts are \
+                      allowed. Only applies while the discovery packet filter is enabled. \
+                      [default: 9]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_DISCOVERY_IP_RATE_LIMIT"),
+        )
+        .arg(
+            Arg::with_name("discovery-max-nodes-per-ip")
+                .long("discovery-max-nodes-per-ip")
+                .value_name("COUNT")
+                .help("The number of discovery nodes which may send requests from the same IP \
+                      address, before the address is banned. Only applies while the discovery \
+                      packet filter is enabled. [default: 10]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_DISCOVERY_MAX_NODES_PER_IP"),
+        )
         .arg(
             Arg::with_name("shutdown-after-sync")
                 .long("shutdown-after-sync")
@@ -83,6 +138,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .max_values(2)
                 .default_value("0.0.0.0")
                 .takes_value(true)
+                .env("LIGHTHOUSE_LISTEN_ADDRESS")
         )
         .arg(
             Arg::with_name("port")
@@ -92,7 +148,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                       --discovery-port flag. If listening over both Ipv4 and Ipv6 the --port flag \
                       will apply to the Ipv4 address and --port6 to the Ipv6 address.")
                 .default_value("9000")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_PORT"),
         )
         .arg(
             Arg::with_name("port6")
@@ -101,14 +158,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The TCP/UDP port to listen on over IpV6 when listening over both Ipv4 and \
                       Ipv6. Defaults to 9090 when required.")
                 .default_value("9090")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_PORT6"),
         )
         .arg(
             Arg::with_name("discovery-port")
                 .long("discovery-port")
                 .value_name("PORT")
                 .help("The UDP port that discovery will listen on. Defaults to `port`")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_DISCOVERY_PORT"),
         )
         .arg(
             Arg::with_name("discovery-port6")
@@ -117,14 +176,60 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The UDP port that discovery will listen on over IpV6 if listening over \
                       both Ipv4 and IpV6. Defaults to `port6`")
                 .hidden(true) // TODO: implement dual stack via two sockets in discv5.
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_DISCOVERY_PORT6"),
+        )
+        .arg(
+            Arg::with_name("quic-port")
+                .long("quic-port")
+                .value_name("PORT")
+                .help("Enables QUIC alongside TCP, listening on this UDP port. The port must \
+                      differ from the discovery port. If listening over both Ipv4 and Ipv6 the \
+                      --quic-port flag will apply to the Ipv4 address and --quic-port6 to the \
+                      Ipv6 address.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_QUIC_PORT"),
+        )
+        .arg(
+            Arg::with_name("quic-port6")
+                .long("quic-port6")
+                .value_name("PORT")
+                .help("The UDP port that QUIC will listen on over IpV6 when listening over both \
+                      Ipv4 and Ipv6. QUIC is only enabled over IpV6 if this flag is set. The port \
+                      must differ from the IpV6 discovery port.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_QUIC_PORT6"),
         )
         .arg(
             Arg::with_name("target-peers")
                 .long("target-peers")
                 .help("The target number of peers.")
                 .default_value("80")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_TARGET_PEERS"),
+        )
+        .arg(
+            Arg::with_name("max-peers")
+                .long("max-peers")
+                .value_name("COUNT")
+                .help("The maximum number of peers, above which new inbound connections are \
+                      refused. Must be at least --target-peers. Peers beyond the target are \
+                      pruned periodically. Up to 20% of the target may be dialed in excess of \
+                      the maximum for validator duties. [default: 10% more than the target]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_MAX_PEERS"),
+        )
+        .arg(
+            Arg::with_name("min-outbound-peers-ratio")
+                .long("min-outbound-peers-ratio")
+                .value_name("RATIO")
+                .help("The minimum fraction of --target-peers which should be peers that this \
+                      node dialed, between 0 and 1. Discovery searches for more peers to dial \
+                      when there are fewer, and such peers are kept when pruning. Peers chosen \
+                      by this node are harder for an attacker to control than inbound ones.")
+                .default_value("0.2")
+                .takes_value(true)
+                .env("LIGHTHOUSE_MIN_OUTBOUND_PEERS_RATIO"),
         )
         .arg(
             Arg::with_name("boot-nodes")
@@ -132,7 +237,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .allow_hyphen_values(true)
                 .value_name("ENR/MULTIADDR LIST")
                 .help("One or more comma-delimited base64-encoded ENR's to bootstrap the p2p network. Multiaddr is also supported.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_BOOT_NODES"),
         )
         .arg(
             Arg::with_name("network-load")
@@ -141,14 +247,42 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Lighthouse's network can be tuned for bandwidth/performance. Setting this to a high value, will increase the bandwidth lighthouse uses, increasing the likelihood of redundant information in exchange for faster communication. This can increase profit of validators marginally by receiving messages faster on the network. Lower values decrease bandwidth usage, but makes communication slower which can lead to validator performance reduction. Values are in the range [1,5].")
                 .default_value("3")
                 .set(clap::ArgSettings::Hidden)
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_NETWORK_LOAD"),
         )
         .arg(
             Arg::with_name("disable-upnp")
                 .long("disable-upnp")
-                .help("Disables UPnP support. Setting this will prevent Lighthouse from attempting to automatically establish external port mappings.")
+                .help("Disables UPnP and NAT-PMP support. Setting this will prevent Lighthouse from attempting to automatically establish external port mappings.")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("port-mapping-protocol")
+                .long("port-mapping-protocol")
+                .value_name("PROTOCOL")
+                .help("The protocol used to automatically establish external port mappings on \
+                      the local router. `auto` uses UPnP, or NAT-PMP/PCP if no UPnP router is \
+                      found.")
+                .possible_values(&["auto", "upnp", "nat-pmp"])
+                .default_value("auto")
+                .takes_value(true)
+                .env("LIGHTHOUSE_PORT_MAPPING_PROTOCOL"),
+        )
+        .arg(
+            Arg::with_name("proxy")
+                .long("proxy")
+                .value_name("URL")
+                .help("Dial peers over TCP through a proxy, given as a URL of the form \
+                      socks5://[user:password@]host:port or http://[user:password@]host:port. \
+                      The proxy is also used to download the genesis and checkpoint sync \
+                      states and to reach an eth1 node without JWT authentication, unless it is \
+                      on the local machine or a private network. Discovery traffic is not \
+                      proxied, and QUIC can't be enabled.")
+                .conflicts_with("quic-port")
+                .conflicts_with("quic-port6")
+                .takes_value(true)
+                .env("LIGHTHOUSE_PROXY"),
+        )
         .arg(
             Arg::with_name("private")
                 .long("private")
@@ -161,7 +295,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("PORT")
                 .help("The UDP4 port of the local ENR. Set this only if you are sure other nodes \
                       can connect to your local node on this port over IpV4.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_UDP_PORT"),
         )
         .arg(
             Arg::with_name("enr-udp6-port")
@@ -169,7 +304,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("PORT")
                 .help("The UDP6 port of the local ENR. Set this only if you are sure other nodes \
                       can connect to your local node on this port over IpV6.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_UDP6_PORT"),
         )
         .arg(
             Arg::with_name("enr-tcp-port")
@@ -178,7 +314,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The TCP4 port of the local ENR. Set this only if you are sure other nodes \
                       can connect to your local node on this port over IpV4. The --port flag is \
                       used if this is not set.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_TCP_PORT"),
         )
         .arg(
             Arg::with_name("enr-tcp6-port")
@@ -187,7 +324,40 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The TCP6 port of the local ENR. Set this only if you are sure other nodes \
                       can connect to your local node on this port over IpV6. The --port6 flag is \
                       used if this is not set.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_TCP6_PORT"),
+        )
+        .arg(
+            Arg::with_name("enr-quic-port")
+                .long("enr-quic-port")
+                .value_name("PORT")
+                .help("The QUIC4 port of the local ENR. Set this only if you are sure other nodes \
+                      can connect to your local node on this port over IpV4. The --quic-port \
+                      flag is used if this is not set.")
+                .requires("quic-port")
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_QUIC_PORT"),
+        )
+        .arg(
+            Arg::with_name("enr-quic6-port")
+                .long("enr-quic6-port")
+                .value_name("PORT")
+                .help("The QUIC6 port of the local ENR. Set this only if you are sure other nodes \
+                      can connect to your local node on this port over IpV6. The --quic-port6 \
+                      flag is used if this is not set.")
+                .requires("quic-port6")
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_QUIC6_PORT"),
+        )
+        .arg(
+            Arg::with_name("enr-omit-address")
+                .long("enr-omit-address")
+                .help("Omits the IP addresses and ports from the local ENR, so that other nodes \
+                      can't use it to connect to this node. This is intended for nodes which \
+                      only dial out, e.g. behind a NAT which can't be traversed. The ENR is \
+                      never updated with an external address, and UPnP is not used.")
+                .conflicts_with_all(&["enr-address", "enr-match"])
+                .takes_value(false),
         )
         .arg(
             Arg::with_name("enr-address")
@@ -202,7 +372,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .requires("enr-udp-port")
                 .multiple(true)
                 .max_values(2)
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_ENR_ADDRESS"),
         )
         .arg(
             Arg::with_name("enr-match")
@@ -225,7 +396,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("MULTIADDR")
                 .help("One or more comma-delimited multiaddrs to manually connect to a libp2p peer \
                        without an ENR.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_LIBP2P_ADDRESSES"),
         )
         .arg(
             Arg::with_name("disable-discovery")
@@ -241,12 +413,72 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(false)
                 .hidden(true),
         )
+        .arg(
+            Arg::with_name("gossipsub-score-file")
+                .long("gossipsub-score-file")
+                .value_name("PATH")
+                .help("Path to a TOML, YAML or JSON file which overrides the gossipsub peer \
+                       scoring parameters, e.g. the weights, thresholds and decays of each topic. \
+                       Parameters which aren't in the file are derived from the chain spec as \
+                       usual. Intended for research and private networks: poorly chosen \
+                       parameters may harm message propagation.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_GOSSIPSUB_SCORE_FILE"),
+        )
         .arg(
             Arg::with_name("trusted-peers")
                 .long("trusted-peers")
                 .value_name("TRUSTED_PEERS")
-                .help("One or more comma-delimited trusted peer ids which always have the highest score according to the peer scoring system.")
-                .takes_value(true),
+                .help("One or more comma-delimited trusted peers, given as peer ids, Multiaddrs \
+                      or ENRs. Trusted peers always have the highest score according to the peer \
+                      scoring system, are never disconnected to reduce the peer count and their \
+                      requests are not rate limited. Trusted peers given as a Multiaddr or ENR \
+                      are dialed on startup.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_TRUSTED_PEERS"),
+        )
+        .arg(
+            Arg::with_name("static-peers")
+                .long("static-peers")
+                .value_name("STATIC_PEERS")
+                .help("One or more comma-delimited static peers, given as ENRs or as Multiaddrs \
+                      ending with /p2p/<peer-id>. Static peers are dialed on startup and redialed \
+                      whenever they disconnect, with an increasing delay between failed attempts. \
+                      They are kept connected independently of discovery and the target peer \
+                      count.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_STATIC_PEERS"),
+        )
+        .arg(
+            Arg::with_name("static-peers-file")
+                .long("static-peers-file")
+                .value_name("FILE")
+                .help("A file containing static peers, in the same format as --static-peers but \
+                      with one per line. Empty lines and lines starting with # are ignored. \
+                      Combined with the peers from --static-peers, if set.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_STATIC_PEERS_FILE"),
+        )
+        .arg(
+            Arg::with_name("peer-allowlist")
+                .long("peer-allowlist")
+                .value_name("PEERS")
+                .help("One or more comma-delimited peer ids, IP addresses or IP subnets in CIDR \
+                      notation (e.g. 10.0.0.0/8). If set, only matching peers may connect. \
+                      The list can be updated at runtime with the HTTP API.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_PEER_ALLOWLIST"),
+        )
+        .arg(
+            Arg::with_name("peer-denylist")
+                .long("peer-denylist")
+                .value_name("PEERS")
+                .help("One or more comma-delimited peer ids, IP addresses or IP subnets in CIDR \
+                      notation (e.g. 192.0.2.0/24). Matching peers are refused connections, even \
+                      if they match the --peer-allowlist. The list can be updated at runtime with \
+                      the HTTP API.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_PEER_DENYLIST"),
         )
         .arg(
             Arg::with_name("enable-private-discovery")
@@ -290,7 +522,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("ADDRESS")
                 .help("Set the listen address for the RESTful HTTP API server.")
                 .default_value("127.0.0.1")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_ADDRESS"),
         )
         .arg(
             Arg::with_name("http-port")
@@ -298,17 +531,21 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("PORT")
                 .help("Set the listen TCP port for the RESTful HTTP API server.")
                 .default_value("5052")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_PORT"),
         )
         .arg(
             Arg::with_name("http-allow-origin")
                 .long("http-allow-origin")
                 .value_name("ORIGIN")
                 .help("Set the value of the Access-Control-Allow-Origin response HTTP header. \
+                    Accepts a comma-separated list of origins (e.g., \
+                    http://localhost:3000,https://dashboard.example.com). \
                     Use * to allow any origin (not recommended in production). \
                     If no value is supplied, the CORS allowed origin is set to the listen \
                     address of this server (e.g., http://localhost:5052).")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_ALLOW_ORIGIN"),
         )
         .arg(
             Arg::with_name("http-disable-legacy-spec")
@@ -322,6 +559,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Serve the spec for a specific hard fork on /eth/v1/config/spec. It should \
                        not be necessary to set this flag.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_SPEC_FORK")
         )
         .arg(
             Arg::with_name("http-enable-tls")
@@ -338,6 +576,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The path of the certificate to be used when serving the HTTP API server \
                     over TLS.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_TLS_CERT")
         )
         .arg(
             Arg::with_name("http-tls-key")
@@ -345,6 +584,18 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The path of the private key to be used when serving the HTTP API server \
                     over TLS. Must not be password-protected.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_TLS_KEY")
+        )
+        .arg(
+            Arg::with_name("http-unix-socket")
+                .long("http-unix-socket")
+                .value_name("PATH")
+                .help("Serve the RESTful HTTP API server on a Unix domain socket at this path, \
+                    instead of the TCP listen address and port. The socket is only accessible \
+                    to the user and group running the beacon node. Unix only.")
+                .conflicts_with("http-enable-tls")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_UNIX_SOCKET")
         )
         .arg(
             Arg::with_name("http-allow-sync-stalled")
@@ -353,6 +604,70 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     stalled. This is useful for very small testnets. TESTING ONLY. DO NOT USE ON \
                     MAINNET.")
         )
+        .arg(
+            Arg::with_name("http-enable-heap-profiling")
+                .long("http-enable-heap-profiling")
+                .help("Serves a profile of the heap allocations of the beacon node at \
+                    /lighthouse/debug/heap. Dumping a profile may pause the node briefly, so \
+                    this is disabled by default.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("http-graphql")
+                .long("http-graphql")
+                .help("Serves a GraphQL schema over blocks, validators and attestations at \
+                    /lighthouse/graphql, so that clients can select only the fields they need.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("http-api-token-file")
+                .long("http-api-token-file")
+                .value_name("PATH")
+                .help("Path of the file containing the bearer token required by privileged \
+                    endpoints, such as those which modify the database. A new token is \
+                    generated if the file, or the .secp-sk file beside it, doesn't exist. \
+                    Defaults to api-token.txt in the beacon node data directory.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_API_TOKEN_FILE")
+        )
+        .arg(
+            Arg::with_name("http-rate-limit")
+                .long("http-rate-limit")
+                .value_name("REQUESTS")
+                .help("Limit the number of requests per second to the HTTP API from each IP \
+                    address. The endpoints used by validator clients to perform their duties \
+                    are not limited. Disabled by default.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_RATE_LIMIT")
+        )
+        .arg(
+            Arg::with_name("http-rate-limit-expensive")
+                .long("http-rate-limit-expensive")
+                .value_name("REQUESTS")
+                .help("Limit the number of requests per second to the expensive debug and \
+                    Lighthouse-specific HTTP API endpoints from each IP address. Defaults to a \
+                    tenth of --http-rate-limit.")
+                .requires("http-rate-limit")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_RATE_LIMIT_EXPENSIVE")
+        )
+        .arg(
+            Arg::with_name("http-log-requests")
+                .long("http-log-requests")
+                .help("Log the method, path, status, duration and response size of every request \
+                    to the HTTP API at INFO level, rather than DEBUG.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("http-slow-request-threshold")
+                .long("http-slow-request-threshold")
+                .value_name("MILLISECONDS")
+                .help("Log a warning for each request to the HTTP API which takes at least this \
+                    many milliseconds to serve, to help find the queries which are loading the \
+                    node. Disabled by default.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_SLOW_REQUEST_THRESHOLD")
+        )
         /* Prometheus metrics HTTP server related arguments */
         .arg(
             Arg::with_name("metrics")
@@ -366,7 +681,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("ADDRESS")
                 .help("Set the listen address for the Prometheus metrics HTTP server.")
                 .default_value("127.0.0.1")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_ADDRESS"),
         )
         .arg(
             Arg::with_name("metrics-port")
@@ -374,7 +690,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("PORT")
                 .help("Set the listen TCP port for the Prometheus metrics HTTP server.")
                 .default_value("5054")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PORT"),
         )
         .arg(
             Arg::with_name("metrics-allow-origin")
@@ -384,7 +701,106 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     Use * to allow any origin (not recommended in production). \
                     If no value is supplied, the CORS allowed origin is set to the listen \
                     address of this server (e.g., http://localhost:5054).")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_ALLOW_ORIGIN"),
+        )
+        .arg(
+            Arg::with_name("metrics-tls-cert")
+                .long("metrics-tls-cert")
+                .value_name("PATH")
+                .help("The path of the certificate to be used when serving the metrics HTTP \
+                    server over TLS.")
+                .requires("metrics-tls-key")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_TLS_CERT"),
+        )
+        .arg(
+            Arg::with_name("metrics-tls-key")
+                .long("metrics-tls-key")
+                .value_name("PATH")
+                .help("The path of the private key to be used when serving the metrics HTTP \
+                    server over TLS. Must not be password-protected.")
+                .requires("metrics-tls-cert")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_TLS_KEY"),
+        )
+        .arg(
+            Arg::with_name("metrics-bearer-token-file")
+                .long("metrics-bearer-token-file")
+                .value_name("PATH")
+                .help("The path of a file containing a token which must be provided in an \
+                    `Authorization: Bearer <token>` header to access the metrics HTTP server.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_BEARER_TOKEN_FILE"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway")
+                .long("metrics-pushgateway")
+                .value_name("URL")
+                .help("Push all metrics to the Prometheus Pushgateway at this URL (e.g., \
+                    http://localhost:9091), for nodes which cannot be scraped. Works with or \
+                    without --metrics.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway-interval")
+                .long("metrics-pushgateway-interval")
+                .value_name("SECONDS")
+                .help("The number of seconds between pushes to the Pushgateway. Default: 15")
+                .requires("metrics-pushgateway")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY_INTERVAL"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway-job")
+                .long("metrics-pushgateway-job")
+                .value_name("JOB")
+                .help("The job label of metrics pushed to the Pushgateway. Default: beacon_node")
+                .requires("metrics-pushgateway")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY_JOB"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway-instance")
+                .long("metrics-pushgateway-instance")
+                .value_name("INSTANCE")
+                .help("The instance label of metrics pushed to the Pushgateway. Defaults to the \
+                    hostname of the machine.")
+                .requires("metrics-pushgateway")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY_INSTANCE"),
+        )
+        .arg(
+            Arg::with_name("metrics-buckets")
+                .long("metrics-buckets")
+                .value_name("NAME=B1,B2,...")
+                .help("Overrides the bucket boundaries of the histogram metric with the given \
+                    name, e.g. beacon_block_processing_seconds=0.01,0.05,0.1,0.5. May be \
+                    provided multiple times to override several histograms.")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_BUCKETS"),
+        )
+        .arg(
+            Arg::with_name("metrics-allowlist")
+                .long("metrics-allowlist")
+                .value_name("PATTERNS")
+                .help("A comma-separated list of glob patterns (e.g. beacon_*,store_*). If \
+                    provided, only the metric families whose names match one of the patterns \
+                    are served or pushed.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_ALLOWLIST"),
+        )
+        .arg(
+            Arg::with_name("metrics-denylist")
+                .long("metrics-denylist")
+                .value_name("PATTERNS")
+                .help("A comma-separated list of glob patterns (e.g. gossipsub_*). Metric \
+                    families whose names match any of the patterns are not served or pushed.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_DENYLIST"),
         )
         .arg(
             Arg::with_name("shuffling-cache-size")
@@ -393,6 +809,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             This flag allows the user to set the shuffling cache size in epochs. \
             Shufflings are dependent on validator count and setting this value to a large number can consume a large amount of memory.")
             .takes_value(true)
+            .env("LIGHTHOUSE_SHUFFLING_CACHE_SIZE")
         )
 
         /*
@@ -409,7 +826,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 Note: This will send information to a remote sever which may identify and associate your \
                 validators, IP address and other personal information. Always use a HTTPS connection \
                 and never provide an untrusted URL.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_MONITORING_ENDPOINT"),
         )
         .arg(
             Arg::with_name("monitoring-endpoint-period")
@@ -418,7 +836,43 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Defines how many seconds to wait between each message sent to \
                        the monitoring-endpoint. Default: 60s")
                 .requires("monitoring-endpoint")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_MONITORING_ENDPOINT_PERIOD"),
+        )
+        .arg(
+            Arg::with_name("ntp-server")
+                .long("ntp-server")
+                .value_name("HOST:PORT")
+                .help("The NTP server used to check the system clock for drift. Default: \
+                       pool.ntp.org:123")
+                .takes_value(true)
+                .env("LIGHTHOUSE_NTP_SERVER"),
+        )
+        .arg(
+            Arg::with_name("ntp-drift-threshold")
+                .long("ntp-drift-threshold")
+                .value_name("MILLISECONDS")
+                .help("A warning is logged if the system clock differs from the NTP server by \
+                       more than this many milliseconds. Default: 500")
+                .takes_value(true)
+                .env("LIGHTHOUSE_NTP_DRIFT_THRESHOLD"),
+        )
+        .arg(
+            Arg::with_name("disable-ntp-check")
+                .long("disable-ntp-check")
+                .help("Disables checking the system clock for drift against an NTP server.")
+                .conflicts_with_all(&["ntp-server", "ntp-drift-threshold"])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("watchdog-stall-slots")
+                .long("watchdog-stall-slots")
+                .value_name("SLOTS")
+                .help("Shuts down the beacon node if a critical service (the network or sync service) makes no \
+                       progress for this many slots, so that a process supervisor can restart \
+                       it. Must be at least 2. Disabled by default.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_WATCHDOG_STALL_SLOTS"),
         )
 
         /*
@@ -457,6 +911,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("HTTP-ENDPOINT")
                 .help("Deprecated. Use --eth1-endpoints.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_ETH1_ENDPOINT")
         )
         .arg(
             Arg::with_name("eth1-endpoints")
@@ -468,6 +923,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        flag to connect to an execution node on mainnet and testnets.
                        Defaults to http://127.0.0.1:8545.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_ETH1_ENDPOINTS")
         )
         .arg(
             Arg::with_name("eth1-purge-cache")
@@ -484,6 +940,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     This will reduce the size of responses from the Eth1 endpoint.")
                 .default_value("1000")
                 .takes_value(true)
+                .env("LIGHTHOUSE_ETH1_BLOCKS_PER_LOG_QUERY")
         )
         .arg(
             Arg::with_name("eth1-cache-follow-distance")
@@ -494,15 +951,60 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        compensate for irregular Proof-of-Work block times, but setting it too low \
                        can make the node vulnerable to re-orgs.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_ETH1_CACHE_FOLLOW_DISTANCE")
+        )
+        .arg(
+            Arg::with_name("db-backend")
+                .long("db-backend")
+                .value_name("DATABASE")
+                .help("Set the database backend to be used by the hot and freezer databases. \
+                       An existing database must be converted with \
+                       `lighthouse db convert-backend` before changing it. [default: leveldb]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_DB_BACKEND")
+                .possible_values(store::config::DatabaseBackend::VARIANTS)
         )
         .arg(
             Arg::with_name("slots-per-restore-point")
                 .long("slots-per-restore-point")
                 .value_name("SLOT_COUNT")
                 .help("Specifies how often a freezer DB restore point should be stored. \
-                       Cannot be changed after initialization. \
+                       Changing it re-anchors an existing freezer DB. \
                        [default: 8192 (mainnet) or 64 (minimal)]")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLOTS_PER_RESTORE_POINT")
+        )
+        .arg(
+            Arg::with_name("restore-points-per-snapshot")
+                .long("restore-points-per-snapshot")
+                .value_name("COUNT")
+                .help("Specifies how often a freezer DB restore point should be stored as a full \
+                       state. The restore points in between are stored as compressed diffs \
+                       against the latest full state, which use much less space. Combine with a \
+                       low --slots-per-restore-point for faster historic state queries. \
+                       [default: 1]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_RESTORE_POINTS_PER_SNAPSHOT")
+        )
+        .arg(
+            Arg::with_name("freezer-compression")
+                .long("freezer-compression")
+                .value_name("COMPRESSION")
+                .help("The compression applied to full states in the freezer database. Existing \
+                       states keep their compression unless they are rewritten with \
+                       `lighthouse db recompress`. [default: none]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_FREEZER_COMPRESSION")
+                .possible_values(store::config::FreezerCompression::VARIANTS)
+        )
+        .arg(
+            Arg::with_name("compression-level")
+                .long("compression-level")
+                .value_name("LEVEL")
+                .help("The zlib compression level for freezer states and state diffs, from 0 \
+                       (fastest) to 9 (smallest). [default: 6]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_COMPRESSION_LEVEL")
         )
         .arg(
             Arg::with_name("block-cache-size")
@@ -510,6 +1012,46 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("SIZE")
                 .help("Specifies how many blocks the database should cache in memory [default: 5]")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BLOCK_CACHE_SIZE")
+        )
+        .arg(
+            Arg::with_name("state-cache-size")
+                .long("state-cache-size")
+                .value_name("SIZE")
+                .help("Specifies how many recent states should be cached in memory for block \
+                       processing and block production [default: 4]")
+                .takes_value(true)
+                .env("LIGHTHOUSE_STATE_CACHE_SIZE")
+        )
+        .arg(
+            Arg::with_name("historic-state-replay-limit")
+                .long("historic-state-replay-limit")
+                .value_name("SLOTS")
+                .help("Maximum number of slots of blocks to replay in order to serve a historic \
+                       state which isn't stored, e.g. after checkpoint sync. Set to 0 to never \
+                       replay.")
+                .default_value("2048")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HISTORIC_STATE_REPLAY_LIMIT")
+        )
+        .arg(
+            Arg::with_name("historic-state-cache-size")
+                .long("historic-state-cache-size")
+                .value_name("SIZE")
+                .help("Number of replayed historic states to cache in memory.")
+                .default_value("2")
+                .takes_value(true)
+                .env("LIGHTHOUSE_HISTORIC_STATE_CACHE_SIZE")
+        )
+        .arg(
+            Arg::with_name("low-memory")
+                .long("low-memory")
+                .help("Reduces memory usage for machines with 4 GB of RAM (e.g. ARM boards), by \
+                       shrinking the state, shuffling and block caches and limiting the number \
+                       of workers and the depth of the queues which process network messages. \
+                       Block processing and HTTP API requests may be slower. Cache sizes set \
+                       by other flags (e.g. --state-cache-size) take precedence.")
+                .takes_value(false)
         )
         /*
          * Execution Layer Integration
@@ -531,6 +1073,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        JSON-RPC connection. Uses the same endpoint to populate the \
                        deposit cache.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_EXECUTION_ENDPOINT")
         )
         .arg(
             Arg::with_name("execution-jwt")
@@ -541,6 +1084,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        execution endpoint provided in the --execution-endpoint flag.")
                 .requires("execution-endpoint")
                 .takes_value(true)
+                .env("LIGHTHOUSE_EXECUTION_JWT")
         )
         .arg(
             Arg::with_name("execution-jwt-secret-key")
@@ -552,6 +1096,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .requires("execution-endpoint")
                 .conflicts_with("execution-jwt")
                 .takes_value(true)
+                .env("LIGHTHOUSE_EXECUTION_JWT_SECRET_KEY")
         )
         .arg(
             Arg::with_name("execution-jwt-id")
@@ -563,6 +1108,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        Set to empty by default")
                 .requires("execution-jwt")
                 .takes_value(true)
+                .env("LIGHTHOUSE_EXECUTION_JWT_ID")
         )
         .arg(
             Arg::with_name("execution-jwt-version")
@@ -574,6 +1120,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        Set to empty by default")
                 .requires("execution-jwt")
                 .takes_value(true)
+                .env("LIGHTHOUSE_EXECUTION_JWT_VERSION")
         )
         .arg(
             Arg::with_name("suggested-fee-recipient")
@@ -584,6 +1131,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        client instead of (or in addition to) setting it here.")
                 .requires("execution-endpoint")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SUGGESTED_FEE_RECIPIENT")
         )
         .arg(
             Arg::with_name("builder")
@@ -593,6 +1141,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The URL of a service compatible with the MEV-boost API.")
                 .requires("execution-endpoint")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER")
         )
         .arg(
             Arg::with_name("execution-timeout-multiplier")
@@ -601,6 +1150,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Unsigned integer to multiply the default execution timeouts by.")
                 .default_value("1")
                 .takes_value(true)
+                .env("LIGHTHOUSE_EXECUTION_TIMEOUT_MULTIPLIER")
         )
         /*
          * Database purging and compaction.
@@ -610,6 +1160,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .long("purge-db")
                 .help("If present, the chain database will be deleted. Use with caution.")
         )
+        .arg(
+            Arg::with_name("auto-recover")
+                .long("auto-recover")
+                .help("If present, check the hot database for corruption on start-up and recover \
+                       by moving it aside and checkpoint syncing from the latest usable freezer \
+                       restore point, or from the --checkpoint-sync-url if there is none. \
+                       Dangling hot states are deleted without moving the database. Use \
+                       `lighthouse db verify` to check the freezer database.")
+        )
         .arg(
             Arg::with_name("compact-db")
                 .long("compact-db")
@@ -621,6 +1180,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .long("auto-compact-db")
                 .help("Enable or disable automatic compaction of the database on finalization.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_AUTO_COMPACT_DB")
                 .default_value("true")
         )
         .arg(
@@ -630,8 +1190,22 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        imposes load on the execution client, as payloads need to be \
                        reconstructed and sent to syncing peers.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_PRUNE_PAYLOADS")
                 .default_value("true")
         )
+        .arg(
+            Arg::with_name("prune-blocks-older-than")
+                .long("prune-blocks-older-than")
+                .value_name("EPOCHS")
+                .help("Delete blocks and historic states which are more than this many epochs \
+                       older than the latest finalized epoch, and stop backfilling them after a \
+                       checkpoint sync. Must be at least the weak subjectivity period. Peers which \
+                       request pruned blocks are told that they are unavailable. By default all \
+                       blocks are kept.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_PRUNE_BLOCKS_OLDER_THAN")
+                .conflicts_with("reconstruct-historic-states")
+        )
 
         /*
          * Misc.
@@ -645,6 +1219,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .value_name("GRAFFITI")
                 .takes_value(true)
+                .env("LIGHTHOUSE_GRAFFITI")
         )
         .arg(
             Arg::with_name("max-skip-slots")
@@ -656,6 +1231,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .value_name("NUM_SLOTS")
                 .takes_value(true)
+                .env("LIGHTHOUSE_MAX_SKIP_SLOTS")
         )
         /*
          * Slasher.
@@ -678,6 +1254,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .value_name("PATH")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_DIR")
                 .requires("slasher")
         )
         .arg(
@@ -689,6 +1266,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("SECONDS")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_UPDATE_PERIOD")
         )
         .arg(
             Arg::with_name("slasher-slot-offset")
@@ -701,6 +1279,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("SECONDS")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_SLOT_OFFSET")
         )
         .arg(
             Arg::with_name("slasher-history-length")
@@ -712,6 +1291,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("EPOCHS")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_HISTORY_LENGTH")
         )
         .arg(
             Arg::with_name("slasher-max-db-size")
@@ -722,6 +1302,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("GIGABYTES")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_MAX_DB_SIZE")
         )
         .arg(
             Arg::with_name("slasher-att-cache-size")
@@ -730,6 +1311,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("COUNT")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_ATT_CACHE_SIZE")
         )
         .arg(
             Arg::with_name("slasher-chunk-size")
@@ -740,6 +1322,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("EPOCHS")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_CHUNK_SIZE")
         )
         .arg(
             Arg::with_name("slasher-validator-chunk-size")
@@ -750,6 +1333,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("NUM_VALIDATORS")
                 .requires("slasher")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_VALIDATOR_CHUNK_SIZE")
         )
         .arg(
             Arg::with_name("slasher-broadcast")
@@ -764,6 +1348,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("DATABASE")
                 .help("Set the database backend to be used by the slasher.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SLASHER_BACKEND")
                 .possible_values(slasher::DatabaseBackend::VARIANTS)
                 .requires("slasher")
         )
@@ -778,6 +1363,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .value_name("WSS_CHECKPOINT")
                 .takes_value(true)
+                .env("LIGHTHOUSE_WSS_CHECKPOINT")
         )
         .arg(
             Arg::with_name("checkpoint-state")
@@ -786,6 +1372,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        --checkpoint-block. Using --checkpoint-sync-url instead is recommended.")
                 .value_name("STATE_SSZ")
                 .takes_value(true)
+                .env("LIGHTHOUSE_CHECKPOINT_STATE")
                 .requires("checkpoint-block")
         )
         .arg(
@@ -795,6 +1382,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        --checkpoint-state. Using --checkpoint-sync-url instead is recommended.")
                 .value_name("BLOCK_SSZ")
                 .takes_value(true)
+                .env("LIGHTHOUSE_CHECKPOINT_BLOCK")
                 .requires("checkpoint-state")
         )
         .arg(
@@ -803,6 +1391,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Set the remote beacon node HTTP endpoint to use for checkpoint sync.")
                 .value_name("BEACON_NODE")
                 .takes_value(true)
+                .env("LIGHTHOUSE_CHECKPOINT_SYNC_URL")
                 .conflicts_with("checkpoint-state")
         )
         .arg(
@@ -811,8 +1400,29 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Set the timeout for checkpoint sync calls to remote beacon node HTTP endpoint.")
                 .value_name("SECONDS")
                 .takes_value(true)
+                .env("LIGHTHOUSE_CHECKPOINT_SYNC_URL_TIMEOUT")
                 .default_value("60")
         )
+        .arg(
+            Arg::with_name("genesis-state-url")
+                .long("genesis-state-url")
+                .help("URL of the SSZ genesis state, for networks which don't include their \
+                       genesis state. It is downloaded when the node first starts, and must have \
+                       the root given by --genesis-state-root.")
+                .value_name("URL")
+                .takes_value(true)
+                .env("LIGHTHOUSE_GENESIS_STATE_URL")
+                .requires("genesis-state-root")
+        )
+        .arg(
+            Arg::with_name("genesis-state-root")
+                .long("genesis-state-root")
+                .help("The root of the genesis state at --genesis-state-url.")
+                .value_name("STATE_ROOT")
+                .takes_value(true)
+                .env("LIGHTHOUSE_GENESIS_STATE_ROOT")
+                .requires("genesis-state-url")
+        )
         .arg(
             Arg::with_name("reconstruct-historic-states")
                 .long("reconstruct-historic-states")
@@ -835,6 +1445,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         logging.")
                 .value_name("PUBKEYS")
                 .takes_value(true)
+                .env("LIGHTHOUSE_VALIDATOR_MONITOR_PUBKEYS")
         )
         .arg(
             Arg::with_name("validator-monitor-file")
@@ -843,6 +1454,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     contained within a file at the given path.")
                 .value_name("PATH")
                 .takes_value(true)
+                .env("LIGHTHOUSE_VALIDATOR_MONITOR_FILE")
         )
         .arg(
             Arg::with_name("validator-monitor-individual-tracking-threshold")
@@ -854,6 +1466,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     high log volume when using many validators. Defaults to 64.")
                 .value_name("INTEGER")
                 .takes_value(true)
+                .env("LIGHTHOUSE_VALIDATOR_MONITOR_INDIVIDUAL_TRACKING_THRESHOLD")
         )
         .arg(
             Arg::with_name("disable-lock-timeouts")
@@ -873,6 +1486,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("proposer-reorg-threshold")
                 .long("proposer-reorg-threshold")
                 .value_name("PERCENT")
+                .env("LIGHTHOUSE_PROPOSER_REORG_THRESHOLD")
                 .help("Percentage of vote weight below which to attempt a proposer reorg. \
                        Default: 20%")
                 .conflicts_with("disable-proposer-reorgs")
@@ -881,6 +1495,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("proposer-reorg-epochs-since-finalization")
                 .long("proposer-reorg-epochs-since-finalization")
                 .value_name("EPOCHS")
+                .env("LIGHTHOUSE_PROPOSER_REORG_EPOCHS_SINCE_FINALIZATION")
                 .help("Maximum number of epochs since finalization at which proposer reorgs are \
                        allowed. Default: 2")
                 .conflicts_with("disable-proposer-reorgs")
@@ -889,6 +1504,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("proposer-reorg-cutoff")
                 .long("proposer-reorg-cutoff")
                 .value_name("MILLISECONDS")
+                .env("LIGHTHOUSE_PROPOSER_REORG_CUTOFF")
                 .help("Maximum delay after the start of the slot at which to propose a reorging \
                        block. Lower values can prevent failed reorgs by ensuring the block has \
                        ample time to propagate and be processed by the network. The default is \
@@ -899,6 +1515,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("proposer-reorg-disallowed-offsets")
                 .long("proposer-reorg-disallowed-offsets")
                 .value_name("N1,N2,...")
+                .env("LIGHTHOUSE_PROPOSER_REORG_DISALLOWED_OFFSETS")
                 .help("Comma-separated list of integer offsets which can be used to avoid \
                        proposing reorging blocks at certain slots. An offset of N means that \
                        reorging proposals will not be attempted at any slot such that \
@@ -916,6 +1533,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        improve their payload after the first call, and high values are useful \
                        for ensuring the EL is given ample notice. Default: 1/3 of a slot.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_PREPARE_PAYLOAD_LOOKAHEAD")
         )
         .arg(
             Arg::with_name("always-prepare-payload")
@@ -934,6 +1552,29 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        to 0, however you risk proposing atop the wrong parent block.")
                 .default_value("250")
                 .takes_value(true)
+                .env("LIGHTHOUSE_FORK_CHOICE_BEFORE_PROPOSAL_TIMEOUT")
+        )
+        .arg(
+            Arg::with_name("fork-choice-persist-interval")
+                .long("fork-choice-persist-interval")
+                .value_name("EPOCHS")
+                .help("The number of epochs between writes of fork choice to disk. Fork choice is \
+                       also written on re-orgs and at shutdown. Higher values reduce disk I/O, \
+                       but more blocks may need to be re-imported after an unclean shutdown.")
+                .default_value("1")
+                .takes_value(true)
+                .env("LIGHTHOUSE_FORK_CHOICE_PERSIST_INTERVAL")
+        )
+        .arg(
+            Arg::with_name("proto-array-prune-threshold")
+                .long("proto-array-prune-threshold")
+                .value_name("NODES")
+                .help("The number of finalized blocks which must accumulate in fork choice before \
+                       they are pruned. Lower values reduce memory usage at the cost of pruning \
+                       more often.")
+                .default_value("256")
+                .takes_value(true)
+                .env("LIGHTHOUSE_PROTO_ARRAY_PRUNE_THRESHOLD")
         )
         .arg(
             Arg::with_name("paranoid-block-proposal")
@@ -953,6 +1594,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         and will use the local execution engine for payload construction.")
                 .default_value("3")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER_FALLBACK_SKIPS")
         )
         .arg(
             Arg::with_name("builder-fallback-skips-per-epoch")
@@ -963,6 +1605,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         payload construction.")
                 .default_value("8")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER_FALLBACK_SKIPS_PER_EPOCH")
         )
         .arg(
             Arg::with_name("builder-fallback-epochs-since-finalization")
@@ -976,6 +1619,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         is set to propose.")
                 .default_value("3")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER_FALLBACK_EPOCHS_SINCE_FINALIZATION")
         )
         .arg(
             Arg::with_name("builder-fallback-disable-checks")
@@ -999,6 +1643,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                      0.25 ETH.")
                 .default_value("0")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER_PROFIT_THRESHOLD")
         )
         .arg(
             Arg::with_name("builder-user-agent")
@@ -1008,6 +1653,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        default is Lighthouse's version string.")
                 .requires("builder")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER_USER_AGENT")
         )
         .arg(
             Arg::with_name("count-unrealized")
@@ -1015,6 +1661,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .hidden(true)
                 .help("This flag is deprecated and has no effect.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_COUNT_UNREALIZED")
                 .default_value("true")
         )
         .arg(
@@ -1023,6 +1670,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .hidden(true)
                 .help("This flag is deprecated and has no effect.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_COUNT_UNREALIZED_FULL")
                 .default_value("false")
         )
         .arg(
@@ -1052,7 +1700,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("light-client-server")
                 .long("light-client-server")
                 .help("Act as a full node supporting light clients on the p2p network \
-                       [experimental]")
+                       and the HTTP API [experimental]")
                 .takes_value(false)
         )
         .arg(
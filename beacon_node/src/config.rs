@@ -3,7 +3,8 @@ use beacon_chain::chain_config::{
     DEFAULT_RE_ORG_MAX_EPOCHS_SINCE_FINALIZATION, DEFAULT_RE_ORG_THRESHOLD,
 };
 use clap::ArgMatches;
-use clap_utils::flags::DISABLE_MALLOC_TUNING_FLAG;
+use clap_utils::flags::{CONFIG_FILE_FLAG, DISABLE_MALLOC_TUNING_FLAG};
+use clap_utils::{cli_value_of, parse_flag, use_cli_value};
 use client::{ClientConfig, ClientGenesis};
 use directory::{DEFAULT_BEACON_NODE_DIR, DEFAULT_NETWORK_DIR, DEFAULT_ROOT_DIR};
 use environment::RuntimeContext;
@@ -11,7 +12,12 @@ use execution_layer::DEFAULT_JWT_FILE;
 use genesis::Eth1Endpoint;
 use http_api::TlsConfig;
 use lighthouse_network::ListenAddress;
-use lighthouse_network::{multiaddr::Protocol, Enr, Multiaddr, NetworkConfig, PeerIdSerialized};
+use lighthouse_network::{
+    discovery_rate_limiter, multiaddr::Protocol, AccessListEntry, Enr, EnrExt, Multiaddr,
+    NetworkConfig, PeerId, PeerIdSerialized, PeerScoreOverrides, ProxyConfig,
+    DEFAULT_DISCOVERY_IP_RATE_LIMIT, DEFAULT_DISCOVERY_RATE_LIMIT,
+};
+use lockfile::{Lockfile, LockfileError};
 use sensitive_url::SensitiveUrl;
 use slog::{info, warn, Logger};
 use std::cmp;
@@ -23,7 +29,19 @@ use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
-use types::{Checkpoint, Epoch, EthSpec, Hash256, PublicKeyBytes, GRAFFITI_BYTES_LEN};
+use types::{
+    Address, Checkpoint, Epoch, EthSpec, Graffiti, Hash256, PublicKeyBytes, GRAFFITI_BYTES_LEN,
+};
+
+/// The settings applied by `--low-memory`.
+const LOW_MEMORY_SNAPSHOT_CACHE_SIZE: usize = 1;
+const LOW_MEMORY_SHUFFLING_CACHE_SIZE: usize = 4;
+const LOW_MEMORY_BLOCK_CACHE_SIZE: usize = 1;
+const LOW_MEMORY_MAX_WORKERS: usize = 2;
+const LOW_MEMORY_QUEUE_LENGTH_DIVISOR: usize = 4;
+
+/// The file in the beacon node directory which caches the state from `--genesis-state-url`.
+const GENESIS_STATE_FILE: &str = "genesis.ssz";
 
 /// Gets the fully-initialized global client.
 ///
@@ -32,6 +50,9 @@ use types::{Checkpoint, Epoch, EthSpec, Hash256, PublicKeyBytes, GRAFFITI_BYTES_
 /// The output of this function depends primarily upon the given `cli_args`, however it's behaviour
 /// may be influenced by other external services like the contents of the file system or the
 /// response of some remote server.
+///
+/// If a `--config-file` is provided it is used as the starting point, with any flags explicitly
+/// supplied in `cli_args` taking precedence over the values in the file.
 pub fn get_config<E: EthSpec>(
     cli_args: &ArgMatches,
     context: &RuntimeContext<E>,
@@ -39,13 +60,19 @@ pub fn get_config<E: EthSpec>(
     let spec = &context.eth2_config.spec;
     let log = context.log();
 
-    let mut client_config = ClientConfig::default();
+    let mut client_config = match clap_utils::parse_optional::<PathBuf>(cli_args, CONFIG_FILE_FLAG)?
+    {
+        Some(path) => clap_utils::load_config_file(&path, "beacon_node")?,
+        None => ClientConfig::default(),
+    };
 
     // Update the client's data directory
-    client_config.set_data_dir(get_data_dir(cli_args));
+    if use_cli_value(cli_args, "datadir") {
+        client_config.set_data_dir(get_data_dir(cli_args));
+    }
 
     // If necessary, remove any existing database and configuration
-    if client_config.data_dir().exists() && cli_args.is_present("purge-db") {
+    if client_config.data_dir().exists() && parse_flag(cli_args, "purge-db")? {
         // Remove the chain_db.
         let chain_db = client_config.get_db_path();
         if chain_db.exists() {
@@ -79,31 +106,63 @@ pub fn get_config<E: EthSpec>(
 
     set_network_config(&mut client_config.network, cli_args, &data_dir_ref, log)?;
 
+    // The HTTP clients use the same proxy. As for libp2p, domain names are resolved by a SOCKS5
+    // proxy rather than locally.
+    if let Some(proxy) = cli_args.value_of("proxy") {
+        let url = match proxy.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("socks5") => {
+                format!("socks5h://{}", rest)
+            }
+            _ => proxy.to_string(),
+        };
+        let url = SensitiveUrl::parse(&url).map_err(|e| format!("Invalid proxy URL: {:?}", e))?;
+        client_config.http_proxy = Some(url.clone());
+        client_config.eth1.http_proxy = Some(url);
+    }
+
+    // The HTTP API serves light client updates when the light client server is enabled.
+    client_config.chain.enable_light_client_server =
+        client_config.network.enable_light_client_server;
+
     /*
      * Staking flag
      * Note: the config values set here can be overwritten by other more specific cli params
      */
 
-    if cli_args.is_present("staking") {
+    if parse_flag(cli_args, "staking")? {
         client_config.http_api.enabled = true;
         client_config.sync_eth1_chain = true;
     }
 
+    /*
+     * Low memory profile
+     * Note: the cache sizes set here can be overwritten by their specific cli params
+     */
+
+    if parse_flag(cli_args, "low-memory")? {
+        client_config.chain.snapshot_cache_size = LOW_MEMORY_SNAPSHOT_CACHE_SIZE;
+        client_config.chain.shuffling_cache_size = LOW_MEMORY_SHUFFLING_CACHE_SIZE;
+        client_config.store.block_cache_size = LOW_MEMORY_BLOCK_CACHE_SIZE;
+        client_config.network.beacon_processor_max_workers = Some(LOW_MEMORY_MAX_WORKERS);
+        client_config.network.beacon_processor_queue_length_divisor =
+            LOW_MEMORY_QUEUE_LENGTH_DIVISOR;
+    }
+
     /*
      * Http API server
      */
 
-    if cli_args.is_present("http") {
+    if parse_flag(cli_args, "http")? {
         client_config.http_api.enabled = true;
     }
 
-    if let Some(address) = cli_args.value_of("http-address") {
+    if let Some(address) = cli_value_of(cli_args, "http-address") {
         client_config.http_api.listen_addr = address
             .parse::<IpAddr>()
             .map_err(|_| "http-address is not a valid IP address.")?;
     }
 
-    if let Some(port) = cli_args.value_of("http-port") {
+    if let Some(port) = cli_value_of(cli_args, "http-port") {
         client_config.http_api.listen_port = port
             .parse::<u16>()
             .map_err(|_| "http-port is not a valid u16.")?;
@@ -118,7 +177,7 @@ pub fn get_config<E: EthSpec>(
         client_config.http_api.allow_origin = Some(allow_origin.to_string());
     }
 
-    if cli_args.is_present("http-disable-legacy-spec") {
+    if parse_flag(cli_args, "http-disable-legacy-spec")? {
         warn!(
             log,
             "The flag --http-disable-legacy-spec is deprecated and will be removed"
@@ -129,7 +188,7 @@ pub fn get_config<E: EthSpec>(
         client_config.http_api.spec_fork_name = Some(fork_name);
     }
 
-    if cli_args.is_present("http-enable-tls") {
+    if parse_flag(cli_args, "http-enable-tls")? {
         client_config.http_api.tls_config = Some(TlsConfig {
             cert: cli_args
                 .value_of("http-tls-cert")
@@ -144,10 +203,52 @@ pub fn get_config<E: EthSpec>(
         });
     }
 
-    if cli_args.is_present("http-allow-sync-stalled") {
+    if let Some(path) = clap_utils::parse_optional(cli_args, "http-unix-socket")? {
+        client_config.http_api.unix_socket = Some(path);
+    }
+
+    client_config.http_api.api_token_path = Some(
+        clap_utils::parse_optional(cli_args, "http-api-token-file")?
+            .unwrap_or_else(|| client_config.data_dir().join(http_api::API_TOKEN_FILENAME)),
+    );
+
+    if let Some(requests_per_second) =
+        clap_utils::parse_optional::<u64>(cli_args, "http-rate-limit")?
+    {
+        let expensive_requests_per_second =
+            clap_utils::parse_optional(cli_args, "http-rate-limit-expensive")?
+                .unwrap_or(std::cmp::max(requests_per_second / 10, 1));
+        if requests_per_second == 0 || expensive_requests_per_second == 0 {
+            return Err("HTTP rate limits must be greater than zero".into());
+        }
+        client_config.http_api.rate_limit = Some(http_api::RateLimitConfig {
+            requests_per_second,
+            expensive_requests_per_second,
+        });
+    }
+
+    if parse_flag(cli_args, "http-allow-sync-stalled")? {
         client_config.http_api.allow_sync_stalled = true;
     }
 
+    if parse_flag(cli_args, "http-enable-heap-profiling")? {
+        client_config.http_api.enable_heap_profiling = true;
+    }
+
+    if parse_flag(cli_args, "http-graphql")? {
+        client_config.http_api.enable_graphql = true;
+    }
+
+    if parse_flag(cli_args, "http-log-requests")? {
+        client_config.http_api.log_requests = true;
+    }
+
+    if let Some(threshold_ms) =
+        clap_utils::parse_optional::<u64>(cli_args, "http-slow-request-threshold")?
+    {
+        client_config.http_api.slow_request_threshold = Some(Duration::from_millis(threshold_ms));
+    }
+
     if let Some(cache_size) = clap_utils::parse_optional(cli_args, "shuffling-cache-size")? {
         client_config.chain.shuffling_cache_size = cache_size;
     }
@@ -156,17 +257,17 @@ pub fn get_config<E: EthSpec>(
      * Prometheus metrics HTTP server
      */
 
-    if cli_args.is_present("metrics") {
+    if parse_flag(cli_args, "metrics")? {
         client_config.http_metrics.enabled = true;
     }
 
-    if let Some(address) = cli_args.value_of("metrics-address") {
+    if let Some(address) = cli_value_of(cli_args, "metrics-address") {
         client_config.http_metrics.listen_addr = address
             .parse::<IpAddr>()
             .map_err(|_| "metrics-address is not a valid IP address.")?;
     }
 
-    if let Some(port) = cli_args.value_of("metrics-port") {
+    if let Some(port) = cli_value_of(cli_args, "metrics-port") {
         client_config.http_metrics.listen_port = port
             .parse::<u16>()
             .map_err(|_| "metrics-port is not a valid u16.")?;
@@ -181,6 +282,68 @@ pub fn get_config<E: EthSpec>(
         client_config.http_metrics.allow_origin = Some(allow_origin.to_string());
     }
 
+    if let Some(cert) = clap_utils::parse_optional(cli_args, "metrics-tls-cert")? {
+        client_config.http_metrics.tls_config = Some(http_metrics::TlsConfig {
+            cert,
+            key: clap_utils::parse_required(cli_args, "metrics-tls-key")?,
+        });
+    }
+
+    if let Some(path) =
+        clap_utils::parse_optional::<PathBuf>(cli_args, "metrics-bearer-token-file")?
+    {
+        if !path.exists() {
+            return Err(format!(
+                "metrics-bearer-token-file {:?} does not exist",
+                path
+            ));
+        }
+        client_config.http_metrics.bearer_token_path = Some(path);
+    }
+
+    if let Some(url) = cli_args.value_of("metrics-pushgateway") {
+        let interval_secs = clap_utils::parse_optional(cli_args, "metrics-pushgateway-interval")?
+            .unwrap_or(pushgateway::DEFAULT_INTERVAL_SECS);
+        if interval_secs == 0 {
+            return Err("metrics-pushgateway-interval must be greater than zero".into());
+        }
+        client_config.http_metrics.pushgateway = Some(pushgateway::Config {
+            url: SensitiveUrl::parse(url)
+                .map_err(|e| format!("Invalid metrics-pushgateway URL: {:?}", e))?,
+            interval: Duration::from_secs(interval_secs),
+            job: clap_utils::parse_optional(cli_args, "metrics-pushgateway-job")?
+                .unwrap_or_else(|| "beacon_node".to_string()),
+            instance: clap_utils::parse_optional(cli_args, "metrics-pushgateway-instance")?
+                .unwrap_or_else(pushgateway::default_instance),
+        });
+    }
+
+    if let Some(buckets) = cli_args.values_of("metrics-buckets") {
+        for buckets in buckets {
+            let (name, buckets) = lighthouse_metrics::parse_histogram_buckets(buckets)?;
+            client_config
+                .http_metrics
+                .histogram_buckets
+                .insert(name, buckets);
+        }
+    }
+
+    if let Some(allowlist) = cli_args.value_of("metrics-allowlist") {
+        client_config.http_metrics.allowlist = allowlist
+            .split(',')
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+    }
+
+    if let Some(denylist) = cli_args.value_of("metrics-denylist") {
+        client_config.http_metrics.denylist = denylist
+            .split(',')
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+    }
+
     /*
      * Explorer metrics
      */
@@ -196,9 +359,31 @@ pub fn get_config<E: EthSpec>(
         });
     }
 
+    /*
+     * Clock drift
+     */
+    if parse_flag(cli_args, "disable-ntp-check")? {
+        client_config.clock_drift = None;
+    } else if cli_args.is_present("ntp-server") || cli_args.is_present("ntp-drift-threshold") {
+        let clock_drift = client_config.clock_drift.get_or_insert_with(<_>::default);
+        if let Some(server) = clap_utils::parse_optional(cli_args, "ntp-server")? {
+            clock_drift.server = server;
+        }
+        if let Some(threshold) = clap_utils::parse_optional(cli_args, "ntp-drift-threshold")? {
+            clock_drift.threshold = Duration::from_millis(threshold);
+        }
+    }
+
+    if let Some(slots) = clap_utils::parse_optional(cli_args, "watchdog-stall-slots")? {
+        if slots < 2 {
+            return Err("--watchdog-stall-slots must be at least 2".to_string());
+        }
+        client_config.watchdog_stall_slots = Some(slots);
+    }
+
     // Log a warning indicating an open HTTP server if it wasn't specified explicitly
     // (e.g. using the --staking flag).
-    if cli_args.is_present("staking") {
+    if parse_flag(cli_args, "staking")? {
         warn!(
             log,
             "Running HTTP server on port {}", client_config.http_api.listen_port
@@ -206,7 +391,7 @@ pub fn get_config<E: EthSpec>(
     }
 
     // Do not scrape for malloc metrics if we've disabled tuning malloc as it may cause panics.
-    if cli_args.is_present(DISABLE_MALLOC_TUNING_FLAG) {
+    if parse_flag(cli_args, DISABLE_MALLOC_TUNING_FLAG)? {
         client_config.http_metrics.allocator_metrics_enabled = false;
     }
 
@@ -217,14 +402,14 @@ pub fn get_config<E: EthSpec>(
     // When present, use an eth1 backend that generates deterministic junk.
     //
     // Useful for running testnets without the overhead of a deposit contract.
-    if cli_args.is_present("dummy-eth1") {
+    if parse_flag(cli_args, "dummy-eth1")? {
         client_config.dummy_eth1_backend = true;
     }
 
     // When present, attempt to sync to an eth1 node.
     //
     // Required for block production.
-    if cli_args.is_present("eth1") {
+    if parse_flag(cli_args, "eth1")? {
         client_config.sync_eth1_chain = true;
     }
 
@@ -247,13 +432,13 @@ pub fn get_config<E: EthSpec>(
         client_config.eth1.endpoint = Eth1Endpoint::NoAuth(endpoint);
     }
 
-    if let Some(val) = cli_args.value_of("eth1-blocks-per-log-query") {
+    if let Some(val) = cli_value_of(cli_args, "eth1-blocks-per-log-query") {
         client_config.eth1.blocks_per_log_query = val
             .parse()
             .map_err(|_| "eth1-blocks-per-log-query is not a valid integer".to_string())?;
     }
 
-    if cli_args.is_present("eth1-purge-cache") {
+    if parse_flag(cli_args, "eth1-purge-cache")? {
         client_config.eth1.purge_cache = true;
     }
 
@@ -263,7 +448,7 @@ pub fn get_config<E: EthSpec>(
         client_config.eth1.cache_follow_distance = Some(follow_distance);
     }
 
-    if cli_args.is_present("merge") {
+    if parse_flag(cli_args, "merge")? {
         if cli_args.is_present("execution-endpoint") {
             warn!(
                 log,
@@ -373,9 +558,36 @@ pub fn get_config<E: EthSpec>(
         client_config.freezer_db_path = Some(PathBuf::from(freezer_dir));
     }
 
-    let (sprp, sprp_explicit) = get_slots_per_restore_point::<E>(cli_args)?;
-    client_config.store.slots_per_restore_point = sprp;
-    client_config.store.slots_per_restore_point_set_explicitly = sprp_explicit;
+    if let Some(backend) = clap_utils::parse_optional(cli_args, "db-backend")? {
+        client_config.store.backend = backend;
+    }
+
+    if use_cli_value(cli_args, "slots-per-restore-point") {
+        let (sprp, sprp_explicit) = get_slots_per_restore_point::<E>(cli_args)?;
+        client_config.store.slots_per_restore_point = sprp;
+        client_config.store.slots_per_restore_point_set_explicitly = sprp_explicit;
+    }
+
+    if let Some(restore_points_per_snapshot) =
+        clap_utils::parse_optional(cli_args, "restore-points-per-snapshot")?
+    {
+        if restore_points_per_snapshot == 0 {
+            return Err("restore-points-per-snapshot must be at least 1".to_string());
+        }
+        client_config.store.restore_points_per_snapshot = restore_points_per_snapshot;
+    }
+
+    if let Some(freezer_compression) = clap_utils::parse_optional(cli_args, "freezer-compression")?
+    {
+        client_config.store.freezer_compression = freezer_compression;
+    }
+
+    if let Some(compression_level) = clap_utils::parse_optional(cli_args, "compression-level")? {
+        if compression_level > 9 {
+            return Err("compression-level must be between 0 and 9".to_string());
+        }
+        client_config.store.compression_level = compression_level;
+    }
 
     if let Some(block_cache_size) = cli_args.value_of("block-cache-size") {
         client_config.store.block_cache_size = block_cache_size
@@ -383,17 +595,44 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
-    client_config.store.compact_on_init = cli_args.is_present("compact-db");
-    if let Some(compact_on_prune) = cli_args.value_of("auto-compact-db") {
+    if let Some(state_cache_size) = clap_utils::parse_optional(cli_args, "state-cache-size")? {
+        client_config.chain.snapshot_cache_size = state_cache_size;
+    }
+
+    if let Some(limit) = clap_utils::parse_optional(cli_args, "historic-state-replay-limit")? {
+        client_config.store.historic_state_replay_limit = limit;
+    }
+    if let Some(size) = clap_utils::parse_optional(cli_args, "historic-state-cache-size")? {
+        client_config.store.historic_state_cache_size = size;
+    }
+
+    if use_cli_value(cli_args, "auto-recover") {
+        client_config.auto_recover = parse_flag(cli_args, "auto-recover")?;
+    }
+
+    if use_cli_value(cli_args, "compact-db") {
+        client_config.store.compact_on_init = parse_flag(cli_args, "compact-db")?;
+    }
+    if let Some(compact_on_prune) = cli_value_of(cli_args, "auto-compact-db") {
         client_config.store.compact_on_prune = compact_on_prune
             .parse()
             .map_err(|_| "auto-compact-db takes a boolean".to_string())?;
     }
 
-    if let Some(prune_payloads) = clap_utils::parse_optional(cli_args, "prune-payloads")? {
+    if let Some(prune_payloads) = cli_value_of(cli_args, "prune-payloads")
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| "prune-payloads takes a boolean".to_string())
+        })
+        .transpose()?
+    {
         client_config.store.prune_payloads = prune_payloads;
     }
 
+    client_config.store.prune_blocks_older_than =
+        clap_utils::parse_optional(cli_args, "prune-blocks-older-than")?;
+
     /*
      * Zero-ports
      *
@@ -403,7 +642,7 @@ pub fn get_config<E: EthSpec>(
      * from lighthouse.
      * Discovery address is set to localhost by default.
      */
-    if cli_args.is_present("zero-ports") {
+    if parse_flag(cli_args, "zero-ports")? {
         client_config.http_api.listen_port = 0;
         client_config.http_metrics.listen_port = 0;
     }
@@ -437,18 +676,51 @@ pub fn get_config<E: EthSpec>(
     // Only append network config bootnodes if discovery is not disabled
     if !client_config.network.disable_discovery {
         if let Some(boot_nodes) = &eth2_network_config.boot_enr {
-            client_config
-                .network
-                .boot_nodes_enr
-                .extend_from_slice(boot_nodes)
+            // The boot nodes may already be present if the config was loaded from a file.
+            for boot_node in boot_nodes {
+                if !client_config.network.boot_nodes_enr.contains(boot_node) {
+                    client_config.network.boot_nodes_enr.push(boot_node.clone());
+                }
+            }
         }
     }
-    client_config.chain.checkpoint_sync_url_timeout =
-        clap_utils::parse_required::<u64>(cli_args, "checkpoint-sync-url-timeout")?;
+    if let Some(timeout) = cli_value_of(cli_args, "checkpoint-sync-url-timeout") {
+        client_config.chain.checkpoint_sync_url_timeout = timeout
+            .parse()
+            .map_err(|_| "checkpoint-sync-url-timeout is not a valid integer".to_string())?;
+    }
 
-    client_config.genesis = if let Some(genesis_state_bytes) =
+    let genesis_state_url = clap_utils::parse_optional::<String>(cli_args, "genesis-state-url")?;
+    let genesis_state_bytes = if let Some(url) = genesis_state_url {
+        if eth2_network_config.genesis_state_bytes.is_some() {
+            return Err(
+                "The genesis state of this network is already known, remove --genesis-state-url"
+                    .to_string(),
+            );
+        }
+        let state_root = clap_utils::parse_required(cli_args, "genesis-state-root")?;
+        let cache_file = client_config.data_dir().join(GENESIS_STATE_FILE);
+        info!(
+            log,
+            "Loading genesis state";
+            "url" => &url,
+            "state_root" => ?state_root,
+        );
+        let proxy = client_config
+            .http_proxy
+            .as_ref()
+            .map(|proxy| proxy.full.as_str());
+        Some(eth2_network_config.fetch_genesis_state_bytes::<E>(
+            &url,
+            state_root,
+            &cache_file,
+            proxy,
+        )?)
+    } else {
         eth2_network_config.genesis_state_bytes.clone()
-    {
+    };
+
+    client_config.genesis = if let Some(genesis_state_bytes) = genesis_state_bytes {
         // Set up weak subjectivity sync, or start from the hardcoded genesis state.
         if let (Some(initial_state_path), Some(initial_block_path)) = (
             cli_args.value_of("checkpoint-state"),
@@ -493,14 +765,15 @@ pub fn get_config<E: EthSpec>(
     } else {
         if cli_args.is_present("checkpoint-state") || cli_args.is_present("checkpoint-sync-url") {
             return Err(
-                "Checkpoint sync is not available for this network as no genesis state is known"
+                "Checkpoint sync is not available for this network as no genesis state is known, \
+                 provide one with --genesis-state-url"
                     .to_string(),
             );
         }
         ClientGenesis::DepositContract
     };
 
-    if cli_args.is_present("reconstruct-historic-states") {
+    if parse_flag(cli_args, "reconstruct-historic-states")? {
         client_config.chain.reconstruct_historic_states = true;
     }
 
@@ -512,16 +785,22 @@ pub fn get_config<E: EthSpec>(
             ));
         }
 
-        graffiti.as_bytes()
-    } else if cli_args.is_present("private") {
-        b""
+        Some(graffiti.as_bytes())
+    } else if parse_flag(cli_args, "private")? {
+        Some(&b""[..])
+    } else if use_cli_value(cli_args, "graffiti") {
+        Some(lighthouse_version::VERSION.as_bytes())
     } else {
-        lighthouse_version::VERSION.as_bytes()
+        // Keep the graffiti from the config file.
+        None
     };
 
-    let trimmed_graffiti_len = cmp::min(raw_graffiti.len(), GRAFFITI_BYTES_LEN);
-    client_config.graffiti.0[..trimmed_graffiti_len]
-        .copy_from_slice(&raw_graffiti[..trimmed_graffiti_len]);
+    if let Some(raw_graffiti) = raw_graffiti {
+        let trimmed_graffiti_len = cmp::min(raw_graffiti.len(), GRAFFITI_BYTES_LEN);
+        client_config.graffiti = Graffiti::default();
+        client_config.graffiti.0[..trimmed_graffiti_len]
+            .copy_from_slice(&raw_graffiti[..trimmed_graffiti_len]);
+    }
 
     if let Some(wss_checkpoint) = cli_args.value_of("wss-checkpoint") {
         let mut split = wss_checkpoint.split(':');
@@ -571,7 +850,7 @@ pub fn get_config<E: EthSpec>(
     client_config.chain.max_network_size =
         lighthouse_network::gossip_max_size(spec.bellatrix_fork_epoch.is_some());
 
-    if cli_args.is_present("slasher") {
+    if parse_flag(cli_args, "slasher")? {
         let slasher_dir = if let Some(slasher_dir) = cli_args.value_of("slasher-dir") {
             PathBuf::from(slasher_dir)
         } else {
@@ -626,7 +905,7 @@ pub fn get_config<E: EthSpec>(
             slasher_config.validator_chunk_size = validator_chunk_size;
         }
 
-        slasher_config.broadcast = cli_args.is_present("slasher-broadcast");
+        slasher_config.broadcast = parse_flag(cli_args, "slasher-broadcast")?;
 
         if let Some(backend) = clap_utils::parse_optional(cli_args, "slasher-backend")? {
             slasher_config.backend = backend;
@@ -635,7 +914,7 @@ pub fn get_config<E: EthSpec>(
         client_config.slasher = Some(slasher_config);
     }
 
-    if cli_args.is_present("validator-monitor-auto") {
+    if parse_flag(cli_args, "validator-monitor-auto")? {
         client_config.validator_monitor_auto = true;
     }
 
@@ -674,23 +953,29 @@ pub fn get_config<E: EthSpec>(
         client_config.validator_monitor_individual_tracking_threshold = count;
     }
 
-    if cli_args.is_present("disable-lock-timeouts") {
+    if parse_flag(cli_args, "disable-lock-timeouts")? {
         client_config.chain.enable_lock_timeouts = false;
     }
 
-    if cli_args.is_present("disable-proposer-reorgs") {
+    if parse_flag(cli_args, "disable-proposer-reorgs")? {
         client_config.chain.re_org_threshold = None;
     } else {
-        client_config.chain.re_org_threshold = Some(
-            clap_utils::parse_optional(cli_args, "proposer-reorg-threshold")?
-                .map(ReOrgThreshold)
-                .unwrap_or(DEFAULT_RE_ORG_THRESHOLD),
-        );
-        client_config.chain.re_org_max_epochs_since_finalization =
+        if let Some(threshold) = clap_utils::parse_optional(cli_args, "proposer-reorg-threshold")? {
+            client_config.chain.re_org_threshold = Some(ReOrgThreshold(threshold));
+        } else if !cli_args.is_present(CONFIG_FILE_FLAG) {
+            client_config.chain.re_org_threshold = Some(DEFAULT_RE_ORG_THRESHOLD);
+        }
+        if let Some(epochs) =
             clap_utils::parse_optional(cli_args, "proposer-reorg-epochs-since-finalization")?
-                .unwrap_or(DEFAULT_RE_ORG_MAX_EPOCHS_SINCE_FINALIZATION);
-        client_config.chain.re_org_cutoff_millis =
-            clap_utils::parse_optional(cli_args, "proposer-reorg-cutoff")?;
+        {
+            client_config.chain.re_org_max_epochs_since_finalization = epochs;
+        } else if !cli_args.is_present(CONFIG_FILE_FLAG) {
+            client_config.chain.re_org_max_epochs_since_finalization =
+                DEFAULT_RE_ORG_MAX_EPOCHS_SINCE_FINALIZATION;
+        }
+        if let Some(cutoff) = clap_utils::parse_optional(cli_args, "proposer-reorg-cutoff")? {
+            client_config.chain.re_org_cutoff_millis = Some(cutoff);
+        }
 
         if let Some(disallowed_offsets_str) =
             clap_utils::parse_optional::<String>(cli_args, "proposer-reorg-disallowed-offsets")?
@@ -709,26 +994,41 @@ pub fn get_config<E: EthSpec>(
     }
 
     // Note: This overrides any previous flags that enable this option.
-    if cli_args.is_present("disable-deposit-contract-sync") {
+    if parse_flag(cli_args, "disable-deposit-contract-sync")? {
         client_config.sync_eth1_chain = false;
     }
 
-    client_config.chain.prepare_payload_lookahead =
-        clap_utils::parse_optional(cli_args, "prepare-payload-lookahead")?
-            .map(Duration::from_millis)
-            .unwrap_or_else(|| {
-                Duration::from_secs(spec.seconds_per_slot)
-                    / DEFAULT_PREPARE_PAYLOAD_LOOKAHEAD_FACTOR
-            });
+    if use_cli_value(cli_args, "prepare-payload-lookahead") {
+        client_config.chain.prepare_payload_lookahead =
+            clap_utils::parse_optional(cli_args, "prepare-payload-lookahead")?
+                .map(Duration::from_millis)
+                .unwrap_or_else(|| {
+                    Duration::from_secs(spec.seconds_per_slot)
+                        / DEFAULT_PREPARE_PAYLOAD_LOOKAHEAD_FACTOR
+                });
+    }
 
-    client_config.chain.always_prepare_payload = cli_args.is_present("always-prepare-payload");
+    if use_cli_value(cli_args, "always-prepare-payload") {
+        client_config.chain.always_prepare_payload =
+            parse_flag(cli_args, "always-prepare-payload")?;
+    }
 
-    if let Some(timeout) =
-        clap_utils::parse_optional(cli_args, "fork-choice-before-proposal-timeout")?
+    if let Some(timeout) = cli_value_of(cli_args, "fork-choice-before-proposal-timeout")
+        .map(str::parse)
+        .transpose()
+        .map_err(|_| "fork-choice-before-proposal-timeout is not a valid integer".to_string())?
     {
         client_config.chain.fork_choice_before_proposal_timeout_ms = timeout;
     }
 
+    client_config.chain.fork_choice_persist_interval =
+        clap_utils::parse_required(cli_args, "fork-choice-persist-interval")?;
+    if client_config.chain.fork_choice_persist_interval == 0 {
+        return Err("--fork-choice-persist-interval must be at least 1".into());
+    }
+    client_config.chain.proto_array_prune_threshold =
+        clap_utils::parse_required(cli_args, "proto-array-prune-threshold")?;
+
     if !clap_utils::parse_required::<bool>(cli_args, "count-unrealized")? {
         warn!(
             log,
@@ -745,43 +1045,60 @@ pub fn get_config<E: EthSpec>(
         );
     }
 
-    client_config.chain.always_reset_payload_statuses =
-        cli_args.is_present("reset-payload-statuses");
+    if use_cli_value(cli_args, "reset-payload-statuses") {
+        client_config.chain.always_reset_payload_statuses =
+            parse_flag(cli_args, "reset-payload-statuses")?;
+    }
 
-    client_config.chain.paranoid_block_proposal = cli_args.is_present("paranoid-block-proposal");
+    if use_cli_value(cli_args, "paranoid-block-proposal") {
+        client_config.chain.paranoid_block_proposal =
+            parse_flag(cli_args, "paranoid-block-proposal")?;
+    }
 
     /*
      * Builder fallback configs.
      */
-    client_config.chain.builder_fallback_skips =
-        clap_utils::parse_required(cli_args, "builder-fallback-skips")?;
-    client_config.chain.builder_fallback_skips_per_epoch =
-        clap_utils::parse_required(cli_args, "builder-fallback-skips-per-epoch")?;
-    client_config
-        .chain
-        .builder_fallback_epochs_since_finalization =
-        clap_utils::parse_required(cli_args, "builder-fallback-epochs-since-finalization")?;
-    client_config.chain.builder_fallback_disable_checks =
-        cli_args.is_present("builder-fallback-disable-checks");
+    if use_cli_value(cli_args, "builder-fallback-skips") {
+        client_config.chain.builder_fallback_skips =
+            clap_utils::parse_required(cli_args, "builder-fallback-skips")?;
+    }
+    if use_cli_value(cli_args, "builder-fallback-skips-per-epoch") {
+        client_config.chain.builder_fallback_skips_per_epoch =
+            clap_utils::parse_required(cli_args, "builder-fallback-skips-per-epoch")?;
+    }
+    if use_cli_value(cli_args, "builder-fallback-epochs-since-finalization") {
+        client_config
+            .chain
+            .builder_fallback_epochs_since_finalization =
+            clap_utils::parse_required(cli_args, "builder-fallback-epochs-since-finalization")?;
+    }
+    if use_cli_value(cli_args, "builder-fallback-disable-checks") {
+        client_config.chain.builder_fallback_disable_checks =
+            parse_flag(cli_args, "builder-fallback-disable-checks")?;
+    }
 
     // Graphical user interface config.
-    if cli_args.is_present("gui") {
+    if parse_flag(cli_args, "gui")? {
         client_config.http_api.enabled = true;
         client_config.validator_monitor_auto = true;
     }
 
     // Optimistic finalized sync.
-    client_config.chain.optimistic_finalized_sync =
-        !cli_args.is_present("disable-optimistic-finalized-sync");
+    if use_cli_value(cli_args, "disable-optimistic-finalized-sync") {
+        client_config.chain.optimistic_finalized_sync =
+            !parse_flag(cli_args, "disable-optimistic-finalized-sync")?;
+    }
 
     // Payload selection configs
-    if cli_args.is_present("always-prefer-builder-payload") {
+    if parse_flag(cli_args, "always-prefer-builder-payload")? {
         client_config.always_prefer_builder_payload = true;
     }
 
     // Backfill sync rate-limiting
-    client_config.chain.enable_backfill_rate_limiting =
-        !cli_args.is_present("disable-backfill-rate-limiting");
+    if use_cli_value(cli_args, "disable-backfill-rate-limiting") {
+        client_config.chain.enable_backfill_rate_limiting =
+            !parse_flag(cli_args, "disable-backfill-rate-limiting")?;
+    }
 
     Ok(client_config)
 }
@@ -795,7 +1112,7 @@ pub fn parse_listening_addresses(
         .values_of("listen-address")
         .expect("--listen_addresses has a default value");
 
-    let use_zero_ports = cli_args.is_present("zero-ports");
+    let use_zero_ports = parse_flag(cli_args, "zero-ports")?;
 
     // parse the possible ips
     let mut maybe_ipv4 = None;
@@ -856,6 +1173,33 @@ pub fn parse_listening_addresses(
             format!("Failed to parse --discovery-port6 as an integer: {parse_error}")
         })?;
 
+    // parse the possible quic ports, which enable quic if set
+    let maybe_quic_port = cli_args
+        .value_of("quic-port")
+        .map(str::parse::<u16>)
+        .transpose()
+        .map_err(|parse_error| {
+            format!("Failed to parse --quic-port as an integer: {parse_error}")
+        })?;
+    let maybe_quic6_port = cli_args
+        .value_of("quic-port6")
+        .map(str::parse::<u16>)
+        .transpose()
+        .map_err(|parse_error| {
+            format!("Failed to parse --quic-port6 as an integer: {parse_error}")
+        })?;
+    // use zero ports if required and quic is enabled. If not, use the given port.
+    let quic_listen_port = |maybe_port: Option<u16>, unused_port: fn() -> Result<u16, String>| {
+        maybe_port
+            .map(|port| {
+                use_zero_ports
+                    .then(unused_port)
+                    .transpose()
+                    .map(|zero_port| zero_port.unwrap_or(port))
+            })
+            .transpose()
+    };
+
     // Now put everything together
     let listening_addresses = match (maybe_ipv4, maybe_ipv6) {
         (None, None) => {
@@ -885,10 +1229,16 @@ pub fn parse_listening_addresses(
                 .or(maybe_udp_port)
                 .unwrap_or(port);
 
+            if maybe_quic6_port.is_some() {
+                warn!(log, "When listening only over IpV6, use the --quic-port flag. The value of --quic-port6 will be ignored.")
+            }
+            let quic_port = quic_listen_port(maybe_quic_port, unused_port::unused_udp6_port)?;
+
             ListenAddress::V6(lighthouse_network::ListenAddr {
                 addr: ipv6,
                 udp_port,
                 tcp_port,
+                quic_port,
             })
         }
         (Some(ipv4), None) => {
@@ -906,10 +1256,12 @@ pub fn parse_listening_addresses(
                 .transpose()?
                 .or(maybe_udp_port)
                 .unwrap_or(port);
+            let quic_port = quic_listen_port(maybe_quic_port, unused_port::unused_udp4_port)?;
             ListenAddress::V4(lighthouse_network::ListenAddr {
                 addr: ipv4,
                 udp_port,
                 tcp_port,
+                quic_port,
             })
         }
         (Some(ipv4), Some(ipv6)) => {
@@ -933,21 +1285,43 @@ pub fn parse_listening_addresses(
                 .transpose()?
                 .or(maybe_udp6_port)
                 .unwrap_or(ipv6_tcp_port);
+            let ipv4_quic_port = quic_listen_port(maybe_quic_port, unused_port::unused_udp4_port)?;
+            let ipv6_quic_port = quic_listen_port(maybe_quic6_port, unused_port::unused_udp6_port)?;
             ListenAddress::DualStack(
                 lighthouse_network::ListenAddr {
                     addr: ipv4,
                     udp_port: ipv4_udp_port,
                     tcp_port: ipv4_tcp_port,
+                    quic_port: ipv4_quic_port,
                 },
                 lighthouse_network::ListenAddr {
                     addr: ipv6,
                     udp_port: ipv6_udp_port,
                     tcp_port: ipv6_tcp_port,
+                    quic_port: ipv6_quic_port,
                 },
             )
         }
     };
 
+    // QUIC and discovery both listen over UDP, so they can't share a port.
+    if let Some(v4) = listening_addresses.v4() {
+        if v4.quic_port == Some(v4.udp_port) {
+            return Err(format!(
+                "--quic-port {} must differ from the discovery port",
+                v4.udp_port
+            ));
+        }
+    }
+    if let Some(v6) = listening_addresses.v6() {
+        if v6.quic_port == Some(v6.udp_port) {
+            return Err(format!(
+                "The IPv6 QUIC port {} must differ from the IPv6 discovery port",
+                v6.udp_port
+            ));
+        }
+    }
+
     Ok(listening_addresses)
 }
 
@@ -961,31 +1335,69 @@ pub fn set_network_config(
     // If a network dir has been specified, override the `datadir` definition.
     if let Some(dir) = cli_args.value_of("network-dir") {
         config.network_dir = PathBuf::from(dir);
-    } else {
+    } else if use_cli_value(cli_args, "datadir") {
         config.network_dir = data_dir.join(DEFAULT_NETWORK_DIR);
     };
 
-    if cli_args.is_present("subscribe-all-subnets") {
+    if parse_flag(cli_args, "subscribe-all-subnets")? {
         config.subscribe_all_subnets = true;
     }
 
-    if cli_args.is_present("import-all-attestations") {
+    if parse_flag(cli_args, "import-all-attestations")? {
         config.import_all_attestations = true;
     }
 
-    if cli_args.is_present("shutdown-after-sync") {
+    if parse_flag(cli_args, "shutdown-after-sync")? {
         config.shutdown_after_sync = true;
     }
 
-    config.set_listening_addr(parse_listening_addresses(cli_args, log)?);
+    if [
+        "listen-address",
+        "port",
+        "port6",
+        "discovery-port",
+        "discovery-port6",
+        "zero-ports",
+    ]
+    .iter()
+    .any(|name| use_cli_value(cli_args, name))
+    {
+        config.set_listening_addr(parse_listening_addresses(cli_args, log)?);
+    }
 
-    if let Some(target_peers_str) = cli_args.value_of("target-peers") {
+    if let Some(target_peers_str) = cli_value_of(cli_args, "target-peers") {
         config.target_peers = target_peers_str
             .parse::<usize>()
             .map_err(|_| format!("Invalid number of target peers: {}", target_peers_str))?;
     }
 
-    if let Some(value) = cli_args.value_of("network-load") {
+    if let Some(max_peers_str) = cli_value_of(cli_args, "max-peers") {
+        let max_peers = max_peers_str
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid maximum number of peers: {}", max_peers_str))?;
+        if max_peers < config.target_peers {
+            return Err(format!(
+                "--max-peers ({}) must be at least --target-peers ({})",
+                max_peers, config.target_peers
+            ));
+        }
+        config.max_peers = Some(max_peers);
+    }
+
+    if let Some(ratio_str) = cli_value_of(cli_args, "min-outbound-peers-ratio") {
+        let ratio = ratio_str
+            .parse::<f32>()
+            .map_err(|_| format!("Invalid minimum outbound peers ratio: {}", ratio_str))?;
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(format!(
+                "--min-outbound-peers-ratio must be between 0 and 1: {}",
+                ratio
+            ));
+        }
+        config.min_outbound_peers_ratio = ratio;
+    }
+
+    if let Some(value) = cli_value_of(cli_args, "network-load") {
         let network_load = value
             .parse::<u8>()
             .map_err(|_| format!("Invalid integer: {}", value))?;
@@ -1028,19 +1440,75 @@ pub fn set_network_config(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
-    if cli_args.is_present("disable-peer-scoring") {
+    if parse_flag(cli_args, "disable-peer-scoring")? {
         config.disable_peer_scoring = true;
     }
 
+    if let Some(path) = clap_utils::parse_optional::<PathBuf>(cli_args, "gossipsub-score-file")? {
+        let overrides: PeerScoreOverrides =
+            clap_utils::load_config_file(&path, "gossipsub_score_overrides")?;
+        overrides.validate()?;
+        config.gossipsub_score_overrides = overrides;
+    }
+
     if let Some(trusted_peers_str) = cli_args.value_of("trusted-peers") {
-        config.trusted_peers = trusted_peers_str
+        for trusted_peer in trusted_peers_str.split(',') {
+            // Trusted peers given as an ENR or Multiaddr are also dialed.
+            let (peer_id, addresses) = if let Ok(enr) = trusted_peer.parse::<Enr>() {
+                (enr.peer_id(), enr.multiaddr_tcp())
+            } else if let Ok(multiaddr) = trusted_peer.parse::<Multiaddr>() {
+                let peer_id = multiaddr
+                    .iter()
+                    .find_map(|proto| match proto {
+                        Protocol::P2p(multihash) => PeerId::from_multihash(multihash).ok(),
+                        _ => None,
+                    })
+                    .ok_or_else(|| {
+                        format!("Missing P2P in trusted peer Multiaddr: {}", multiaddr)
+                    })?;
+                (peer_id, vec![multiaddr])
+            } else {
+                let peer_id = trusted_peer
+                    .parse::<PeerId>()
+                    .map_err(|_| format!("Invalid trusted peer: {}", trusted_peer))?;
+                (peer_id, vec![])
+            };
+            config.trusted_peers.push(PeerIdSerialized::from(peer_id));
+            config.libp2p_nodes.extend(addresses);
+        }
+    }
+
+    if let Some(static_peers_str) = cli_args.value_of("static-peers") {
+        for static_peer in static_peers_str.split(',') {
+            config.static_peers.extend(parse_static_peer(static_peer)?);
+        }
+    }
+
+    if let Some(static_peers_file) = cli_args.value_of("static-peers-file") {
+        let static_peers_str = fs::read_to_string(static_peers_file)
+            .map_err(|e| format!("Unable to read static peers file: {:?}", e))?;
+        // One static peer per line, ignoring blank lines and comments.
+        for static_peer in static_peers_str
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            config.static_peers.extend(parse_static_peer(static_peer)?);
+        }
+    }
+
+    if let Some(peer_allowlist_str) = cli_args.value_of("peer-allowlist") {
+        config.peer_allowlist = peer_allowlist_str
             .split(',')
-            .map(|peer_id| {
-                peer_id
-                    .parse()
-                    .map_err(|_| format!("Invalid trusted peer id: {}", peer_id))
-            })
-            .collect::<Result<Vec<PeerIdSerialized>, _>>()?;
+            .map(AccessListEntry::from_str)
+            .collect::<Result<_, _>>()?;
+    }
+
+    if let Some(peer_denylist_str) = cli_args.value_of("peer-denylist") {
+        config.peer_denylist = peer_denylist_str
+            .split(',')
+            .map(AccessListEntry::from_str)
+            .collect::<Result<_, _>>()?;
     }
 
     if let Some(enr_udp_port_str) = cli_args.value_of("enr-udp-port") {
@@ -1075,7 +1543,23 @@ pub fn set_network_config(
         );
     }
 
-    if cli_args.is_present("enr-match") {
+    if let Some(enr_quic_port_str) = cli_args.value_of("enr-quic-port") {
+        config.enr_quic4_port = Some(
+            enr_quic_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR QUIC port: {}", enr_quic_port_str))?,
+        );
+    }
+
+    if let Some(enr_quic_port_str) = cli_args.value_of("enr-quic6-port") {
+        config.enr_quic6_port = Some(
+            enr_quic_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR QUIC port: {}", enr_quic_port_str))?,
+        );
+    }
+
+    if parse_flag(cli_args, "enr-match")? {
         // Match the Ip and UDP port in the enr.
 
         // set the enr address to localhost if the address is unspecified
@@ -1178,49 +1662,191 @@ pub fn set_network_config(
         }
     }
 
-    if cli_args.is_present("disable-enr-auto-update") {
+    if parse_flag(cli_args, "disable-enr-auto-update")? {
         config.discv5_config.enr_update = false;
     }
 
-    if cli_args.is_present("disable-packet-filter") {
+    if parse_flag(cli_args, "enr-omit-address")? {
+        // Discovery must not add the address it learns from its peers.
+        config.enr_omit_address = true;
+        config.discv5_config.enr_update = false;
+    }
+
+    if parse_flag(cli_args, "disable-packet-filter")? {
         warn!(log, "Discv5 packet filter is disabled");
         config.discv5_config.enable_packet_filter = false;
     }
 
-    if cli_args.is_present("disable-discovery") {
+    let discovery_rate_limit = clap_utils::parse_optional::<u64>(cli_args, "discovery-rate-limit")?;
+    let discovery_ip_rate_limit =
+        clap_utils::parse_optional::<u64>(cli_args, "discovery-ip-rate-limit")?;
+    if discovery_rate_limit.is_some() || discovery_ip_rate_limit.is_some() {
+        let total = discovery_rate_limit.unwrap_or(DEFAULT_DISCOVERY_RATE_LIMIT);
+        let per_ip = discovery_ip_rate_limit.unwrap_or(DEFAULT_DISCOVERY_IP_RATE_LIMIT);
+        if total == 0 || per_ip == 0 {
+            return Err("Discovery rate limits must be greater than zero".to_string());
+        }
+        config.discv5_config.filter_rate_limiter = Some(discovery_rate_limiter(total, per_ip));
+    }
+
+    if let Some(max_nodes_per_ip) =
+        clap_utils::parse_optional::<usize>(cli_args, "discovery-max-nodes-per-ip")?
+    {
+        if max_nodes_per_ip == 0 {
+            return Err("--discovery-max-nodes-per-ip must be greater than zero".to_string());
+        }
+        config.discv5_config.filter_max_nodes_per_ip = Some(max_nodes_per_ip);
+    }
+
+    if parse_flag(cli_args, "disable-discovery")? {
         config.disable_discovery = true;
         warn!(log, "Discovery is disabled. New peers will not be found");
     }
 
-    if cli_args.is_present("disable-upnp") {
+    if let Some(proxy) = clap_utils::parse_optional::<ProxyConfig>(cli_args, "proxy")? {
+        info!(log, "Dialing peers through proxy"; "proxy" => %proxy);
+        if !config.disable_discovery {
+            warn!(
+                log,
+                "Discovery traffic is not sent through the proxy";
+                "info" => "use --disable-discovery with --static-peers to only connect through it",
+            );
+        }
+        config.proxy = Some(proxy);
+    }
+
+    if parse_flag(cli_args, "disable-upnp")? {
         config.upnp_enabled = false;
     }
 
-    if cli_args.is_present("private") {
+    if let Some(protocol) = clap_utils::parse_optional(cli_args, "port-mapping-protocol")? {
+        config.port_mapping_protocol = protocol;
+    }
+
+    if parse_flag(cli_args, "private")? {
         config.private = true;
     }
 
-    if cli_args.is_present("metrics") {
+    // Network metrics are also required when they're pushed to a Pushgateway.
+    if parse_flag(cli_args, "metrics")? || cli_args.is_present("metrics-pushgateway") {
         config.metrics_enabled = true;
     }
 
-    if cli_args.is_present("enable-private-discovery") {
+    if parse_flag(cli_args, "enable-private-discovery")? {
         config.discv5_config.table_filter = |_| true;
     }
 
     // Light client server config.
-    config.enable_light_client_server = cli_args.is_present("light-client-server");
+    if use_cli_value(cli_args, "light-client-server") {
+        config.enable_light_client_server = parse_flag(cli_args, "light-client-server")?;
+    }
 
     // This flag can be used both with or without a value. Try to parse it first with a value, if
     // no value is defined but the flag is present, use the default params.
-    config.outbound_rate_limiter_config = clap_utils::parse_optional(cli_args, "self-limiter")?;
-    if cli_args.is_present("self-limiter") && config.outbound_rate_limiter_config.is_none() {
-        config.outbound_rate_limiter_config = Some(Default::default());
+    if use_cli_value(cli_args, "self-limiter") {
+        config.outbound_rate_limiter_config = clap_utils::parse_optional(cli_args, "self-limiter")?;
+        if parse_flag(cli_args, "self-limiter")? && config.outbound_rate_limiter_config.is_none() {
+            config.outbound_rate_limiter_config = Some(Default::default());
+        }
     }
 
     Ok(())
 }
 
+/// Re-reads the settings which can be changed while the beacon node is running (the debug level,
+/// graffiti and fee recipient) from the `--config-file`.
+///
+/// Values which were explicitly supplied on the command line take precedence over the file.
+pub struct ConfigReloader {
+    config_file: PathBuf,
+    debug_level: Option<String>,
+    graffiti: Option<Graffiti>,
+    suggested_fee_recipient: Option<Option<Address>>,
+}
+
+impl ConfigReloader {
+    /// Returns `None` if no `--config-file` was supplied, since there is nothing to reload.
+    pub fn new(
+        cli_args: &ArgMatches,
+        client_config: &ClientConfig,
+    ) -> Result<Option<Self>, String> {
+        let config_file = match clap_utils::parse_optional(cli_args, CONFIG_FILE_FLAG)? {
+            Some(config_file) => config_file,
+            None => return Ok(None),
+        };
+
+        let debug_level = use_cli_value(cli_args, "debug-level")
+            .then(|| client_config.logger_config.debug_level.clone());
+        let graffiti = (use_cli_value(cli_args, "graffiti") || parse_flag(cli_args, "private")?)
+            .then_some(client_config.graffiti);
+        let suggested_fee_recipient =
+            use_cli_value(cli_args, "suggested-fee-recipient").then(|| {
+                client_config
+                    .execution_layer
+                    .as_ref()
+                    .and_then(|config| config.suggested_fee_recipient)
+            });
+
+        Ok(Some(Self {
+            config_file,
+            debug_level,
+            graffiti,
+            suggested_fee_recipient,
+        }))
+    }
+
+    /// Reads the config file, overriding any values supplied on the command line.
+    pub fn reload(&self) -> Result<ClientConfig, String> {
+        let mut client_config: ClientConfig =
+            clap_utils::load_config_file(&self.config_file, "beacon_node")?;
+
+        if let Some(debug_level) = &self.debug_level {
+            client_config.logger_config.debug_level = debug_level.clone();
+        }
+        if let Some(graffiti) = self.graffiti {
+            client_config.graffiti = graffiti;
+        }
+        if let Some(suggested_fee_recipient) = self.suggested_fee_recipient {
+            client_config
+                .execution_layer
+                .get_or_insert_with(Default::default)
+                .suggested_fee_recipient = suggested_fee_recipient;
+        }
+
+        Ok(client_config)
+    }
+
+    /// Returns the path of the config file.
+    pub fn config_file(&self) -> &Path {
+        &self.config_file
+    }
+}
+
+/// Parses a static peer given as an ENR, or as a Multiaddr ending with the peer id.
+fn parse_static_peer(static_peer: &str) -> Result<Vec<Multiaddr>, String> {
+    if let Ok(enr) = static_peer.parse::<Enr>() {
+        let multiaddrs = enr.multiaddr_p2p_tcp();
+        if multiaddrs.is_empty() {
+            return Err(format!(
+                "Static peer ENR has no TCP address: {}",
+                static_peer
+            ));
+        }
+        return Ok(multiaddrs);
+    }
+
+    let multiaddr = static_peer
+        .parse::<Multiaddr>()
+        .map_err(|_| format!("Invalid static peer: {}", static_peer))?;
+    match multiaddr.iter().last() {
+        Some(Protocol::P2p(_)) => Ok(vec![multiaddr]),
+        _ => Err(format!(
+            "Missing P2P in static peer Multiaddr: {}",
+            multiaddr
+        )),
+    }
+}
+
 /// Gets the datadir which should be used.
 pub fn get_data_dir(cli_args: &ArgMatches) -> PathBuf {
     // Read the `--datadir` flag.
@@ -1241,6 +1867,30 @@ pub fn get_data_dir(cli_args: &ArgMatches) -> PathBuf {
         .unwrap_or_else(|| PathBuf::from("."))
 }
 
+/// The name of the file in the beacon node datadir which is locked whilst a beacon node uses it.
+pub const DATADIR_LOCK_FILENAME: &str = "lighthouse.lock";
+
+/// Takes an exclusive lock on the datadir of `client_config` (creating the datadir if required),
+/// which is held until the returned `Lockfile` is dropped.
+///
+/// Fails if another process holds the lock, since two beacon nodes using the same datadir will
+/// corrupt its database. The lock is released by the OS if the process holding it exits, so it
+/// cannot become stale after a crash.
+pub fn lock_data_dir(client_config: &ClientConfig) -> Result<Lockfile, String> {
+    let path = client_config.create_data_dir()?.join(DATADIR_LOCK_FILENAME);
+    Lockfile::new(path).map_err(|e| match e {
+        LockfileError::FileLocked(path, _) => format!(
+            "The datadir is in use by another Lighthouse process (it holds the lock on {:?}). \
+             Stop the other process, or use --force-unlock if you are certain that no other \
+             process is using the datadir",
+            path
+        ),
+        LockfileError::IoError(path, e) | LockfileError::UnableToOpenFile(path, e) => {
+            format!("Unable to lock the datadir using {:?}: {:?}", path, e)
+        }
+    })
+}
+
 /// Get the `slots_per_restore_point` value to use for the database.
 ///
 /// Return `(sprp, set_explicitly)` where `set_explicitly` is `true` if the user provided the value.
@@ -3,28 +3,42 @@ extern crate clap;
 
 mod cli;
 mod config;
+mod migration;
 
 pub use beacon_chain;
-use beacon_chain::store::LevelDB;
+use beacon_chain::store::BeaconNodeBackend;
 use beacon_chain::{
     builder::Witness, eth1_chain::CachingEth1Backend, slot_clock::SystemTimeSlotClock,
     TimeoutRwLock,
 };
 use clap::ArgMatches;
-pub use cli::cli_app;
+pub use cli::{cli_app, cli_args};
 pub use client::{Client, ClientBuilder, ClientConfig, ClientGenesis};
-pub use config::{get_config, get_data_dir, get_slots_per_restore_point, set_network_config};
-use environment::RuntimeContext;
+pub use config::{
+    get_config, get_data_dir, get_slots_per_restore_point, lock_data_dir, set_network_config,
+    ConfigReloader, DATADIR_LOCK_FILENAME,
+};
+use environment::{LoggerHandle, ReloadReceiver, RuntimeContext, StartupError};
 pub use eth2_config::Eth2Config;
+pub use migration::{
+    migrate_data_dir, plan_data_dir_migration, DataDirMigration, BEACON_NODE_SCHEMA,
+};
 use slasher::Slasher;
-use slog::{info, warn};
+use slog::{info, warn, Logger};
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 use types::EthSpec;
 
 /// A type-alias to the tighten the definition of a production-intended `Client`.
-pub type ProductionClient<E> =
-    Client<Witness<SystemTimeSlotClock, CachingEth1Backend<E>, E, LevelDB<E>, LevelDB<E>>>;
+pub type ProductionClient<E> = Client<
+    Witness<
+        SystemTimeSlotClock,
+        CachingEth1Backend<E>,
+        E,
+        BeaconNodeBackend<E>,
+        BeaconNodeBackend<E>,
+    >,
+>;
 
 /// The beacon node `Client` that will be used in production.
 ///
@@ -47,7 +61,7 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
         matches: ArgMatches<'static>,
     ) -> Result<Self, String> {
         let client_config = get_config::<E>(&matches, &context)?;
-        Self::new(context, client_config).await
+        Self::new(context, client_config).await.map_err(Into::into)
     }
 
     /// Starts a new beacon node `Client` in the given `environment`.
@@ -56,13 +70,21 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
     pub async fn new(
         context: RuntimeContext<E>,
         mut client_config: ClientConfig,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, StartupError> {
         let spec = context.eth2_config().spec.clone();
-        let client_genesis = client_config.genesis.clone();
+        let mut client_genesis = client_config.genesis.clone();
         let store_config = client_config.store.clone();
         let log = context.log().clone();
         let _datadir = client_config.create_data_dir()?;
         let db_path = client_config.create_db_path()?;
+        if let Some(old_path) = client_config.migrate_freezer_db()? {
+            info!(
+                log,
+                "Moved freezer database";
+                "from" => ?old_path,
+                "to" => ?client_config.get_freezer_db_path(),
+            );
+        }
         let freezer_db_path = client_config.create_freezer_db_path()?;
         let executor = context.executor.clone();
 
@@ -80,12 +102,30 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
             TimeoutRwLock::disable_timeouts()
         }
 
+        // Must happen before any histograms are used.
+        for name in lighthouse_metrics::set_histogram_buckets(
+            client_config.http_metrics.histogram_buckets.clone(),
+        ) {
+            warn!(
+                log,
+                "Unable to override histogram buckets";
+                "histogram" => name,
+                "reason" => "histogram already in use",
+            );
+        }
+
         let builder = ClientBuilder::new(context.eth_spec_instance.clone())
             .runtime_context(context)
             .chain_spec(spec)
             .http_api_config(client_config.http_api.clone())
             .disk_store(&db_path, &freezer_db_path, store_config, log.clone())?;
 
+        let builder = if client_config.auto_recover {
+            builder.recover_disk_store(&mut client_genesis, log.clone())?
+        } else {
+            builder
+        };
+
         let builder = if let Some(slasher_config) = client_config.slasher.clone() {
             let slasher = Arc::new(
                 Slasher::open(slasher_config, log.new(slog::o!("service" => "slasher")))
@@ -104,6 +144,18 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
             builder
         };
 
+        let builder = if let Some(clock_drift_config) = client_config.clock_drift.clone() {
+            builder.clock_drift_monitor(clock_drift_config)?
+        } else {
+            builder
+        };
+
+        let builder = if let Some(stall_slots) = client_config.watchdog_stall_slots {
+            builder.watchdog(stall_slots)?
+        } else {
+            builder
+        };
+
         let builder = builder
             .beacon_chain_builder(client_genesis, client_config.clone())
             .await?;
@@ -149,6 +201,62 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
             .map(Self)
     }
 
+    /// Applies the settings of `client_config` which can be changed while the node is running.
+    ///
+    /// These are the default graffiti and the default fee recipient.
+    pub async fn apply_reloadable_config(&self, client_config: &ClientConfig) {
+        if let Some(chain) = self.beacon_chain() {
+            chain.set_graffiti(client_config.graffiti);
+            if let Some(execution_layer) = &chain.execution_layer {
+                execution_layer
+                    .set_suggested_fee_recipient(
+                        client_config
+                            .execution_layer
+                            .as_ref()
+                            .and_then(|config| config.suggested_fee_recipient),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// Uses `reloader` to reload the configuration of the node each time `reload_rx` is notified
+    /// (i.e., on SIGHUP). Runs until the environment is shut down.
+    pub async fn reload_on_signal(
+        self,
+        reloader: ConfigReloader,
+        mut reload_rx: ReloadReceiver,
+        logger_handle: Option<LoggerHandle>,
+        log: Logger,
+    ) {
+        while reload_rx.changed().await.is_ok() {
+            let client_config = match reloader.reload() {
+                Ok(client_config) => client_config,
+                Err(e) => {
+                    warn!(log, "Unable to reload config file"; "error" => e);
+                    continue;
+                }
+            };
+
+            if let Some(logger_handle) = &logger_handle {
+                if let Err(e) =
+                    logger_handle.set_debug_level(&client_config.logger_config.debug_level)
+                {
+                    warn!(log, "Unable to reload debug level"; "error" => e);
+                }
+            }
+            self.apply_reloadable_config(&client_config).await;
+
+            info!(
+                log,
+                "Reloaded config file";
+                "path" => ?reloader.config_file(),
+                "debug_level" => &client_config.logger_config.debug_level,
+                "graffiti" => client_config.graffiti.as_utf8_lossy(),
+            );
+        }
+    }
+
     pub fn into_inner(self) -> ProductionClient<E> {
         self.0
     }
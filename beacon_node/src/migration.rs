@@ -0,0 +1,154 @@
+//! Versioning of the beacon node datadir, see `datadir_schema`.
+
+use beacon_chain::store::metadata::{
+    SchemaVersion, CURRENT_FREEZER_SCHEMA_VERSION, CURRENT_SCHEMA_VERSION,
+    FREEZER_SCHEMA_VERSION_KEY, SCHEMA_VERSION_KEY,
+};
+use beacon_chain::store::{BeaconNodeBackend, Error as StoreError, ItemStore};
+use client::ClientConfig;
+use datadir_schema::{Error, Migration, Plan, Schema};
+use slog::{info, Logger};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use types::{EthSpec, Hash256};
+
+/// The layout of the beacon node datadir.
+///
+/// The hot and freezer databases have their own schema version, which is migrated when the
+/// database is opened.
+pub static BEACON_NODE_SCHEMA: Schema = Schema {
+    current: 1,
+    migrations: &[Migration {
+        to: 1,
+        description: "Add a schema marker to the datadir",
+        apply: |_| Ok(()),
+    }],
+    metadata_files: &["network/enr.dat"],
+    existing_files: &["chain_db"],
+};
+
+/// The migrations required to bring a beacon node datadir up to date.
+pub struct DataDirMigration {
+    pub plan: Plan<'static>,
+    pub db_path: PathBuf,
+    /// The schema version of the hot database, if it exists.
+    pub db_version: Option<SchemaVersion>,
+    pub freezer_db_path: PathBuf,
+    /// The schema version of the freezer database, if it exists.
+    ///
+    /// A freezer database created before the freezer was versioned is reported as version 1.
+    pub freezer_db_version: Option<SchemaVersion>,
+}
+
+impl DataDirMigration {
+    /// Returns `true` if the hot database will be migrated when it is opened.
+    pub fn migrates_db(&self) -> bool {
+        self.db_version
+            .map_or(false, |version| version != CURRENT_SCHEMA_VERSION)
+    }
+}
+
+/// Describes the version of the database at `path` compared to the version `current`.
+fn fmt_db_version(
+    f: &mut fmt::Formatter,
+    path: &Path,
+    version: Option<SchemaVersion>,
+    current: SchemaVersion,
+) -> fmt::Result {
+    match version {
+        Some(version) if version != current => write!(
+            f,
+            "{:?} will be migrated from database schema version {} to {}",
+            path,
+            version.as_u64(),
+            current.as_u64()
+        ),
+        Some(version) => write!(
+            f,
+            "{:?} is at database schema version {}",
+            path,
+            version.as_u64()
+        ),
+        None => write!(f, "{:?} does not exist yet", path),
+    }
+}
+
+impl fmt::Display for DataDirMigration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.plan)?;
+        fmt_db_version(f, &self.db_path, self.db_version, CURRENT_SCHEMA_VERSION)?;
+        writeln!(f)?;
+        fmt_db_version(
+            f,
+            &self.freezer_db_path,
+            self.freezer_db_version,
+            CURRENT_FREEZER_SCHEMA_VERSION,
+        )
+    }
+}
+
+/// Returns the migrations required to bring the datadir of `config` up to date, without modifying
+/// it.
+pub fn plan_data_dir_migration<E: EthSpec>(
+    config: &ClientConfig,
+) -> Result<DataDirMigration, Error> {
+    let plan = datadir_schema::plan(&config.get_data_dir(), &BEACON_NODE_SCHEMA)?;
+
+    // The databases are opened without creating them, so the datadir is left untouched.
+    let db_path = config.get_db_path();
+    let db_version = read_schema_version::<E>(&db_path, SCHEMA_VERSION_KEY)?.flatten();
+    let freezer_db_path = config.get_freezer_db_path();
+    let freezer_db_version =
+        match read_schema_version::<E>(&freezer_db_path, FREEZER_SCHEMA_VERSION_KEY)? {
+            Some(None) => Some(SchemaVersion(1)),
+            version => version.flatten(),
+        };
+
+    // The freezer database is migrated with the hot database, so only a newer version can't be
+    // opened.
+    if let Some(version) = freezer_db_version {
+        if version > CURRENT_FREEZER_SCHEMA_VERSION {
+            return Err(Error::UnsupportedSchemaVersion {
+                on_disk: version.as_u64(),
+                supported: CURRENT_FREEZER_SCHEMA_VERSION.as_u64(),
+            });
+        }
+    }
+
+    Ok(DataDirMigration {
+        plan,
+        db_path,
+        db_version,
+        freezer_db_path,
+        freezer_db_version,
+    })
+}
+
+/// Reads the schema version stored under `key` in the database at `path`.
+///
+/// Returns `None` if there is no database, and `Some(None)` if the database has no version.
+fn read_schema_version<E: EthSpec>(
+    path: &Path,
+    key: Hash256,
+) -> Result<Option<Option<SchemaVersion>>, Error> {
+    let db_error = |e: StoreError| Error::Database(path.to_path_buf(), format!("{:?}", e));
+    match BeaconNodeBackend::<E>::open_existing(path).map_err(db_error)? {
+        Some(db) => db.get::<SchemaVersion>(&key).map(Some).map_err(db_error),
+        None => Ok(None),
+    }
+}
+
+/// Backs up the metadata of the datadir if either it or the hot database requires migration, then
+/// migrates the datadir. The hot database is migrated once it has been opened.
+pub fn migrate_data_dir(migration: &DataDirMigration, log: &Logger) -> Result<(), Error> {
+    if !migration.plan.is_current() || migration.migrates_db() {
+        if let Some(backup_dir) = datadir_schema::backup(
+            &migration.plan.dir,
+            &BEACON_NODE_SCHEMA,
+            migration.plan.from,
+        )? {
+            info!(log, "Backed up datadir metadata"; "path" => ?backup_dir);
+        }
+    }
+    datadir_schema::apply(&migration.plan, log)
+}
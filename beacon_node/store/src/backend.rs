@@ -0,0 +1,182 @@
+//! The key-value stores which can be selected with `--db-backend`.
+use crate::config::DatabaseBackend;
+use crate::{
+    get_key_for_col, ColumnIter, ColumnKeyIter, DBColumn, Error, ItemStore, KeyValueStore,
+    KeyValueStoreOp, LevelDB, RawEntryIter, RawKeyIter,
+};
+use parking_lot::MutexGuard;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use types::EthSpec;
+
+#[cfg(feature = "mdbx")]
+use crate::Mdbx;
+
+/// A file which is only present in LevelDB databases.
+const LEVELDB_MARKER: &str = "CURRENT";
+/// A file which is only present in MDBX databases.
+#[cfg(feature = "mdbx")]
+const MDBX_MARKER: &str = "mdbx.dat";
+
+/// The number of values written by each transaction when copying a database.
+const COPY_BATCH_SIZE: usize = 1024;
+
+/// A database using any of the supported backends.
+pub enum BeaconNodeBackend<E: EthSpec> {
+    LevelDb(LevelDB<E>),
+    #[cfg(feature = "mdbx")]
+    Mdbx(Mdbx<E>),
+}
+
+impl<E: EthSpec> BeaconNodeBackend<E> {
+    /// Open a database at `path` using `backend`, creating a new database if one does not already
+    /// exist.
+    ///
+    /// Returns an error if `path` contains a database using a different backend.
+    pub fn open(backend: DatabaseBackend, path: &Path) -> Result<Self, Error> {
+        if let Some(existing) = Self::detect(path) {
+            if existing != backend {
+                return Err(Error::DBError {
+                    message: format!(
+                        "The database at {} uses the {} backend, not {}. Run with \
+                         `--db-backend {}` or convert it with `lighthouse db convert-backend`",
+                        path.display(),
+                        existing,
+                        backend,
+                        existing
+                    ),
+                });
+            }
+        }
+
+        match backend {
+            DatabaseBackend::LevelDb => LevelDB::open(path).map(Self::LevelDb),
+            #[cfg(feature = "mdbx")]
+            DatabaseBackend::Mdbx => Mdbx::open(path).map(Self::Mdbx),
+        }
+    }
+
+    /// Open the database at `path` without creating or writing to it, returning `None` if there
+    /// is no database.
+    ///
+    /// The database is opened read-only where the backend supports it. LevelDB may still write
+    /// its log files.
+    pub fn open_existing(path: &Path) -> Result<Option<Self>, Error> {
+        let db = match Self::detect(path) {
+            Some(DatabaseBackend::LevelDb) => LevelDB::open_existing(path).map(Self::LevelDb)?,
+            #[cfg(feature = "mdbx")]
+            Some(DatabaseBackend::Mdbx) => Mdbx::open_existing(path).map(Self::Mdbx)?,
+            None => return Ok(None),
+        };
+        Ok(Some(db))
+    }
+
+    /// Return the backend of the database at `path`, if one exists.
+    pub fn detect(path: &Path) -> Option<DatabaseBackend> {
+        if path.join(LEVELDB_MARKER).exists() {
+            return Some(DatabaseBackend::LevelDb);
+        }
+        #[cfg(feature = "mdbx")]
+        if path.join(MDBX_MARKER).exists() {
+            return Some(DatabaseBackend::Mdbx);
+        }
+        None
+    }
+
+    /// Copy every value of every column into `target`, returning the number of values copied.
+    ///
+    /// `self` must not be written to during the copy, as iteration is not necessarily
+    /// snapshot-consistent.
+    pub fn copy_into(&self, target: &Self) -> Result<usize, Error> {
+        let mut total = 0;
+        for column in DBColumn::iter() {
+            let mut ops = Vec::with_capacity(COPY_BATCH_SIZE);
+            for entry in self.iter_raw_entries(column) {
+                let (key, value) = entry?;
+                ops.push(KeyValueStoreOp::PutKeyValue(
+                    get_key_for_col(column.as_str(), &key),
+                    value,
+                ));
+                if ops.len() == COPY_BATCH_SIZE {
+                    total += ops.len();
+                    target.do_atomically(std::mem::take(&mut ops))?;
+                }
+            }
+            total += ops.len();
+            target.do_atomically(ops)?;
+        }
+        target.sync()?;
+        Ok(total)
+    }
+}
+
+/// Evaluate `$body` with `$db` bound to the database of whichever backend is in use.
+macro_rules! dispatch {
+    ($self:ident, $db:ident => $body:expr) => {
+        match $self {
+            BeaconNodeBackend::LevelDb($db) => $body,
+            #[cfg(feature = "mdbx")]
+            BeaconNodeBackend::Mdbx($db) => $body,
+        }
+    };
+}
+
+impl<E: EthSpec> KeyValueStore<E> for BeaconNodeBackend<E> {
+    fn get_bytes(&self, column: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        dispatch!(self, db => db.get_bytes(column, key))
+    }
+
+    fn put_bytes(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        dispatch!(self, db => db.put_bytes(column, key, value))
+    }
+
+    fn put_bytes_sync(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        dispatch!(self, db => db.put_bytes_sync(column, key, value))
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        dispatch!(self, db => db.sync())
+    }
+
+    fn key_exists(&self, column: &str, key: &[u8]) -> Result<bool, Error> {
+        dispatch!(self, db => db.key_exists(column, key))
+    }
+
+    fn key_delete(&self, column: &str, key: &[u8]) -> Result<(), Error> {
+        dispatch!(self, db => db.key_delete(column, key))
+    }
+
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        dispatch!(self, db => db.do_atomically(batch))
+    }
+
+    fn begin_rw_transaction(&self) -> MutexGuard<()> {
+        dispatch!(self, db => db.begin_rw_transaction())
+    }
+
+    fn compact(&self) -> Result<(), Error> {
+        dispatch!(self, db => db.compact())
+    }
+
+    fn compact_all(&self) -> Result<(), Error> {
+        dispatch!(self, db => db.compact_all())
+    }
+
+    fn iter_column(&self, column: DBColumn) -> ColumnIter {
+        dispatch!(self, db => db.iter_column(column))
+    }
+
+    fn iter_column_keys(&self, column: DBColumn) -> ColumnKeyIter {
+        dispatch!(self, db => db.iter_column_keys(column))
+    }
+
+    fn iter_raw_entries(&self, column: DBColumn) -> RawEntryIter {
+        dispatch!(self, db => db.iter_raw_entries(column))
+    }
+
+    fn iter_raw_keys(&self, column: DBColumn) -> RawKeyIter {
+        dispatch!(self, db => db.iter_raw_keys(column))
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for BeaconNodeBackend<E> {}
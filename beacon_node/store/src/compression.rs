@@ -0,0 +1,84 @@
+//! Compression for the full states stored at freezer restore points.
+//!
+//! Compressed values are prefixed with a byte identifying their compression, so that values
+//! written with different settings can be read back regardless of the current configuration.
+use crate::config::FreezerCompression;
+use crate::Error;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use std::io::{Read, Write};
+
+const SNAPPY_PREFIX: u8 = 1;
+const ZLIB_PREFIX: u8 = 2;
+
+/// Compress `bytes` with `compression`, prefixing the result with its compression.
+///
+/// The `level` is only used by zlib, and is clamped to the range 0-9.
+pub fn compress(
+    compression: FreezerCompression,
+    level: u32,
+    bytes: &[u8],
+) -> Result<Vec<u8>, Error> {
+    match compression {
+        FreezerCompression::None => Err(Error::CompressionError(
+            "values stored without compression have no prefix".into(),
+        )),
+        FreezerCompression::Snappy => {
+            let mut compressed = vec![SNAPPY_PREFIX];
+            compressed.extend(
+                snap::raw::Encoder::new()
+                    .compress_vec(bytes)
+                    .map_err(|e| Error::CompressionError(format!("snappy: {}", e)))?,
+            );
+            Ok(compressed)
+        }
+        FreezerCompression::Zlib => {
+            let mut encoder =
+                ZlibEncoder::new(vec![ZLIB_PREFIX], flate2::Compression::new(level.min(9)));
+            encoder
+                .write_all(bytes)
+                .map_err(|e| Error::CompressionError(format!("zlib: {}", e)))?;
+            encoder
+                .finish()
+                .map_err(|e| Error::CompressionError(format!("zlib: {}", e)))
+        }
+    }
+}
+
+/// Decompress a value written by `compress`.
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    match bytes.split_first() {
+        Some((&SNAPPY_PREFIX, compressed)) => snap::raw::Decoder::new()
+            .decompress_vec(compressed)
+            .map_err(|e| Error::CompressionError(format!("snappy: {}", e))),
+        Some((&ZLIB_PREFIX, compressed)) => {
+            let mut decompressed = vec![];
+            ZlibDecoder::new(compressed)
+                .read_to_end(&mut decompressed)
+                .map_err(|e| Error::CompressionError(format!("zlib: {}", e)))?;
+            Ok(decompressed)
+        }
+        Some((prefix, _)) => Err(Error::CompressionError(format!(
+            "unknown compression {}",
+            prefix
+        ))),
+        None => Err(Error::CompressionError("empty value".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let bytes = [0, 1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].repeat(64);
+        for compression in [FreezerCompression::Snappy, FreezerCompression::Zlib] {
+            let compressed = compress(compression, 9, &bytes).unwrap();
+            assert!(compressed.len() < bytes.len());
+            assert_eq!(decompress(&compressed).unwrap(), bytes);
+        }
+        assert!(compress(FreezerCompression::None, 9, &bytes).is_err());
+        assert!(decompress(&[3, 0]).is_err());
+    }
+}
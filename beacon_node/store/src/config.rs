@@ -2,11 +2,17 @@ use crate::{DBColumn, Error, StoreItem};
 use serde_derive::{Deserialize, Serialize};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
+use strum::{Display, EnumString, EnumVariantNames};
 use types::{EthSpec, MinimalEthSpec};
 
 pub const PREV_DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 2048;
 pub const DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 8192;
 pub const DEFAULT_BLOCK_CACHE_SIZE: usize = 5;
+pub const DEFAULT_RESTORE_POINTS_PER_SNAPSHOT: u64 = 1;
+pub const DEFAULT_BACKEND: DatabaseBackend = DatabaseBackend::LevelDb;
+pub const DEFAULT_COMPRESSION_LEVEL: u32 = 6;
+pub const DEFAULT_HISTORIC_STATE_REPLAY_LIMIT: u64 = 2048;
+pub const DEFAULT_HISTORIC_STATE_CACHE_SIZE: usize = 2;
 
 /// Database configuration parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -15,6 +21,9 @@ pub struct StoreConfig {
     pub slots_per_restore_point: u64,
     /// Flag indicating whether the `slots_per_restore_point` was set explicitly by the user.
     pub slots_per_restore_point_set_explicitly: bool,
+    /// Number of restore points per full state snapshot in the freezer DB. The restore points
+    /// between snapshots are stored as diffs against the latest snapshot.
+    pub restore_points_per_snapshot: u64,
     /// Maximum number of blocks to store in the in-memory block cache.
     pub block_cache_size: usize,
     /// Whether to compact the database on initialization.
@@ -23,6 +32,19 @@ pub struct StoreConfig {
     pub compact_on_prune: bool,
     /// Whether to prune payloads on initialization and finalization.
     pub prune_payloads: bool,
+    /// Number of epochs of blocks and states to keep before the split, or `None` to keep them all.
+    pub prune_blocks_older_than: Option<u64>,
+    /// The key-value store used for the hot and freezer databases.
+    pub backend: DatabaseBackend,
+    /// The compression applied to full states in the freezer database.
+    pub freezer_compression: FreezerCompression,
+    /// The zlib compression level, from 0 (none) to 9 (best), for freezer states and state diffs.
+    pub compression_level: u32,
+    /// Maximum number of slots to replay to serve a historic state which isn't stored, or 0 to
+    /// never replay.
+    pub historic_state_replay_limit: u64,
+    /// Maximum number of replayed historic states to keep in memory.
+    pub historic_state_cache_size: usize,
 }
 
 /// Variant of `StoreConfig` that gets written to disk. Contains immutable configuration params.
@@ -31,6 +53,28 @@ pub struct OnDiskStoreConfig {
     pub slots_per_restore_point: u64,
 }
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString, EnumVariantNames,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    LevelDb,
+    #[cfg(feature = "mdbx")]
+    Mdbx,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString, EnumVariantNames,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FreezerCompression {
+    None,
+    Snappy,
+    Zlib,
+}
+
 #[derive(Debug, Clone)]
 pub enum StoreConfigError {
     MismatchedSlotsPerRestorePoint { config: u64, on_disk: u64 },
@@ -42,10 +86,17 @@ impl Default for StoreConfig {
             // Safe default for tests, shouldn't ever be read by a CLI node.
             slots_per_restore_point: MinimalEthSpec::slots_per_historical_root() as u64,
             slots_per_restore_point_set_explicitly: false,
+            restore_points_per_snapshot: DEFAULT_RESTORE_POINTS_PER_SNAPSHOT,
             block_cache_size: DEFAULT_BLOCK_CACHE_SIZE,
             compact_on_init: false,
             compact_on_prune: true,
             prune_payloads: true,
+            prune_blocks_older_than: None,
+            backend: DEFAULT_BACKEND,
+            freezer_compression: FreezerCompression::None,
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+            historic_state_replay_limit: DEFAULT_HISTORIC_STATE_REPLAY_LIMIT,
+            historic_state_cache_size: DEFAULT_HISTORIC_STATE_CACHE_SIZE,
         }
     }
 }
@@ -0,0 +1,467 @@
+//! Reading and writing of `.era` archives of finalized history.
+//!
+//! An era file is an `e2store` file containing the blocks of one era of `SLOTS_PER_HISTORICAL_ROOT`
+//! slots, followed by the state at the end of the era. Blocks and states are SSZ encoded and
+//! compressed with the snappy framing format. See
+//! https://github.com/status-im/nimbus-eth2/blob/stable/docs/e2store.md for the specification.
+use crate::chunked_vector::{chunk_key, BlockRoots, Chunk, Field, StateRoots};
+use crate::hot_cold_store::HotColdDB;
+use crate::{AnchorInfo, ChunkWriter, Error, ItemStore};
+use slog::debug;
+use ssz::Encode;
+use state_processing::StateRootStrategy;
+use std::io::{Read, Write};
+use tree_hash::TreeHash;
+use types::{BeaconState, ChainSpec, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// The length of the header of each `e2store` entry.
+const HEADER_LEN: usize = 8;
+
+pub const VERSION: [u8; 2] = [0x65, 0x32];
+pub const COMPRESSED_SIGNED_BEACON_BLOCK: [u8; 2] = [0x01, 0x00];
+pub const COMPRESSED_BEACON_STATE: [u8; 2] = [0x02, 0x00];
+pub const SLOT_INDEX: [u8; 2] = [0x69, 0x32];
+
+/// The contents of an era file.
+pub struct Era<E: EthSpec> {
+    /// The blocks of the era, in slot order.
+    pub blocks: Vec<SignedBeaconBlock<E>>,
+    /// The state at the first slot of the next era.
+    pub state: BeaconState<E>,
+}
+
+impl<E: EthSpec> Era<E> {
+    /// Decode an era file.
+    pub fn from_bytes(bytes: &[u8], spec: &ChainSpec) -> Result<Self, Error> {
+        let mut entries = Entries { bytes };
+        match entries.next().transpose()? {
+            Some((VERSION, _)) => (),
+            _ => return Err(Error::EraError("missing version entry".into())),
+        }
+
+        let mut blocks = vec![];
+        let mut state = None;
+        for entry in entries {
+            let (entry_type, data) = entry?;
+            match entry_type {
+                COMPRESSED_SIGNED_BEACON_BLOCK => {
+                    let block = SignedBeaconBlock::from_ssz_bytes(&decompress(data)?, spec)?;
+                    if blocks.last().map_or(false, |prev: &SignedBeaconBlock<E>| {
+                        prev.slot() >= block.slot()
+                    }) {
+                        return Err(Error::EraError(format!(
+                            "block at slot {} is out of order",
+                            block.slot()
+                        )));
+                    }
+                    blocks.push(block);
+                }
+                COMPRESSED_BEACON_STATE if state.is_none() => {
+                    state = Some(BeaconState::from_ssz_bytes(&decompress(data)?, spec)?);
+                }
+                COMPRESSED_BEACON_STATE => {
+                    return Err(Error::EraError("more than one state".into()));
+                }
+                // Indices and unknown entries are skipped, as the file is read in full.
+                _ => (),
+            }
+        }
+
+        let state = state.ok_or_else(|| Error::EraError("missing state".into()))?;
+        if blocks
+            .last()
+            .map_or(false, |block| block.slot() >= state.slot())
+        {
+            return Err(Error::EraError("blocks are newer than the state".into()));
+        }
+        Ok(Self { blocks, state })
+    }
+
+    /// The era number, the index of the era's last slot in `historical_roots`.
+    pub fn number(&self) -> u64 {
+        self.state.slot().as_u64() / E::slots_per_historical_root() as u64
+    }
+
+    /// Encode the era file, with a slot index of the blocks and of the state.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = entry(VERSION, &[]);
+
+        let start_slot = era_start_slot::<E>(self.number());
+        let mut block_offsets = vec![];
+        if !self.blocks.is_empty() {
+            block_offsets.resize(E::slots_per_historical_root(), 0);
+        }
+        for block in &self.blocks {
+            let offset = block
+                .slot()
+                .as_usize()
+                .checked_sub(start_slot.as_usize())
+                .and_then(|index| block_offsets.get_mut(index))
+                .ok_or_else(|| {
+                    Error::EraError(format!("block at slot {} is outside the era", block.slot()))
+                })?;
+            *offset = bytes.len() as i64;
+            bytes.extend(entry(
+                COMPRESSED_SIGNED_BEACON_BLOCK,
+                &compress(&block.as_ssz_bytes())?,
+            ));
+        }
+
+        let state_offset = bytes.len() as i64;
+        bytes.extend(entry(
+            COMPRESSED_BEACON_STATE,
+            &compress(&self.state.as_ssz_bytes())?,
+        ));
+
+        if !block_offsets.is_empty() {
+            bytes.extend(slot_index(start_slot, &block_offsets, bytes.len()));
+        }
+        bytes.extend(slot_index(self.state.slot(), &[state_offset], bytes.len()));
+        Ok(bytes)
+    }
+
+    /// The conventional file name of the era file, `<config-name>-<era-number>-<short-root>.era`.
+    ///
+    /// The short root is the first 4 bytes of the era's historical root, or of the genesis
+    /// validators root for era 0.
+    pub fn file_name(&self, config_name: &str) -> Result<String, Error> {
+        let era = self.number();
+        let root = match era.checked_sub(1) {
+            None => self.state.genesis_validators_root(),
+            Some(index) => {
+                let historical_roots = self.state.historical_roots();
+                match historical_roots.get(index as usize) {
+                    Some(root) => *root,
+                    None => self
+                        .state
+                        .historical_summaries()
+                        .ok()
+                        .and_then(|summaries| {
+                            summaries.get(index as usize - historical_roots.len())
+                        })
+                        .map(|summary| summary.tree_hash_root())
+                        .ok_or_else(|| {
+                            Error::EraError(format!("missing historical root for era {}", era))
+                        })?,
+                }
+            }
+        };
+        Ok(format!(
+            "{}-{:05}-{}.era",
+            config_name,
+            era,
+            hex::encode(&root.as_bytes()[..4])
+        ))
+    }
+}
+
+/// Encode an `e2store` entry.
+fn entry(entry_type: [u8; 2], data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + data.len());
+    bytes.extend_from_slice(&entry_type);
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0, 0]);
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+/// Encode a slot index entry at `position` in the file.
+///
+/// Offsets are relative to the start of the index, and are 0 for slots without a value.
+fn slot_index(start_slot: Slot, offsets: &[i64], position: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity((offsets.len() + 2) * 8);
+    data.extend_from_slice(&start_slot.as_u64().to_le_bytes());
+    for offset in offsets {
+        let relative = if *offset == 0 {
+            0
+        } else {
+            offset - position as i64
+        };
+        data.extend_from_slice(&relative.to_le_bytes());
+    }
+    data.extend_from_slice(&(offsets.len() as i64).to_le_bytes());
+    entry(SLOT_INDEX, &data)
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = snap::write::FrameEncoder::new(vec![]);
+    encoder
+        .write_all(data)
+        .map_err(|e| Error::EraError(format!("snappy: {}", e)))?;
+    encoder
+        .into_inner()
+        .map_err(|e| Error::EraError(format!("snappy: {}", e)))
+}
+
+/// Iterator over the `(type, data)` entries of an `e2store` file.
+struct Entries<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<([u8; 2], &'a [u8]), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        if self.bytes.len() < HEADER_LEN {
+            self.bytes = &[];
+            return Some(Err(Error::EraError("truncated entry header".into())));
+        }
+        let (header, rest) = self.bytes.split_at(HEADER_LEN);
+        let entry_type = [header[0], header[1]];
+        let len = u32::from_le_bytes([header[2], header[3], header[4], header[5]]) as usize;
+        if header[6..] != [0, 0] || rest.len() < len {
+            self.bytes = &[];
+            return Some(Err(Error::EraError("invalid entry header".into())));
+        }
+        let (data, rest) = rest.split_at(len);
+        self.bytes = rest;
+        Some(Ok((entry_type, data)))
+    }
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut decompressed = vec![];
+    snap::read::FrameDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| Error::EraError(format!("snappy: {}", e)))?;
+    Ok(decompressed)
+}
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Store the blocks of an era which are older than the oldest block in the database.
+    ///
+    /// The blocks must form a chain of parent roots from the oldest block, so no other
+    /// verification is required. Returns the number of blocks stored.
+    pub fn import_era_blocks(&self, blocks: Vec<SignedBeaconBlock<E>>) -> Result<usize, Error> {
+        let anchor_info = match self.get_anchor_info() {
+            Some(anchor_info) if anchor_info.oldest_block_slot > self.spec.genesis_slot => {
+                anchor_info
+            }
+            // All blocks are already stored.
+            _ => return Ok(0),
+        };
+
+        let mut expected_block_root = anchor_info.oldest_block_parent;
+        let mut prev_block_slot = anchor_info.oldest_block_slot;
+        let mut chunk_writer =
+            ChunkWriter::<BlockRoots, _, _>::new(&self.cold_db, prev_block_slot.as_usize())?;
+        let mut hot_batch = vec![];
+        let mut cold_batch = vec![];
+        let mut imported = 0;
+
+        for block in blocks
+            .into_iter()
+            .rev()
+            .skip_while(|block| block.slot() >= anchor_info.oldest_block_slot)
+        {
+            let block_root = block.canonical_root();
+            let slot = block.slot();
+            if block_root != expected_block_root {
+                return Err(Error::EraError(format!(
+                    "block {:?} at slot {} doesn't match the expected root {:?}",
+                    block_root, slot, expected_block_root
+                )));
+            }
+
+            expected_block_root = block.parent_root();
+            self.block_as_kv_store_ops(&block_root, block, &mut hot_batch)?;
+            for skipped_slot in (slot.as_usize()..prev_block_slot.as_usize()).rev() {
+                chunk_writer.set(skipped_slot, block_root, &mut cold_batch)?;
+            }
+            imported += 1;
+            prev_block_slot = slot;
+
+            // The genesis block is stored when the database is initialised.
+            if let Some(genesis_block) = self
+                .get_blinded_block(&expected_block_root)?
+                .filter(|parent| parent.slot() == self.spec.genesis_slot)
+            {
+                chunk_writer.set(
+                    genesis_block.slot().as_usize(),
+                    expected_block_root,
+                    &mut cold_batch,
+                )?;
+                prev_block_slot = genesis_block.slot();
+                expected_block_root = Hash256::zero();
+                break;
+            }
+        }
+        chunk_writer.write(&mut cold_batch)?;
+
+        if imported == 0 {
+            return Ok(0);
+        }
+
+        self.hot_db.do_atomically(hot_batch)?;
+        self.cold_db.do_atomically(cold_batch)?;
+        self.compare_and_set_anchor_info_with_write(
+            Some(anchor_info.clone()),
+            Some(AnchorInfo {
+                oldest_block_slot: prev_block_slot,
+                oldest_block_parent: expected_block_root,
+                ..anchor_info
+            }),
+        )?;
+
+        debug!(
+            self.log,
+            "Imported era blocks";
+            "count" => imported,
+            "oldest_block_slot" => prev_block_slot,
+        );
+        Ok(imported)
+    }
+
+    /// Store the state at the end of an era as a restore point, so that historic states are
+    /// available from its slot onwards.
+    ///
+    /// The root of the state must match the state root stored in the freezer for its slot, which
+    /// is known once the restore point at the end of the next era has been stored. Restore points
+    /// between the state and the `state_upper_limit` are regenerated by replaying blocks, so the
+    /// blocks of the era after the state must already be stored.
+    ///
+    /// Returns `false` if the state wasn't stored, because states at its slot are already
+    /// available or because its state root isn't known yet.
+    pub fn import_era_state(&self, mut state: BeaconState<E>) -> Result<bool, Error> {
+        let anchor_info = match self.get_anchor_info() {
+            Some(anchor_info) if !anchor_info.historic_states_pruned() => anchor_info,
+            // All states are already stored, or historic states aren't stored at all.
+            _ => return Ok(false),
+        };
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let slot = state.slot();
+        if slot <= anchor_info.state_lower_limit || slot >= anchor_info.state_upper_limit {
+            return Ok(false);
+        }
+        if slot % slots_per_restore_point != 0 {
+            return Err(Error::EraError(format!(
+                "state at slot {} is not at a restore point",
+                slot
+            )));
+        }
+
+        let era_state_root = match self.load_cold_vector_root::<StateRoots>(slot)? {
+            Some(state_root) => state_root,
+            None => return Ok(false),
+        };
+        let mut expected_state_root = era_state_root;
+        let mut ops = vec![];
+        loop {
+            let state_root = state.update_tree_hash_cache()?;
+            if state_root != expected_state_root {
+                return Err(Error::EraError(format!(
+                    "state {:?} at slot {} doesn't match the expected root {:?}",
+                    state_root,
+                    state.slot(),
+                    expected_state_root
+                )));
+            }
+            let pending_snapshot = self.store_cold_state(&state_root, &state, &mut ops)?;
+            self.cold_db.do_atomically(std::mem::take(&mut ops))?;
+            self.cache_state_snapshot(pending_snapshot);
+
+            let next_slot = state.slot() + slots_per_restore_point;
+            if next_slot >= anchor_info.state_upper_limit {
+                break;
+            }
+            let (block_root, state_root) = match (
+                self.load_cold_vector_root::<BlockRoots>(next_slot)?,
+                self.load_cold_vector_root::<StateRoots>(next_slot)?,
+            ) {
+                (Some(block_root), Some(state_root)) => (block_root, state_root),
+                _ => {
+                    return Err(Error::EraError(format!(
+                        "missing block or state root at slot {}",
+                        next_slot
+                    )))
+                }
+            };
+            let blocks = self.load_blocks_to_replay(state.slot(), next_slot, block_root)?;
+            state = self.replay_blocks(
+                state,
+                blocks,
+                next_slot,
+                None::<std::iter::Empty<_>>,
+                StateRootStrategy::Accurate,
+            )?;
+            expected_state_root = state_root;
+        }
+
+        // Once the restore points meet and every block is stored, all states are available.
+        let new_anchor = if slot <= anchor_info.state_lower_limit + slots_per_restore_point
+            && anchor_info.oldest_block_slot == self.spec.genesis_slot
+        {
+            None
+        } else {
+            Some(AnchorInfo {
+                state_upper_limit: slot,
+                ..anchor_info.clone()
+            })
+        };
+        self.compare_and_set_anchor_info_with_write(Some(anchor_info), new_anchor)?;
+
+        debug!(
+            self.log,
+            "Imported era state";
+            "slot" => slot,
+            "state_root" => format!("{:?}", era_state_root),
+        );
+        Ok(true)
+    }
+
+    /// Load the value at `slot` of a vector of roots in the freezer, if it has been stored.
+    fn load_cold_vector_root<F: Field<E, Value = Hash256>>(
+        &self,
+        slot: Slot,
+    ) -> Result<Option<Hash256>, Error> {
+        let chunk = Chunk::<Hash256>::load(
+            &self.cold_db,
+            F::column(),
+            &chunk_key(F::chunk_index(slot.as_usize())),
+        )?;
+        Ok(chunk
+            .and_then(|chunk| chunk.values.get(slot.as_usize() % F::chunk_size()).copied())
+            .filter(|root| !root.is_zero()))
+    }
+}
+
+/// The first slot of the blocks of `era`.
+pub fn era_start_slot<E: EthSpec>(era: u64) -> Slot {
+    Slot::new(era.saturating_sub(1) * E::slots_per_historical_root() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{Eth1Data, MinimalEthSpec};
+
+    #[test]
+    fn roundtrip() {
+        let spec = MinimalEthSpec::default_spec();
+        let mut state = BeaconState::<MinimalEthSpec>::new(0, Eth1Data::default(), &spec);
+        *state.slot_mut() = Slot::new(MinimalEthSpec::slots_per_historical_root() as u64);
+
+        let bytes = Era {
+            blocks: vec![],
+            state: state.clone(),
+        }
+        .to_bytes()
+        .unwrap();
+
+        let era = Era::<MinimalEthSpec>::from_bytes(&bytes, &spec).unwrap();
+        assert!(era.blocks.is_empty());
+        assert_eq!(era.state.slot(), state.slot());
+        assert_eq!(era.number(), 1);
+
+        assert!(Era::<MinimalEthSpec>::from_bytes(&bytes[HEADER_LEN..], &spec).is_err());
+        assert!(Era::<MinimalEthSpec>::from_bytes(&bytes[..bytes.len() - 1], &spec).is_err());
+    }
+}
@@ -45,6 +45,12 @@ pub enum Error {
     SlotClockUnavailableForMigration,
     UnableToDowngrade,
     InconsistentFork(InconsistentFork),
+    /// A freezer state diff couldn't be encoded or applied.
+    StateDiffError(String),
+    /// A freezer state couldn't be compressed or decompressed.
+    CompressionError(String),
+    /// An era file is invalid, or its blocks don't match the database.
+    EraError(String),
 }
 
 pub trait HandleUnavailable<T> {
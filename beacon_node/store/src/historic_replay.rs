@@ -0,0 +1,175 @@
+//! Serve historic states which aren't stored, e.g. after checkpoint sync, by replaying blocks on
+//! the latest stored state before them.
+use crate::chunked_vector::{chunk_key, BlockRoots, Chunk, Field, StateRoots};
+use crate::hot_cold_store::{HotColdDB, HotColdDBError};
+use crate::{Error, ItemStore};
+use slog::debug;
+use state_processing::BlockReplayer;
+use std::time::{Duration, Instant};
+use types::{BeaconState, EthSpec, Hash256, Slot};
+
+/// The maximum time to spend replaying blocks to serve a single historic state.
+pub const HISTORIC_STATE_REPLAY_TIME_BUDGET: Duration = Duration::from_secs(30);
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Load the canonical state at `slot` and its root, if it lies in the gap in the historic
+    /// states and can be replayed within the configured limit.
+    ///
+    /// The replay starts from the latest state at or before `slot` which is available: a restore
+    /// point, the state at the lower limit of the gap, or a previously replayed state. It requires
+    /// all of the blocks after that state, so `None` is returned until backfill sync has reached
+    /// it. Replayed states are cached, so that they can be loaded by root afterwards.
+    ///
+    /// An error is returned if the replay takes longer than `HISTORIC_STATE_REPLAY_TIME_BUDGET`.
+    pub fn load_historic_state_by_replay(
+        &self,
+        slot: Slot,
+    ) -> Result<Option<(Hash256, BeaconState<E>)>, Error> {
+        let (lower_limit, upper_limit) = self.get_historic_state_limits();
+        if slot <= lower_limit || slot >= upper_limit {
+            return Ok(None);
+        }
+
+        if let Some(cached) = self.replayed_state_cache.lock().get(&slot) {
+            return Ok(Some(cached.clone()));
+        }
+
+        let replay_limit = self.config.historic_state_replay_limit;
+        if replay_limit == 0 {
+            return Ok(None);
+        }
+        let min_base_slot = std::cmp::max(lower_limit, slot.saturating_sub(replay_limit));
+        let block_root = match self.load_frozen_block_root(slot)? {
+            Some(block_root) => block_root,
+            None => return Ok(None),
+        };
+
+        let timer = Instant::now();
+        let base_state = match self.load_replay_base_state(slot, min_base_slot)? {
+            Some(state) if self.get_oldest_block_slot() <= state.slot() => state,
+            _ => {
+                debug!(
+                    self.log,
+                    "Historic state unavailable";
+                    "slot" => slot,
+                    "lower_limit" => lower_limit,
+                    "replay_limit" => replay_limit,
+                );
+                return Ok(None);
+            }
+        };
+        let base_slot = base_state.slot();
+        let blocks = self.load_blocks_to_replay(base_slot, slot, block_root)?;
+        let num_blocks = blocks.len();
+        let mut state = BlockReplayer::<E, Error, _>::new(base_state, &self.spec)
+            .no_signature_verification()
+            .minimal_block_root_verification()
+            .no_state_root_iter()
+            .pre_slot_hook(Box::new(|_| {
+                if timer.elapsed() > HISTORIC_STATE_REPLAY_TIME_BUDGET {
+                    Err(Error::from(HotColdDBError::HistoricStateReplayTimeout(
+                        slot,
+                    )))
+                } else {
+                    Ok(())
+                }
+            }))
+            .apply_blocks(blocks, Some(slot))?
+            .into_state();
+        let state_root = state.update_tree_hash_cache()?;
+
+        debug!(
+            self.log,
+            "Replayed historic state";
+            "slot" => slot,
+            "base_slot" => base_slot,
+            "blocks" => num_blocks,
+            "time_ms" => timer.elapsed().as_millis(),
+        );
+        self.replayed_state_cache
+            .lock()
+            .put(slot, (state_root, state.clone()));
+        Ok(Some((state_root, state)))
+    }
+
+    /// Load the latest available state with a slot between `min_slot` and `slot`, to replay
+    /// blocks on in order to reach `slot`.
+    fn load_replay_base_state(
+        &self,
+        slot: Slot,
+        min_slot: Slot,
+    ) -> Result<Option<BeaconState<E>>, Error> {
+        let cached = self
+            .replayed_state_cache
+            .lock()
+            .iter()
+            .filter(|(cached_slot, _)| **cached_slot >= min_slot && **cached_slot < slot)
+            .max_by_key(|(cached_slot, _)| **cached_slot)
+            .map(|(_, (_, state))| state.clone());
+        let min_slot = cached
+            .as_ref()
+            .map_or(min_slot, |state| std::cmp::max(min_slot, state.slot() + 1));
+
+        // Restore points may be stored within the gap, e.g. by a partial reconstruction.
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let min_index = (min_slot.as_u64() + slots_per_restore_point - 1) / slots_per_restore_point;
+        let max_index = slot.as_u64() / slots_per_restore_point;
+        for index in (min_index..=max_index).rev() {
+            match self.load_restore_point_by_index(index) {
+                Ok(state) => return Ok(Some(state)),
+                Err(Error::HotColdDBError(
+                    HotColdDBError::MissingRestorePointHash(_)
+                    | HotColdDBError::MissingRestorePoint(_),
+                )) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(cached)
+    }
+
+    /// Return a replayed historic state from the cache.
+    pub(crate) fn get_replayed_state(&self, state_root: &Hash256) -> Option<BeaconState<E>> {
+        self.replayed_state_cache
+            .lock()
+            .iter()
+            .find(|(_, (root, _))| root == state_root)
+            .map(|(_, (_, state))| state.clone())
+    }
+
+    /// Load the root of the canonical block at `slot` from the freezer database.
+    ///
+    /// Returns `None` if the block roots at `slot` haven't been stored.
+    fn load_frozen_block_root(&self, slot: Slot) -> Result<Option<Hash256>, Error> {
+        self.load_frozen_root::<BlockRoots>(slot)
+    }
+
+    /// Load the root of the canonical state at `slot` from the freezer database, without loading
+    /// or replaying the state.
+    ///
+    /// Returns `None` if the state roots at `slot` haven't been stored, which may be the case for
+    /// slots in the gap in the historic states.
+    pub fn load_frozen_state_root(&self, slot: Slot) -> Result<Option<Hash256>, Error> {
+        self.load_frozen_root::<StateRoots>(slot)
+    }
+
+    fn load_frozen_root<F: Field<E, Value = Hash256>>(
+        &self,
+        slot: Slot,
+    ) -> Result<Option<Hash256>, Error> {
+        let vindex = slot.as_usize();
+        let chunk = Chunk::<Hash256>::load(
+            &self.cold_db,
+            F::column(),
+            &chunk_key(F::chunk_index(vindex)),
+        )?;
+        Ok(chunk
+            .and_then(|chunk| chunk.values.get(vindex % F::chunk_size()).copied())
+            .filter(|root| !root.is_zero()))
+    }
+}
@@ -1,28 +1,31 @@
+use crate::backend::BeaconNodeBackend;
 use crate::chunked_vector::{
     store_updated_vector, BlockRoots, HistoricalRoots, HistoricalSummaries, RandaoMixes, StateRoots,
 };
+use crate::compression;
 use crate::config::{
-    OnDiskStoreConfig, StoreConfig, DEFAULT_SLOTS_PER_RESTORE_POINT,
+    FreezerCompression, OnDiskStoreConfig, StoreConfig, DEFAULT_SLOTS_PER_RESTORE_POINT,
     PREV_DEFAULT_SLOTS_PER_RESTORE_POINT,
 };
 use crate::forwards_iter::{HybridForwardsBlockRootsIterator, HybridForwardsStateRootsIterator};
 use crate::impls::beacon_state::{get_full_state, store_full_state};
 use crate::iter::{BlockRootsIterator, ParentRootBlockIterator, RootsIterator};
 use crate::leveldb_store::BytesKey;
-use crate::leveldb_store::LevelDB;
 use crate::memory_store::MemoryStore;
 use crate::metadata::{
-    AnchorInfo, CompactionTimestamp, PruningCheckpoint, SchemaVersion, ANCHOR_INFO_KEY,
-    COMPACTION_TIMESTAMP_KEY, CONFIG_KEY, CURRENT_SCHEMA_VERSION, PRUNING_CHECKPOINT_KEY,
-    SCHEMA_VERSION_KEY, SPLIT_KEY,
+    AnchorInfo, CompactionTimestamp, FreezerCompressionFlag, PruningCheckpoint, SchemaVersion,
+    ANCHOR_INFO_KEY, COMPACTION_TIMESTAMP_KEY, CONFIG_KEY, CURRENT_FREEZER_SCHEMA_VERSION,
+    CURRENT_SCHEMA_VERSION, FREEZER_COMPRESSION_KEY, FREEZER_COMPRESSION_SCHEMA_VERSION,
+    FREEZER_SCHEMA_VERSION_KEY, PRUNING_CHECKPOINT_KEY, SCHEMA_VERSION_KEY, SPLIT_KEY,
+    STATE_UPPER_LIMIT_NO_RETAIN,
 };
 use crate::metrics;
+use crate::state_diff::PartialStateDiff;
 use crate::{
-    get_key_for_col, DBColumn, DatabaseBlock, Error, ItemStore, KeyValueStoreOp,
+    get_key_for_col, DBColumn, DatabaseBlock, Error, ItemStore, KeyValueStore, KeyValueStoreOp,
     PartialBeaconState, StoreItem, StoreOp,
 };
 use itertools::process_results;
-use leveldb::iterator::LevelDBIterator;
 use lru::LruCache;
 use parking_lot::{Mutex, RwLock};
 use serde_derive::{Deserialize, Serialize};
@@ -32,7 +35,8 @@ use ssz_derive::{Decode, Encode};
 use state_processing::{
     BlockProcessingError, BlockReplayer, SlotProcessingError, StateRootStrategy,
 };
-use std::cmp::min;
+use std::cmp::{max, min};
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::marker::PhantomData;
 use std::path::Path;
@@ -62,6 +66,10 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     pub hot_db: Hot,
     /// LRU cache of deserialized blocks. Updated whenever a block is loaded.
     block_cache: Mutex<LruCache<Hash256, SignedBeaconBlock<E>>>,
+    /// The most recently used freezer snapshot, which restore points are stored as diffs against.
+    state_snapshot_cache: Mutex<Option<(Hash256, Arc<PartialBeaconState<E>>)>>,
+    /// LRU cache of historic states which aren't stored and were replayed on demand, by slot.
+    pub(crate) replayed_state_cache: Mutex<LruCache<Slot, (Hash256, BeaconState<E>)>>,
     /// Chain spec.
     pub(crate) spec: ChainSpec,
     /// Logger.
@@ -76,6 +84,15 @@ pub enum HotColdDBError {
         target_version: SchemaVersion,
         current_version: SchemaVersion,
     },
+    /// The freezer database was written by a newer version of Lighthouse.
+    UnsupportedFreezerSchemaVersion {
+        on_disk: SchemaVersion,
+        supported: SchemaVersion,
+    },
+    /// The freezer database holds compressed restore points, which can't be read.
+    UnsupportedFreezerCompression {
+        supported: SchemaVersion,
+    },
     /// Recoverable error indicating that the database freeze point couldn't be updated
     /// due to the finalized block not lying on an epoch boundary (should be infrequent).
     FreezeSlotUnaligned(Slot),
@@ -99,6 +116,13 @@ pub enum HotColdDBError {
     BlockReplaySlotError(SlotProcessingError),
     BlockReplayBlockError(BlockProcessingError),
     MissingLowerLimitState(Slot),
+    /// The genesis state supplied for pruning isn't the genesis state of the database.
+    GenesisStateRootMismatch {
+        stored: Hash256,
+        supplied: Hash256,
+    },
+    /// Replaying blocks to serve the historic state at the slot took too long.
+    HistoricStateReplayTimeout(Slot),
     InvalidSlotsPerRestorePoint {
         slots_per_restore_point: u64,
         slots_per_historical_root: u64,
@@ -129,6 +153,8 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
             cold_db: MemoryStore::open(),
             hot_db: MemoryStore::open(),
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            state_snapshot_cache: Mutex::new(None),
+            replayed_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
@@ -139,7 +165,7 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
     }
 }
 
-impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
+impl<E: EthSpec> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>> {
     /// Open a new or existing database, with the given paths to the hot and cold DBs.
     ///
     /// The `slots_per_restore_point` parameter must be a divisor of `SLOTS_PER_HISTORICAL_ROOT`.
@@ -159,9 +185,11 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         let mut db = HotColdDB {
             split: RwLock::new(Split::default()),
             anchor_info: RwLock::new(None),
-            cold_db: LevelDB::open(cold_path)?,
-            hot_db: LevelDB::open(hot_path)?,
+            cold_db: BeaconNodeBackend::open(config.backend, cold_path)?,
+            hot_db: BeaconNodeBackend::open(config.backend, hot_path)?,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            state_snapshot_cache: Mutex::new(None),
+            replayed_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
@@ -203,6 +231,16 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
             );
         }
 
+        // The freezer database is versioned separately from the hot database, and migrated by the
+        // hot database's migrations. Refuse to open a freezer written by a newer version before
+        // the hot database is migrated.
+        let freezer_schema_version = db.load_freezer_schema_version()?;
+        check_freezer_schema_version(
+            freezer_schema_version,
+            db.load_freezer_compression_flag()?,
+            CURRENT_FREEZER_SCHEMA_VERSION,
+        )?;
+
         // Ensure that the schema version of the on-disk database matches the software.
         // If the version is mismatched, an automatic migration will be attempted.
         let db = Arc::new(db);
@@ -217,10 +255,28 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         } else {
             db.store_schema_version(CURRENT_SCHEMA_VERSION)?;
         }
+        if freezer_schema_version.is_none() {
+            db.store_freezer_schema_version(CURRENT_FREEZER_SCHEMA_VERSION)?;
+        }
+        if db.config.freezer_compression != FreezerCompression::None
+            && !db.load_freezer_compression_flag()?
+        {
+            db.store_freezer_compression_flag(true)?;
+        }
 
-        // Ensure that any on-disk config is compatible with the supplied config.
-        if let Some(disk_config) = db.load_config()? {
-            db.config.check_compatibility(&disk_config)?;
+        // Ensure that any on-disk config is compatible with the supplied config. If the
+        // slots-per-restore-point has been changed explicitly, re-index the freezer database.
+        //
+        // The freezer's copy of the config is written atomically with any re-indexing, so it takes
+        // precedence over the hot database's copy, which is only written afterwards.
+        if let Some(disk_config) = db.load_freezer_config()?.or(db.load_config()?) {
+            if db.config.slots_per_restore_point_set_explicitly
+                && db.config.slots_per_restore_point != disk_config.slots_per_restore_point
+            {
+                db.reanchor_restore_points(disk_config.slots_per_restore_point)?;
+            } else {
+                db.config.check_compatibility(&disk_config)?;
+            }
         }
         db.store_config()?;
 
@@ -239,23 +295,7 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
 
     /// Return an iterator over the state roots of all temporary states.
     pub fn iter_temporary_state_roots(&self) -> impl Iterator<Item = Result<Hash256, Error>> + '_ {
-        let column = DBColumn::BeaconStateTemporary;
-        let start_key =
-            BytesKey::from_vec(get_key_for_col(column.into(), Hash256::zero().as_bytes()));
-
-        let keys_iter = self.hot_db.keys_iter();
-        keys_iter.seek(&start_key);
-
-        keys_iter
-            .take_while(move |key| key.matches_column(column))
-            .map(move |bytes_key| {
-                bytes_key.remove_column(column).ok_or_else(|| {
-                    HotColdDBError::IterationError {
-                        unexpected_key: bytes_key,
-                    }
-                    .into()
-                })
-            })
+        self.hot_db.iter_column_keys(DBColumn::BeaconStateTemporary)
     }
 }
 
@@ -327,6 +367,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             metrics::inc_counter(&metrics::BEACON_BLOCK_CACHE_HIT_COUNT);
             return Ok(Some(DatabaseBlock::Full(block.clone())));
         }
+        metrics::inc_counter(&metrics::BEACON_BLOCK_CACHE_MISS_COUNT);
 
         // Load the blinded block.
         let blinded_block = match self.get_blinded_block(block_root)? {
@@ -496,8 +537,10 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     pub fn put_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
         let mut ops: Vec<KeyValueStoreOp> = Vec::new();
         if state.slot() < self.get_split_slot() {
-            self.store_cold_state(state_root, state, &mut ops)?;
-            self.cold_db.do_atomically(ops)
+            let pending_snapshot = self.store_cold_state(state_root, state, &mut ops)?;
+            self.cold_db.do_atomically(ops)?;
+            self.cache_state_snapshot(pending_snapshot);
+            Ok(())
         } else {
             self.store_hot_state(state_root, state, &mut ops)?;
             self.hot_db.do_atomically(ops)
@@ -525,7 +568,14 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
                 // frozen state using `load_cold_state_by_slot`, that would be incorrect
                 // in the case where the caller provides a `state_root` that's off the canonical
                 // chain. This way we avoid returning a state that doesn't match `state_root`.
-                self.load_cold_state(state_root)
+                match self.load_cold_state(state_root)? {
+                    Some(state) => Ok(Some(state)),
+                    // A canonical state which isn't stored may be replayed.
+                    None if self.load_frozen_state_root(slot)? == Some(*state_root) => Ok(self
+                        .load_historic_state_by_replay(slot)?
+                        .map(|(_, state)| state)),
+                    None => Ok(None),
+                }
             } else {
                 self.load_hot_state(state_root, StateRootStrategy::Accurate)
             }
@@ -876,16 +926,21 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     /// Store a pre-finalization state in the freezer database.
     ///
     /// If the state doesn't lie on a restore point boundary then just its summary will be stored.
+    /// Restore points are stored as diffs against the latest snapshot, unless they are due to be a
+    /// snapshot themselves or there is no suitable snapshot.
+    ///
+    /// If a snapshot is stored then it is returned, and should be passed to `cache_state_snapshot`
+    /// once `ops` have been written.
     pub fn store_cold_state(
         &self,
         state_root: &Hash256,
         state: &BeaconState<E>,
         ops: &mut Vec<KeyValueStoreOp>,
-    ) -> Result<(), Error> {
+    ) -> Result<Option<PendingStateSnapshot<E>>, Error> {
         ops.push(ColdStateSummary { slot: state.slot() }.as_kv_store_op(*state_root));
 
         if state.slot() % self.config.slots_per_restore_point != 0 {
-            return Ok(());
+            return Ok(None);
         }
 
         trace!(
@@ -895,10 +950,28 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             "state_root" => format!("{:?}", state_root)
         );
 
-        // 1. Convert to PartialBeaconState and store that in the DB.
+        // 1. Convert to PartialBeaconState and store that in the DB, either in full or as a diff.
+        let restore_point_index = state.slot().as_u64() / self.config.slots_per_restore_point;
         let partial_state = PartialBeaconState::from_state_forgetful(state);
-        let op = partial_state.as_kv_store_op(*state_root);
-        ops.push(op);
+        let pending_snapshot = match self.get_state_diff_base(restore_point_index)? {
+            Some((base_state_root, base)) => {
+                let diff = PartialStateDiff::compute(base_state_root, &base, partial_state);
+                ops.push(KeyValueStoreOp::PutKeyValue(
+                    get_key_for_col(DBColumn::BeaconStateDiff.into(), state_root.as_bytes()),
+                    diff.as_compressed_bytes(self.config.compression_level)?,
+                ));
+                None
+            }
+            None => {
+                ops.push(
+                    self.state_snapshot_as_kv_store_op(state_root, partial_state.as_ssz_bytes())?,
+                );
+                (self.config.restore_points_per_snapshot > 1).then(|| PendingStateSnapshot {
+                    state_root: *state_root,
+                    snapshot: Arc::new(partial_state),
+                })
+            }
+        };
 
         // 2. Store updated vector entries.
         let db = &self.cold_db;
@@ -909,10 +982,21 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         store_updated_vector(HistoricalSummaries, db, state, &self.spec, ops)?;
 
         // 3. Store restore point.
-        let restore_point_index = state.slot().as_u64() / self.config.slots_per_restore_point;
         self.store_restore_point_hash(restore_point_index, *state_root, ops);
 
-        Ok(())
+        Ok(pending_snapshot)
+    }
+
+    /// Cache a snapshot returned by `store_cold_state`, which must have been written to the
+    /// freezer database.
+    pub fn cache_state_snapshot(&self, pending_snapshot: Option<PendingStateSnapshot<E>>) {
+        if let Some(PendingStateSnapshot {
+            state_root,
+            snapshot,
+        }) = pending_snapshot
+        {
+            *self.state_snapshot_cache.lock() = Some((state_root, snapshot));
+        }
     }
 
     /// Try to load a pre-finalization state from the freezer database.
@@ -921,7 +1005,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     pub fn load_cold_state(&self, state_root: &Hash256) -> Result<Option<BeaconState<E>>, Error> {
         match self.load_cold_state_slot(state_root)? {
             Some(slot) => self.load_cold_state_by_slot(slot),
-            None => Ok(None),
+            None => Ok(self.get_replayed_state(state_root)),
         }
     }
 
@@ -947,14 +1031,147 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         }
     }
 
-    /// Load a restore point state by its `state_root`.
-    fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
+    /// Return the snapshot that the restore point with `restore_point_index` should be stored as a
+    /// diff against, or `None` if it should be stored in full.
+    ///
+    /// The snapshot must lie between the previous multiple of `restore_points_per_snapshot` and
+    /// the restore point, so that the diff stays small.
+    #[allow(clippy::type_complexity)]
+    fn get_state_diff_base(
+        &self,
+        restore_point_index: u64,
+    ) -> Result<Option<(Hash256, Arc<PartialBeaconState<E>>)>, Error> {
+        let restore_points_per_snapshot = self.config.restore_points_per_snapshot;
+        if restore_points_per_snapshot <= 1
+            || restore_point_index % restore_points_per_snapshot == 0
+        {
+            return Ok(None);
+        }
+        let first_index = restore_point_index - restore_point_index % restore_points_per_snapshot;
+        let min_slot = first_index * self.config.slots_per_restore_point;
+        let max_slot = restore_point_index * self.config.slots_per_restore_point;
+
+        if let Some((state_root, snapshot)) = self.state_snapshot_cache.lock().as_ref() {
+            if snapshot.slot() >= min_slot && snapshot.slot() < max_slot {
+                return Ok(Some((*state_root, snapshot.clone())));
+            }
+        }
+
+        // The cache is empty after a restart, so fall back to the first snapshot of the period.
+        let state_root = match self
+            .cold_db
+            .get::<RestorePointHash>(&Self::restore_point_key(first_index))?
+        {
+            Some(restore_point) => restore_point.state_root,
+            None => return Ok(None),
+        };
+        if !self.state_snapshot_exists(&state_root)? {
+            return Ok(None);
+        }
+        self.load_state_snapshot(&state_root)
+            .map(|snapshot| Some((state_root, snapshot)))
+    }
+
+    /// Load a restore point which is stored in full, via the snapshot cache.
+    fn load_state_snapshot(
+        &self,
+        state_root: &Hash256,
+    ) -> Result<Arc<PartialBeaconState<E>>, Error> {
+        if let Some((cached_root, snapshot)) = self.state_snapshot_cache.lock().as_ref() {
+            if cached_root == state_root {
+                return Ok(snapshot.clone());
+            }
+        }
+
         let partial_state_bytes = self
+            .load_state_snapshot_bytes(state_root)?
+            .ok_or(HotColdDBError::MissingRestorePoint(*state_root))?;
+        let snapshot = Arc::new(PartialBeaconState::from_ssz_bytes(
+            &partial_state_bytes,
+            &self.spec,
+        )?);
+        *self.state_snapshot_cache.lock() = Some((*state_root, snapshot.clone()));
+        Ok(snapshot)
+    }
+
+    /// Stage the SSZ bytes of a restore point which is stored in full, compressed if configured.
+    fn state_snapshot_as_kv_store_op(
+        &self,
+        state_root: &Hash256,
+        partial_state_bytes: Vec<u8>,
+    ) -> Result<KeyValueStoreOp, Error> {
+        Ok(match self.config.freezer_compression {
+            FreezerCompression::None => KeyValueStoreOp::PutKeyValue(
+                get_key_for_col(DBColumn::BeaconState.into(), state_root.as_bytes()),
+                partial_state_bytes,
+            ),
+            freezer_compression => KeyValueStoreOp::PutKeyValue(
+                get_key_for_col(
+                    DBColumn::BeaconStateCompressed.into(),
+                    state_root.as_bytes(),
+                ),
+                compression::compress(
+                    freezer_compression,
+                    self.config.compression_level,
+                    &partial_state_bytes,
+                )?,
+            ),
+        })
+    }
+
+    /// Load the SSZ bytes of a restore point which is stored in full, whether compressed or not.
+    fn load_state_snapshot_bytes(&self, state_root: &Hash256) -> Result<Option<Vec<u8>>, Error> {
+        if let Some(bytes) = self.cold_db.get_bytes(
+            DBColumn::BeaconStateCompressed.into(),
+            state_root.as_bytes(),
+        )? {
+            return compression::decompress(&bytes).map(Some);
+        }
+        self.cold_db
+            .get_bytes(DBColumn::BeaconState.into(), state_root.as_bytes())
+    }
+
+    /// Return `true` if a restore point is stored in full with `state_root`.
+    fn state_snapshot_exists(&self, state_root: &Hash256) -> Result<bool, Error> {
+        Ok(self
             .cold_db
-            .get_bytes(DBColumn::BeaconState.into(), state_root.as_bytes())?
+            .key_exists(DBColumn::BeaconState.into(), state_root.as_bytes())?
+            || self.cold_db.key_exists(
+                DBColumn::BeaconStateCompressed.into(),
+                state_root.as_bytes(),
+            )?)
+    }
+
+    /// Load the partial state of a restore point, which is stored either in full or as a diff.
+    ///
+    /// Return `None` if there is no restore point with `state_root`.
+    fn load_restore_point_partial_state(
+        &self,
+        state_root: &Hash256,
+    ) -> Result<Option<PartialBeaconState<E>>, Error> {
+        if let Some(partial_state_bytes) = self.load_state_snapshot_bytes(state_root)? {
+            return PartialBeaconState::from_ssz_bytes(&partial_state_bytes, &self.spec)
+                .map(Some)
+                .map_err(Into::into);
+        }
+
+        let diff_bytes = match self
+            .cold_db
+            .get_bytes(DBColumn::BeaconStateDiff.into(), state_root.as_bytes())?
+        {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let diff = PartialStateDiff::from_compressed_bytes(&diff_bytes)?;
+        let base = self.load_state_snapshot(&diff.base_state_root)?;
+        diff.apply(&base, &self.spec).map(Some)
+    }
+
+    /// Load a restore point state by its `state_root`.
+    fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
+        let mut partial_state = self
+            .load_restore_point_partial_state(state_root)?
             .ok_or(HotColdDBError::MissingRestorePoint(*state_root))?;
-        let mut partial_state: PartialBeaconState<E> =
-            PartialBeaconState::from_ssz_bytes(&partial_state_bytes, &self.spec)?;
 
         // Fill in the fields of the partial state.
         partial_state.load_block_roots(&self.cold_db, &self.spec)?;
@@ -967,7 +1184,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load a restore point state by its `restore_point_index`.
-    fn load_restore_point_by_index(
+    pub(crate) fn load_restore_point_by_index(
         &self,
         restore_point_index: u64,
     ) -> Result<BeaconState<E>, Error> {
@@ -1090,7 +1307,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     ///
     /// Will skip slots as necessary. The returned state is not guaranteed
     /// to have any caches built, beyond those immediately required by block processing.
-    fn replay_blocks(
+    pub(crate) fn replay_blocks(
         &self,
         state: BeaconState<E>,
         blocks: Vec<SignedBeaconBlock<E, BlindedPayload<E>>>,
@@ -1154,7 +1371,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load the database schema version from disk.
-    fn load_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
+    pub fn load_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
         self.hot_db.get(&SCHEMA_VERSION_KEY)
     }
 
@@ -1163,6 +1380,36 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         self.hot_db.put(&SCHEMA_VERSION_KEY, &schema_version)
     }
 
+    /// Load the schema version of the freezer database from disk.
+    ///
+    /// Freezer databases created before the freezer was versioned have no version stored, and
+    /// are at version 1.
+    pub fn load_freezer_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
+        self.cold_db.get(&FREEZER_SCHEMA_VERSION_KEY)
+    }
+
+    /// Store the schema version of the freezer database.
+    pub fn store_freezer_schema_version(&self, schema_version: SchemaVersion) -> Result<(), Error> {
+        self.cold_db
+            .put(&FREEZER_SCHEMA_VERSION_KEY, &schema_version)
+    }
+
+    /// Load whether the freezer database may hold compressed restore points.
+    pub fn load_freezer_compression_flag(&self) -> Result<bool, Error> {
+        Ok(self
+            .cold_db
+            .get::<FreezerCompressionFlag>(&FREEZER_COMPRESSION_KEY)?
+            .map_or(false, |flag| flag.0))
+    }
+
+    /// Store whether the freezer database may hold compressed restore points.
+    pub fn store_freezer_compression_flag(&self, compressed: bool) -> Result<(), Error> {
+        self.cold_db.put(
+            &FREEZER_COMPRESSION_KEY,
+            &FreezerCompressionFlag(compressed),
+        )
+    }
+
     /// Store the database schema version atomically with additional operations.
     pub fn store_schema_version_atomically(
         &self,
@@ -1309,6 +1556,22 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             .map_or(self.spec.genesis_slot, |anchor| anchor.oldest_block_slot)
     }
 
+    /// Return the slot before which blocks and states are pruned, if `prune_blocks_older_than` is
+    /// set, or the genesis slot otherwise.
+    ///
+    /// The horizon lies on an epoch boundary, and advances with the split slot.
+    pub fn get_history_horizon(&self) -> Slot {
+        let slots_per_epoch = E::slots_per_epoch();
+        self.config
+            .prune_blocks_older_than
+            .map_or(self.spec.genesis_slot, |epochs| {
+                self.get_split_slot()
+                    .epoch(slots_per_epoch)
+                    .saturating_sub(epochs)
+                    .start_slot(slots_per_epoch)
+            })
+    }
+
     /// Return the in-memory configuration used by the database.
     pub fn get_config(&self) -> &StoreConfig {
         &self.config
@@ -1319,9 +1582,18 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         self.hot_db.get(&CONFIG_KEY)
     }
 
-    /// Write the config to disk.
+    /// Load the freezer database's copy of the config from disk.
+    ///
+    /// Freezer databases last opened by an older version of Lighthouse have no copy.
+    fn load_freezer_config(&self) -> Result<Option<OnDiskStoreConfig>, Error> {
+        self.cold_db.get(&CONFIG_KEY)
+    }
+
+    /// Write the config to disk, in both the hot and freezer databases.
     fn store_config(&self) -> Result<(), Error> {
-        self.hot_db.put(&CONFIG_KEY, &self.config.as_disk_config())
+        let disk_config = self.config.as_disk_config();
+        self.hot_db.put(&CONFIG_KEY, &disk_config)?;
+        self.cold_db.put(&CONFIG_KEY, &disk_config)
     }
 
     /// Load the split point from disk.
@@ -1335,7 +1607,10 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load the state root of a restore point.
-    fn load_restore_point_hash(&self, restore_point_index: u64) -> Result<Hash256, Error> {
+    pub(crate) fn load_restore_point_hash(
+        &self,
+        restore_point_index: u64,
+    ) -> Result<Hash256, Error> {
         let key = Self::restore_point_key(restore_point_index);
         self.cold_db
             .get(&key)?
@@ -1569,6 +1844,553 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         );
         Ok(())
     }
+
+    /// Delete the blocks and historic states which are older than the history horizon, if
+    /// `prune_blocks_older_than` is set.
+    ///
+    /// The anchor is updated so that the pruned blocks are never backfilled again, and so that
+    /// requests for them can be told apart from requests for blocks which are yet to be
+    /// backfilled.
+    pub fn try_prune_history(&self) -> Result<(), Error> {
+        if self.config.prune_blocks_older_than.is_none() {
+            return Ok(());
+        }
+        let horizon = self.get_history_horizon();
+        let old_anchor = self.get_anchor_info();
+        let mut new_anchor = old_anchor.clone().unwrap_or(AnchorInfo {
+            anchor_slot: self.spec.genesis_slot,
+            oldest_block_slot: self.spec.genesis_slot,
+            oldest_block_parent: Hash256::zero(),
+            state_upper_limit: self.spec.genesis_slot,
+            state_lower_limit: self.spec.genesis_slot,
+        });
+        let initial_anchor = new_anchor.clone();
+
+        // 1. Delete the blocks before the first block at or prior to the horizon, which becomes
+        // the oldest block.
+        let mut hot_ops = vec![];
+        if new_anchor.oldest_block_slot < horizon {
+            let split = self.get_split_info();
+            let split_state = self.get_state(&split.state_root, Some(split.slot))?.ok_or(
+                HotColdDBError::MissingSplitState(split.state_root, split.slot),
+            )?;
+            let split_block_root = split_state.get_latest_block_root(split.state_root);
+
+            let block_roots = process_results(
+                self.forwards_block_roots_iterator_until(
+                    new_anchor.oldest_block_slot,
+                    horizon,
+                    || (split_state, split_block_root),
+                    &self.spec,
+                )?,
+                |iter| {
+                    iter.take_while(|(_, slot)| *slot <= horizon)
+                        .map(|(block_root, _)| block_root)
+                        .collect::<Vec<_>>()
+                },
+            )?;
+
+            if let Some(&oldest_block_root) = block_roots.last() {
+                let oldest_block = self
+                    .get_blinded_block(&oldest_block_root)?
+                    .ok_or(Error::BlockNotFound(oldest_block_root))?;
+
+                let mut last_pruned_block_root = None;
+                for block_root in block_roots {
+                    if block_root != oldest_block_root && Some(block_root) != last_pruned_block_root
+                    {
+                        hot_ops.push(StoreOp::DeleteBlock(block_root));
+                        hot_ops.push(StoreOp::DeleteExecutionPayload(block_root));
+                        last_pruned_block_root = Some(block_root);
+                    }
+                }
+                new_anchor.oldest_block_slot = oldest_block.slot();
+                new_anchor.oldest_block_parent = oldest_block.parent_root();
+            }
+        }
+        let blocks_pruned = hot_ops.len() / 2;
+
+        // 2. Delete the restore points before the horizon, apart from the genesis state. Diffs are
+        // only stored against snapshots in the same period, so whole periods are deleted.
+        let mut cold_ops = vec![];
+        if !new_anchor.historic_states_pruned() {
+            let slots_per_restore_point = self.config.slots_per_restore_point;
+            let slots_per_snapshot =
+                slots_per_restore_point * self.config.restore_points_per_snapshot;
+            let cutoff = horizon - horizon % slots_per_snapshot;
+            let (lower_limit, upper_limit) =
+                (new_anchor.state_lower_limit, new_anchor.state_upper_limit);
+
+            // Restore points between the limits are missing, so there's no need to look for them.
+            let first_index = if lower_limit == self.spec.genesis_slot && lower_limit < upper_limit
+            {
+                (upper_limit.as_u64() + slots_per_restore_point - 1) / slots_per_restore_point
+            } else {
+                1
+            };
+            let cutoff_index = cutoff.as_u64() / slots_per_restore_point;
+
+            for index in first_index..cutoff_index {
+                let key = Self::restore_point_key(index);
+                if let Some(restore_point) = self.cold_db.get::<RestorePointHash>(&key)? {
+                    for column in [
+                        DBColumn::BeaconState,
+                        DBColumn::BeaconStateCompressed,
+                        DBColumn::BeaconStateDiff,
+                    ] {
+                        cold_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                            column.into(),
+                            restore_point.state_root.as_bytes(),
+                        )));
+                    }
+                    cold_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                        DBColumn::BeaconRestorePoint.into(),
+                        key.as_bytes(),
+                    )));
+                }
+            }
+
+            if cutoff_index > first_index {
+                // States are only available from the cutoff onwards, unless there is a gap in the
+                // restore points after it.
+                new_anchor.state_upper_limit = if lower_limit >= upper_limit {
+                    cutoff
+                } else {
+                    max(cutoff, upper_limit)
+                };
+                new_anchor.state_lower_limit = self.spec.genesis_slot;
+            }
+        }
+        let states_pruned = cold_ops.len() / 3;
+
+        if new_anchor == initial_anchor {
+            return Ok(());
+        }
+
+        // Delete the states before updating the anchor, so that the pruning is run again if it's
+        // interrupted.
+        self.cold_db.do_atomically(cold_ops)?;
+        hot_ops.push(StoreOp::KeyValueOp(
+            self.compare_and_set_anchor_info(old_anchor, Some(new_anchor.clone()))?,
+        ));
+        self.do_atomically(hot_ops)?;
+
+        debug!(
+            self.log,
+            "Pruned old history";
+            "blocks_pruned" => blocks_pruned,
+            "states_pruned" => states_pruned,
+            "oldest_block_slot" => new_anchor.oldest_block_slot,
+            "state_upper_limit" => new_anchor.state_upper_limit,
+        );
+        Ok(())
+    }
+
+    /// Re-index the restore points of the freezer database, which were stored every
+    /// `old_slots_per_restore_point` slots, for the configured `slots_per_restore_point`.
+    ///
+    /// Restore points which don't lie on the new boundaries are deleted. If any of the new restore
+    /// points are missing, the anchor info is updated so that the states which depend on them are
+    /// unavailable until they are regenerated by historic state reconstruction.
+    fn reanchor_restore_points(&self, old_slots_per_restore_point: u64) -> Result<(), Error> {
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let split = self.get_split_info();
+        let old_anchor = self.get_anchor_info();
+
+        warn!(
+            self.log,
+            "Re-anchoring freezer database";
+            "old_slots_per_restore_point" => old_slots_per_restore_point,
+            "new_slots_per_restore_point" => slots_per_restore_point,
+        );
+
+        // Only the genesis restore point is stored if historic states are pruned, which has the
+        // same index regardless of the slots-per-restore-point.
+        if old_anchor
+            .as_ref()
+            .map_or(false, AnchorInfo::historic_states_pruned)
+        {
+            return Ok(());
+        }
+
+        // All restore points are deleted before any are re-inserted, as their keys may overlap.
+        let mut delete_ops = vec![];
+        let mut put_ops = vec![];
+        let mut available = HashSet::new();
+        let mut kept_state_roots = vec![];
+        let mut deleted_state_roots = HashSet::new();
+        let mut restore_points_deleted = 0;
+        let old_restore_point_count =
+            (split.slot.as_u64() + old_slots_per_restore_point - 1) / old_slots_per_restore_point;
+        for old_index in 0..old_restore_point_count {
+            let key = Self::restore_point_key(old_index);
+            let state_root = match self.cold_db.get::<RestorePointHash>(&key)? {
+                Some(restore_point) => restore_point.state_root,
+                None => continue,
+            };
+            let slot = old_index * old_slots_per_restore_point;
+
+            delete_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::BeaconRestorePoint.into(),
+                key.as_bytes(),
+            )));
+            if slot % slots_per_restore_point == 0 {
+                self.store_restore_point_hash(
+                    slot / slots_per_restore_point,
+                    state_root,
+                    &mut put_ops,
+                );
+                available.insert(slot);
+                kept_state_roots.push(state_root);
+            } else {
+                // The state can still be reconstructed from the surrounding restore points.
+                for column in [
+                    DBColumn::BeaconState,
+                    DBColumn::BeaconStateCompressed,
+                    DBColumn::BeaconStateDiff,
+                ] {
+                    delete_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                        column.into(),
+                        state_root.as_bytes(),
+                    )));
+                }
+                deleted_state_roots.insert(state_root);
+                restore_points_deleted += 1;
+            }
+        }
+
+        // Restore points stored as diffs against a deleted snapshot are stored in full instead.
+        for state_root in kept_state_roots {
+            let diff_bytes = match self
+                .cold_db
+                .get_bytes(DBColumn::BeaconStateDiff.into(), state_root.as_bytes())?
+            {
+                Some(bytes) => bytes,
+                None => continue,
+            };
+            let diff = PartialStateDiff::from_compressed_bytes(&diff_bytes)?;
+            if deleted_state_roots.contains(&diff.base_state_root) {
+                let base = self.load_state_snapshot(&diff.base_state_root)?;
+                put_ops.push(self.state_snapshot_as_kv_store_op(
+                    &state_root,
+                    diff.apply(&base, &self.spec)?.as_ssz_bytes(),
+                )?);
+                delete_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconStateDiff.into(),
+                    state_root.as_bytes(),
+                )));
+            }
+        }
+        *self.state_snapshot_cache.lock() = None;
+
+        // States are available up to the last of the contiguous restore points from genesis, and
+        // from the first of the contiguous restore points before the split, as for checkpoint
+        // sync.
+        let mut state_lower_limit = 0;
+        while available.contains(&(state_lower_limit + slots_per_restore_point)) {
+            state_lower_limit += slots_per_restore_point;
+        }
+        let mut state_upper_limit = (split.slot.as_u64() + slots_per_restore_point - 1)
+            / slots_per_restore_point
+            * slots_per_restore_point;
+        while state_upper_limit >= slots_per_restore_point
+            && available.contains(&(state_upper_limit - slots_per_restore_point))
+        {
+            state_upper_limit -= slots_per_restore_point;
+        }
+
+        let new_anchor = if state_lower_limit >= state_upper_limit && old_anchor.is_none() {
+            None
+        } else {
+            let anchor = old_anchor.clone().unwrap_or(AnchorInfo {
+                anchor_slot: self.spec.genesis_slot,
+                oldest_block_slot: self.spec.genesis_slot,
+                oldest_block_parent: Hash256::zero(),
+                state_upper_limit: self.spec.genesis_slot,
+                state_lower_limit: self.spec.genesis_slot,
+            });
+            Some(AnchorInfo {
+                state_lower_limit: Slot::new(state_lower_limit),
+                state_upper_limit: Slot::new(state_upper_limit),
+                ..anchor
+            })
+        };
+
+        // The new config is written to the freezer in the same batch as the re-indexed restore
+        // points, so that they're never re-indexed from the wrong index if the process dies
+        // before the hot database's copy is written. If it dies before the batch is written then
+        // the restore points are re-indexed from scratch on the next start.
+        self.compare_and_set_anchor_info_with_write(old_anchor, new_anchor.clone())?;
+        delete_ops.append(&mut put_ops);
+        delete_ops.push(self.config.as_disk_config().as_kv_store_op(CONFIG_KEY));
+        self.cold_db.do_atomically(delete_ops)?;
+        self.store_config()?;
+
+        if let Some(anchor) = new_anchor
+            .as_ref()
+            .filter(|anchor| anchor.state_lower_limit < anchor.state_upper_limit)
+        {
+            warn!(
+                self.log,
+                "Some historic states are unavailable";
+                "available_until_slot" => anchor.state_lower_limit,
+                "available_from_slot" => anchor.state_upper_limit,
+                "info" => "use --reconstruct-historic-states to regenerate them",
+            );
+        }
+        info!(
+            self.log,
+            "Re-anchored freezer database";
+            "restore_points_deleted" => restore_points_deleted,
+        );
+        Ok(())
+    }
+
+    /// Delete all historic states from the freezer database except the genesis state, and stop
+    /// storing new ones, so that the node is no longer an archive node.
+    ///
+    /// Blocks and block roots are kept, so the states could be regenerated later by
+    /// re-syncing. This should only be run while the beacon node is offline.
+    ///
+    /// The supplied genesis state must be the one stored as the first restore point, as it
+    /// replaces it.
+    pub fn prune_historic_states(
+        &self,
+        genesis_state_root: Hash256,
+        genesis_state: &BeaconState<E>,
+    ) -> Result<(), Error> {
+        let old_anchor = self.get_anchor_info();
+        if old_anchor
+            .as_ref()
+            .map_or(false, AnchorInfo::historic_states_pruned)
+        {
+            info!(self.log, "Historic states are already pruned");
+            return Ok(());
+        }
+
+        // Check the genesis state before deleting anything, so that a state for another network
+        // can't replace it.
+        let stored_genesis_state_root = self.load_restore_point_hash(0)?;
+        if stored_genesis_state_root != genesis_state_root {
+            return Err(HotColdDBError::GenesisStateRootMismatch {
+                stored: stored_genesis_state_root,
+                supplied: genesis_state_root,
+            }
+            .into());
+        }
+
+        warn!(
+            self.log,
+            "Pruning historic states";
+            "info" => "this may take several minutes"
+        );
+
+        // Delete the states, their summaries and the vectors which are only used to reconstruct
+        // them. The block roots vector is required to look up historic blocks, so it is kept.
+        let mut ops = vec![];
+        for column in [
+            DBColumn::BeaconState,
+            DBColumn::BeaconStateCompressed,
+            DBColumn::BeaconStateSummary,
+            DBColumn::BeaconStateDiff,
+            DBColumn::BeaconRestorePoint,
+            DBColumn::BeaconStateRoots,
+            DBColumn::BeaconHistoricalRoots,
+            DBColumn::BeaconRandaoMixes,
+            DBColumn::BeaconHistoricalSummaries,
+        ] {
+            for key in self.cold_db.iter_raw_keys(column) {
+                let key = key?;
+                ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    column.as_str(),
+                    &key,
+                )));
+            }
+        }
+        let keys_deleted = ops.len();
+
+        // Store the genesis state again in the same batch, so that it can always be loaded.
+        let pending_snapshot =
+            self.store_cold_state(&genesis_state_root, genesis_state, &mut ops)?;
+        self.cold_db.do_atomically(ops)?;
+        self.cache_state_snapshot(pending_snapshot);
+
+        // Only record that states are no longer retained once they're deleted, so that the pruning
+        // is run again if it's interrupted.
+        let new_anchor = match &old_anchor {
+            Some(anchor) => AnchorInfo {
+                state_upper_limit: STATE_UPPER_LIMIT_NO_RETAIN,
+                state_lower_limit: self.spec.genesis_slot,
+                ..anchor.clone()
+            },
+            None => AnchorInfo {
+                anchor_slot: self.spec.genesis_slot,
+                oldest_block_slot: self.spec.genesis_slot,
+                oldest_block_parent: Hash256::zero(),
+                state_upper_limit: STATE_UPPER_LIMIT_NO_RETAIN,
+                state_lower_limit: self.spec.genesis_slot,
+            },
+        };
+        self.compare_and_set_anchor_info_with_write(old_anchor, Some(new_anchor))?;
+
+        info!(
+            self.log,
+            "Historic state pruning complete";
+            "keys_deleted" => keys_deleted,
+            "info" => "compacting the freezer database",
+        );
+        self.cold_db.compact_all()
+    }
+
+    /// Rewrite the restore points which are stored in full with the configured compression,
+    /// returning the number of states rewritten.
+    ///
+    /// Each state is rewritten atomically, so this is safe to interrupt. This should only be run
+    /// while the beacon node is offline.
+    pub fn recompress_freezer_states(&self) -> Result<usize, Error> {
+        let mut state_roots = vec![];
+        for column in [DBColumn::BeaconState, DBColumn::BeaconStateCompressed] {
+            // Uncompressed states are already in the right format if compression is disabled.
+            if column == DBColumn::BeaconState
+                && self.config.freezer_compression == FreezerCompression::None
+            {
+                continue;
+            }
+            for state_root in self.cold_db.iter_column_keys(column) {
+                state_roots.push(state_root?);
+            }
+        }
+
+        info!(
+            self.log,
+            "Recompressing freezer states";
+            "count" => state_roots.len(),
+            "compression" => %self.config.freezer_compression,
+        );
+        for (i, state_root) in state_roots.iter().enumerate() {
+            let partial_state_bytes = self
+                .load_state_snapshot_bytes(state_root)?
+                .ok_or(HotColdDBError::MissingRestorePoint(*state_root))?;
+            let mut ops = vec![];
+            for column in [DBColumn::BeaconState, DBColumn::BeaconStateCompressed] {
+                ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    column.into(),
+                    state_root.as_bytes(),
+                )));
+            }
+            ops.push(self.state_snapshot_as_kv_store_op(state_root, partial_state_bytes)?);
+            self.cold_db.do_atomically(ops)?;
+
+            if (i + 1) % 100 == 0 {
+                info!(
+                    self.log,
+                    "Recompressing freezer states";
+                    "done" => i + 1,
+                    "remaining" => state_roots.len() - i - 1,
+                );
+            }
+        }
+
+        info!(
+            self.log,
+            "Freezer state recompression complete";
+            "count" => state_roots.len(),
+            "info" => "compacting the freezer database",
+        );
+        self.cold_db.compact()?;
+        Ok(state_roots.len())
+    }
+
+    /// Rewrite the compressed restore points without compression, returning the number of states
+    /// rewritten.
+    ///
+    /// Used to downgrade the database to a schema version which predates compression. Each state
+    /// is rewritten atomically, so this is safe to interrupt. This should only be run while the
+    /// beacon node is offline.
+    pub fn decompress_freezer_states(&self) -> Result<usize, Error> {
+        let state_roots = self
+            .cold_db
+            .iter_column_keys(DBColumn::BeaconStateCompressed)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        info!(
+            self.log,
+            "Decompressing freezer states";
+            "count" => state_roots.len(),
+        );
+        for state_root in &state_roots {
+            let partial_state_bytes = self
+                .load_state_snapshot_bytes(state_root)?
+                .ok_or(HotColdDBError::MissingRestorePoint(*state_root))?;
+            self.cold_db.do_atomically(vec![
+                KeyValueStoreOp::PutKeyValue(
+                    get_key_for_col(DBColumn::BeaconState.into(), state_root.as_bytes()),
+                    partial_state_bytes,
+                ),
+                KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconStateCompressed.into(),
+                    state_root.as_bytes(),
+                )),
+            ])?;
+        }
+        Ok(state_roots.len())
+    }
+
+    /// Rewrite the restore points which are stored as diffs as uncompressed full states, returning
+    /// the number of states rewritten.
+    ///
+    /// Used to downgrade the database to a schema version which predates diffs. Each state is
+    /// rewritten atomically, so this is safe to interrupt. This should only be run while the
+    /// beacon node is offline.
+    pub fn store_state_diffs_in_full(&self) -> Result<usize, Error> {
+        let state_roots = self
+            .cold_db
+            .iter_column_keys(DBColumn::BeaconStateDiff)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        info!(
+            self.log,
+            "Storing freezer state diffs in full";
+            "count" => state_roots.len(),
+        );
+        for state_root in &state_roots {
+            let partial_state = self
+                .load_restore_point_partial_state(state_root)?
+                .ok_or(HotColdDBError::MissingRestorePoint(*state_root))?;
+            self.cold_db.do_atomically(vec![
+                KeyValueStoreOp::PutKeyValue(
+                    get_key_for_col(DBColumn::BeaconState.into(), state_root.as_bytes()),
+                    partial_state.as_ssz_bytes(),
+                ),
+                KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconStateDiff.into(),
+                    state_root.as_bytes(),
+                )),
+            ])?;
+        }
+        Ok(state_roots.len())
+    }
+}
+
+/// Check that a version of Lighthouse which supports freezer schema versions up to `supported`
+/// can open a freezer database at version `on_disk`, which holds compressed restore points if
+/// `compressed` is set.
+///
+/// Freezers without a version are at version 1, which every version can open.
+pub fn check_freezer_schema_version(
+    on_disk: Option<SchemaVersion>,
+    compressed: bool,
+    supported: SchemaVersion,
+) -> Result<(), HotColdDBError> {
+    match on_disk {
+        Some(on_disk) if on_disk > supported => {
+            Err(HotColdDBError::UnsupportedFreezerSchemaVersion { on_disk, supported })
+        }
+        _ if compressed && supported < FREEZER_COMPRESSION_SCHEMA_VERSION => {
+            Err(HotColdDBError::UnsupportedFreezerCompression { supported })
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Advance the split point of the store, moving new finalized states to the freezer.
@@ -1587,11 +2409,13 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     // The new frozen head must increase the current split slot, and lie on an epoch
     // boundary (in order for the hot state summary scheme to work).
     let current_split_slot = store.split.read_recursive().slot;
-    let anchor_slot = store
+    let (anchor_slot, state_upper_limit) = store
         .anchor_info
         .read_recursive()
         .as_ref()
-        .map(|a| a.anchor_slot);
+        .map_or((None, store.spec.genesis_slot), |a| {
+            (Some(a.anchor_slot), a.state_upper_limit)
+        });
 
     if frozen_head.slot() < current_split_slot {
         return Err(HotColdDBError::FreezeSlotError {
@@ -1620,12 +2444,15 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
         let (block_root, state_root, slot) = maybe_tuple?;
 
         let mut cold_db_ops: Vec<KeyValueStoreOp> = Vec::new();
+        let mut pending_snapshot = None;
 
-        if slot % store.config.slots_per_restore_point == 0 {
+        // Restore points are only stored from the state upper limit onwards, which is never reached
+        // if historic states have been pruned.
+        if slot % store.config.slots_per_restore_point == 0 && slot >= state_upper_limit {
             let state: BeaconState<E> = get_full_state(&store.hot_db, &state_root, &store.spec)?
                 .ok_or(HotColdDBError::MissingStateToFreeze(state_root))?;
 
-            store.store_cold_state(&state_root, &state, &mut cold_db_ops)?;
+            pending_snapshot = store.store_cold_state(&state_root, &state, &mut cold_db_ops)?;
         }
 
         // Store a pointer from this state root to its slot, so we can later reconstruct states
@@ -1637,6 +2464,7 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
         // There are data dependencies between calls to `store_cold_state()` that prevent us from
         // doing one big call to `store.cold_db.do_atomically()` at end of the loop.
         store.cold_db.do_atomically(cold_db_ops)?;
+        store.cache_state_snapshot(pending_snapshot);
 
         // Delete the old summary, and the full state if we lie on an epoch boundary.
         hot_db_ops.push(StoreOp::DeleteState(state_root, Some(slot)));
@@ -1710,11 +2538,17 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     Ok(())
 }
 
+/// A freezer snapshot staged by `HotColdDB::store_cold_state`, which is cached once it's written.
+pub struct PendingStateSnapshot<E: EthSpec> {
+    state_root: Hash256,
+    snapshot: Arc<PartialBeaconState<E>>,
+}
+
 /// Struct for storing the split slot and state root in the database.
 #[derive(Debug, Clone, Copy, PartialEq, Default, Encode, Decode, Deserialize, Serialize)]
 pub struct Split {
-    pub(crate) slot: Slot,
-    pub(crate) state_root: Hash256,
+    pub slot: Slot,
+    pub state_root: Hash256,
 }
 
 impl StoreItem for Split {
@@ -1743,7 +2577,7 @@ fn no_state_root_iter() -> Option<std::iter::Empty<Result<(Hash256, Slot), Error
 pub struct HotStateSummary {
     pub slot: Slot,
     pub latest_block_root: Hash256,
-    epoch_boundary_state_root: Hash256,
+    pub(crate) epoch_boundary_state_root: Hash256,
 }
 
 impl StoreItem for HotStateSummary {
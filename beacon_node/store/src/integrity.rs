@@ -0,0 +1,326 @@
+//! Integrity checks for the hot and freezer databases, to detect the inconsistencies which can be
+//! left behind by an unclean shutdown.
+use crate::hot_cold_store::{HotColdDB, HotStateSummary};
+use crate::{DBColumn, Error, ItemStore, StoreItem, StoreOp};
+use slog::{info, warn};
+use std::collections::HashSet;
+use types::{BeaconState, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// The problems found by `HotColdDB::verify_integrity`, or `HotColdDB::verify_hot_integrity`
+/// which leaves the finalized blocks and restore points unchecked.
+#[derive(Debug, Default)]
+pub struct IntegrityReport {
+    /// Whether the state at the split point is missing from the hot database.
+    ///
+    /// The finalized chain can't be checked without it.
+    pub split_state_missing: bool,
+    /// The number of finalized blocks which were checked.
+    pub blocks_checked: usize,
+    /// The slot and root of finalized blocks which are missing, or don't link to their parent.
+    pub broken_blocks: Vec<(Slot, Hash256)>,
+    /// The number of restore points which were checked.
+    pub restore_points_checked: usize,
+    /// The slot and state root of restore points which are missing or fail to load, or whose
+    /// contents don't match their state root.
+    pub corrupt_restore_points: Vec<(Slot, Hash256)>,
+    /// Hot states which can never be loaded or are left over from an interrupted migration, with
+    /// the slot of their summary if it exists.
+    ///
+    /// These are the only problems which can be repaired, by deleting the states.
+    pub dangling_states: Vec<(Hash256, Option<Slot>)>,
+    /// Whether the dangling states have been deleted.
+    pub repaired: bool,
+}
+
+impl IntegrityReport {
+    /// Returns `true` if no problems were found, or all of them were repaired.
+    pub fn is_ok(&self) -> bool {
+        !self.split_state_missing
+            && self.broken_blocks.is_empty()
+            && self.corrupt_restore_points.is_empty()
+            && (self.dangling_states.is_empty() || self.repaired)
+    }
+}
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Check the consistency of the split point, the finalized chain of blocks, the restore points
+    /// in the freezer database and the states in the hot database.
+    ///
+    /// If `repair` is `true` then dangling hot states are deleted. All other problems are only
+    /// reported.
+    pub fn verify_integrity(&self, repair: bool) -> Result<IntegrityReport, Error> {
+        let mut report = IntegrityReport::default();
+        let split_state = self.load_split_state()?;
+        report.split_state_missing = split_state.is_none();
+        if let Some(split_state) = split_state {
+            self.verify_finalized_blocks(split_state, &mut report)?;
+        }
+        self.verify_restore_points(&mut report)?;
+        self.verify_hot_states(repair, &mut report)?;
+        Ok(report)
+    }
+
+    /// Check the consistency of the split point and the states in the hot database only.
+    ///
+    /// Unlike `verify_integrity` this doesn't read the freezer database, so it takes time
+    /// proportional to the size of the hot database rather than the length of the chain.
+    pub fn verify_hot_integrity(&self, repair: bool) -> Result<IntegrityReport, Error> {
+        let mut report = IntegrityReport {
+            split_state_missing: self.load_split_state()?.is_none(),
+            ..IntegrityReport::default()
+        };
+        self.verify_hot_states(repair, &mut report)?;
+        Ok(report)
+    }
+
+    /// Load the state at the split point, which must be stored in full in the hot database.
+    fn load_split_state(&self) -> Result<Option<BeaconState<E>>, Error> {
+        let split = self.get_split_info();
+        match self.load_hot_state_summary(&split.state_root)? {
+            Some(summary)
+                if summary.slot == split.slot
+                    && self.hot_db.key_exists(
+                        DBColumn::BeaconState.into(),
+                        split.state_root.as_bytes(),
+                    )? =>
+            {
+                self.get_state(&split.state_root, Some(split.slot))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Check that every block of the finalized chain is stored, and links to its parent.
+    fn verify_finalized_blocks(
+        &self,
+        split_state: BeaconState<E>,
+        report: &mut IntegrityReport,
+    ) -> Result<(), Error> {
+        let split = self.get_split_info();
+        let split_block_root = split_state.get_latest_block_root(split.state_root);
+        let oldest_block_slot = self.get_oldest_block_slot();
+        if oldest_block_slot >= split.slot {
+            return Ok(());
+        }
+
+        info!(
+            self.log,
+            "Verifying finalized blocks";
+            "from_slot" => oldest_block_slot,
+            "to_slot" => split.slot,
+        );
+        let mut parent_root = self
+            .get_anchor_info()
+            .filter(|anchor| anchor.oldest_block_slot > self.spec.genesis_slot)
+            .map(|anchor| anchor.oldest_block_parent);
+        let block_roots = self.forwards_block_roots_iterator_until(
+            oldest_block_slot,
+            split.slot,
+            || (split_state, split_block_root),
+            &self.spec,
+        )?;
+        let mut previous_root = None;
+        for result in block_roots {
+            let (block_root, slot) = result?;
+            // Skipped slots repeat the root of the previous block.
+            if previous_root == Some(block_root) {
+                continue;
+            }
+            previous_root = Some(block_root);
+            report.blocks_checked += 1;
+
+            let valid = match self.get_blinded_block(&block_root) {
+                Ok(Some(block)) => {
+                    block.slot() == slot
+                        && parent_root.map_or(true, |root| block.parent_root() == root)
+                }
+                Ok(None) | Err(_) => false,
+            };
+            if !valid {
+                warn!(
+                    self.log,
+                    "Broken block in finalized chain";
+                    "slot" => slot,
+                    "block_root" => ?block_root,
+                );
+                report.broken_blocks.push((slot, block_root));
+            }
+            parent_root = Some(block_root);
+        }
+        Ok(())
+    }
+
+    /// Check that every restore point in the range of available states loads, and matches its
+    /// state root.
+    fn verify_restore_points(&self, report: &mut IntegrityReport) -> Result<(), Error> {
+        let split = self.get_split_info();
+        let (lower_limit, upper_limit) = self.get_historic_state_limits();
+        info!(
+            self.log,
+            "Verifying restore points";
+            "lower_limit" => lower_limit,
+            "upper_limit" => upper_limit,
+        );
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        for index in
+            0..(split.slot.as_u64() + slots_per_restore_point - 1) / slots_per_restore_point
+        {
+            let slot = Slot::new(index * slots_per_restore_point);
+            if slot > lower_limit && slot < upper_limit {
+                continue;
+            }
+            report.restore_points_checked += 1;
+
+            let state_root = self.load_restore_point_hash(index).ok();
+            let valid = match state_root {
+                Some(state_root) => match self.load_restore_point_by_index(index) {
+                    Ok(state) => {
+                        state.slot() == slot
+                            && state.canonical_root() == state_root
+                            && self.load_cold_state_slot(&state_root)? == Some(slot)
+                    }
+                    Err(_) => false,
+                },
+                None => false,
+            };
+            if !valid {
+                let state_root = state_root.unwrap_or_default();
+                warn!(
+                    self.log,
+                    "Corrupt restore point";
+                    "slot" => slot,
+                    "state_root" => ?state_root,
+                );
+                report.corrupt_restore_points.push((slot, state_root));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that hot states are newer than the split, and loadable from their latest block and
+    /// epoch boundary state, deleting those which aren't if `repair` is `true`.
+    fn verify_hot_states(&self, repair: bool, report: &mut IntegrityReport) -> Result<(), Error> {
+        let split = self.get_split_info();
+        info!(self.log, "Verifying hot states"; "split_slot" => split.slot);
+        let mut summaries = vec![];
+        for result in self.hot_db.iter_column(DBColumn::BeaconStateSummary) {
+            let (state_root, bytes) = result?;
+            summaries.push((state_root, HotStateSummary::from_store_bytes(&bytes)?));
+        }
+
+        let mut dangling = HashSet::new();
+        for (state_root, summary) in &summaries {
+            if *state_root == split.state_root {
+                continue;
+            }
+            if summary.slot < split.slot
+                || !self.block_exists(&summary.latest_block_root)?
+                || !self.hot_db.key_exists(
+                    DBColumn::BeaconState.into(),
+                    summary.epoch_boundary_state_root.as_bytes(),
+                )?
+            {
+                dangling.insert(*state_root);
+            }
+        }
+        // States which are replayed from a dangling epoch boundary state are dangling too.
+        for (state_root, summary) in &summaries {
+            if *state_root != split.state_root
+                && dangling.contains(&summary.epoch_boundary_state_root)
+            {
+                dangling.insert(*state_root);
+            }
+        }
+        report.dangling_states = summaries
+            .iter()
+            .filter(|(state_root, _)| dangling.contains(state_root))
+            .map(|(state_root, summary)| (*state_root, Some(summary.slot)))
+            .collect();
+
+        // Full states without a summary can never be loaded.
+        let summary_roots = summaries
+            .iter()
+            .map(|(state_root, _)| *state_root)
+            .collect::<HashSet<_>>();
+        for state_root in self.hot_db.iter_column_keys(DBColumn::BeaconState) {
+            let state_root = state_root?;
+            if !summary_roots.contains(&state_root) {
+                report.dangling_states.push((state_root, None));
+            }
+        }
+
+        for (state_root, slot) in &report.dangling_states {
+            warn!(
+                self.log,
+                "Dangling hot state";
+                "state_root" => ?state_root,
+                "slot" => ?slot,
+            );
+        }
+
+        if repair && !report.dangling_states.is_empty() {
+            let ops = report
+                .dangling_states
+                .iter()
+                .map(|(state_root, slot)| StoreOp::DeleteState(*state_root, *slot))
+                .collect();
+            self.do_atomically(ops)?;
+            report.repaired = true;
+            info!(
+                self.log,
+                "Deleted dangling hot states";
+                "count" => report.dangling_states.len(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Load the latest restore point which can anchor a new hot database, along with its block.
+    ///
+    /// Restore points which are corrupt, at skipped slots, or whose block is missing or has had
+    /// its execution payload pruned are passed over.
+    #[allow(clippy::type_complexity)]
+    pub fn load_recovery_anchor(
+        &self,
+    ) -> Result<Option<(BeaconState<E>, SignedBeaconBlock<E>)>, Error> {
+        let split = self.get_split_info();
+        let (lower_limit, upper_limit) = self.get_historic_state_limits();
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        for index in
+            (0..(split.slot.as_u64() + slots_per_restore_point - 1) / slots_per_restore_point).rev()
+        {
+            let slot = Slot::new(index * slots_per_restore_point);
+            if slot > lower_limit && slot < upper_limit {
+                continue;
+            }
+
+            let state_root = match self.load_restore_point_hash(index) {
+                Ok(state_root) => state_root,
+                Err(_) => continue,
+            };
+            let state = match self.load_restore_point_by_index(index) {
+                Ok(state) if state.slot() == slot && state.canonical_root() == state_root => state,
+                _ => continue,
+            };
+            let block_root = state.get_latest_block_root(state_root);
+            match self.get_full_block(&block_root) {
+                Ok(Some(block)) if block.slot() == slot => return Ok(Some((state, block))),
+                _ => {
+                    info!(
+                        self.log,
+                        "Restore point unsuitable for recovery";
+                        "slot" => slot,
+                        "block_root" => ?block_root,
+                    );
+                }
+            }
+        }
+        Ok(None)
+    }
+}
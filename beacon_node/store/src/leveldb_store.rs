@@ -12,6 +12,7 @@ use leveldb::options::{Options, ReadOptions, WriteOptions};
 use parking_lot::{Mutex, MutexGuard};
 use std::marker::PhantomData;
 use std::path::Path;
+use strum::IntoEnumIterator;
 
 /// A wrapped leveldb database.
 pub struct LevelDB<E: EthSpec> {
@@ -24,9 +25,18 @@ pub struct LevelDB<E: EthSpec> {
 impl<E: EthSpec> LevelDB<E> {
     /// Open a database at `path`, creating a new database if one does not already exist.
     pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with_create(path, true)
+    }
+
+    /// Open an existing database at `path`, returning an error if there is no database.
+    pub fn open_existing(path: &Path) -> Result<Self, Error> {
+        Self::open_with_create(path, false)
+    }
+
+    fn open_with_create(path: &Path, create_if_missing: bool) -> Result<Self, Error> {
         let mut options = Options::new();
 
-        options.create_if_missing = true;
+        options.create_if_missing = create_if_missing;
 
         let db = Database::open(path, options)?;
         let transaction_mutex = Mutex::new(());
@@ -170,12 +180,29 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
         for (start_key, end_key) in vec![
             endpoints(DBColumn::BeaconStateTemporary),
             endpoints(DBColumn::BeaconState),
+            endpoints(DBColumn::BeaconStateCompressed),
         ] {
             self.db.compact(&start_key, &end_key);
         }
         Ok(())
     }
 
+    /// Compact all values in every column.
+    fn compact_all(&self) -> Result<(), Error> {
+        for column in DBColumn::iter() {
+            // Every key in the column lies between its prefix and the prefix with its last byte
+            // incremented, whatever the length of the key.
+            let start_key = column.as_bytes().to_vec();
+            let mut end_key = start_key.clone();
+            if let Some(last_byte) = end_key.last_mut() {
+                *last_byte = last_byte.saturating_add(1);
+            }
+            self.db
+                .compact(&BytesKey::from_vec(start_key), &BytesKey::from_vec(end_key));
+        }
+        Ok(())
+    }
+
     /// Iterate through all keys and values in a particular column.
     fn iter_column(&self, column: DBColumn) -> ColumnIter {
         let start_key =
@@ -219,6 +246,48 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
                 }),
         )
     }
+
+    /// Iterate through all keys and values in a particular column, whatever their length.
+    fn iter_raw_entries(&self, column: DBColumn) -> RawEntryIter {
+        let start_key = BytesKey::from_vec(column.as_bytes().to_vec());
+
+        let iter = self.db.iter(self.read_options());
+        iter.seek(&start_key);
+
+        Box::new(
+            iter.take_while(move |(key, _)| key.matches_column(column))
+                .map(move |(bytes_key, value)| {
+                    let key = bytes_key
+                        .remove_column_variable(column)
+                        .ok_or_else(|| HotColdDBError::IterationError {
+                            unexpected_key: BytesKey::from_vec(bytes_key.key.clone()),
+                        })?
+                        .to_vec();
+                    Ok((key, value))
+                }),
+        )
+    }
+
+    /// Iterate through all keys in a particular column, whatever their length.
+    fn iter_raw_keys(&self, column: DBColumn) -> RawKeyIter {
+        let start_key = BytesKey::from_vec(column.as_bytes().to_vec());
+
+        let iter = self.db.keys_iter(self.read_options());
+        iter.seek(&start_key);
+
+        Box::new(
+            iter.take_while(move |key| key.matches_column(column))
+                .map(move |bytes_key| {
+                    let key = bytes_key
+                        .remove_column_variable(column)
+                        .ok_or_else(|| HotColdDBError::IterationError {
+                            unexpected_key: BytesKey::from_vec(bytes_key.key.clone()),
+                        })?
+                        .to_vec();
+                    Ok(key)
+                }),
+        )
+    }
 }
 
 impl<E: EthSpec> ItemStore<E> for LevelDB<E> {}
@@ -247,13 +316,18 @@ impl BytesKey {
 
     /// Remove the column from a key, returning its `Hash256` portion.
     pub fn remove_column(&self, column: DBColumn) -> Option<Hash256> {
+        self.remove_column_variable(column)
+            .filter(|subkey| subkey.len() == 32)
+            .map(Hash256::from_slice)
+    }
+
+    /// Remove the column from a key of any length, returning the remainder.
+    pub fn remove_column_variable(&self, column: DBColumn) -> Option<&[u8]> {
         if self.matches_column(column) {
-            let subkey = &self.key[column.as_bytes().len()..];
-            if subkey.len() == 32 {
-                return Some(Hash256::from_slice(subkey));
-            }
+            Some(&self.key[column.as_bytes().len()..])
+        } else {
+            None
         }
-        None
     }
 
     pub fn from_vec(key: Vec<u8>) -> Self {
@@ -10,28 +10,40 @@
 #[macro_use]
 extern crate lazy_static;
 
+pub mod backend;
 mod chunk_writer;
 pub mod chunked_iter;
 pub mod chunked_vector;
+mod compression;
 pub mod config;
+pub mod era;
 pub mod errors;
 mod forwards_iter;
 mod garbage_collection;
+mod historic_replay;
 pub mod hot_cold_store;
 mod impls;
+mod integrity;
 mod leveldb_store;
+#[cfg(feature = "mdbx")]
+mod mdbx_store;
 mod memory_store;
 pub mod metadata;
 pub mod metrics;
 mod partial_beacon_state;
 pub mod reconstruct;
+mod state_diff;
 
 pub mod iter;
 
+pub use self::backend::BeaconNodeBackend;
 pub use self::chunk_writer::ChunkWriter;
 pub use self::config::StoreConfig;
 pub use self::hot_cold_store::{HotColdDB, HotStateSummary, Split};
+pub use self::integrity::IntegrityReport;
 pub use self::leveldb_store::LevelDB;
+#[cfg(feature = "mdbx")]
+pub use self::mdbx_store::Mdbx;
 pub use self::memory_store::MemoryStore;
 pub use self::partial_beacon_state::PartialBeaconState;
 pub use errors::Error;
@@ -40,11 +52,13 @@ pub use metadata::AnchorInfo;
 pub use metrics::scrape_for_metrics;
 use parking_lot::MutexGuard;
 use std::sync::Arc;
-use strum::{EnumString, IntoStaticStr};
+use strum::{EnumIter, EnumString, IntoStaticStr};
 pub use types::*;
 
 pub type ColumnIter<'a> = Box<dyn Iterator<Item = Result<(Hash256, Vec<u8>), Error>> + 'a>;
 pub type ColumnKeyIter<'a> = Box<dyn Iterator<Item = Result<Hash256, Error>> + 'a>;
+pub type RawEntryIter<'a> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>> + 'a>;
+pub type RawKeyIter<'a> = Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + 'a>;
 
 pub trait KeyValueStore<E: EthSpec>: Sync + Send + Sized + 'static {
     /// Retrieve some bytes in `column` with `key`.
@@ -79,6 +93,13 @@ pub trait KeyValueStore<E: EthSpec>: Sync + Send + Sized + 'static {
     /// Compact the database, freeing space used by deleted items.
     fn compact(&self) -> Result<(), Error>;
 
+    /// Compact every column of the database.
+    ///
+    /// `compact` may only compact the columns in which most deletions happen.
+    fn compact_all(&self) -> Result<(), Error> {
+        self.compact()
+    }
+
     /// Iterate through all keys and values in a particular column.
     fn iter_column(&self, _column: DBColumn) -> ColumnIter {
         // Default impl for non LevelDB databases
@@ -90,6 +111,21 @@ pub trait KeyValueStore<E: EthSpec>: Sync + Send + Sized + 'static {
         // Default impl for non LevelDB databases
         Box::new(std::iter::empty())
     }
+
+    /// Iterate through all keys and values in a particular column, including keys which are not
+    /// a `Hash256` (e.g. the chunk indices of the freezer vectors).
+    ///
+    /// The column prefix is removed from the keys.
+    fn iter_raw_entries(&self, _column: DBColumn) -> RawEntryIter {
+        // Default impl for non-persistent databases
+        Box::new(std::iter::empty())
+    }
+
+    /// Iterate through all keys in a particular column, as for `iter_raw_entries`.
+    fn iter_raw_keys(&self, _column: DBColumn) -> RawKeyIter {
+        // Default impl for non-persistent databases
+        Box::new(std::iter::empty())
+    }
 }
 
 pub fn get_key_for_col(column: &str, key: &[u8]) -> Vec<u8> {
@@ -165,7 +201,7 @@ pub enum StoreOp<'a, E: EthSpec> {
 }
 
 /// A unique column identifier.
-#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr, EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr, EnumString, EnumIter)]
 pub enum DBColumn {
     /// For data related to the database itself.
     #[strum(serialize = "bma")]
@@ -214,6 +250,15 @@ pub enum DBColumn {
     OptimisticTransitionBlock,
     #[strum(serialize = "bhs")]
     BeaconHistoricalSummaries,
+    /// For restore points stored as a diff against an earlier restore point.
+    #[strum(serialize = "bsd")]
+    BeaconStateDiff,
+    /// For full states in the freezer database which are stored compressed.
+    #[strum(serialize = "bsz")]
+    BeaconStateCompressed,
+    /// For the committee caches of the shuffling cache, persisted on shutdown.
+    #[strum(serialize = "shc")]
+    ShufflingCache,
 }
 
 /// A block from the database, which might have an execution payload or not.
@@ -307,6 +352,25 @@ mod tests {
         test_impl(store);
     }
 
+    #[test]
+    fn iter_raw_keys_of_vector_column() {
+        let dir = tempdir().unwrap();
+        let store = LevelDB::<MinimalEthSpec>::open(dir.path()).unwrap();
+        let column = DBColumn::BeaconRandaoMixes;
+        let key = chunked_vector::chunk_key(0);
+        store.put_bytes(column.as_str(), &key, &[1]).unwrap();
+
+        // The keys of the freezer vector columns are chunk indices rather than a `Hash256`.
+        assert!(store.iter_column_keys(column).any(|key| key.is_err()));
+        assert_eq!(
+            store
+                .iter_raw_keys(column)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![key.to_vec()]
+        );
+    }
+
     #[test]
     fn memorydb() {
         let store = MemoryStore::open();
@@ -0,0 +1,285 @@
+use super::*;
+use crate::hot_cold_store::HotColdDBError;
+use crate::leveldb_store::BytesKey;
+use crate::metrics;
+use mdbx::{EnvironmentFlags, Geometry, Mode, WriteFlags};
+use parking_lot::{Mutex, MutexGuard};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// The maximum size of each database.
+const MDBX_MAX_SIZE: usize = 16 * (1 << 40); // 16 TiB
+/// The size by which the database file grows when it is full.
+const MDBX_GROWTH_STEP: isize = 256 * (1 << 20); // 256 MiB
+/// The amount of free space at the end of the database file above which it is shrunk.
+const MDBX_SHRINK_THRESHOLD: isize = 4 * MDBX_GROWTH_STEP; // 1 GiB
+/// The number of entries read by each transaction when iterating a column.
+const ITER_BATCH_SIZE: usize = 1024;
+
+/// A wrapped MDBX database.
+///
+/// All columns are stored in the default table of the environment, with keys prefixed by their
+/// column as for `LevelDB`.
+pub struct Mdbx<E: EthSpec> {
+    env: mdbx::Environment<mdbx::NoWriteMap>,
+    /// A mutex to synchronise sensitive read-write transactions.
+    transaction_mutex: Mutex<()>,
+    _phantom: PhantomData<E>,
+}
+
+impl<E: EthSpec> Mdbx<E> {
+    /// Open a database at `path`, creating a new database if one does not already exist.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        fs::create_dir_all(path).map_err(|e| Error::DBError {
+            message: format!("Unable to create {}: {}", path.display(), e),
+        })?;
+        let env = mdbx::Environment::new()
+            .set_geometry(Geometry {
+                size: Some(0..MDBX_MAX_SIZE),
+                growth_step: Some(MDBX_GROWTH_STEP),
+                shrink_threshold: Some(MDBX_SHRINK_THRESHOLD),
+                page_size: None,
+            })
+            .open_with_permissions(path, 0o600)?;
+
+        Ok(Self {
+            env,
+            transaction_mutex: Mutex::new(()),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Open an existing database at `path` in read-only mode, returning an error if there is no
+    /// database.
+    pub fn open_existing(path: &Path) -> Result<Self, Error> {
+        let env = mdbx::Environment::new()
+            .set_flags(EnvironmentFlags {
+                mode: Mode::ReadOnly,
+                ..EnvironmentFlags::default()
+            })
+            .open(path)?;
+
+        Ok(Self {
+            env,
+            transaction_mutex: Mutex::new(()),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Read up to `ITER_BATCH_SIZE` entries of `column`, starting from the key `from`.
+    fn read_batch(
+        &self,
+        column: DBColumn,
+        from: &[u8],
+    ) -> Result<VecDeque<(Vec<u8>, Vec<u8>)>, Error> {
+        let txn = self.env.begin_ro_txn()?;
+        let db = txn.open_db(None)?;
+        let mut cursor = txn.cursor(&db)?;
+
+        let mut batch = VecDeque::with_capacity(ITER_BATCH_SIZE);
+        let mut entry = cursor.set_range::<Cow<[u8]>, Cow<[u8]>>(from)?;
+        while let Some((key, value)) = entry {
+            if !key.starts_with(column.as_bytes()) || batch.len() == ITER_BATCH_SIZE {
+                break;
+            }
+            batch.push_back((key.into_owned(), value.into_owned()));
+            entry = cursor.next::<Cow<[u8]>, Cow<[u8]>>()?;
+        }
+        Ok(batch)
+    }
+
+    /// Iterate through the keys and values of `column`, reading them in batches so that no
+    /// transaction is held open between calls to `next`.
+    ///
+    /// Each batch is read by a separate transaction, so unlike LevelDB iteration this is not a
+    /// consistent snapshot of the column: values written or deleted during iteration may or may
+    /// not be returned, depending on whether their batch has been read yet.
+    fn iter_batched(
+        &self,
+        column: DBColumn,
+    ) -> impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>> + '_ {
+        let mut next_key = Some(column.as_bytes().to_vec());
+        let mut batch = VecDeque::new();
+
+        std::iter::from_fn(move || {
+            if batch.is_empty() {
+                let from = next_key.take()?;
+                batch = match self.read_batch(column, &from) {
+                    Ok(batch) => batch,
+                    Err(e) => return Some(Err(e)),
+                };
+                // The next batch starts from the smallest key after the last one read.
+                if batch.len() == ITER_BATCH_SIZE {
+                    next_key = batch.back().map(|(key, _)| {
+                        let mut key = key.clone();
+                        key.push(0);
+                        key
+                    });
+                }
+            }
+
+            let (mut key, value) = batch.pop_front()?;
+            key.drain(..column.as_bytes().len());
+            Some(Ok((key, value)))
+        })
+    }
+
+    /// Iterate through the keys and values of `column`, which must all be a `Hash256`.
+    fn iter_hash_column(
+        &self,
+        column: DBColumn,
+    ) -> impl Iterator<Item = Result<(Hash256, Vec<u8>), Error>> + '_ {
+        self.iter_batched(column).map(move |result| {
+            let (key, value) = result?;
+            if key.len() != Hash256::len_bytes() {
+                return Err(HotColdDBError::IterationError {
+                    unexpected_key: BytesKey::from_vec(get_key_for_col(column.into(), &key)),
+                }
+                .into());
+            }
+            Ok((Hash256::from_slice(&key), value))
+        })
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for Mdbx<E> {
+    /// Store some `value` in `column`, indexed with `key`.
+    fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+        metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, val.len() as u64);
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        let txn = self.env.begin_rw_txn()?;
+        let db = txn.open_db(None)?;
+        txn.put(&db, column_key, val, WriteFlags::default())?;
+        txn.commit()?;
+
+        metrics::stop_timer(timer);
+        Ok(())
+    }
+
+    /// MDBX transactions are durable once committed, so this is the same as `put_bytes`.
+    fn put_bytes_sync(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        self.put_bytes(col, key, val)
+    }
+
+    /// Flush the environment to disk.
+    fn sync(&self) -> Result<(), Error> {
+        self.env.sync(true)?;
+        Ok(())
+    }
+
+    /// Retrieve some bytes in `column` with `key`.
+    fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_READ_COUNT);
+        let timer = metrics::start_timer(&metrics::DISK_DB_READ_TIMES);
+
+        let txn = self.env.begin_ro_txn()?;
+        let db = txn.open_db(None)?;
+        let value = txn.get::<Cow<[u8]>>(&db, &column_key)?.map(|bytes| {
+            metrics::inc_counter_by(&metrics::DISK_DB_READ_BYTES, bytes.len() as u64);
+            bytes.into_owned()
+        });
+
+        metrics::stop_timer(timer);
+        Ok(value)
+    }
+
+    /// Return `true` if `key` exists in `column`.
+    fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_EXISTS_COUNT);
+
+        let txn = self.env.begin_ro_txn()?;
+        let db = txn.open_db(None)?;
+        Ok(txn.get::<()>(&db, &column_key)?.is_some())
+    }
+
+    /// Removes `key` from `column`.
+    fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+
+        let txn = self.env.begin_rw_txn()?;
+        let db = txn.open_db(None)?;
+        txn.del(&db, column_key, None)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn do_atomically(&self, ops_batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        let txn = self.env.begin_rw_txn()?;
+        let db = txn.open_db(None)?;
+        for op in ops_batch {
+            match op {
+                KeyValueStoreOp::PutKeyValue(key, value) => {
+                    txn.put(&db, key, value, WriteFlags::default())?;
+                }
+
+                KeyValueStoreOp::DeleteKey(key) => {
+                    txn.del(&db, key, None)?;
+                }
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn begin_rw_transaction(&self) -> MutexGuard<()> {
+        self.transaction_mutex.lock()
+    }
+
+    /// MDBX can't be compacted while it is open. The pages of deleted values are reused, and
+    /// free space at the end of the file beyond `MDBX_SHRINK_THRESHOLD` is returned to the
+    /// filesystem when a write transaction is committed. This commits an empty write
+    /// transaction to trigger that, then flushes the environment to disk.
+    fn compact(&self) -> Result<(), Error> {
+        self.env.begin_rw_txn()?.commit()?;
+        self.sync()
+    }
+
+    /// Iterate through all keys and values in a particular column.
+    fn iter_column(&self, column: DBColumn) -> ColumnIter {
+        Box::new(self.iter_hash_column(column))
+    }
+
+    /// Iterate through all keys in a particular column.
+    fn iter_column_keys(&self, column: DBColumn) -> ColumnKeyIter {
+        Box::new(
+            self.iter_hash_column(column)
+                .map(|result| result.map(|(key, _)| key)),
+        )
+    }
+
+    /// Iterate through all keys and values in a particular column, whatever their length.
+    fn iter_raw_entries(&self, column: DBColumn) -> RawEntryIter {
+        Box::new(self.iter_batched(column))
+    }
+
+    /// Iterate through all keys in a particular column, whatever their length.
+    fn iter_raw_keys(&self, column: DBColumn) -> RawKeyIter {
+        Box::new(
+            self.iter_batched(column)
+                .map(|result| result.map(|(key, _)| key)),
+        )
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for Mdbx<E> {}
+
+impl From<mdbx::Error> for Error {
+    fn from(e: mdbx::Error) -> Error {
+        Error::DBError {
+            message: format!("{:?}", e),
+        }
+    }
+}
@@ -4,7 +4,16 @@ use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use types::{Checkpoint, Hash256, Slot};
 
-pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(16);
+pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(18);
+/// The schema version of the freezer database, which is versioned separately from the hot
+/// database.
+///
+/// - 1: restore points are stored in full.
+/// - 2: restore points may be stored as diffs against a snapshot.
+/// - 3: restore points may be stored compressed.
+pub const CURRENT_FREEZER_SCHEMA_VERSION: SchemaVersion = SchemaVersion(3);
+/// The first freezer schema version which can read compressed restore points.
+pub const FREEZER_COMPRESSION_SCHEMA_VERSION: SchemaVersion = SchemaVersion(3);
 
 // All the keys that get stored under the `BeaconMeta` column.
 //
@@ -15,6 +24,14 @@ pub const SPLIT_KEY: Hash256 = Hash256::repeat_byte(2);
 pub const PRUNING_CHECKPOINT_KEY: Hash256 = Hash256::repeat_byte(3);
 pub const COMPACTION_TIMESTAMP_KEY: Hash256 = Hash256::repeat_byte(4);
 pub const ANCHOR_INFO_KEY: Hash256 = Hash256::repeat_byte(5);
+/// Stored in the freezer database rather than the hot database.
+pub const FREEZER_SCHEMA_VERSION_KEY: Hash256 = Hash256::repeat_byte(6);
+/// Stored in the freezer database rather than the hot database.
+pub const FREEZER_COMPRESSION_KEY: Hash256 = Hash256::repeat_byte(7);
+
+/// The `state_upper_limit` of a database which no longer stores historic states, i.e. one on which
+/// `prune_historic_states` has been run.
+pub const STATE_UPPER_LIMIT_NO_RETAIN: Slot = Slot::new(u64::MAX);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SchemaVersion(pub u64);
@@ -80,6 +97,27 @@ impl StoreItem for CompactionTimestamp {
     }
 }
 
+/// Whether the freezer database may hold compressed restore points.
+///
+/// Stored before the first compressed restore point is written, and cleared once they have all
+/// been decompressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreezerCompressionFlag(pub bool);
+
+impl StoreItem for FreezerCompressionFlag {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(FreezerCompressionFlag(bool::from_ssz_bytes(bytes)?))
+    }
+}
+
 /// Database parameters relevant to weak subjectivity sync.
 #[derive(Debug, PartialEq, Eq, Clone, Encode, Decode, Serialize, Deserialize)]
 pub struct AnchorInfo {
@@ -98,9 +136,15 @@ pub struct AnchorInfo {
 }
 
 impl AnchorInfo {
-    /// Returns true if the block backfill has completed.
-    pub fn block_backfill_complete(&self) -> bool {
-        self.oldest_block_slot == 0
+    /// Returns true if the block backfill has reached `target_slot`, which is the genesis slot
+    /// unless old blocks are pruned.
+    pub fn block_backfill_complete(&self, target_slot: Slot) -> bool {
+        self.oldest_block_slot <= target_slot
+    }
+
+    /// Returns true if historic states other than the genesis state are never stored.
+    pub fn historic_states_pruned(&self) -> bool {
+        self.state_lower_limit == 0 && self.state_upper_limit == STATE_UPPER_LIMIT_NO_RETAIN
     }
 }
 
@@ -11,6 +11,14 @@ lazy_static! {
         try_create_int_gauge("store_disk_db_size", "Size of the hot on-disk database (bytes)");
     pub static ref FREEZER_DB_SIZE: Result<IntGauge> =
         try_create_int_gauge("store_freezer_db_size", "Size of the on-disk freezer database (bytes)");
+    pub static ref DISK_DB_FREE_SPACE: Result<IntGauge> = try_create_int_gauge(
+        "store_disk_db_free_space",
+        "Free space on the filesystem of the hot database and data directory (bytes)"
+    );
+    pub static ref FREEZER_DB_FREE_SPACE: Result<IntGauge> = try_create_int_gauge(
+        "store_freezer_db_free_space",
+        "Free space on the filesystem of the freezer database (bytes)"
+    );
     pub static ref DISK_DB_WRITE_BYTES: Result<IntCounter> = try_create_int_counter(
         "store_disk_db_write_bytes_total",
         "Number of bytes attempted to be written to the hot on-disk DB"
@@ -101,6 +109,10 @@ lazy_static! {
         "store_beacon_block_cache_hit_total",
         "Number of hits to the store's block cache"
     );
+    pub static ref BEACON_BLOCK_CACHE_MISS_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_beacon_block_cache_miss_total",
+        "Number of misses to the store's block cache"
+    );
     pub static ref BEACON_BLOCK_READ_TIMES: Result<Histogram> = try_create_histogram(
         "store_beacon_block_read_overhead_seconds",
         "Overhead on reading a beacon block from the DB (e.g., decoding)"
@@ -133,4 +145,10 @@ pub fn scrape_for_metrics(db_path: &Path, freezer_db_path: &Path) {
     set_gauge(&DISK_DB_SIZE, db_size as i64);
     let freezer_db_size = size_of_dir(freezer_db_path);
     set_gauge(&FREEZER_DB_SIZE, freezer_db_size as i64);
+    if let Ok(free_space) = fs2::available_space(db_path) {
+        set_gauge(&DISK_DB_FREE_SPACE, free_space as i64);
+    }
+    if let Ok(free_space) = fs2::available_space(freezer_db_path) {
+        set_gauge(&FREEZER_DB_FREE_SPACE, free_space as i64);
+    }
 }
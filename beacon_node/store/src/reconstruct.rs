@@ -2,7 +2,7 @@
 use crate::hot_cold_store::{HotColdDB, HotColdDBError};
 use crate::{Error, ItemStore};
 use itertools::{process_results, Itertools};
-use slog::info;
+use slog::{info, warn};
 use state_processing::{
     per_block_processing, per_slot_processing, BlockSignatureStrategy, ConsensusContext,
     VerifyBlockRoot,
@@ -24,6 +24,16 @@ where
             return Ok(());
         };
 
+        // States aren't stored once they have been pruned, so there's nothing to reconstruct.
+        if anchor.historic_states_pruned() {
+            warn!(
+                self.log,
+                "Not reconstructing pruned historic states";
+                "info" => "re-sync the node to regenerate historic states"
+            );
+            return Ok(());
+        }
+
         // Check that all historic blocks are known.
         if anchor.oldest_block_slot != 0 {
             return Err(Error::MissingHistoricBlocks {
@@ -67,6 +77,7 @@ where
 
         process_results(block_root_iter, |iter| -> Result<(), Error> {
             let mut io_batch = vec![];
+            let mut pending_snapshot = None;
 
             let mut prev_state_root = None;
 
@@ -110,7 +121,9 @@ where
                     .or_else(|_| state.update_tree_hash_cache())?;
 
                 // Stage state for storage in freezer DB.
-                self.store_cold_state(&state_root, &state, &mut io_batch)?;
+                if let Some(snapshot) = self.store_cold_state(&state_root, &state, &mut io_batch)? {
+                    pending_snapshot = Some(snapshot);
+                }
 
                 // If the slot lies on an epoch boundary, commit the batch and update the anchor.
                 if slot % slots_per_restore_point == 0 || slot + 1 == upper_limit_slot {
@@ -122,6 +135,7 @@ where
                     );
 
                     self.cold_db.do_atomically(std::mem::take(&mut io_batch))?;
+                    self.cache_state_snapshot(pending_snapshot.take());
 
                     // Update anchor.
                     let old_anchor = Some(anchor.clone());
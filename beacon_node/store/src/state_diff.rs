@@ -0,0 +1,166 @@
+//! Compact storage for freezer restore points, as diffs against an earlier full snapshot.
+//!
+//! Most of a restore point state is the validator registry and balances, which barely change
+//! between restore points. A diff stores only the validators which differ from the snapshot, the
+//! change in each balance, and the remaining fields of the state in full. It is compressed before
+//! being written, which shrinks the (mostly zero) balance changes and participation flags.
+use crate::{Error, PartialBeaconState};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::cmp::Ordering;
+use std::io::{Read, Write};
+use types::{ChainSpec, EthSpec, Hash256, Validator, VariableList};
+
+/// The difference between the partial state of a restore point and an earlier snapshot.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub struct PartialStateDiff {
+    /// The state root of the snapshot, which is stored in full.
+    pub base_state_root: Hash256,
+    /// The indices of the validators which differ from the snapshot, in ascending order.
+    validator_indices: Vec<u64>,
+    /// The validators at `validator_indices`.
+    validators: Vec<Validator>,
+    /// The change in the balance of every validator since the snapshot, modulo 2^64.
+    balance_deltas: Vec<u64>,
+    /// The SSZ bytes of the remaining fields, with an empty validator registry and balances.
+    state: Vec<u8>,
+}
+
+impl PartialStateDiff {
+    /// Compute the diff which transforms `base` into `target`.
+    pub fn compute<E: EthSpec>(
+        base_state_root: Hash256,
+        base: &PartialBeaconState<E>,
+        mut target: PartialBeaconState<E>,
+    ) -> Self {
+        let validators = std::mem::take(target.validators_mut());
+        let balances = std::mem::take(target.balances_mut());
+
+        let (validator_indices, validators) = validators
+            .iter()
+            .enumerate()
+            .filter(|(i, validator)| base.validators().get(*i) != Some(*validator))
+            .map(|(i, validator)| (i as u64, validator.clone()))
+            .unzip();
+        let balance_deltas = balances
+            .iter()
+            .enumerate()
+            .map(|(i, balance)| {
+                balance.wrapping_sub(base.balances().get(i).copied().unwrap_or_default())
+            })
+            .collect();
+
+        Self {
+            base_state_root,
+            validator_indices,
+            validators,
+            balance_deltas,
+            state: target.as_ssz_bytes(),
+        }
+    }
+
+    /// Apply the diff to `base`, which must be the snapshot with `self.base_state_root`.
+    pub fn apply<E: EthSpec>(
+        &self,
+        base: &PartialBeaconState<E>,
+        spec: &ChainSpec,
+    ) -> Result<PartialBeaconState<E>, Error> {
+        let mut state = PartialBeaconState::from_ssz_bytes(&self.state, spec)?;
+
+        let mut validators = base.validators().to_vec();
+        for (&index, validator) in self.validator_indices.iter().zip(&self.validators) {
+            let index = index as usize;
+            match index.cmp(&validators.len()) {
+                Ordering::Less => validators[index] = validator.clone(),
+                Ordering::Equal => validators.push(validator.clone()),
+                Ordering::Greater => {
+                    return Err(Error::StateDiffError(format!(
+                        "validator index {} is out of bounds",
+                        index
+                    )))
+                }
+            }
+        }
+        if validators.len() != self.balance_deltas.len() {
+            return Err(Error::StateDiffError(format!(
+                "{} balances for {} validators",
+                self.balance_deltas.len(),
+                validators.len()
+            )));
+        }
+        let balances = self
+            .balance_deltas
+            .iter()
+            .enumerate()
+            .map(|(i, delta)| {
+                delta.wrapping_add(base.balances().get(i).copied().unwrap_or_default())
+            })
+            .collect();
+
+        *state.validators_mut() = VariableList::new(validators)
+            .map_err(|e| Error::StateDiffError(format!("invalid validators: {:?}", e)))?;
+        *state.balances_mut() = VariableList::new(balances)
+            .map_err(|e| Error::StateDiffError(format!("invalid balances: {:?}", e)))?;
+        Ok(state)
+    }
+
+    /// Encode and compress the diff with the zlib compression `level`, for storage in the
+    /// database.
+    pub fn as_compressed_bytes(&self, level: u32) -> Result<Vec<u8>, Error> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::new(level.min(9)));
+        encoder
+            .write_all(&self.as_ssz_bytes())
+            .map_err(|e| Error::StateDiffError(format!("unable to compress: {}", e)))?;
+        encoder
+            .finish()
+            .map_err(|e| Error::StateDiffError(format!("unable to compress: {}", e)))
+    }
+
+    /// Decompress and decode a diff from the database.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut ssz_bytes = vec![];
+        ZlibDecoder::new(bytes)
+            .read_to_end(&mut ssz_bytes)
+            .map_err(|e| Error::StateDiffError(format!("unable to decompress: {}", e)))?;
+        Ok(Self::from_ssz_bytes(&ssz_bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{BeaconState, Epoch, Eth1Data, MainnetEthSpec};
+
+    type E = MainnetEthSpec;
+
+    fn partial_state(validators: &[Validator], balances: &[u64]) -> PartialBeaconState<E> {
+        let spec = E::default_spec();
+        let mut state = BeaconState::<E>::new(0, Eth1Data::default(), &spec);
+        *state.validators_mut() = VariableList::new(validators.to_vec()).unwrap();
+        *state.balances_mut() = VariableList::new(balances.to_vec()).unwrap();
+        PartialBeaconState::from_state_forgetful(&state)
+    }
+
+    #[test]
+    fn apply_diff() {
+        let spec = E::default_spec();
+        let validator = Validator::default();
+        let exited = Validator {
+            exit_epoch: Epoch::new(5),
+            ..validator.clone()
+        };
+        let base = partial_state(&[validator.clone(), validator.clone()], &[32, 32]);
+        let target = partial_state(&[exited, validator.clone(), validator], &[31, 33, 32]);
+
+        let diff = PartialStateDiff::compute(Hash256::repeat_byte(1), &base, target.clone());
+        assert_eq!(diff.validator_indices, vec![0, 2]);
+
+        let bytes = diff.as_compressed_bytes(6).unwrap();
+        let diff = PartialStateDiff::from_compressed_bytes(&bytes).unwrap();
+        assert_eq!(diff.base_state_root, Hash256::repeat_byte(1));
+        assert_eq!(diff.apply(&base, &spec).unwrap(), target);
+    }
+}
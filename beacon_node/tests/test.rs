@@ -1,14 +1,36 @@
 #![cfg(test)]
 
+use beacon_chain::builder::{BeaconChainBuilder, Witness};
+use beacon_chain::eth1_chain::CachingEth1Backend;
+use beacon_chain::slot_clock::SystemTimeSlotClock;
+use beacon_chain::store::{BeaconNodeBackend, DBColumn, HotColdDB, KeyValueStore, StoreConfig};
+use beacon_chain::test_utils::{test_spec, AttestationStrategy, BeaconChainHarness, BlockStrategy};
 use beacon_chain::StateSkipConfig;
+use beacon_node::{ClientBuilder, ClientGenesis};
 use node_test_rig::{
     environment::{Environment, EnvironmentBuilder},
     eth2::types::StateId,
     testing_client_config, LocalBeaconNode,
 };
-use types::{EthSpec, MinimalEthSpec, Slot};
+use ssz::Encode;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use types::{
+    BeaconState, ChainSpec, Epoch, EthSpec, Hash256, MinimalEthSpec, SignedBeaconBlock, Slot,
+};
+
+type E = MinimalEthSpec;
+type DiskStore = HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>;
+type DiskWitness = Witness<
+    SystemTimeSlotClock,
+    CachingEth1Backend<E>,
+    E,
+    BeaconNodeBackend<E>,
+    BeaconNodeBackend<E>,
+>;
 
-fn env_builder() -> EnvironmentBuilder<MinimalEthSpec> {
+fn env_builder() -> EnvironmentBuilder<E> {
     EnvironmentBuilder::minimal()
 }
 
@@ -61,3 +83,195 @@ fn http_server_genesis_state() {
 
     env.fire_signal();
 }
+
+/// Builds a finalized chain in an on-disk database with a restore point every epoch, then closes
+/// the database.
+///
+/// Returns the root of the head block and the SSZ bytes of the genesis state.
+fn build_disk_chain(
+    env: &Environment<E>,
+    hot_path: &Path,
+    cold_path: &Path,
+    spec: &ChainSpec,
+) -> (Hash256, Vec<u8>) {
+    let store = open_store(env, hot_path, cold_path, spec);
+    let harness = BeaconChainHarness::builder(MinimalEthSpec)
+        .spec(spec.clone())
+        .deterministic_keypairs(8)
+        .logger(env.core_context().log().clone())
+        .fresh_disk_store(store)
+        .mock_execution_layer()
+        .build();
+    harness.advance_slot();
+    env.runtime().block_on(harness.extend_chain(
+        5 * E::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    ));
+    let genesis_state = harness
+        .chain
+        .get_state(&harness.chain.genesis_state_root, Some(Slot::new(0)))
+        .expect("should read genesis state")
+        .expect("genesis state should exist");
+    (harness.head_block_root(), genesis_state.as_ssz_bytes())
+}
+
+/// Opens the hot and cold databases at `hot_path` and `cold_path`.
+fn open_store(
+    env: &Environment<E>,
+    hot_path: &Path,
+    cold_path: &Path,
+    spec: &ChainSpec,
+) -> Arc<DiskStore> {
+    HotColdDB::open(
+        hot_path,
+        cold_path,
+        |_, _, _| Ok(()),
+        store_config(),
+        spec.clone(),
+        env.core_context().log().clone(),
+    )
+    .expect("should open database")
+}
+
+fn store_config() -> StoreConfig {
+    StoreConfig {
+        slots_per_restore_point: E::slots_per_epoch(),
+        ..StoreConfig::default()
+    }
+}
+
+/// A spec whose weak subjectivity period is long enough that the restore points of a chain
+/// which started at `HARNESS_GENESIS_TIME` are still usable.
+fn recovery_spec() -> ChainSpec {
+    let mut spec = test_spec::<E>();
+    spec.min_validator_withdrawability_delay = Epoch::new(1 << 40);
+    spec
+}
+
+#[test]
+fn auto_recover_leaves_healthy_database_untouched() {
+    let env = env_builder()
+        .null_logger()
+        .expect("should build env logger")
+        .multi_threaded_tokio_runtime()
+        .expect("should start tokio runtime")
+        .build()
+        .expect("environment should build");
+    let log = env.core_context().log().clone();
+
+    let datadir = tempdir().expect("should create temp directory");
+    let hot_path = datadir.path().join("chain_db");
+    let cold_path = datadir.path().join("freezer_db");
+    let spec = recovery_spec();
+    let (head_block_root, _) = build_disk_chain(&env, &hot_path, &cold_path, &spec);
+
+    // A corrupt restore point is only found by `lighthouse db verify`, and mustn't cause the hot
+    // database to be moved.
+    let store = open_store(&env, &hot_path, &cold_path, &spec);
+    // Restore points are keyed by their index, which is 1 for the restore point at the first
+    // epoch boundary.
+    let restore_point_slot = Slot::new(E::slots_per_epoch());
+    let restore_point_index = restore_point_slot.as_u64() / store_config().slots_per_restore_point;
+    store
+        .cold_db
+        .key_delete(
+            DBColumn::BeaconRestorePoint.into(),
+            Hash256::from_low_u64_be(restore_point_index).as_bytes(),
+        )
+        .expect("should delete restore point");
+    let report = store
+        .verify_integrity(false)
+        .expect("should verify database");
+    assert_eq!(
+        report
+            .corrupt_restore_points
+            .iter()
+            .map(|(slot, _)| *slot)
+            .collect::<Vec<_>>(),
+        vec![restore_point_slot]
+    );
+    drop(store);
+
+    // The genesis state is unknown, so any attempt to re-anchor the database would fail.
+    let mut client_genesis = ClientGenesis::FromStore;
+    let builder = ClientBuilder::<DiskWitness>::new(MinimalEthSpec)
+        .chain_spec(spec.clone())
+        .runtime_context(env.core_context())
+        .disk_store(&hot_path, &cold_path, store_config(), log.clone())
+        .expect("should open database")
+        .recover_disk_store(&mut client_genesis, log)
+        .expect("healthy database should be accepted");
+    assert!(matches!(client_genesis, ClientGenesis::FromStore));
+    assert!(!hot_path.with_extension("corrupt").exists());
+    drop(builder);
+
+    let store = open_store(&env, &hot_path, &cold_path, &spec);
+    assert!(store
+        .get_blinded_block(&head_block_root)
+        .expect("should read head block")
+        .is_some());
+    assert!(BeaconChainBuilder::<DiskWitness>::new(MinimalEthSpec)
+        .store(store)
+        .store_contains_beacon_chain()
+        .expect("should read persisted chain"));
+}
+
+#[test]
+fn auto_recover_reanchors_corrupt_hot_database() {
+    let env = env_builder()
+        .null_logger()
+        .expect("should build env logger")
+        .multi_threaded_tokio_runtime()
+        .expect("should start tokio runtime")
+        .build()
+        .expect("environment should build");
+    let log = env.core_context().log().clone();
+
+    let datadir = tempdir().expect("should create temp directory");
+    let hot_path = datadir.path().join("chain_db");
+    let cold_path = datadir.path().join("freezer_db");
+    let spec = recovery_spec();
+    let (_, genesis_state_bytes) = build_disk_chain(&env, &hot_path, &cold_path, &spec);
+
+    // Delete the state at the split point from the hot database.
+    let store = open_store(&env, &hot_path, &cold_path, &spec);
+    let split = store.get_split_info();
+    store
+        .hot_db
+        .key_delete(DBColumn::BeaconState.into(), split.state_root.as_bytes())
+        .expect("should delete split state");
+    drop(store);
+
+    let mut client_genesis = ClientGenesis::SszBytes {
+        genesis_state_bytes: genesis_state_bytes.clone(),
+    };
+    let builder = ClientBuilder::<DiskWitness>::new(MinimalEthSpec)
+        .chain_spec(spec.clone())
+        .runtime_context(env.core_context())
+        .disk_store(&hot_path, &cold_path, store_config(), log.clone())
+        .expect("should open database")
+        .recover_disk_store(&mut client_genesis, log)
+        .expect("corrupt database should be re-anchored");
+    drop(builder);
+
+    // The node checkpoint syncs from the latest restore point before the split, and the corrupt
+    // hot database is kept.
+    match client_genesis {
+        ClientGenesis::WeakSubjSszBytes {
+            genesis_state_bytes: recovered_genesis_state_bytes,
+            anchor_state_bytes,
+            anchor_block_bytes,
+        } => {
+            assert_eq!(recovered_genesis_state_bytes, genesis_state_bytes);
+            let anchor_state = BeaconState::<E>::from_ssz_bytes(&anchor_state_bytes, &spec)
+                .expect("should decode anchor state");
+            let anchor_block = SignedBeaconBlock::<E>::from_ssz_bytes(&anchor_block_bytes, &spec)
+                .expect("should decode anchor block");
+            assert_eq!(anchor_state.slot(), split.slot - E::slots_per_epoch());
+            assert_eq!(anchor_block.state_root(), anchor_state.canonical_root());
+        }
+        _ => panic!("database should be re-anchored at a restore point"),
+    }
+    assert!(hot_path.with_extension("corrupt").exists());
+}
@@ -4,6 +4,7 @@
 //! attempt) to load into the `crate::intialized_validators::InitializedValidators` struct.
 
 use crate::{default_keystore_password_path, write_file_via_temporary, ZeroizeString};
+use datadir_schema::{Migration, Schema};
 use directory::ensure_dir_exists;
 use eth2_keystore::Keystore;
 use regex::Regex;
@@ -25,6 +26,18 @@ pub const CONFIG_FILENAME: &str = "validator_definitions.yml";
 /// See: https://github.com/sigp/lighthouse/issues/2159
 pub const CONFIG_TEMP_FILENAME: &str = ".validator_definitions.yml.tmp";
 
+/// The layout of the validators directory, see `datadir_schema`.
+pub static VALIDATORS_DIR_SCHEMA: Schema = Schema {
+    current: 1,
+    migrations: &[Migration {
+        to: 1,
+        description: "Add a schema marker to the validators directory",
+        apply: |_| Ok(()),
+    }],
+    metadata_files: &[CONFIG_FILENAME],
+    existing_files: &[CONFIG_FILENAME],
+};
+
 #[derive(Debug)]
 pub enum Error {
     /// The config file could not be opened.
@@ -1,10 +1,13 @@
 //! A helper library for parsing values from `clap::ArgMatches`.
 
 use clap::ArgMatches;
-use eth2_network_config::{Eth2NetworkConfig, DEFAULT_HARDCODED_NETWORK};
+use eth2_network_config::{bundle::fetch_bundle, Eth2NetworkConfig, DEFAULT_HARDCODED_NETWORK};
 use ethereum_types::U256 as Uint256;
+use flags::CONFIG_FILE_FLAG;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use ssz::Decode;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use types::{ChainSpec, Config, EthSpec};
 
@@ -15,13 +18,19 @@ pub const BAD_TESTNET_DIR_MESSAGE: &str = "The hard-coded testnet directory was
                                         or when there is no default public network to connect to. \
                                         During these times you must specify a --testnet-dir.";
 
-/// Try to parse the eth2 network config from the `network`, `testnet-dir` flags in that order.
+/// The directory, relative to the home directory, in which downloaded network configs are cached.
+pub const NETWORK_CONFIG_CACHE_DIR: &str = ".lighthouse/network_configs";
+
+/// Try to parse the eth2 network config from the `network`, `testnet-dir`, `network-config-url`
+/// flags in that order.
 /// Returns the default hardcoded testnet if neither flags are set.
 pub fn get_eth2_network_config(cli_args: &ArgMatches) -> Result<Eth2NetworkConfig, String> {
     let optional_network_config = if cli_args.is_present("network") {
         parse_hardcoded_network(cli_args, "network")?
     } else if cli_args.is_present("testnet-dir") {
         parse_testnet_dir(cli_args, "testnet-dir")?
+    } else if cli_args.is_present("network-config-url") {
+        parse_network_config_url(cli_args, "network-config-url", "network-config-checksum")?
     } else {
         // if neither is present, assume the default network
         Eth2NetworkConfig::constant(DEFAULT_HARDCODED_NETWORK)?
@@ -74,6 +83,27 @@ pub fn parse_testnet_dir(
         .map(Some)
 }
 
+/// Downloads (or loads from the cache) the network config bundle at the URL in `name`, verifying
+/// it against the SHA256 checksum in `checksum_name` if present.
+///
+/// Bundles are cached in `~/.lighthouse/network_configs`.
+pub fn parse_network_config_url(
+    matches: &ArgMatches,
+    name: &'static str,
+    checksum_name: &'static str,
+) -> Result<Option<Eth2NetworkConfig>, String> {
+    let url = parse_required::<String>(matches, name)?;
+    let checksum = parse_optional::<String>(matches, checksum_name)?;
+    let cache_dir = dirs::home_dir()
+        .map(|home| home.join(NETWORK_CONFIG_CACHE_DIR))
+        .ok_or_else(|| format!("Unable to locate home directory to cache {}", url))?;
+
+    let dir = fetch_bundle(&url, checksum.as_deref(), &cache_dir)?;
+    Eth2NetworkConfig::load(dir.clone())
+        .map_err(|e| format!("Invalid network config at {} ({:?}): {}", url, dir, e))
+        .map(Some)
+}
+
 /// Attempts to load a hardcoded network config if `name` is in `matches`, returning an error if
 /// the name is not a valid network name.
 pub fn parse_hardcoded_network(
@@ -190,3 +220,111 @@ where
     }
     Ok(())
 }
+
+/// Returns the name of the environment variable which supplies the argument with the long name
+/// `long`, e.g. `LIGHTHOUSE_TARGET_PEERS` for `--target-peers`.
+pub fn env_var_name(long: &str) -> String {
+    format!("LIGHTHOUSE_{}", long.to_uppercase().replace('-', "_"))
+}
+
+/// Returns `true` if the flag `name`, which doesn't take a value, is set on the command line or by
+/// its environment variable.
+///
+/// `clap` ignores `Arg::env` for flags which don't take a value, so their environment variables are
+/// read here. The variable may be `true` or `1` to set the flag, or `false` or `0` to leave it unset.
+pub fn parse_flag(matches: &ArgMatches, name: &str) -> Result<bool, String> {
+    if matches.is_present(name) {
+        return Ok(true);
+    }
+    let var = env_var_name(name);
+    match std::env::var(&var) {
+        Ok(value) => match value.as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(format!(
+                "Invalid value for {}: {:?}, expected true or false",
+                var, value
+            )),
+        },
+        Err(std::env::VarError::NotPresent) => Ok(false),
+        Err(std::env::VarError::NotUnicode(value)) => Err(format!(
+            "Invalid value for {}: {:?}, expected true or false",
+            var, value
+        )),
+    }
+}
+
+/// Returns `true` if the value of `name` should be read from `matches`.
+///
+/// When a `--config-file` is provided, arguments which were not explicitly supplied on the command
+/// line or by their environment variable (i.e., those which would only yield their `clap` default)
+/// must not override the values from the file. `clap` doesn't count values from the environment as
+/// occurrences, so the environment is checked as well.
+pub fn use_cli_value(matches: &ArgMatches, name: &str) -> bool {
+    !matches.is_present(CONFIG_FILE_FLAG)
+        || matches.occurrences_of(name) > 0
+        || std::env::var_os(env_var_name(name)).is_some()
+}
+
+/// Returns the value of `name`, unless it is a `clap` default which is superseded by a value from
+/// the `--config-file`.
+pub fn cli_value_of<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    if use_cli_value(matches, name) {
+        matches.value_of(name)
+    } else {
+        None
+    }
+}
+
+/// Loads a config of type `T` from the TOML, YAML or JSON file at `path`.
+///
+/// The file may either contain the config itself or, as produced by `lighthouse node
+/// --dump-config`, a mapping which holds the config under the `section` key. Any values missing
+/// from the file are taken from `T::default()`.
+pub fn load_config_file<T>(path: &Path, section: &str) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read config file {:?}: {:?}", path, e))?;
+
+    let mut file_value: Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&contents)
+            .map_err(|e| format!("Unable to parse config file {:?}: {}", path, e))?,
+        Some("yaml") | Some("yml") | Some("json") => serde_yaml::from_str(&contents)
+            .map_err(|e| format!("Unable to parse config file {:?}: {}", path, e))?,
+        _ => {
+            return Err(format!(
+                "Config file {:?} must have a .toml, .yaml, .yml or .json extension",
+                path
+            ))
+        }
+    };
+
+    if let Some(section_value) = file_value.get_mut(section) {
+        file_value = section_value.take();
+    }
+
+    let mut config = serde_json::to_value(T::default())
+        .map_err(|e| format!("Unable to serialize default config: {:?}", e))?;
+    merge_values(&mut config, file_value);
+
+    serde_json::from_value(config).map_err(|e| format!("Invalid config file {:?}: {}", path, e))
+}
+
+/// Recursively overwrites the fields of `base` with those present in `overrides`.
+///
+/// Single-key objects with differing keys are replaced rather than merged, since they represent
+/// different variants of an enum.
+fn merge_values(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides))
+            if !(base.len() == 1 && overrides.len() == 1 && base.keys().ne(overrides.keys())) =>
+        {
+            for (key, value) in overrides {
+                merge_values(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
@@ -0,0 +1,211 @@
+//! Periodically compares the system clock with an NTP server, since a skewed clock causes
+//! attestations and blocks to be produced at the wrong time without any other sign of a problem.
+//!
+//! The drift is measured using a single SNTP request (RFC 4330) and exported as a metric. A
+//! warning is logged whenever it exceeds the configured threshold. The system clock is never
+//! adjusted.
+
+use lazy_static::lazy_static;
+use lighthouse_metrics::{
+    inc_counter, set_gauge, try_create_int_counter, try_create_int_gauge, IntCounter, IntGauge,
+    Result as MetricsResult,
+};
+use serde_derive::{Deserialize, Serialize};
+use slog::{debug, warn, Logger};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::{lookup_host, UdpSocket};
+use tokio::time::timeout;
+
+/// The default NTP server.
+pub const DEFAULT_SERVER: &str = "pool.ntp.org:123";
+/// The default number of seconds between checks.
+pub const DEFAULT_INTERVAL_SECS: u64 = 600;
+/// The default drift, in milliseconds, above which a warning is logged.
+pub const DEFAULT_THRESHOLD_MILLIS: u64 = 500;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const PACKET_LEN: usize = 48;
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+
+lazy_static! {
+    pub static ref CLOCK_DRIFT_MILLISECONDS: MetricsResult<IntGauge> = try_create_int_gauge(
+        "clock_drift_milliseconds",
+        "Offset of the NTP server's clock from the system clock, positive if the system clock \
+         is behind"
+    );
+    pub static ref CLOCK_DRIFT_CHECK_ERRORS_TOTAL: MetricsResult<IntCounter> =
+        try_create_int_counter(
+            "clock_drift_check_errors_total",
+            "Count of NTP requests which failed"
+        );
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The `host:port` of the NTP server.
+    pub server: String,
+    pub interval: Duration,
+    /// Drift above which a warning is logged.
+    pub threshold: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            server: DEFAULT_SERVER.to_string(),
+            interval: Duration::from_secs(DEFAULT_INTERVAL_SECS),
+            threshold: Duration::from_millis(DEFAULT_THRESHOLD_MILLIS),
+        }
+    }
+}
+
+/// Checks the drift of the system clock every `config.interval`, forever.
+pub async fn monitor(config: Config, log: Logger) {
+    let threshold_millis = config.threshold.as_millis() as u64;
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        match query_offset(&config.server).await {
+            Ok(offset_millis) => {
+                set_gauge(&CLOCK_DRIFT_MILLISECONDS, offset_millis);
+                if offset_millis.unsigned_abs() > threshold_millis {
+                    warn!(
+                        log,
+                        "System clock is out of sync";
+                        "drift_ms" => offset_millis,
+                        "ntp_server" => &config.server,
+                        "advice" => "ensure time synchronization (e.g. chrony or \
+                                     systemd-timesyncd) is enabled",
+                    );
+                } else {
+                    debug!(
+                        log,
+                        "Checked system clock";
+                        "drift_ms" => offset_millis,
+                        "ntp_server" => &config.server,
+                    );
+                }
+            }
+            Err(e) => {
+                inc_counter(&CLOCK_DRIFT_CHECK_ERRORS_TOTAL);
+                debug!(
+                    log,
+                    "Unable to check system clock";
+                    "ntp_server" => &config.server,
+                    "error" => e,
+                );
+            }
+        }
+    }
+}
+
+/// Returns the offset of the clock of the NTP `server` from the system clock in milliseconds,
+/// positive if the system clock is behind.
+pub async fn query_offset(server: &str) -> Result<i64, String> {
+    let addr = lookup_host(server)
+        .await
+        .map_err(|e| format!("Unable to resolve {}: {}", server, e))?
+        .next()
+        .ok_or_else(|| format!("No addresses for {}", server))?;
+    let bind_addr: SocketAddr = if addr.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind_addr)
+        .await
+        .map_err(|e| format!("Unable to bind socket: {}", e))?;
+    socket
+        .connect(addr)
+        .await
+        .map_err(|e| format!("Unable to connect to {}: {}", addr, e))?;
+
+    let mut request = [0; PACKET_LEN];
+    // Leap indicator 0, version 3, client mode.
+    request[0] = 0x1b;
+    let sent = unix_now()?;
+    request[40..48].copy_from_slice(&to_ntp_timestamp(sent).to_be_bytes());
+    socket
+        .send(&request)
+        .await
+        .map_err(|e| format!("Unable to send request: {}", e))?;
+
+    let mut response = [0; PACKET_LEN];
+    let len = timeout(REQUEST_TIMEOUT, socket.recv(&mut response))
+        .await
+        .map_err(|_| "Request timed out".to_string())?
+        .map_err(|e| format!("Unable to receive response: {}", e))?;
+    let received = unix_now()?;
+
+    // The response must be from a server with a valid stratum (0 indicates a "kiss-of-death"
+    // packet) and must echo the transmit time of the request.
+    if len < PACKET_LEN
+        || response[0] & 0x7 != 4
+        || response[1] == 0
+        || response[24..32] != request[40..48]
+    {
+        return Err("Invalid response".to_string());
+    }
+
+    let server_received = from_ntp_timestamp(read_timestamp(&response[32..40]));
+    let server_sent = from_ntp_timestamp(read_timestamp(&response[40..48]));
+    Ok(offset_millis(sent, server_received, server_sent, received))
+}
+
+/// Returns the clock offset from the client send, server receive, server send and client receive
+/// times (in seconds), as defined by RFC 4330.
+fn offset_millis(sent: f64, server_received: f64, server_sent: f64, received: f64) -> i64 {
+    (((server_received - sent) + (server_sent - received)) / 2.0 * 1000.0).round() as i64
+}
+
+fn unix_now() -> Result<f64, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .map_err(|e| format!("System time is before the Unix epoch: {}", e))
+}
+
+fn read_timestamp(bytes: &[u8]) -> u64 {
+    let mut timestamp = [0; 8];
+    timestamp.copy_from_slice(bytes);
+    u64::from_be_bytes(timestamp)
+}
+
+/// Converts seconds since the Unix epoch to a 64-bit NTP timestamp.
+fn to_ntp_timestamp(unix_secs: f64) -> u64 {
+    let secs = unix_secs.trunc() as u64 + NTP_UNIX_OFFSET_SECS;
+    let fraction = (unix_secs.fract() * 2_f64.powi(32)) as u64;
+    (secs << 32) | fraction
+}
+
+/// Converts a 64-bit NTP timestamp to seconds since the Unix epoch.
+fn from_ntp_timestamp(timestamp: u64) -> f64 {
+    let secs = (timestamp >> 32) as f64 - NTP_UNIX_OFFSET_SECS as f64;
+    let fraction = (timestamp & 0xffff_ffff) as f64 / 2_f64.powi(32);
+    secs + fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ntp_timestamp_round_trip() {
+        let unix_secs = 1_681_000_000.25;
+        assert_eq!(
+            to_ntp_timestamp(unix_secs) >> 32,
+            1_681_000_000 + NTP_UNIX_OFFSET_SECS
+        );
+        assert_eq!(from_ntp_timestamp(to_ntp_timestamp(unix_secs)), unix_secs);
+    }
+
+    #[test]
+    fn offset() {
+        // The system clock is 1s behind the server, with a 100ms round trip.
+        assert_eq!(offset_millis(10.0, 11.05, 11.06, 10.11), 1_000);
+        // The system clock is 2s ahead of the server.
+        assert_eq!(offset_millis(10.0, 8.05, 8.05, 10.1), -2_000);
+    }
+}
@@ -0,0 +1,348 @@
+//! Versions the files in a Lighthouse data directory, and migrates them between releases.
+//!
+//! Each data directory contains a `schema.json` marker recording the version of its layout. At
+//! startup the marker is compared with the `Schema` supported by the running binary and any
+//! outstanding `Migration`s are applied in order, after backing up the small metadata files of
+//! the directory. Data directories created before the marker existed are treated as version `0`.
+//!
+//! Databases with their own schema version (such as the beacon node's hot and freezer databases)
+//! are migrated by their own code, but the caller should still take a backup via `backup` before
+//! allowing them to migrate.
+
+use serde_derive::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name of the schema marker in a data directory.
+pub const SCHEMA_FILENAME: &str = "schema.json";
+/// The directory within a data directory to which backups are written.
+pub const BACKUP_DIR: &str = "backups";
+/// Files larger than this are never backed up, since the backups are intended for metadata.
+pub const MAX_BACKUP_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The data directory was written by a newer version of Lighthouse.
+    UnsupportedSchemaVersion {
+        on_disk: u64,
+        supported: u64,
+    },
+    /// The migration to `version` failed.
+    MigrationFailed {
+        version: u64,
+        error: String,
+    },
+    InvalidMarker(PathBuf, String),
+    /// A database within the data directory could not be read.
+    Database(PathBuf, String),
+    Io(PathBuf, std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnsupportedSchemaVersion { on_disk, supported } => write!(
+                f,
+                "The data directory has schema version {}, but this version of Lighthouse only \
+                 supports up to version {}. Upgrade Lighthouse, or restore a backup made by \
+                 this version",
+                on_disk, supported
+            ),
+            Error::MigrationFailed { version, error } => write!(
+                f,
+                "Migration to data directory schema version {} failed: {}",
+                version, error
+            ),
+            Error::InvalidMarker(path, e) => write!(f, "Invalid schema marker {:?}: {}", path, e),
+            Error::Database(path, e) => write!(f, "Unable to read database {:?}: {}", path, e),
+            Error::Io(path, e) => write!(f, "Unable to access {:?}: {:?}", path, e),
+        }
+    }
+}
+
+/// The contents of the schema marker.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SchemaMarker {
+    pub version: u64,
+}
+
+/// A change to the files of a data directory.
+pub struct Migration {
+    /// The version which this migration upgrades to, from the version before it.
+    pub to: u64,
+    pub description: &'static str,
+    /// Applies the migration to the data directory at the given path.
+    pub apply: fn(&Path) -> Result<(), String>,
+}
+
+/// The layout of a kind of data directory.
+pub struct Schema {
+    /// The version written by this version of Lighthouse.
+    pub current: u64,
+    /// The migrations to each version from `1` to `current`, in order.
+    pub migrations: &'static [Migration],
+    /// The paths, relative to the data directory, of the files copied by `backup`.
+    pub metadata_files: &'static [&'static str],
+    /// Paths, relative to the data directory, whose presence indicates that a data directory
+    /// without a schema marker was created before the marker existed, rather than being new.
+    pub existing_files: &'static [&'static str],
+}
+
+/// The migrations required to bring a data directory up to date.
+pub struct Plan<'a> {
+    pub dir: PathBuf,
+    /// The version of the data directory on disk.
+    pub from: u64,
+    pub to: u64,
+    pub migrations: Vec<&'a Migration>,
+    /// False if the marker must be written, e.g. for a new data directory.
+    pub marker_exists: bool,
+}
+
+impl<'a> Plan<'a> {
+    /// Returns `true` if the data directory is at the current version.
+    pub fn is_current(&self) -> bool {
+        self.migrations.is_empty()
+    }
+}
+
+impl<'a> fmt::Display for Plan<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_current() {
+            return write!(f, "{:?} is at schema version {}", self.dir, self.to);
+        }
+        write!(
+            f,
+            "{:?} will be migrated from schema version {} to {}:",
+            self.dir, self.from, self.to
+        )?;
+        for migration in &self.migrations {
+            write!(f, "\n  {}: {}", migration.to, migration.description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the migrations required to bring the data directory at `dir` up to the current version
+/// of `schema`, without modifying it.
+pub fn plan<'a>(dir: &Path, schema: &'a Schema) -> Result<Plan<'a>, Error> {
+    let marker_path = dir.join(SCHEMA_FILENAME);
+    let (from, marker_exists) = if marker_path.exists() {
+        let bytes = fs::read(&marker_path).map_err(|e| Error::Io(marker_path.clone(), e))?;
+        let marker: SchemaMarker = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::InvalidMarker(marker_path.clone(), e.to_string()))?;
+        (marker.version, true)
+    } else if schema
+        .existing_files
+        .iter()
+        .any(|path| dir.join(path).exists())
+    {
+        (0, false)
+    } else {
+        (schema.current, false)
+    };
+
+    if from > schema.current {
+        return Err(Error::UnsupportedSchemaVersion {
+            on_disk: from,
+            supported: schema.current,
+        });
+    }
+
+    Ok(Plan {
+        dir: dir.to_path_buf(),
+        from,
+        to: schema.current,
+        migrations: schema
+            .migrations
+            .iter()
+            .filter(|migration| migration.to > from)
+            .collect(),
+        marker_exists,
+    })
+}
+
+/// Copies the metadata files of `schema` which exist in `dir` into a new directory within
+/// `dir/backups`, returning the path of that directory, or `None` if there were no files to copy.
+///
+/// Files larger than `MAX_BACKUP_FILE_SIZE` are skipped.
+pub fn backup(dir: &Path, schema: &Schema, from: u64) -> Result<Option<PathBuf>, Error> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let backup_dir = dir
+        .join(BACKUP_DIR)
+        .join(format!("schema-v{}-{}", from, timestamp));
+
+    let mut copied = false;
+    for file in schema.metadata_files.iter().chain(Some(&SCHEMA_FILENAME)) {
+        let source = dir.join(file);
+        match fs::metadata(&source) {
+            Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_BACKUP_FILE_SIZE => (),
+            _ => continue,
+        }
+
+        let dest = backup_dir.join(file);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::Io(parent.to_path_buf(), e))?;
+        }
+        fs::copy(&source, &dest).map_err(|e| Error::Io(dest.clone(), e))?;
+        copied = true;
+    }
+
+    Ok(copied.then_some(backup_dir))
+}
+
+/// Applies the migrations of `plan` in order, updating the schema marker after each one so that
+/// an interrupted migration resumes from the last one to complete.
+pub fn apply(plan: &Plan, log: &Logger) -> Result<(), Error> {
+    for migration in &plan.migrations {
+        info!(
+            log,
+            "Migrating data directory";
+            "dir" => ?plan.dir,
+            "to_version" => migration.to,
+            "description" => migration.description,
+        );
+        (migration.apply)(&plan.dir).map_err(|error| Error::MigrationFailed {
+            version: migration.to,
+            error,
+        })?;
+        write_marker(&plan.dir, migration.to)?;
+    }
+
+    if !plan.marker_exists && plan.migrations.is_empty() {
+        write_marker(&plan.dir, plan.to)?;
+    }
+    Ok(())
+}
+
+/// Backs up the data directory at `dir` if it requires migration, then migrates it to the current
+/// version of `schema`.
+pub fn migrate(dir: &Path, schema: &Schema, log: &Logger) -> Result<(), Error> {
+    let plan = plan(dir, schema)?;
+    if !plan.is_current() {
+        if let Some(backup_dir) = backup(dir, schema, plan.from)? {
+            info!(log, "Backed up data directory metadata"; "path" => ?backup_dir);
+        }
+    }
+    apply(&plan, log)
+}
+
+/// Writes the marker atomically, so that it's never left partially written.
+fn write_marker(dir: &Path, version: u64) -> Result<(), Error> {
+    fs::create_dir_all(dir).map_err(|e| Error::Io(dir.to_path_buf(), e))?;
+    let path = dir.join(SCHEMA_FILENAME);
+    let temp_path = dir.join(format!("{}.tmp", SCHEMA_FILENAME));
+    let bytes = serde_json::to_vec(&SchemaMarker { version })
+        .map_err(|e| Error::InvalidMarker(path.clone(), e.to_string()))?;
+    fs::write(&temp_path, bytes).map_err(|e| Error::Io(temp_path.clone(), e))?;
+    fs::rename(&temp_path, &path).map_err(|e| Error::Io(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const DATA_FILE: &str = "data.txt";
+
+    static SCHEMA: Schema = Schema {
+        current: 2,
+        migrations: &[
+            Migration {
+                to: 1,
+                description: "add a schema marker",
+                apply: |_| Ok(()),
+            },
+            Migration {
+                to: 2,
+                description: "upper-case the data",
+                apply: |dir| {
+                    let path = dir.join(DATA_FILE);
+                    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+                    fs::write(&path, data.to_uppercase()).map_err(|e| e.to_string())
+                },
+            },
+        ],
+        metadata_files: &[DATA_FILE],
+        existing_files: &[DATA_FILE],
+    };
+
+    fn logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
+    fn read_marker(dir: &Path) -> SchemaMarker {
+        serde_json::from_slice(&fs::read(dir.join(SCHEMA_FILENAME)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn new_dir() {
+        let dir = TempDir::new().unwrap();
+        let plan = plan(dir.path(), &SCHEMA).unwrap();
+        assert!(plan.is_current());
+        assert!(!plan.marker_exists);
+
+        migrate(dir.path(), &SCHEMA, &logger()).unwrap();
+        assert_eq!(read_marker(dir.path()), SchemaMarker { version: 2 });
+        assert!(!dir.path().join(BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn legacy_dir() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(DATA_FILE), "data").unwrap();
+
+        let plan = plan(dir.path(), &SCHEMA).unwrap();
+        assert_eq!(plan.from, 0);
+        assert_eq!(plan.migrations.len(), 2);
+
+        migrate(dir.path(), &SCHEMA, &logger()).unwrap();
+        assert_eq!(read_marker(dir.path()), SchemaMarker { version: 2 });
+        assert_eq!(
+            fs::read_to_string(dir.path().join(DATA_FILE)).unwrap(),
+            "DATA"
+        );
+
+        // The original file is backed up.
+        let backups = fs::read_dir(dir.path().join(BACKUP_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            fs::read_to_string(backups[0].join(DATA_FILE)).unwrap(),
+            "data"
+        );
+    }
+
+    #[test]
+    fn partial_migration() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(DATA_FILE), "data").unwrap();
+        write_marker(dir.path(), 1).unwrap();
+
+        let plan = plan(dir.path(), &SCHEMA).unwrap();
+        assert_eq!(plan.from, 1);
+        assert_eq!(plan.migrations.len(), 1);
+        assert_eq!(plan.migrations[0].to, 2);
+    }
+
+    #[test]
+    fn newer_version() {
+        let dir = TempDir::new().unwrap();
+        write_marker(dir.path(), 3).unwrap();
+        assert!(matches!(
+            plan(dir.path(), &SCHEMA),
+            Err(Error::UnsupportedSchemaVersion {
+                on_disk: 3,
+                supported: 2
+            })
+        ));
+    }
+}
@@ -11,18 +11,22 @@ pub const DEFAULT_VALIDATOR_DIR: &str = "validators";
 pub const DEFAULT_SECRET_DIR: &str = "secrets";
 pub const DEFAULT_WALLET_DIR: &str = "wallets";
 
-/// Base directory name for unnamed testnets passed through the --testnet-dir flag
+/// Base directory name for unnamed testnets passed through the --testnet-dir or
+/// --network-config-url flags
 pub const CUSTOM_TESTNET_DIR: &str = "custom";
 
 /// Gets the network directory name
 ///
 /// Tries to get the name first from the "network" flag,
-/// if not present, then checks the "testnet-dir" flag and returns a custom name
+/// if not present, then checks the "testnet-dir" and "network-config-url" flags and returns a
+/// custom name
 /// If neither flags are present, returns the default hardcoded network name.
 pub fn get_network_dir(matches: &ArgMatches) -> String {
     if let Some(network_name) = matches.value_of("network") {
         network_name.to_string()
-    } else if matches.value_of("testnet-dir").is_some() {
+    } else if matches.value_of("testnet-dir").is_some()
+        || matches.value_of("network-config-url").is_some()
+    {
         CUSTOM_TESTNET_DIR.to_string()
     } else {
         eth2_network_config::DEFAULT_HARDCODED_NETWORK.to_string()
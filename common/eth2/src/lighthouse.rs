@@ -23,14 +23,15 @@ use ssz_derive::{Decode, Encode};
 use store::{AnchorInfo, Split, StoreConfig};
 
 pub use attestation_performance::{
-    AttestationPerformance, AttestationPerformanceQuery, AttestationPerformanceStatistics,
+    AttestationPerformance, AttestationPerformanceQuery, AttestationPerformanceRequest,
+    AttestationPerformanceStatistics, AttestationPerformanceSummary, AttestationPerformanceTotals,
 };
 pub use attestation_rewards::StandardAttestationRewards;
 pub use block_packing_efficiency::{
     BlockPackingEfficiency, BlockPackingEfficiencyQuery, ProposerInfo, UniqueAttestation,
 };
 pub use block_rewards::{AttestationRewards, BlockReward, BlockRewardMeta, BlockRewardsQuery};
-pub use lighthouse_network::{types::SyncState, PeerInfo};
+pub use lighthouse_network::{types::SyncState, AccessListEntry, PeerInfo};
 pub use standard_block_rewards::StandardBlockReward;
 pub use sync_committee_rewards::SyncCommitteeReward;
 
@@ -50,6 +51,58 @@ pub struct Peer<T: EthSpec> {
     pub peer_info: PeerInfo<T>,
 }
 
+/// An estimate of whether each of the node's ports is reachable from the internet, returned by
+/// `lighthouse/nat/ports`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NatPorts {
+    /// The UDP port advertised for discovery in the local ENR.
+    pub discovery_port: Option<u16>,
+    /// True if other nodes have reported our discovery address back to us.
+    pub discovery_reachable: bool,
+    /// The TCP port libp2p is listening on.
+    pub libp2p_port: Option<u16>,
+    /// True if at least one peer has connected to us.
+    pub libp2p_reachable: bool,
+}
+
+/// The peers and IP subnets which are allowed or denied connections, returned by
+/// `lighthouse/peers/access_list`.
+///
+/// The same structure is posted to `lighthouse/peers/access_list/add` and
+/// `lighthouse/peers/access_list/remove` to update the lists, in which case either may be omitted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerAccessList {
+    /// If non-empty, only peers matching an entry of the allowlist may connect.
+    #[serde(default)]
+    pub allowlist: Vec<AccessListEntry>,
+    /// Peers matching an entry of the denylist may not connect.
+    #[serde(default)]
+    pub denylist: Vec<AccessListEntry>,
+}
+
+/// A query posted to `lighthouse/graphql`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variables: Option<serde_json::Value>,
+}
+
+/// The result of a query posted to `lighthouse/graphql`.
+///
+/// Errors are reported in `errors` along with any data which could be resolved, rather than by the
+/// status of the response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLResponse {
+    #[serde(default)]
+    pub data: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<serde_json::Value>,
+}
+
 /// The results of validators voting during an epoch.
 ///
 /// Provides information about the current and previous epochs.
@@ -306,6 +359,32 @@ pub struct Eth1SyncStatusData {
     pub lighthouse_is_cached_and_ready: bool,
 }
 
+/// The health of a beacon node, as returned by `GET lighthouse/health/node`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeHealth {
+    /// `true` if none of the checks failed, in which case the response has a `200` status code.
+    pub healthy: bool,
+    /// A description of each check which failed.
+    pub issues: Vec<String>,
+    pub sync_state: SyncState,
+    pub head_slot: Slot,
+    /// The slot according to the wall clock.
+    pub current_slot: Slot,
+    /// The number of slots by which the head is behind the wall clock.
+    pub head_distance: u64,
+    pub peer_count: usize,
+    /// Whether the eth1 deposit cache is ready, or `None` if eth1 sync is disabled.
+    pub eth1_connected: Option<bool>,
+    /// Whether the execution engine is online and synced, or `None` if there is no execution
+    /// engine.
+    pub execution_engine_synced: Option<bool>,
+    /// Free space on the filesystem containing the data directory, or `None` if it could not be
+    /// read.
+    pub disk_bytes_free: Option<u64>,
+    /// The schema version of the database, or `None` if it could not be read.
+    pub db_schema_version: Option<u64>,
+}
+
 /// A fully parsed eth1 deposit contract log.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct DepositLog {
@@ -359,6 +438,10 @@ pub struct DatabaseInfo {
     pub config: StoreConfig,
     pub split: Split,
     pub anchor: Option<AnchorInfo>,
+    /// The size of the hot database on disk, in bytes.
+    pub hot_db_size: Option<u64>,
+    /// The size of the freezer database on disk, in bytes.
+    pub freezer_db_size: Option<u64>,
 }
 
 impl BeaconNodeHttpClient {
@@ -395,6 +478,40 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/health/node`
+    ///
+    /// Returns the health of the node, regardless of whether it is healthy.
+    pub async fn get_lighthouse_health_node(&self) -> Result<GenericResponse<NodeHealth>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("health")
+            .push("node");
+
+        let response = self.client.get(path).send().await?;
+        let status = response.status();
+        if status == StatusCode::OK || status == StatusCode::SERVICE_UNAVAILABLE {
+            Ok(response.json().await?)
+        } else {
+            Err(Error::StatusCode(status))
+        }
+    }
+
+    /// `GET lighthouse/nat/ports`
+    pub async fn get_lighthouse_nat_ports(&self) -> Result<GenericResponse<NatPorts>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("nat")
+            .push("ports");
+
+        self.get(path).await
+    }
+
     /// `GET lighthouse/syncing`
     pub async fn get_lighthouse_syncing(&self) -> Result<GenericResponse<SyncState>, Error> {
         let mut path = self.server.full.clone();
@@ -415,6 +532,70 @@ impl BeaconNodeHttpClient {
      * fairly simply achieved, if desired.
      */
 
+    /// `GET lighthouse/peers/access_list`
+    pub async fn get_lighthouse_peers_access_list(
+        &self,
+    ) -> Result<GenericResponse<PeerAccessList>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push("access_list");
+
+        self.get(path).await
+    }
+
+    /// `POST lighthouse/peers/access_list/add`
+    pub async fn post_lighthouse_peers_access_list_add(
+        &self,
+        entries: &PeerAccessList,
+    ) -> Result<GenericResponse<PeerAccessList>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push("access_list")
+            .push("add");
+
+        self.post_with_response(path, entries).await
+    }
+
+    /// `POST lighthouse/peers/access_list/remove`
+    pub async fn post_lighthouse_peers_access_list_remove(
+        &self,
+        entries: &PeerAccessList,
+    ) -> Result<GenericResponse<PeerAccessList>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push("access_list")
+            .push("remove");
+
+        self.post_with_response(path, entries).await
+    }
+
+    /// `POST lighthouse/graphql`
+    pub async fn post_lighthouse_graphql(
+        &self,
+        request: &GraphQLRequest,
+    ) -> Result<GraphQLResponse, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("graphql");
+
+        self.post_with_response(path, request).await
+    }
+
     /// `GET lighthouse/proto_array`
     pub async fn get_lighthouse_proto_array(&self) -> Result<GenericResponse<ProtoArray>, Error> {
         let mut path = self.server.full.clone();
@@ -529,6 +710,21 @@ impl BeaconNodeHttpClient {
             .transpose()
     }
 
+    /// `GET lighthouse/debug/heap`
+    ///
+    /// Returns `Ok(None)` on a 404 error, i.e. if heap profiling is not enabled.
+    pub async fn get_lighthouse_debug_heap(&self) -> Result<Option<Vec<u8>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("debug")
+            .push("heap");
+
+        self.get_bytes_opt(path).await
+    }
+
     /// `GET lighthouse/staking`
     pub async fn get_lighthouse_staking(&self) -> Result<bool, Error> {
         let mut path = self.server.full.clone();
@@ -635,4 +831,20 @@ impl BeaconNodeHttpClient {
 
         self.get(path).await
     }
+
+    /// `POST` lighthouse/analysis/attestation_performance
+    pub async fn post_lighthouse_analysis_attestation_performance(
+        &self,
+        request: &AttestationPerformanceRequest,
+    ) -> Result<AttestationPerformanceSummary, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("analysis")
+            .push("attestation_performance");
+
+        self.post_with_response(path, request).await
+    }
 }
@@ -37,3 +37,53 @@ pub struct AttestationPerformanceQuery {
     pub start_epoch: Epoch,
     pub end_epoch: Epoch,
 }
+
+/// Request body for the `POST /lighthouse/analysis/attestation_performance` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AttestationPerformanceRequest {
+    pub start_epoch: Epoch,
+    pub end_epoch: Epoch,
+    pub indices: Vec<u64>,
+}
+
+/// The attestation performance of a set of validators, summed over all of their epochs.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AttestationPerformanceTotals {
+    /// The number of epochs in which a validator was active.
+    pub active: u64,
+    pub head: u64,
+    pub target: u64,
+    pub source: u64,
+    /// The mean inclusion delay of the included attestations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_delay: Option<f64>,
+}
+
+impl AttestationPerformanceTotals {
+    pub fn from_performances(performances: &[AttestationPerformance]) -> Self {
+        let mut totals = Self::default();
+        let mut total_delay = 0;
+        let mut included = 0;
+        for statistics in performances.iter().flat_map(|perf| perf.epochs.values()) {
+            totals.active += statistics.active as u64;
+            totals.head += statistics.head as u64;
+            totals.target += statistics.target as u64;
+            totals.source += statistics.source as u64;
+            if let Some(delay) = statistics.delay {
+                total_delay += delay;
+                included += 1;
+            }
+        }
+        if included > 0 {
+            totals.mean_delay = Some(total_delay as f64 / included as f64);
+        }
+        totals
+    }
+}
+
+/// Response of the `POST /lighthouse/analysis/attestation_performance` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AttestationPerformanceSummary {
+    pub totals: AttestationPerformanceTotals,
+    pub validators: Vec<AttestationPerformance>,
+}
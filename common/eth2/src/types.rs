@@ -208,6 +208,20 @@ pub struct ExecutionOptimisticFinalizedResponse<T: Serialize + serde::de::Deseri
     pub data: T,
 }
 
+/// A page of a response which is split across requests.
+///
+/// `next_page` is the `page` to request for the rest of the response, and is absent from the last
+/// page, so an unpaginated response is the same as an `ExecutionOptimisticFinalizedResponse`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + serde::de::DeserializeOwned")]
+pub struct ExecutionOptimisticFinalizedPageResponse<T: Serialize + serde::de::DeserializeOwned> {
+    pub execution_optimistic: Option<bool>,
+    pub finalized: Option<bool>,
+    pub data: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_page: Option<eth2_serde_utils::quoted_u64::Quoted<u64>>,
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(bound = "T: Serialize + serde::de::DeserializeOwned")]
 pub struct GenericResponse<T: Serialize + serde::de::DeserializeOwned> {
@@ -275,7 +289,7 @@ pub struct FinalityCheckpointsData {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(try_from = "&str")]
+#[serde(into = "String", try_from = "&str")]
 pub enum ValidatorId {
     PublicKey(PublicKeyBytes),
     Index(u64),
@@ -305,6 +319,12 @@ impl FromStr for ValidatorId {
     }
 }
 
+impl From<ValidatorId> for String {
+    fn from(id: ValidatorId) -> Self {
+        id.to_string()
+    }
+}
+
 impl fmt::Display for ValidatorId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -487,6 +507,18 @@ pub struct ValidatorsQuery {
     pub id: Option<Vec<ValidatorId>>,
     #[serde(default, deserialize_with = "option_query_vec")]
     pub status: Option<Vec<ValidatorStatus>>,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+/// The body of `POST beacon/states/{state_id}/validators`, which allows more validators to be
+/// requested than would fit in a query string.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ValidatorsRequestBody {
+    #[serde(default)]
+    pub ids: Option<Vec<ValidatorId>>,
+    #[serde(default)]
+    pub statuses: Option<Vec<ValidatorStatus>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -517,6 +549,12 @@ pub struct SyncSubcommittee {
     pub indices: Vec<u64>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct LightClientUpdatesQuery {
+    pub start_period: u64,
+    pub count: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HeadersQuery {
     pub slot: Option<Slot>,
@@ -645,6 +683,16 @@ impl<T: FromStr> From<QueryVec<T>> for Vec<T> {
 pub struct ValidatorBalancesQuery {
     #[serde(default, deserialize_with = "option_query_vec")]
     pub id: Option<Vec<ValidatorId>>,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+/// Splits the validators or balances of a state into pages of at most `limit` validators, starting
+/// from the validator with index `page`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -1095,6 +1143,17 @@ pub struct EventQuery {
     pub topics: Vec<EventTopic>,
 }
 
+/// The query of the `lighthouse/events/ws` WebSocket endpoint.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketEventQuery {
+    #[serde(deserialize_with = "query_vec")]
+    pub topics: Vec<EventTopic>,
+    /// Only send events which concern these validators, or the chain as a whole.
+    #[serde(default, deserialize_with = "option_query_vec")]
+    pub validator_indices: Option<Vec<u64>>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventTopic {
@@ -0,0 +1,368 @@
+//! Downloads network configuration bundles, so that custom networks can be joined without
+//! assembling a testnet directory by hand.
+//!
+//! A bundle is a `.zip` or `.tar.gz` archive containing the files of a testnet directory (i.e.
+//! `config.yaml`, `deploy_block.txt` and optionally `genesis.ssz` and `boot_enr.yaml`), either at
+//! the root of the archive or in a single directory.
+
+use crate::{BASE_CONFIG_FILE, BOOT_ENR_FILE, DEPLOY_BLOCK_FILE, GENESIS_STATE_FILE};
+use eth2_hashing::hash;
+use flate2::read::GzDecoder;
+use sensitive_url::NO_PROXY_HOSTS;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use zip::ZipArchive;
+
+/// The files which are extracted from a bundle.
+const BUNDLE_FILES: &[&str] = &[
+    BASE_CONFIG_FILE,
+    BOOT_ENR_FILE,
+    DEPLOY_BLOCK_FILE,
+    GENESIS_STATE_FILE,
+];
+/// The name of the downloaded archive in the cache.
+const ARCHIVE_FILE: &str = "bundle";
+/// The name of the directory in the cache to which the archive is extracted.
+const CONFIG_DIR: &str = "config";
+/// The timeout for downloading a bundle, which may contain a large genesis state.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Downloads the bundle at `url` into `cache_dir`, returning the path of a testnet directory
+/// containing its files.
+///
+/// If `checksum` is provided the bundle must have that SHA256 hash (as hex). A previously
+/// downloaded bundle is used instead of downloading it again, provided it still matches the
+/// `checksum`.
+///
+/// Must not be called from within an async runtime.
+pub fn fetch_bundle(
+    url: &str,
+    checksum: Option<&str>,
+    cache_dir: &Path,
+) -> Result<PathBuf, String> {
+    // Each URL is cached in its own directory.
+    let dir = cache_dir.join(hex::encode(&hash(url.as_bytes())[..8]));
+    let archive_path = dir.join(ARCHIVE_FILE);
+    let config_dir = dir.join(CONFIG_DIR);
+
+    if archive_path.exists() && config_dir.join(BASE_CONFIG_FILE).exists() {
+        let cached = fs::read(&archive_path)
+            .map_err(|e| format!("Unable to read {:?}: {:?}", archive_path, e))?;
+        if checksum.map_or(true, |checksum| verify_checksum(&cached, checksum).is_ok()) {
+            return Ok(config_dir);
+        }
+    }
+
+    let bytes = download(url, None)?;
+    if let Some(checksum) = checksum {
+        verify_checksum(&bytes, checksum)?;
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create {:?}: {:?}", dir, e))?;
+    if config_dir.exists() {
+        fs::remove_dir_all(&config_dir)
+            .map_err(|e| format!("Unable to remove {:?}: {:?}", config_dir, e))?;
+    }
+    extract_bundle(&bytes, &config_dir)?;
+    // The archive is written last, so that it's only cached once it has been extracted.
+    fs::write(&archive_path, &bytes)
+        .map_err(|e| format!("Unable to write {:?}: {:?}", archive_path, e))?;
+
+    Ok(config_dir)
+}
+
+/// Downloads the contents of `url`, through the HTTP or SOCKS5 proxy at `proxy` if one is given
+/// and `url` isn't one of the `NO_PROXY_HOSTS`.
+///
+/// Must not be called from within an async runtime.
+pub fn download(url: &str, proxy: Option<&str>) -> Result<Vec<u8>, String> {
+    let mut builder = reqwest::blocking::Client::builder().timeout(DOWNLOAD_TIMEOUT);
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| format!("Invalid proxy: {}", e))?
+            .no_proxy(reqwest::NoProxy::from_string(NO_PROXY_HOSTS));
+        builder = builder.proxy(proxy);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| format!("Unable to build HTTP client: {:?}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Unable to download {}: {}", url, e))?;
+    response
+        .bytes()
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Unable to download {}: {}", url, e))
+}
+
+/// Checks that the SHA256 hash of `bytes` is the hex string `checksum`.
+pub fn verify_checksum(bytes: &[u8], checksum: &str) -> Result<(), String> {
+    let expected = checksum.trim_start_matches("0x").to_lowercase();
+    let actual = hex::encode(hash(bytes));
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "Network config checksum mismatch, expected {} but got {}",
+            expected, actual
+        ))
+    }
+}
+
+/// Extracts the files of a testnet directory from the `.zip` or `.tar.gz` archive `bytes` into
+/// `dir`.
+///
+/// Only the files named in `BUNDLE_FILES` are extracted, and they are always written directly
+/// into `dir` regardless of their path in the archive.
+pub fn extract_bundle(bytes: &[u8], dir: &Path) -> Result<(), String> {
+    let files = if bytes.starts_with(b"PK\x03\x04") {
+        read_zip(bytes)?
+    } else if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut tar = vec![];
+        GzDecoder::new(bytes)
+            .read_to_end(&mut tar)
+            .map_err(|e| format!("Unable to decompress network config bundle: {:?}", e))?;
+        read_tar(&tar)?
+    } else {
+        return Err("Network config bundle must be a .zip or .tar.gz archive".to_string());
+    };
+
+    if !files.iter().any(|(name, _)| name == BASE_CONFIG_FILE) {
+        return Err(format!(
+            "Network config bundle does not contain {}",
+            BASE_CONFIG_FILE
+        ));
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Unable to create {:?}: {:?}", dir, e))?;
+    for (name, contents) in files {
+        let path = dir.join(&name);
+        if path.exists() {
+            return Err(format!("Network config bundle contains {} twice", name));
+        }
+        fs::write(&path, contents).map_err(|e| format!("Unable to write {:?}: {:?}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Returns the name of the file at `path` in an archive, if it is one of the `BUNDLE_FILES`.
+fn bundle_file_name(path: &str) -> Option<String> {
+    let name = path.rsplit('/').next()?;
+    BUNDLE_FILES.contains(&name).then(|| name.to_string())
+}
+
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("Unable to read network config bundle: {}", e))?;
+
+    let mut files = vec![];
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| format!("Unable to read network config bundle: {}", e))?;
+        if file.is_dir() {
+            continue;
+        }
+        if let Some(name) = bundle_file_name(file.name()) {
+            let mut contents = vec![];
+            file.read_to_end(&mut contents)
+                .map_err(|e| format!("Unable to read {} from bundle: {:?}", name, e))?;
+            files.push((name, contents));
+        }
+    }
+    Ok(files)
+}
+
+/// Reads the regular files of an uncompressed tar archive.
+fn read_tar(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    const BLOCK_SIZE: usize = 512;
+
+    let field = |header: &[u8]| -> String {
+        let end = header.iter().position(|b| *b == 0).unwrap_or(header.len());
+        String::from_utf8_lossy(&header[..end]).to_string()
+    };
+
+    let mut files = vec![];
+    let mut offset = 0;
+    while offset + BLOCK_SIZE <= bytes.len() {
+        let header = &bytes[offset..offset + BLOCK_SIZE];
+        // The archive is terminated by zero-filled blocks.
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+
+        let size_field = field(&header[124..136]);
+        let size = usize::from_str_radix(size_field.trim(), 8)
+            .map_err(|e| format!("Invalid tar entry size {:?}: {:?}", size_field, e))?;
+        let start = offset + BLOCK_SIZE;
+        let end = start
+            .checked_add(size)
+            .filter(|end| *end <= bytes.len())
+            .ok_or("Network config bundle is truncated")?;
+
+        // Only regular files are read, all other entries (e.g. directories) are skipped.
+        let type_flag = header[156];
+        if type_flag == b'0' || type_flag == 0 {
+            let name = field(&header[0..100]);
+            // POSIX archives may split long paths into a prefix and a name.
+            let path = match field(&header[345..500]) {
+                prefix if &header[257..263] == b"ustar\0" && !prefix.is_empty() => {
+                    format!("{}/{}", prefix, name)
+                }
+                _ => name,
+            };
+            if let Some(name) = bundle_file_name(&path) {
+                files.push((name, bytes[start..end].to_vec()));
+            }
+        }
+
+        offset = start + (size + BLOCK_SIZE - 1) / BLOCK_SIZE * BLOCK_SIZE;
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Eth2NetworkConfig;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::{write::FileOptions, ZipWriter};
+
+    /// Returns the name and contents of each file of the `mainnet` testnet directory.
+    fn mainnet_files() -> Vec<(&'static str, Vec<u8>)> {
+        let dir = TempDir::new().unwrap();
+        let config = Eth2NetworkConfig::constant("mainnet").unwrap().unwrap();
+        config
+            .force_write_to_file(dir.path().to_path_buf())
+            .unwrap();
+        BUNDLE_FILES
+            .iter()
+            .map(|name| (*name, fs::read(dir.path().join(name)).unwrap()))
+            .collect()
+    }
+
+    /// Returns a zip archive containing the files of the `mainnet` testnet directory, in a
+    /// directory named `net`.
+    fn mainnet_zip() -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, contents) in mainnet_files() {
+            writer
+                .start_file(format!("net/{}", name), FileOptions::default())
+                .unwrap();
+            writer.write_all(&contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Returns a ustar header block for an entry of `size` bytes, with the path split into
+    /// `prefix` and `name`.
+    fn tar_header(prefix: &str, name: &str, type_flag: u8, size: usize) -> Vec<u8> {
+        let mut header = vec![0; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+        header[156] = type_flag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+        // The checksum is calculated with the checksum field filled with spaces.
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+        header
+    }
+
+    /// Returns a tar archive containing the files of the `mainnet` testnet directory, in the
+    /// directory `prefix`.
+    fn mainnet_tar(prefix: &str) -> Vec<u8> {
+        // Directory entries are skipped when reading the archive.
+        let dir = prefix.split('/').next().unwrap();
+        let mut tar = tar_header("", &format!("{}/", dir), b'5', 0);
+        for (name, contents) in mainnet_files() {
+            tar.extend(tar_header(prefix, name, b'0', contents.len()));
+            tar.extend(&contents);
+            tar.resize((tar.len() + 511) / 512 * 512, 0);
+        }
+        tar.extend([0; 1024]);
+        tar
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn extract_zip_bundle() {
+        let dir = TempDir::new().unwrap();
+        let config_dir = dir.path().join("config");
+        extract_bundle(&mainnet_zip(), &config_dir).unwrap();
+
+        let expected = Eth2NetworkConfig::constant("mainnet").unwrap().unwrap();
+        assert_eq!(Eth2NetworkConfig::load(config_dir).unwrap(), expected);
+    }
+
+    #[test]
+    fn extract_tar_gz_bundle() {
+        let dir = TempDir::new().unwrap();
+        let config_dir = dir.path().join("config");
+        extract_bundle(&gzip(&mainnet_tar("net")), &config_dir).unwrap();
+
+        let expected = Eth2NetworkConfig::constant("mainnet").unwrap().unwrap();
+        assert_eq!(Eth2NetworkConfig::load(config_dir).unwrap(), expected);
+    }
+
+    #[test]
+    fn extract_tar_gz_bundle_with_ustar_prefix() {
+        // Paths longer than the 100 byte name field are split into a prefix and a name.
+        let prefix = ["network-configs"; 8].join("/");
+        let files = read_tar(&mainnet_tar(&prefix)).unwrap();
+        let names = files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, BUNDLE_FILES);
+
+        let dir = TempDir::new().unwrap();
+        extract_bundle(&gzip(&mainnet_tar(&prefix)), dir.path()).unwrap();
+        let expected = Eth2NetworkConfig::constant("mainnet").unwrap().unwrap();
+        assert_eq!(
+            Eth2NetworkConfig::load(dir.path().to_path_buf()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn truncated_tar_gz_bundle() {
+        let mut tar = mainnet_tar("net");
+        // Truncate the archive within the contents of its first file.
+        tar.truncate(512 * 2 + 10);
+
+        let dir = TempDir::new().unwrap();
+        let err = extract_bundle(&gzip(&tar), dir.path()).unwrap_err();
+        assert!(err.contains("truncated"), "{}", err);
+    }
+
+    #[test]
+    fn invalid_bundle() {
+        let dir = TempDir::new().unwrap();
+        assert!(extract_bundle(b"not an archive", dir.path()).is_err());
+    }
+
+    #[test]
+    fn checksum() {
+        let bytes = mainnet_zip();
+        let checksum = hex::encode(hash(&bytes));
+        assert!(verify_checksum(&bytes, &checksum).is_ok());
+        assert!(verify_checksum(&bytes, &format!("0x{}", checksum.to_uppercase())).is_ok());
+        assert!(verify_checksum(&bytes[1..], &checksum).is_err());
+    }
+}
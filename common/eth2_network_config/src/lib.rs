@@ -11,12 +11,14 @@
 //! To add a new built-in testnet, add it to the `define_hardcoded_nets` invocation in the `eth2_config`
 //! crate.
 
+pub mod bundle;
+
 use discv5::enr::{CombinedKey, Enr};
 use eth2_config::{instantiate_hardcoded_nets, HardcodedNet};
-use std::fs::{create_dir_all, File};
+use std::fs::{self, create_dir_all, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
-use types::{BeaconState, ChainSpec, Config, EthSpec, EthSpecId};
+use std::path::{Path, PathBuf};
+use types::{BeaconState, ChainSpec, Config, EthSpec, EthSpecId, Hash256};
 
 pub const DEPLOY_BLOCK_FILE: &str = "deploy_block.txt";
 pub const BOOT_ENR_FILE: &str = "boot_enr.yaml";
@@ -107,6 +109,52 @@ impl Eth2NetworkConfig {
             .map_err(|e| format!("Genesis state SSZ bytes are invalid: {:?}", e))
     }
 
+    /// Returns the SSZ bytes of the genesis state at `url`, which must have the root
+    /// `state_root`, for networks which don't include their genesis state.
+    ///
+    /// The state is downloaded into `cache_file`, and read from there if it has been downloaded
+    /// before. It is downloaded through `proxy` if one is given.
+    ///
+    /// Must not be called from within an async runtime.
+    pub fn fetch_genesis_state_bytes<E: EthSpec>(
+        &self,
+        url: &str,
+        state_root: Hash256,
+        cache_file: &Path,
+        proxy: Option<&str>,
+    ) -> Result<Vec<u8>, String> {
+        let spec = self.chain_spec::<E>()?;
+        let verify = |bytes: &[u8]| {
+            let state = BeaconState::<E>::from_ssz_bytes(bytes, &spec)
+                .map_err(|e| format!("Genesis state SSZ bytes are invalid: {:?}", e))?;
+            let root = state.canonical_root();
+            if root == state_root {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Genesis state root mismatch, expected {:?} but got {:?}",
+                    state_root, root
+                ))
+            }
+        };
+
+        // A cached state which doesn't match (e.g. because the root was changed) is replaced.
+        if let Ok(bytes) = fs::read(cache_file) {
+            if verify(&bytes).is_ok() {
+                return Ok(bytes);
+            }
+        }
+
+        let bytes = bundle::download(url, proxy)?;
+        verify(&bytes)?;
+        if let Some(dir) = cache_file.parent() {
+            create_dir_all(dir).map_err(|e| format!("Unable to create {:?}: {:?}", dir, e))?;
+        }
+        fs::write(cache_file, &bytes)
+            .map_err(|e| format!("Unable to write {:?}: {:?}", cache_file, e))?;
+        Ok(bytes)
+    }
+
     /// Write the files to the directory.
     ///
     /// Overwrites files if specified to do so.
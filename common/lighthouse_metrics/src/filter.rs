@@ -0,0 +1,166 @@
+//! Filters metric families by name, so that users can drop families they don't need.
+
+use prometheus::proto::MetricFamily;
+
+/// Selects metric families using glob patterns, where `*` matches any sequence of characters and
+/// `?` matches any single character.
+///
+/// A family is kept if it matches any pattern of the allowlist (or the allowlist is empty), and
+/// doesn't match any pattern of the denylist.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsFilter {
+    pub allowlist: Vec<String>,
+    pub denylist: Vec<String>,
+}
+
+impl MetricsFilter {
+    pub fn new(allowlist: Vec<String>, denylist: Vec<String>) -> Self {
+        Self {
+            allowlist,
+            denylist,
+        }
+    }
+
+    /// Returns `true` if the filter keeps every family.
+    pub fn is_empty(&self) -> bool {
+        self.allowlist.is_empty() && self.denylist.is_empty()
+    }
+
+    /// Returns `true` if the family called `name` should be kept.
+    pub fn is_allowed(&self, name: &str) -> bool {
+        (self.allowlist.is_empty()
+            || self
+                .allowlist
+                .iter()
+                .any(|pattern| glob_match(pattern, name)))
+            && !self
+                .denylist
+                .iter()
+                .any(|pattern| glob_match(pattern, name))
+    }
+
+    /// Removes the families which aren't allowed.
+    pub fn filter_families(&self, mut families: Vec<MetricFamily>) -> Vec<MetricFamily> {
+        if !self.is_empty() {
+            families.retain(|family| self.is_allowed(family.get_name()));
+        }
+        families
+    }
+
+    /// Removes the families which aren't allowed from metrics which have already been encoded in
+    /// the Prometheus (or OpenMetrics) text format, where each family begins with `# HELP` and/or
+    /// `# TYPE` comments.
+    pub fn filter_text(&self, text: &str) -> String {
+        if self.is_empty() {
+            return text.to_string();
+        }
+
+        let mut filtered = String::with_capacity(text.len());
+        let mut allowed = true;
+        let mut in_comments = false;
+        for line in text.lines() {
+            if line == "# EOF" {
+                allowed = true;
+            } else if let Some(comment) = line.strip_prefix("# ") {
+                // The first comment of a family determines whether it's kept.
+                if !in_comments {
+                    let name = comment.split_whitespace().nth(1).unwrap_or_default();
+                    allowed = self.is_allowed(name);
+                }
+                in_comments = true;
+            } else {
+                in_comments = false;
+            }
+
+            if allowed {
+                filtered.push_str(line);
+                filtered.push('\n');
+            }
+        }
+        filtered
+    }
+}
+
+/// Returns `true` if `name` matches the glob `pattern`.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let name = name.as_bytes();
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` in the pattern, and of the name when it was reached.
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` match one more character.
+                Some((star, star_n)) => {
+                    backtrack = Some((star, star_n + 1));
+                    p = star + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(allowlist: &[&str], denylist: &[&str]) -> MetricsFilter {
+        MetricsFilter::new(
+            allowlist.iter().map(|s| s.to_string()).collect(),
+            denylist.iter().map(|s| s.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("beacon_*", "beacon_block_processing_seconds"));
+        assert!(glob_match("*_seconds", "beacon_block_processing_seconds"));
+        assert!(glob_match(
+            "*block*seconds",
+            "beacon_block_processing_seconds"
+        ));
+        assert!(glob_match("sync_?", "sync_a"));
+        assert!(!glob_match("sync_?", "sync_ab"));
+        assert!(!glob_match("beacon_*", "gossipsub_mesh_peers"));
+        assert!(!glob_match("beacon", "beacon_block"));
+    }
+
+    #[test]
+    fn allow_and_deny() {
+        let filter = filter(&["beacon_*", "store_*"], &["*_seconds"]);
+        assert!(filter.is_allowed("beacon_head_slot"));
+        assert!(filter.is_allowed("store_disk_db_size"));
+        assert!(!filter.is_allowed("beacon_block_processing_seconds"));
+        assert!(!filter.is_allowed("gossipsub_mesh_peers_per_topic"));
+    }
+
+    #[test]
+    fn text() {
+        let text = "# HELP a_total A.\n\
+                    # TYPE a_total counter\n\
+                    a_total 1\n\
+                    # HELP b B.\n\
+                    # TYPE b gauge\n\
+                    b{x=\"1\"} 2\n\
+                    # EOF\n";
+        assert_eq!(
+            filter(&[], &["b"]).filter_text(text),
+            "# HELP a_total A.\n# TYPE a_total counter\na_total 1\n# EOF\n"
+        );
+        assert_eq!(filter(&[], &[]).filter_text(text), text);
+    }
+}
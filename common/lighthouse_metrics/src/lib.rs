@@ -54,9 +54,15 @@
 //! }
 //! ```
 
+mod filter;
+
+use lazy_static::lazy_static;
 use prometheus::{Error, HistogramOpts, Opts};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
 use std::time::Duration;
 
+pub use filter::MetricsFilter;
 use prometheus::core::{Atomic, GenericGauge, GenericGaugeVec};
 pub use prometheus::{
     exponential_buckets, linear_buckets,
@@ -65,6 +71,82 @@ pub use prometheus::{
     IntGauge, IntGaugeVec, Result, TextEncoder, DEFAULT_BUCKETS,
 };
 
+lazy_static! {
+    /// Buckets which replace those of the histogram with the same name when it is created.
+    static ref HISTOGRAM_BUCKETS: RwLock<HashMap<String, Vec<f64>>> = RwLock::new(HashMap::new());
+    /// The names of the histograms which have been created.
+    static ref CREATED_HISTOGRAMS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+}
+
+/// Overrides the buckets of the histograms (or histogram vecs) with the given names, returning
+/// the names of those which already exist.
+///
+/// Metrics are created when they are first used, so histograms which already exist keep their
+/// buckets. The overrides apply to the whole process and are added to those set previously, so
+/// when the beacon node and validator client share a process both of their overrides apply, and
+/// a later override of the same histogram replaces an earlier one.
+pub fn set_histogram_buckets(buckets: HashMap<String, Vec<f64>>) -> Vec<String> {
+    let created = CREATED_HISTOGRAMS
+        .read()
+        .map(|created| {
+            buckets
+                .keys()
+                .filter(|name| created.contains(*name))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Ok(mut overrides) = HISTOGRAM_BUCKETS.write() {
+        overrides.extend(buckets);
+    }
+    created
+}
+
+/// Parses a histogram bucket override of the form `NAME=B1,B2,...`, where the bucket boundaries
+/// are finite and strictly increasing.
+pub fn parse_histogram_buckets(s: &str) -> std::result::Result<(String, Vec<f64>), String> {
+    let (name, boundaries) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid histogram buckets {:?}, expected NAME=B1,B2,...", s))?;
+    if name.is_empty() {
+        return Err(format!("Invalid histogram buckets {:?}, missing name", s));
+    }
+    let buckets = boundaries
+        .split(',')
+        .map(|boundary| {
+            boundary
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|boundary| boundary.is_finite())
+                .ok_or_else(|| format!("Invalid bucket boundary {:?} for {}", boundary, name))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(format!(
+            "Bucket boundaries for {} must be strictly increasing",
+            name
+        ));
+    }
+    Ok((name.to_string(), buckets))
+}
+
+/// Returns the buckets to use for the histogram called `name`, preferring any override, and records
+/// that it has been created.
+fn histogram_buckets(name: &str, buckets: Result<Vec<f64>>) -> Result<Vec<f64>> {
+    if let Ok(mut created) = CREATED_HISTOGRAMS.write() {
+        created.insert(name.to_string());
+    }
+    match HISTOGRAM_BUCKETS
+        .read()
+        .ok()
+        .and_then(|overrides| overrides.get(name).cloned())
+    {
+        Some(buckets) => Ok(buckets),
+        None => buckets,
+    }
+}
+
 /// Collect all the metrics for reporting.
 pub fn gather() -> Vec<prometheus::proto::MetricFamily> {
     prometheus::gather()
@@ -110,7 +192,7 @@ pub fn try_create_histogram_with_buckets(
     help: &str,
     buckets: Result<Vec<f64>>,
 ) -> Result<Histogram> {
-    let opts = HistogramOpts::new(name, help).buckets(buckets?);
+    let opts = HistogramOpts::new(name, help).buckets(histogram_buckets(name, buckets)?);
     let histogram = Histogram::with_opts(opts)?;
     prometheus::register(Box::new(histogram.clone()))?;
     Ok(histogram)
@@ -134,7 +216,7 @@ pub fn try_create_histogram_vec_with_buckets(
     buckets: Result<Vec<f64>>,
     label_names: &[&str],
 ) -> Result<HistogramVec> {
-    let opts = HistogramOpts::new(name, help).buckets(buckets?);
+    let opts = HistogramOpts::new(name, help).buckets(histogram_buckets(name, buckets)?);
     let histogram_vec = HistogramVec::new(opts, label_names)?;
     prometheus::register(Box::new(histogram_vec.clone()))?;
     Ok(histogram_vec)
@@ -404,3 +486,38 @@ pub fn decimal_buckets(min_power: i32, max_power: i32) -> Result<Vec<f64>> {
     }
     Ok(buckets)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::core::Metric as _;
+
+    #[test]
+    fn histogram_bucket_overrides() {
+        let first = HashMap::from([("test_overrides_first".to_string(), vec![1.0, 2.0])]);
+        assert!(set_histogram_buckets(first).is_empty());
+
+        let histogram = try_create_histogram("test_overrides_first", "first").unwrap();
+        let bounds = histogram
+            .metric()
+            .get_histogram()
+            .get_bucket()
+            .iter()
+            .map(|bucket| bucket.get_upper_bound())
+            .collect::<Vec<_>>();
+        assert_eq!(bounds, vec![1.0, 2.0]);
+
+        // Later overrides are added to earlier ones, and those for existing histograms are
+        // reported.
+        let second = HashMap::from([
+            ("test_overrides_first".to_string(), vec![3.0]),
+            ("test_overrides_second".to_string(), vec![3.0]),
+        ]);
+        assert_eq!(
+            set_histogram_buckets(second),
+            vec!["test_overrides_first".to_string()]
+        );
+        let histogram = try_create_histogram("test_overrides_second", "second").unwrap();
+        assert_eq!(histogram.metric().get_histogram().get_bucket().len(), 1);
+    }
+}
@@ -9,6 +9,15 @@ use slog_term::Decorator;
 use std::io::{Result, Write};
 use std::time::{Duration, Instant};
 
+mod logfmt;
+#[cfg(target_family = "unix")]
+mod system_log;
+
+pub use logfmt::LogfmtDrain;
+
+#[cfg(target_family = "unix")]
+pub use system_log::{JournaldDrain, SyslogDrain};
+
 pub const MAX_MESSAGE_WIDTH: usize = 40;
 
 /// The minimum interval between log messages indicating that a queue is full.
@@ -25,6 +34,17 @@ lazy_static! {
         try_create_int_counter("crit_total", "Count of crits logged");
 }
 
+/// Increments the counter for the level of a log message.
+fn count_log_level(level: slog::Level) {
+    match level {
+        slog::Level::Info => inc_counter(&INFOS_TOTAL),
+        slog::Level::Warning => inc_counter(&WARNS_TOTAL),
+        slog::Level::Error => inc_counter(&ERRORS_TOTAL),
+        slog::Level::Critical => inc_counter(&CRITS_TOTAL),
+        _ => (),
+    }
+}
+
 pub struct AlignedTermDecorator<D: Decorator> {
     wrapped: D,
     message_width: usize,
@@ -49,13 +69,7 @@ impl<D: Decorator> Decorator for AlignedTermDecorator<D> {
     where
         F: FnOnce(&mut dyn slog_term::RecordDecorator) -> std::io::Result<()>,
     {
-        match record.level() {
-            slog::Level::Info => inc_counter(&INFOS_TOTAL),
-            slog::Level::Warning => inc_counter(&WARNS_TOTAL),
-            slog::Level::Error => inc_counter(&ERRORS_TOTAL),
-            slog::Level::Critical => inc_counter(&CRITS_TOTAL),
-            _ => (),
-        }
+        count_log_level(record.level());
 
         self.wrapped.with_record(record, _logger_values, |deco| {
             f(&mut AlignedRecordDecorator::new(deco, self.message_width))
@@ -0,0 +1,123 @@
+//! A drain which writes records in the [logfmt](https://brandur.org/logfmt) format, e.g.:
+//!
+//! ```text
+//! ts=2023-04-20T10:00:00.123Z level=info msg="Synced" peers=42 service=slot_notifier
+//! ```
+//!
+//! This format is understood by log aggregators like Loki without any further configuration.
+
+use slog::{Drain, Key, Level, OwnedKVList, Record, Serializer, KV};
+use slog_term::Decorator;
+use std::fmt::{self, Write as _};
+use std::io;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// Returns the logfmt name of `level`.
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::Critical => "crit",
+        Level::Error => "error",
+        Level::Warning => "warn",
+        Level::Info => "info",
+        Level::Debug => "debug",
+        Level::Trace => "trace",
+    }
+}
+
+/// Appends `value` to `line`, quoting and escaping it if necessary.
+fn push_value(line: &mut String, value: &str) {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '=' || c == '"');
+    if !needs_quotes {
+        line.push_str(value);
+        return;
+    }
+
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(line, "\\u{{{:x}}}", c as u32);
+            }
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+/// Appends each key-value pair to a line.
+struct LineSerializer<'a> {
+    line: &'a mut String,
+}
+
+impl<'a> Serializer for LineSerializer<'a> {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        self.line.push(' ');
+        self.line.push_str(key);
+        self.line.push('=');
+        push_value(self.line, &val.to_string());
+        Ok(())
+    }
+}
+
+/// A drain which writes each record through the decorator `D` as a single logfmt line.
+///
+/// Any decorator may be used, e.g. a `slog_term::PlainDecorator` to write to the terminal, or
+/// that of the `sloggers` file logger to write to a rotated log file.
+pub struct LogfmtDrain<D: Decorator> {
+    decorator: D,
+    timestamps: bool,
+}
+
+impl<D: Decorator> LogfmtDrain<D> {
+    pub fn new(decorator: D) -> Self {
+        Self {
+            decorator,
+            timestamps: true,
+        }
+    }
+
+    /// Omits the `ts` field from each line.
+    pub fn without_timestamps(mut self) -> Self {
+        self.timestamps = false;
+        self
+    }
+}
+
+impl<D: Decorator> Drain for LogfmtDrain<D> {
+    type Ok = ();
+    type Err = io::Error;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> io::Result<()> {
+        let mut line = String::new();
+        if self.timestamps {
+            let ts = OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            let _ = write!(line, "ts={} ", ts);
+        }
+        let _ = write!(line, "level={} msg=", level_name(record.level()));
+        push_value(&mut line, &record.msg().to_string());
+
+        let mut serializer = LineSerializer { line: &mut line };
+        record
+            .kv()
+            .serialize(record, &mut serializer)
+            .and_then(|_| values.serialize(record, &mut serializer))
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        line.push('\n');
+
+        self.decorator.with_record(record, values, |decorator| {
+            decorator.write_all(line.as_bytes())?;
+            decorator.flush()
+        })
+    }
+}
@@ -0,0 +1,342 @@
+//! Drains which send log records to the system log, either to journald using its native
+//! protocol or to a syslog daemon via `/dev/log`.
+//!
+//! The key-value pairs of each record (e.g. `slot`, `peer_id`, `service`) are sent as journald
+//! fields, so they can be queried with `journalctl` without any parsing. Records which are too
+//! large for a single datagram are passed to journald in a sealed memfd, as `sd_journal_send`
+//! does.
+
+use crate::count_log_level;
+use slog::{Drain, Key, Level, OwnedKVList, Record, Serializer, KV};
+use std::fmt::{self, Write as _};
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+
+/// The socket on which journald listens for the native protocol.
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+/// The socket on which the syslog daemon listens.
+pub const SYSLOG_SOCKET: &str = "/dev/log";
+
+/// The identifier used to tag all records.
+const IDENTIFIER: &str = "lighthouse";
+/// The syslog facility used for all records (`daemon`).
+const SYSLOG_FACILITY: u8 = 3;
+
+/// Returns the syslog severity of `level`.
+fn priority(level: Level) -> u8 {
+    match level {
+        Level::Critical => 2,
+        Level::Error => 3,
+        Level::Warning => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Collects the key-value pairs of a record.
+#[derive(Default)]
+struct FieldCollector {
+    fields: Vec<(Key, String)>,
+}
+
+impl Serializer for FieldCollector {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        self.fields.push((key, val.to_string()));
+        Ok(())
+    }
+}
+
+/// Returns the key-value pairs of `record`, followed by those of the logger.
+fn collect_fields(record: &Record, values: &OwnedKVList) -> Result<Vec<(Key, String)>, io::Error> {
+    let mut collector = FieldCollector::default();
+    record
+        .kv()
+        .serialize(record, &mut collector)
+        .and_then(|_| values.serialize(record, &mut collector))
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    Ok(collector.fields)
+}
+
+/// Converts a key to a valid journald field name (uppercase ASCII letters, digits and
+/// underscores, not starting with an underscore).
+fn journald_field_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    name.trim_start_matches('_').to_string()
+}
+
+/// Appends a field to a journald native protocol datagram.
+fn append_journald_field(buf: &mut Vec<u8>, name: &str, value: &str) {
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        // Values containing newlines are sent as a little-endian length followed by the data.
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        buf.extend_from_slice(value.as_bytes());
+    } else {
+        buf.push(b'=');
+        buf.extend_from_slice(value.as_bytes());
+    }
+    buf.push(b'\n');
+}
+
+/// A drain which sends records to journald, with their key-value pairs as journald fields.
+pub struct JournaldDrain {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl JournaldDrain {
+    /// Returns a drain which sends records to journald, failing if journald is not running.
+    pub fn new() -> io::Result<Self> {
+        Self::with_socket(JOURNALD_SOCKET)
+    }
+
+    pub fn with_socket<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist, is journald running?", path.display()),
+            ));
+        }
+        Ok(Self {
+            socket: UnixDatagram::unbound()?,
+            path,
+        })
+    }
+}
+
+impl Drain for JournaldDrain {
+    type Ok = ();
+    type Err = io::Error;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> io::Result<()> {
+        count_log_level(record.level());
+
+        let mut buf = Vec::new();
+        append_journald_field(&mut buf, "MESSAGE", &record.msg().to_string());
+        append_journald_field(&mut buf, "PRIORITY", &priority(record.level()).to_string());
+        append_journald_field(&mut buf, "SYSLOG_IDENTIFIER", IDENTIFIER);
+        append_journald_field(&mut buf, "CODE_FILE", record.file());
+        append_journald_field(&mut buf, "CODE_LINE", &record.line().to_string());
+        append_journald_field(&mut buf, "CODE_MODULE", record.module());
+        for (key, value) in collect_fields(record, values)? {
+            let name = journald_field_name(key);
+            if !name.is_empty() {
+                append_journald_field(&mut buf, &name, &value);
+            }
+        }
+
+        match self.socket.send_to(&buf, &self.path) {
+            Ok(_) => Ok(()),
+            #[cfg(target_os = "linux")]
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EMSGSIZE) | Some(libc::ENOBUFS)) => {
+                send_memfd(&self.socket, &self.path, &buf)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Sends the datagram `buf` to the journald socket at `path` by writing it to a sealed memfd and
+/// sending only the file descriptor, which journald reads the record from.
+#[cfg(target_os = "linux")]
+fn send_memfd(socket: &UnixDatagram, path: &Path, buf: &[u8]) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::fs::File;
+    use std::io::Write;
+    use std::mem;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+    let name = CString::new("lighthouse-journal").expect("name has no nul bytes");
+    let fd =
+        unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_ALLOW_SEALING | libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Closes the memfd once it has been sent, journald keeps its own copy of the descriptor.
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(buf)?;
+    // journald only accepts memfds which can no longer be modified.
+    let seals = libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+    if unsafe { libc::fcntl(fd, libc::F_ADD_SEALS, seals) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+    let path_bytes = path.as_os_str().as_bytes();
+    if path_bytes.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Socket path {} is too long", path.display()),
+        ));
+    }
+    for (dst, src) in addr.sun_path.iter_mut().zip(path_bytes) {
+        *dst = *src as libc::c_char;
+    }
+
+    // The control message buffer must be aligned for `cmsghdr`.
+    let control_len = unsafe { libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) } as usize;
+    let mut control = vec![0_u64; control_len / mem::size_of::<u64>() + 1];
+
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = &mut addr as *mut libc::sockaddr_un as *mut libc::c_void;
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = control_len as _;
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut RawFd, file.as_raw_fd());
+    }
+
+    if unsafe { libc::sendmsg(socket.as_raw_fd(), &msg, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// A drain which sends records to the syslog daemon, with their key-value pairs appended to the
+/// message.
+pub struct SyslogDrain {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl SyslogDrain {
+    /// Returns a drain which sends records to the syslog daemon, failing if it is not running.
+    pub fn new() -> io::Result<Self> {
+        Self::with_socket(SYSLOG_SOCKET)
+    }
+
+    pub fn with_socket<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "{} does not exist, is a syslog daemon running?",
+                    path.display()
+                ),
+            ));
+        }
+        Ok(Self {
+            socket: UnixDatagram::unbound()?,
+            path,
+        })
+    }
+}
+
+impl Drain for SyslogDrain {
+    type Ok = ();
+    type Err = io::Error;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> io::Result<()> {
+        count_log_level(record.level());
+
+        let mut msg = format!(
+            "<{}>{}[{}]: {}",
+            SYSLOG_FACILITY * 8 + priority(record.level()),
+            IDENTIFIER,
+            std::process::id(),
+            record.msg()
+        );
+        for (key, value) in collect_fields(record, values)? {
+            let _ = write!(msg, ", {}: {}", key, value);
+        }
+
+        self.socket.send_to(msg.as_bytes(), &self.path).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_names_are_sanitised() {
+        assert_eq!(journald_field_name("slot"), "SLOT");
+        assert_eq!(journald_field_name("peer_id"), "PEER_ID");
+        assert_eq!(journald_field_name("block-root"), "BLOCK_ROOT");
+        assert_eq!(journald_field_name("sync état"), "SYNC__TAT");
+        // Fields starting with an underscore are reserved for journald.
+        assert_eq!(journald_field_name("_pid"), "PID");
+        assert_eq!(journald_field_name("__"), "");
+    }
+
+    #[test]
+    fn single_line_field() {
+        let mut buf = vec![];
+        append_journald_field(&mut buf, "MESSAGE", "Synced");
+        append_journald_field(&mut buf, "SLOT", "");
+        assert_eq!(buf, b"MESSAGE=Synced\nSLOT=\n");
+    }
+
+    #[test]
+    fn multiline_field() {
+        let mut buf = vec![];
+        append_journald_field(&mut buf, "ERROR", "first\nsecond");
+        let mut expected = b"ERROR\n".to_vec();
+        expected.extend_from_slice(&12_u64.to_le_bytes());
+        expected.extend_from_slice(b"first\nsecond\n");
+        assert_eq!(buf, expected);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn large_record_sent_in_memfd() {
+        use std::fs::File;
+        use std::io::{Read, Seek, SeekFrom};
+        use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("socket");
+        let journald = UnixDatagram::bind(&path).unwrap();
+        let socket = UnixDatagram::unbound().unwrap();
+
+        // Larger than the maximum datagram size.
+        let mut buf = vec![];
+        append_journald_field(&mut buf, "MESSAGE", &"a".repeat(16 * 1024 * 1024));
+        assert_eq!(
+            socket.send_to(&buf, &path).unwrap_err().raw_os_error(),
+            Some(libc::EMSGSIZE)
+        );
+        send_memfd(&socket, &path, &buf).unwrap();
+
+        // Receive the file descriptor sent by `send_memfd`.
+        let mut control = [0_u64; 8];
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = std::mem::size_of_val(&control) as _;
+        assert_eq!(
+            unsafe { libc::recvmsg(journald.as_raw_fd(), &mut msg, 0) },
+            0
+        );
+        let fd = unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            assert_eq!((*cmsg).cmsg_type, libc::SCM_RIGHTS);
+            std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const RawFd)
+        };
+
+        // The memfd shares its offset with the sender's descriptor, which is past the record.
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut received = vec![];
+        file.read_to_end(&mut received).unwrap();
+        assert!(received == buf, "memfd contents differ from the record");
+    }
+}
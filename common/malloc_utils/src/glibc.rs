@@ -8,7 +8,10 @@ use lazy_static::lazy_static;
 use lighthouse_metrics::*;
 use parking_lot::Mutex;
 use std::env;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::os::raw::c_int;
+use std::os::unix::io::FromRawFd;
 use std::result::Result;
 
 /// The optimal mmap threshold for Lighthouse seems to be around 128KB.
@@ -160,6 +163,40 @@ fn mallinfo() -> libc::mallinfo2 {
     unsafe { libc::mallinfo2() }
 }
 
+/// Returns the XML description of the state of the allocator produced by `malloc_info`.
+///
+/// https://man7.org/linux/man-pages/man3/malloc_info.3.html
+pub fn malloc_info() -> Result<Vec<u8>, String> {
+    // Prevent this function from being called in parallel with any other non-thread-safe function.
+    let _lock = GLOBAL_LOCK.lock();
+
+    let (result, mut file) = unsafe {
+        let stream = libc::tmpfile();
+        if stream.is_null() {
+            return Err("failed to create temporary file".to_string());
+        }
+        let result = libc::malloc_info(0, stream);
+        libc::fflush(stream);
+        // Duplicate the descriptor so that it remains open once the stream is closed.
+        let fd = libc::dup(libc::fileno(stream));
+        libc::fclose(stream);
+        if fd < 0 {
+            return Err("failed to duplicate file descriptor".to_string());
+        }
+        // Take ownership of the descriptor straight away so that it is closed on every path.
+        (result, File::from_raw_fd(fd))
+    };
+    if result != 0 {
+        return Err(format!("malloc_info failed (code {})", result));
+    }
+
+    let mut info = vec![];
+    file.seek(SeekFrom::Start(0))
+        .and_then(|_| file.read_to_end(&mut info))
+        .map_err(|e| format!("failed to read malloc_info output: {}", e))?;
+    Ok(info)
+}
+
 fn into_result(result: c_int) -> Result<(), c_int> {
     if result == 1 {
         Ok(())
@@ -176,4 +213,10 @@ mod tests {
     fn malloc_mmap_threshold_does_not_panic() {
         malloc_mmap_threshold(OPTIMAL_MMAP_THRESHOLD).unwrap();
     }
+
+    #[test]
+    fn malloc_info_is_xml() {
+        let info = String::from_utf8(malloc_info().unwrap()).unwrap();
+        assert!(info.starts_with("<malloc"));
+    }
 }
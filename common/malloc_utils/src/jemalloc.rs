@@ -7,9 +7,18 @@
 //!
 //! A) `JEMALLOC_SYS_WITH_MALLOC_CONF` at compile-time.
 //! B) `_RJEM_MALLOC_CONF` at runtime.
+//!
+//! Heap profiles are only available if the `jemalloc-profiling` feature is enabled and profiling is
+//! activated at runtime, e.g. with `_RJEM_MALLOC_CONF=prof:true`.
 use jemalloc_ctl::{arenas, epoch, stats, Error};
 use lazy_static::lazy_static;
 use lighthouse_metrics::{set_gauge, try_create_int_gauge, IntGauge};
+#[cfg(feature = "jemalloc-profiling")]
+use std::{
+    ffi::CString,
+    fs,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
@@ -50,3 +59,36 @@ pub fn scrape_jemalloc_metrics_fallible() -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Dumps a heap profile in the format read by `jeprof`.
+#[cfg(feature = "jemalloc-profiling")]
+pub fn dump_heap_profile() -> Result<Vec<u8>, String> {
+    // Distinguishes the files of concurrent dumps.
+    static DUMP_COUNT: AtomicU64 = AtomicU64::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "lighthouse.{}.{}.heap",
+        std::process::id(),
+        DUMP_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let c_path = CString::new(path.to_string_lossy().as_bytes())
+        .map_err(|e| format!("invalid profile path: {}", e))?;
+
+    // Safe since `prof.dump` takes a `const char *` which is only read during the call.
+    unsafe { jemalloc_ctl::raw::write(b"prof.dump\0", c_path.as_ptr()) }.map_err(|e| {
+        format!(
+            "failed to dump heap profile, ensure profiling is enabled with \
+             _RJEM_MALLOC_CONF=prof:true: {}",
+            e
+        )
+    })?;
+
+    let profile = fs::read(&path).map_err(|e| format!("failed to read heap profile: {}", e));
+    let _ = fs::remove_file(&path);
+    profile
+}
+
+#[cfg(not(feature = "jemalloc-profiling"))]
+pub fn dump_heap_profile() -> Result<Vec<u8>, String> {
+    Err("heap profiles require the jemalloc-profiling feature".to_string())
+}
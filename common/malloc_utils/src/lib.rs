@@ -8,6 +8,10 @@
 //! - GNU malloc, if no features are set and the system supports it.
 //! - The system allocator, if no features are set and the allocator is not GNU malloc.
 //!
+//! Heap profiles can be dumped with `dump_heap_profile`. With Jemalloc this requires the
+//! `jemalloc-profiling` feature and profiling to be enabled at runtime (e.g.
+//! `_RJEM_MALLOC_CONF=prof:true`). With GNU malloc the output of `malloc_info` is returned instead.
+//!
 //! It is assumed that if Jemalloc is not in use, and the following two statements are correct then
 //! we should expect to configure `glibc`:
 //!
@@ -43,6 +47,7 @@ pub use interface::*;
 ))]
 mod interface {
     pub use crate::glibc::configure_glibc_malloc as configure_memory_allocator;
+    pub use crate::glibc::malloc_info as dump_heap_profile;
     pub use crate::glibc::scrape_mallinfo_metrics as scrape_allocator_metrics;
 }
 
@@ -53,6 +58,7 @@ mod interface {
         Ok(())
     }
 
+    pub use crate::jemalloc::dump_heap_profile;
     pub use crate::jemalloc::scrape_jemalloc_metrics as scrape_allocator_metrics;
 }
 
@@ -68,4 +74,9 @@ mod interface {
 
     #[allow(dead_code)]
     pub fn scrape_allocator_metrics() {}
+
+    #[allow(dead_code)]
+    pub fn dump_heap_profile() -> Result<Vec<u8>, String> {
+        Err("heap profiles are not supported by the system allocator".to_string())
+    }
 }
@@ -0,0 +1,47 @@
+//! Exports the `tracing` spans emitted by Lighthouse to an OpenTelemetry collector via OTLP.
+//!
+//! Spans are batched and exported in the background by the Tokio runtime, so `init` must be called
+//! from within that runtime.
+
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Flushes any outstanding spans and stops the exporter when dropped.
+pub struct OtlpGuard(());
+
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        opentelemetry::global::shutdown_tracer_provider();
+    }
+}
+
+/// Installs a global `tracing` subscriber which exports spans to the OTLP gRPC `endpoint`, e.g.
+/// `http://localhost:4317`.
+pub fn init(
+    endpoint: &str,
+    service_name: &'static str,
+    service_version: &'static str,
+) -> Result<OtlpGuard, String> {
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![
+            KeyValue::new("service.name", service_name),
+            KeyValue::new("service.version", service_version),
+        ])))
+        .install_batch(opentelemetry::runtime::Tokio)
+        .map_err(|e| format!("Unable to start OTLP exporter: {:?}", e))?;
+
+    let subscriber =
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| format!("Unable to set tracing subscriber: {:?}", e))?;
+
+    Ok(OtlpGuard(()))
+}
@@ -0,0 +1,147 @@
+//! Periodically pushes metrics to a Prometheus Pushgateway, for nodes which can't be scraped
+//! (e.g. because they are behind NAT).
+//!
+//! Each push replaces all of the metrics previously pushed with the same `job` and `instance`
+//! labels.
+
+use lazy_static::lazy_static;
+use lighthouse_metrics::{
+    inc_counter, try_create_int_counter, IntCounter, Result as MetricsResult,
+};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use sensitive_url::SensitiveUrl;
+use serde_derive::{Deserialize, Serialize};
+use slog::{debug, warn, Logger};
+use std::time::Duration;
+
+/// The default number of seconds between pushes.
+pub const DEFAULT_INTERVAL_SECS: u64 = 15;
+
+lazy_static! {
+    pub static ref PUSHGATEWAY_PUSHES_TOTAL: MetricsResult<IntCounter> = try_create_int_counter(
+        "pushgateway_pushes_total",
+        "Count of metrics pushes to the Pushgateway"
+    );
+    pub static ref PUSHGATEWAY_PUSH_ERRORS_TOTAL: MetricsResult<IntCounter> =
+        try_create_int_counter(
+            "pushgateway_push_errors_total",
+            "Count of metrics pushes to the Pushgateway which failed"
+        );
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The base URL of the Pushgateway, e.g. `http://localhost:9091`.
+    pub url: SensitiveUrl,
+    pub interval: Duration,
+    pub job: String,
+    pub instance: String,
+}
+
+impl Config {
+    /// Returns the URL to which metrics are pushed, i.e. `/metrics/job/<job>/instance/<instance>`.
+    pub fn push_url(&self) -> Result<Url, String> {
+        let mut url = self.url.full.clone();
+        url.path_segments_mut()
+            .map_err(|_| format!("Invalid Pushgateway URL: {}", self.url))?
+            .pop_if_empty()
+            .extend(&["metrics", "job", &self.job, "instance", &self.instance]);
+        Ok(url)
+    }
+}
+
+/// Returns the hostname of the machine, which is used as the `instance` label by default.
+pub fn default_instance() -> String {
+    hostname::get()
+        .ok()
+        .and_then(|hostname| hostname.into_string().ok())
+        .unwrap_or_else(|| "lighthouse".to_string())
+}
+
+/// Pushes the metrics returned by `gather` (in the Prometheus text format) to the Pushgateway every
+/// `config.interval`, forever.
+///
+/// Failed pushes are logged and retried at the next interval.
+pub async fn push_periodically<F>(config: Config, gather: F, log: Logger)
+where
+    F: Fn() -> Result<String, String>,
+{
+    let url = match config.push_url() {
+        Ok(url) => url,
+        Err(e) => {
+            warn!(log, "Unable to push metrics"; "error" => e);
+            return;
+        }
+    };
+    let client = reqwest::Client::new();
+    let mut interval = tokio::time::interval(config.interval);
+
+    loop {
+        interval.tick().await;
+        inc_counter(&PUSHGATEWAY_PUSHES_TOTAL);
+        match push(&client, url.clone(), config.interval, &gather).await {
+            Ok(()) => debug!(log, "Pushed metrics"; "pushgateway" => %config.url),
+            Err(e) => {
+                inc_counter(&PUSHGATEWAY_PUSH_ERRORS_TOTAL);
+                warn!(
+                    log,
+                    "Unable to push metrics";
+                    "pushgateway" => %config.url,
+                    "error" => e,
+                );
+            }
+        }
+    }
+}
+
+async fn push<F>(
+    client: &reqwest::Client,
+    url: Url,
+    timeout: Duration,
+    gather: &F,
+) -> Result<(), String>
+where
+    F: Fn() -> Result<String, String>,
+{
+    let body = gather()?;
+    client
+        .put(url)
+        .timeout(timeout)
+        .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(url: &str) -> Config {
+        Config {
+            url: SensitiveUrl::parse(url).unwrap(),
+            interval: Duration::from_secs(DEFAULT_INTERVAL_SECS),
+            job: "beacon_node".to_string(),
+            instance: "node 1".to_string(),
+        }
+    }
+
+    #[test]
+    fn push_url() {
+        assert_eq!(
+            config("http://localhost:9091").push_url().unwrap().as_str(),
+            "http://localhost:9091/metrics/job/beacon_node/instance/node%201"
+        );
+        assert_eq!(
+            config("http://localhost:9091/prefix/")
+                .push_url()
+                .unwrap()
+                .as_str(),
+            "http://localhost:9091/prefix/metrics/job/beacon_node/instance/node%201"
+        );
+    }
+}
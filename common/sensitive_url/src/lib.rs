@@ -3,6 +3,11 @@ use std::fmt;
 use std::str::FromStr;
 use url::Url;
 
+/// Hosts which are always connected to directly, rather than through a proxy: the local machine,
+/// and private and link-local addresses.
+pub const NO_PROXY_HOSTS: &str = "localhost,127.0.0.0/8,::1,10.0.0.0/8,172.16.0.0/12,\
+     192.168.0.0/16,169.254.0.0/16,fc00::/7,fe80::/10";
+
 #[derive(Debug)]
 pub enum SensitiveError {
     InvalidUrl(String),
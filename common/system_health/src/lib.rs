@@ -76,7 +76,7 @@ pub struct SystemHealthBN {
 }
 
 /// Populates the system health.
-fn observe_system_health(
+pub fn observe_system_health(
     sysinfo: Arc<RwLock<System>>,
     data_dir: PathBuf,
     app_uptime: u64,
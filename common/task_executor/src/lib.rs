@@ -1,28 +1,49 @@
 mod metrics;
 pub mod test_utils;
+mod watchdog;
 
 use futures::channel::mpsc::Sender;
 use futures::prelude::*;
 use slog::{crit, debug, o, trace};
 use std::sync::Weak;
+use std::time::Duration;
 use tokio::runtime::{Handle, Runtime};
+use watchdog::Watchdog;
 
+pub use metrics::running_tasks;
 pub use tokio::task::JoinHandle;
+pub use watchdog::{Heartbeat, HEARTBEAT_INTERVAL};
+
+/// The category of a failure which shuts Lighthouse down, from which the exit code of the
+/// `lighthouse` binary is chosen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// A database could not be opened or is corrupt.
+    Database,
+    /// A TCP or UDP port could not be bound.
+    PortBind,
+    /// The on-disk database schema is not supported by this version of Lighthouse.
+    IncompatibleSchema,
+    /// The validator client refused to run to avoid a slashable offence.
+    SlashingProtection,
+    /// Any other failure.
+    Other,
+}
 
 /// Provides a reason when Lighthouse is shut down.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ShutdownReason {
     /// The node shut down successfully.
     Success(&'static str),
-    /// The node shut down due to an error condition.
-    Failure(&'static str),
+    /// The node shut down due to an error condition of the given kind.
+    Failure(FailureKind, &'static str),
 }
 
 impl ShutdownReason {
     pub fn message(&self) -> &'static str {
         match self {
             ShutdownReason::Success(msg) => msg,
-            ShutdownReason::Failure(msg) => msg,
+            ShutdownReason::Failure(_, msg) => msg,
         }
     }
 }
@@ -79,6 +100,8 @@ pub struct TaskExecutor {
     ///
     /// The task must provide a reason for shutting down.
     signal_tx: Sender<ShutdownReason>,
+    /// The heartbeats of the services which are monitored for stalls.
+    watchdog: Watchdog,
 
     log: slog::Logger,
 }
@@ -101,6 +124,7 @@ impl TaskExecutor {
             handle_provider: handle.into(),
             exit,
             signal_tx,
+            watchdog: Watchdog::default(),
             log,
         }
     }
@@ -111,6 +135,7 @@ impl TaskExecutor {
             handle_provider: self.handle_provider.clone(),
             exit: self.exit.clone(),
             signal_tx: self.signal_tx.clone(),
+            watchdog: self.watchdog.clone(),
             log: self.log.new(o!("service" => service_name)),
         }
     }
@@ -155,8 +180,10 @@ impl TaskExecutor {
                             "advice" => "Please check above for a backtrace and notify \
                                          the developers"
                         );
-                        let _ = shutdown_sender
-                            .try_send(ShutdownReason::Failure("Panic (fatal error)"));
+                        let _ = shutdown_sender.try_send(ShutdownReason::Failure(
+                            FailureKind::Other,
+                            "Panic (fatal error)",
+                        ));
                     }
                 }
                 drop(timer);
@@ -384,6 +411,26 @@ impl TaskExecutor {
         self.signal_tx.clone()
     }
 
+    /// Registers a critical service called `name` with the watchdog, returning the heartbeat
+    /// which the service must `beat` regularly (at least once per slot).
+    ///
+    /// Heartbeats are only checked once `Self::spawn_watchdog` has been called.
+    pub fn heartbeat(&self, name: &'static str) -> Heartbeat {
+        self.watchdog.heartbeat(name)
+    }
+
+    /// Spawn a task which shuts down the executor if any service registered with
+    /// `Self::heartbeat` doesn't make progress for longer than `stall_timeout`.
+    pub fn spawn_watchdog(&self, stall_timeout: Duration) {
+        let watchdog = self.watchdog.clone();
+        let shutdown_sender = self.shutdown_sender();
+        let log = self.log.clone();
+        self.spawn(
+            watchdog.run(stall_timeout, shutdown_sender, log),
+            "watchdog",
+        );
+    }
+
     /// Returns a reference to the logger.
     pub fn log(&self) -> &slog::Logger {
         &self.log
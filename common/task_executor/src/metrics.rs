@@ -33,4 +33,30 @@ lazy_static! {
         "Time taken by async tasks",
         &["async_task_hist"]
     );
+    pub static ref WATCHDOG_SECONDS_SINCE_HEARTBEAT: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "watchdog_seconds_since_heartbeat",
+        "Seconds since each service monitored by the watchdog last made progress",
+        &["service"]
+    );
+}
+
+/// The names of the gauges which count the running tasks of each kind.
+const TASK_COUNT_METRICS: &[&str] = &[
+    "async_tasks_count",
+    "blocking_tasks_count",
+    "block_on_tasks_count",
+];
+
+/// Returns the name and number of running instances of each task which has not completed.
+pub fn running_tasks() -> Vec<(String, i64)> {
+    gather()
+        .iter()
+        .filter(|family| TASK_COUNT_METRICS.contains(&family.get_name()))
+        .flat_map(|family| family.get_metric())
+        .filter_map(|metric| {
+            let count = metric.get_gauge().get_value() as i64;
+            let name = metric.get_label().first()?.get_value().to_string();
+            (count > 0).then_some((name, count))
+        })
+        .collect()
 }
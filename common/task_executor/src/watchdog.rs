@@ -0,0 +1,138 @@
+//! Detects critical services which have stopped making progress.
+//!
+//! Each service periodically calls `Heartbeat::beat` from its main loop. If any service fails to
+//! do so for longer than the stall timeout, the watchdog logs the stalled service and shuts down
+//! the node, so that a process supervisor (e.g. systemd) can restart it.
+
+use crate::{metrics, FailureKind, ShutdownReason};
+use futures::channel::mpsc::Sender;
+use slog::{crit, debug, Logger};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the watchdog checks the heartbeats. Services driven by a `tokio::select!` loop should
+/// beat on an interval of this length, whilst once-per-slot loops can beat once per slot.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Records the latest progress of a single service.
+#[derive(Clone)]
+pub struct Heartbeat {
+    last_beat: Arc<Mutex<Instant>>,
+}
+
+impl Heartbeat {
+    fn new() -> Self {
+        Self {
+            last_beat: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Records that the service is making progress.
+    pub fn beat(&self) {
+        if let Ok(mut last_beat) = self.last_beat.lock() {
+            *last_beat = Instant::now();
+        }
+    }
+
+    /// Returns the time since the last call to `beat`.
+    fn elapsed(&self) -> Duration {
+        self.last_beat
+            .lock()
+            .map(|last_beat| last_beat.elapsed())
+            .unwrap_or_default()
+    }
+}
+
+/// The heartbeats of all the services of a node, shared by every clone of a `TaskExecutor`.
+#[derive(Clone, Default)]
+pub struct Watchdog {
+    heartbeats: Arc<Mutex<Vec<(&'static str, Heartbeat)>>>,
+}
+
+impl Watchdog {
+    /// Registers a service called `name`, returning the heartbeat it must keep alive.
+    pub fn heartbeat(&self, name: &'static str) -> Heartbeat {
+        let heartbeat = Heartbeat::new();
+        if let Ok(mut heartbeats) = self.heartbeats.lock() {
+            heartbeats.push((name, heartbeat.clone()));
+        }
+        heartbeat
+    }
+
+    /// Returns the name of the first service which hasn't made progress for longer than
+    /// `stall_timeout`, and the time since it last did.
+    fn stalled_service(&self, stall_timeout: Duration) -> Option<(&'static str, Duration)> {
+        let heartbeats = self.heartbeats.lock().ok()?;
+        let mut stalled = None;
+        for &(name, ref heartbeat) in heartbeats.iter() {
+            let elapsed = heartbeat.elapsed();
+            metrics::set_gauge_vec(
+                &metrics::WATCHDOG_SECONDS_SINCE_HEARTBEAT,
+                &[name],
+                elapsed.as_secs() as i64,
+            );
+            if stalled.is_none() && elapsed > stall_timeout {
+                stalled = Some((name, elapsed));
+            }
+        }
+        stalled
+    }
+
+    /// Checks the heartbeats until a service stalls, then requests a shutdown.
+    pub(crate) async fn run(
+        self,
+        stall_timeout: Duration,
+        mut shutdown_sender: Sender<ShutdownReason>,
+        log: Logger,
+    ) {
+        debug!(
+            log,
+            "Started watchdog";
+            "stall_timeout_secs" => stall_timeout.as_secs(),
+        );
+
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Some((name, elapsed)) = self.stalled_service(stall_timeout) {
+                crit!(
+                    log,
+                    "Critical service has stalled, shutting down";
+                    "service" => name,
+                    "secs_since_progress" => elapsed.as_secs(),
+                    "advice" => "this is a bug, please check the logs above and notify the \
+                                 developers",
+                );
+                let _ = shutdown_sender.try_send(ShutdownReason::Failure(
+                    FailureKind::Other,
+                    "A critical service stalled, see the logs for details",
+                ));
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stalled_service() {
+        let watchdog = Watchdog::default();
+        let sync = watchdog.heartbeat("sync");
+        let network = watchdog.heartbeat("network");
+
+        std::thread::sleep(Duration::from_millis(20));
+        network.beat();
+        assert_eq!(
+            watchdog
+                .stalled_service(Duration::from_millis(10))
+                .map(|(name, _)| name),
+            Some("sync")
+        );
+
+        sync.beat();
+        assert_eq!(watchdog.stalled_service(Duration::from_millis(10)), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::auth::header_matches;
 use eth2::lighthouse_vc::{PK_LEN, SECRET_PREFIX as PK_PREFIX};
 use filesystem::create_with_600_perms;
 use libsecp256k1::{Message, PublicKey, SecretKey};
@@ -24,7 +25,8 @@ pub const SK_LEN: usize = 32;
 pub const PK_FILENAME: &str = "api-token.txt";
 
 /// Contains a `secp256k1` keypair that is saved-to/loaded-from disk on instantiation. The keypair
-/// is used for authorization/authentication for requests/responses on the HTTP API.
+/// is used for authorization/authentication for requests/responses on the HTTP APIs of the
+/// validator client and, without the signature, the privileged endpoints of the beacon node.
 ///
 /// Provides convenience functions to ultimately provide:
 ///
@@ -50,8 +52,14 @@ impl ApiSecret {
     /// If either the secret or public key files are missing on disk, create a new keypair and
     /// write it to disk (over-writing any existing files).
     pub fn create_or_open<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
-        let sk_path = dir.as_ref().join(SK_FILENAME);
-        let pk_path = dir.as_ref().join(PK_FILENAME);
+        Self::create_or_open_token_file(dir.as_ref().join(PK_FILENAME))
+    }
+
+    /// As `create_or_open`, but with the public key stored in the file at `pk_path` rather than
+    /// `PK_FILENAME`. The secret key is stored in `SK_FILENAME` in the same directory.
+    pub fn create_or_open_token_file<P: AsRef<Path>>(pk_path: P) -> Result<Self, String> {
+        let pk_path = pk_path.as_ref().to_path_buf();
+        let sk_path = pk_path.with_file_name(SK_FILENAME);
 
         if !(sk_path.exists() && pk_path.exists()) {
             let sk = SecretKey::random(&mut thread_rng());
@@ -187,10 +195,10 @@ impl ApiSecret {
             .map(move || expected.clone())
             .and(warp::filters::header::header("Authorization"))
             .and_then(move |expected: Vec<String>, header: String| async move {
-                if expected.contains(&header) {
+                if header_matches(&expected, &header) {
                     Ok(())
                 } else {
-                    Err(warp_utils::reject::invalid_auth(header))
+                    Err(crate::reject::invalid_auth(header))
                 }
             })
             .untuple_one()
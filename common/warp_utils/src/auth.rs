@@ -0,0 +1,47 @@
+use crate::reject::invalid_auth;
+use ring::constant_time;
+use std::fs;
+use std::path::Path;
+use warp::filters::BoxedFilter;
+use warp::Filter;
+
+/// Reads a bearer token from the file at `path`, ignoring surrounding whitespace.
+pub fn read_bearer_token(path: &Path) -> Result<String, String> {
+    let token = fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read bearer token file {:?}: {:?}", path, e))?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        return Err(format!("Bearer token file {:?} is empty", path));
+    }
+    Ok(token)
+}
+
+/// Returns `true` if `header` is equal to one of the `expected` values.
+///
+/// Each comparison takes the same time wherever the first differing byte is, so that a token
+/// can't be recovered from the response times of guesses.
+pub fn header_matches(expected: &[String], header: &str) -> bool {
+    expected.iter().any(|expected| {
+        constant_time::verify_slices_are_equal(expected.as_bytes(), header.as_bytes()).is_ok()
+    })
+}
+
+/// Returns a filter which rejects any request without an `Authorization: Bearer <token>` header.
+///
+/// The rejections are converted to `401` and `403` responses by `reject::handle_rejection`.
+pub fn bearer_token_filter(token: String) -> BoxedFilter<()> {
+    let expected = vec![format!("Bearer {}", token)];
+    warp::any()
+        .map(move || expected.clone())
+        .and(warp::filters::header::header("Authorization"))
+        .and_then(|expected: Vec<String>, header: String| async move {
+            if header_matches(&expected, &header) {
+                Ok(())
+            } else {
+                Err(invalid_auth("invalid bearer token".to_string()))
+            }
+        })
+        .untuple_one()
+        .boxed()
+}
@@ -3,7 +3,8 @@ use warp::filters::cors::Builder;
 
 /// Configure a `cors::Builder`.
 ///
-/// If `allow_origin.is_none()` the `default_origin` is used.
+/// `allow_origin` is a comma-separated list of origins, or `*` to allow any origin. If
+/// `allow_origin.is_none()` the `default_origin` is used.
 pub fn set_builder_origins(
     builder: Builder,
     allow_origin: Option<&str>,
@@ -14,7 +15,12 @@ pub fn set_builder_origins(
             .split(',')
             .map(|s| verify_cors_origin_str(s).map(|_| s))
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(builder.allow_origins(origins))
+        // `warp` panics if the wildcard is given as an origin, it must be allowed separately.
+        if origins.contains(&"*") {
+            Ok(builder.allow_any_origin())
+        } else {
+            Ok(builder.allow_origins(origins))
+        }
     } else {
         let origin = match default_origin.0 {
             IpAddr::V4(_) => format!("http://{}:{}", default_origin.0, default_origin.1),
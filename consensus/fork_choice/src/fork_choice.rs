@@ -1396,6 +1396,12 @@ where
         self.fc_store.proposer_boost_root()
     }
 
+    /// Sets the number of finalized nodes which must accumulate before the fork choice DAG is
+    /// pruned.
+    pub fn set_prune_threshold(&mut self, prune_threshold: usize) {
+        self.proto_array.set_prune_threshold(prune_threshold)
+    }
+
     /// Prunes the underlying fork choice DAG.
     pub fn prune(&mut self) -> Result<(), Error<T::Error>> {
         let finalized_root = self.fc_store.finalized_checkpoint().root;
@@ -0,0 +1,111 @@
+use safe_arith::{ArithError, SafeArith};
+use types::{BeaconState, BeaconStateError, ChainSpec, Epoch, EthSpec, Unsigned};
+
+/// The percentage of the validator set which may be lost before the weak subjectivity period is
+/// considered unsafe, as per the spec.
+pub const SAFETY_DECAY: u64 = 10;
+
+/// The number of Gwei in one Ether.
+const ETH_TO_GWEI: u64 = 1_000_000_000;
+
+/// Returns the number of epochs after the epoch of `state` during which it is safe to sync from
+/// `state`, as per the `compute_weak_subjectivity_period` function of the spec.
+///
+/// Does not use the committee or total active balance caches.
+pub fn compute_weak_subjectivity_period<E: EthSpec>(
+    state: &BeaconState<E>,
+    spec: &ChainSpec,
+) -> Result<u64, BeaconStateError> {
+    let active_validator_indices =
+        state.get_active_validator_indices(state.current_epoch(), spec)?;
+    let validator_count = active_validator_indices.len() as u64;
+    if validator_count == 0 {
+        return Ok(spec.min_validator_withdrawability_delay.as_u64());
+    }
+    let total_active_balance = state.get_total_balance(&active_validator_indices, spec)?;
+    let churn_limit = std::cmp::max(
+        spec.min_per_epoch_churn_limit,
+        validator_count.safe_div(spec.churn_limit_quotient)?,
+    );
+
+    Ok(weak_subjectivity_period(
+        spec.min_validator_withdrawability_delay,
+        validator_count,
+        total_active_balance
+            .safe_div(validator_count)?
+            .safe_div(ETH_TO_GWEI)?,
+        spec.max_effective_balance.safe_div(ETH_TO_GWEI)?,
+        churn_limit,
+        E::MaxDeposits::to_u64().safe_mul(E::slots_per_epoch())?,
+    )?)
+}
+
+/// Computes the weak subjectivity period from the number of active validators `n`, their average
+/// balance `t` and maximum balance `max_t` in Ether, the churn limit and the maximum number of
+/// deposits per epoch.
+fn weak_subjectivity_period(
+    min_validator_withdrawability_delay: Epoch,
+    n: u64,
+    t: u64,
+    max_t: u64,
+    churn_limit: u64,
+    max_deposits_per_epoch: u64,
+) -> Result<u64, ArithError> {
+    let d = SAFETY_DECAY;
+    let period = min_validator_withdrawability_delay.as_u64();
+
+    let max_t_factor = max_t.safe_mul(d.safe_mul(3)?.safe_add(200)?)?;
+    let t_factor = t.safe_mul(d.safe_mul(12)?.safe_add(200)?)?;
+
+    if max_t_factor < t_factor {
+        let epochs_for_validator_set_churn =
+            n.safe_mul(t_factor.safe_sub(max_t_factor)?)?.safe_div(
+                churn_limit
+                    .safe_mul(600)?
+                    .safe_mul(t.safe_mul(2)?.safe_add(max_t)?)?,
+            )?;
+        let epochs_for_balance_top_ups = n
+            .safe_mul(d.safe_mul(3)?.safe_add(200)?)?
+            .safe_div(max_deposits_per_epoch.safe_mul(600)?)?;
+        period.safe_add(std::cmp::max(
+            epochs_for_validator_set_churn,
+            epochs_for_balance_top_ups,
+        ))
+    } else {
+        period.safe_add(
+            n.safe_mul(d)?.safe_mul(t)?.safe_mul(3)?.safe_div(
+                max_deposits_per_epoch
+                    .safe_mul(200)?
+                    .safe_mul(max_t.safe_sub(t)?)?,
+            )?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Values from the table in the weak subjectivity section of the spec, with mainnet
+    /// parameters.
+    #[test]
+    fn spec_examples() {
+        let period = |n, t| {
+            let churn_limit = std::cmp::max(4, n / 65_536);
+            weak_subjectivity_period(Epoch::new(256), n, t, 32, churn_limit, 16 * 32).unwrap()
+        };
+
+        assert_eq!(period(32_768, 28), 504);
+        assert_eq!(period(65_536, 28), 752);
+        assert_eq!(period(131_072, 28), 1_248);
+        assert_eq!(period(262_144, 28), 2_241);
+        assert_eq!(period(524_288, 28), 2_241);
+        assert_eq!(period(1_048_576, 28), 2_241);
+        assert_eq!(period(32_768, 32), 665);
+        assert_eq!(period(65_536, 32), 1_075);
+        assert_eq!(period(131_072, 32), 1_894);
+        assert_eq!(period(262_144, 32), 3_532);
+        assert_eq!(period(524_288, 32), 3_532);
+        assert_eq!(period(1_048_576, 32), 3_532);
+    }
+}
@@ -164,6 +164,36 @@ where
         let verified_proposer_index =
             Some(ctxt.get_proposer_index_from_epoch_state(self.state, self.spec)?);
         self.include_randao_reveal(block, verified_proposer_index)?;
+        self.include_operations(block, ctxt)
+    }
+
+    /// Includes all signatures on the block (except the deposit signatures) for verification,
+    /// assuming that the block's `proposer_index` is correct.
+    ///
+    /// The proposer index is neither checked against `self.state` nor cached in `ctxt`, so it is
+    /// checked against the shuffling when the block is processed. This allows a block's signatures
+    /// to be verified with a state that has the correct committees but not the correct proposers,
+    /// e.g. one advanced through earlier blocks without applying them.
+    pub fn include_all_signatures_with_block_proposer<Payload: AbstractExecPayload<T>>(
+        &mut self,
+        block: &'a SignedBeaconBlock<T, Payload>,
+        ctxt: &mut ConsensusContext<T>,
+    ) -> Result<()> {
+        let block_root = Some(ctxt.get_current_block_root(block)?);
+        let proposer_index = Some(block.message().proposer_index());
+
+        self.include_block_proposal(block, block_root, proposer_index)?;
+        self.include_randao_reveal(block, proposer_index)?;
+        self.include_operations(block, ctxt)
+    }
+
+    /// Includes the signatures of all of the block's operations (except the deposits) for
+    /// verification.
+    fn include_operations<Payload: AbstractExecPayload<T>>(
+        &mut self,
+        block: &'a SignedBeaconBlock<T, Payload>,
+        ctxt: &mut ConsensusContext<T>,
+    ) -> Result<()> {
         self.include_proposer_slashings(block)?;
         self.include_attester_slashings(block)?;
         self.include_attestations(block, ctxt)?;
@@ -372,6 +402,12 @@ where
             Err(Error::SignatureInvalid)
         }
     }
+
+    /// Return the signatures that have been included in `self`, so that they can be verified in
+    /// the same batch as those from other verifiers.
+    pub fn into_signature_sets(self) -> ParallelSignatureSets<'a> {
+        self.sets
+    }
 }
 
 impl<'a> ParallelSignatureSets<'a> {
@@ -379,6 +415,19 @@ impl<'a> ParallelSignatureSets<'a> {
         self.sets.push(set);
     }
 
+    /// Add all of the signatures in `other` to `self`.
+    pub fn append(&mut self, other: ParallelSignatureSets<'a>) {
+        self.sets.extend(other.sets);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
     /// Verify all the signatures that have been included in `self`, returning `true` if and only if
     /// all the signatures are valid.
     ///
@@ -1,6 +1,7 @@
 use super::{BeaconBlockHeader, EthSpec, Slot, SyncAggregate};
 use crate::{
-    light_client_update::Error, test_utils::TestRandom, BeaconState, ChainSpec, SignedBeaconBlock,
+    light_client_update::Error, test_utils::TestRandom, BeaconState, ChainSpec, ForkName,
+    ForkVersionDeserialize, SignedBeaconBlock,
 };
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
@@ -60,6 +61,27 @@ impl<T: EthSpec> LightClientOptimisticUpdate<T> {
     }
 }
 
+impl<T: EthSpec> ForkVersionDeserialize for LightClientOptimisticUpdate<T> {
+    fn deserialize_by_fork<'de, D: serde::Deserializer<'de>>(
+        value: serde_json::value::Value,
+        fork_name: ForkName,
+    ) -> Result<Self, D::Error> {
+        match fork_name {
+            ForkName::Altair | ForkName::Merge | ForkName::Capella => serde_json::from_value(value)
+                .map_err(|e| {
+                    serde::de::Error::custom(format!(
+                        "LightClientOptimisticUpdate failed to deserialize: {:?}",
+                        e
+                    ))
+                }),
+            ForkName::Base => Err(serde::de::Error::custom(format!(
+                "LightClientOptimisticUpdate failed to deserialize: unsupported fork '{}'",
+                fork_name
+            ))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
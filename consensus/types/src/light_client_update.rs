@@ -1,5 +1,8 @@
 use super::{BeaconBlockHeader, EthSpec, FixedVector, Hash256, Slot, SyncAggregate, SyncCommittee};
-use crate::{beacon_state, test_utils::TestRandom, BeaconBlock, BeaconState, ChainSpec};
+use crate::{
+    beacon_state, test_utils::TestRandom, BeaconBlock, BeaconState, ChainSpec, ForkName,
+    ForkVersionDeserialize,
+};
 use safe_arith::ArithError;
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
@@ -139,6 +142,27 @@ impl<T: EthSpec> LightClientUpdate<T> {
     }
 }
 
+impl<T: EthSpec> ForkVersionDeserialize for LightClientUpdate<T> {
+    fn deserialize_by_fork<'de, D: serde::Deserializer<'de>>(
+        value: serde_json::value::Value,
+        fork_name: ForkName,
+    ) -> Result<Self, D::Error> {
+        match fork_name {
+            ForkName::Altair | ForkName::Merge | ForkName::Capella => serde_json::from_value(value)
+                .map_err(|e| {
+                    serde::de::Error::custom(format!(
+                        "LightClientUpdate failed to deserialize: {:?}",
+                        e
+                    ))
+                }),
+            ForkName::Base => Err(serde::de::Error::custom(format!(
+                "LightClientUpdate failed to deserialize: unsupported fork '{}'",
+                fork_name
+            ))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,291 @@
+//! Import finalized history from `.era` archives, instead of backfilling it from peers, and
+//! export it so that it can be published.
+
+use crate::open_db;
+use beacon_node::ClientConfig;
+use clap::{App, AppSettings, Arg, ArgMatches};
+use environment::RuntimeContext;
+use slog::{info, Logger};
+use std::fs;
+use std::path::{Path, PathBuf};
+use store::era::{era_start_slot, Era};
+use types::{EthSpec, Slot};
+
+pub const IMPORT_CMD: &str = "import_era";
+pub const EXPORT_CMD: &str = "export_era";
+
+pub fn import_cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(IMPORT_CMD)
+        .alias("import-era")
+        .setting(AppSettings::ColoredHelp)
+        .about(
+            "Import the blocks older than the oldest block in the database, and the states at the \
+             end of each era, from a directory of era files",
+        )
+        .arg(
+            Arg::with_name("dir")
+                .value_name("DIR")
+                .help("The directory containing the era files")
+                .required(true)
+                .index(1),
+        )
+}
+
+pub fn export_cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(EXPORT_CMD)
+        .alias("export-era")
+        .setting(AppSettings::ColoredHelp)
+        .about("Write the finalized blocks and states in the database to era files")
+        .arg(
+            Arg::with_name("dir")
+                .value_name("DIR")
+                .help("The directory to write the era files to, which is created if necessary")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("start-era")
+                .long("start-era")
+                .value_name("ERA")
+                .help(
+                    "The first era to export. Defaults to the oldest era whose blocks are all \
+                     stored",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("end-era")
+                .long("end-era")
+                .value_name("ERA")
+                .help("The last era to export. Defaults to the latest finalized era")
+                .takes_value(true),
+        )
+}
+
+/// Returns the era files in `dir` with their era number, newest first.
+///
+/// Era files are named `<config-name>-<era-number>-<short-historical-root>.era`.
+fn era_files(dir: &Path) -> Result<Vec<(u64, PathBuf)>, String> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir).map_err(|e| format!("Unable to read {:?}: {}", dir, e))? {
+        let path = entry
+            .map_err(|e| format!("Unable to read {:?}: {}", dir, e))?
+            .path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "era")
+        {
+            continue;
+        }
+        let era = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.rsplit('-').nth(1))
+            .and_then(|era| era.parse::<u64>().ok())
+            .ok_or_else(|| format!("Unable to parse the era number of {:?}", path))?;
+        files.push((era, path));
+    }
+    files.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+    Ok(files)
+}
+
+pub fn run_import<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let dir: PathBuf = clap_utils::parse_required(cli_args, "dir")?;
+    let db = open_db(client_config, runtime_context, log.clone())?;
+    let spec = &runtime_context.eth2_config.spec;
+
+    let mut imported = 0;
+    for (era, path) in era_files(&dir)? {
+        // Every block and state is already stored.
+        let anchor_info = match db.get_anchor_info() {
+            Some(anchor_info) => anchor_info,
+            None => break,
+        };
+        // Every block in this era and the state at its end are already stored.
+        let state_slot = Slot::new(era * E::slots_per_historical_root() as u64);
+        if era_start_slot::<E>(era) >= anchor_info.oldest_block_slot
+            && (anchor_info.historic_states_pruned()
+                || state_slot <= anchor_info.state_lower_limit
+                || state_slot >= anchor_info.state_upper_limit)
+        {
+            continue;
+        }
+
+        let bytes = fs::read(&path).map_err(|e| format!("Unable to read {:?}: {}", path, e))?;
+        let era_contents = Era::<E>::from_bytes(&bytes, spec)
+            .map_err(|e| format!("Unable to decode {:?}: {:?}", path, e))?;
+        if era_contents.number() != era {
+            return Err(format!(
+                "{:?} contains era {} rather than era {}",
+                path,
+                era_contents.number(),
+                era
+            ));
+        }
+
+        let count = db
+            .import_era_blocks(era_contents.blocks)
+            .map_err(|e| format!("Unable to import {:?}: {:?}", path, e))?;
+        imported += count;
+        let state_imported = db
+            .import_era_state(era_contents.state)
+            .map_err(|e| format!("Unable to import the state of {:?}: {:?}", path, e))?;
+        info!(
+            log,
+            "Imported era";
+            "era" => era,
+            "blocks" => count,
+            "state" => state_imported,
+            "oldest_block_slot" => db.get_oldest_block_slot(),
+        );
+    }
+
+    let oldest_block_slot = db.get_oldest_block_slot();
+    info!(
+        log,
+        "Era import complete";
+        "blocks" => imported,
+        "oldest_block_slot" => oldest_block_slot,
+    );
+    if db.get_anchor_info().is_none() {
+        info!(log, "All historic blocks and states are stored");
+    } else if oldest_block_slot == spec.genesis_slot {
+        info!(
+            log,
+            "All historic blocks are stored";
+            "info" => "start the beacon node with --reconstruct-historic-states to regenerate \
+                       historic states",
+        );
+    }
+    Ok(())
+}
+
+pub fn run_export<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let dir: PathBuf = clap_utils::parse_required(cli_args, "dir")?;
+    let db = open_db(client_config, runtime_context, log.clone())?;
+    let spec = &runtime_context.eth2_config.spec;
+    let config_name = spec.config_name.as_deref().unwrap_or("unknown");
+    let slots_per_era = E::slots_per_historical_root() as u64;
+
+    // The blocks of an era must all be stored, and the state at its end must be finalized.
+    let oldest_block_slot = db.get_oldest_block_slot();
+    let first_era = if oldest_block_slot == spec.genesis_slot {
+        0
+    } else {
+        (oldest_block_slot.as_u64() + slots_per_era - 1) / slots_per_era + 1
+    };
+    let split = db.get_split_info();
+    let last_era = split.slot.as_u64() / slots_per_era;
+    let start_era = clap_utils::parse_optional(cli_args, "start-era")?.unwrap_or(first_era);
+    let end_era = clap_utils::parse_optional(cli_args, "end-era")?.unwrap_or(last_era);
+    if start_era < first_era || end_era > last_era || start_era > end_era {
+        return Err(format!(
+            "Eras {} to {} can't be exported, the database contains eras {} to {}",
+            start_era, end_era, first_era, last_era
+        ));
+    }
+
+    let split_state = db
+        .get_state(&split.state_root, Some(split.slot))
+        .map_err(|e| format!("Unable to load split state: {:?}", e))?
+        .ok_or("Split state is missing from the database")?;
+    let split_block_root = split_state.get_latest_block_root(split.state_root);
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create {:?}: {}", dir, e))?;
+
+    for era in start_era..=end_era {
+        let state_slot = Slot::new(era * slots_per_era);
+        let (state_root, _) = db
+            .forwards_state_roots_iterator_until(
+                state_slot,
+                state_slot,
+                || (split_state.clone(), split.state_root),
+                spec,
+            )
+            .and_then(|mut iter| iter.next().transpose())
+            .map_err(|e| format!("Unable to iterate state roots: {:?}", e))?
+            .ok_or_else(|| format!("No state root is stored for slot {}", state_slot))?;
+        let state = db
+            .get_state(&state_root, Some(state_slot))
+            .map_err(|e| format!("Unable to load state at slot {}: {:?}", state_slot, e))?
+            .ok_or_else(|| {
+                format!(
+                    "State at slot {} is unavailable, historic states must be reconstructed",
+                    state_slot
+                )
+            })?;
+
+        let mut blocks = vec![];
+        if era > 0 {
+            let block_roots = db
+                .forwards_block_roots_iterator_until(
+                    era_start_slot::<E>(era),
+                    state_slot - 1,
+                    || (split_state.clone(), split_block_root),
+                    spec,
+                )
+                .map_err(|e| format!("Unable to iterate block roots: {:?}", e))?;
+            let mut previous_root = None;
+            for result in block_roots {
+                let (block_root, slot) =
+                    result.map_err(|e| format!("Unable to iterate block roots: {:?}", e))?;
+                // Skipped slots repeat the root of the previous block, and the genesis block is
+                // part of the genesis state rather than an era.
+                if previous_root == Some(block_root) || slot == spec.genesis_slot {
+                    previous_root = Some(block_root);
+                    continue;
+                }
+                previous_root = Some(block_root);
+
+                let block = db
+                    .get_full_block(&block_root)
+                    .map_err(|e| {
+                        format!(
+                            "Unable to load block at slot {}, its payload may have been \
+                             pruned: {:?}",
+                            slot, e
+                        )
+                    })?
+                    .ok_or_else(|| format!("Block at slot {} is missing", slot))?;
+                // The first slots of the era may repeat the last block of the previous era.
+                if block.slot() == slot {
+                    blocks.push(block);
+                }
+            }
+        }
+
+        let era_contents = Era { blocks, state };
+        let bytes = era_contents
+            .to_bytes()
+            .map_err(|e| format!("Unable to encode era {}: {:?}", era, e))?;
+        let path = dir.join(
+            era_contents
+                .file_name(config_name)
+                .map_err(|e| format!("Unable to name era {}: {:?}", era, e))?,
+        );
+        // Write to a temporary file first so that an interrupted export leaves no partial era.
+        let temp_path = path.with_extension("era.tmp");
+        fs::write(&temp_path, &bytes)
+            .and_then(|()| fs::rename(&temp_path, &path))
+            .map_err(|e| format!("Unable to write {:?}: {}", path, e))?;
+        info!(
+            log,
+            "Exported era";
+            "era" => era,
+            "blocks" => era_contents.blocks.len(),
+            "path" => ?path,
+        );
+    }
+
+    Ok(())
+}
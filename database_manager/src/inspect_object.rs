@@ -0,0 +1,190 @@
+//! Print a block or state from the database, so that it can be compared against other nodes
+//! without custom SSZ tooling.
+//!
+//! Objects are looked up by root, or by slot for finalized slots.
+
+use crate::{open_db, Db};
+use beacon_node::ClientConfig;
+use clap::{App, AppSettings, Arg, ArgMatches};
+use environment::RuntimeContext;
+use serde::Serialize;
+use slog::Logger;
+use store::DatabaseBlock;
+use strum::{EnumString, EnumVariantNames, VariantNames};
+use types::{EthSpec, Hash256, Slot};
+
+pub const BLOCK_CMD: &str = "inspect_block";
+pub const STATE_CMD: &str = "inspect_state";
+
+#[derive(Debug, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    Yaml,
+    Json,
+}
+
+fn cli_app<'a, 'b>(
+    name: &'static str,
+    alias: &'static str,
+    about: &'static str,
+    root_help: &'static str,
+) -> App<'a, 'b> {
+    App::new(name)
+        .alias(alias)
+        .setting(AppSettings::ColoredHelp)
+        .about(about)
+        .arg(
+            Arg::with_name("root")
+                .long("root")
+                .value_name("ROOT")
+                .help(root_help)
+                .takes_value(true)
+                .required_unless("slot"),
+        )
+        .arg(
+            Arg::with_name("slot")
+                .long("slot")
+                .value_name("SLOT")
+                .help("The slot of the canonical object, which must be finalized")
+                .takes_value(true)
+                .conflicts_with("root"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("The format in which to print the object")
+                .default_value("yaml")
+                .possible_values(OutputFormat::VARIANTS),
+        )
+}
+
+pub fn block_cli_app<'a, 'b>() -> App<'a, 'b> {
+    cli_app(
+        BLOCK_CMD,
+        "inspect-block",
+        "Print a block from the database",
+        "The root of the block",
+    )
+}
+
+pub fn state_cli_app<'a, 'b>() -> App<'a, 'b> {
+    cli_app(
+        STATE_CMD,
+        "inspect-state",
+        "Print a state from the database",
+        "The root of the state",
+    )
+}
+
+fn print<T: Serialize>(object: &T, format: OutputFormat) -> Result<(), String> {
+    let output = match format {
+        OutputFormat::Yaml => serde_yaml::to_string(object).map_err(|e| e.to_string())?,
+        OutputFormat::Json => serde_json::to_string_pretty(object).map_err(|e| e.to_string())?,
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+/// Returns the canonical block and state roots at the finalized `slot`.
+///
+/// The block root is that of the latest block at or before `slot`.
+fn roots_at_slot<E: EthSpec>(db: &Db<E>, slot: Slot) -> Result<(Hash256, Hash256), String> {
+    let split = db.get_split_info();
+    if slot > split.slot {
+        return Err(format!(
+            "Slot {} is not finalized, the latest finalized slot in the database is {}. Use \
+             --root instead.",
+            slot, split.slot
+        ));
+    }
+    let spec = db.get_chain_spec();
+    let split_state = db
+        .get_state(&split.state_root, Some(split.slot))
+        .map_err(|e| format!("Unable to load split state: {:?}", e))?
+        .ok_or("Split state is missing from the database")?;
+    let split_block_root = split_state.get_latest_block_root(split.state_root);
+
+    let (block_root, _) = db
+        .forwards_block_roots_iterator_until(
+            slot,
+            slot,
+            || (split_state.clone(), split_block_root),
+            spec,
+        )
+        .and_then(|mut iter| iter.next().transpose())
+        .map_err(|e| format!("Unable to iterate block roots: {:?}", e))?
+        .ok_or_else(|| format!("No block root is stored for slot {}", slot))?;
+    let (state_root, _) = db
+        .forwards_state_roots_iterator_until(slot, slot, || (split_state, split.state_root), spec)
+        .and_then(|mut iter| iter.next().transpose())
+        .map_err(|e| format!("Unable to iterate state roots: {:?}", e))?
+        .ok_or_else(|| format!("No state root is stored for slot {}", slot))?;
+
+    Ok((block_root, state_root))
+}
+
+pub fn run_inspect_block<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let format = clap_utils::parse_required(cli_args, "format")?;
+    let db = open_db(client_config, runtime_context, log)?;
+
+    let block_root = match clap_utils::parse_optional::<Slot>(cli_args, "slot")? {
+        Some(slot) => {
+            let (block_root, _) = roots_at_slot(&db, slot)?;
+            let block = db
+                .get_blinded_block(&block_root)
+                .map_err(|e| format!("Unable to load block: {:?}", e))?
+                .ok_or_else(|| format!("Block {:?} is missing from the database", block_root))?;
+            if block.slot() != slot {
+                return Err(format!(
+                    "Slot {} was skipped, the previous block is at slot {}",
+                    slot,
+                    block.slot()
+                ));
+            }
+            block_root
+        }
+        None => clap_utils::parse_required(cli_args, "root")?,
+    };
+
+    // The execution payload is omitted if it has been pruned.
+    match db
+        .try_get_full_block(&block_root)
+        .map_err(|e| format!("Unable to load block: {:?}", e))?
+    {
+        Some(DatabaseBlock::Full(block)) => print(&block, format),
+        Some(DatabaseBlock::Blinded(block)) => print(&block, format),
+        None => Err(format!("Block {:?} is not in the database", block_root)),
+    }
+}
+
+pub fn run_inspect_state<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let format = clap_utils::parse_required(cli_args, "format")?;
+    let db = open_db(client_config, runtime_context, log)?;
+
+    let (state_root, slot) = match clap_utils::parse_optional::<Slot>(cli_args, "slot")? {
+        Some(slot) => (roots_at_slot(&db, slot)?.1, Some(slot)),
+        None => (clap_utils::parse_required(cli_args, "root")?, None),
+    };
+
+    let state = db
+        .get_state(&state_root, slot)
+        .map_err(|e| format!("Unable to load state: {:?}", e))?
+        .ok_or_else(|| {
+            format!(
+                "State {:?} is not in the database, it may have been pruned",
+                state_root
+            )
+        })?;
+    print(&state, format)
+}
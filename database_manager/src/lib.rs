@@ -1,21 +1,32 @@
+mod era;
+mod inspect_object;
+pub mod replay_blocks;
+mod snapshot;
+mod verify;
+
 use beacon_chain::{
     builder::Witness, eth1_chain::CachingEth1Backend, schema_change::migrate_schema,
     slot_clock::SystemTimeSlotClock,
 };
-use beacon_node::{get_data_dir, get_slots_per_restore_point, ClientConfig};
+use beacon_node::{get_data_dir, get_slots_per_restore_point, lock_data_dir, ClientConfig};
 use clap::{App, Arg, ArgMatches};
 use environment::{Environment, RuntimeContext};
-use slog::{info, Logger};
+use slog::{info, warn, Logger};
+use std::fs;
+use std::sync::Arc;
 use store::{
+    config::{DatabaseBackend, FreezerCompression},
     errors::Error,
     metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION},
-    DBColumn, HotColdDB, KeyValueStore, LevelDB,
+    BeaconNodeBackend, DBColumn, HotColdDB, KeyValueStore,
 };
 use strum::{EnumString, EnumVariantNames, VariantNames};
-use types::EthSpec;
+use types::{BeaconState, EthSpec};
 
 pub const CMD: &str = "database_manager";
 
+type Db<E> = HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>;
+
 pub fn version_cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new("version")
         .visible_aliases(&["v"])
@@ -61,10 +72,81 @@ pub fn inspect_cli_app<'a, 'b>() -> App<'a, 'b> {
 
 pub fn prune_payloads_app<'a, 'b>() -> App<'a, 'b> {
     App::new("prune_payloads")
+        .alias("prune-payloads")
         .setting(clap::AppSettings::ColoredHelp)
         .about("Prune finalized execution payloads")
 }
 
+pub fn compact_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("compact")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Compact the hot and freezer databases, reclaiming the space used by deleted \
+             values",
+        )
+}
+
+pub fn recompress_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("recompress")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about("Rewrite the full states in the freezer database with a different compression")
+        .arg(
+            Arg::with_name("compression")
+                .long("compression")
+                .value_name("COMPRESSION")
+                .help("The compression to rewrite the states with")
+                .takes_value(true)
+                .possible_values(FreezerCompression::VARIANTS)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("compression-level")
+                .long("compression-level")
+                .value_name("LEVEL")
+                .help("The zlib compression level, from 0 (fastest) to 9 (smallest)")
+                .takes_value(true)
+                .default_value("6"),
+        )
+}
+
+pub fn prune_states_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("prune_states")
+        .alias("prune-states")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Delete all historic states except the genesis state from the freezer database, \
+             and stop storing them. Blocks are kept.",
+        )
+        .arg(
+            Arg::with_name("confirm")
+                .long("confirm")
+                .help(
+                    "Commit to pruning the states. Without this flag the states are left \
+                     untouched.",
+                )
+                .takes_value(false),
+        )
+}
+
+pub fn convert_backend_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("convert_backend")
+        .alias("convert-backend")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Copy the hot and freezer databases into new databases using a different backend. \
+             The original databases are kept as backups.",
+        )
+        .arg(
+            Arg::with_name("to")
+                .long("to")
+                .value_name("DATABASE")
+                .help("The backend to convert the databases to")
+                .takes_value(true)
+                .possible_values(DatabaseBackend::VARIANTS)
+                .required(true),
+        )
+}
+
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new(CMD)
         .visible_aliases(&["db"])
@@ -76,7 +158,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("SLOT_COUNT")
                 .help(
                     "Specifies how often a freezer DB restore point should be stored. \
-                       Cannot be changed after initialization. \
+                       Changing it re-anchors an existing freezer DB. \
                        [default: 2048 (mainnet) or 64 (minimal)]",
                 )
                 .takes_value(true),
@@ -88,13 +170,51 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Data directory for the freezer database.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("db-backend")
+                .long("db-backend")
+                .value_name("DATABASE")
+                .help(
+                    "The database backend used by the hot and freezer databases. \
+                     [default: leveldb]",
+                )
+                .takes_value(true)
+                .possible_values(DatabaseBackend::VARIANTS),
+        )
         .subcommand(migrate_cli_app())
         .subcommand(version_cli_app())
         .subcommand(inspect_cli_app())
+        .subcommand(inspect_object::block_cli_app())
+        .subcommand(inspect_object::state_cli_app())
         .subcommand(prune_payloads_app())
+        .subcommand(prune_states_app())
+        .subcommand(compact_app())
+        .subcommand(recompress_app())
+        .subcommand(convert_backend_app())
+        .subcommand(snapshot::cli_app())
+        .subcommand(verify::cli_app())
+        .subcommand(era::import_cli_app())
+        .subcommand(era::export_cli_app())
+}
+
+/// Opens the hot and cold databases of `client_config` without running any schema migrations.
+fn open_db<E: EthSpec>(
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<Arc<Db<E>>, String> {
+    Db::<E>::open(
+        &client_config.get_db_path(),
+        &client_config.get_freezer_db_path(),
+        |_, _, _| Ok(()),
+        client_config.store,
+        runtime_context.eth2_config.spec.clone(),
+        log,
+    )
+    .map_err(|e| format!("Unable to open database: {:?}", e))
 }
 
-fn parse_client_config<E: EthSpec>(
+pub fn parse_client_config<E: EthSpec>(
     cli_args: &ArgMatches,
     _env: &Environment<E>,
 ) -> Result<ClientConfig, String> {
@@ -110,6 +230,10 @@ fn parse_client_config<E: EthSpec>(
     client_config.store.slots_per_restore_point = sprp;
     client_config.store.slots_per_restore_point_set_explicitly = sprp_explicit;
 
+    if let Some(backend) = clap_utils::parse_optional(cli_args, "db-backend")? {
+        client_config.store.backend = backend;
+    }
+
     Ok(client_config)
 }
 
@@ -123,7 +247,7 @@ pub fn display_db_version<E: EthSpec>(
     let cold_path = client_config.get_freezer_db_path();
 
     let mut version = CURRENT_SCHEMA_VERSION;
-    HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, from, _| {
@@ -178,7 +302,7 @@ pub fn inspect_db<E: EthSpec>(
     let hot_path = client_config.get_db_path();
     let cold_path = client_config.get_freezer_db_path();
 
-    let db = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, _, _| Ok(()),
@@ -234,7 +358,7 @@ pub fn migrate_db<E: EthSpec>(
 
     let mut from = CURRENT_SCHEMA_VERSION;
     let to = migrate_config.to;
-    let db = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, db_initial_version, _| {
@@ -272,7 +396,7 @@ pub fn prune_payloads<E: EthSpec>(
     let hot_path = client_config.get_db_path();
     let cold_path = client_config.get_freezer_db_path();
 
-    let db = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, _, _| Ok(()),
@@ -287,12 +411,168 @@ pub fn prune_payloads<E: EthSpec>(
     db.try_prune_execution_payloads(force)
 }
 
+pub struct PruneStatesConfig {
+    confirm: bool,
+}
+
+fn parse_prune_states_config(cli_args: &ArgMatches) -> PruneStatesConfig {
+    PruneStatesConfig {
+        confirm: cli_args.is_present("confirm"),
+    }
+}
+
+pub fn prune_states<E: EthSpec>(
+    prune_config: PruneStatesConfig,
+    genesis_state: BeaconState<E>,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let db = open_db(client_config, runtime_context, log.clone())?;
+
+    if !prune_config.confirm {
+        warn!(
+            log,
+            "Pruning states is irreversible";
+            "info" => "historic states can only be regenerated by re-syncing",
+        );
+        warn!(
+            log,
+            "Re-run this command with --confirm to delete the states"
+        );
+        return Ok(());
+    }
+
+    let genesis_state_root = genesis_state.canonical_root();
+    db.prune_historic_states(genesis_state_root, &genesis_state)
+        .map_err(|e| format!("Unable to prune states: {:?}", e))
+}
+
+pub fn compact_db<E: EthSpec>(
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let hot_path = client_config.get_db_path();
+    let cold_path = client_config.get_freezer_db_path();
+    let db = open_db(client_config, runtime_context, log.clone())?;
+
+    info!(log, "Compacting hot database"; "path" => ?hot_path);
+    db.hot_db
+        .compact_all()
+        .map_err(|e| format!("Unable to compact hot database: {:?}", e))?;
+    info!(log, "Compacting freezer database"; "path" => ?cold_path);
+    db.cold_db
+        .compact_all()
+        .map_err(|e| format!("Unable to compact freezer database: {:?}", e))?;
+    info!(log, "Database compaction complete");
+
+    Ok(())
+}
+
+pub fn recompress_db<E: EthSpec>(
+    cli_args: &ArgMatches,
+    mut client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    client_config.store.freezer_compression = clap_utils::parse_required(cli_args, "compression")?;
+    client_config.store.compression_level =
+        clap_utils::parse_required(cli_args, "compression-level")?;
+    if client_config.store.compression_level > 9 {
+        return Err("compression-level must be between 0 and 9".into());
+    }
+
+    let db = open_db(client_config, runtime_context, log)?;
+
+    db.recompress_freezer_states()
+        .map(|_| ())
+        .map_err(|e| format!("Unable to recompress freezer states: {:?}", e))
+}
+
+pub struct ConvertBackendConfig {
+    to: DatabaseBackend,
+}
+
+fn parse_convert_backend_config(cli_args: &ArgMatches) -> Result<ConvertBackendConfig, String> {
+    let to = clap_utils::parse_required(cli_args, "to")?;
+
+    Ok(ConvertBackendConfig { to })
+}
+
+pub fn convert_backend<E: EthSpec>(
+    convert_config: ConvertBackendConfig,
+    client_config: ClientConfig,
+    log: Logger,
+) -> Result<(), Error> {
+    let to = convert_config.to;
+    let io_error = |e: std::io::Error| Error::DBError {
+        message: e.to_string(),
+    };
+
+    for db_path in [
+        client_config.get_db_path(),
+        client_config.get_freezer_db_path(),
+    ] {
+        let from = BeaconNodeBackend::<E>::detect(&db_path).ok_or_else(|| Error::DBError {
+            message: format!("No database found at {}", db_path.display()),
+        })?;
+        if from == to {
+            info!(log, "Database already uses backend"; "path" => ?db_path, "backend" => %to);
+            continue;
+        }
+
+        // Write to a temporary directory, so that a failed conversion leaves the original intact.
+        let tmp_path = db_path.with_extension("partial");
+        if tmp_path.exists() {
+            fs::remove_dir_all(&tmp_path).map_err(io_error)?;
+        }
+
+        info!(
+            log,
+            "Converting database";
+            "path" => ?db_path,
+            "from" => %from,
+            "to" => %to,
+        );
+        let source = BeaconNodeBackend::<E>::open(from, &db_path)?;
+        let target = BeaconNodeBackend::<E>::open(to, &tmp_path)?;
+
+        let total = source.copy_into(&target)?;
+        drop(source);
+        drop(target);
+
+        let backup_path = db_path.with_extension(format!("{}.bak", from));
+        fs::rename(&db_path, &backup_path).map_err(io_error)?;
+        fs::rename(&tmp_path, &db_path).map_err(io_error)?;
+        info!(
+            log,
+            "Converted database";
+            "path" => ?db_path,
+            "values" => total,
+            "backup" => ?backup_path,
+        );
+    }
+
+    warn!(
+        log,
+        "Run the beacon node with the new backend";
+        "flag" => format!("--db-backend {}", to),
+        "info" => "the backups may be deleted once the node is running",
+    );
+    Ok(())
+}
+
 /// Run the database manager, returning an error string if the operation did not succeed.
 pub fn run<T: EthSpec>(cli_args: &ArgMatches<'_>, env: Environment<T>) -> Result<(), String> {
     let client_config = parse_client_config(cli_args, &env)?;
     let context = env.core_context();
     let log = context.log().clone();
 
+    // Hold the datadir lock until the command completes, so that the database is never modified
+    // or read mid-write whilst a beacon node is using it.
+    let _datadir_lock = lock_data_dir(&client_config)?;
+
     match cli_args.subcommand() {
         ("version", Some(_)) => display_db_version(client_config, &context, log),
         ("migrate", Some(cli_args)) => {
@@ -304,6 +584,42 @@ pub fn run<T: EthSpec>(cli_args: &ArgMatches<'_>, env: Environment<T>) -> Result
             inspect_db(inspect_config, client_config, &context, log)
         }
         ("prune_payloads", Some(_)) => prune_payloads(client_config, &context, log),
+        ("compact", Some(_)) => return compact_db(client_config, &context, log),
+        ("convert_backend", Some(cli_args)) => {
+            let convert_config = parse_convert_backend_config(cli_args)?;
+            convert_backend::<T>(convert_config, client_config, log)
+        }
+        (inspect_object::BLOCK_CMD, Some(cli_args)) => {
+            return inspect_object::run_inspect_block(cli_args, client_config, &context, log)
+        }
+        (inspect_object::STATE_CMD, Some(cli_args)) => {
+            return inspect_object::run_inspect_state(cli_args, client_config, &context, log)
+        }
+        (snapshot::CMD, Some(cli_args)) => {
+            return snapshot::run(cli_args, client_config, &context, log)
+        }
+        ("recompress", Some(cli_args)) => {
+            return recompress_db(cli_args, client_config, &context, log)
+        }
+        (verify::CMD, Some(cli_args)) => {
+            return verify::run(cli_args, client_config, &context, log)
+        }
+        (era::IMPORT_CMD, Some(cli_args)) => {
+            return era::run_import(cli_args, client_config, &context, log)
+        }
+        (era::EXPORT_CMD, Some(cli_args)) => {
+            return era::run_export(cli_args, client_config, &context, log)
+        }
+        ("prune_states", Some(cli_args)) => {
+            let prune_config = parse_prune_states_config(cli_args);
+            let genesis_state = env
+                .eth2_network_config
+                .as_ref()
+                .ok_or("Missing network config")?
+                .beacon_state::<T>()
+                .map_err(|e| format!("Unable to load genesis state: {}", e))?;
+            return prune_states(prune_config, genesis_state, client_config, &context, log);
+        }
         _ => {
             return Err("Unknown subcommand, for help `lighthouse database_manager --help`".into())
         }
@@ -0,0 +1,289 @@
+//! Replay the finalized blocks in the database through the state transition, timing each phase,
+//! for benchmarking hardware and hunting performance regressions.
+
+use crate::open_db;
+use beacon_chain::{
+    builder::Witness, eth1_chain::CachingEth1Backend, slot_clock::SystemTimeSlotClock,
+    validator_pubkey_cache::ValidatorPubkeyCache,
+};
+use beacon_node::ClientConfig;
+use clap::{App, AppSettings, Arg, ArgMatches};
+use environment::RuntimeContext;
+use slog::{debug, info, Logger};
+use state_processing::{
+    block_signature_verifier::BlockSignatureVerifier, per_block_processing, per_slot_processing,
+    BlockSignatureStrategy, ConsensusContext, VerifyBlockRoot,
+};
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+use store::BeaconNodeBackend;
+use types::{EthSpec, Slot};
+
+pub const CMD: &str = "replay_blocks";
+
+type Types<E> = Witness<
+    SystemTimeSlotClock,
+    CachingEth1Backend<E>,
+    E,
+    BeaconNodeBackend<E>,
+    BeaconNodeBackend<E>,
+>;
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .alias("replay-blocks")
+        .setting(AppSettings::ColoredHelp)
+        .about(
+            "Replay the finalized blocks in the database through the state transition, and \
+             print the time spent in each phase",
+        )
+        .arg(
+            Arg::with_name("start-slot")
+                .long("start-slot")
+                .value_name("SLOT")
+                .help("The slot of the state to start replaying from")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("end-slot")
+                .long("end-slot")
+                .value_name("SLOT")
+                .help("The slot to replay up to, which must be finalized")
+                .takes_value(true)
+                .required(true),
+        )
+}
+
+/// The total time spent in each phase of the state transition.
+#[derive(Default)]
+struct Timings {
+    signature_verification: Duration,
+    slot_processing: Duration,
+    epoch_processing: Duration,
+    block_processing: Duration,
+    tree_hashing: Duration,
+}
+
+impl Timings {
+    fn total(&self) -> Duration {
+        self.signature_verification
+            + self.slot_processing
+            + self.epoch_processing
+            + self.block_processing
+            + self.tree_hashing
+    }
+}
+
+pub fn run<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let start_slot: Slot = clap_utils::parse_required(cli_args, "start-slot")?;
+    let end_slot: Slot = clap_utils::parse_required(cli_args, "end-slot")?;
+    let spec = &runtime_context.eth2_config.spec;
+
+    let db = open_db(client_config, runtime_context, log.clone())?;
+
+    let split = db.get_split_info();
+    if start_slot >= end_slot || end_slot > split.slot {
+        return Err(format!(
+            "The start slot must be before the end slot, which must be at or before the \
+             finalized slot {}",
+            split.slot
+        ));
+    }
+    let split_state = db
+        .get_state(&split.state_root, Some(split.slot))
+        .map_err(|e| format!("Unable to load split state: {:?}", e))?
+        .ok_or("Split state is missing from the database")?;
+    let split_block_root = split_state.get_latest_block_root(split.state_root);
+
+    let (start_state_root, _) = db
+        .forwards_state_roots_iterator_until(
+            start_slot,
+            start_slot,
+            || (split_state.clone(), split.state_root),
+            spec,
+        )
+        .and_then(|mut iter| iter.next().transpose())
+        .map_err(|e| format!("Unable to iterate state roots: {:?}", e))?
+        .ok_or_else(|| format!("No state root is stored for slot {}", start_slot))?;
+    let mut state = db
+        .get_state(&start_state_root, Some(start_slot))
+        .map_err(|e| format!("Unable to load state at slot {}: {:?}", start_slot, e))?
+        .ok_or_else(|| format!("State at slot {} is unavailable", start_slot))?;
+    let start_block_root = state.get_latest_block_root(start_state_root);
+
+    let timer = Instant::now();
+    let mut blocks = vec![];
+    let block_roots = db
+        .forwards_block_roots_iterator_until(
+            start_slot,
+            end_slot,
+            || (split_state.clone(), split_block_root),
+            spec,
+        )
+        .map_err(|e| format!("Unable to iterate block roots: {:?}", e))?;
+    let mut previous_root = start_block_root;
+    for result in block_roots {
+        let (block_root, slot) =
+            result.map_err(|e| format!("Unable to iterate block roots: {:?}", e))?;
+        // Skipped slots repeat the root of the previous block.
+        if block_root == previous_root {
+            continue;
+        }
+        previous_root = block_root;
+        let block = db
+            .get_full_block(&block_root)
+            .map_err(|e| format!("Unable to load block at slot {}: {:?}", slot, e))?
+            .ok_or_else(|| format!("Block at slot {} is missing", slot))?;
+        blocks.push((block_root, block));
+    }
+    info!(
+        log,
+        "Loaded blocks";
+        "blocks" => blocks.len(),
+        "time_ms" => timer.elapsed().as_millis(),
+    );
+
+    let timer = Instant::now();
+    let pubkey_cache = ValidatorPubkeyCache::<Types<E>>::load_from_store(db.clone())
+        .map_err(|e| format!("Unable to load pubkey cache: {:?}", e))?;
+    state
+        .build_all_caches(spec)
+        .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+    info!(
+        log,
+        "Prepared start state";
+        "slot" => start_slot,
+        "time_ms" => timer.elapsed().as_millis(),
+    );
+
+    let mut timings = Timings::default();
+    let mut state_root = start_state_root;
+    for (block_root, block) in &blocks {
+        let mut block_timings = Timings::default();
+
+        while state.slot() < block.slot() {
+            let is_epoch_transition = (state.slot() + 1) % E::slots_per_epoch() == 0;
+            let timer = Instant::now();
+            per_slot_processing(&mut state, Some(state_root), spec)
+                .map_err(|e| format!("Slot processing failed at {}: {:?}", state.slot(), e))?;
+            if is_epoch_transition {
+                block_timings.epoch_processing += timer.elapsed();
+            } else {
+                block_timings.slot_processing += timer.elapsed();
+            }
+
+            if state.slot() < block.slot() {
+                let timer = Instant::now();
+                state_root = state
+                    .update_tree_hash_cache()
+                    .map_err(|e| format!("Unable to hash state: {:?}", e))?;
+                block_timings.tree_hashing += timer.elapsed();
+            }
+        }
+
+        let timer = Instant::now();
+        state
+            .build_all_caches(spec)
+            .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+        block_timings.block_processing += timer.elapsed();
+
+        let mut ctxt = ConsensusContext::new(block.slot())
+            .set_current_block_root(*block_root)
+            .set_proposer_index(block.message().proposer_index());
+
+        let get_pubkey = |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed);
+        let decompressor = |pubkey_bytes| {
+            let validator_index = pubkey_cache.get_index(pubkey_bytes)?;
+            pubkey_cache.get(validator_index).map(Cow::Borrowed)
+        };
+        let timer = Instant::now();
+        BlockSignatureVerifier::verify_entire_block(
+            &state,
+            get_pubkey,
+            decompressor,
+            block,
+            &mut ctxt,
+            spec,
+        )
+        .map_err(|e| format!("Invalid signature in block {:?}: {:?}", block_root, e))?;
+        block_timings.signature_verification += timer.elapsed();
+
+        let timer = Instant::now();
+        per_block_processing(
+            &mut state,
+            block,
+            BlockSignatureStrategy::NoVerification,
+            VerifyBlockRoot::True,
+            &mut ctxt,
+            spec,
+        )
+        .map_err(|e| format!("Block processing failed at {}: {:?}", block.slot(), e))?;
+        block_timings.block_processing += timer.elapsed();
+
+        let timer = Instant::now();
+        state_root = state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Unable to hash state: {:?}", e))?;
+        block_timings.tree_hashing += timer.elapsed();
+        if state_root != block.state_root() {
+            return Err(format!(
+                "State root mismatch at slot {}: block has {:?}, computed {:?}",
+                block.slot(),
+                block.state_root(),
+                state_root
+            ));
+        }
+
+        debug!(
+            log,
+            "Replayed block";
+            "slot" => block.slot(),
+            "signature_verification_ms" => block_timings.signature_verification.as_millis(),
+            "slot_processing_ms" => block_timings.slot_processing.as_millis(),
+            "epoch_processing_ms" => block_timings.epoch_processing.as_millis(),
+            "block_processing_ms" => block_timings.block_processing.as_millis(),
+            "tree_hashing_ms" => block_timings.tree_hashing.as_millis(),
+        );
+        timings.signature_verification += block_timings.signature_verification;
+        timings.slot_processing += block_timings.slot_processing;
+        timings.epoch_processing += block_timings.epoch_processing;
+        timings.block_processing += block_timings.block_processing;
+        timings.tree_hashing += block_timings.tree_hashing;
+    }
+
+    let num_blocks = blocks.len().max(1) as u32;
+    let total = timings.total();
+    println!(
+        "Replayed {} blocks from slot {} to {}",
+        blocks.len(),
+        start_slot,
+        end_slot
+    );
+    println!(
+        "{:<24} {:>12} {:>12} {:>8}",
+        "phase", "total", "per block", "share"
+    );
+    for (phase, time) in [
+        ("signature verification", timings.signature_verification),
+        ("slot processing", timings.slot_processing),
+        ("epoch processing", timings.epoch_processing),
+        ("block processing", timings.block_processing),
+        ("tree hashing", timings.tree_hashing),
+        ("total", total),
+    ] {
+        println!(
+            "{:<24} {:>12} {:>12} {:>7.1}%",
+            phase,
+            format!("{:.2?}", time),
+            format!("{:.2?}", time / num_blocks),
+            100.0 * time.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE),
+        );
+    }
+    Ok(())
+}
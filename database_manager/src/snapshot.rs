@@ -0,0 +1,554 @@
+//! Export and import snapshots of the hot and freezer databases, so that new nodes can be
+//! provisioned from an existing one instead of syncing.
+//!
+//! A snapshot is a directory containing copies of the `hot_db` and `freezer_db` directories, a
+//! `SHA256SUMS` file in the format of `sha256sum` and a `snapshot.json` file recording the number
+//! and total size of the files and the checksum of `SHA256SUMS`. All of these are verified on
+//! import, and files which aren't listed in `SHA256SUMS` are rejected.
+
+use beacon_node::ClientConfig;
+use clap::{App, AppSettings, Arg, ArgMatches};
+use environment::RuntimeContext;
+use eth2_hashing::{hash, Context, Sha256Context};
+use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use store::{BeaconNodeBackend, HotColdDB};
+use types::EthSpec;
+
+pub const CMD: &str = "snapshot";
+pub const EXPORT_CMD: &str = "export";
+pub const IMPORT_CMD: &str = "import";
+
+const HOT_DB_DIR: &str = "hot_db";
+const FREEZER_DB_DIR: &str = "freezer_db";
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
+const METADATA_FILE: &str = "snapshot.json";
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+/// The contents of a snapshot, used to detect a truncated or modified `SHA256SUMS` file.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SnapshotMetadata {
+    /// The number of files in the databases.
+    files: usize,
+    /// The total size of the files in bytes.
+    size: u64,
+    /// The hex-encoded SHA256 checksum of the `SHA256SUMS` file.
+    checksums_sha256: String,
+}
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .setting(AppSettings::ColoredHelp)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .about("Export or import a snapshot of the database, while the beacon node is stopped")
+        .subcommand(
+            App::new(EXPORT_CMD)
+                .setting(AppSettings::ColoredHelp)
+                .about("Copy the hot and freezer databases into a new snapshot directory")
+                .arg(
+                    Arg::with_name("path")
+                        .value_name("PATH")
+                        .help("The directory to create the snapshot in, which must be empty")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            App::new(IMPORT_CMD)
+                .setting(AppSettings::ColoredHelp)
+                .about(
+                    "Verify a snapshot and copy it into the data directory, which must not \
+                     contain a database",
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .value_name("PATH")
+                        .help("The snapshot directory created by `snapshot export`")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+}
+
+pub fn run<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    match cli_args.subcommand() {
+        (EXPORT_CMD, Some(cli_args)) => {
+            let path = clap_utils::parse_required(cli_args, "path")?;
+            export(path, client_config, runtime_context, log)
+        }
+        (IMPORT_CMD, Some(cli_args)) => {
+            let path = clap_utils::parse_required(cli_args, "path")?;
+            import(path, client_config, runtime_context, log)
+        }
+        _ => Err(format!(
+            "Unknown subcommand, for help `lighthouse database_manager {} --help`",
+            CMD
+        )),
+    }
+}
+
+/// Open the database to check that it's valid and not in use by a beacon node, logging its
+/// split point.
+fn check_db<E: EthSpec>(
+    client_config: &ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: &Logger,
+) -> Result<(), String> {
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+        &client_config.get_db_path(),
+        &client_config.get_freezer_db_path(),
+        |_, _, _| Ok(()),
+        client_config.store.clone(),
+        runtime_context.eth2_config.spec.clone(),
+        log.clone(),
+    )
+    .map_err(|e| format!("Unable to open database: {:?}", e))?;
+
+    let split = db.get_split_info();
+    info!(
+        log,
+        "Database is valid";
+        "split_slot" => split.slot,
+        "split_state" => ?split.state_root,
+    );
+    Ok(())
+}
+
+pub fn export<E: EthSpec>(
+    path: PathBuf,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    if !is_empty_dir(&path)? {
+        return Err(format!("{} is not empty", path.display()));
+    }
+    // The database is closed again before copying, so that no compactions run during the copy.
+    check_db(&client_config, runtime_context, &log)?;
+
+    let metadata = write_snapshot(&path, &db_dirs(&client_config), &log)?;
+    info!(
+        log,
+        "Exported database snapshot";
+        "path" => ?path,
+        "files" => metadata.files,
+        "size" => metadata.size,
+    );
+    Ok(())
+}
+
+pub fn import<E: EthSpec>(
+    path: PathBuf,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let targets = db_dirs(&client_config);
+    for (db_path, _) in &targets {
+        if !is_empty_dir(db_path)? {
+            return Err(format!(
+                "A database already exists at {}, remove it before importing",
+                db_path.display()
+            ));
+        }
+    }
+
+    let metadata = read_snapshot(&path, &targets, &log)?;
+
+    check_db(&client_config, runtime_context, &log)?;
+    info!(
+        log,
+        "Imported database snapshot";
+        "files" => metadata.files,
+        "size" => metadata.size,
+    );
+    Ok(())
+}
+
+/// Returns the path of each database, along with the directory it is stored in in a snapshot.
+fn db_dirs(client_config: &ClientConfig) -> [(PathBuf, &'static str); 2] {
+    [
+        (client_config.get_db_path(), HOT_DB_DIR),
+        (client_config.get_freezer_db_path(), FREEZER_DB_DIR),
+    ]
+}
+
+/// Copies the databases in `sources` into the snapshot at `path`, writing the checksums and
+/// metadata of their files.
+fn write_snapshot(
+    path: &Path,
+    sources: &[(PathBuf, &str)],
+    log: &Logger,
+) -> Result<SnapshotMetadata, String> {
+    let mut files = vec![];
+    for (db_path, dir) in sources {
+        info!(log, "Exporting database"; "from" => ?db_path);
+        copy_dir(db_path, &path.join(dir), Path::new(dir), &mut files)?;
+    }
+
+    let mut sums = String::new();
+    for (file, checksum, _) in &files {
+        let _ = writeln!(sums, "{}  {}", checksum, file.display());
+    }
+    fs::write(path.join(CHECKSUMS_FILE), &sums)
+        .map_err(|e| format!("Unable to write {}: {}", CHECKSUMS_FILE, e))?;
+
+    let metadata = SnapshotMetadata {
+        files: files.len(),
+        size: files.iter().map(|(_, _, size)| size).sum(),
+        checksums_sha256: to_hex(&hash(sums.as_bytes())),
+    };
+    let json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Unable to encode {}: {}", METADATA_FILE, e))?;
+    fs::write(path.join(METADATA_FILE), json)
+        .map_err(|e| format!("Unable to write {}: {}", METADATA_FILE, e))?;
+    Ok(metadata)
+}
+
+/// Verifies the snapshot at `path` and copies it into the databases in `targets`.
+///
+/// Each database is copied into a temporary directory first, so that a failed import never leaves
+/// a partial database.
+fn read_snapshot(
+    path: &Path,
+    targets: &[(PathBuf, &str)],
+    log: &Logger,
+) -> Result<SnapshotMetadata, String> {
+    let metadata: SnapshotMetadata = fs::read_to_string(path.join(METADATA_FILE))
+        .map_err(|e| format!("Unable to read {}: {}", METADATA_FILE, e))
+        .and_then(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| format!("Unable to decode {}: {}", METADATA_FILE, e))
+        })?;
+
+    let sums = fs::read_to_string(path.join(CHECKSUMS_FILE))
+        .map_err(|e| format!("Unable to read {}: {}", CHECKSUMS_FILE, e))?;
+    if to_hex(&hash(sums.as_bytes())) != metadata.checksums_sha256 {
+        return Err(format!(
+            "{} doesn't match the checksum in {}",
+            CHECKSUMS_FILE, METADATA_FILE
+        ));
+    }
+
+    let mut checksums = HashMap::new();
+    for line in sums.lines() {
+        let (file, checksum) = line
+            .split_once("  ")
+            .map(|(checksum, file)| (PathBuf::from(file), checksum.to_string()))
+            // Only allow files within the databases of the snapshot.
+            .filter(|(file, _)| {
+                file.components()
+                    .all(|component| matches!(component, Component::Normal(_)))
+                    && targets.iter().any(|(_, dir)| file.starts_with(dir))
+            })
+            .ok_or_else(|| format!("Invalid line in {}: {}", CHECKSUMS_FILE, line))?;
+        if checksums.insert(file, checksum).is_some() {
+            return Err(format!("Duplicate line in {}: {}", CHECKSUMS_FILE, line));
+        }
+    }
+    if checksums.len() != metadata.files {
+        return Err(format!(
+            "{} lists {} files but the snapshot has {}",
+            CHECKSUMS_FILE,
+            checksums.len(),
+            metadata.files
+        ));
+    }
+
+    let mut snapshot_files = vec![];
+    list_files(path, Path::new(""), &mut snapshot_files)?;
+    for file in snapshot_files {
+        if file != Path::new(CHECKSUMS_FILE)
+            && file != Path::new(METADATA_FILE)
+            && !checksums.contains_key(&file)
+        {
+            return Err(format!(
+                "{} is not listed in {}",
+                file.display(),
+                CHECKSUMS_FILE
+            ));
+        }
+    }
+
+    let mut size = 0;
+    for (db_path, dir) in targets {
+        let tmp = db_path.with_extension("partial");
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp)
+                .map_err(|e| format!("Unable to remove {}: {}", tmp.display(), e))?;
+        }
+        fs::create_dir_all(&tmp)
+            .map_err(|e| format!("Unable to create {}: {}", tmp.display(), e))?;
+
+        info!(log, "Importing database"; "to" => ?db_path);
+        for (file, expected) in &checksums {
+            let relative = match file.strip_prefix(dir) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+            let (checksum, file_size) = copy_file(&path.join(file), &tmp.join(relative))?;
+            if checksum != *expected {
+                return Err(format!("Checksum mismatch for {}", file.display()));
+            }
+            size += file_size;
+        }
+    }
+    if size != metadata.size {
+        return Err(format!(
+            "The snapshot has {} bytes but {} were copied",
+            metadata.size, size
+        ));
+    }
+
+    for (db_path, _) in targets {
+        if db_path.exists() {
+            fs::remove_dir(db_path)
+                .map_err(|e| format!("Unable to remove {}: {}", db_path.display(), e))?;
+        }
+        let tmp = db_path.with_extension("partial");
+        fs::rename(&tmp, db_path)
+            .map_err(|e| format!("Unable to rename {}: {}", tmp.display(), e))?;
+    }
+    Ok(metadata)
+}
+
+/// Returns `true` if `path` doesn't exist or is an empty directory.
+fn is_empty_dir(path: &Path) -> Result<bool, String> {
+    if !path.exists() {
+        return Ok(true);
+    }
+    fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .map_err(|e| format!("Unable to read {}: {}", path.display(), e))
+}
+
+/// Recursively copies the database files in `from` to `to`, recording the checksum and size of
+/// each one by its path relative to the snapshot.
+fn copy_dir(
+    from: &Path,
+    to: &Path,
+    relative: &Path,
+    files: &mut Vec<(PathBuf, String, u64)>,
+) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Unable to create {}: {}", to.display(), e))?;
+    let mut entries = fs::read_dir(from)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Unable to read {}: {}", from.display(), e))?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name();
+        // The lock and log files are recreated when the database is opened.
+        if name == "LOCK" || name == "mdbx.lck" || name.to_string_lossy().starts_with("LOG") {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &to.join(&name), &relative.join(&name), files)?;
+        } else {
+            let (checksum, size) = copy_file(&path, &to.join(&name))?;
+            files.push((relative.join(&name), checksum, size));
+        }
+    }
+    Ok(())
+}
+
+/// Recursively lists the files in `dir` by their path relative to the snapshot.
+fn list_files(dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Unable to read {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.path();
+        let relative = relative.join(entry.file_name());
+        if path.is_dir() {
+            list_files(&path, &relative, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Copies the file `from` to `to`, returning the hex-encoded SHA256 checksum of its contents and
+/// its size.
+fn copy_file(from: &Path, to: &Path) -> Result<(String, u64), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Unable to create {}: {}", parent.display(), e))?;
+    }
+    let mut source =
+        File::open(from).map_err(|e| format!("Unable to open {}: {}", from.display(), e))?;
+    let mut target =
+        File::create(to).map_err(|e| format!("Unable to create {}: {}", to.display(), e))?;
+
+    let mut context = Context::new();
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut size = 0;
+    loop {
+        let len = source
+            .read(&mut buffer)
+            .map_err(|e| format!("Unable to read {}: {}", from.display(), e))?;
+        if len == 0 {
+            break;
+        }
+        context.update(&buffer[..len]);
+        size += len as u64;
+        target
+            .write_all(&buffer[..len])
+            .map_err(|e| format!("Unable to write {}: {}", to.display(), e))?;
+    }
+    target
+        .sync_all()
+        .map_err(|e| format!("Unable to write {}: {}", to.display(), e))?;
+
+    Ok((to_hex(&context.finalize()), size))
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    let mut hex = String::new();
+    for byte in digest {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{tempdir, TempDir};
+
+    const FILES: [(&str, &str); 3] = [
+        ("hot_db/000001.sst", "hot"),
+        ("hot_db/archive/MANIFEST", "manifest"),
+        ("freezer_db/000002.sst", "freezer"),
+    ];
+
+    fn logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
+    fn node_db_dirs(root: &Path) -> [(PathBuf, &'static str); 2] {
+        [
+            (root.join("beacon/chain_db"), HOT_DB_DIR),
+            (root.join("beacon/freezer_db"), FREEZER_DB_DIR),
+        ]
+    }
+
+    /// Creates a node with databases containing `FILES` and exports a snapshot of them.
+    fn export_snapshot() -> (TempDir, PathBuf) {
+        let dir = tempdir().unwrap();
+        let node = dir.path().join("node");
+        let dbs = node_db_dirs(&node);
+        for (file, contents) in FILES {
+            let (db_dir, relative) = file.split_once('/').unwrap();
+            let (db_path, _) = dbs.iter().find(|(_, dir)| *dir == db_dir).unwrap();
+            let path = db_path.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        // The lock file isn't exported.
+        fs::write(dbs[0].0.join("LOCK"), "").unwrap();
+
+        let snapshot = dir.path().join("snapshot");
+        let metadata = write_snapshot(&snapshot, &dbs, &logger()).unwrap();
+        assert_eq!(metadata.files, FILES.len());
+        assert_eq!(
+            metadata.size,
+            FILES
+                .iter()
+                .map(|(_, contents)| contents.len() as u64)
+                .sum()
+        );
+        (dir, snapshot)
+    }
+
+    fn import_snapshot(dir: &TempDir, snapshot: &Path) -> Result<SnapshotMetadata, String> {
+        read_snapshot(
+            snapshot,
+            &node_db_dirs(&dir.path().join("imported")),
+            &logger(),
+        )
+    }
+
+    /// Checks that the import failed without creating a database.
+    fn assert_import_fails(dir: &TempDir, snapshot: &Path) {
+        assert!(import_snapshot(dir, snapshot).is_err());
+        for (db_path, _) in node_db_dirs(&dir.path().join("imported")) {
+            assert!(!db_path.exists());
+        }
+    }
+
+    #[test]
+    fn export_and_import() {
+        let (dir, snapshot) = export_snapshot();
+        let metadata = import_snapshot(&dir, &snapshot).unwrap();
+        assert_eq!(metadata.files, FILES.len());
+
+        let dbs = node_db_dirs(&dir.path().join("imported"));
+        for (file, contents) in FILES {
+            let (db_dir, relative) = file.split_once('/').unwrap();
+            let (db_path, _) = dbs.iter().find(|(_, dir)| *dir == db_dir).unwrap();
+            assert_eq!(
+                fs::read_to_string(db_path.join(relative)).unwrap(),
+                contents
+            );
+        }
+        assert!(!dbs[0].0.join("LOCK").exists());
+    }
+
+    #[test]
+    fn modified_file_is_rejected() {
+        let (dir, snapshot) = export_snapshot();
+        fs::write(snapshot.join(FILES[0].0), "modified").unwrap();
+        assert_import_fails(&dir, &snapshot);
+    }
+
+    #[test]
+    fn unlisted_file_is_rejected() {
+        let (dir, snapshot) = export_snapshot();
+        fs::write(snapshot.join("freezer_db/000003.sst"), "unlisted").unwrap();
+        assert_import_fails(&dir, &snapshot);
+    }
+
+    #[test]
+    fn missing_file_is_rejected() {
+        let (dir, snapshot) = export_snapshot();
+        fs::remove_file(snapshot.join(FILES[2].0)).unwrap();
+        assert_import_fails(&dir, &snapshot);
+    }
+
+    #[test]
+    fn truncated_checksums_are_rejected() {
+        let (dir, snapshot) = export_snapshot();
+        let sums = fs::read_to_string(snapshot.join(CHECKSUMS_FILE)).unwrap();
+        let truncated = sums.lines().take(FILES.len() - 1).collect::<Vec<_>>();
+        fs::write(snapshot.join(CHECKSUMS_FILE), truncated.join("\n")).unwrap();
+        fs::remove_file(snapshot.join(FILES[2].0)).unwrap();
+        assert_import_fails(&dir, &snapshot);
+    }
+
+    #[test]
+    fn modified_metadata_is_rejected() {
+        let (dir, snapshot) = export_snapshot();
+        let json = fs::read_to_string(snapshot.join(METADATA_FILE)).unwrap();
+        let mut metadata: SnapshotMetadata = serde_json::from_str(&json).unwrap();
+        metadata.size += 1;
+        fs::write(
+            snapshot.join(METADATA_FILE),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+        assert_import_fails(&dir, &snapshot);
+    }
+}
@@ -0,0 +1,70 @@
+//! Verify the integrity of the database after an unclean shutdown, optionally deleting the hot
+//! states which can never be loaded.
+
+use crate::open_db;
+use beacon_node::ClientConfig;
+use clap::{App, AppSettings, Arg, ArgMatches};
+use environment::RuntimeContext;
+use slog::{info, Logger};
+use types::EthSpec;
+
+pub const CMD: &str = "verify";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .setting(AppSettings::ColoredHelp)
+        .about(
+            "Check the finalized chain of blocks, the freezer restore points and the hot states \
+             for missing or corrupt entries",
+        )
+        .arg(
+            Arg::with_name("repair")
+                .long("repair")
+                .help(
+                    "Delete hot states which can never be loaded, or which were left behind by an \
+                     interrupted migration to the freezer database",
+                )
+                .takes_value(false),
+        )
+}
+
+pub fn run<E: EthSpec>(
+    cli_args: &ArgMatches,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), String> {
+    let repair = cli_args.is_present("repair");
+    let db = open_db(client_config, runtime_context, log.clone())?;
+
+    let report = db
+        .verify_integrity(repair)
+        .map_err(|e| format!("Unable to verify database: {:?}", e))?;
+
+    info!(
+        log,
+        "Database verification complete";
+        "split_state_missing" => report.split_state_missing,
+        "blocks_checked" => report.blocks_checked,
+        "broken_blocks" => report.broken_blocks.len(),
+        "restore_points_checked" => report.restore_points_checked,
+        "corrupt_restore_points" => report.corrupt_restore_points.len(),
+        "dangling_states" => report.dangling_states.len(),
+        "repaired" => report.repaired,
+    );
+
+    if report.is_ok() {
+        Ok(())
+    } else if report.split_state_missing
+        || !report.broken_blocks.is_empty()
+        || !report.corrupt_restore_points.is_empty()
+    {
+        Err(
+            "The database is corrupt and can't be repaired, resync the beacon node \
+             or import a snapshot"
+                .into(),
+        )
+    } else {
+        Err("The database has dangling states, run again with --repair to delete them".into())
+    }
+}
@@ -801,10 +801,13 @@ fn run<T: EthSpec>(
             logfile_debug_level: String::from("trace"),
             log_format: None,
             logfile_format: None,
+            log_target: None,
+            log_filter: None,
             log_color: false,
             disable_log_timestamp: false,
             max_log_size: 0,
             max_log_number: 0,
+            max_log_age: 0,
             compression: false,
             is_restricted: true,
         })
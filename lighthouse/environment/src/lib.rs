@@ -7,20 +7,32 @@
 //! `Context` which can be handed to any service that wishes to start async tasks or perform
 //! logging.
 
+mod log_rotation;
+mod startup_error;
+pub mod systemd;
+
 use eth2_config::Eth2Config;
 use eth2_network_config::Eth2NetworkConfig;
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::{future, StreamExt};
 
 use serde_derive::{Deserialize, Serialize};
-use slog::{error, info, o, warn, Drain, Duplicate, Level, Logger};
-use sloggers::{file::FileLoggerBuilder, types::Format, types::Severity, Build};
+use slog::{
+    crit, debug, error, info, o, warn, Drain, Duplicate, Level, Logger, Never, OwnedKVList, Record,
+};
+use sloggers::{
+    file::FileLoggerBuilder, types::Format, types::Severity, Build, BuildWithCustomFormat,
+};
+use std::cmp::Reverse;
 use std::fs::create_dir_all;
 use std::io::{Result as IOResult, Write};
-use std::path::PathBuf;
-use std::sync::Arc;
-use task_executor::{ShutdownReason, TaskExecutor};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use task_executor::{running_tasks, ShutdownReason, TaskExecutor};
 use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+use tokio::sync::watch;
 use types::{EthSpec, GnosisEthSpec, MainnetEthSpec, MinimalEthSpec};
 
 #[cfg(target_family = "unix")]
@@ -33,17 +45,27 @@ use {
 #[cfg(not(target_family = "unix"))]
 use {futures::channel::oneshot, std::cell::RefCell};
 
+pub use startup_error::StartupError;
 pub use task_executor::test_utils::null_logger;
+pub use task_executor::FailureKind;
 
 const LOG_CHANNEL_SIZE: usize = 2048;
-/// The maximum time in seconds the client will wait for all internal tasks to shutdown.
+/// The default maximum time in seconds the client will wait for all internal tasks to shutdown.
 const MAXIMUM_SHUTDOWN_TIME: u64 = 15;
+/// The time allowed after the shutdown timeout, once the runtime has been shut down, for the
+/// process to exit before the shutdown watchdog forces it to.
+const SHUTDOWN_WATCHDOG_GRACE: Duration = Duration::from_secs(5);
+/// The time allowed for the log drains to write out the final records before a forced exit.
+const FORCED_EXIT_LOG_FLUSH_TIME: Duration = Duration::from_millis(500);
 
 /// Configuration for logging.
 /// Background file logging is disabled if one of:
 /// - `path` == None,
 /// - `max_log_size` == 0,
 /// - `max_log_number` == 0,
+///
+/// The log file is rotated once it exceeds `max_log_size` bytes, or once it is older than
+/// `max_log_age` seconds (if non-zero).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggerConfig {
     pub path: Option<PathBuf>,
@@ -51,10 +73,16 @@ pub struct LoggerConfig {
     pub logfile_debug_level: String,
     pub log_format: Option<String>,
     pub logfile_format: Option<String>,
+    #[serde(default)]
+    pub log_target: Option<String>,
+    #[serde(default)]
+    pub log_filter: Option<String>,
     pub log_color: bool,
     pub disable_log_timestamp: bool,
     pub max_log_size: u64,
     pub max_log_number: usize,
+    #[serde(default)]
+    pub max_log_age: u64,
     pub compression: bool,
     pub is_restricted: bool,
 }
@@ -66,23 +94,222 @@ impl Default for LoggerConfig {
             logfile_debug_level: String::from("debug"),
             log_format: None,
             logfile_format: None,
+            log_target: None,
+            log_filter: None,
             log_color: false,
             disable_log_timestamp: false,
             max_log_size: 200,
             max_log_number: 5,
+            max_log_age: 0,
             compression: false,
             is_restricted: true,
         }
     }
 }
 
+/// The logger section of a config file.
+///
+/// The logger is initialized before the beacon node or validator client config is loaded, so
+/// this section is read separately.
+#[derive(Default, Serialize, Deserialize)]
+pub struct LoggerSection {
+    pub logger_config: LoggerConfig,
+}
+
+/// Receives a notification each time the runtime configuration should be reloaded (i.e., when
+/// a SIGHUP is received).
+pub type ReloadReceiver = watch::Receiver<()>;
+
+/// Parses a `--debug-level` style string.
+fn parse_level(level: &str) -> Result<Level, String> {
+    match level {
+        "info" => Ok(Level::Info),
+        "debug" => Ok(Level::Debug),
+        "trace" => Ok(Level::Trace),
+        "warn" => Ok(Level::Warning),
+        "error" => Ok(Level::Error),
+        "crit" => Ok(Level::Critical),
+        unknown => Err(format!("Unknown debug-level: {}", unknown)),
+    }
+}
+
+/// Parses a `--log-filter` string, e.g. `network=debug,beacon_chain=trace`, into a list of module
+/// paths and the level to use for records from those modules.
+///
+/// The list is sorted so that the most specific modules come first.
+pub fn parse_log_filter(filter: &str) -> Result<Vec<(String, Level)>, String> {
+    let mut module_levels = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            let (module, level) = directive
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid log filter directive: {}", directive))?;
+            let module = module.trim();
+            if module.is_empty() {
+                return Err(format!("Invalid log filter directive: {}", directive));
+            }
+            Ok((module.to_string(), parse_level(level.trim())?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    module_levels.sort_by_key(|(module, _)| Reverse(module.len()));
+    Ok(module_levels)
+}
+
+/// A drain which discards records below a level that can be changed at runtime.
+///
+/// Records from the modules in `module_levels` (and their submodules) are filtered using the
+/// level of the most specific matching module instead.
+struct ReloadableLevelFilter<D> {
+    drain: D,
+    level: Arc<AtomicUsize>,
+    module_levels: Vec<(String, Level)>,
+}
+
+impl<D> ReloadableLevelFilter<D> {
+    fn level_for(&self, module: &str) -> usize {
+        self.module_levels
+            .iter()
+            .find(|(prefix, _)| {
+                module
+                    .strip_prefix(prefix.as_str())
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map(|(_, level)| level.as_usize())
+            .unwrap_or_else(|| self.level.load(Ordering::Relaxed))
+    }
+}
+
+impl<D: Drain> Drain for ReloadableLevelFilter<D> {
+    type Ok = Option<D::Ok>;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        if record.level().as_usize() <= self.level_for(record.module()) {
+            self.drain.log(record, values).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A drain which forwards records to a logger that can be replaced at runtime.
+struct ReloadableDrain {
+    logger: Arc<RwLock<Logger>>,
+}
+
+impl Drain for ReloadableDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        match self.logger.read() {
+            Ok(logger) => Drain::log(&*logger, record, values),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// A handle which allows the logger built by `EnvironmentBuilder::initialize_logger` to be
+/// reconfigured without restarting the process.
+#[derive(Clone)]
+pub struct LoggerHandle {
+    config: LoggerConfig,
+    stdout_level: Arc<AtomicUsize>,
+    file_logger: Option<Arc<RwLock<Logger>>>,
+}
+
+impl LoggerHandle {
+    /// Sets the verbosity level used when emitting logs to the terminal.
+    pub fn set_debug_level(&self, level: &str) -> Result<(), String> {
+        self.stdout_level
+            .store(parse_level(level)?.as_usize(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Closes the log file and opens it again at the same path.
+    ///
+    /// This allows tools like `logrotate` to move the log file out of the way before sending a
+    /// SIGHUP. Does nothing if file logging is disabled.
+    pub fn reopen_logfile(&self) -> Result<(), String> {
+        if let (Some(file_logger), Some(path)) = (&self.file_logger, &self.config.path) {
+            let new_logger = build_file_logger(&self.config, path)?;
+            *file_logger
+                .write()
+                .map_err(|_| "Log file lock poisoned".to_string())? = new_logger;
+        }
+        Ok(())
+    }
+
+    /// Moves the log file to `{path}.1` and opens a new one, shifting older log files along in
+    /// the same way as the size-based rotation of the file logger. Log files beyond
+    /// `max_log_number` are deleted and, if compression is enabled, the rotated file is gzipped
+    /// to `{path}.1.gz`.
+    ///
+    /// Does nothing if file logging is disabled.
+    pub fn rotate_logfile(&self) -> Result<(), String> {
+        let path = match (&self.file_logger, &self.config.path) {
+            (Some(_), Some(path)) => path,
+            _ => return Ok(()),
+        };
+        let plain_path = log_rotation::shift_log_files(
+            path,
+            self.config.max_log_number,
+            self.config.compression,
+        )
+        .map_err(|e| format!("Unable to rotate {:?}: {}", path, e))?;
+        self.reopen_logfile()?;
+
+        if self.config.compression {
+            log_rotation::compress(&plain_path)
+                .map_err(|e| format!("Unable to compress {:?}: {}", plain_path, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a logger which writes to the file at `path`.
+fn build_file_logger(config: &LoggerConfig, path: &Path) -> Result<Logger, String> {
+    let logfile_level = match config.logfile_debug_level.as_str() {
+        "info" => Severity::Info,
+        "debug" => Severity::Debug,
+        "trace" => Severity::Trace,
+        "warn" => Severity::Warning,
+        "error" => Severity::Error,
+        "crit" => Severity::Critical,
+        unknown => return Err(format!("Unknown loglevel-debug-level: {}", unknown)),
+    };
+
+    let mut builder = FileLoggerBuilder::new(path);
+    builder
+        .level(logfile_level)
+        .channel_size(LOG_CHANNEL_SIZE)
+        .rotate_size(config.max_log_size)
+        .rotate_keep(config.max_log_number)
+        .rotate_compress(config.compression)
+        .restrict_permissions(config.is_restricted);
+
+    match config.logfile_format.as_deref() {
+        Some("JSON") => builder.format(Format::Json).build(),
+        // The file logger has no logfmt format, but can rotate a file written by any decorator.
+        Some("LOGFMT") => builder.build_with_custom_format(|decorator| {
+            Ok(logging::LogfmtDrain::new(decorator).ignore_res())
+        }),
+        _ => builder.build(),
+    }
+    .map_err(|e| format!("Unable to build file logger: {}", e))
+}
+
 /// Builds an `Environment`.
 pub struct EnvironmentBuilder<E: EthSpec> {
     runtime: Option<Arc<Runtime>>,
     log: Option<Logger>,
+    logger_handle: Option<LoggerHandle>,
     eth_spec_instance: E,
     eth2_config: Eth2Config,
     eth2_network_config: Option<Eth2NetworkConfig>,
+    shutdown_timeout: Duration,
 }
 
 impl EnvironmentBuilder<MinimalEthSpec> {
@@ -91,9 +318,11 @@ impl EnvironmentBuilder<MinimalEthSpec> {
         Self {
             runtime: None,
             log: None,
+            logger_handle: None,
             eth_spec_instance: MinimalEthSpec,
             eth2_config: Eth2Config::minimal(),
             eth2_network_config: None,
+            shutdown_timeout: Duration::from_secs(MAXIMUM_SHUTDOWN_TIME),
         }
     }
 }
@@ -104,9 +333,11 @@ impl EnvironmentBuilder<MainnetEthSpec> {
         Self {
             runtime: None,
             log: None,
+            logger_handle: None,
             eth_spec_instance: MainnetEthSpec,
             eth2_config: Eth2Config::mainnet(),
             eth2_network_config: None,
+            shutdown_timeout: Duration::from_secs(MAXIMUM_SHUTDOWN_TIME),
         }
     }
 }
@@ -117,9 +348,11 @@ impl EnvironmentBuilder<GnosisEthSpec> {
         Self {
             runtime: None,
             log: None,
+            logger_handle: None,
             eth_spec_instance: GnosisEthSpec,
             eth2_config: Eth2Config::gnosis(),
             eth2_network_config: None,
+            shutdown_timeout: Duration::from_secs(MAXIMUM_SHUTDOWN_TIME),
         }
     }
 }
@@ -148,15 +381,9 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
         Ok(())
     }
 
-    /// Initializes the logger using the specified configuration.
-    /// The logger is "async" because it has a dedicated thread that accepts logs and then
-    /// asynchronously flushes them to stdout/files/etc. This means the thread that raised the log
-    /// does not have to wait for the logs to be flushed.
-    /// The logger can be duplicated and more detailed logs can be output to `logfile`.
-    /// Note that background file logging will spawn a new thread.
-    pub fn initialize_logger(mut self, config: LoggerConfig) -> Result<Self, String> {
-        // Setting up the initial logger format and build it.
-        let stdout_drain = if let Some(ref format) = config.log_format {
+    /// Builds the drain which emits logs to the terminal, in the format given by `log_format`.
+    fn stdout_drain(config: &LoggerConfig) -> Result<slog_async::Async, String> {
+        let drain = if let Some(ref format) = config.log_format {
             match format.to_uppercase().as_str() {
                 "JSON" => {
                     let stdout_drain = slog_json::Json::default(std::io::stdout()).fuse();
@@ -164,6 +391,36 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
                         .chan_size(LOG_CHANNEL_SIZE)
                         .build()
                 }
+                "LOGFMT" => {
+                    let stdout_drain = logging::LogfmtDrain::new(slog_term::PlainDecorator::new(
+                        std::io::stdout(),
+                    ));
+                    let stdout_drain = if config.disable_log_timestamp {
+                        stdout_drain.without_timestamps()
+                    } else {
+                        stdout_drain
+                    };
+                    slog_async::Async::new(stdout_drain.ignore_res())
+                        .chan_size(LOG_CHANNEL_SIZE)
+                        .build()
+                }
+                "PLAIN" => {
+                    let stdout_decorator = logging::AlignedTermDecorator::new(
+                        slog_term::PlainDecorator::new(std::io::stdout()),
+                        logging::MAX_MESSAGE_WIDTH,
+                    );
+                    let stdout_drain = slog_term::FullFormat::new(stdout_decorator);
+                    let stdout_drain = if config.disable_log_timestamp {
+                        stdout_drain.use_custom_timestamp(Self::log_nothing)
+                    } else {
+                        stdout_drain
+                    }
+                    .build()
+                    .fuse();
+                    slog_async::Async::new(stdout_drain)
+                        .chan_size(LOG_CHANNEL_SIZE)
+                        .build()
+                }
                 _ => return Err("Logging format provided is not supported".to_string()),
             }
         } else {
@@ -189,29 +446,72 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
                 .build()
         };
 
-        let stdout_drain = match config.debug_level.as_str() {
-            "info" => stdout_drain.filter_level(Level::Info),
-            "debug" => stdout_drain.filter_level(Level::Debug),
-            "trace" => stdout_drain.filter_level(Level::Trace),
-            "warn" => stdout_drain.filter_level(Level::Warning),
-            "error" => stdout_drain.filter_level(Level::Error),
-            "crit" => stdout_drain.filter_level(Level::Critical),
-            unknown => return Err(format!("Unknown debug-level: {}", unknown)),
+        Ok(drain)
+    }
+
+    /// Initializes the logger using the specified configuration.
+    /// The logger is "async" because it has a dedicated thread that accepts logs and then
+    /// asynchronously flushes them to stdout/files/etc. This means the thread that raised the log
+    /// does not have to wait for the logs to be flushed.
+    /// The logger can be duplicated and more detailed logs can be output to `logfile`.
+    /// Note that background file logging will spawn a new thread.
+    pub fn initialize_logger(mut self, config: LoggerConfig) -> Result<Self, String> {
+        // Setting up the initial logger format and build it.
+        let stdout_drain = match config.log_target.as_deref() {
+            None | Some("stdout") => Self::stdout_drain(&config)?,
+            #[cfg(target_family = "unix")]
+            Some("journald") => {
+                let drain = logging::JournaldDrain::new()
+                    .map_err(|e| format!("Unable to log to journald: {}", e))?;
+                slog_async::Async::new(drain.ignore_res())
+                    .chan_size(LOG_CHANNEL_SIZE)
+                    .build()
+            }
+            #[cfg(target_family = "unix")]
+            Some("syslog") => {
+                let drain = logging::SyslogDrain::new()
+                    .map_err(|e| format!("Unable to log to syslog: {}", e))?;
+                slog_async::Async::new(drain.ignore_res())
+                    .chan_size(LOG_CHANNEL_SIZE)
+                    .build()
+            }
+            Some(target) => return Err(format!("Unsupported log target: {}", target)),
+        };
+
+        let stdout_level = Arc::new(AtomicUsize::new(
+            parse_level(config.debug_level.as_str())?.as_usize(),
+        ));
+        let module_levels = match &config.log_filter {
+            Some(filter) => parse_log_filter(filter)?,
+            None => vec![],
+        };
+        let stdout_drain = ReloadableLevelFilter {
+            drain: stdout_drain,
+            level: stdout_level.clone(),
+            module_levels,
         };
 
         let stdout_logger = Logger::root(stdout_drain.fuse(), o!());
 
+        let mut logger_handle = LoggerHandle {
+            config: config.clone(),
+            stdout_level,
+            file_logger: None,
+        };
+
         // Disable file logging if values set to 0.
         if config.max_log_size == 0 || config.max_log_number == 0 {
             self.log = Some(stdout_logger);
+            self.logger_handle = Some(logger_handle);
             return Ok(self);
         }
 
         // Disable file logging if no path is specified.
         let path = match config.path {
-            Some(path) => path,
+            Some(ref path) => path.clone(),
             None => {
                 self.log = Some(stdout_logger);
+                self.logger_handle = Some(logger_handle);
                 return Ok(self);
             }
         };
@@ -235,37 +535,20 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
                             "Background file logging is disabled";
                             "error" => e);
                         self.log = Some(log);
+                        self.logger_handle = Some(logger_handle);
                         return Ok(self);
                     }
                 }
             }
         }
 
-        let logfile_level = match config.logfile_debug_level.as_str() {
-            "info" => Severity::Info,
-            "debug" => Severity::Debug,
-            "trace" => Severity::Trace,
-            "warn" => Severity::Warning,
-            "error" => Severity::Error,
-            "crit" => Severity::Critical,
-            unknown => return Err(format!("Unknown loglevel-debug-level: {}", unknown)),
+        let file_logger = Arc::new(RwLock::new(build_file_logger(&config, &path)?));
+        logger_handle.file_logger = Some(file_logger.clone());
+        let file_drain = ReloadableDrain {
+            logger: file_logger,
         };
 
-        let file_logger = FileLoggerBuilder::new(&path)
-            .level(logfile_level)
-            .channel_size(LOG_CHANNEL_SIZE)
-            .format(match config.logfile_format.as_deref() {
-                Some("JSON") => Format::Json,
-                _ => Format::default(),
-            })
-            .rotate_size(config.max_log_size)
-            .rotate_keep(config.max_log_number)
-            .rotate_compress(config.compression)
-            .restrict_permissions(config.is_restricted)
-            .build()
-            .map_err(|e| format!("Unable to build file logger: {}", e))?;
-
-        let log = Logger::root(Duplicate::new(stdout_logger, file_logger).fuse(), o!());
+        let log = Logger::root(Duplicate::new(stdout_logger, file_drain).fuse(), o!());
 
         info!(
             log,
@@ -274,6 +557,7 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
         );
 
         self.log = Some(log);
+        self.logger_handle = Some(logger_handle);
 
         Ok(self)
     }
@@ -302,11 +586,18 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
         }
     }
 
+    /// Sets the maximum time to wait for all tasks to complete once a shutdown has been requested.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
     /// Consumes the builder, returning an `Environment`.
     pub fn build(self) -> Result<Environment<E>, String> {
         let (signal, exit) = exit_future::signal();
         let (signal_tx, signal_rx) = channel(1);
-        Ok(Environment {
+        let (reload_tx, reload_rx) = watch::channel(());
+        let environment = Environment {
             runtime: self
                 .runtime
                 .ok_or("Cannot build environment without runtime")?,
@@ -315,10 +606,16 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
             signal: Some(signal),
             exit,
             log: self.log.ok_or("Cannot build environment without log")?,
+            logger_handle: self.logger_handle,
+            reload_tx: Arc::new(reload_tx),
+            reload_rx,
             eth_spec_instance: self.eth_spec_instance,
             eth2_config: self.eth2_config,
             eth2_network_config: self.eth2_network_config.map(Arc::new),
-        })
+            shutdown_timeout: self.shutdown_timeout,
+        };
+        environment.spawn_logfile_age_rotation();
+        Ok(environment)
     }
 }
 
@@ -369,9 +666,16 @@ pub struct Environment<E: EthSpec> {
     signal: Option<exit_future::Signal>,
     exit: exit_future::Exit,
     log: Logger,
+    /// Allows the logger to be reconfigured, if it was built by `initialize_logger`.
+    logger_handle: Option<LoggerHandle>,
+    /// Notifies subscribers that the runtime configuration should be reloaded.
+    reload_tx: Arc<watch::Sender<()>>,
+    reload_rx: ReloadReceiver,
     eth_spec_instance: E,
     pub eth2_config: Eth2Config,
     pub eth2_network_config: Option<Arc<Eth2NetworkConfig>>,
+    /// The maximum time to wait for all tasks to complete once a shutdown has been requested.
+    shutdown_timeout: Duration,
 }
 
 impl<E: EthSpec> Environment<E> {
@@ -413,9 +717,76 @@ impl<E: EthSpec> Environment<E> {
         }
     }
 
+    /// Returns a handle which can be used to reconfigure the logger at runtime.
+    pub fn logger_handle(&self) -> Option<LoggerHandle> {
+        self.logger_handle.clone()
+    }
+
+    /// If file logging is enabled with a non-zero `max_log_age`, spawns a task which rotates the
+    /// log file once it is older than that period.
+    ///
+    /// The age is measured from the creation of the log file, so a log file which was left by a
+    /// previous run, or which was started by a size-based rotation, is not kept for too long.
+    fn spawn_logfile_age_rotation(&self) {
+        let (logger_handle, path) = match &self.logger_handle {
+            Some(handle) if handle.file_logger.is_some() && handle.config.max_log_age > 0 => {
+                match &handle.config.path {
+                    Some(path) => (handle.clone(), path.clone()),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let log = self.log.clone();
+        let max_age = Duration::from_secs(logger_handle.config.max_log_age);
+
+        self.runtime().spawn(async move {
+            let mut last_rotation = None;
+            loop {
+                let created =
+                    log_rotation::created_time(&path, last_rotation).unwrap_or_else(|e| {
+                        error!(log, "Unable to read log file metadata"; "error" => %e);
+                        None
+                    });
+                let age = created
+                    .and_then(|created| SystemTime::now().duration_since(created).ok())
+                    .unwrap_or_default();
+                if age < max_age {
+                    tokio::time::sleep(max_age - age).await;
+                    continue;
+                }
+
+                let logger_handle = logger_handle.clone();
+                match tokio::task::spawn_blocking(move || logger_handle.rotate_logfile()).await {
+                    Ok(Ok(())) => {
+                        debug!(log, "Rotated log file"; "age" => ?age);
+                        last_rotation = Some(SystemTime::now());
+                    }
+                    // Avoid retrying continuously if the log file can't be rotated.
+                    Ok(Err(e)) => {
+                        error!(log, "Failed to rotate log file"; "error" => e);
+                        tokio::time::sleep(max_age).await;
+                    }
+                    Err(e) => {
+                        error!(log, "Failed to rotate log file"; "error" => ?e);
+                        tokio::time::sleep(max_age).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Returns a receiver which is notified each time a SIGHUP is received, after the log file
+    /// has been reopened.
+    pub fn reload_receiver(&self) -> ReloadReceiver {
+        self.reload_rx.clone()
+    }
+
     /// Block the current thread until a shutdown signal is received.
     ///
-    /// This can be either the user Ctrl-C'ing or a task requesting to shutdown.
+    /// This can be either the user Ctrl-C'ing or a task requesting to shutdown. A SIGHUP does not
+    /// cause a shutdown, instead the log file is reopened and subscribers to `reload_receiver` are
+    /// notified.
     #[cfg(target_family = "unix")]
     pub fn block_until_shutdown_requested(&mut self) -> Result<ShutdownReason, String> {
         // future of a task requesting to shutdown
@@ -450,9 +821,21 @@ impl<E: EthSpec> Environment<E> {
 
             // setup for handling a SIGHUP
             match signal(SignalKind::hangup()) {
-                Ok(hup_stream) => {
-                    let hup = SignalFuture::new(hup_stream, "Received SIGHUP");
-                    handles.push(hup);
+                Ok(mut hup_stream) => {
+                    let log = self.log.clone();
+                    let logger_handle = self.logger_handle.clone();
+                    let reload_tx = self.reload_tx.clone();
+                    self.runtime().spawn(async move {
+                        while hup_stream.recv().await.is_some() {
+                            info!(log, "Received SIGHUP, reloading configuration");
+                            if let Some(logger_handle) = &logger_handle {
+                                if let Err(e) = logger_handle.reopen_logfile() {
+                                    error!(log, "Failed to reopen log file"; "error" => e);
+                                }
+                            }
+                            let _ = reload_tx.send(());
+                        }
+                    });
                 }
                 Err(e) => error!(self.log, "Could not register SIGHUP handler"; "error" => e),
             }
@@ -518,10 +901,20 @@ impl<E: EthSpec> Environment<E> {
     }
 
     /// Shutdown the `tokio` runtime when all tasks are idle.
+    ///
+    /// Blocking tasks which are still running after the shutdown timeout are logged and abandoned.
     pub fn shutdown_on_idle(self) {
         match Arc::try_unwrap(self.runtime) {
             Ok(runtime) => {
-                runtime.shutdown_timeout(std::time::Duration::from_secs(MAXIMUM_SHUTDOWN_TIME))
+                runtime.shutdown_timeout(self.shutdown_timeout);
+                for (task, count) in running_tasks() {
+                    warn!(
+                        self.log,
+                        "Task did not complete before shutdown timeout";
+                        "task" => task,
+                        "count" => count,
+                    );
+                }
             }
             Err(e) => warn!(
                 self.log,
@@ -531,6 +924,38 @@ impl<E: EthSpec> Environment<E> {
         }
     }
 
+    /// Spawns a thread which exits the process with `exit_code` if it is still running once the
+    /// shutdown timeout and a further grace period have elapsed, logging the tasks which have not
+    /// completed.
+    ///
+    /// Should be called once a shutdown has been requested, so that a stuck task (e.g. the libp2p
+    /// swarm or a database compaction) cannot prevent the process from exiting. The grace period
+    /// ensures that the watchdog does not pre-empt `shutdown_on_idle`, which waits for up to the
+    /// shutdown timeout, and the normal exit which follows it.
+    pub fn spawn_shutdown_watchdog(&self, exit_code: i32) -> Result<(), String> {
+        let log = self.log.clone();
+        let timeout = self.shutdown_timeout + SHUTDOWN_WATCHDOG_GRACE;
+        std::thread::Builder::new()
+            .name("shutdown_watchdog".to_string())
+            .spawn(move || {
+                std::thread::sleep(timeout);
+                let tasks = running_tasks();
+                crit!(
+                    log,
+                    "Shutdown timed out, forcing exit";
+                    "timeout_secs" => timeout.as_secs(),
+                    "pending_tasks" => tasks.len(),
+                );
+                for (task, count) in tasks {
+                    crit!(log, "Task did not complete"; "task" => task, "count" => count);
+                }
+                std::thread::sleep(FORCED_EXIT_LOG_FLUSH_TIME);
+                std::process::exit(exit_code)
+            })
+            .map(drop)
+            .map_err(|e| format!("Unable to spawn shutdown watchdog: {:?}", e))
+    }
+
     /// Fire exit signal which shuts down all spawned services
     pub fn fire_signal(&mut self) {
         if let Some(signal) = self.signal.take() {
@@ -0,0 +1,72 @@
+//! Rotation of log files on demand, using the same naming scheme as the size-based rotation of the
+//! file logger from `sloggers`: rotated files are named `{path}.1`, `{path}.2`, etc., with a `.gz`
+//! suffix if compressed.
+
+use flate2::{write::GzEncoder, Compression};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Shifts the rotated log files along by one, deleting those beyond `keep`, then moves the log
+/// file at `path` to `{path}.1`.
+///
+/// Returns the path of the uncompressed `{path}.1`, which should be passed to `compress` once the
+/// log file is no longer being written to, if compression is enabled.
+pub fn shift_log_files(path: &Path, keep: usize, compression: bool) -> io::Result<PathBuf> {
+    for i in (1..=keep).rev() {
+        let from = rotated_path(path, i, compression);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, i + 1, compression))?;
+        }
+    }
+    let delete_path = rotated_path(path, keep + 1, compression);
+    if delete_path.exists() {
+        fs::remove_file(&delete_path)?;
+    }
+
+    let plain_path = rotated_path(path, 1, false);
+    if path.exists() {
+        fs::rename(path, &plain_path)?;
+    }
+    Ok(plain_path)
+}
+
+/// Gzips the rotated log file at `plain_path` (as returned by `shift_log_files`) to
+/// `{plain_path}.gz`, then deletes it.
+pub fn compress(plain_path: &Path) -> io::Result<()> {
+    if !plain_path.exists() {
+        return Ok(());
+    }
+    let gz_path = PathBuf::from(format!("{}.gz", plain_path.display()));
+    let mut input = File::open(plain_path)?;
+    let mut encoder = GzEncoder::new(File::create(gz_path)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(plain_path)
+}
+
+/// Returns the time the log file at `path` was created, or `None` if it does not exist.
+///
+/// Not all platforms and filesystems record creation times. In that case `last_rotation` is used
+/// if the file has been rotated by this process, otherwise the time the file was last modified.
+pub fn created_time(
+    path: &Path,
+    last_rotation: Option<SystemTime>,
+) -> io::Result<Option<SystemTime>> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    match (metadata.created(), last_rotation) {
+        (Ok(created), _) => Ok(Some(created)),
+        (Err(_), Some(last_rotation)) => Ok(Some(last_rotation)),
+        (Err(_), None) => metadata.modified().map(Some),
+    }
+}
+
+fn rotated_path(path: &Path, i: usize, compression: bool) -> PathBuf {
+    let suffix = if compression { ".gz" } else { "" };
+    PathBuf::from(format!("{}.{}{}", path.display(), i, suffix))
+}
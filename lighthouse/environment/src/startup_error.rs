@@ -0,0 +1,56 @@
+//! Errors which prevent the beacon node or validator client from starting.
+//!
+//! Each error carries the category assigned where it occurred, which the `lighthouse` binary maps
+//! to its exit code.
+
+use std::fmt;
+use task_executor::FailureKind;
+
+/// An error which prevented the beacon node or validator client from starting.
+#[derive(Debug)]
+pub struct StartupError {
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl StartupError {
+    pub fn new(kind: FailureKind, message: String) -> Self {
+        Self { kind, message }
+    }
+
+    pub fn database(message: String) -> Self {
+        Self::new(FailureKind::Database, message)
+    }
+
+    pub fn port_bind(message: String) -> Self {
+        Self::new(FailureKind::PortBind, message)
+    }
+
+    pub fn slashing_protection(message: String) -> Self {
+        Self::new(FailureKind::SlashingProtection, message)
+    }
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for StartupError {
+    fn from(message: String) -> Self {
+        Self::new(FailureKind::Other, message)
+    }
+}
+
+impl From<&str> for StartupError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<StartupError> for String {
+    fn from(error: StartupError) -> Self {
+        error.message
+    }
+}
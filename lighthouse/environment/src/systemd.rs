@@ -0,0 +1,76 @@
+//! Integration with the systemd service manager via `sd_notify`.
+//!
+//! All functions are no-ops when the process was not started by systemd (i.e., when the
+//! `NOTIFY_SOCKET` environment variable is not set) or when not running on a unix platform.
+
+use slog::Logger;
+use task_executor::TaskExecutor;
+
+#[cfg(target_family = "unix")]
+use {
+    sd_notify::NotifyState,
+    slog::{debug, info, warn},
+    std::time::Duration,
+};
+
+/// Informs systemd that startup has completed and the service is ready (`READY=1`).
+///
+/// Units with `Type=notify` are only considered "started" once this has been sent.
+#[cfg(target_family = "unix")]
+pub fn notify_ready(log: &Logger) {
+    match sd_notify::notify(false, &[NotifyState::Ready]) {
+        Ok(()) => debug!(log, "Notified systemd of readiness"),
+        Err(e) => warn!(log, "Failed to notify systemd of readiness"; "error" => %e),
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn notify_ready(_log: &Logger) {}
+
+/// Informs systemd that the service is shutting down (`STOPPING=1`).
+#[cfg(target_family = "unix")]
+pub fn notify_stopping(log: &Logger) {
+    if let Err(e) = sd_notify::notify(false, &[NotifyState::Stopping]) {
+        warn!(log, "Failed to notify systemd of shutdown"; "error" => %e);
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn notify_stopping(_log: &Logger) {}
+
+/// If the systemd watchdog is enabled for this service (`WatchdogSec=`), spawns a task on
+/// `executor` which sends `WATCHDOG=1` at half the watchdog interval.
+///
+/// Since the keepalives are sent from the executor, systemd will restart the service if the
+/// runtime stops making progress.
+#[cfg(target_family = "unix")]
+pub fn spawn_watchdog(executor: &TaskExecutor) {
+    let mut watchdog_usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut watchdog_usec) || watchdog_usec == 0 {
+        return;
+    }
+
+    let log = executor.log().clone();
+    let interval = Duration::from_micros(watchdog_usec) / 2;
+    info!(
+        log,
+        "Systemd watchdog enabled";
+        "keepalive_interval" => ?interval
+    );
+
+    executor.spawn(
+        async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = sd_notify::notify(false, &[NotifyState::Watchdog]) {
+                    warn!(log, "Failed to send systemd watchdog keepalive"; "error" => %e);
+                }
+            }
+        },
+        "systemd_watchdog",
+    );
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn spawn_watchdog(_executor: &TaskExecutor) {}
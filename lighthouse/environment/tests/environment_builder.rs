@@ -49,3 +49,152 @@ mod setup_eth2_config {
         }
     }
 }
+
+mod logger_handle {
+    use super::*;
+    use environment::LoggerConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn null_logger_has_no_handle() {
+        let environment = builder().build().expect("should build environment");
+
+        assert!(environment.logger_handle().is_none());
+    }
+
+    #[test]
+    fn set_debug_level() {
+        let environment = EnvironmentBuilder::mainnet()
+            .multi_threaded_tokio_runtime()
+            .expect("should set runtime")
+            .initialize_logger(LoggerConfig::default())
+            .expect("should set logger")
+            .build()
+            .expect("should build environment");
+        let logger_handle = environment
+            .logger_handle()
+            .expect("should have logger handle");
+
+        assert!(logger_handle.set_debug_level("debug").is_ok());
+        assert!(logger_handle.set_debug_level("verbose").is_err());
+        // File logging is disabled, so there is nothing to reopen.
+        assert!(logger_handle.reopen_logfile().is_ok());
+    }
+
+    #[test]
+    fn rotate_logfile() {
+        let dir = tempfile::tempdir().expect("should create temp dir");
+        let path = dir.path().join("beacon.log");
+        let environment = EnvironmentBuilder::mainnet()
+            .multi_threaded_tokio_runtime()
+            .expect("should set runtime")
+            .initialize_logger(LoggerConfig {
+                path: Some(path.clone()),
+                max_log_number: 2,
+                compression: true,
+                ..LoggerConfig::default()
+            })
+            .expect("should set logger")
+            .build()
+            .expect("should build environment");
+        let logger_handle = environment
+            .logger_handle()
+            .expect("should have logger handle");
+
+        // The file logger writes asynchronously, so write the log file directly.
+        for _ in 0..3 {
+            std::fs::write(&path, "log").expect("should write log file");
+            logger_handle
+                .rotate_logfile()
+                .expect("should rotate log file");
+        }
+
+        assert!(dir.path().join("beacon.log.1.gz").exists());
+        assert!(dir.path().join("beacon.log.2.gz").exists());
+        assert!(!dir.path().join("beacon.log.3.gz").exists());
+        assert!(!dir.path().join("beacon.log.1").exists());
+    }
+
+    #[test]
+    fn rotate_old_logfile_on_startup() {
+        let dir = tempfile::tempdir().expect("should create temp dir");
+        let path = dir.path().join("beacon.log");
+        // A log file left by a previous run, which is already older than the maximum age.
+        std::fs::write(&path, "log").expect("should write log file");
+        std::thread::sleep(Duration::from_secs(2));
+
+        let _environment = EnvironmentBuilder::mainnet()
+            .multi_threaded_tokio_runtime()
+            .expect("should set runtime")
+            .initialize_logger(LoggerConfig {
+                path: Some(path),
+                max_log_age: 1,
+                ..LoggerConfig::default()
+            })
+            .expect("should set logger")
+            .build()
+            .expect("should build environment");
+
+        // The log file is rotated straight away rather than after `max_log_age` seconds.
+        let rotated = dir.path().join("beacon.log.1");
+        for _ in 0..8 {
+            if rotated.exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert!(rotated.exists());
+    }
+
+    #[test]
+    fn logfmt_logfile() {
+        let dir = tempfile::tempdir().expect("should create temp dir");
+        let path = dir.path().join("beacon.log");
+        let environment = EnvironmentBuilder::mainnet()
+            .multi_threaded_tokio_runtime()
+            .expect("should set runtime")
+            .initialize_logger(LoggerConfig {
+                path: Some(path.clone()),
+                logfile_format: Some("LOGFMT".into()),
+                // Large enough that the record isn't rotated out of the log file.
+                max_log_size: 1024 * 1024,
+                ..LoggerConfig::default()
+            })
+            .expect("should set logger")
+            .build()
+            .expect("should build environment");
+
+        slog::info!(environment.core_context().log(), "Hello world"; "slot" => 5);
+        // Dropping the environment flushes the logs.
+        drop(environment);
+
+        let logs = std::fs::read_to_string(&path).expect("should read log file");
+        assert!(logs
+            .lines()
+            .any(|line| line.contains("level=info msg=\"Hello world\" slot=5")));
+    }
+}
+
+mod log_filter {
+    use environment::parse_log_filter;
+    use slog::Level;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_log_filter("network=debug, network::sync=trace,").unwrap(),
+            vec![
+                ("network::sync".to_string(), Level::Trace),
+                ("network".to_string(), Level::Debug)
+            ]
+        );
+        assert!(parse_log_filter("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse_log_filter("network").is_err());
+        assert!(parse_log_filter("=debug").is_err());
+        assert!(parse_log_filter("network=verbose").is_err());
+    }
+}
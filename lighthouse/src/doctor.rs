@@ -0,0 +1,255 @@
+//! The `doctor` subcommand, which checks whether this machine is suitable for running a beacon
+//! node before it is relied upon for staking.
+
+use crate::bls_library_name;
+use clap::{App, Arg, ArgMatches};
+use eth2_hashing::have_sha_extensions;
+use std::fmt;
+use std::net::{Ipv4Addr, TcpListener, UdpSocket};
+use std::path::Path;
+use std::time::Duration;
+
+pub const CMD: &str = "doctor";
+
+const GIB: u64 = 1024 * 1024 * 1024;
+/// A rough estimate of the space needed by the beacon node database over a year on mainnet, with
+/// the default `--slots-per-restore-point`.
+const EXPECTED_DB_GROWTH: u64 = 200 * GIB;
+/// Below this the beacon node is likely to run out of space within weeks.
+const MINIMUM_FREE_SPACE: u64 = 20 * GIB;
+/// The maximum clock offset tolerated by gossip validation (`MAXIMUM_GOSSIP_CLOCK_DISPARITY`).
+const MAXIMUM_CLOCK_OFFSET: Duration = Duration::from_millis(500);
+/// Above half the maximum, timely attestations start to become less likely.
+const WARN_CLOCK_OFFSET: Duration = Duration::from_millis(250);
+/// Enough file descriptors for a full set of peers and the database.
+const RECOMMENDED_OPEN_FILES: u64 = 65_536;
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Checks whether this machine is suitable for running a beacon node, printing a \
+             report of each check. Exits with a non-zero code if any check fails.",
+        )
+        .arg(
+            Arg::with_name("port")
+                .long("port")
+                .value_name("PORT")
+                .help("The TCP/UDP port that the beacon node will listen on.")
+                .default_value("9000")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ntp-server")
+                .long("ntp-server")
+                .value_name("HOST:PORT")
+                .help("The NTP server against which the system clock is compared.")
+                .default_value(clock_drift::DEFAULT_SERVER)
+                .takes_value(true),
+        )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Uses `pad` so that the report can be aligned with a width.
+        match self {
+            Status::Pass => f.pad("PASS"),
+            Status::Warn => f.pad("WARN"),
+            Status::Fail => f.pad("FAIL"),
+        }
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let port: u16 = clap_utils::parse_required(matches, "port")?;
+    let ntp_server: String = clap_utils::parse_required(matches, "ntp-server")?;
+    let data_dir = beacon_node::get_data_dir(matches);
+
+    let checks = [
+        ("Disk space", check_disk_space(&data_dir)),
+        ("System clock", check_clock(&ntp_server)),
+        ("TCP port", check_tcp_port(port)),
+        ("UDP port", check_udp_port(port)),
+        ("Open files limit", check_open_files_limit()),
+        ("CPU", check_cpu()),
+    ];
+
+    for (name, (status, detail)) in &checks {
+        println!("{:<6}{}: {}", status, name, detail);
+    }
+
+    let failures = checks
+        .iter()
+        .filter(|(_, (status, _))| *status == Status::Fail)
+        .count();
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} check(s) failed", failures))
+    }
+}
+
+fn check_disk_space(data_dir: &Path) -> (Status, String) {
+    // The datadir may not have been created yet.
+    let existing = match data_dir.ancestors().find(|dir| dir.exists()) {
+        Some(dir) => dir,
+        None => return (Status::Fail, format!("no parent of {:?} exists", data_dir)),
+    };
+
+    match fs2::available_space(existing) {
+        Ok(free) => {
+            let detail = format!(
+                "{} GiB free at {:?}, a year of database growth is roughly {} GiB",
+                free / GIB,
+                existing,
+                EXPECTED_DB_GROWTH / GIB
+            );
+            let status = if free < MINIMUM_FREE_SPACE {
+                Status::Fail
+            } else if free < EXPECTED_DB_GROWTH {
+                Status::Warn
+            } else {
+                Status::Pass
+            };
+            (status, detail)
+        }
+        Err(e) => (
+            Status::Fail,
+            format!("unable to read free space of {:?}: {}", existing, e),
+        ),
+    }
+}
+
+fn check_clock(ntp_server: &str) -> (Status, String) {
+    match query_clock_offset(ntp_server) {
+        Ok(offset_millis) => {
+            let magnitude = Duration::from_millis(offset_millis.unsigned_abs());
+            let detail = format!(
+                "{:+}ms offset from {} (maximum {}ms)",
+                offset_millis,
+                ntp_server,
+                MAXIMUM_CLOCK_OFFSET.as_millis()
+            );
+            let status = if magnitude > MAXIMUM_CLOCK_OFFSET {
+                Status::Fail
+            } else if magnitude > WARN_CLOCK_OFFSET {
+                Status::Warn
+            } else {
+                Status::Pass
+            };
+            (status, detail)
+        }
+        Err(e) => (
+            Status::Warn,
+            format!("unable to query {}, clock not checked: {}", ntp_server, e),
+        ),
+    }
+}
+
+/// Returns the offset in milliseconds of the clock of `ntp_server` from the system clock, using
+/// the same SNTP request as the clock drift monitor of the beacon node.
+fn query_clock_offset(ntp_server: &str) -> Result<i64, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("unable to start runtime: {}", e))?
+        .block_on(clock_drift::query_offset(ntp_server))
+}
+
+fn check_tcp_port(port: u16) -> (Status, String) {
+    match TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)) {
+        Ok(_) => (
+            Status::Pass,
+            format!(
+                "{} is available, ensure it is forwarded to this machine if behind a NAT",
+                port
+            ),
+        ),
+        Err(e) => (
+            Status::Fail,
+            format!(
+                "unable to bind {}, is a beacon node already running? {}",
+                port, e
+            ),
+        ),
+    }
+}
+
+fn check_udp_port(port: u16) -> (Status, String) {
+    match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)) {
+        Ok(_) => (
+            Status::Pass,
+            format!(
+                "{} is available, ensure it is forwarded to this machine if behind a NAT",
+                port
+            ),
+        ),
+        Err(e) => (
+            Status::Fail,
+            format!(
+                "unable to bind {}, is a beacon node already running? {}",
+                port, e
+            ),
+        ),
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn check_open_files_limit() -> (Status, String) {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // Safe because `limit` is a valid `rlimit` for the duration of the call.
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return (
+            Status::Warn,
+            format!("unable to read limit: {}", std::io::Error::last_os_error()),
+        );
+    }
+
+    // `rlim_t` is not a `u64` on all platforms.
+    #[allow(clippy::unnecessary_cast)]
+    let current = limit.rlim_cur as u64;
+    let detail = format!(
+        "{} (recommended at least {}, e.g. with `ulimit -n` or `LimitNOFILE`)",
+        current, RECOMMENDED_OPEN_FILES
+    );
+    if current < RECOMMENDED_OPEN_FILES {
+        (Status::Warn, detail)
+    } else {
+        (Status::Pass, detail)
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn check_open_files_limit() -> (Status, String) {
+    (Status::Pass, "not limited on this platform".to_string())
+}
+
+fn check_cpu() -> (Status, String) {
+    #[cfg(all(feature = "modern", target_arch = "x86_64"))]
+    if !std::is_x86_feature_detected!("adx") {
+        return (
+            Status::Fail,
+            "this build requires the ADX instruction set, which this CPU does not support. Use \
+             a portable build instead"
+                .to_string(),
+        );
+    }
+
+    (
+        Status::Pass,
+        format!(
+            "compatible with this build (BLS library: {}, SHA256 hardware acceleration: {})",
+            bls_library_name(),
+            have_sha_extensions()
+        ),
+    )
+}
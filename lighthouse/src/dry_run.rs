@@ -0,0 +1,270 @@
+//! Implements `--dry-run`, which validates the resolved configuration of the beacon node and/or
+//! validator client without starting any services.
+//!
+//! All checks are run, even once one has failed, so that every problem is reported at once.
+
+use crate::exit_code::ExitError;
+use account_utils::validator_definitions::{
+    SigningDefinition, ValidatorDefinitions, CONFIG_FILENAME,
+};
+use beacon_node::ClientConfig;
+use clap::ArgMatches;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::net::{SocketAddr, TcpListener, UdpSocket};
+use std::path::Path;
+use validator_client::SLASHING_PROTECTION_FILENAME;
+
+/// The name of the file written to check that a directory is writable.
+const PROBE_FILENAME: &str = ".lighthouse_dry_run";
+
+/// Beacon node flags which modify the datadir whilst the configuration is being built, before
+/// any check is run.
+const MODIFYING_FLAGS: &[&str] = &["purge-db", "execution-jwt-secret-key"];
+
+/// Returns an error if `--dry-run` is combined with a flag which would modify the datadir.
+///
+/// Must be called before the beacon node configuration is built.
+pub fn check_beacon_node_flags(matches: &ArgMatches) -> Result<(), ExitError> {
+    match MODIFYING_FLAGS.iter().find(|flag| matches.is_present(flag)) {
+        Some(flag) if parse_flag(matches, "dry-run").map_err(ExitError::config)? => {
+            Err(ExitError::config(format!(
+                "--{} cannot be used with --dry-run since it modifies the datadir",
+                flag
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Collects the outcome of each check.
+#[derive(Default)]
+pub struct DryRun {
+    errors: Vec<String>,
+}
+
+impl DryRun {
+    /// Checks the beacon node datadir, listening ports and execution engine JWT secret.
+    pub fn check_beacon_node(&mut self, config: &ClientConfig) {
+        self.check(
+            "beacon node datadir",
+            check_writable_dir(&config.get_data_dir()),
+        );
+        if config.get_data_dir().exists() {
+            self.check("datadir lock", beacon_node::lock_data_dir(config).map(drop));
+        }
+        if let Some(freezer_dir) = &config.freezer_db_path {
+            self.check(
+                "freezer database directory",
+                check_writable_dir(freezer_dir),
+            );
+        }
+
+        let listen_addrs = config.network.listen_addrs();
+        let v4 = listen_addrs
+            .v4()
+            .map(|addr| (addr.tcp_socket_addr(), addr.udp_socket_addr()));
+        let v6 = listen_addrs
+            .v6()
+            .map(|addr| (addr.tcp_socket_addr(), addr.udp_socket_addr()));
+        for (tcp, udp) in v4.into_iter().chain(v6) {
+            self.check("libp2p port", check_tcp_port(tcp));
+            if !config.network.disable_discovery {
+                self.check("discovery port", check_udp_port(udp));
+            }
+        }
+        if config.http_api.enabled {
+            self.check(
+                "HTTP API port",
+                check_tcp_port(SocketAddr::new(
+                    config.http_api.listen_addr,
+                    config.http_api.listen_port,
+                )),
+            );
+        }
+        if config.http_metrics.enabled {
+            self.check(
+                "HTTP metrics port",
+                check_tcp_port(SocketAddr::new(
+                    config.http_metrics.listen_addr,
+                    config.http_metrics.listen_port,
+                )),
+            );
+        }
+
+        if let Some(el_config) = &config.execution_layer {
+            for secret_file in &el_config.secret_files {
+                self.check("execution engine JWT secret", check_jwt_secret(secret_file));
+            }
+        }
+    }
+
+    /// Checks the validator client directories, listening ports and the availability of the
+    /// keystores, passwords and slashing protection database of all enabled validators.
+    pub fn check_validator_client(&mut self, config: &validator_client::Config) {
+        self.check(
+            "validators directory",
+            check_existing_writable_dir(&config.validator_dir),
+        );
+
+        if config.http_api.enabled {
+            self.check(
+                "validator HTTP API port",
+                check_tcp_port(SocketAddr::new(
+                    config.http_api.listen_addr,
+                    config.http_api.listen_port,
+                )),
+            );
+        }
+        if config.http_metrics.enabled {
+            self.check(
+                "validator HTTP metrics port",
+                check_tcp_port(SocketAddr::new(
+                    config.http_metrics.listen_addr,
+                    config.http_metrics.listen_port,
+                )),
+            );
+        }
+
+        // The definitions file is created on startup if it does not exist.
+        if !config.validator_dir.join(CONFIG_FILENAME).exists() {
+            return;
+        }
+        let definitions = match ValidatorDefinitions::open(&config.validator_dir) {
+            Ok(definitions) => definitions,
+            Err(e) => {
+                self.check("validator definitions", Err(format!("{:?}", e)));
+                return;
+            }
+        };
+
+        let mut num_enabled = 0;
+        for def in definitions.as_slice().iter().filter(|def| def.enabled) {
+            num_enabled += 1;
+            if let SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+            } = &def.signing_definition
+            {
+                let result = if !voting_keystore_path.exists() {
+                    Err(format!("{:?} does not exist", voting_keystore_path))
+                } else {
+                    match (voting_keystore_password_path, voting_keystore_password) {
+                        (_, Some(_)) => Ok(()),
+                        (Some(path), None) if path.exists() => Ok(()),
+                        (Some(path), None) => Err(format!("{:?} does not exist", path)),
+                        (None, None) => Err("no password file or password provided".into()),
+                    }
+                };
+                self.check(&format!("keystore for {}", def.voting_public_key), result);
+            }
+        }
+
+        if num_enabled > 0 && !config.init_slashing_protection {
+            let path = config.validator_dir.join(SLASHING_PROTECTION_FILENAME);
+            let result = if path.exists() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{:?} does not exist, use --init-slashing-protection if this is a new \
+                     validator client",
+                    path
+                ))
+            };
+            self.check("slashing protection database", result);
+        }
+    }
+
+    /// Prints the resolved `config` followed by any failed checks.
+    ///
+    /// Returns an error if any check failed.
+    pub fn finish<T: Serialize>(self, config: &T) -> Result<(), ExitError> {
+        let config = serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Unable to serialize config: {:?}", e))?;
+        println!("{}", config);
+
+        if self.errors.is_empty() {
+            eprintln!("Dry run succeeded, all checks passed");
+            Ok(())
+        } else {
+            for error in &self.errors {
+                eprintln!("Check failed: {}", error);
+            }
+            Err(ExitError::config(format!(
+                "Dry run failed, {} check(s) did not pass",
+                self.errors.len()
+            )))
+        }
+    }
+
+    fn check(&mut self, name: &str, result: Result<(), String>) {
+        if let Err(e) = result {
+            self.errors.push(format!("{}: {}", name, e));
+        }
+    }
+}
+
+/// Checks that `path` is a writable directory or, if it does not exist, that it can be created.
+fn check_writable_dir(path: &Path) -> Result<(), String> {
+    let existing = path
+        .ancestors()
+        .find(|dir| dir.exists())
+        .ok_or_else(|| format!("no parent of {:?} exists", path))?;
+    if !existing.is_dir() {
+        return Err(format!("{:?} is not a directory", existing));
+    }
+
+    let probe = existing.join(PROBE_FILENAME);
+    File::create(&probe)
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| format!("{:?} is not writable: {}", existing, e))
+}
+
+/// Checks that `path` is an existing, writable directory.
+fn check_existing_writable_dir(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{:?} does not exist", path));
+    }
+    check_writable_dir(path)
+}
+
+fn check_tcp_port(addr: SocketAddr) -> Result<(), String> {
+    if addr.port() == 0 {
+        return Ok(());
+    }
+    TcpListener::bind(addr)
+        .map(drop)
+        .map_err(|e| format!("unable to bind {}: {}", addr, e))
+}
+
+fn check_udp_port(addr: SocketAddr) -> Result<(), String> {
+    if addr.port() == 0 {
+        return Ok(());
+    }
+    UdpSocket::bind(addr)
+        .map(drop)
+        .map_err(|e| format!("unable to bind {}: {}", addr, e))
+}
+
+/// Checks that the JWT secret at `path` is a 32-byte hex string or, if it does not exist, that it
+/// can be generated.
+fn check_jwt_secret(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        return check_writable_dir(parent);
+    }
+
+    let secret =
+        fs::read_to_string(path).map_err(|e| format!("unable to read {:?}: {}", path, e))?;
+    let secret = secret.trim_end();
+    let secret = secret.strip_prefix("0x").unwrap_or(secret);
+    if secret.len() == 64 && secret.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(format!("{:?} does not contain a 32-byte hex string", path))
+    }
+}
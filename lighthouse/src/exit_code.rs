@@ -0,0 +1,145 @@
+//! Process exit codes, allowing orchestration tooling (e.g. systemd, Kubernetes) to react
+//! differently to each category of failure.
+//!
+//! The codes are part of the public interface of the `lighthouse` binary and are documented in
+//! the book. Existing codes must never be re-numbered.
+
+use task_executor::{FailureKind, ShutdownReason};
+
+/// The exit code of the `lighthouse` process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// The process exited cleanly.
+    Success = 0,
+    /// An error which does not fit any of the other categories.
+    Failure = 1,
+    /// The CLI arguments, config file or network configuration are invalid.
+    InvalidConfig = 2,
+    /// A database could not be opened or is corrupt.
+    Database = 3,
+    /// A TCP or UDP port could not be bound, usually because it is already in use.
+    PortBind = 4,
+    /// The on-disk database schema is not supported by this version of Lighthouse.
+    IncompatibleSchema = 5,
+    /// The validator client refused to run to avoid a slashable offence (e.g. a missing
+    /// slashing protection database or a doppelganger was detected). The process should not be
+    /// restarted without operator intervention.
+    SlashingProtection = 6,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Returns the exit code for a failure of the given category.
+    pub fn from_failure_kind(kind: FailureKind) -> Self {
+        match kind {
+            FailureKind::Database => ExitCode::Database,
+            FailureKind::PortBind => ExitCode::PortBind,
+            FailureKind::IncompatibleSchema => ExitCode::IncompatibleSchema,
+            FailureKind::SlashingProtection => ExitCode::SlashingProtection,
+            FailureKind::Other => ExitCode::Failure,
+        }
+    }
+
+    /// Returns the exit code for the reason the process was shut down.
+    pub fn from_shutdown_reason(reason: ShutdownReason) -> Self {
+        match reason {
+            ShutdownReason::Success(_) => ExitCode::Success,
+            ShutdownReason::Failure(kind, _) => Self::from_failure_kind(kind),
+        }
+    }
+}
+
+/// An error which terminates the process, along with the code to exit with.
+#[derive(Debug)]
+pub struct ExitError {
+    pub code: ExitCode,
+    pub message: String,
+}
+
+impl ExitError {
+    /// An error in the CLI arguments or configuration.
+    pub fn config(message: String) -> Self {
+        Self {
+            code: ExitCode::InvalidConfig,
+            message,
+        }
+    }
+}
+
+impl From<String> for ExitError {
+    fn from(message: String) -> Self {
+        Self {
+            code: ExitCode::Failure,
+            message,
+        }
+    }
+}
+
+impl From<datadir_schema::Error> for ExitError {
+    fn from(error: datadir_schema::Error) -> Self {
+        let code = match error {
+            datadir_schema::Error::UnsupportedSchemaVersion { .. } => ExitCode::IncompatibleSchema,
+            _ => ExitCode::Database,
+        };
+        Self {
+            code,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<&str> for ExitError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure_code(kind: FailureKind) -> i32 {
+        ExitCode::from_shutdown_reason(ShutdownReason::Failure(kind, "failure")).code()
+    }
+
+    #[test]
+    fn success() {
+        assert_eq!(
+            ExitCode::from_shutdown_reason(ShutdownReason::Success("success")).code(),
+            0
+        );
+    }
+
+    #[test]
+    fn failure() {
+        assert_eq!(failure_code(FailureKind::Other), 1);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_eq!(ExitError::config("invalid".into()).code.code(), 2);
+    }
+
+    #[test]
+    fn database() {
+        assert_eq!(failure_code(FailureKind::Database), 3);
+    }
+
+    #[test]
+    fn port_bind() {
+        assert_eq!(failure_code(FailureKind::PortBind), 4);
+    }
+
+    #[test]
+    fn incompatible_schema() {
+        assert_eq!(failure_code(FailureKind::IncompatibleSchema), 5);
+    }
+
+    #[test]
+    fn slashing_protection() {
+        assert_eq!(failure_code(FailureKind::SlashingProtection), 6);
+    }
+}
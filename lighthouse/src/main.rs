@@ -1,18 +1,34 @@
+mod crash_report;
+mod doctor;
+mod dry_run;
+mod exit_code;
 mod metrics;
+mod metrics_snapshot;
+mod node;
+mod tools;
+mod version;
 
+use account_utils::validator_definitions::VALIDATORS_DIR_SCHEMA;
 use beacon_node::ProductionBeaconNode;
 use clap::{App, Arg, ArgMatches};
-use clap_utils::{flags::DISABLE_MALLOC_TUNING_FLAG, get_eth2_network_config};
+use clap_utils::{
+    flags::{CONFIG_FILE_FLAG, DISABLE_MALLOC_TUNING_FLAG},
+    get_eth2_network_config, parse_flag, use_cli_value,
+};
 use directory::{parse_path_or_default, DEFAULT_BEACON_NODE_DIR, DEFAULT_VALIDATOR_DIR};
 use env_logger::{Builder, Env};
-use environment::{EnvironmentBuilder, LoggerConfig};
+use environment::{systemd, EnvironmentBuilder, LoggerConfig, LoggerSection};
 use eth2_hashing::have_sha_extensions;
 use eth2_network_config::{Eth2NetworkConfig, DEFAULT_HARDCODED_NETWORK, HARDCODED_NET_NAMES};
+use exit_code::{ExitCode, ExitError};
 use lighthouse_version::VERSION;
+use lockfile::Lockfile;
 use malloc_utils::configure_memory_allocator;
+use sensitive_url::SensitiveUrl;
 use slog::{crit, info, warn};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::time::Duration;
 use task_executor::ShutdownReason;
 use types::{EthSpec, EthSpecId};
 use validator_client::ProductionValidatorClient;
@@ -37,36 +53,17 @@ fn allocator_name() -> &'static str {
     }
 }
 
-fn main() {
-    // Enable backtraces unless a RUST_BACKTRACE value has already been explicitly provided.
-    if std::env::var("RUST_BACKTRACE").is_err() {
-        std::env::set_var("RUST_BACKTRACE", "1");
-    }
-
-    // Parse the CLI parameters.
-    let matches = App::new("Lighthouse")
-        .version(VERSION.replace("Lighthouse/", "").as_str())
+/// Returns the top-level command, with the Lighthouse programs as its subcommands.
+fn cli_app<'a>(short_version: &'a str, long_version: &'a str) -> App<'a, 'a> {
+    App::new("Lighthouse")
+        .version(short_version)
         .author("Sigma Prime <contact@sigmaprime.io>")
         .setting(clap::AppSettings::ColoredHelp)
         .about(
             "Ethereum 2.0 client by Sigma Prime. Provides a full-featured beacon \
              node, a validator client and utilities for managing validator accounts.",
         )
-        .long_version(
-            format!(
-                "{}\n\
-                 BLS library: {}\n\
-                 SHA256 hardware acceleration: {}\n\
-                 Allocator: {}\n\
-                 Specs: mainnet (true), minimal ({}), gnosis ({})",
-                 VERSION.replace("Lighthouse/", ""),
-                 bls_library_name(),
-                 have_sha_extensions(),
-                 allocator_name(),
-                 cfg!(feature = "spec-minimal"),
-                 cfg!(feature = "gnosis"),
-            ).as_str()
-        )
+        .long_version(long_version)
         .arg(
             Arg::with_name("spec")
                 .short("s")
@@ -75,6 +72,7 @@ fn main() {
                 .help("This flag is deprecated, it will be disallowed in a future release. This \
                     value is now derived from the --network or --testnet-dir flags.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SPEC")
                 .global(true)
         )
         .arg(
@@ -95,6 +93,7 @@ fn main() {
                     Once the number of log files exceeds the value specified in \
                     `--logfile-max-number` the oldest log file will be overwritten.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_LOGFILE")
                 .global(true),
         )
         .arg(
@@ -103,6 +102,7 @@ fn main() {
                 .value_name("LEVEL")
                 .help("The verbosity level used when emitting logs to the log file.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_LOGFILE_DEBUG_LEVEL")
                 .possible_values(&["info", "debug", "trace", "warn", "error", "crit"])
                 .default_value("debug")
                 .global(true),
@@ -111,9 +111,12 @@ fn main() {
             Arg::with_name("logfile-format")
                 .long("logfile-format")
                 .value_name("FORMAT")
-                .help("Specifies the log format used when emitting logs to the logfile.")
-                .possible_values(&["DEFAULT", "JSON"])
+                .help(
+                    "Specifies the log format used when emitting logs to the logfile. `PLAIN` is \
+                    the same as `DEFAULT`, since colors are never written to the logfile.")
+                .possible_values(&["DEFAULT", "JSON", "LOGFMT", "PLAIN"])
                 .takes_value(true)
+                .env("LIGHTHOUSE_LOGFILE_FORMAT")
                 .global(true)
         )
         .arg(
@@ -124,6 +127,7 @@ fn main() {
                     "The maximum size (in MB) each log file can grow to before rotating. If set \
                     to 0, background file logging is disabled.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_LOGFILE_MAX_SIZE")
                 .default_value("200")
                 .global(true),
         )
@@ -135,9 +139,22 @@ fn main() {
                     "The maximum number of log files that will be stored. If set to 0, \
                     background file logging is disabled.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_LOGFILE_MAX_NUMBER")
                 .default_value("5")
                 .global(true),
         )
+        .arg(
+            Arg::with_name("logfile-max-age")
+                .long("logfile-max-age")
+                .value_name("HOURS")
+                .help(
+                    "The maximum age in hours of the log file, measured from its creation, \
+                    before it is rotated regardless of its size. If set to 0, log files are only rotated by size.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_LOGFILE_MAX_AGE")
+                .default_value("0")
+                .global(true),
+        )
         .arg(
             Arg::with_name("logfile-compress")
                 .long("logfile-compress")
@@ -159,9 +176,40 @@ fn main() {
             Arg::with_name("log-format")
                 .long("log-format")
                 .value_name("FORMAT")
-                .help("Specifies the log format used when emitting logs to the terminal.")
-                .possible_values(&["JSON"])
+                .help(
+                    "Specifies the log format used when emitting logs to the terminal. `LOGFMT` \
+                    is suitable for ingestion by Loki, while `PLAIN` is the default format \
+                    without colors.")
+                .possible_values(&["JSON", "LOGFMT", "PLAIN"])
+                .takes_value(true)
+                .env("LIGHTHOUSE_LOG_FORMAT")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("log-filter")
+                .long("log-filter")
+                .value_name("FILTER")
+                .help(
+                    "Overrides --debug-level for specific modules, as a comma-separated list \
+                    of MODULE=LEVEL directives, e.g. `network=debug,beacon_chain=trace`. \
+                    Directives also apply to submodules, with the most specific one taking \
+                    precedence. The log file is not affected.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_LOG_FILTER")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("log-target")
+                .long("log-target")
+                .value_name("TARGET")
+                .help(
+                    "Where to emit the logs otherwise written to the terminal. `journald` and \
+                    `syslog` send each log along with its fields (e.g. slot, peer_id, service) \
+                    to the system log, which is useful when running under systemd. The log \
+                    file is not affected. [default: stdout]")
+                .possible_values(&["stdout", "journald", "syslog"])
+                .takes_value(true)
+                .env("LIGHTHOUSE_LOG_TARGET")
                 .global(true),
         )
         .arg(
@@ -183,6 +231,7 @@ fn main() {
                 .value_name("LEVEL")
                 .help("Specifies the verbosity level used when emitting logs to the terminal.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_DEBUG_LEVEL")
                 .possible_values(&["info", "debug", "trace", "warn", "error", "crit"])
                 .global(true)
                 .default_value("info"),
@@ -197,7 +246,8 @@ fn main() {
                     "Used to specify a custom root data directory for lighthouse keys and databases. \
                     Defaults to $HOME/.lighthouse/{network} where network is the value of the `network` flag \
                     Note: Users should specify separate custom datadirs for different networks.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_DATADIR"),
         )
         .arg(
             Arg::with_name("testnet-dir")
@@ -210,6 +260,7 @@ fn main() {
                       existing database.",
                 )
                 .takes_value(true)
+                .env("LIGHTHOUSE_TESTNET_DIR")
                 .global(true),
         )
         .arg(
@@ -220,15 +271,60 @@ fn main() {
                 .possible_values(HARDCODED_NET_NAMES)
                 .conflicts_with("testnet-dir")
                 .takes_value(true)
+                .env("LIGHTHOUSE_NETWORK")
                 .global(true)
 
         )
+        .arg(
+            Arg::with_name("network-config-url")
+                .long("network-config-url")
+                .value_name("URL")
+                .help(
+                    "URL of a .zip or .tar.gz archive containing the files of a testnet \
+                     directory (config.yaml, deploy_block.txt and optionally genesis.ssz and \
+                     boot_enr.yaml). The archive is downloaded once and cached in \
+                     ~/.lighthouse/network_configs. Use to join a custom network without \
+                     assembling a --testnet-dir.",
+                )
+                .conflicts_with_all(&["network", "testnet-dir"])
+                .takes_value(true)
+                .env("LIGHTHOUSE_NETWORK_CONFIG_URL")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("network-config-checksum")
+                .long("network-config-checksum")
+                .value_name("SHA256")
+                .help(
+                    "The SHA256 hash of the archive at --network-config-url, as hex. If \
+                     provided, Lighthouse refuses to use an archive with a different hash.",
+                )
+                .requires("network-config-url")
+                .takes_value(true)
+                .env("LIGHTHOUSE_NETWORK_CONFIG_CHECKSUM")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name(CONFIG_FILE_FLAG)
+                .long(CONFIG_FILE_FLAG)
+                .value_name("FILE")
+                .help(
+                    "Path to a TOML, YAML or JSON file from which to load the beacon node or \
+                     validator client configuration. The file uses the same format as the \
+                     output of --dump-config. Flags supplied on the command line take \
+                     precedence over values in the file.",
+                )
+                .takes_value(true)
+                .env("LIGHTHOUSE_CONFIG_FILE")
+                .global(true)
+        )
         .arg(
             Arg::with_name("dump-config")
                 .long("dump-config")
                 .hidden(true)
                 .help("Dumps the config to a desired location. Used for testing only.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_DUMP_CONFIG")
                 .global(true)
         )
         .arg(
@@ -237,6 +333,7 @@ fn main() {
                 .hidden(true)
                 .help("Dumps the chain config to a desired location. Used for testing only.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_DUMP_CHAIN_CONFIG")
                 .global(true)
         )
         .arg(
@@ -248,6 +345,74 @@ fn main() {
                     Used for testing only, DO NOT USE IN PRODUCTION.")
                 .global(true)
         )
+        .arg(
+            Arg::with_name("shutdown-timeout")
+                .long("shutdown-timeout")
+                .value_name("SECONDS")
+                .help(
+                    "The maximum number of seconds to wait for all tasks to complete once a \
+                    shutdown has been requested (e.g. with Ctrl+C). If any tasks are still \
+                    running after this time they are logged and the process exits regardless.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_SHUTDOWN_TIMEOUT")
+                .default_value("15")
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("dry-run")
+                .long("dry-run")
+                .help(
+                    "Builds the complete configuration of the beacon node and/or validator \
+                    client, checks that the data directories are writable, that the listening \
+                    ports are free and that the validator keys are available, then prints the \
+                    configuration and exits without starting any services. Exits with a non-zero \
+                    code if any check fails. Cannot be used with --purge-db or \
+                    --execution-jwt-secret-key, which modify the datadir.")
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("crash-reporting")
+                .long("crash-reporting")
+                .help(
+                    "If present, a report of any crash (the panic message, stack trace, \
+                    Lighthouse version and network) is written to the crash_reports directory \
+                    within the datadir. IP addresses, keys, peer IDs and ENRs are removed from \
+                    the report.")
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("crash-report-endpoint")
+                .long("crash-report-endpoint")
+                .value_name("URL")
+                .help(
+                    "An HTTP(S) endpoint to which crash reports are also sent as a JSON POST \
+                    request. Requires --crash-reporting.")
+                .requires("crash-reporting")
+                .takes_value(true)
+                .env("LIGHTHOUSE_CRASH_REPORT_ENDPOINT")
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("otlp-endpoint")
+                .long("otlp-endpoint")
+                .value_name("URL")
+                .help(
+                    "An OpenTelemetry collector endpoint (OTLP over gRPC, e.g. \
+                    http://localhost:4317) to which tracing spans for block processing, sync, \
+                    attestation verification and HTTP API requests are exported.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_OTLP_ENDPOINT")
+                .global(true)
+        )
+        .arg(
+            Arg::with_name("dry-run-migration")
+                .long("dry-run-migration")
+                .help(
+                    "Prints the schema migrations which would be applied to the data directories \
+                    and databases of the beacon node and/or validator client, then exits without \
+                    modifying them or starting any services.")
+                .global(true)
+        )
         .arg(
             Arg::with_name(DISABLE_MALLOC_TUNING_FLAG)
                 .long(DISABLE_MALLOC_TUNING_FLAG)
@@ -269,6 +434,7 @@ fn main() {
                        Incorrect use of this flag will cause your node to experience a consensus \
                        failure. Be extremely careful with this flag.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_TERMINAL_TOTAL_DIFFICULTY_OVERRIDE")
                 .global(true)
         )
         .arg(
@@ -282,6 +448,7 @@ fn main() {
                        failure. Be extremely careful with this flag.")
                 .requires("terminal-block-hash-epoch-override")
                 .takes_value(true)
+                .env("LIGHTHOUSE_TERMINAL_BLOCK_HASH_OVERRIDE")
                 .global(true)
         )
         .arg(
@@ -295,6 +462,7 @@ fn main() {
                        failure. Be extremely careful with this flag.")
                 .requires("terminal-block-hash-override")
                 .takes_value(true)
+                .env("LIGHTHOUSE_TERMINAL_BLOCK_HASH_EPOCH_OVERRIDE")
                 .global(true)
         )
         .arg(
@@ -308,22 +476,63 @@ fn main() {
                       node to possibly accept an invalid chain or sync more slowly. Be extremely careful with \
                       this flag.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SAFE_SLOTS_TO_IMPORT_OPTIMISTICALLY")
                 .global(true)
         )
         .subcommand(beacon_node::cli_app())
         .subcommand(boot_node::cli_app())
         .subcommand(validator_client::cli_app())
+        .subcommand(node::cli_app())
         .subcommand(account_manager::cli_app())
         .subcommand(database_manager::cli_app())
-        .get_matches();
+        .subcommand(version::cli_app())
+        .subcommand(doctor::cli_app())
+        .subcommand(metrics_snapshot::cli_app())
+        .subcommand(tools::cli_app())
+}
+
+fn main() {
+    // Enable backtraces unless a RUST_BACKTRACE value has already been explicitly provided.
+    if std::env::var("RUST_BACKTRACE").is_err() {
+        std::env::set_var("RUST_BACKTRACE", "1");
+    }
+
+    let long_version = format!(
+        "{}\n\
+         BLS library: {}\n\
+         SHA256 hardware acceleration: {}\n\
+         Allocator: {}\n\
+         Specs: mainnet (true), minimal ({}), gnosis ({})",
+        VERSION.replace("Lighthouse/", ""),
+        bls_library_name(),
+        have_sha_extensions(),
+        allocator_name(),
+        cfg!(feature = "spec-minimal"),
+        cfg!(feature = "gnosis"),
+    );
+
+    // Parse the CLI parameters.
+    let short_version = VERSION.replace("Lighthouse/", "");
+    let matches = cli_app(&short_version, &long_version)
+        .get_matches_safe()
+        .unwrap_or_else(|e| {
+            // Errors are written to stderr, whilst `--help` and `--version` are written to stdout.
+            if e.use_stderr() {
+                eprintln!("{}", e.message);
+                exit(ExitCode::InvalidConfig.code())
+            } else {
+                e.exit()
+            }
+        });
 
     // Configure the allocator early in the process, before it has the chance to use the default values for
     // anything important.
     //
     // Only apply this optimization for the beacon node. It's the only process with a substantial
     // memory footprint.
-    let is_beacon_node = matches.subcommand_name() == Some("beacon_node");
-    if is_beacon_node && !matches.is_present(DISABLE_MALLOC_TUNING_FLAG) {
+    let is_beacon_node = matches.subcommand_name() == Some("beacon_node")
+        || matches.subcommand_name() == Some(node::CMD);
+    if is_beacon_node && !flag_or_exit(&matches, DISABLE_MALLOC_TUNING_FLAG) {
         if let Err(e) = configure_memory_allocator() {
             eprintln!(
                 "Unable to configure the memory allocator: {} \n\
@@ -335,85 +544,147 @@ fn main() {
     }
 
     // Debugging output for libp2p and external crates.
-    if matches.is_present("env_log") {
+    if flag_or_exit(&matches, "env_log") {
         Builder::from_env(Env::default()).init();
     }
 
-    let result = get_eth2_network_config(&matches).and_then(|eth2_network_config| {
-        let eth_spec_id = eth2_network_config.eth_spec_id()?;
-
-        // boot node subcommand circumvents the environment
-        if let Some(bootnode_matches) = matches.subcommand_matches("boot_node") {
-            // The bootnode uses the main debug-level flag
-            let debug_info = matches
-                .value_of("debug-level")
-                .expect("Debug-level must be present")
-                .into();
-
-            boot_node::run(
-                &matches,
-                bootnode_matches,
-                eth_spec_id,
-                &eth2_network_config,
-                debug_info,
-            );
+    // The version subcommand does not require a network or an environment.
+    if let Some(version_matches) = matches.subcommand_matches(version::CMD) {
+        match version::run(version_matches, &long_version) {
+            Ok(()) => exit(ExitCode::Success.code()),
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(ExitCode::Failure.code())
+            }
+        }
+    }
+
+    // The doctor subcommand only inspects the machine, so it also runs without an environment.
+    if let Some(doctor_matches) = matches.subcommand_matches(doctor::CMD) {
+        match doctor::run(doctor_matches) {
+            Ok(()) => exit(ExitCode::Success.code()),
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(ExitCode::Failure.code())
+            }
+        }
+    }
 
-            return Ok(());
+    // The metrics subcommand only queries a running node, so it also runs without an environment.
+    if let Some(metrics_matches) = matches.subcommand_matches(metrics_snapshot::CMD) {
+        match metrics_snapshot::run(metrics_matches) {
+            Ok(()) => exit(ExitCode::Success.code()),
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(ExitCode::Failure.code())
+            }
         }
+    }
 
-        match eth_spec_id {
-            EthSpecId::Mainnet => run(EnvironmentBuilder::mainnet(), &matches, eth2_network_config),
-            #[cfg(feature = "gnosis")]
-            EthSpecId::Gnosis => run(EnvironmentBuilder::gnosis(), &matches, eth2_network_config),
-            #[cfg(feature = "spec-minimal")]
-            EthSpecId::Minimal => run(EnvironmentBuilder::minimal(), &matches, eth2_network_config),
-            #[cfg(not(all(feature = "spec-minimal", feature = "gnosis")))]
-            other => {
-                eprintln!(
-                    "Eth spec `{}` is not supported by this build of Lighthouse",
-                    other
+    let result = get_eth2_network_config(&matches)
+        .map_err(ExitError::config)
+        .and_then(|eth2_network_config| {
+            let eth_spec_id = eth2_network_config
+                .eth_spec_id()
+                .map_err(ExitError::config)?;
+
+            // boot node subcommand circumvents the environment
+            if let Some(bootnode_matches) = matches.subcommand_matches("boot_node") {
+                // The bootnode uses the main debug-level flag
+                let debug_info = matches
+                    .value_of("debug-level")
+                    .expect("Debug-level must be present")
+                    .into();
+
+                boot_node::run(
+                    &matches,
+                    bootnode_matches,
+                    eth_spec_id,
+                    &eth2_network_config,
+                    debug_info,
                 );
-                eprintln!("You must compile with a feature flag to enable this spec variant");
-                exit(1);
+
+                return Ok(());
             }
-        }
-    });
+
+            match eth_spec_id {
+                EthSpecId::Mainnet => {
+                    run(EnvironmentBuilder::mainnet(), &matches, eth2_network_config)
+                }
+                #[cfg(feature = "gnosis")]
+                EthSpecId::Gnosis => {
+                    run(EnvironmentBuilder::gnosis(), &matches, eth2_network_config)
+                }
+                #[cfg(feature = "spec-minimal")]
+                EthSpecId::Minimal => {
+                    run(EnvironmentBuilder::minimal(), &matches, eth2_network_config)
+                }
+                #[cfg(not(all(feature = "spec-minimal", feature = "gnosis")))]
+                other => {
+                    eprintln!(
+                        "Eth spec `{}` is not supported by this build of Lighthouse",
+                        other
+                    );
+                    eprintln!("You must compile with a feature flag to enable this spec variant");
+                    exit(ExitCode::InvalidConfig.code());
+                }
+            }
+        });
 
     // `std::process::exit` does not run destructors so we drop manually.
     drop(matches);
 
     // Return the appropriate error code.
     match result {
-        Ok(()) => exit(0),
+        Ok(()) => exit(ExitCode::Success.code()),
         Err(e) => {
-            eprintln!("{}", e);
+            eprintln!("{}", e.message);
+            let code = e.code;
             drop(e);
-            exit(1)
+            exit(code.code())
         }
     }
 }
 
+/// Returns `true` if the flag `name` is set, exiting if its environment variable is invalid.
+fn flag_or_exit(matches: &ArgMatches, name: &str) -> bool {
+    parse_flag(matches, name).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(ExitCode::InvalidConfig.code())
+    })
+}
+
 fn run<E: EthSpec>(
     environment_builder: EnvironmentBuilder<E>,
     matches: &ArgMatches,
     eth2_network_config: Eth2NetworkConfig,
-) -> Result<(), String> {
+) -> Result<(), ExitError> {
     if std::mem::size_of::<usize>() != 8 {
         return Err(format!(
             "{}-bit architecture is not supported (64-bit only).",
             std::mem::size_of::<usize>() * 8
-        ));
+        )
+        .into());
     }
 
-    let debug_level = matches
-        .value_of("debug-level")
-        .ok_or("Expected --debug-level flag")?;
+    let debug_level = match config_file_debug_level(matches)? {
+        Some(debug_level) => debug_level,
+        None => matches
+            .value_of("debug-level")
+            .ok_or("Expected --debug-level flag")?
+            .to_string(),
+    };
 
     let log_format = matches.value_of("log-format");
 
-    let log_color = matches.is_present("log-color");
+    let log_target = matches.value_of("log-target");
+
+    let log_filter = matches.value_of("log-filter");
+
+    let log_color = parse_flag(matches, "log-color").map_err(ExitError::config)?;
 
-    let disable_log_timestamp = matches.is_present("disable-log-timestamp");
+    let disable_log_timestamp =
+        parse_flag(matches, "disable-log-timestamp").map_err(ExitError::config)?;
 
     let logfile_debug_level = matches
         .value_of("logfile-debug-level")
@@ -428,23 +699,36 @@ fn run<E: EthSpec>(
         .value_of("logfile-max-size")
         .ok_or("Expected --logfile-max-size flag")?
         .parse()
-        .map_err(|e| format!("Failed to parse `logfile-max-size`: {:?}", e))?;
+        .map_err(|e| ExitError::config(format!("Failed to parse `logfile-max-size`: {:?}", e)))?;
 
     let logfile_max_number: usize = matches
         .value_of("logfile-max-number")
         .ok_or("Expected --logfile-max-number flag")?
         .parse()
-        .map_err(|e| format!("Failed to parse `logfile-max-number`: {:?}", e))?;
+        .map_err(|e| ExitError::config(format!("Failed to parse `logfile-max-number`: {:?}", e)))?;
 
-    let logfile_compress = matches.is_present("logfile-compress");
+    let logfile_max_age: u64 = matches
+        .value_of("logfile-max-age")
+        .ok_or("Expected --logfile-max-age flag")?
+        .parse()
+        .map_err(|e| ExitError::config(format!("Failed to parse `logfile-max-age`: {:?}", e)))?;
+
+    let shutdown_timeout: u64 = matches
+        .value_of("shutdown-timeout")
+        .ok_or("Expected --shutdown-timeout flag")?
+        .parse()
+        .map_err(|e| ExitError::config(format!("Failed to parse `shutdown-timeout`: {:?}", e)))?;
 
-    let logfile_restricted = !matches.is_present("logfile-no-restricted-perms");
+    let logfile_compress = parse_flag(matches, "logfile-compress").map_err(ExitError::config)?;
+
+    let logfile_restricted =
+        !parse_flag(matches, "logfile-no-restricted-perms").map_err(ExitError::config)?;
 
     // Construct the path to the log file.
     let mut log_path: Option<PathBuf> = clap_utils::parse_optional(matches, "logfile")?;
     if log_path.is_none() {
         log_path = match matches.subcommand() {
-            ("beacon_node", _) => Some(
+            ("beacon_node", _) | (node::CMD, _) => Some(
                 parse_path_or_default(matches, "datadir")?
                     .join(DEFAULT_BEACON_NODE_DIR)
                     .join("logs")
@@ -471,23 +755,29 @@ fn run<E: EthSpec>(
 
     let logger_config = LoggerConfig {
         path: log_path,
-        debug_level: String::from(debug_level),
+        debug_level,
         logfile_debug_level: String::from(logfile_debug_level),
         log_format: log_format.map(String::from),
         logfile_format: logfile_format.map(String::from),
+        log_target: log_target.map(String::from),
+        log_filter: log_filter.map(String::from),
         log_color,
         disable_log_timestamp,
         max_log_size: logfile_max_size * 1_024 * 1_024,
         max_log_number: logfile_max_number,
+        max_log_age: logfile_max_age * 60 * 60,
         compression: logfile_compress,
         is_restricted: logfile_restricted,
     };
 
-    let builder = environment_builder.initialize_logger(logger_config.clone())?;
+    let builder = environment_builder
+        .initialize_logger(logger_config.clone())
+        .map_err(ExitError::config)?;
 
     let mut environment = builder
         .multi_threaded_tokio_runtime()?
         .optional_eth2_network_config(Some(eth2_network_config))?
+        .shutdown_timeout(Duration::from_secs(shutdown_timeout))
         .build()?;
 
     let log = environment.core_context().log().clone();
@@ -497,6 +787,20 @@ fn run<E: EthSpec>(
 
     // Allow Prometheus access to the version and commit of the Lighthouse build.
     metrics::expose_lighthouse_version();
+    metrics::expose_build_info();
+
+    let otlp_guard =
+        if let Some(endpoint) = clap_utils::parse_optional::<String>(matches, "otlp-endpoint")? {
+            let endpoint = SensitiveUrl::parse(&endpoint)
+                .map_err(|e| ExitError::config(format!("Invalid --otlp-endpoint: {:?}", e)))?;
+            // The exporter is spawned on the runtime of the environment.
+            let _runtime = environment.runtime().enter();
+            let guard = otlp_tracing::init(endpoint.full.as_str(), "lighthouse", VERSION)?;
+            info!(log, "Exporting tracing spans"; "endpoint" => %endpoint);
+            Some(guard)
+        } else {
+            None
+        };
 
     if matches.is_present("spec") {
         warn!(
@@ -514,25 +818,39 @@ fn run<E: EthSpec>(
         );
     }
 
-    // Note: the current code technically allows for starting a beacon node _and_ a validator
-    // client at the same time.
-    //
-    // Whilst this is possible, the mutual-exclusivity of `clap` sub-commands prevents it from
-    // actually happening.
-    //
-    // Creating a command which can run both might be useful future works.
-
     // Print an indication of which network is currently in use.
     let optional_testnet = clap_utils::parse_optional::<String>(matches, "network")?;
     let optional_testnet_dir = clap_utils::parse_optional::<PathBuf>(matches, "testnet-dir")?;
+    let optional_config_url = clap_utils::parse_optional::<String>(matches, "network-config-url")?;
 
-    let network_name = match (optional_testnet, optional_testnet_dir) {
-        (Some(testnet), None) => testnet,
-        (None, Some(testnet_dir)) => format!("custom ({})", testnet_dir.display()),
-        (None, None) => DEFAULT_HARDCODED_NETWORK.to_string(),
-        (Some(_), Some(_)) => panic!("CLI prevents both --network and --testnet-dir"),
+    let network_name = match (optional_testnet, optional_testnet_dir, optional_config_url) {
+        (Some(testnet), None, None) => testnet,
+        (None, Some(testnet_dir), None) => format!("custom ({})", testnet_dir.display()),
+        (None, None, Some(url)) => format!("custom ({})", url),
+        (None, None, None) => DEFAULT_HARDCODED_NETWORK.to_string(),
+        _ => panic!(
+            "CLI prevents more than one of --network, --testnet-dir and --network-config-url"
+        ),
     };
 
+    if parse_flag(matches, "crash-reporting").map_err(ExitError::config)? {
+        let endpoint = clap_utils::parse_optional::<String>(matches, "crash-report-endpoint")?
+            .map(|url| SensitiveUrl::parse(&url))
+            .transpose()
+            .map_err(|e| ExitError::config(format!("Invalid --crash-report-endpoint: {:?}", e)))?;
+        // The path or URL of a custom network could identify the user.
+        let network = if network_name.starts_with("custom") {
+            "custom".to_string()
+        } else {
+            network_name.clone()
+        };
+        crash_report::install(
+            parse_path_or_default(matches, "datadir")?,
+            network,
+            endpoint,
+        );
+    }
+
     if let Some(sub_matches) = matches.subcommand_matches("account_manager") {
         eprintln!("Running account manager for {} network", network_name);
         // Pass the entire `environment` to the account manager so it can run blocking operations.
@@ -551,6 +869,12 @@ fn run<E: EthSpec>(
         return Ok(());
     }
 
+    if let Some(sub_matches) = matches.subcommand_matches(tools::CMD) {
+        info!(log, "Running tools for {} network", network_name);
+        tools::run(sub_matches, environment)?;
+        return Ok(());
+    }
+
     info!(log, "Lighthouse started"; "version" => VERSION);
     info!(
         log,
@@ -558,29 +882,68 @@ fn run<E: EthSpec>(
         "name" => &network_name
     );
 
+    // Send keepalives to the systemd watchdog, if enabled.
+    systemd::spawn_watchdog(&environment.core_context().executor);
+
+    // Held until the process exits, so that no other beacon node can use the same datadir.
+    let mut datadir_lock = None;
+
     match matches.subcommand() {
         ("beacon_node", Some(matches)) => {
             let context = environment.core_context();
             let log = context.log().clone();
             let executor = context.executor.clone();
-            let mut config = beacon_node::get_config::<E>(matches, &context)?;
+            dry_run::check_beacon_node_flags(matches)?;
+            let mut config =
+                beacon_node::get_config::<E>(matches, &context).map_err(ExitError::config)?;
             config.logger_config = logger_config;
-            let shutdown_flag = matches.is_present("immediate-shutdown");
+            let reloader =
+                beacon_node::ConfigReloader::new(matches, &config).map_err(ExitError::config)?;
+            let reload_rx = environment.reload_receiver();
+            let logger_handle = environment.logger_handle();
+            let shutdown_flag =
+                parse_flag(matches, "immediate-shutdown").map_err(ExitError::config)?;
             // Dump configs if `dump-config` or `dump-chain-config` flags are set
             clap_utils::check_dump_configs::<_, E>(matches, &config, &context.eth2_config.spec)?;
+            if parse_flag(matches, "dry-run").map_err(ExitError::config)? {
+                let mut dry_run = dry_run::DryRun::default();
+                dry_run.check_beacon_node(&config);
+                return dry_run.finish(&config);
+            }
+            let dry_run_migration =
+                parse_flag(matches, "dry-run-migration").map_err(ExitError::config)?;
+            if !dry_run_migration {
+                // Don't lock the datadir during a dry run, so that it isn't written to.
+                datadir_lock = lock_beacon_data_dir(matches, &config, &log)?;
+            }
+            migrate_beacon_data_dir::<E>(dry_run_migration, &config, &log)?;
+            if dry_run_migration {
+                return Ok(());
+            }
             executor.clone().spawn(
                 async move {
-                    if let Err(e) = ProductionBeaconNode::new(context.clone(), config).await {
-                        crit!(log, "Failed to start beacon node"; "reason" => e);
-                        // Ignore the error since it always occurs during normal operation when
-                        // shutting down.
-                        let _ = executor
-                            .shutdown_sender()
-                            .try_send(ShutdownReason::Failure("Failed to start beacon node"));
-                    } else if shutdown_flag {
-                        let _ = executor.shutdown_sender().try_send(ShutdownReason::Success(
-                            "Beacon node immediate shutdown triggered.",
-                        ));
+                    match ProductionBeaconNode::new(context.clone(), config).await {
+                        Err(e) => {
+                            let reason =
+                                ShutdownReason::Failure(e.kind, "Failed to start beacon node");
+                            crit!(log, "Failed to start beacon node"; "reason" => %e);
+                            // Ignore the error since it always occurs during normal operation when
+                            // shutting down.
+                            let _ = executor.shutdown_sender().try_send(reason);
+                        }
+                        Ok(_) if shutdown_flag => {
+                            let _ = executor.shutdown_sender().try_send(ShutdownReason::Success(
+                                "Beacon node immediate shutdown triggered.",
+                            ));
+                        }
+                        Ok(beacon_node) => {
+                            systemd::notify_ready(&log);
+                            if let Some(reloader) = reloader {
+                                beacon_node
+                                    .reload_on_signal(reloader, reload_rx, logger_handle, log)
+                                    .await;
+                            }
+                        }
                     }
                 },
                 "beacon_node",
@@ -590,24 +953,54 @@ fn run<E: EthSpec>(
             let context = environment.core_context();
             let log = context.log().clone();
             let executor = context.executor.clone();
-            let config = validator_client::Config::from_cli(matches, context.log())
-                .map_err(|e| format!("Unable to initialize validator config: {}", e))?;
-            let shutdown_flag = matches.is_present("immediate-shutdown");
+            let config =
+                validator_client::Config::from_cli(matches, context.log()).map_err(|e| {
+                    ExitError::config(format!("Unable to initialize validator config: {}", e))
+                })?;
+            let reloader = validator_client::ConfigReloader::new(matches, &config)
+                .map_err(ExitError::config)?;
+            let reload_rx = environment.reload_receiver();
+            let logger_handle = environment.logger_handle();
+            let shutdown_flag =
+                parse_flag(matches, "immediate-shutdown").map_err(ExitError::config)?;
             // Dump configs if `dump-config` or `dump-chain-config` flags are set
             clap_utils::check_dump_configs::<_, E>(matches, &config, &context.eth2_config.spec)?;
+            if parse_flag(matches, "dry-run").map_err(ExitError::config)? {
+                let mut dry_run = dry_run::DryRun::default();
+                dry_run.check_validator_client(&config);
+                return dry_run.finish(&config);
+            }
+            let dry_run_migration =
+                parse_flag(matches, "dry-run-migration").map_err(ExitError::config)?;
+            migrate_validators_dir(dry_run_migration, &config.validator_dir, &log)?;
+            if dry_run_migration {
+                return Ok(());
+            }
             if !shutdown_flag {
                 executor.clone().spawn(
                     async move {
-                        if let Err(e) = ProductionValidatorClient::new(context, config)
+                        match ProductionValidatorClient::new(context, config)
                             .await
-                            .and_then(|mut vc| vc.start_service())
+                            .and_then(|mut vc| vc.start_service().map(|()| vc))
                         {
-                            crit!(log, "Failed to start validator client"; "reason" => e);
-                            // Ignore the error since it always occurs during normal operation when
-                            // shutting down.
-                            let _ = executor.shutdown_sender().try_send(ShutdownReason::Failure(
-                                "Failed to start validator client",
-                            ));
+                            Err(e) => {
+                                let reason = ShutdownReason::Failure(
+                                    e.kind,
+                                    "Failed to start validator client",
+                                );
+                                crit!(log, "Failed to start validator client"; "reason" => %e);
+                                // Ignore the error since it always occurs during normal operation
+                                // when shutting down.
+                                let _ = executor.shutdown_sender().try_send(reason);
+                            }
+                            Ok(validator_client) => {
+                                systemd::notify_ready(&log);
+                                if let Some(reloader) = reloader {
+                                    validator_client
+                                        .reload_on_signal(reloader, reload_rx, logger_handle, log)
+                                        .await;
+                                }
+                            }
                         }
                     },
                     "validator_client",
@@ -618,23 +1011,279 @@ fn run<E: EthSpec>(
                 ));
             }
         }
+        (node::CMD, Some(matches)) => {
+            let context = environment.core_context();
+            let validator_context = environment.service_context("validator_client".into());
+            let log = context.log().clone();
+            let executor = context.executor.clone();
+            dry_run::check_beacon_node_flags(matches)?;
+            let mut beacon_config =
+                beacon_node::get_config::<E>(matches, &context).map_err(ExitError::config)?;
+            beacon_config.logger_config = logger_config;
+            // The bundled validator client talks to the beacon node via the HTTP API.
+            beacon_config.http_api.enabled = true;
+            let reloader = beacon_node::ConfigReloader::new(matches, &beacon_config)
+                .map_err(ExitError::config)?;
+            let reload_rx = environment.reload_receiver();
+            let logger_handle = environment.logger_handle();
+            let node_config = node::NodeConfig {
+                validator_client: node::validator_client_config(matches, &beacon_config)
+                    .map_err(ExitError::config)?,
+                beacon_node: beacon_config,
+            };
+            let shutdown_flag =
+                parse_flag(matches, "immediate-shutdown").map_err(ExitError::config)?;
+            // Dump configs if `dump-config` or `dump-chain-config` flags are set
+            clap_utils::check_dump_configs::<_, E>(
+                matches,
+                &node_config,
+                &context.eth2_config.spec,
+            )?;
+            if parse_flag(matches, "dry-run").map_err(ExitError::config)? {
+                let mut dry_run = dry_run::DryRun::default();
+                dry_run.check_beacon_node(&node_config.beacon_node);
+                dry_run.check_validator_client(&node_config.validator_client);
+                return dry_run.finish(&node_config);
+            }
+            let dry_run_migration =
+                parse_flag(matches, "dry-run-migration").map_err(ExitError::config)?;
+            if !dry_run_migration {
+                // Don't lock the datadir during a dry run, so that it isn't written to.
+                datadir_lock = lock_beacon_data_dir(matches, &node_config.beacon_node, &log)?;
+            }
+            migrate_beacon_data_dir::<E>(dry_run_migration, &node_config.beacon_node, &log)?;
+            migrate_validators_dir(
+                dry_run_migration,
+                &node_config.validator_client.validator_dir,
+                &log,
+            )?;
+            if dry_run_migration {
+                return Ok(());
+            }
+            let node::NodeConfig {
+                beacon_node: beacon_config,
+                validator_client: mut validator_config,
+            } = node_config;
+            executor.clone().spawn(
+                async move {
+                    let beacon_node =
+                        match ProductionBeaconNode::new(context.clone(), beacon_config).await {
+                            Ok(beacon_node) => beacon_node,
+                            Err(e) => {
+                                let reason =
+                                    ShutdownReason::Failure(e.kind, "Failed to start beacon node");
+                                crit!(log, "Failed to start beacon node"; "reason" => %e);
+                                // Ignore the error since it always occurs during normal operation
+                                // when shutting down.
+                                let _ = executor.shutdown_sender().try_send(reason);
+                                return;
+                            }
+                        };
+
+                    if shutdown_flag {
+                        let _ = executor.shutdown_sender().try_send(ShutdownReason::Success(
+                            "Node immediate shutdown triggered.",
+                        ));
+                        return;
+                    }
+
+                    // Use the address the HTTP server actually bound to, since the port may have
+                    // been chosen by the OS.
+                    if let Some(listen_addr) = beacon_node.http_api_listen_addr() {
+                        match node::beacon_node_url(listen_addr) {
+                            Ok(url) => validator_config.beacon_nodes = vec![url],
+                            Err(e) => warn!(
+                                log,
+                                "Unable to determine beacon node URL";
+                                "error" => e,
+                            ),
+                        }
+                    }
+
+                    if let Err(e) =
+                        ProductionValidatorClient::new(validator_context, validator_config)
+                            .await
+                            .and_then(|mut vc| vc.start_service())
+                    {
+                        let reason =
+                            ShutdownReason::Failure(e.kind, "Failed to start validator client");
+                        crit!(log, "Failed to start validator client"; "reason" => %e);
+                        // Ignore the error since it always occurs during normal operation when
+                        // shutting down.
+                        let _ = executor.shutdown_sender().try_send(reason);
+                    } else {
+                        systemd::notify_ready(&log);
+                        if let Some(reloader) = reloader {
+                            beacon_node
+                                .reload_on_signal(reloader, reload_rx, logger_handle, log)
+                                .await;
+                        }
+                    }
+                },
+                "node",
+            );
+        }
         _ => {
             crit!(log, "No subcommand supplied. See --help .");
-            return Err("No subcommand supplied.".into());
+            return Err(ExitError::config("No subcommand supplied.".into()));
         }
     };
 
     // Block this thread until we get a ctrl-c or a task sends a shutdown signal.
     let shutdown_reason = environment.block_until_shutdown_requested()?;
     info!(log, "Shutting down.."; "reason" => ?shutdown_reason);
+    systemd::notify_stopping(&log);
 
+    // Exit regardless of any tasks which fail to complete within the shutdown timeout.
+    environment.spawn_shutdown_watchdog(ExitCode::Failure.code())?;
     environment.fire_signal();
 
+    // Flush any outstanding spans whilst the runtime is still available.
+    drop(otlp_guard);
+
     // Shutdown the environment once all tasks have completed.
     environment.shutdown_on_idle();
 
-    match shutdown_reason {
-        ShutdownReason::Success(_) => Ok(()),
-        ShutdownReason::Failure(msg) => Err(msg.to_string()),
+    drop(datadir_lock);
+
+    match ExitCode::from_shutdown_reason(shutdown_reason) {
+        ExitCode::Success => Ok(()),
+        code => Err(ExitError {
+            code,
+            message: shutdown_reason.message().to_string(),
+        }),
+    }
+}
+
+/// Returns the debug level from the `--config-file` of the sub-command, unless `--debug-level` was
+/// supplied on the command line.
+///
+/// The same value is applied when the file is reloaded on SIGHUP, so reloading an unchanged file
+/// doesn't change the debug level.
+fn config_file_debug_level(matches: &ArgMatches) -> Result<Option<String>, String> {
+    let (section, matches) = match matches.subcommand() {
+        ("beacon_node", Some(matches)) | (node::CMD, Some(matches)) => ("beacon_node", matches),
+        ("validator_client", Some(matches)) => ("validator_client", matches),
+        _ => return Ok(None),
+    };
+    if use_cli_value(matches, "debug-level") {
+        return Ok(None);
+    }
+    let path: PathBuf = clap_utils::parse_required(matches, CONFIG_FILE_FLAG)?;
+    let logger: LoggerSection = clap_utils::load_config_file(&path, section)?;
+    Ok(Some(logger.logger_config.debug_level))
+}
+
+/// Locks the beacon node datadir, unless `--force-unlock` is set.
+fn lock_beacon_data_dir(
+    matches: &ArgMatches,
+    config: &beacon_node::ClientConfig,
+    log: &slog::Logger,
+) -> Result<Option<Lockfile>, ExitError> {
+    if parse_flag(matches, "force-unlock").map_err(ExitError::config)? {
+        warn!(
+            log,
+            "Not locking the datadir";
+            "info" => "running two beacon nodes on the same datadir will corrupt the database",
+            "datadir" => ?config.get_data_dir(),
+        );
+        return Ok(None);
+    }
+
+    beacon_node::lock_data_dir(config)
+        .map(Some)
+        .map_err(|message| ExitError {
+            code: ExitCode::Database,
+            message,
+        })
+}
+
+/// Migrates the beacon node datadir and backs up its metadata, or only prints the migrations if
+/// `dry_run` is set.
+fn migrate_beacon_data_dir<E: EthSpec>(
+    dry_run: bool,
+    config: &beacon_node::ClientConfig,
+    log: &slog::Logger,
+) -> Result<(), ExitError> {
+    let migration = beacon_node::plan_data_dir_migration::<E>(config)?;
+    if dry_run {
+        println!("{}", migration);
+    } else {
+        beacon_node::migrate_data_dir(&migration, log)?;
+    }
+    Ok(())
+}
+
+/// Migrates the validators directory and backs up its metadata, or only prints the migrations if
+/// `dry_run` is set.
+fn migrate_validators_dir(
+    dry_run: bool,
+    validator_dir: &Path,
+    log: &slog::Logger,
+) -> Result<(), ExitError> {
+    if dry_run {
+        println!(
+            "{}",
+            datadir_schema::plan(validator_dir, &VALIDATORS_DIR_SCHEMA)?
+        );
+    } else {
+        datadir_schema::migrate(validator_dir, &VALIDATORS_DIR_SCHEMA, log)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_utils::env_var_name;
+    use std::ffi::OsStr;
+
+    /// Asserts that every argument of `app` can be supplied by its `LIGHTHOUSE_*` environment
+    /// variable.
+    ///
+    /// Arguments which take a value must declare the variable with `Arg::env`. `clap` ignores the
+    /// variable of a flag, which `clap_utils::parse_flag` derives from the flag's name instead, so
+    /// the name must match the long name shown by `--help`.
+    fn assert_args_have_env_vars(app: &App) {
+        let command = &app.p.meta.name;
+        for opt in &app.p.opts {
+            let long = opt.s.long.unwrap_or(opt.b.name);
+            assert_eq!(
+                opt.v.env.as_ref().map(|(name, _)| *name),
+                Some(OsStr::new(&env_var_name(long))),
+                "--{} of {} has no environment variable",
+                long,
+                command
+            );
+        }
+        for flag in &app.p.flags {
+            assert_eq!(
+                flag.s.long.unwrap_or(flag.b.name),
+                flag.b.name,
+                "the environment variable of a flag of {} doesn't match its long name",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn every_arg_has_env_var() {
+        let app = cli_app("", "");
+        assert_args_have_env_vars(&app);
+
+        let commands = ["beacon_node", "validator_client", node::CMD];
+        for subcommand in &app.p.subcommands {
+            if commands.contains(&subcommand.p.meta.name.as_str()) {
+                assert_args_have_env_vars(subcommand);
+            }
+        }
+        assert_eq!(
+            app.p
+                .subcommands
+                .iter()
+                .filter(|subcommand| commands.contains(&subcommand.p.meta.name.as_str()))
+                .count(),
+            commands.len()
+        );
     }
 }
@@ -1,3 +1,4 @@
+use crate::version::BuildInfo;
 use lazy_static::lazy_static;
 pub use lighthouse_metrics::*;
 use lighthouse_version::VERSION;
@@ -17,6 +18,11 @@ lazy_static! {
         "The build of Lighthouse running on the server",
         &["version"],
     );
+    pub static ref LIGHTHOUSE_BUILD_INFO: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "lighthouse_build_info",
+        "Always 1, labelled with the version, commit and compile-time features of the build",
+        &["version", "git_commit", "bls_library", "allocator", "specs"],
+    );
 }
 
 pub fn expose_process_start_time(log: &Logger) {
@@ -33,3 +39,29 @@ pub fn expose_process_start_time(log: &Logger) {
 pub fn expose_lighthouse_version() {
     set_gauge_vec(&LIGHTHOUSE_VERSION, &[VERSION], 1);
 }
+
+pub fn expose_build_info() {
+    let info = BuildInfo::current();
+    let specs = [
+        ("mainnet", info.specs.mainnet),
+        ("minimal", info.specs.minimal),
+        ("gnosis", info.specs.gnosis),
+    ]
+    .iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(spec, _)| *spec)
+    .collect::<Vec<_>>()
+    .join(",");
+
+    set_gauge_vec(
+        &LIGHTHOUSE_BUILD_INFO,
+        &[
+            info.semver,
+            info.git_commit.unwrap_or("unknown"),
+            info.bls_library,
+            info.allocator,
+            specs.as_str(),
+        ],
+        1,
+    );
+}
@@ -0,0 +1,305 @@
+//! The `metrics snapshot` subcommand, which prints a summary of the metrics of a running beacon
+//! node and validator client, for quick checks without a dashboard.
+
+use clap::{App, AppSettings, Arg, ArgMatches};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const CMD: &str = "metrics";
+pub const SNAPSHOT_CMD: &str = "snapshot";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about("Inspects the metrics of a running beacon node or validator client.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            App::new(SNAPSHOT_CMD)
+                .about(
+                    "Prints a summary of the metrics of a running beacon node and validator \
+                     client, such as the head slot, peers, resource usage and validator \
+                     performance.",
+                )
+                .arg(
+                    Arg::with_name("beacon-node-metrics")
+                        .long("beacon-node-metrics")
+                        .value_name("URL")
+                        .help("The metrics endpoint of the beacon node.")
+                        .default_value("http://localhost:5054/metrics")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("validator-client-metrics")
+                        .long("validator-client-metrics")
+                        .value_name("URL")
+                        .help("The metrics endpoint of the validator client.")
+                        .default_value("http://localhost:5064/metrics")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("bearer-token-file")
+                        .long("bearer-token-file")
+                        .value_name("PATH")
+                        .help(
+                            "A file containing the bearer token required by the metrics \
+                             servers, if any.",
+                        )
+                        .takes_value(true),
+                ),
+        )
+}
+
+/// A single sample of the Prometheus text format.
+struct Sample {
+    name: String,
+    labels: String,
+    value: f64,
+}
+
+/// The samples scraped from a metrics endpoint.
+struct Metrics(Vec<Sample>);
+
+impl Metrics {
+    /// Parses metrics in the Prometheus text format, ignoring any invalid lines.
+    fn parse(text: &str) -> Self {
+        let samples = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let (series, rest) = match line.find('}') {
+                    Some(end) => line.split_at(end + 1),
+                    None => line.split_at(line.find(' ')?),
+                };
+                let (name, labels) = match series.split_once('{') {
+                    Some((name, labels)) => (name, labels.trim_end_matches('}')),
+                    None => (series, ""),
+                };
+                let value = rest.split_whitespace().next()?.parse().ok()?;
+                Some(Sample {
+                    name: name.to_string(),
+                    labels: labels.to_string(),
+                    value,
+                })
+            })
+            .collect();
+        Self(samples)
+    }
+
+    /// Returns the sum of the series called `name` with the given label, if any exist.
+    fn sum_with_label(&self, name: &str, label: Option<(&str, &str)>) -> Option<f64> {
+        let label = label.map(|(key, value)| format!("{}=\"{}\"", key, value));
+        let mut samples = self
+            .0
+            .iter()
+            .filter(|sample| sample.name == name)
+            .filter(|sample| {
+                label
+                    .as_ref()
+                    .map_or(true, |label| sample.labels.contains(label.as_str()))
+            })
+            .peekable();
+        samples.peek()?;
+        Some(samples.map(|sample| sample.value).sum())
+    }
+
+    /// Returns the sum of all the series called `name`, if any exist.
+    fn sum(&self, name: &str) -> Option<f64> {
+        self.sum_with_label(name, None)
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let matches = matches
+        .subcommand_matches(SNAPSHOT_CMD)
+        .ok_or_else(|| format!("Unknown subcommand, see `lighthouse {} --help`", CMD))?;
+    let bn_url: String = clap_utils::parse_required(matches, "beacon-node-metrics")?;
+    let vc_url: String = clap_utils::parse_required(matches, "validator-client-metrics")?;
+    let token = clap_utils::parse_optional::<PathBuf>(matches, "bearer-token-file")?
+        .map(|path| {
+            fs::read_to_string(&path)
+                .map(|token| token.trim().to_string())
+                .map_err(|e| format!("Unable to read {:?}: {}", path, e))
+        })
+        .transpose()?;
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Unable to build HTTP client: {}", e))?;
+    let fetch = |url: &str| -> Result<Metrics, String> {
+        let mut request = client.get(url);
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        request
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map(|text| Metrics::parse(&text))
+            .map_err(|e| e.to_string())
+    };
+
+    let beacon_node = fetch(&bn_url);
+    let validator_client = fetch(&vc_url);
+
+    println!("Beacon node ({})", bn_url);
+    match &beacon_node {
+        Ok(metrics) => print!("{}", beacon_node_summary(metrics)),
+        Err(e) => println!("  unavailable: {}", e),
+    }
+    println!("Validator client ({})", vc_url);
+    match &validator_client {
+        Ok(metrics) => print!("{}", validator_client_summary(metrics)),
+        Err(e) => println!("  unavailable: {}", e),
+    }
+
+    if beacon_node.is_err() && validator_client.is_err() {
+        Err("Unable to read metrics from the beacon node or validator client".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn beacon_node_summary(metrics: &Metrics) -> String {
+    let mut summary = String::new();
+
+    let head_slot = metrics.sum("beacon_head_state_slot");
+    let current_slot = metrics.sum("slotclock_present_slot");
+    let head = match (head_slot, current_slot) {
+        (Some(head), Some(current)) => format!(
+            "{} (current slot {}, {} behind)",
+            head,
+            current,
+            (current - head).max(0.0)
+        ),
+        (Some(head), None) => head.to_string(),
+        _ => "unknown".to_string(),
+    };
+    let _ = writeln!(summary, "  {:<18}{}", "Head slot:", head);
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "Synced:",
+        metrics
+            .sum("sync_eth2_synced")
+            .map_or("unknown", |synced| if synced > 0.0 { "yes" } else { "no" })
+    );
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "Finalized epoch:",
+        display(metrics.sum("beacon_head_state_finalized_epoch"))
+    );
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "Peers:",
+        display(metrics.sum("libp2p_peers"))
+    );
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "Database:",
+        match (
+            metrics.sum("store_disk_db_size"),
+            metrics.sum("store_freezer_db_size"),
+            metrics.sum("store_disk_db_free_space"),
+        ) {
+            (Some(hot), Some(cold), Some(free)) => format!(
+                "{:.1} GiB hot, {:.1} GiB freezer, {:.1} GiB free",
+                hot / GIB,
+                cold / GIB,
+                free / GIB
+            ),
+            (Some(hot), Some(cold), None) =>
+                format!("{:.1} GiB hot, {:.1} GiB freezer", hot / GIB, cold / GIB),
+            _ => "unknown".to_string(),
+        }
+    );
+    summary.push_str(&process_summary(metrics));
+    summary
+}
+
+fn validator_client_summary(metrics: &Metrics) -> String {
+    let mut summary = String::new();
+
+    let _ = writeln!(
+        summary,
+        "  {:<18}{} enabled of {}",
+        "Validators:",
+        display(metrics.sum("vc_validators_enabled_count")),
+        display(metrics.sum("vc_validators_total_count"))
+    );
+    let _ = writeln!(
+        summary,
+        "  {:<18}{} synced of {}",
+        "Beacon nodes:",
+        display(metrics.sum("vc_beacon_nodes_synced_count")),
+        display(metrics.sum("vc_beacon_nodes_total_count"))
+    );
+    // Only available if per-validator metrics are enabled.
+    if let Some(included) = metrics.sum("vc_validator_attestations_included_total") {
+        let _ = writeln!(
+            summary,
+            "  {:<18}{} included, {} missed",
+            "Attestations:",
+            included,
+            metrics
+                .sum("vc_validator_attestations_missed_total")
+                .unwrap_or(0.0)
+        );
+    }
+    if metrics.sum("vc_validator_proposals_total").is_some() {
+        let proposals = |status| {
+            metrics
+                .sum_with_label("vc_validator_proposals_total", Some(("status", status)))
+                .unwrap_or(0.0)
+        };
+        let _ = writeln!(
+            summary,
+            "  {:<18}{} proposed, {} missed",
+            "Proposals:",
+            proposals("proposed"),
+            proposals("missed")
+        );
+    }
+    summary.push_str(&process_summary(metrics));
+    summary
+}
+
+/// Summarises the resource usage of the process.
+fn process_summary(metrics: &Metrics) -> String {
+    let mut summary = String::new();
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "Memory:",
+        metrics.sum("process_resident_memory_bytes").map_or(
+            "unknown".to_string(),
+            |bytes| format!("{:.2} GiB resident", bytes / GIB)
+        )
+    );
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "CPU time:",
+        metrics
+            .sum("process_cpu_seconds_total")
+            .map_or("unknown".to_string(), |secs| format!("{:.0}s", secs))
+    );
+    let _ = writeln!(
+        summary,
+        "  {:<18}{}",
+        "Load average:",
+        display(metrics.sum("system_loadavg_1"))
+    );
+    summary
+}
+
+fn display(value: Option<f64>) -> String {
+    value.map_or("unknown".to_string(), |value| value.to_string())
+}
@@ -0,0 +1,219 @@
+//! The `node` subcommand runs a beacon node and a validator client inside a single process.
+//!
+//! Both services share the same tokio runtime and `Environment`. The validator client is
+//! configured to use the HTTP API of the in-process beacon node, which is always enabled.
+
+use beacon_node::ClientConfig;
+use clap::{App, Arg, ArgMatches};
+use clap_utils::{
+    flags::CONFIG_FILE_FLAG, parse_flag, parse_optional, parse_required, use_cli_value,
+};
+use directory::{get_network_dir, DEFAULT_ROOT_DIR, DEFAULT_SECRET_DIR, DEFAULT_VALIDATOR_DIR};
+use sensitive_url::SensitiveUrl;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use types::Address;
+
+pub const CMD: &str = "node";
+
+/// The configuration of both services, as written by `--dump-config`.
+#[derive(Serialize, Deserialize)]
+pub struct NodeConfig {
+    pub beacon_node: ClientConfig,
+    pub validator_client: validator_client::Config,
+}
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    beacon_node::cli_args(
+        App::new(CMD)
+            .visible_alias("n")
+            .author("Sigma Prime <contact@sigmaprime.io>")
+            .setting(clap::AppSettings::ColoredHelp)
+            .about(
+                "Runs a beacon node and a validator client in the same process. Accepts all \
+                 beacon node flags, along with the validator client flags listed below. The \
+                 validator client always connects to the HTTP API of the beacon node it is \
+                 bundled with.",
+            ),
+    )
+    .arg(
+        Arg::with_name("validators-dir")
+            .long("validators-dir")
+            .alias("validator-dir")
+            .value_name("VALIDATORS_DIR")
+            .help(
+                "The directory which contains the validator keystores, deposit data for \
+                 each validator along with the common slashing protection database \
+                 and the validator_definitions.yml",
+            )
+            .takes_value(true)
+            .env("LIGHTHOUSE_VALIDATORS_DIR")
+            .conflicts_with("datadir"),
+    )
+    .arg(
+        Arg::with_name("secrets-dir")
+            .long("secrets-dir")
+            .value_name("SECRETS_DIRECTORY")
+            .help(
+                "The directory which contains the password to unlock the validator \
+                 voting keypairs. Defaults to ~/.lighthouse/{network}/secrets.",
+            )
+            .takes_value(true)
+            .env("LIGHTHOUSE_SECRETS_DIR")
+            .conflicts_with("datadir"),
+    )
+    .arg(
+        Arg::with_name("init-slashing-protection")
+            .long("init-slashing-protection")
+            .help(
+                "If present, do not require the slashing protection database to exist before \
+                 running. You SHOULD NOT use this flag unless you're certain that a new \
+                 slashing protection database is required.",
+            ),
+    )
+    .arg(
+        Arg::with_name("disable-auto-discover")
+            .long("disable-auto-discover")
+            .help(
+                "If present, do not attempt to discover new validators in the validators-dir. \
+                 Validators will need to be manually added to the validator_definitions.yml file.",
+            ),
+    )
+    .arg(
+        Arg::with_name("enable-doppelganger-protection")
+            .long("enable-doppelganger-protection")
+            .help(
+                "If this flag is set, the validator client will delay startup for three epochs \
+                 and monitor for messages on the network by any of the validators it manages.",
+            )
+            .takes_value(false),
+    )
+    .arg(
+        Arg::with_name("builder-proposals")
+            .long("builder-proposals")
+            .help(
+                "If this flag is set, the validator client will query the beacon node for only \
+                 block headers during proposals and will sign over headers.",
+            )
+            .takes_value(false),
+    )
+    .arg(
+        Arg::with_name("gas-limit")
+            .long("gas-limit")
+            .value_name("INTEGER")
+            .takes_value(true)
+            .env("LIGHTHOUSE_GAS_LIMIT")
+            .help(
+                "The gas limit to be used in all builder proposals for all validators managed \
+                 by the validator client. [default: 30,000,000]",
+            )
+            .requires("builder-proposals"),
+    )
+}
+
+/// Returns the URL which the bundled validator client should use to reach the beacon node HTTP
+/// API listening on `listen_addr`.
+pub fn beacon_node_url(listen_addr: SocketAddr) -> Result<SensitiveUrl, String> {
+    let ip = match listen_addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    SensitiveUrl::parse(&format!(
+        "http://{}/",
+        SocketAddr::new(ip, listen_addr.port())
+    ))
+    .map_err(|e| format!("Unable to build beacon node URL: {:?}", e))
+}
+
+/// Builds the validator client configuration from the `node` subcommand arguments.
+///
+/// Only the validator client flags exposed by `cli_app` are considered. Flags which are shared
+/// with the beacon node (e.g. `--graffiti`, `--suggested-fee-recipient`) apply to both services.
+/// The HTTP and metrics servers of the validator client are left disabled to avoid clashing with
+/// those of the beacon node, unless enabled by the `--config-file`.
+pub fn validator_client_config(
+    cli_args: &ArgMatches,
+    beacon_node_config: &ClientConfig,
+) -> Result<validator_client::Config, String> {
+    let mut config = match parse_optional::<PathBuf>(cli_args, CONFIG_FILE_FLAG)? {
+        Some(path) => clap_utils::load_config_file(&path, "validator_client")?,
+        None => validator_client::Config::default(),
+    };
+
+    let default_root_dir = dirs::home_dir()
+        .map(|home| home.join(DEFAULT_ROOT_DIR))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let (mut validator_dir, mut secrets_dir) = (None, None);
+    if cli_args.value_of("datadir").is_some() {
+        let base_dir: PathBuf = parse_required(cli_args, "datadir")?;
+        validator_dir = Some(base_dir.join(DEFAULT_VALIDATOR_DIR));
+        secrets_dir = Some(base_dir.join(DEFAULT_SECRET_DIR));
+    }
+    if cli_args.value_of("validators-dir").is_some() {
+        validator_dir = Some(parse_required(cli_args, "validators-dir")?);
+    }
+    if cli_args.value_of("secrets-dir").is_some() {
+        secrets_dir = Some(parse_required(cli_args, "secrets-dir")?);
+    }
+
+    let use_default_dirs = !cli_args.is_present(CONFIG_FILE_FLAG);
+    if let Some(validator_dir) = validator_dir {
+        config.validator_dir = validator_dir;
+    } else if use_default_dirs {
+        config.validator_dir = default_root_dir
+            .join(get_network_dir(cli_args))
+            .join(DEFAULT_VALIDATOR_DIR);
+    }
+    if let Some(secrets_dir) = secrets_dir {
+        config.secrets_dir = secrets_dir;
+    } else if use_default_dirs {
+        config.secrets_dir = default_root_dir
+            .join(get_network_dir(cli_args))
+            .join(DEFAULT_SECRET_DIR);
+    }
+
+    // A dry run reports a missing directory rather than creating it.
+    if !config.validator_dir.exists() && !parse_flag(cli_args, "dry-run")? {
+        fs::create_dir_all(&config.validator_dir)
+            .map_err(|e| format!("Failed to create {:?}: {:?}", config.validator_dir, e))?;
+    }
+
+    // This value is replaced with the actual listen address once the beacon node has started,
+    // which matters when the OS picks the port (e.g. `--zero-ports`).
+    config.beacon_nodes = vec![beacon_node_url(SocketAddr::new(
+        beacon_node_config.http_api.listen_addr,
+        beacon_node_config.http_api.listen_port,
+    ))?];
+
+    for (name, value) in [
+        (
+            "init-slashing-protection",
+            &mut config.init_slashing_protection,
+        ),
+        ("disable-auto-discover", &mut config.disable_auto_discover),
+        (
+            "enable-doppelganger-protection",
+            &mut config.enable_doppelganger_protection,
+        ),
+        ("builder-proposals", &mut config.builder_proposals),
+    ] {
+        if use_cli_value(cli_args, name) {
+            *value = parse_flag(cli_args, name)?;
+        }
+    }
+    if use_cli_value(cli_args, "gas-limit") {
+        config.gas_limit = parse_optional(cli_args, "gas-limit")?;
+    }
+    if cli_args.is_present("graffiti") {
+        config.graffiti = Some(beacon_node_config.graffiti);
+    }
+    if use_cli_value(cli_args, "suggested-fee-recipient") {
+        config.fee_recipient = parse_optional::<Address>(cli_args, "suggested-fee-recipient")?;
+    }
+
+    Ok(config)
+}
@@ -0,0 +1,45 @@
+//! The `tools` subcommand, which groups utilities for benchmarking and debugging a beacon node
+//! against its database.
+
+use clap::{App, AppSettings, Arg, ArgMatches};
+use database_manager::replay_blocks;
+use environment::Environment;
+use slog::info;
+use types::EthSpec;
+
+pub const CMD: &str = "tools";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about("Utilities for benchmarking and debugging a beacon node using its database.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("freezer-dir")
+                .long("freezer-dir")
+                .value_name("DIR")
+                .help("Data directory for the freezer database.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("db-backend")
+                .long("db-backend")
+                .value_name("DATABASE")
+                .help("The database backend used by the hot and freezer databases.")
+                .takes_value(true),
+        )
+        .subcommand(replay_blocks::cli_app())
+}
+
+pub fn run<E: EthSpec>(matches: &ArgMatches, env: Environment<E>) -> Result<(), String> {
+    let client_config = database_manager::parse_client_config(matches, &env)?;
+    let context = env.core_context();
+    let log = context.log().clone();
+
+    match matches.subcommand() {
+        (replay_blocks::CMD, Some(sub_matches)) => {
+            info!(log, "Replaying blocks from the database");
+            replay_blocks::run(sub_matches, client_config, &context, log)
+        }
+        _ => Err("Unknown tools subcommand".into()),
+    }
+}
@@ -0,0 +1,113 @@
+//! The `version` subcommand, which prints information about this build of Lighthouse.
+
+use crate::{allocator_name, bls_library_name};
+use clap::{App, Arg, ArgMatches};
+use eth2_hashing::have_sha_extensions;
+use lighthouse_version::VERSION;
+use serde::Serialize;
+
+pub const CMD: &str = "version";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about("Prints information about this build of Lighthouse.")
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Prints the information as a JSON document, for use by monitoring tools."),
+        )
+}
+
+/// The spec presets supported by this build.
+#[derive(Serialize)]
+pub struct Specs {
+    pub mainnet: bool,
+    pub minimal: bool,
+    pub gnosis: bool,
+}
+
+/// Information about this build of Lighthouse.
+#[derive(Serialize)]
+pub struct BuildInfo {
+    /// The full version string, e.g. `Lighthouse/v4.1.0-67da032+`.
+    pub version: &'static str,
+    /// The semantic version, e.g. `4.1.0`.
+    pub semver: &'static str,
+    /// The abbreviated git commit, if known.
+    pub git_commit: Option<&'static str>,
+    /// True if the build was made from a git tree with uncommitted changes.
+    pub git_dirty: bool,
+    pub bls_library: &'static str,
+    pub sha256_hardware_acceleration: bool,
+    pub allocator: &'static str,
+    pub specs: Specs,
+    /// The target the binary was compiled for, as `{arch}-{os}` followed by `-{env}` if the
+    /// target has an environment (e.g. `x86_64-linux-gnu`).
+    pub target: String,
+    /// `debug` if debug assertions are enabled, otherwise `release`.
+    pub profile: &'static str,
+}
+
+impl BuildInfo {
+    /// Returns the information about the running binary.
+    pub fn current() -> Self {
+        let version = VERSION.strip_prefix("Lighthouse/v").unwrap_or(VERSION);
+        let (semver, git_commit) = match version.split_once('-') {
+            // Release candidates have a suffix like `-rc.0` before the commit.
+            Some((semver, rest)) if rest.starts_with("rc.") => match rest.split_once('-') {
+                Some((rc, commit)) => (&version[..semver.len() + 1 + rc.len()], Some(commit)),
+                None => (version, None),
+            },
+            Some((semver, commit)) => (semver, Some(commit)),
+            None => (version, None),
+        };
+        let git_dirty = git_commit.map_or(false, |commit| commit.ends_with('+'));
+
+        let target_env = if cfg!(target_env = "gnu") {
+            "-gnu"
+        } else if cfg!(target_env = "musl") {
+            "-musl"
+        } else if cfg!(target_env = "msvc") {
+            "-msvc"
+        } else {
+            ""
+        };
+
+        Self {
+            version: VERSION,
+            semver,
+            git_commit: git_commit.map(|commit| commit.trim_end_matches('+')),
+            git_dirty,
+            bls_library: bls_library_name(),
+            sha256_hardware_acceleration: have_sha_extensions(),
+            allocator: allocator_name(),
+            specs: Specs {
+                mainnet: true,
+                minimal: cfg!(feature = "spec-minimal"),
+                gnosis: cfg!(feature = "gnosis"),
+            },
+            target: format!(
+                "{}-{}{}",
+                std::env::consts::ARCH,
+                std::env::consts::OS,
+                target_env
+            ),
+            profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+        }
+    }
+}
+
+pub fn run(matches: &ArgMatches, long_version: &str) -> Result<(), String> {
+    if matches.is_present("json") {
+        let info = serde_json::to_string_pretty(&BuildInfo::current())
+            .map_err(|e| format!("Unable to serialize build info: {:?}", e))?;
+        println!("{}", info);
+    } else {
+        println!("{}", long_version);
+    }
+    Ok(())
+}
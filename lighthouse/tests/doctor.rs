@@ -0,0 +1,43 @@
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Returns the `lighthouse doctor` command.
+fn base_cmd() -> Command {
+    let lighthouse_bin = env!("CARGO_BIN_EXE_lighthouse");
+    let path = lighthouse_bin
+        .parse::<PathBuf>()
+        .expect("should parse CARGO_TARGET_DIR");
+
+    let mut cmd = Command::new(path);
+    cmd.arg("doctor");
+    cmd
+}
+
+#[test]
+fn doctor_port_in_use() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let listener =
+        TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).expect("should bind an unused port");
+    let port = listener.local_addr().unwrap().port();
+
+    let output = base_cmd()
+        .arg("--datadir")
+        .arg(dir.path().as_os_str())
+        .arg("--port")
+        .arg(port.to_string())
+        // Nothing listens on this port, so the clock check is skipped immediately.
+        .arg("--ntp-server")
+        .arg("127.0.0.1:1")
+        .output()
+        .expect("should run command");
+    let stdout = String::from_utf8(output.stdout).expect("stdout is not utf8");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("FAIL  TCP port"), "{}", stdout);
+    assert!(stdout.contains("WARN  System clock"), "{}", stdout);
+    for check in ["Disk space", "UDP port", "Open files limit", "CPU"] {
+        assert!(stdout.contains(check), "{}", stdout);
+    }
+}
@@ -21,6 +21,12 @@ pub trait CommandLineTestExec {
         self
     }
 
+    /// Sets an environment variable for the command.
+    fn env(&mut self, key: &str, value: &str) -> &mut Self {
+        self.cmd_mut().env(key, value);
+        self
+    }
+
     /// Executes the `Command` returned by `Self::cmd_mut` with temporary data directory, dumps
     /// the configuration and shuts down immediately.
     ///
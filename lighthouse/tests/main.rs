@@ -3,5 +3,9 @@
 mod account_manager;
 mod beacon_node;
 mod boot_node;
+mod doctor;
 mod exec;
+mod metrics_snapshot;
+mod node;
 mod validator_client;
+mod version;
@@ -0,0 +1,86 @@
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+
+const METRICS: &str = "# HELP beacon_head_state_slot The slot of the head state.\n\
+                       # TYPE beacon_head_state_slot gauge\n\
+                       beacon_head_state_slot 100\n\
+                       slotclock_present_slot 102\n\
+                       sync_eth2_synced 1\n\
+                       libp2p_peers 42\n";
+
+/// Returns the `lighthouse metrics snapshot` command.
+fn base_cmd() -> Command {
+    let lighthouse_bin = env!("CARGO_BIN_EXE_lighthouse");
+    let path = lighthouse_bin
+        .parse::<PathBuf>()
+        .expect("should parse CARGO_TARGET_DIR");
+
+    let mut cmd = Command::new(path);
+    cmd.arg("metrics").arg("snapshot");
+    cmd
+}
+
+/// Serves `METRICS` in response to a single HTTP request, returning the URL.
+fn serve_metrics() -> String {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("should bind");
+    let url = format!("http://{}/metrics", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("should accept");
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+            METRICS.len(),
+            METRICS
+        );
+        stream
+            .write_all(response.as_bytes())
+            .expect("should respond");
+    });
+    url
+}
+
+/// Returns the URL of a port on which nothing is listening.
+fn unused_url() -> String {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("should bind");
+    format!("http://{}/metrics", listener.local_addr().unwrap())
+}
+
+#[test]
+fn metrics_snapshot_beacon_node() {
+    let output = base_cmd()
+        .arg("--beacon-node-metrics")
+        .arg(serve_metrics())
+        .arg("--validator-client-metrics")
+        .arg(unused_url())
+        .output()
+        .expect("should run command");
+    let stdout = String::from_utf8(output.stdout).expect("stdout is not utf8");
+
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(
+        stdout.contains("Head slot:        100 (current slot 102, 2 behind)"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Synced:           yes"), "{}", stdout);
+    assert!(stdout.contains("Peers:            42"), "{}", stdout);
+    assert!(stdout.contains("Finalized epoch:  unknown"), "{}", stdout);
+    assert!(stdout.contains("  unavailable: "), "{}", stdout);
+}
+
+#[test]
+fn metrics_snapshot_unavailable() {
+    let output = base_cmd()
+        .arg("--beacon-node-metrics")
+        .arg(unused_url())
+        .arg("--validator-client-metrics")
+        .arg(unused_url())
+        .output()
+        .expect("should run command");
+
+    assert_eq!(output.status.code(), Some(1));
+}
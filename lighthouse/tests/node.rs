@@ -0,0 +1,157 @@
+use beacon_node::ClientConfig;
+use serde::Deserialize;
+
+use crate::exec::{CommandLineTestExec, CompletedTest};
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+use tempfile::TempDir;
+use types::Address;
+
+/// Mirrors the configuration dumped by `lighthouse node --dump-config`.
+#[derive(Deserialize)]
+struct Config {
+    beacon_node: ClientConfig,
+    validator_client: validator_client::Config,
+}
+
+/// Returns the `lighthouse node` command.
+fn base_cmd() -> Command {
+    let lighthouse_bin = env!("CARGO_BIN_EXE_lighthouse");
+    let path = lighthouse_bin
+        .parse::<PathBuf>()
+        .expect("should parse CARGO_TARGET_DIR");
+
+    let mut cmd = Command::new(path);
+    cmd.arg("node");
+    cmd
+}
+
+// Wrapper around `Command` for easier Command Line Testing.
+struct CommandLineTest {
+    cmd: Command,
+}
+impl CommandLineTest {
+    fn new() -> CommandLineTest {
+        let base_cmd = base_cmd();
+        CommandLineTest { cmd: base_cmd }
+    }
+
+    fn run_with_zero_port(&mut self) -> CompletedTest<Config> {
+        self.cmd.arg("-z");
+        self.run()
+    }
+}
+
+impl CommandLineTestExec for CommandLineTest {
+    type Config = Config;
+
+    fn cmd_mut(&mut self) -> &mut Command {
+        &mut self.cmd
+    }
+}
+
+#[test]
+fn datadir_flag() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config_and_dir(|config, dir| {
+            assert_eq!(*config.beacon_node.data_dir(), dir.path().join("beacon"));
+            assert_eq!(
+                config.validator_client.validator_dir,
+                dir.path().join("validators")
+            );
+            assert_eq!(
+                config.validator_client.secrets_dir,
+                dir.path().join("secrets")
+            );
+        });
+}
+
+#[test]
+fn http_api_always_enabled() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert!(config.beacon_node.http_api.enabled));
+}
+
+#[test]
+fn validator_client_uses_bundled_beacon_node() {
+    CommandLineTest::new()
+        .flag("http-address", Some("0.0.0.0"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.validator_client.beacon_nodes.len(), 1);
+            assert_eq!(
+                config.validator_client.beacon_nodes[0].full.to_string(),
+                "http://127.0.0.1:0/"
+            );
+        });
+}
+
+#[test]
+fn validators_and_secrets_dir_flags() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    CommandLineTest::new()
+        .flag("validators-dir", dir.path().join("validators").to_str())
+        .flag("secrets-dir", dir.path().join("secrets").to_str())
+        .flag("zero-ports", None)
+        .run_with_no_datadir()
+        .with_config(|config| {
+            assert_eq!(
+                config.validator_client.validator_dir,
+                dir.path().join("validators")
+            );
+            assert_eq!(
+                config.validator_client.secrets_dir,
+                dir.path().join("secrets")
+            );
+        });
+}
+
+#[test]
+fn shared_fee_recipient_flag() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let jwt_path = dir.path().join("jwt.hex");
+    std::fs::write(&jwt_path, "0".repeat(64)).expect("Unable to write jwt file");
+    CommandLineTest::new()
+        .flag("execution-endpoint", Some("http://localhost:8551/"))
+        .flag("execution-jwt", jwt_path.to_str())
+        .flag(
+            "suggested-fee-recipient",
+            Some("0x00000000219ab540356cbb839cbe05303d7705fa"),
+        )
+        .run_with_zero_port()
+        .with_config(|config| {
+            let expected =
+                Some(Address::from_str("0x00000000219ab540356cbb839cbe05303d7705fa").unwrap());
+            assert_eq!(config.validator_client.fee_recipient, expected);
+            assert_eq!(
+                config
+                    .beacon_node
+                    .execution_layer
+                    .as_ref()
+                    .unwrap()
+                    .suggested_fee_recipient,
+                expected
+            );
+        });
+}
+
+#[test]
+fn config_file_sections() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "[beacon_node.network]\ntarget_peers = 42\n\n[validator_client]\ninit_slashing_protection = true\n",
+    )
+    .expect("Unable to write config file");
+    CommandLineTest::new()
+        .flag("config-file", path.to_str())
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.beacon_node.network.target_peers, 42);
+            assert!(config.validator_client.init_slashing_protection);
+        });
+}
@@ -9,6 +9,7 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::str::FromStr;
 use std::string::ToString;
+use std::time::Duration;
 use tempfile::TempDir;
 use types::Address;
 
@@ -363,6 +364,54 @@ fn metrics_allow_origin_all_flag() {
         .with_config(|config| assert_eq!(config.http_metrics.allow_origin, Some("*".to_string())));
 }
 #[test]
+fn metrics_tls_flags() {
+    CommandLineTest::new()
+        .flag("metrics-tls-cert", Some("tls.crt"))
+        .flag("metrics-tls-key", Some("tls.key"))
+        .run()
+        .with_config(|config| {
+            let tls_config = config.http_metrics.tls_config.as_ref().unwrap();
+            assert_eq!(tls_config.cert, PathBuf::from("tls.crt"));
+            assert_eq!(tls_config.key, PathBuf::from("tls.key"));
+        });
+}
+#[test]
+fn metrics_pushgateway_flag() {
+    CommandLineTest::new()
+        .flag("metrics-pushgateway", Some("http://localhost:9091"))
+        .flag("metrics-pushgateway-job", Some("vc"))
+        .run()
+        .with_config(|config| {
+            let pushgateway = config.http_metrics.pushgateway.as_ref().unwrap();
+            assert_eq!(pushgateway.interval, Duration::from_secs(15));
+            assert_eq!(pushgateway.job, "vc");
+        });
+}
+#[test]
+fn metrics_buckets_flag() {
+    CommandLineTest::new()
+        .flag("metrics-buckets", Some("vc_beacon_node_latency=0.01,0.1"))
+        .run()
+        .with_config(|config| {
+            assert_eq!(
+                config.http_metrics.histogram_buckets["vc_beacon_node_latency"],
+                vec![0.01, 0.1]
+            )
+        });
+}
+#[test]
+fn metrics_denylist_flag() {
+    CommandLineTest::new()
+        .flag("metrics-denylist", Some("vc_signed_*,vc_beacon_node_*"))
+        .run()
+        .with_config(|config| {
+            assert_eq!(
+                config.http_metrics.denylist,
+                vec!["vc_signed_*", "vc_beacon_node_*"]
+            )
+        });
+}
+#[test]
 pub fn malloc_tuning_flag() {
     CommandLineTest::new()
         .flag("disable-malloc-tuning", None)
@@ -461,6 +510,20 @@ fn monitoring_endpoint() {
         });
 }
 #[test]
+fn disable_ntp_check_flag() {
+    CommandLineTest::new()
+        .flag("disable-ntp-check", None)
+        .run()
+        .with_config(|config| assert_eq!(config.clock_drift, None));
+}
+#[test]
+fn watchdog_stall_slots_flag() {
+    CommandLineTest::new()
+        .flag("watchdog-stall-slots", Some("4"))
+        .run()
+        .with_config(|config| assert_eq!(config.watchdog_stall_slots, Some(4)));
+}
+#[test]
 fn disable_run_on_all_default() {
     CommandLineTest::new().run().with_config(|config| {
         assert!(!config.disable_run_on_all);
@@ -501,3 +564,93 @@ fn latency_measurement_service() {
             assert!(!config.enable_latency_measurement_service);
         });
 }
+
+#[test]
+fn config_file_flag() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let path = dir.path().join("config.toml");
+    let mut file = File::create(&path).expect("Unable to create config file");
+    file.write_all(
+        b"beacon_nodes = [\"http://localhost:1001/\"]\n\
+          enable_latency_measurement_service = false\n\
+          \n\
+          [http_metrics]\n\
+          listen_port = 6064\n",
+    )
+    .expect("Unable to write to config file");
+    CommandLineTest::new()
+        .flag("config-file", path.to_str())
+        .run()
+        .with_config(|config| {
+            assert_eq!(
+                config.beacon_nodes[0].full.to_string(),
+                "http://localhost:1001/"
+            );
+            assert!(!config.enable_latency_measurement_service);
+            assert_eq!(config.http_metrics.listen_port, 6064);
+        });
+}
+
+#[test]
+fn config_file_flags_take_precedence() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let path = dir.path().join("config.yaml");
+    let mut file = File::create(&path).expect("Unable to create config file");
+    file.write_all(
+        b"enable_latency_measurement_service: false\nhttp_metrics:\n  listen_port: 6064\n",
+    )
+    .expect("Unable to write to config file");
+    CommandLineTest::new()
+        .flag("config-file", path.to_str())
+        .flag("latency-measurement-service", Some("true"))
+        .flag("metrics-port", Some("7064"))
+        .run()
+        .with_config(|config| {
+            assert!(config.enable_latency_measurement_service);
+            assert_eq!(config.http_metrics.listen_port, 7064);
+        });
+}
+
+#[test]
+fn dry_run_missing_keystore() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let validators_dir = dir.path().join("validators");
+    std::fs::create_dir_all(&validators_dir).expect("Unable to create validators dir");
+    let mut file = File::create(validators_dir.join("validator_definitions.yml"))
+        .expect("Unable to create definitions file");
+    write!(
+        file,
+        "---\n- enabled: true\n  voting_public_key: \"{}\"\n  type: local_keystore\n  \
+         voting_keystore_path: {}\n",
+        Keypair::random().pk,
+        dir.path().join("missing.json").display()
+    )
+    .expect("Unable to write definitions file");
+
+    let output = base_cmd()
+        .arg("--datadir")
+        .arg(dir.path().as_os_str())
+        .arg("--dry-run")
+        .output()
+        .expect("should run command");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).expect("stderr is not utf8");
+    assert!(stderr.contains("missing.json"));
+}
+
+#[test]
+fn dry_run_missing_validators_dir() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let validators_dir = dir.path().join("validators");
+
+    let output = base_cmd()
+        .arg("--datadir")
+        .arg(dir.path().as_os_str())
+        .arg("--dry-run")
+        .output()
+        .expect("should run command");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).expect("stderr is not utf8");
+    assert!(stderr.contains("validators directory"));
+    assert!(!validators_dir.exists());
+}
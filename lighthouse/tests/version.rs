@@ -0,0 +1,55 @@
+use lighthouse_version::VERSION;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Returns the `lighthouse version` command.
+fn base_cmd() -> Command {
+    let lighthouse_bin = env!("CARGO_BIN_EXE_lighthouse");
+    let path = lighthouse_bin
+        .parse::<PathBuf>()
+        .expect("should parse CARGO_TARGET_DIR");
+
+    let mut cmd = Command::new(path);
+    cmd.arg("version");
+    cmd
+}
+
+#[test]
+fn version_plain() {
+    let output = base_cmd().output().expect("should run command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout is not utf8");
+    assert!(stdout.starts_with(&VERSION.replace("Lighthouse/", "")));
+    assert!(stdout.contains("BLS library: "));
+}
+
+#[test]
+fn version_json() {
+    let output = base_cmd()
+        .arg("--json")
+        .output()
+        .expect("should run command");
+    assert!(output.status.success());
+    let info: Value = serde_json::from_slice(&output.stdout).expect("should print valid JSON");
+    assert_eq!(info["version"], VERSION);
+    assert!(VERSION.contains(info["semver"].as_str().unwrap()));
+    assert_eq!(info["specs"]["mainnet"], true);
+    // The tests and the binary are built with the same profile.
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    assert_eq!(info["profile"], profile);
+    for key in [
+        "git_commit",
+        "git_dirty",
+        "bls_library",
+        "sha256_hardware_acceleration",
+        "allocator",
+        "target",
+    ] {
+        assert!(info.get(key).is_some(), "missing {}", key);
+    }
+}
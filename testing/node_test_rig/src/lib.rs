@@ -57,6 +57,7 @@ impl<E: EthSpec> LocalBeaconNode<E> {
                 client: client.into_inner(),
                 datadir,
             })
+            .map_err(Into::into)
     }
 }
 
@@ -213,6 +214,7 @@ impl<E: EthSpec> LocalValidatorClient<E> {
                     .expect("should start validator services");
                 Self { client, files }
             })
+            .map_err(Into::into)
     }
 }
 
@@ -52,10 +52,13 @@ fn syncing_sim(
             logfile_debug_level: String::from("debug"),
             log_format: log_format.map(String::from),
             logfile_format: None,
+            log_target: None,
+            log_filter: None,
             log_color: false,
             disable_log_timestamp: false,
             max_log_size: 0,
             max_log_number: 0,
+            max_log_age: 0,
             compression: false,
             is_restricted: true,
         })?
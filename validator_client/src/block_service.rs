@@ -15,8 +15,7 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
 use types::{
-    AbstractExecPayload, BlindedPayload, BlockType, EthSpec, FullPayload, Graffiti, PublicKeyBytes,
-    Slot,
+    AbstractExecPayload, BlindedPayload, BlockType, EthSpec, FullPayload, PublicKeyBytes, Slot,
 };
 
 #[derive(Debug)]
@@ -46,7 +45,6 @@ pub struct BlockServiceBuilder<T, E: EthSpec> {
     slot_clock: Option<Arc<T>>,
     beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
     context: Option<RuntimeContext<E>>,
-    graffiti: Option<Graffiti>,
     graffiti_file: Option<GraffitiFile>,
     block_delay: Option<Duration>,
 }
@@ -58,7 +56,6 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
             slot_clock: None,
             beacon_nodes: None,
             context: None,
-            graffiti: None,
             graffiti_file: None,
             block_delay: None,
         }
@@ -84,11 +81,6 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
         self
     }
 
-    pub fn graffiti(mut self, graffiti: Option<Graffiti>) -> Self {
-        self.graffiti = graffiti;
-        self
-    }
-
     pub fn graffiti_file(mut self, graffiti_file: Option<GraffitiFile>) -> Self {
         self.graffiti_file = graffiti_file;
         self
@@ -114,7 +106,6 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
                 context: self
                     .context
                     .ok_or("Cannot build BlockService without runtime_context")?,
-                graffiti: self.graffiti,
                 graffiti_file: self.graffiti_file,
                 block_delay: self.block_delay,
             }),
@@ -128,7 +119,6 @@ pub struct Inner<T, E: EthSpec> {
     slot_clock: Arc<T>,
     beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
     context: RuntimeContext<E>,
-    graffiti: Option<Graffiti>,
     graffiti_file: Option<GraffitiFile>,
     block_delay: Option<Duration>,
 }
@@ -327,7 +317,7 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
             log,
             self.graffiti_file.clone(),
             self.validator_store.graffiti(&validator_pubkey),
-            self.graffiti,
+            self.validator_store.graffiti_process(),
         );
 
         let randao_reveal_ref = &randao_reveal;
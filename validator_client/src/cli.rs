@@ -15,6 +15,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("NETWORK_ADDRESS")
                 .help("Deprecated. Use --beacon-nodes.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BEACON_NODE")
                 .conflicts_with("beacon-nodes"),
         )
         .arg(
@@ -24,7 +25,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Comma-separated addresses to one or more beacon node HTTP APIs. \
                        Default is http://localhost:5052."
                 )
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_BEACON_NODES"),
         )
         .arg(
             Arg::with_name("disable-run-on-all")
@@ -43,6 +45,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("NETWORK_ADDRESS")
                 .help("Deprecated. Use --beacon-nodes.")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SERVER")
                 .conflicts_with_all(&["beacon-node", "beacon-nodes"]),
         )
         .arg(
@@ -56,6 +59,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     and the validator_definitions.yml"
                 )
                 .takes_value(true)
+                .env("LIGHTHOUSE_VALIDATORS_DIR")
                 .conflicts_with("datadir")
         )
         .arg(
@@ -69,6 +73,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     key. Defaults to ~/.lighthouse/{network}/secrets.",
                 )
                 .takes_value(true)
+                .env("LIGHTHOUSE_SECRETS_DIR")
                 .conflicts_with("datadir")
         )
         .arg(
@@ -117,6 +122,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .long("beacon-nodes-tls-certs")
                 .value_name("CERTIFICATE-FILES")
                 .takes_value(true)
+                .env("LIGHTHOUSE_BEACON_NODES_TLS_CERTS")
                 .help("Comma-separated paths to custom TLS certificates to use when connecting \
                         to a beacon node. These certificates must be in PEM format and are used \
                         in addition to the OS trust store. Commas must only be used as a \
@@ -129,6 +135,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specify your custom graffiti to be included in blocks.")
                 .value_name("GRAFFITI")
                 .takes_value(true)
+                .env("LIGHTHOUSE_GRAFFITI")
         )
         .arg(
             Arg::with_name("graffiti-file")
@@ -136,6 +143,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specify a graffiti file to load validator graffitis from.")
                 .value_name("GRAFFITI-FILE")
                 .takes_value(true)
+                .env("LIGHTHOUSE_GRAFFITI_FILE")
                 .conflicts_with("graffiti")
         )
         .arg(
@@ -146,6 +154,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        configured in the validator definitions it takes priority over this value.")
                 .value_name("FEE-RECIPIENT")
                 .takes_value(true)
+                .env("LIGHTHOUSE_SUGGESTED_FEE_RECIPIENT")
         )
         /* REST API related arguments */
         .arg(
@@ -165,6 +174,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
              Arg::with_name("http-address")
                  .long("http-address")
                  .value_name("ADDRESS")
+                 .env("LIGHTHOUSE_HTTP_ADDRESS")
                  .help("Set the address for the HTTP address. The HTTP server is not encrypted \
                         and therefore it is unsafe to publish on a public network. When this \
                         flag is used, it additionally requires the explicit use of the \
@@ -186,7 +196,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("PORT")
                 .help("Set the listen TCP port for the RESTful HTTP API server.")
                 .default_value("5062")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_PORT"),
         )
         .arg(
             Arg::with_name("http-allow-origin")
@@ -196,7 +207,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     Use * to allow any origin (not recommended in production). \
                     If no value is supplied, the CORS allowed origin is set to the listen \
                     address of this server (e.g., http://localhost:5062).")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_HTTP_ALLOW_ORIGIN"),
         )
         /* Prometheus metrics HTTP server related arguments */
         .arg(
@@ -211,7 +223,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("ADDRESS")
                 .help("Set the listen address for the Prometheus metrics HTTP server.")
                 .default_value("127.0.0.1")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_ADDRESS"),
         )
         .arg(
             Arg::with_name("metrics-port")
@@ -219,7 +232,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("PORT")
                 .help("Set the listen TCP port for the Prometheus metrics HTTP server.")
                 .default_value("5064")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PORT"),
         )
         .arg(
             Arg::with_name("metrics-allow-origin")
@@ -229,7 +243,106 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     Use * to allow any origin (not recommended in production). \
                     If no value is supplied, the CORS allowed origin is set to the listen \
                     address of this server (e.g., http://localhost:5064).")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_ALLOW_ORIGIN"),
+        )
+        .arg(
+            Arg::with_name("metrics-tls-cert")
+                .long("metrics-tls-cert")
+                .value_name("PATH")
+                .help("The path of the certificate to be used when serving the metrics HTTP \
+                    server over TLS.")
+                .requires("metrics-tls-key")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_TLS_CERT"),
+        )
+        .arg(
+            Arg::with_name("metrics-tls-key")
+                .long("metrics-tls-key")
+                .value_name("PATH")
+                .help("The path of the private key to be used when serving the metrics HTTP \
+                    server over TLS. Must not be password-protected.")
+                .requires("metrics-tls-cert")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_TLS_KEY"),
+        )
+        .arg(
+            Arg::with_name("metrics-bearer-token-file")
+                .long("metrics-bearer-token-file")
+                .value_name("PATH")
+                .help("The path of a file containing a token which must be provided in an \
+                    `Authorization: Bearer <token>` header to access the metrics HTTP server.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_BEARER_TOKEN_FILE"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway")
+                .long("metrics-pushgateway")
+                .value_name("URL")
+                .help("Push all metrics to the Prometheus Pushgateway at this URL (e.g., \
+                    http://localhost:9091), for nodes which cannot be scraped. Works with or \
+                    without --metrics.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway-interval")
+                .long("metrics-pushgateway-interval")
+                .value_name("SECONDS")
+                .help("The number of seconds between pushes to the Pushgateway. Default: 15")
+                .requires("metrics-pushgateway")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY_INTERVAL"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway-job")
+                .long("metrics-pushgateway-job")
+                .value_name("JOB")
+                .help("The job label of metrics pushed to the Pushgateway. Default: validator_client")
+                .requires("metrics-pushgateway")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY_JOB"),
+        )
+        .arg(
+            Arg::with_name("metrics-pushgateway-instance")
+                .long("metrics-pushgateway-instance")
+                .value_name("INSTANCE")
+                .help("The instance label of metrics pushed to the Pushgateway. Defaults to the \
+                    hostname of the machine.")
+                .requires("metrics-pushgateway")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_PUSHGATEWAY_INSTANCE"),
+        )
+        .arg(
+            Arg::with_name("metrics-buckets")
+                .long("metrics-buckets")
+                .value_name("NAME=B1,B2,...")
+                .help("Overrides the bucket boundaries of the histogram metric with the given \
+                    name, e.g. beacon_block_processing_seconds=0.01,0.05,0.1,0.5. May be \
+                    provided multiple times to override several histograms.")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_BUCKETS"),
+        )
+        .arg(
+            Arg::with_name("metrics-allowlist")
+                .long("metrics-allowlist")
+                .value_name("PATTERNS")
+                .help("A comma-separated list of glob patterns (e.g. beacon_*,store_*). If \
+                    provided, only the metric families whose names match one of the patterns \
+                    are served or pushed.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_ALLOWLIST"),
+        )
+        .arg(
+            Arg::with_name("metrics-denylist")
+                .long("metrics-denylist")
+                .value_name("PATTERNS")
+                .help("A comma-separated list of glob patterns (e.g. gossipsub_*). Metric \
+                    families whose names match any of the patterns are not served or pushed.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_METRICS_DENYLIST"),
         )
         .arg(
             Arg::with_name("enable-high-validator-count-metrics")
@@ -253,7 +366,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 Note: This will send information to a remote sever which may identify and associate your \
                 validators, IP address and other personal information. Always use a HTTPS connection \
                 and never provide an untrusted URL.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_MONITORING_ENDPOINT"),
         )
         .arg(
             Arg::with_name("monitoring-endpoint-period")
@@ -262,7 +376,43 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Defines how many seconds to wait between each message sent to \
                        the monitoring-endpoint. Default: 60s")
                 .requires("monitoring-endpoint")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_MONITORING_ENDPOINT_PERIOD"),
+        )
+        .arg(
+            Arg::with_name("ntp-server")
+                .long("ntp-server")
+                .value_name("HOST:PORT")
+                .help("The NTP server used to check the system clock for drift. Default: \
+                       pool.ntp.org:123")
+                .takes_value(true)
+                .env("LIGHTHOUSE_NTP_SERVER"),
+        )
+        .arg(
+            Arg::with_name("ntp-drift-threshold")
+                .long("ntp-drift-threshold")
+                .value_name("MILLISECONDS")
+                .help("A warning is logged if the system clock differs from the NTP server by \
+                       more than this many milliseconds. Default: 500")
+                .takes_value(true)
+                .env("LIGHTHOUSE_NTP_DRIFT_THRESHOLD"),
+        )
+        .arg(
+            Arg::with_name("disable-ntp-check")
+                .long("disable-ntp-check")
+                .help("Disables checking the system clock for drift against an NTP server.")
+                .conflicts_with_all(&["ntp-server", "ntp-drift-threshold"])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("watchdog-stall-slots")
+                .long("watchdog-stall-slots")
+                .value_name("SLOTS")
+                .help("Shuts down the validator client if a critical service (the duties service) makes no \
+                       progress for this many slots, so that a process supervisor can restart \
+                       it. Must be at least 2. Disabled by default.")
+                .takes_value(true)
+                .env("LIGHTHOUSE_WATCHDOG_STALL_SLOTS"),
         )
         .arg(
             Arg::with_name("enable-doppelganger-protection")
@@ -306,13 +456,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .alias("builder-registration-timestamp-override")
                 .help("This flag takes a unix timestamp value that will be used to override the \
                     timestamp used in the builder api registration")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_BUILDER_REGISTRATION_TIMESTAMP_OVERRIDE"),
         )
         .arg(
             Arg::with_name("gas-limit")
                 .long("gas-limit")
                 .value_name("INTEGER")
                 .takes_value(true)
+                .env("LIGHTHOUSE_GAS_LIMIT")
                 .help("The gas limit to be used in all builder proposals for all validators managed \
                     by this validator client. Note this will not necessarily be used if the gas limit \
                     set here moves too far from the previous block's gas limit. [default: 30,000,000]")
@@ -325,7 +477,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Set to 'true' to enable a service that periodically attempts to measure latency to BNs. \
                     Set to 'false' to disable.")
                 .default_value("true")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_LATENCY_MEASUREMENT_SERVICE"),
         )
         /*
          * Experimental/development options.
@@ -337,6 +490,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .hidden(true)
                 .help("Time to delay block production from the start of the slot. Should only be \
                        used for testing.")
-                .takes_value(true),
+                .takes_value(true)
+                .env("LIGHTHOUSE_BLOCK_DELAY_MS"),
         )
 }
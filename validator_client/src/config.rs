@@ -1,18 +1,23 @@
 use crate::graffiti_file::GraffitiFile;
 use crate::{http_api, http_metrics};
 use clap::ArgMatches;
-use clap_utils::{flags::DISABLE_MALLOC_TUNING_FLAG, parse_optional, parse_required};
+use clap_utils::{
+    cli_value_of,
+    flags::{CONFIG_FILE_FLAG, DISABLE_MALLOC_TUNING_FLAG},
+    parse_flag, parse_optional, parse_required, use_cli_value,
+};
 use directory::{
     get_network_dir, DEFAULT_HARDCODED_NETWORK, DEFAULT_ROOT_DIR, DEFAULT_SECRET_DIR,
     DEFAULT_VALIDATOR_DIR,
 };
+use environment::LoggerSection;
 use eth2::types::Graffiti;
 use sensitive_url::SensitiveUrl;
 use serde_derive::{Deserialize, Serialize};
 use slog::{info, warn, Logger};
 use std::fs;
 use std::net::IpAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use types::{Address, GRAFFITI_BYTES_LEN};
 
@@ -50,6 +55,10 @@ pub struct Config {
     pub http_metrics: http_metrics::Config,
     /// Configuration for sending metrics to a remote explorer endpoint.
     pub monitoring_api: Option<monitoring_api::Config>,
+    /// Configuration for checking the system clock against an NTP server.
+    pub clock_drift: Option<clock_drift::Config>,
+    /// Shut down if the duties service makes no progress for this many slots.
+    pub watchdog_stall_slots: Option<u64>,
     /// If true, enable functionality that monitors the network for attestations or proposals from
     /// any of the validators managed by this client before starting up.
     pub enable_doppelganger_protection: bool,
@@ -105,6 +114,8 @@ impl Default for Config {
             http_api: <_>::default(),
             http_metrics: <_>::default(),
             monitoring_api: None,
+            clock_drift: Some(<_>::default()),
+            watchdog_stall_slots: None,
             enable_doppelganger_protection: false,
             enable_high_validator_count_metrics: false,
             beacon_nodes_tls_certs: None,
@@ -121,8 +132,14 @@ impl Default for Config {
 impl Config {
     /// Returns a `Default` implementation of `Self` with some parameters modified by the supplied
     /// `cli_args`.
+    ///
+    /// If a `--config-file` is provided it is used as the starting point, with any flags
+    /// explicitly supplied in `cli_args` taking precedence over the values in the file.
     pub fn from_cli(cli_args: &ArgMatches, log: &Logger) -> Result<Config, String> {
-        let mut config = Config::default();
+        let mut config = match parse_optional::<PathBuf>(cli_args, CONFIG_FILE_FLAG)? {
+            Some(path) => clap_utils::load_config_file(&path, "validator_client")?,
+            None => Config::default(),
+        };
 
         let default_root_dir = dirs::home_dir()
             .map(|home| home.join(DEFAULT_ROOT_DIR))
@@ -141,19 +158,27 @@ impl Config {
             secrets_dir = Some(parse_required(cli_args, "secrets-dir")?);
         }
 
-        config.validator_dir = validator_dir.unwrap_or_else(|| {
-            default_root_dir
+        // Directories from the config file are only replaced by those given on the command line.
+        let use_default_dirs = !cli_args.is_present(CONFIG_FILE_FLAG);
+
+        if let Some(validator_dir) = validator_dir {
+            config.validator_dir = validator_dir;
+        } else if use_default_dirs {
+            config.validator_dir = default_root_dir
                 .join(get_network_dir(cli_args))
-                .join(DEFAULT_VALIDATOR_DIR)
-        });
+                .join(DEFAULT_VALIDATOR_DIR);
+        }
 
-        config.secrets_dir = secrets_dir.unwrap_or_else(|| {
-            default_root_dir
+        if let Some(secrets_dir) = secrets_dir {
+            config.secrets_dir = secrets_dir;
+        } else if use_default_dirs {
+            config.secrets_dir = default_root_dir
                 .join(get_network_dir(cli_args))
-                .join(DEFAULT_SECRET_DIR)
-        });
+                .join(DEFAULT_SECRET_DIR);
+        }
 
-        if !config.validator_dir.exists() {
+        // A dry run reports a missing directory rather than creating it.
+        if !config.validator_dir.exists() && !parse_flag(cli_args, "dry-run")? {
             fs::create_dir_all(&config.validator_dir)
                 .map_err(|e| format!("Failed to create {:?}: {:?}", config.validator_dir, e))?;
         }
@@ -186,7 +211,7 @@ impl Config {
                 .map_err(|e| format!("Unable to parse beacon node URL: {:?}", e))?];
         }
 
-        if cli_args.is_present("delete-lockfiles") {
+        if parse_flag(cli_args, "delete-lockfiles")? {
             warn!(
                 log,
                 "The --delete-lockfiles flag is deprecated";
@@ -194,11 +219,21 @@ impl Config {
             );
         }
 
-        config.allow_unsynced_beacon_node = cli_args.is_present("allow-unsynced");
-        config.disable_run_on_all = cli_args.is_present("disable-run-on-all");
-        config.disable_auto_discover = cli_args.is_present("disable-auto-discover");
-        config.init_slashing_protection = cli_args.is_present("init-slashing-protection");
-        config.use_long_timeouts = cli_args.is_present("use-long-timeouts");
+        if use_cli_value(cli_args, "allow-unsynced") {
+            config.allow_unsynced_beacon_node = parse_flag(cli_args, "allow-unsynced")?;
+        }
+        if use_cli_value(cli_args, "disable-run-on-all") {
+            config.disable_run_on_all = parse_flag(cli_args, "disable-run-on-all")?;
+        }
+        if use_cli_value(cli_args, "disable-auto-discover") {
+            config.disable_auto_discover = parse_flag(cli_args, "disable-auto-discover")?;
+        }
+        if use_cli_value(cli_args, "init-slashing-protection") {
+            config.init_slashing_protection = parse_flag(cli_args, "init-slashing-protection")?;
+        }
+        if use_cli_value(cli_args, "use-long-timeouts") {
+            config.use_long_timeouts = parse_flag(cli_args, "use-long-timeouts")?;
+        }
 
         if let Some(graffiti_file_path) = cli_args.value_of("graffiti-file") {
             let mut graffiti_file = GraffitiFile::new(graffiti_file_path.into());
@@ -242,12 +277,12 @@ impl Config {
          * Http API server
          */
 
-        if cli_args.is_present("http") {
+        if parse_flag(cli_args, "http")? {
             config.http_api.enabled = true;
         }
 
         if let Some(address) = cli_args.value_of("http-address") {
-            if cli_args.is_present("unencrypted-http-transport") {
+            if parse_flag(cli_args, "unencrypted-http-transport")? {
                 config.http_api.listen_addr = address
                     .parse::<IpAddr>()
                     .map_err(|_| "http-address is not a valid IP address.")?;
@@ -259,7 +294,7 @@ impl Config {
             }
         }
 
-        if let Some(port) = cli_args.value_of("http-port") {
+        if let Some(port) = cli_value_of(cli_args, "http-port") {
             config.http_api.listen_port = port
                 .parse::<u16>()
                 .map_err(|_| "http-port is not a valid u16.")?;
@@ -278,21 +313,21 @@ impl Config {
          * Prometheus metrics HTTP server
          */
 
-        if cli_args.is_present("metrics") {
+        if parse_flag(cli_args, "metrics")? {
             config.http_metrics.enabled = true;
         }
 
-        if cli_args.is_present("enable-high-validator-count-metrics") {
+        if parse_flag(cli_args, "enable-high-validator-count-metrics")? {
             config.enable_high_validator_count_metrics = true;
         }
 
-        if let Some(address) = cli_args.value_of("metrics-address") {
+        if let Some(address) = cli_value_of(cli_args, "metrics-address") {
             config.http_metrics.listen_addr = address
                 .parse::<IpAddr>()
                 .map_err(|_| "metrics-address is not a valid IP address.")?;
         }
 
-        if let Some(port) = cli_args.value_of("metrics-port") {
+        if let Some(port) = cli_value_of(cli_args, "metrics-port") {
             config.http_metrics.listen_port = port
                 .parse::<u16>()
                 .map_err(|_| "metrics-port is not a valid u16.")?;
@@ -307,7 +342,64 @@ impl Config {
             config.http_metrics.allow_origin = Some(allow_origin.to_string());
         }
 
-        if cli_args.is_present(DISABLE_MALLOC_TUNING_FLAG) {
+        if let Some(cert) = parse_optional(cli_args, "metrics-tls-cert")? {
+            config.http_metrics.tls_config = Some(http_metrics::TlsConfig {
+                cert,
+                key: parse_required(cli_args, "metrics-tls-key")?,
+            });
+        }
+
+        if let Some(path) = parse_optional::<PathBuf>(cli_args, "metrics-bearer-token-file")? {
+            if !path.exists() {
+                return Err(format!(
+                    "metrics-bearer-token-file {:?} does not exist",
+                    path
+                ));
+            }
+            config.http_metrics.bearer_token_path = Some(path);
+        }
+
+        if let Some(url) = cli_args.value_of("metrics-pushgateway") {
+            let interval_secs = parse_optional(cli_args, "metrics-pushgateway-interval")?
+                .unwrap_or(pushgateway::DEFAULT_INTERVAL_SECS);
+            if interval_secs == 0 {
+                return Err("metrics-pushgateway-interval must be greater than zero".into());
+            }
+            config.http_metrics.pushgateway = Some(pushgateway::Config {
+                url: SensitiveUrl::parse(url)
+                    .map_err(|e| format!("Invalid metrics-pushgateway URL: {:?}", e))?,
+                interval: Duration::from_secs(interval_secs),
+                job: parse_optional(cli_args, "metrics-pushgateway-job")?
+                    .unwrap_or_else(|| "validator_client".to_string()),
+                instance: parse_optional(cli_args, "metrics-pushgateway-instance")?
+                    .unwrap_or_else(pushgateway::default_instance),
+            });
+        }
+
+        if let Some(buckets) = cli_args.values_of("metrics-buckets") {
+            for buckets in buckets {
+                let (name, buckets) = lighthouse_metrics::parse_histogram_buckets(buckets)?;
+                config.http_metrics.histogram_buckets.insert(name, buckets);
+            }
+        }
+
+        if let Some(allowlist) = cli_args.value_of("metrics-allowlist") {
+            config.http_metrics.allowlist = allowlist
+                .split(',')
+                .map(|pattern| pattern.trim().to_string())
+                .filter(|pattern| !pattern.is_empty())
+                .collect();
+        }
+
+        if let Some(denylist) = cli_args.value_of("metrics-denylist") {
+            config.http_metrics.denylist = denylist
+                .split(',')
+                .map(|pattern| pattern.trim().to_string())
+                .filter(|pattern| !pattern.is_empty())
+                .collect();
+        }
+
+        if parse_flag(cli_args, DISABLE_MALLOC_TUNING_FLAG)? {
             config.http_metrics.allocator_metrics_enabled = false;
         }
 
@@ -325,22 +417,46 @@ impl Config {
             });
         }
 
-        if cli_args.is_present("enable-doppelganger-protection") {
+        /*
+         * Clock drift
+         */
+        if parse_flag(cli_args, "disable-ntp-check")? {
+            config.clock_drift = None;
+        } else if cli_args.is_present("ntp-server") || cli_args.is_present("ntp-drift-threshold") {
+            let clock_drift = config.clock_drift.get_or_insert_with(<_>::default);
+            if let Some(server) = parse_optional(cli_args, "ntp-server")? {
+                clock_drift.server = server;
+            }
+            if let Some(threshold) = parse_optional(cli_args, "ntp-drift-threshold")? {
+                clock_drift.threshold = Duration::from_millis(threshold);
+            }
+        }
+
+        if let Some(slots) = parse_optional(cli_args, "watchdog-stall-slots")? {
+            if slots < 2 {
+                return Err("--watchdog-stall-slots must be at least 2".to_string());
+            }
+            config.watchdog_stall_slots = Some(slots);
+        }
+
+        if parse_flag(cli_args, "enable-doppelganger-protection")? {
             config.enable_doppelganger_protection = true;
         }
 
-        if cli_args.is_present("builder-proposals") {
+        if parse_flag(cli_args, "builder-proposals")? {
             config.builder_proposals = true;
         }
 
-        config.gas_limit = cli_args
-            .value_of("gas-limit")
-            .map(|gas_limit| {
-                gas_limit
-                    .parse::<u64>()
-                    .map_err(|_| "gas-limit is not a valid u64.")
-            })
-            .transpose()?;
+        if use_cli_value(cli_args, "gas-limit") {
+            config.gas_limit = cli_args
+                .value_of("gas-limit")
+                .map(|gas_limit| {
+                    gas_limit
+                        .parse::<u64>()
+                        .map_err(|_| "gas-limit is not a valid u64.")
+                })
+                .transpose()?;
+        }
 
         if let Some(registration_timestamp_override) =
             cli_args.value_of("builder-registration-timestamp-override")
@@ -352,7 +468,7 @@ impl Config {
             );
         }
 
-        if cli_args.is_present("strict-fee-recipient") {
+        if parse_flag(cli_args, "strict-fee-recipient")? {
             warn!(
                 log,
                 "The flag `--strict-fee-recipient` has been deprecated due to a bug causing \
@@ -360,8 +476,11 @@ impl Config {
             );
         }
 
-        config.enable_latency_measurement_service =
-            parse_optional(cli_args, "latency-measurement-service")?.unwrap_or(true);
+        if let Some(enabled) = cli_value_of(cli_args, "latency-measurement-service") {
+            config.enable_latency_measurement_service = enabled
+                .parse()
+                .map_err(|_| "latency-measurement-service takes a boolean".to_string())?;
+        }
 
         /*
          * Experimental
@@ -374,6 +493,63 @@ impl Config {
     }
 }
 
+/// The settings which can be changed while the validator client is running.
+pub struct ReloadableConfig {
+    pub debug_level: String,
+    pub graffiti: Option<Graffiti>,
+    pub fee_recipient: Option<Address>,
+}
+
+/// Re-reads the settings which can be changed while the validator client is running (the debug
+/// level, graffiti and fee recipient) from the `--config-file`.
+///
+/// Values which were explicitly supplied on the command line take precedence over the file.
+pub struct ConfigReloader {
+    config_file: PathBuf,
+    debug_level: Option<String>,
+    graffiti: Option<Option<Graffiti>>,
+    fee_recipient: Option<Option<Address>>,
+}
+
+impl ConfigReloader {
+    /// Returns `None` if no `--config-file` was supplied, since there is nothing to reload.
+    pub fn new(cli_args: &ArgMatches, config: &Config) -> Result<Option<Self>, String> {
+        let config_file = match parse_optional(cli_args, CONFIG_FILE_FLAG)? {
+            Some(config_file) => config_file,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            config_file,
+            debug_level: cli_value_of(cli_args, "debug-level").map(String::from),
+            graffiti: use_cli_value(cli_args, "graffiti").then_some(config.graffiti),
+            fee_recipient: use_cli_value(cli_args, "suggested-fee-recipient")
+                .then_some(config.fee_recipient),
+        }))
+    }
+
+    /// Reads the config file, overriding any values supplied on the command line.
+    pub fn reload(&self) -> Result<ReloadableConfig, String> {
+        let config: Config = clap_utils::load_config_file(&self.config_file, "validator_client")?;
+        let logger: LoggerSection =
+            clap_utils::load_config_file(&self.config_file, "validator_client")?;
+
+        Ok(ReloadableConfig {
+            debug_level: self
+                .debug_level
+                .clone()
+                .unwrap_or(logger.logger_config.debug_level),
+            graffiti: self.graffiti.unwrap_or(config.graffiti),
+            fee_recipient: self.fee_recipient.unwrap_or(config.fee_recipient),
+        })
+    }
+
+    /// Returns the path of the config file.
+    pub fn config_file(&self) -> &Path {
+        &self.config_file
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,7 +40,7 @@ use slot_clock::SlotClock;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::sync::Arc;
-use task_executor::ShutdownReason;
+use task_executor::{FailureKind, ShutdownReason};
 use tokio::time::sleep;
 use types::{Epoch, EthSpec, PublicKeyBytes, Slot};
 
@@ -289,9 +289,10 @@ impl DoppelgangerService {
         let mut shutdown_sender = context.executor.shutdown_sender();
         let log = service.log.clone();
         let mut shutdown_func = move || {
-            if let Err(e) =
-                shutdown_sender.try_send(ShutdownReason::Failure("Doppelganger detected."))
-            {
+            if let Err(e) = shutdown_sender.try_send(ShutdownReason::Failure(
+                FailureKind::SlashingProtection,
+                "Doppelganger detected.",
+            )) {
                 crit!(
                     log,
                     "Failed to send shutdown signal";
@@ -325,9 +325,12 @@ pub fn start_update_service<T: SlotClock + 'static, E: EthSpec>(
      */
     let duties_service = core_duties_service.clone();
     let log = core_duties_service.context.log().clone();
+    let heartbeat = core_duties_service.context.executor.heartbeat("duties");
     core_duties_service.context.executor.spawn(
         async move {
             loop {
+                heartbeat.beat();
+
                 if let Some(duration) = duties_service.slot_clock.duration_to_next_slot() {
                     sleep(duration).await;
                 } else {
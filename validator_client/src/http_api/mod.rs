@@ -1,4 +1,3 @@
-mod api_secret;
 mod create_signed_voluntary_exit;
 mod create_validator;
 mod keystores;
@@ -11,11 +10,10 @@ use account_utils::{
     mnemonic_from_phrase,
     validator_definitions::{SigningDefinition, ValidatorDefinition, Web3SignerDefinition},
 };
-pub use api_secret::ApiSecret;
 use create_validator::{create_validators_mnemonic, create_validators_web3signer};
 use eth2::lighthouse_vc::{
     std_types::{AuthResponse, GetFeeRecipientResponse, GetGasLimitResponse},
-    types::{self as api_types, GenericResponse, Graffiti, PublicKey, PublicKeyBytes},
+    types::{self as api_types, GenericResponse, PublicKey, PublicKeyBytes},
 };
 use lighthouse_version::version_with_platform;
 use parking_lot::RwLock;
@@ -41,6 +39,7 @@ use warp::{
     },
     Filter,
 };
+pub use warp_utils::api_secret::ApiSecret;
 
 #[derive(Debug)]
 pub enum Error {
@@ -69,7 +68,6 @@ pub struct Context<T: SlotClock, E: EthSpec> {
     pub validator_store: Option<Arc<ValidatorStore<T, E>>>,
     pub validator_dir: Option<PathBuf>,
     pub graffiti_file: Option<GraffitiFile>,
-    pub graffiti_flag: Option<Graffiti>,
     pub spec: ChainSpec,
     pub config: Config,
     pub log: Logger,
@@ -186,9 +184,6 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
     let inner_graffiti_file = ctx.graffiti_file.clone();
     let graffiti_file_filter = warp::any().map(move || inner_graffiti_file.clone());
 
-    let inner_graffiti_flag = ctx.graffiti_flag;
-    let graffiti_flag_filter = warp::any().map(move || inner_graffiti_flag);
-
     let inner_ctx = ctx.clone();
     let log_filter = warp::any().map(move || inner_ctx.log.clone());
 
@@ -350,16 +345,15 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
         .and(warp::path::end())
         .and(validator_store_filter.clone())
         .and(graffiti_file_filter)
-        .and(graffiti_flag_filter)
         .and(signer.clone())
         .and(log_filter.clone())
         .and_then(
             |validator_store: Arc<ValidatorStore<T, E>>,
              graffiti_file: Option<GraffitiFile>,
-             graffiti_flag: Option<Graffiti>,
              signer,
              log| {
                 blocking_signed_json_task(signer, move || {
+                    let graffiti_flag = validator_store.graffiti_process();
                     let mut result = HashMap::new();
                     for (key, graffiti_definition) in validator_store
                         .initialized_validators()
@@ -36,6 +36,8 @@ pub const ATTESTATION_SELECTION_PROOFS: &str = "attestation_selection_proofs";
 pub const SUBSCRIPTIONS: &str = "subscriptions";
 pub const LOCAL_KEYSTORE: &str = "local_keystore";
 pub const WEB3SIGNER: &str = "web3signer";
+pub const PROPOSED: &str = "proposed";
+pub const MISSED: &str = "missed";
 
 pub use lighthouse_metrics::*;
 
@@ -188,6 +190,34 @@ lazy_static::lazy_static! {
         "Attestation duty slot for all managed validators",
         &["validator"]
     );
+    /*
+     * Per-validator performance, collected by the performance service.
+     */
+    pub static ref VALIDATOR_BALANCE_GWEI: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "vc_validator_balance_gwei",
+        "Balance of each managed validator at the head of the chain",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_ATTESTATION_INCLUSION_DISTANCE: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "vc_validator_attestation_inclusion_distance",
+        "Inclusion distance in slots of the latest included attestation of each managed validator",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_ATTESTATIONS_INCLUDED_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_validator_attestations_included_total",
+        "Count of attestations of each managed validator included on chain",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_ATTESTATIONS_MISSED_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_validator_attestations_missed_total",
+        "Count of attestations of each managed validator not included within an epoch",
+        &["validator"]
+    );
+    pub static ref VALIDATOR_PROPOSALS_TOTAL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_validator_proposals_total",
+        "Count of block proposals of each managed validator, by whether the block is on chain",
+        &["validator", "status"]
+    );
     /*
      * BN latency
      */
@@ -250,8 +280,12 @@ pub fn gather_prometheus_metrics<T: EthSpec>(
 
     warp_utils::metrics::scrape_health_metrics();
 
+    let filter = MetricsFilter::new(ctx.config.allowlist.clone(), ctx.config.denylist.clone());
     encoder
-        .encode(&lighthouse_metrics::gather(), &mut buffer)
+        .encode(
+            &filter.filter_families(lighthouse_metrics::gather()),
+            &mut buffer,
+        )
         .unwrap();
 
     String::from_utf8(buffer).map_err(|e| format!("Failed to encode prometheus info: {:?}", e))
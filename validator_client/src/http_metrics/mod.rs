@@ -9,8 +9,11 @@ use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use slog::{crit, info, Logger};
 use slot_clock::SystemTimeSlotClock;
+use std::collections::HashMap;
 use std::future::Future;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
 use types::EthSpec;
 use warp::{http::Response, Filter};
@@ -49,6 +52,13 @@ pub struct Context<T: EthSpec> {
     pub log: Logger,
 }
 
+/// Configuration used when serving the HTTP server over TLS.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
 /// Configuration for the HTTP server.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -56,7 +66,18 @@ pub struct Config {
     pub listen_addr: IpAddr,
     pub listen_port: u16,
     pub allow_origin: Option<String>,
+    pub tls_config: Option<TlsConfig>,
+    /// If set, requests must provide the bearer token contained in this file.
+    pub bearer_token_path: Option<PathBuf>,
     pub allocator_metrics_enabled: bool,
+    /// If set, metrics are also pushed to this Pushgateway, even if the server is disabled.
+    pub pushgateway: Option<pushgateway::Config>,
+    /// Bucket boundaries which override the defaults of the histograms with the given names.
+    pub histogram_buckets: HashMap<String, Vec<f64>>,
+    /// If non-empty, only the metric families matching one of these glob patterns are served.
+    pub allowlist: Vec<String>,
+    /// Metric families matching any of these glob patterns are not served.
+    pub denylist: Vec<String>,
 }
 
 impl Default for Config {
@@ -66,7 +87,13 @@ impl Default for Config {
             listen_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
             listen_port: 5064,
             allow_origin: None,
+            tls_config: None,
+            bearer_token_path: None,
             allocator_metrics_enabled: true,
+            pushgateway: None,
+            histogram_buckets: HashMap::new(),
+            allowlist: vec![],
+            denylist: vec![],
         }
     }
 }
@@ -97,7 +124,7 @@ pub fn serve<T: EthSpec>(
     let cors_builder = {
         let builder = warp::cors()
             .allow_method("GET")
-            .allow_headers(vec!["Content-Type"]);
+            .allow_headers(vec!["Content-Type", "Authorization"]);
 
         warp_utils::cors::set_builder_origins(
             builder,
@@ -114,9 +141,17 @@ pub fn serve<T: EthSpec>(
         ));
     }
 
+    let auth_filter = match &config.bearer_token_path {
+        Some(path) => {
+            warp_utils::auth::bearer_token_filter(warp_utils::auth::read_bearer_token(path)?)
+        }
+        None => warp::any().boxed(),
+    };
+
     let inner_ctx = ctx.clone();
     let routes = warp::get()
         .and(warp::path("metrics"))
+        .and(auth_filter)
         .map(move || inner_ctx.clone())
         .and_then(|ctx: Arc<Context<T>>| async move {
             Ok::<_, warp::Rejection>(
@@ -137,22 +172,52 @@ pub fn serve<T: EthSpec>(
                     }),
             )
         })
+        .recover(warp_utils::reject::handle_rejection)
         // Add a `Server` header.
         .map(|reply| warp::reply::with_header(reply, "Server", &version_with_platform()))
         .with(cors_builder.build());
 
-    let (listening_socket, server) = warp::serve(routes).try_bind_with_graceful_shutdown(
-        SocketAddr::new(config.listen_addr, config.listen_port),
-        async {
-            shutdown.await;
-        },
-    )?;
+    let socket = SocketAddr::new(config.listen_addr, config.listen_port);
+    let (listening_socket, server): (_, Pin<Box<dyn Future<Output = ()> + Send>>) =
+        match &config.tls_config {
+            Some(tls_config) => {
+                let (listening_socket, server) = warp::serve(routes)
+                    .tls()
+                    .cert_path(&tls_config.cert)
+                    .key_path(&tls_config.key)
+                    .try_bind_with_graceful_shutdown(socket, async {
+                        shutdown.await;
+                    })?;
+                (listening_socket, Box::pin(server))
+            }
+            None => {
+                let (listening_socket, server) = warp::serve(routes)
+                    .try_bind_with_graceful_shutdown(socket, async {
+                        shutdown.await;
+                    })?;
+                (listening_socket, Box::pin(server))
+            }
+        };
 
     info!(
         log,
         "Metrics HTTP server started";
         "listen_address" => listening_socket.to_string(),
+        "tls" => config.tls_config.is_some(),
+        "auth" => config.bearer_token_path.is_some(),
     );
 
     Ok((listening_socket, server))
 }
+
+/// Returns a future which periodically pushes the metrics gathered using `ctx` to the Pushgateway
+/// in its config, or `None` if no Pushgateway is configured.
+pub fn push<T: EthSpec>(ctx: Arc<Context<T>>) -> Option<impl Future<Output = ()>> {
+    let config = ctx.config.pushgateway.clone()?;
+    let log = ctx.log.clone();
+    Some(pushgateway::push_periodically(
+        config,
+        move || metrics::gather_prometheus_metrics(&ctx),
+        log,
+    ))
+}
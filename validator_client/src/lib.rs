@@ -10,6 +10,7 @@ mod http_metrics;
 mod key_cache;
 mod latency;
 mod notifier;
+mod performance_service;
 mod preparation_service;
 mod signing_method;
 mod sync_committee_service;
@@ -20,7 +21,7 @@ pub mod initialized_validators;
 pub mod validator_store;
 
 pub use cli::cli_app;
-pub use config::Config;
+pub use config::{Config, ConfigReloader};
 use initialized_validators::InitializedValidators;
 use lighthouse_metrics::set_gauge;
 use monitoring_api::{MonitoringHttpClient, ProcessType};
@@ -38,11 +39,12 @@ use attestation_service::{AttestationService, AttestationServiceBuilder};
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
 use duties_service::DutiesService;
-use environment::RuntimeContext;
+use environment::{LoggerHandle, ReloadReceiver, RuntimeContext, StartupError};
 use eth2::{reqwest::ClientBuilder, types::Graffiti, BeaconNodeHttpClient, StatusCode, Timeouts};
 use http_api::ApiSecret;
 use notifier::spawn_notifier;
 use parking_lot::RwLock;
+use performance_service::spawn_performance_service;
 use preparation_service::{PreparationService, PreparationServiceBuilder};
 use reqwest::Certificate;
 use slog::{error, info, warn, Logger};
@@ -108,12 +110,12 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
     ) -> Result<Self, String> {
         let config = Config::from_cli(cli_args, context.log())
             .map_err(|e| format!("Unable to initialize config: {}", e))?;
-        Self::new(context, config).await
+        Self::new(context, config).await.map_err(Into::into)
     }
 
     /// Instantiates the validator client, _without_ starting the timers to trigger block
     /// and attestation production.
-    pub async fn new(context: RuntimeContext<T>, config: Config) -> Result<Self, String> {
+    pub async fn new(context: RuntimeContext<T>, config: Config) -> Result<Self, StartupError> {
         let log = context.log().clone();
 
         info!(
@@ -123,35 +125,63 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             "validator_dir" => format!("{:?}", config.validator_dir),
         );
 
-        // Optionally start the metrics server.
-        let http_metrics_ctx = if config.http_metrics.enabled {
-            let shared = http_metrics::Shared {
-                validator_store: None,
-                genesis_time: None,
-                duties_service: None,
-            };
+        // Must happen before any histograms are used.
+        for name in
+            lighthouse_metrics::set_histogram_buckets(config.http_metrics.histogram_buckets.clone())
+        {
+            warn!(
+                log,
+                "Unable to override histogram buckets";
+                "histogram" => name,
+                "reason" => "histogram already in use",
+            );
+        }
 
-            let ctx: Arc<http_metrics::Context<T>> = Arc::new(http_metrics::Context {
-                config: config.http_metrics.clone(),
-                shared: RwLock::new(shared),
-                log: log.clone(),
-            });
+        // Optionally start the metrics server and/or push metrics to a Pushgateway.
+        let http_metrics_ctx =
+            if config.http_metrics.enabled || config.http_metrics.pushgateway.is_some() {
+                let shared = http_metrics::Shared {
+                    validator_store: None,
+                    genesis_time: None,
+                    duties_service: None,
+                };
+
+                let ctx: Arc<http_metrics::Context<T>> = Arc::new(http_metrics::Context {
+                    config: config.http_metrics.clone(),
+                    shared: RwLock::new(shared),
+                    log: log.clone(),
+                });
 
+                if let Some(push_metrics) = http_metrics::push(ctx.clone()) {
+                    context.executor.spawn(push_metrics, "metrics-pushgateway");
+                }
+
+                Some(ctx)
+            } else {
+                None
+            };
+
+        if let Some(ctx) = http_metrics_ctx
+            .as_ref()
+            .filter(|_| config.http_metrics.enabled)
+        {
             let exit = context.executor.exit();
 
-            let (_listen_addr, server) = http_metrics::serve(ctx.clone(), exit)
-                .map_err(|e| format!("Unable to start metrics API server: {:?}", e))?;
+            let (_listen_addr, server) = http_metrics::serve(ctx.clone(), exit).map_err(|e| {
+                let message = format!("Unable to start metrics API server: {:?}", e);
+                match e {
+                    http_metrics::Error::Warp(_) => StartupError::port_bind(message),
+                    http_metrics::Error::Other(_) => message.into(),
+                }
+            })?;
 
             context
                 .clone()
                 .executor
                 .spawn_without_exit(async move { server.await }, "metrics-api");
-
-            Some(ctx)
         } else {
             info!(log, "HTTP metrics server is disabled");
-            None
-        };
+        }
 
         // Start the explorer client which periodically sends validator process
         // and system metrics to the configured endpoint.
@@ -164,6 +194,23 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             );
         };
 
+        // Start the service which periodically checks the system clock against an NTP server.
+        if let Some(clock_drift_config) = config.clock_drift.clone() {
+            let clock_drift_context = context.service_context("clock_drift".into());
+            let clock_drift_log = clock_drift_context.log().clone();
+            clock_drift_context.executor.spawn(
+                clock_drift::monitor(clock_drift_config, clock_drift_log),
+                "clock_drift",
+            );
+        }
+
+        // Start the watchdog, which shuts down the validator client if the duties service stalls.
+        if let Some(stall_slots) = config.watchdog_stall_slots {
+            context.executor.spawn_watchdog(Duration::from_secs(
+                context.eth2_config.spec.seconds_per_slot * stall_slots,
+            ));
+        }
+
         let mut validator_defs = ValidatorDefinitions::open_or_create(&config.validator_dir)
             .map_err(|e| format!("Unable to open or create validator definitions: {:?}", e))?;
 
@@ -223,18 +270,18 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         let slashing_db_path = config.validator_dir.join(SLASHING_PROTECTION_FILENAME);
         let slashing_protection = if config.init_slashing_protection || voting_pubkeys.is_empty() {
             SlashingDatabase::open_or_create(&slashing_db_path).map_err(|e| {
-                format!(
+                StartupError::slashing_protection(format!(
                     "Failed to open or create slashing protection database: {:?}",
                     e
-                )
+                ))
             })
         } else {
             SlashingDatabase::open(&slashing_db_path).map_err(|e| {
-                format!(
+                StartupError::slashing_protection(format!(
                     "Failed to open slashing protection database: {:?}.\n\
                      Ensure that `slashing_protection.sqlite` is in {:?} folder",
                     e, config.validator_dir
-                )
+                ))
             })
         }?;
 
@@ -242,18 +289,23 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         if config.init_slashing_protection {
             slashing_protection
                 .register_validators(voting_pubkeys.iter().copied())
-                .map_err(|e| format!("Error while registering slashing protection: {:?}", e))?;
+                .map_err(|e| {
+                    StartupError::slashing_protection(format!(
+                        "Error while registering slashing protection: {:?}",
+                        e
+                    ))
+                })?;
         } else {
             slashing_protection
                 .check_validator_registrations(voting_pubkeys.iter().copied())
                 .map_err(|e| {
-                    format!(
+                    StartupError::slashing_protection(format!(
                         "One or more validators not found in slashing protection database.\n\
                          Ensure you haven't misplaced your slashing protection database, or \
                          carefully consider running with --init-slashing-protection (see --help). \
                          Error: {:?}",
                         e
-                    )
+                    ))
                 })?;
         }
 
@@ -352,7 +404,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         // Perform some potentially long-running initialization tasks.
         let (genesis_time, genesis_validators_root) = tokio::select! {
             tuple = init_from_beacon_node(&beacon_nodes, &context) => tuple?,
-            () = context.executor.exit() => return Err("Shutting down".to_string())
+            () = context.executor.exit() => return Err("Shutting down".into())
         };
 
         // Update the metrics server.
@@ -438,7 +490,6 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             .validator_store(validator_store.clone())
             .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("block".into()))
-            .graffiti(config.graffiti)
             .graffiti_file(config.graffiti_file.clone())
             .block_delay(config.block_delay)
             .build()?;
@@ -488,7 +539,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         })
     }
 
-    pub fn start_service(&mut self) -> Result<(), String> {
+    pub fn start_service(&mut self) -> Result<(), StartupError> {
         // We use `SLOTS_PER_EPOCH` as the capacity of the block notification channel, because
         // we don't except notifications to be delayed by more than a single slot, let alone a
         // whole epoch!
@@ -534,6 +585,11 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
 
         spawn_notifier(self).map_err(|e| format!("Failed to start notifier: {}", e))?;
 
+        if self.config.http_metrics.enabled || self.config.http_metrics.pushgateway.is_some() {
+            spawn_performance_service(self)
+                .map_err(|e| format!("Failed to start performance service: {}", e))?;
+        }
+
         let api_secret = ApiSecret::create_or_open(&self.config.validator_dir)?;
 
         self.http_api_listen_addr = if self.config.http_api.enabled {
@@ -543,7 +599,6 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                 validator_store: Some(self.validator_store.clone()),
                 validator_dir: Some(self.config.validator_dir.clone()),
                 graffiti_file: self.config.graffiti_file.clone(),
-                graffiti_flag: self.config.graffiti,
                 spec: self.context.eth2_config.spec.clone(),
                 config: self.config.http_api.clone(),
                 slot_clock: self.slot_clock.clone(),
@@ -553,8 +608,13 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
 
             let exit = self.context.executor.exit();
 
-            let (listen_addr, server) = http_api::serve(ctx, exit)
-                .map_err(|e| format!("Unable to start HTTP API server: {:?}", e))?;
+            let (listen_addr, server) = http_api::serve(ctx, exit).map_err(|e| {
+                let message = format!("Unable to start HTTP API server: {:?}", e);
+                match e {
+                    http_api::Error::Warp(_) => StartupError::port_bind(message),
+                    http_api::Error::Other(_) => message.into(),
+                }
+            })?;
 
             self.context
                 .clone()
@@ -577,6 +637,43 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
 
         Ok(())
     }
+
+    /// Uses `reloader` to reload the configuration of the validator client each time `reload_rx`
+    /// is notified (i.e., on SIGHUP). Runs until the environment is shut down.
+    pub async fn reload_on_signal(
+        self,
+        reloader: ConfigReloader,
+        mut reload_rx: ReloadReceiver,
+        logger_handle: Option<LoggerHandle>,
+        log: Logger,
+    ) {
+        while reload_rx.changed().await.is_ok() {
+            let config = match reloader.reload() {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!(log, "Unable to reload config file"; "error" => e);
+                    continue;
+                }
+            };
+
+            if let Some(logger_handle) = &logger_handle {
+                if let Err(e) = logger_handle.set_debug_level(&config.debug_level) {
+                    warn!(log, "Unable to reload debug level"; "error" => e);
+                }
+            }
+            self.validator_store.set_graffiti_process(config.graffiti);
+            self.validator_store
+                .set_fee_recipient_process(config.fee_recipient);
+
+            info!(
+                log,
+                "Reloaded config file";
+                "path" => ?reloader.config_file(),
+                "debug_level" => &config.debug_level,
+                "graffiti" => ?config.graffiti.map(|g| g.as_utf8_lossy()),
+            );
+        }
+    }
 }
 
 async fn init_from_beacon_node<E: EthSpec>(
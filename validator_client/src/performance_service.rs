@@ -0,0 +1,447 @@
+//! Tracks the on-chain performance of each local validator, exposing it as per-validator metrics.
+//!
+//! Each slot, the blocks since the previous check are downloaded from the beacon node (without
+//! their execution payloads) and searched for the attestations and proposals expected from the
+//! local validators. Attestations which are not included within `SLOTS_PER_EPOCH` slots are
+//! considered missed. Balances are updated once per epoch.
+//!
+//! Like the other per-validator metrics, these are only collected if there are few validators or
+//! `--enable-high-validator-count-metrics` is set.
+
+use crate::beacon_node_fallback::OfflineOnFailure;
+use crate::http_metrics::metrics;
+use crate::validator_store::DoppelgangerStatus;
+use crate::{DutiesService, ProductionValidatorClient};
+use eth2::types::{BlockId, StateId, ValidatorId};
+use lighthouse_metrics::{inc_counter_vec, set_int_gauge};
+use slog::{debug, error, Logger};
+use slot_clock::SlotClock;
+use std::collections::HashSet;
+use tokio::time::{sleep, Duration};
+use types::{Epoch, EthSpec, SignedBlindedBeaconBlock, Slot};
+
+/// An attestation which a local validator was expected to make, which is yet to be included.
+struct PendingAttestation {
+    validator_index: u64,
+    slot: Slot,
+    committee_index: u64,
+    committee_position: usize,
+}
+
+#[derive(Default)]
+struct PerformanceTracker {
+    pending_attestations: Vec<PendingAttestation>,
+    /// The latest slot for which the block has been checked.
+    last_checked_slot: Option<Slot>,
+    /// The epoch at which balances were last updated.
+    last_balance_epoch: Option<Epoch>,
+}
+
+/// Spawns a service which updates the per-validator performance metrics each slot.
+pub fn spawn_performance_service<T: EthSpec>(
+    client: &ProductionValidatorClient<T>,
+) -> Result<(), String> {
+    let context = client.context.service_context("performance".into());
+    let executor = context.executor.clone();
+    let duties_service = client.duties_service.clone();
+
+    let slot_duration = Duration::from_secs(context.eth2_config.spec.seconds_per_slot);
+
+    let interval_fut = async move {
+        let log = context.log();
+        let mut tracker = PerformanceTracker::default();
+
+        loop {
+            if let Some(duration_to_next_slot) = duties_service.slot_clock.duration_to_next_slot() {
+                // Wait until the block for the previous slot is likely to have been imported.
+                sleep(duration_to_next_slot + slot_duration / 2).await;
+                if duties_service.per_validator_metrics() {
+                    tracker.update(&duties_service, log).await;
+                }
+            } else {
+                error!(log, "Failed to read slot clock");
+                // If we can't read the slot clock, just wait another slot.
+                sleep(slot_duration).await;
+            }
+        }
+    };
+
+    executor.spawn(interval_fut, "validator_performance");
+    Ok(())
+}
+
+impl PerformanceTracker {
+    async fn update<T: SlotClock + 'static, E: EthSpec>(
+        &mut self,
+        duties_service: &DutiesService<T, E>,
+        log: &Logger,
+    ) {
+        let current_slot = match duties_service.slot_clock.now() {
+            Some(slot) => slot,
+            None => return,
+        };
+
+        // Record the attestations expected during the current slot.
+        for duty_and_proof in duties_service.attesters(current_slot) {
+            let duty = duty_and_proof.duty;
+            self.pending_attestations.push(PendingAttestation {
+                validator_index: duty.validator_index,
+                slot: duty.slot,
+                committee_index: duty.committee_index,
+                committee_position: duty.validator_committee_index as usize,
+            });
+        }
+
+        // Check each block since the last check, up to the previous slot. Blocks older than an
+        // epoch can no longer include attestations, so there is no need to check them.
+        let first_slot = self
+            .last_checked_slot
+            .map_or(current_slot.saturating_sub(1_u64), |slot| slot + 1)
+            .max(current_slot.saturating_sub(E::slots_per_epoch()));
+        for slot in first_slot.as_u64()..current_slot.as_u64() {
+            let slot = Slot::new(slot);
+            match get_block(duties_service, slot).await {
+                Ok(block) => {
+                    let proposers = local_proposers(duties_service, slot);
+                    self.process_block(slot, &proposers, block.as_ref());
+                }
+                Err(e) => {
+                    debug!(
+                        log,
+                        "Unable to check validator performance";
+                        "slot" => slot,
+                        "error" => e,
+                    );
+                    break;
+                }
+            }
+            self.last_checked_slot = Some(slot);
+        }
+
+        if let Some(last_checked_slot) = self.last_checked_slot {
+            self.remove_missed_attestations::<E>(last_checked_slot);
+        }
+
+        let current_epoch = current_slot.epoch(E::slots_per_epoch());
+        if self.last_balance_epoch != Some(current_epoch) {
+            match update_balances(duties_service).await {
+                Ok(()) => self.last_balance_epoch = Some(current_epoch),
+                Err(e) => debug!(log, "Unable to update validator balances"; "error" => e),
+            }
+        }
+    }
+
+    /// Records the inclusion of any pending attestations in the block at `slot`, and whether each
+    /// of the local validators in `proposers`, which were assigned to propose at `slot`, did so.
+    fn process_block<E: EthSpec>(
+        &mut self,
+        slot: Slot,
+        proposers: &[u64],
+        block: Option<&SignedBlindedBeaconBlock<E>>,
+    ) {
+        for &validator_index in proposers {
+            let status = if block.map_or(false, |block| {
+                block.message().proposer_index() == validator_index
+            }) {
+                metrics::PROPOSED
+            } else {
+                metrics::MISSED
+            };
+            inc_counter_vec(
+                &metrics::VALIDATOR_PROPOSALS_TOTAL,
+                &[&validator_index.to_string(), status],
+            );
+        }
+
+        let block = match block {
+            Some(block) => block,
+            None => return,
+        };
+        for attestation in block.message().body().attestations() {
+            self.pending_attestations.retain(|pending| {
+                let included = pending.slot == attestation.data.slot
+                    && pending.committee_index == attestation.data.index
+                    && attestation
+                        .aggregation_bits
+                        .get(pending.committee_position)
+                        .unwrap_or(false);
+                if included {
+                    let validator = pending.validator_index.to_string();
+                    inc_counter_vec(
+                        &metrics::VALIDATOR_ATTESTATIONS_INCLUDED_TOTAL,
+                        &[&validator],
+                    );
+                    set_int_gauge(
+                        &metrics::VALIDATOR_ATTESTATION_INCLUSION_DISTANCE,
+                        &[&validator],
+                        (slot - pending.slot).as_u64() as i64,
+                    );
+                }
+                !included
+            });
+        }
+    }
+
+    /// Removes the pending attestations which can no longer be included in a block after
+    /// `last_checked_slot`, counting them as missed.
+    fn remove_missed_attestations<E: EthSpec>(&mut self, last_checked_slot: Slot) {
+        self.pending_attestations.retain(|pending| {
+            let missed = pending.slot + E::slots_per_epoch() <= last_checked_slot;
+            if missed {
+                inc_counter_vec(
+                    &metrics::VALIDATOR_ATTESTATIONS_MISSED_TOTAL,
+                    &[&pending.validator_index.to_string()],
+                );
+            }
+            !missed
+        });
+    }
+}
+
+/// Returns the indices of the local validators assigned to propose at `slot`.
+fn local_proposers<T: SlotClock + 'static, E: EthSpec>(
+    duties_service: &DutiesService<T, E>,
+    slot: Slot,
+) -> Vec<u64> {
+    let epoch = slot.epoch(E::slots_per_epoch());
+    let local_pubkeys: HashSet<_> = duties_service
+        .validator_store
+        .voting_pubkeys(DoppelgangerStatus::only_safe);
+    duties_service
+        .proposers
+        .read()
+        .get(&epoch)
+        .map(|(_, proposers)| {
+            proposers
+                .iter()
+                .filter(|proposer| {
+                    proposer.slot == slot && local_pubkeys.contains(&proposer.pubkey)
+                })
+                .map(|proposer| proposer.validator_index)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the block at `slot`, or `None` if the slot was skipped.
+///
+/// Failures do not mark the beacon node offline, since these requests aren't needed to perform
+/// any duties.
+async fn get_block<T: SlotClock + 'static, E: EthSpec>(
+    duties_service: &DutiesService<T, E>,
+    slot: Slot,
+) -> Result<Option<SignedBlindedBeaconBlock<E>>, String> {
+    duties_service
+        .beacon_nodes
+        .first_success(
+            duties_service.require_synced,
+            OfflineOnFailure::No,
+            |beacon_node| async move {
+                beacon_node
+                    .get_beacon_blinded_blocks::<E>(BlockId::Slot(slot))
+                    .await
+                    .map(|response| response.map(|response| response.data))
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn update_balances<T: SlotClock + 'static, E: EthSpec>(
+    duties_service: &DutiesService<T, E>,
+) -> Result<(), String> {
+    let pubkeys: Vec<_> = duties_service
+        .validator_store
+        .voting_pubkeys(DoppelgangerStatus::ignored);
+    let indices = {
+        let validators = duties_service.validator_store.initialized_validators();
+        let validators = validators.read();
+        pubkeys
+            .iter()
+            .filter_map(|pubkey| validators.get_index(pubkey))
+            .map(ValidatorId::Index)
+            .collect::<Vec<_>>()
+    };
+    if indices.is_empty() {
+        return Ok(());
+    }
+
+    let validators = duties_service
+        .beacon_nodes
+        .first_success(
+            duties_service.require_synced,
+            OfflineOnFailure::No,
+            |beacon_node| {
+                let indices = &indices;
+                async move {
+                    beacon_node
+                        .get_beacon_states_validators(StateId::Head, Some(indices), None)
+                        .await
+                }
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Head state not found")?
+        .data;
+
+    for validator in validators {
+        set_int_gauge(
+            &metrics::VALIDATOR_BALANCE_GWEI,
+            &[&validator.index.to_string()],
+            validator.balance as i64,
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{
+        AggregateSignature, Attestation, AttestationData, BeaconBlock, BitList, BlindedPayload,
+        MainnetEthSpec, Signature,
+    };
+
+    type E = MainnetEthSpec;
+
+    const COMMITTEE_SIZE: usize = 8;
+
+    fn pending(validator_index: u64, slot: u64, committee_position: usize) -> PendingAttestation {
+        PendingAttestation {
+            validator_index,
+            slot: Slot::new(slot),
+            committee_index: 0,
+            committee_position,
+        }
+    }
+
+    /// Returns a block at `slot` proposed by `proposer_index`, including an attestation from the
+    /// members of committee 0 at `attestation_slot` in `committee_positions`.
+    fn block(
+        slot: u64,
+        proposer_index: u64,
+        attestation_slot: u64,
+        committee_positions: &[usize],
+    ) -> SignedBlindedBeaconBlock<E> {
+        let mut block = BeaconBlock::<E, BlindedPayload<E>>::empty(&E::default_spec());
+        *block.slot_mut() = Slot::new(slot);
+        *block.proposer_index_mut() = proposer_index;
+
+        let mut aggregation_bits = BitList::with_capacity(COMMITTEE_SIZE).unwrap();
+        for &position in committee_positions {
+            aggregation_bits.set(position, true).unwrap();
+        }
+        block
+            .body_mut()
+            .attestations_mut()
+            .push(Attestation {
+                aggregation_bits,
+                data: AttestationData {
+                    slot: Slot::new(attestation_slot),
+                    index: 0,
+                    ..AttestationData::default()
+                },
+                signature: AggregateSignature::empty(),
+            })
+            .unwrap();
+
+        SignedBlindedBeaconBlock::from_block(block, Signature::empty())
+    }
+
+    fn counter(counter: &metrics::Result<metrics::IntCounterVec>, labels: &[&str]) -> u64 {
+        counter.as_ref().unwrap().with_label_values(labels).get()
+    }
+
+    // Each test uses its own validator indices, since the metrics are global.
+
+    #[test]
+    fn process_block_records_included_attestations() {
+        let mut tracker = PerformanceTracker {
+            pending_attestations: vec![pending(100, 10, 1), pending(101, 10, 2)],
+            ..PerformanceTracker::default()
+        };
+
+        tracker.process_block(Slot::new(12), &[], Some(&block(12, 0, 10, &[1])));
+
+        assert_eq!(
+            counter(&metrics::VALIDATOR_ATTESTATIONS_INCLUDED_TOTAL, &["100"]),
+            1
+        );
+        assert_eq!(
+            metrics::VALIDATOR_ATTESTATION_INCLUSION_DISTANCE
+                .as_ref()
+                .unwrap()
+                .with_label_values(&["100"])
+                .get(),
+            2
+        );
+        assert_eq!(
+            counter(&metrics::VALIDATOR_ATTESTATIONS_INCLUDED_TOTAL, &["101"]),
+            0
+        );
+        let remaining: Vec<_> = tracker
+            .pending_attestations
+            .iter()
+            .map(|pending| pending.validator_index)
+            .collect();
+        assert_eq!(remaining, vec![101]);
+    }
+
+    #[test]
+    fn process_block_ignores_attestations_for_other_slots() {
+        let mut tracker = PerformanceTracker {
+            pending_attestations: vec![pending(110, 10, 1)],
+            ..PerformanceTracker::default()
+        };
+
+        tracker.process_block(Slot::new(12), &[], Some(&block(12, 0, 11, &[1])));
+
+        assert_eq!(
+            counter(&metrics::VALIDATOR_ATTESTATIONS_INCLUDED_TOTAL, &["110"]),
+            0
+        );
+        assert_eq!(tracker.pending_attestations.len(), 1);
+    }
+
+    #[test]
+    fn process_block_records_proposals() {
+        let mut tracker = PerformanceTracker::default();
+
+        tracker.process_block(Slot::new(12), &[120], Some(&block(12, 120, 11, &[])));
+        tracker.process_block(Slot::new(13), &[121], Some(&block(13, 7, 12, &[])));
+        tracker.process_block::<E>(Slot::new(14), &[122], None);
+
+        let proposals = &metrics::VALIDATOR_PROPOSALS_TOTAL;
+        assert_eq!(counter(proposals, &["120", metrics::PROPOSED]), 1);
+        assert_eq!(counter(proposals, &["120", metrics::MISSED]), 0);
+        assert_eq!(counter(proposals, &["121", metrics::PROPOSED]), 0);
+        assert_eq!(counter(proposals, &["121", metrics::MISSED]), 1);
+        assert_eq!(counter(proposals, &["122", metrics::MISSED]), 1);
+    }
+
+    #[test]
+    fn attestations_missed_after_an_epoch() {
+        let slots_per_epoch = E::slots_per_epoch();
+        let mut tracker = PerformanceTracker {
+            pending_attestations: vec![pending(130, 10, 0)],
+            ..PerformanceTracker::default()
+        };
+
+        // The attestation can still be included in the block at `slot + SLOTS_PER_EPOCH`, so it
+        // isn't missed until that block has been checked.
+        tracker.remove_missed_attestations::<E>(Slot::new(10 + slots_per_epoch - 1));
+        assert_eq!(tracker.pending_attestations.len(), 1);
+        assert_eq!(
+            counter(&metrics::VALIDATOR_ATTESTATIONS_MISSED_TOTAL, &["130"]),
+            0
+        );
+
+        tracker.remove_missed_attestations::<E>(Slot::new(10 + slots_per_epoch));
+        assert!(tracker.pending_attestations.is_empty());
+        assert_eq!(
+            counter(&metrics::VALIDATOR_ATTESTATIONS_MISSED_TOTAL, &["130"]),
+            1
+        );
+    }
+}
@@ -94,7 +94,8 @@ pub struct ValidatorStore<T, E: EthSpec> {
     log: Logger,
     doppelganger_service: Option<Arc<DoppelgangerService>>,
     slot_clock: T,
-    fee_recipient_process: Option<Address>,
+    graffiti_process: RwLock<Option<Graffiti>>,
+    fee_recipient_process: RwLock<Option<Address>>,
     gas_limit: Option<u64>,
     builder_proposals: bool,
     task_executor: TaskExecutor,
@@ -125,7 +126,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
             log,
             doppelganger_service,
             slot_clock,
-            fee_recipient_process: config.fee_recipient,
+            graffiti_process: RwLock::new(config.graffiti),
+            fee_recipient_process: RwLock::new(config.fee_recipient),
             gas_limit: config.gas_limit,
             builder_proposals: config.builder_proposals,
             task_executor,
@@ -401,6 +403,21 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         self.validators.read().graffiti(validator_pubkey)
     }
 
+    /// Returns the process level graffiti, used for validators without graffiti of their own.
+    pub fn graffiti_process(&self) -> Option<Graffiti> {
+        *self.graffiti_process.read()
+    }
+
+    /// Sets the process level graffiti, e.g. when the config file is reloaded.
+    pub fn set_graffiti_process(&self, graffiti: Option<Graffiti>) {
+        *self.graffiti_process.write() = graffiti;
+    }
+
+    /// Sets the process level fee recipient, e.g. when the config file is reloaded.
+    pub fn set_fee_recipient_process(&self, fee_recipient: Option<Address>) {
+        *self.fee_recipient_process.write() = fee_recipient;
+    }
+
     /// Returns the fee recipient for the given public key. The priority order for fetching
     /// the fee recipient is:
     /// 1. validator_definitions.yml
@@ -413,7 +430,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
 
     pub fn get_fee_recipient_defaulting(&self, fee_recipient: Option<Address>) -> Option<Address> {
         // If there's nothing in the file, try the process-level default value.
-        fee_recipient.or(self.fee_recipient_process)
+        fee_recipient.or(*self.fee_recipient_process.read())
     }
 
     /// Returns the suggested_fee_recipient from `validator_definitions.yml` if any.